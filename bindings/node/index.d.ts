@@ -0,0 +1,23 @@
+export interface CheckpointOptions {
+  /** Agent preset (`claude`, `cursor`, `github-copilot`, `ai_tab`, `agent-v1`); omit for a human checkpoint */
+  preset?: string;
+  /** Payload for the preset, as a JSON string or an object to serialize */
+  hookInput?: string | object;
+}
+
+export type LineAuthor =
+  | { line: number; kind: "human"; author: string }
+  | { line: number; kind: "ai"; promptHash: string; tool: string; model: string };
+
+export interface Checkpoint {
+  kind: "Human" | "AiAgent" | "AiTab";
+  author: string;
+  timestamp: number;
+  agent_id: { tool: string; id: string; model: string } | null;
+  entries: Array<{ file: string; blob_sha: string }>;
+  [key: string]: unknown;
+}
+
+export function checkpoint(repoPath: string, options?: CheckpointOptions): Promise<number>;
+export function blame(repoPath: string, filePath: string): Promise<LineAuthor[]>;
+export function workingLog(repoPath: string): Promise<Checkpoint[]>;
@@ -0,0 +1,80 @@
+"use strict";
+
+// In-process bindings to git-ai for editor extensions. Loads the native addon built from
+// crates/git-ai-node and parses its JSON results.
+//
+// The addon is looked up in this order:
+// - the GIT_AI_NODE_ADDON environment variable
+// - git_ai.node next to this file (where the npm package ships it)
+// - target/release and target/debug of the git-ai checkout this package lives in
+
+const fs = require("fs");
+const os = require("os");
+const path = require("path");
+
+function libraryName() {
+  switch (process.platform) {
+    case "darwin":
+      return "libgit_ai_node.dylib";
+    case "win32":
+      return "git_ai_node.dll";
+    default:
+      return "libgit_ai_node.so";
+  }
+}
+
+function findAddon() {
+  if (process.env.GIT_AI_NODE_ADDON) {
+    return process.env.GIT_AI_NODE_ADDON;
+  }
+  const checkout = path.resolve(__dirname, "..", "..");
+  const candidates = [
+    path.join(__dirname, "git_ai.node"),
+    path.join(checkout, "target", "release", libraryName()),
+    path.join(checkout, "target", "debug", libraryName()),
+  ];
+  const found = candidates.find((candidate) => fs.existsSync(candidate));
+  if (!found) {
+    throw new Error(
+      "git-ai native addon not found; build it with `cargo build -p git-ai-node` or set GIT_AI_NODE_ADDON"
+    );
+  }
+  return found;
+}
+
+const native = { exports: {} };
+process.dlopen(native, findAddon(), os.constants.dlopen.RTLD_NOW);
+
+function requireString(value, name) {
+  if (typeof value !== "string") {
+    throw new TypeError(`${name} must be a string`);
+  }
+}
+
+/**
+ * Record a checkpoint of the working directory, like `git-ai checkpoint [preset]`.
+ * Resolves to the number of files captured.
+ */
+async function checkpoint(repoPath, options = {}) {
+  requireString(repoPath, "repoPath");
+  const hookInput =
+    options.hookInput === undefined || typeof options.hookInput === "string"
+      ? options.hookInput
+      : JSON.stringify(options.hookInput);
+  return JSON.parse(await native.exports.checkpoint(repoPath, options.preset, hookInput));
+}
+
+/** Author of every line of a working-directory file. */
+async function blame(repoPath, filePath) {
+  requireString(repoPath, "repoPath");
+  requireString(filePath, "filePath");
+  return JSON.parse(await native.exports.blame(repoPath, filePath));
+}
+
+/** Checkpoints recorded since HEAD, oldest first. */
+async function workingLog(repoPath) {
+  requireString(repoPath, "repoPath");
+  return JSON.parse(await native.exports.workingLog(repoPath));
+}
+
+module.exports = { checkpoint, blame, workingLog };
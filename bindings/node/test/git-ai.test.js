@@ -0,0 +1,65 @@
+"use strict";
+
+const assert = require("node:assert");
+const { execFileSync } = require("node:child_process");
+const fs = require("node:fs");
+const os = require("node:os");
+const path = require("node:path");
+const { test, beforeEach, afterEach } = require("node:test");
+
+const gitAi = require("..");
+
+let repo;
+
+function git(...args) {
+  execFileSync("git", args, { cwd: repo, stdio: "pipe" });
+}
+
+beforeEach(() => {
+  repo = fs.mkdtempSync(path.join(os.tmpdir(), "git-ai-node-"));
+  git("init", "-q");
+  git("config", "user.name", "Test User");
+  git("config", "user.email", "test@example.com");
+  fs.writeFileSync(path.join(repo, "a.txt"), "one\n");
+  git("add", "a.txt");
+  git("commit", "-q", "-m", "initial");
+});
+
+afterEach(() => {
+  fs.rmSync(repo, { recursive: true, force: true });
+});
+
+test("records checkpoints in-process and reads them back", async () => {
+  assert.deepStrictEqual(await gitAi.workingLog(repo), []);
+
+  fs.appendFileSync(path.join(repo, "a.txt"), "two\n");
+  const files = await gitAi.checkpoint(repo, {
+    preset: "ai_tab",
+    hookInput: {
+      hook_event_name: "after_edit",
+      tool: "tab-tool",
+      model: "tab-model",
+      edited_filepaths: ["a.txt"],
+    },
+  });
+  assert.strictEqual(files, 1);
+
+  const checkpoints = await gitAi.workingLog(repo);
+  assert.strictEqual(checkpoints.length, 1);
+  assert.strictEqual(checkpoints[0].kind, "AiTab");
+  assert.strictEqual(checkpoints[0].agent_id.tool, "tab-tool");
+
+  // Blame reads committed authorship, so the uncommitted AI line isn't attributed yet.
+  const lines = await gitAi.blame(repo, "a.txt");
+  assert.deepStrictEqual(
+    lines.map((line) => line.line),
+    [1, 2]
+  );
+  assert.strictEqual(lines[0].kind, "human");
+});
+
+test("rejects failed queries and throws on bad arguments", async () => {
+  await assert.rejects(gitAi.checkpoint(repo, { preset: "no-such-preset" }), /no-such-preset/);
+  await assert.rejects(gitAi.workingLog(path.join(repo, "missing")));
+  await assert.rejects(gitAi.blame(repo), TypeError);
+});
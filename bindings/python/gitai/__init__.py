@@ -0,0 +1,88 @@
+"""
+Python bindings for git-ai authorship data.
+
+Backed by the `gitai._native` extension module, built with PyO3 from `crates/git-ai-python`,
+so queries run in-process instead of parsing `git-ai` CLI output. Every query returns plain
+dicts and lists, which load straight into pandas:
+
+    import pandas as pd
+    import gitai
+
+    repo = gitai.Repository(".")
+    df = pd.DataFrame(repo.ai_line_records("HEAD~50..HEAD"))
+
+The extension module is looked up in this order:
+- the GITAI_LIBRARY environment variable
+- next to this file (where wheels ship it)
+- target/release and target/debug of the git-ai checkout this package lives in
+"""
+
+import importlib.machinery
+import importlib.util
+import os
+import sys
+from pathlib import Path
+
+__all__ = ["GitAiError", "Repository"]
+
+
+def _library_name():
+    if sys.platform == "darwin":
+        return "libgit_ai_python.dylib"
+    if sys.platform == "win32":
+        return "git_ai_python.dll"
+    return "libgit_ai_python.so"
+
+
+def _load_from(path):
+    loader = importlib.machinery.ExtensionFileLoader(f"{__name__}._native", str(path))
+    spec = importlib.util.spec_from_file_location(f"{__name__}._native", str(path), loader=loader)
+    module = importlib.util.module_from_spec(spec)
+    loader.exec_module(module)
+    return module
+
+
+def _load():
+    override = os.environ.get("GITAI_LIBRARY")
+    if override:
+        return _load_from(override)
+    try:
+        from . import _native
+
+        return _native
+    except ImportError:
+        pass
+
+    name = _library_name()
+    checkout = Path(__file__).resolve().parents[3]
+    for profile in ("release", "debug"):
+        candidate = checkout / "target" / profile / name
+        if candidate.exists():
+            return _load_from(candidate)
+    raise ImportError(
+        "could not find the gitai extension module; build it with "
+        "`cargo build -p git-ai-python` or set GITAI_LIBRARY"
+    )
+
+
+_native = _load()
+
+GitAiError = _native.GitAiError
+
+
+class Repository(_native.Repository):
+    """A git repository with git-ai authorship data. Usable as a context manager."""
+
+    def iter_commits(self, revision="HEAD", max_count=None):
+        """Yield `(sha, ai_lines)` for every commit reachable from `revision`."""
+        for sha in self.commits(revision, max_count):
+            yield sha, self.ai_lines(sha)
+
+    def ai_line_records(self, revision="HEAD", max_count=None):
+        """AI-authored lines of every commit reachable from `revision`, each record tagged
+        with its `commit`. Ready for `pandas.DataFrame(...)`."""
+        return [
+            {"commit": sha, **line}
+            for sha, lines in self.iter_commits(revision, max_count)
+            for line in lines
+        ]
@@ -0,0 +1,61 @@
+import os
+import subprocess
+import tempfile
+import unittest
+from pathlib import Path
+
+import sys
+
+sys.path.insert(0, str(Path(__file__).resolve().parents[1]))
+
+import gitai  # noqa: E402
+
+
+def run_git(cwd, *args):
+    subprocess.run(["git", *args], cwd=cwd, check=True, capture_output=True)
+
+
+class RepositoryTest(unittest.TestCase):
+    def setUp(self):
+        self.tmp = tempfile.TemporaryDirectory()
+        self.path = self.tmp.name
+        run_git(self.path, "init", "-q")
+        run_git(self.path, "config", "user.name", "Test User")
+        run_git(self.path, "config", "user.email", "test@example.com")
+        Path(self.path, "a.txt").write_text("one\n")
+        run_git(self.path, "add", "a.txt")
+        run_git(self.path, "commit", "-q", "-m", "plain")
+
+    def tearDown(self):
+        self.tmp.cleanup()
+
+    def test_commit_without_authorship(self):
+        with gitai.Repository(self.path) as repo:
+            commits = repo.commits()
+            self.assertEqual(len(commits), 1)
+            self.assertIsNone(repo.authorship_log(commits[0]))
+            self.assertEqual(repo.ai_lines(), [])
+            self.assertEqual(repo.ai_line_records(), [])
+            self.assertEqual(repo.stats()["ai_additions"], 0)
+            self.assertEqual([r["kind"] for r in repo.blame("a.txt")], ["human"])
+
+    def test_errors_carry_message(self):
+        with gitai.Repository(self.path) as repo:
+            with self.assertRaises(gitai.GitAiError) as ctx:
+                repo.stats("no-such-revision")
+            self.assertTrue(str(ctx.exception))
+
+    def test_open_outside_a_repository_fails(self):
+        with tempfile.TemporaryDirectory() as empty:
+            with self.assertRaises(gitai.GitAiError):
+                gitai.Repository(empty)
+
+    def test_closed_repository_rejects_queries(self):
+        repo = gitai.Repository(self.path)
+        repo.close()
+        with self.assertRaises(gitai.GitAiError):
+            repo.commits()
+
+
+if __name__ == "__main__":
+    unittest.main()
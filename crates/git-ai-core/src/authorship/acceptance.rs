@@ -0,0 +1,225 @@
+//! How much of what AI sessions wrote survived to commit, per session, agent and model.
+//!
+//! Every commit's authorship note keeps counters for each session that wrote its lines: the lines
+//! the session added while working on the commit (`total_additions`), the ones that were committed
+//! as the session wrote them (`accepted_lines`) and the ones a human edited before committing
+//! (`overriden_lines`). The acceptance rate is the share of the session's additions committed
+//! unchanged.
+
+use crate::error::GitAiError;
+use crate::git::refs::get_authorship;
+use crate::git::repository::Repository;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// One session's counters, summed over the commits it wrote lines in
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct AcceptanceCounts {
+    /// Lines the session added while working, committed or not
+    pub total_additions: u32,
+    /// Lines committed as the session wrote them
+    pub accepted_lines: u32,
+    /// Lines the session wrote that a human edited before committing
+    pub overriden_lines: u32,
+}
+
+impl AcceptanceCounts {
+    fn add(&mut self, other: &AcceptanceCounts) {
+        self.total_additions += other.total_additions;
+        self.accepted_lines += other.accepted_lines;
+        self.overriden_lines += other.overriden_lines;
+    }
+
+    /// Share of the additions committed unchanged, between 0 and 1. Notes built from history
+    /// (`backfill`, `import`) can count more accepted lines than additions, so it's capped.
+    pub fn acceptance_rate(&self) -> f64 {
+        if self.total_additions == 0 {
+            return 0.0;
+        }
+        (self.accepted_lines as f64 / self.total_additions as f64).min(1.0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SessionAcceptance {
+    pub session_id: String,
+    pub tool: String,
+    pub model: String,
+    /// Commits the session wrote lines in
+    pub commits: usize,
+    #[serde(flatten)]
+    pub counts: AcceptanceCounts,
+    pub acceptance_rate: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ToolModelAcceptance {
+    pub tool: String,
+    pub model: String,
+    pub sessions: usize,
+    #[serde(flatten)]
+    pub counts: AcceptanceCounts,
+    pub acceptance_rate: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AcceptanceReport {
+    /// Most accepted first
+    pub by_tool_model: Vec<ToolModelAcceptance>,
+    /// Most accepted first
+    pub sessions: Vec<SessionAcceptance>,
+}
+
+/// Sessions of the commits `rev` selects, anything `git log` takes (`HEAD`, `main..feature`)
+pub fn session_acceptance(
+    repo: &Repository,
+    rev: &str,
+) -> Result<Vec<SessionAcceptance>, GitAiError> {
+    let output = repo.git(&["log", "--format=%H", rev])?;
+    let mut sessions: BTreeMap<String, SessionAcceptance> = BTreeMap::new();
+    for commit in output.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        let Some(log) = get_authorship(repo, commit) else {
+            continue;
+        };
+        for (session_id, record) in &log.metadata.prompts {
+            let session = sessions
+                .entry(session_id.clone())
+                .or_insert_with(|| SessionAcceptance {
+                    session_id: session_id.clone(),
+                    tool: record.agent_id.tool.clone(),
+                    model: record.agent_id.model.clone(),
+                    commits: 0,
+                    counts: AcceptanceCounts::default(),
+                    acceptance_rate: 0.0,
+                });
+            session.commits += 1;
+            session.counts.add(&AcceptanceCounts {
+                total_additions: record.total_additions,
+                accepted_lines: record.accepted_lines,
+                overriden_lines: record.overriden_lines,
+            });
+        }
+    }
+    let mut sessions: Vec<SessionAcceptance> = sessions
+        .into_values()
+        .map(|mut session| {
+            session.acceptance_rate = session.counts.acceptance_rate();
+            session
+        })
+        .collect();
+    sessions.sort_by(|a, b| b.acceptance_rate.total_cmp(&a.acceptance_rate));
+    Ok(sessions)
+}
+
+/// Totals `sessions` per agent and model, with `sessions` itself as the per-session view
+pub fn report(sessions: Vec<SessionAcceptance>) -> AcceptanceReport {
+    let mut groups: BTreeMap<(&str, &str), (usize, AcceptanceCounts)> = BTreeMap::new();
+    for session in &sessions {
+        let (count, counts) = groups
+            .entry((session.tool.as_str(), session.model.as_str()))
+            .or_default();
+        *count += 1;
+        counts.add(&session.counts);
+    }
+    let mut by_tool_model: Vec<ToolModelAcceptance> = groups
+        .into_iter()
+        .map(|((tool, model), (count, counts))| ToolModelAcceptance {
+            tool: tool.to_string(),
+            model: model.to_string(),
+            sessions: count,
+            acceptance_rate: counts.acceptance_rate(),
+            counts,
+        })
+        .collect();
+    by_tool_model.sort_by(|a, b| b.acceptance_rate.total_cmp(&a.acceptance_rate));
+    AcceptanceReport {
+        by_tool_model,
+        sessions,
+    }
+}
+
+pub fn print_acceptance(report: &AcceptanceReport) {
+    if report.sessions.is_empty() {
+        println!("No AI sessions in these commits");
+        return;
+    }
+    println!(
+        "{:<32}  {:>8}  {:>8}  {:>10}  {:>8}  {:>8}",
+        "agent::model", "sessions", "accepted", "overridden", "total", "rate"
+    );
+    for stat in &report.by_tool_model {
+        println!(
+            "{:<32}  {:>8}  {:>8}  {:>10}  {:>8}  {:>7.0}%",
+            format!("{}::{}", stat.tool, stat.model),
+            stat.sessions,
+            stat.counts.accepted_lines,
+            stat.counts.overriden_lines,
+            stat.counts.total_additions,
+            stat.acceptance_rate * 100.0
+        );
+    }
+    println!();
+    println!(
+        "{:<16}  {:<32}  {:>8}  {:>10}  {:>8}  {:>8}",
+        "session", "agent::model", "accepted", "overridden", "total", "rate"
+    );
+    for session in &report.sessions {
+        println!(
+            "{:<16}  {:<32}  {:>8}  {:>10}  {:>8}  {:>7.0}%",
+            session.session_id,
+            format!("{}::{}", session.tool, session.model),
+            session.counts.accepted_lines,
+            session.counts.overriden_lines,
+            session.counts.total_additions,
+            session.acceptance_rate * 100.0
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(id: &str, tool: &str, accepted: u32, total: u32) -> SessionAcceptance {
+        let counts = AcceptanceCounts {
+            total_additions: total,
+            accepted_lines: accepted,
+            overriden_lines: 0,
+        };
+        SessionAcceptance {
+            session_id: id.to_string(),
+            tool: tool.to_string(),
+            model: "model".to_string(),
+            commits: 1,
+            acceptance_rate: counts.acceptance_rate(),
+            counts,
+        }
+    }
+
+    #[test]
+    fn test_report_totals_per_tool_model_most_accepted_first() {
+        let report = report(vec![
+            session("a", "cursor", 2, 10),
+            session("b", "claude", 9, 10),
+            session("c", "cursor", 6, 10),
+        ]);
+        let rates: Vec<(&str, usize, f64)> = report
+            .by_tool_model
+            .iter()
+            .map(|stat| (stat.tool.as_str(), stat.sessions, stat.acceptance_rate))
+            .collect();
+        assert_eq!(rates, vec![("claude", 1, 0.9), ("cursor", 2, 0.4)]);
+        assert_eq!(report.sessions.len(), 3);
+    }
+
+    #[test]
+    fn test_acceptance_rate_is_capped_and_handles_no_additions() {
+        let counts = AcceptanceCounts {
+            total_additions: 2,
+            accepted_lines: 5,
+            overriden_lines: 0,
+        };
+        assert_eq!(counts.acceptance_rate(), 1.0);
+        assert_eq!(AcceptanceCounts::default().acceptance_rate(), 0.0);
+    }
+}
@@ -0,0 +1,236 @@
+use std::collections::{BTreeMap, HashSet};
+
+use crate::authorship::import::EMPTY_TREE;
+use crate::error::GitAiError;
+use crate::git::repository::Repository;
+
+/// What a commit does with lines no checkpoint recorded. Set with `unattributed_changes` in the
+/// config file or `GIT_AI_UNATTRIBUTED_CHANGES`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnattributedChanges {
+    /// Attribute them to the committer (default)
+    #[default]
+    Human,
+    /// Attribute them to the committer, warning about any there are
+    Warn,
+    /// Refuse the commit until they're checkpointed
+    Block,
+    /// Attribute them to the `unknown` author class, counted apart from both human and AI lines
+    Unknown,
+}
+
+impl UnattributedChanges {
+    pub fn parse(input: &str) -> Option<Self> {
+        match input.trim().to_lowercase().as_str() {
+            "human" => Some(UnattributedChanges::Human),
+            "warn" => Some(UnattributedChanges::Warn),
+            "block" => Some(UnattributedChanges::Block),
+            "unknown" => Some(UnattributedChanges::Unknown),
+            _ => None,
+        }
+    }
+}
+
+/// Fewest uncovered lines worth a warning, so small hand edits after the last checkpoint pass
+const MIN_UNCOVERED_LINES: u32 = 20;
+/// Smallest share of the commit's added lines that must be uncovered for a warning
+const MIN_UNCOVERED_FRACTION: f64 = 0.5;
+
+/// A file the commit adds lines to that no checkpoint ever recorded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileGap {
+    pub file: String,
+    pub uncovered_lines: u32,
+}
+
+/// How much of what the commit adds went past the working log unseen
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttributionGaps {
+    pub added_lines: u32,
+    pub files: Vec<FileGap>,
+}
+
+impl AttributionGaps {
+    pub fn uncovered_lines(&self) -> u32 {
+        self.files.iter().map(|file| file.uncovered_lines).sum()
+    }
+
+    /// Whether enough of the commit is uncovered that a tool is probably editing without
+    /// checkpointing
+    pub fn is_suspicious(&self) -> bool {
+        let uncovered = self.uncovered_lines();
+        uncovered >= MIN_UNCOVERED_LINES
+            && uncovered as f64 >= self.added_lines as f64 * MIN_UNCOVERED_FRACTION
+    }
+}
+
+/// The lines about to be committed on top of `base_commit` (in `changed_files`, all changed
+/// files when it's None) whose text no checkpoint recorded, i.e. that changed after the last
+/// checkpoint of their file. Must run before the pre-commit checkpoint, which covers everything.
+/// None when nothing has checkpointed on top of `base_commit`, since then git-ai isn't set up to
+/// see edits as they happen and every commit would look like a gap.
+pub fn attribution_gaps(
+    repo: &Repository,
+    base_commit: &str,
+    changed_files: Option<&HashSet<String>>,
+) -> Result<Option<AttributionGaps>, GitAiError> {
+    let working_log = repo.storage.working_log_for_base_commit(base_commit);
+    let checkpoints = working_log.read_all_checkpoints()?;
+    if checkpoints.is_empty() {
+        return Ok(None);
+    }
+    // The latest entry of a file has its content as of the last checkpoint that saw it
+    let mut latest_blobs = BTreeMap::new();
+    for checkpoint in &checkpoints {
+        for entry in &checkpoint.entries {
+            latest_blobs.insert(entry.file.as_str(), entry.blob_sha.as_str());
+        }
+    }
+    // Lines carried over from before the base commit were checkpointed then
+    let initial = working_log.read_initial_attributions();
+
+    let from_ref = if base_commit == "initial" {
+        EMPTY_TREE
+    } else {
+        base_commit
+    };
+    let added_lines = repo.diff_workdir_added_lines(from_ref, changed_files)?;
+
+    let mut gaps = AttributionGaps {
+        added_lines: 0,
+        files: Vec::new(),
+    };
+    let mut files: Vec<(&String, &Vec<u32>)> = added_lines.iter().collect();
+    files.sort();
+    for (file, lines) in files {
+        gaps.added_lines += lines.len() as u32;
+        let uncovered_lines = match latest_blobs.get(file.as_str()) {
+            Some(blob_sha) => {
+                let checkpointed = working_log.get_file_version(blob_sha).unwrap_or_default();
+                let content = working_log.read_current_file_content(file)?;
+                uncovered(&checkpointed, &content, lines)
+            }
+            None if initial.files.contains_key(file) => 0,
+            None => lines.len() as u32,
+        };
+        if uncovered_lines > 0 {
+            gaps.files.push(FileGap {
+                file: file.clone(),
+                uncovered_lines,
+            });
+        }
+    }
+    Ok(Some(gaps))
+}
+
+/// How many of the `added` lines (1-based) of `content` aren't in its `checkpointed` version
+fn uncovered(checkpointed: &str, content: &str, added: &[u32]) -> u32 {
+    let checkpointed_lines: Vec<&str> = checkpointed.lines().collect();
+    let lines: Vec<&str> = content.lines().collect();
+    let added: HashSet<u32> = added.iter().copied().collect();
+    similar::capture_diff_slices(similar::Algorithm::Myers, &checkpointed_lines, &lines)
+        .into_iter()
+        .filter_map(|op| match op {
+            similar::DiffOp::Insert {
+                new_index, new_len, ..
+            }
+            | similar::DiffOp::Replace {
+                new_index, new_len, ..
+            } => Some(new_index..new_index + new_len),
+            similar::DiffOp::Equal { .. } | similar::DiffOp::Delete { .. } => None,
+        })
+        .flatten()
+        .filter(|index| added.contains(&(*index as u32 + 1)))
+        .count() as u32
+}
+
+/// What the commit hook prints when much of a commit never passed through a checkpoint
+pub fn gap_warning(gaps: &AttributionGaps) -> String {
+    let mut message = format!(
+        "git-ai: warning: {} of the {} lines this commit adds were never checkpointed, so they're \
+         attributed to you\n",
+        gaps.uncovered_lines(),
+        gaps.added_lines
+    );
+    push_gap_files(&mut message, gaps);
+    message.push_str(
+        "If an AI tool wrote them, it isn't reporting its edits to git-ai. Run \
+         `git-ai install-hooks` to set up supported tools, or have the tool call \
+         `git-ai checkpoint` after it edits.\n",
+    );
+    message
+}
+
+/// What the commit hook prints when `unattributed_changes` is `block` and some of the commit was
+/// never checkpointed
+pub fn blocked_gap_message(gaps: &AttributionGaps) -> String {
+    let mut message = format!(
+        "git-ai: commit blocked: {} of the {} lines this commit adds were never checkpointed, so \
+         nothing says who wrote them\n",
+        gaps.uncovered_lines(),
+        gaps.added_lines
+    );
+    push_gap_files(&mut message, gaps);
+    message.push_str(
+        "Run `git-ai checkpoint` to record them as yours, or `git-ai checkpoint <preset>` if an \
+         agent wrote them, then commit again.\n",
+    );
+    message
+}
+
+fn push_gap_files(message: &mut String, gaps: &AttributionGaps) {
+    for file in &gaps.files {
+        message.push_str(&format!(
+            "    {} ({} {})\n",
+            file.file,
+            file.uncovered_lines,
+            if file.uncovered_lines == 1 {
+                "line"
+            } else {
+                "lines"
+            }
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uncovered_counts_added_lines_missing_from_the_checkpoint() {
+        let checkpointed = "fn a() {}\nfn b() {}\n";
+        let content = "fn a() {}\nfn b() {}\nfn c() {}\nfn d() {}\n";
+        assert_eq!(uncovered(checkpointed, content, &[1, 2, 3, 4]), 2);
+        // Lines the commit doesn't add don't count, even if they changed since the checkpoint
+        assert_eq!(uncovered(checkpointed, content, &[1, 2, 3]), 1);
+        assert_eq!(uncovered(content, content, &[1, 2, 3, 4]), 0);
+    }
+
+    #[test]
+    fn test_gaps_are_suspicious_when_large_in_lines_and_share() {
+        let gaps = |added_lines, uncovered_lines| AttributionGaps {
+            added_lines,
+            files: vec![FileGap {
+                file: "lib.rs".to_string(),
+                uncovered_lines,
+            }],
+        };
+        assert!(gaps(40, 20).is_suspicious());
+        assert!(!gaps(41, 20).is_suspicious());
+        assert!(!gaps(10, 10).is_suspicious());
+    }
+
+    #[test]
+    fn test_parse_unattributed_changes() {
+        assert_eq!(
+            UnattributedChanges::parse(" Block "),
+            Some(UnattributedChanges::Block)
+        );
+        assert_eq!(
+            UnattributedChanges::parse("unknown"),
+            Some(UnattributedChanges::Unknown)
+        );
+        assert_eq!(UnattributedChanges::parse("ask"), None);
+    }
+}
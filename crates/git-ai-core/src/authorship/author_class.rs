@@ -0,0 +1,195 @@
+//! Author classes: who wrote lines that are neither a person at the keyboard nor an AI agent,
+//! like a code generator or code vendored in from elsewhere. A checkpoint declares its class
+//! with `git-ai checkpoint --class <name>`, and its lines are attributed to the class name
+//! itself rather than to a prompt session. Authorship logs list the classes they use in
+//! `metadata.author_classes`, so `blame` and `stats` can tell them apart from prompt hashes even
+//! for classes the reading machine isn't configured with.
+
+use std::fmt;
+
+use crate::authorship::working_log::CheckpointKind;
+use crate::config::Config;
+
+/// The class a line's author id names, when it names one rather than a person or a session
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AuthorClass {
+    /// Lines written by a deterministic generator (protobuf, OpenAPI clients, migrations...)
+    Codegen,
+    /// Lines vendored or copied in from another project
+    Import,
+    /// Lines no checkpoint saw written, given to the commit's own checkpoint by
+    /// `unattributed_changes = "unknown"`
+    Unknown,
+    /// Lines the large-paste heuristic flagged as possibly written by an AI git-ai didn't see.
+    /// They still count as the human's; only paste detection gives lines this class.
+    PossibleAi,
+    /// A class added with `author_classes`, or one recorded on a machine configured with it
+    Custom(String),
+}
+
+/// Classes every repository has
+pub const BUILTIN_CLASSES: &[AuthorClass] = &[
+    AuthorClass::Codegen,
+    AuthorClass::Import,
+    AuthorClass::Unknown,
+    AuthorClass::PossibleAi,
+];
+
+impl AuthorClass {
+    /// The class `name` names: one of the built-in classes, or any other name that could be a
+    /// class, configured here or not. None for prompt session hashes, `human` and the other
+    /// author ids checkpoints fall back to.
+    pub fn from_name(name: &str) -> Option<AuthorClass> {
+        match name {
+            "codegen" => Some(AuthorClass::Codegen),
+            "import" => Some(AuthorClass::Import),
+            "unknown" => Some(AuthorClass::Unknown),
+            "possible_ai" => Some(AuthorClass::PossibleAi),
+            name if name_problem(name).is_none() => Some(AuthorClass::Custom(name.to_string())),
+            _ => None,
+        }
+    }
+
+    /// The author id lines of this class are attributed to
+    pub fn as_str(&self) -> &str {
+        match self {
+            AuthorClass::Codegen => "codegen",
+            AuthorClass::Import => "import",
+            AuthorClass::Unknown => "unknown",
+            AuthorClass::PossibleAi => "possible_ai",
+            AuthorClass::Custom(name) => name,
+        }
+    }
+
+    /// True if a checkpoint can declare this class with `--class`
+    pub fn is_declarable(&self) -> bool {
+        *self != AuthorClass::PossibleAi
+    }
+
+    /// True if this machine knows about the class
+    pub fn is_known(&self) -> bool {
+        match self {
+            AuthorClass::Custom(name) => Config::get()
+                .author_classes()
+                .iter()
+                .any(|class| class == name),
+            _ => true,
+        }
+    }
+}
+
+impl fmt::Display for AuthorClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The classes a checkpoint can declare: the built-in ones, then those added with
+/// `author_classes`
+pub fn known_classes() -> Vec<AuthorClass> {
+    let mut classes: Vec<AuthorClass> = BUILTIN_CLASSES
+        .iter()
+        .filter(|class| class.is_declarable())
+        .cloned()
+        .collect();
+    for class in Config::get().author_classes() {
+        if let Some(class) = AuthorClass::from_name(class)
+            && !classes.contains(&class)
+        {
+            classes.push(class);
+        }
+    }
+    classes
+}
+
+/// Why `name` can't be a custom class, if it can't. Class names share the attestation hash
+/// field with prompt session hashes, so they mustn't look like one or like the other author
+/// ids.
+fn name_problem(name: &str) -> Option<String> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-')
+    {
+        return Some(format!(
+            "'{}' isn't a valid author class; use lowercase letters, digits, '_' and '-'",
+            name
+        ));
+    }
+    // Checkpoints without an agent id fall back to their kind as the author id
+    let kinds = [
+        CheckpointKind::Human,
+        CheckpointKind::AiAgent,
+        CheckpointKind::AiTab,
+        CheckpointKind::AuthorClass,
+    ];
+    if kinds.iter().any(|kind| kind.to_str() == name) {
+        return Some(format!(
+            "'{}' is reserved and can't be an author class",
+            name
+        ));
+    }
+    if name.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Some(format!(
+            "'{}' looks like a prompt session hash and can't be an author class",
+            name
+        ));
+    }
+    None
+}
+
+/// The class a checkpoint declares with `--class <name>`, or why it can't declare it
+pub fn parse_declared_class(name: &str) -> Result<AuthorClass, String> {
+    let class = AuthorClass::from_name(name).ok_or_else(|| {
+        name_problem(name).unwrap_or_else(|| format!("'{}' isn't a valid author class", name))
+    })?;
+    if !class.is_declarable() {
+        return Err(format!(
+            "'{}' is reserved and can't be an author class",
+            name
+        ));
+    }
+    if !class.is_known() {
+        let known: Vec<String> = known_classes().iter().map(|c| c.to_string()).collect();
+        return Err(format!(
+            "unknown author class '{}'; expected one of {} (add more with author_classes)",
+            name,
+            known.join(", ")
+        ));
+    }
+    Ok(class)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_classes_round_trip() {
+        for class in BUILTIN_CLASSES {
+            assert_eq!(AuthorClass::from_name(class.as_str()).as_ref(), Some(class));
+        }
+        assert_eq!(
+            AuthorClass::from_name("proto-gen"),
+            Some(AuthorClass::Custom("proto-gen".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_declarable_classes() {
+        assert_eq!(parse_declared_class("codegen"), Ok(AuthorClass::Codegen));
+        assert_eq!(parse_declared_class("unknown"), Ok(AuthorClass::Unknown));
+        assert!(parse_declared_class("possible_ai").is_err());
+    }
+
+    #[test]
+    fn test_class_names_cannot_shadow_other_authors() {
+        assert_eq!(AuthorClass::from_name("human"), None);
+        assert_eq!(AuthorClass::from_name("ai_agent"), None);
+        assert_eq!(AuthorClass::from_name("6d68a91"), None);
+        assert_eq!(AuthorClass::from_name("0123456789abcdef"), None);
+        assert_eq!(AuthorClass::from_name("Codegen"), None);
+        assert!(parse_declared_class("human").is_err());
+        assert!(parse_declared_class("not-configured").is_err());
+    }
+}
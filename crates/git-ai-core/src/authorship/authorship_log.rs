@@ -184,11 +184,59 @@ impl fmt::Display for LineRange {
     }
 }
 
+/// Part of a single line attributed to one author, used when a line was co-written.
+/// Columns are 1-indexed character positions; both start and end are inclusive.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ColumnRange {
+    pub line: u32,
+    pub start: u32,
+    pub end: u32,
+}
+
+impl ColumnRange {
+    pub fn new(line: u32, start: u32, end: u32) -> Self {
+        ColumnRange { line, start, end }
+    }
+}
+
+/// What a human pairing with others on an agent session did
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ParticipantRole {
+    /// Prompting the agent
+    Driver,
+    /// Reviewing and steering alongside the driver
+    Navigator,
+}
+
+impl ParticipantRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ParticipantRole::Driver => "driver",
+            ParticipantRole::Navigator => "navigator",
+        }
+    }
+}
+
+/// A human taking part in an agent session, as recorded while `git-ai pair` is on
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Participant {
+    /// `Name <email>`, like `human_author`
+    pub author: String,
+    pub role: ParticipantRole,
+}
+
 /// Prompt session details stored in the top-level prompts map keyed by short hash (agent_id + tool)
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PromptRecord {
     pub agent_id: AgentId,
+    /// The human who prompted the session, `Name <email>`. Notes written before git-ai recorded
+    /// it on checkpoints have the author of the commit the session's lines were first committed in.
     pub human_author: Option<String>,
+    /// Everyone pairing on the session, drivers first. Empty when nobody was pairing; older
+    /// versions of git-ai skip it and see only `human_author`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub human_authors: Vec<Participant>,
     pub messages: Vec<Message>,
     #[serde(default)]
     pub total_additions: u32,
@@ -198,6 +246,32 @@ pub struct PromptRecord {
     pub accepted_lines: u32,
     #[serde(default)]
     pub overriden_lines: u32,
+    /// When the session's first checkpoint in the commit was made (unix timestamp). Older
+    /// versions of git-ai don't record it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_checkpoint_at: Option<u64>,
+    /// When the session's last checkpoint in the commit was made (unix timestamp)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_checkpoint_at: Option<u64>,
+}
+
+impl PromptRecord {
+    /// The human driving the session: its pairing driver if there was one, else `human_author`
+    pub fn prompter(&self) -> Option<&str> {
+        self.human_authors
+            .iter()
+            .find(|participant| participant.role == ParticipantRole::Driver)
+            .map(|participant| participant.author.as_str())
+            .or(self.human_author.as_deref())
+    }
+}
+
+/// The name part of a `Name <email>` author
+pub fn author_name(author: &str) -> &str {
+    match author.find(" <") {
+        Some(end) if author.ends_with('>') => &author[..end],
+        _ => author,
+    }
 }
 
 impl Eq for PromptRecord {}
@@ -245,11 +319,14 @@ mod tests {
         PromptRecord {
             agent_id,
             human_author: None,
+            human_authors: Vec::new(),
             messages: message_list,
             total_additions: additions,
             total_deletions: deletions,
             accepted_lines: 0,
             overriden_lines: 0,
+            first_checkpoint_at: None,
+            last_checkpoint_at: None,
         }
     }
 
@@ -277,4 +354,43 @@ mod tests {
                 || records[1].total_deletions > 0
         );
     }
+
+    #[test]
+    fn test_human_authors_round_trip_and_default_for_older_notes() {
+        let mut record = create_prompt_record(1, 2, 0);
+        let json = serde_json::to_string(&record).unwrap();
+        assert!(!json.contains("human_authors"));
+        assert_eq!(serde_json::from_str::<PromptRecord>(&json).unwrap(), record);
+
+        record.human_authors = vec![
+            Participant {
+                author: "Jane <jane@example.com>".to_string(),
+                role: ParticipantRole::Driver,
+            },
+            Participant {
+                author: "Joe <joe@example.com>".to_string(),
+                role: ParticipantRole::Navigator,
+            },
+        ];
+        let json = serde_json::to_string(&record).unwrap();
+        assert!(json.contains(r#""role":"navigator""#), "{}", json);
+        assert_eq!(serde_json::from_str::<PromptRecord>(&json).unwrap(), record);
+    }
+
+    #[test]
+    fn test_prompter_prefers_pairing_driver() {
+        let mut record = create_prompt_record(1, 2, 0);
+        assert_eq!(record.prompter(), None);
+
+        record.human_author = Some("Joe <joe@example.com>".to_string());
+        assert_eq!(record.prompter(), Some("Joe <joe@example.com>"));
+
+        record.human_authors = vec![Participant {
+            author: "Jane <jane@example.com>".to_string(),
+            role: ParticipantRole::Driver,
+        }];
+        assert_eq!(record.prompter(), Some("Jane <jane@example.com>"));
+        assert_eq!(author_name("Jane <jane@example.com>"), "Jane");
+        assert_eq!(author_name("jane"), "jane");
+    }
 }
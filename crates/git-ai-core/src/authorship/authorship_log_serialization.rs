@@ -1,12 +1,13 @@
-use crate::authorship::authorship_log::{Author, LineRange, PromptRecord};
+use crate::authorship::author_class::{AuthorClass, BUILTIN_CLASSES};
+use crate::authorship::authorship_log::{Author, ColumnRange, LineRange, PromptRecord};
 use crate::authorship::working_log::CheckpointKind;
 use crate::git::repository::Repository;
+use crate::utils::now_millis;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fmt;
 use std::io::{BufRead, Write};
-use std::time::{SystemTime, UNIX_EPOCH};
 
 /// Authorship log format version identifier
 pub const AUTHORSHIP_LOG_VERSION: &str = "authorship/3.0.0";
@@ -27,6 +28,36 @@ pub struct AuthorshipMetadata {
     pub git_ai_version: Option<String>,
     pub base_commit_sha: String,
     pub prompts: BTreeMap<String, PromptRecord>,
+    /// Confidence of attestation entries that aren't fully certain (file -> hash -> percent).
+    /// Only populated while (de)serializing; in memory it lives on `AttestationEntry`.
+    /// Kept here rather than on the entry lines so older parsers can still read the log.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub confidence: BTreeMap<String, BTreeMap<String, u8>>,
+    /// Column ranges of attestation entries on co-written lines (file -> hash -> ranges).
+    /// Like `confidence`, only populated while (de)serializing.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub columns: BTreeMap<String, BTreeMap<String, Vec<ColumnRange>>>,
+    /// Lines a human edited that kept their AI attribution (file -> hash -> ranges).
+    /// Like `confidence`, only populated while (de)serializing.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub overridden: BTreeMap<String, BTreeMap<String, Vec<LineRange>>>,
+    /// Prompt whose agent wrote the commit message, if an agent did
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit_message_prompt: Option<String>,
+    /// Set when `git-ai backfill` guessed the log from the commit rather than it being recorded,
+    /// to the heuristic that matched (e.g. `author copilot-swe-agent[bot]`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inferred: Option<String>,
+    /// Files the commit changed that weren't attributed, with why (e.g. `lfs`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub skipped_files: BTreeMap<String, String>,
+    /// Files the commit renamed, old path -> new path. Attestations are keyed by the path in
+    /// their own commit, so this is how a file is followed across logs.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub renames: BTreeMap<String, String>,
+    /// Attestation hashes that are author classes (e.g. `codegen`) rather than prompts
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub author_classes: BTreeSet<String>,
 }
 
 impl AuthorshipMetadata {
@@ -36,8 +67,24 @@ impl AuthorshipMetadata {
             git_ai_version: Some(GIT_AI_VERSION.to_string()),
             base_commit_sha: String::new(),
             prompts: BTreeMap::new(),
+            confidence: BTreeMap::new(),
+            columns: BTreeMap::new(),
+            overridden: BTreeMap::new(),
+            commit_message_prompt: None,
+            inferred: None,
+            skipped_files: BTreeMap::new(),
+            renames: BTreeMap::new(),
+            author_classes: BTreeSet::new(),
         }
     }
+
+    /// The path `path` had before this commit renamed it to `path`, if it did
+    pub fn renamed_from(&self, path: &str) -> Option<&str> {
+        self.renames
+            .iter()
+            .find(|(_, new_path)| *new_path == path)
+            .map(|(old_path, _)| old_path.as_str())
+    }
 }
 
 impl Default for AuthorshipMetadata {
@@ -48,19 +95,50 @@ impl Default for AuthorshipMetadata {
 
 /// Attestation entry: short hash followed by line ranges
 ///
-/// IMPORTANT: The hash ALWAYS corresponds to a prompt in the prompts section.
-/// This system only tracks AI-generated content, not human-authored content.
+/// IMPORTANT: The hash corresponds to a prompt in the prompts section, unless it's one of the
+/// built-in author classes or those in `metadata.author_classes`. Human-authored content isn't
+/// tracked.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct AttestationEntry {
     /// Short hash (7 chars) that maps to an entry in the prompts section of the metadata
     pub hash: String,
     /// Line ranges that this prompt is responsible for
     pub line_ranges: Vec<LineRange>,
+    /// How certain the attribution is, as a percentage (None means it was recorded directly)
+    pub confidence: Option<u8>,
+    /// Parts of co-written lines this prompt is responsible for
+    pub column_ranges: Vec<ColumnRange>,
+    /// Lines in `line_ranges` that a human edited without taking over the attribution
+    pub overridden_lines: Vec<LineRange>,
 }
 
 impl AttestationEntry {
     pub fn new(hash: String, line_ranges: Vec<LineRange>) -> Self {
-        Self { hash, line_ranges }
+        Self {
+            hash,
+            line_ranges,
+            confidence: None,
+            column_ranges: Vec::new(),
+            overridden_lines: Vec::new(),
+        }
+    }
+
+    /// Set the lines of this entry that a human edited but that stayed attributed to it
+    pub fn with_overridden_lines(mut self, overridden_lines: Vec<LineRange>) -> Self {
+        self.overridden_lines = overridden_lines;
+        self
+    }
+
+    /// Set the column ranges of this entry
+    pub fn with_column_ranges(mut self, column_ranges: Vec<ColumnRange>) -> Self {
+        self.column_ranges = column_ranges;
+        self
+    }
+
+    /// Set the confidence of this entry
+    pub fn with_confidence(mut self, confidence: Option<u8>) -> Self {
+        self.confidence = confidence;
+        self
     }
 
     #[allow(dead_code)]
@@ -135,6 +213,31 @@ impl AuthorshipLog {
         }
     }
 
+    /// The class attestations with `hash` belong to, if they're an author class's lines. The
+    /// built-in classes always are, as logs from before they were classes don't list them.
+    pub fn author_class(&self, hash: &str) -> Option<AuthorClass> {
+        let class = AuthorClass::from_name(hash)?;
+        (BUILTIN_CLASSES.contains(&class) || self.metadata.author_classes.contains(hash))
+            .then_some(class)
+    }
+
+    /// Lists the author classes among the attestation hashes in `metadata.author_classes`.
+    /// Any hash that isn't a prompt and could be a class name is one, so classes this machine
+    /// isn't configured with survive rewrites.
+    pub fn record_author_classes(&mut self) {
+        let classes: BTreeSet<String> = self
+            .attestations
+            .iter()
+            .flat_map(|file| &file.entries)
+            .map(|entry| &entry.hash)
+            .filter(|hash| {
+                !self.metadata.prompts.contains_key(*hash) && AuthorClass::from_name(hash).is_some()
+            })
+            .cloned()
+            .collect();
+        self.metadata.author_classes = classes;
+    }
+
     pub fn get_or_create_file(&mut self, file: &str) -> &mut FileAttestation {
         // Check if file already exists
         let exists = self.attestations.iter().any(|f| f.file_path == file);
@@ -178,8 +281,41 @@ impl AuthorshipLog {
         // Write divider
         output.push_str("---\n");
 
-        // Write JSON metadata section
-        let json_str = serde_json::to_string_pretty(&self.metadata).map_err(|_| fmt::Error)?;
+        // Write JSON metadata section, carrying entry confidences, columns and overridden
+        // lines along with it
+        let mut metadata = self.metadata.clone();
+        for file_attestation in &self.attestations {
+            for entry in &file_attestation.entries {
+                if !entry.column_ranges.is_empty() {
+                    metadata
+                        .columns
+                        .entry(file_attestation.file_path.clone())
+                        .or_default()
+                        .entry(entry.hash.clone())
+                        .or_default()
+                        .extend(entry.column_ranges.iter().cloned());
+                }
+                if !entry.overridden_lines.is_empty() {
+                    metadata
+                        .overridden
+                        .entry(file_attestation.file_path.clone())
+                        .or_default()
+                        .entry(entry.hash.clone())
+                        .or_default()
+                        .extend(entry.overridden_lines.iter().cloned());
+                }
+                if let Some(confidence) = entry.confidence {
+                    let existing = metadata
+                        .confidence
+                        .entry(file_attestation.file_path.clone())
+                        .or_default()
+                        .entry(entry.hash.clone())
+                        .or_insert(confidence);
+                    *existing = (*existing).min(confidence);
+                }
+            }
+        }
+        let json_str = serde_json::to_string_pretty(&metadata).map_err(|_| fmt::Error)?;
         output.push_str(&json_str);
 
         Ok(output)
@@ -206,12 +342,39 @@ impl AuthorshipLog {
 
         // Parse attestation section (before divider)
         let attestation_lines = &lines[..divider_pos];
-        let attestations = parse_attestation_section(attestation_lines)?;
+        let mut attestations = parse_attestation_section(attestation_lines)?;
 
         // Parse JSON metadata section (after divider)
         let json_lines = &lines[divider_pos + 1..];
         let json_content = json_lines.join("\n");
-        let metadata: AuthorshipMetadata = serde_json::from_str(&json_content)?;
+        let mut metadata: AuthorshipMetadata = serde_json::from_str(&json_content)?;
+
+        // Move entry confidences, columns and overridden lines from the metadata back onto
+        // their entries
+        let confidence = std::mem::take(&mut metadata.confidence);
+        let mut columns = std::mem::take(&mut metadata.columns);
+        let mut overridden = std::mem::take(&mut metadata.overridden);
+        for file_attestation in attestations.iter_mut() {
+            if let Some(file_confidence) = confidence.get(&file_attestation.file_path) {
+                for entry in file_attestation.entries.iter_mut() {
+                    entry.confidence = file_confidence.get(&entry.hash).copied();
+                }
+            }
+            if let Some(file_columns) = columns.get_mut(&file_attestation.file_path) {
+                for entry in file_attestation.entries.iter_mut() {
+                    if let Some(ranges) = file_columns.remove(&entry.hash) {
+                        entry.column_ranges = ranges;
+                    }
+                }
+            }
+            if let Some(file_overridden) = overridden.get_mut(&file_attestation.file_path) {
+                for entry in file_attestation.entries.iter_mut() {
+                    if let Some(ranges) = file_overridden.remove(&entry.hash) {
+                        entry.overridden_lines = ranges;
+                    }
+                }
+            }
+        }
 
         Ok(Self {
             attestations,
@@ -228,6 +391,31 @@ impl AuthorshipLog {
         Self::deserialize_from_string(&content)
     }
 
+    /// Confidence of the entry attributing the given file and line, if it isn't fully certain
+    pub fn get_line_confidence(&self, file: &str, line: u32) -> Option<u8> {
+        let file_attestation = self.attestations.iter().find(|f| f.file_path == file)?;
+
+        // Same precedence as get_line_attribution: latest matching entry wins
+        file_attestation
+            .entries
+            .iter()
+            .rev()
+            .find(|entry| entry.line_ranges.iter().any(|range| range.contains(line)))
+            .and_then(|entry| entry.confidence)
+    }
+
+    /// Hash of the latest entry holding column ranges on the given line, if the line was co-written
+    pub fn get_line_column_owner(&self, file: &str, line: u32) -> Option<&str> {
+        let file_attestation = self.attestations.iter().find(|f| f.file_path == file)?;
+
+        file_attestation
+            .entries
+            .iter()
+            .rev()
+            .find(|entry| entry.column_ranges.iter().any(|range| range.line == line))
+            .map(|entry| entry.hash.as_str())
+    }
+
     /// Lookup the author and optional prompt for a given file and line
     pub fn get_line_attribution(
         &self,
@@ -244,6 +432,19 @@ impl AuthorshipLog {
             // Check if this line is covered by any of the line ranges
             let contains = entry.line_ranges.iter().any(|range| range.contains(line));
             if contains {
+                match self.author_class(&entry.hash) {
+                    // Lines the large-paste heuristic flagged are still the human's
+                    Some(AuthorClass::PossibleAi) => return None,
+                    // Lines of an author class belong to no agent, and are shown as the class
+                    Some(class) => {
+                        let author = Author {
+                            username: class.to_string(),
+                            email: String::new(),
+                        };
+                        return Some((author, Some(entry.hash.clone()), None));
+                    }
+                    None => {}
+                }
                 // The hash corresponds to a prompt session short hash
                 if let Some(prompt_record) = self.metadata.prompts.get(&entry.hash) {
                     // Create author info from the prompt record
@@ -269,17 +470,13 @@ impl AuthorshipLog {
                         let shas =
                             crate::git::refs::grep_ai_notes(repo, &format!("\"{}\"", &entry.hash))
                                 .unwrap_or_default();
-                        let result = if let Some(latest_sha) = shas.first() {
-                            if let Some(authorship_log) =
-                                crate::git::refs::get_authorship(repo, latest_sha)
-                            {
+                        // The hash can also appear in other metadata (e.g. confidence), so
+                        // take the newest note that actually holds the prompt
+                        let result = shas.iter().find_map(|sha| {
+                            crate::git::refs::get_authorship(repo, sha).and_then(|authorship_log| {
                                 authorship_log.metadata.prompts.get(&entry.hash).cloned()
-                            } else {
-                                None
-                            }
-                        } else {
-                            None
-                        };
+                            })
+                        });
                         // Cache the result (even if None) to avoid repeated grepping
                         foreign_prompts_cache.insert(entry.hash.clone(), result.clone());
                         result
@@ -320,18 +517,15 @@ impl AuthorshipLog {
         };
         use crate::authorship::authorship_log::PromptRecord;
         use crate::authorship::working_log::{Checkpoint, WorkingLogEntry};
-        use std::collections::{HashMap, HashSet};
+        use std::collections::{BTreeSet, HashMap};
 
         let mut checkpoints = Vec::new();
 
         // Get the current timestamp in milliseconds since the Unix epoch
-        let ts = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_millis();
+        let ts = now_millis();
 
-        // Track all files that have attestations
-        let mut all_files: HashSet<String> = HashSet::new();
+        // Track all files that have attestations, in path order
+        let mut all_files: BTreeSet<String> = BTreeSet::new();
         for file_attestation in &self.attestations {
             all_files.insert(file_attestation.file_path.clone());
         }
@@ -504,7 +698,7 @@ impl Default for AuthorshipLog {
 
 /// Format line ranges as comma-separated values with ranges as "start-end"
 /// Sorts ranges first: Single ranges by their value, Range ones by their lowest bound
-fn format_line_ranges(ranges: &[LineRange]) -> String {
+pub(crate) fn format_line_ranges(ranges: &[LineRange]) -> String {
     let mut sorted_ranges = ranges.to_vec();
     sorted_ranges.sort_by(|a, b| {
         let a_start = match a {
@@ -597,8 +791,11 @@ fn parse_attestation_section(
                 }
             }
 
-            // Parse file path, handling quoted paths
-            let file_path = if line.starts_with('"') && line.ends_with('"') {
+            // Parse file path, handling quoted paths. A path that isn't UTF-8 is keyed by its
+            // git-quoted spelling, quotes included.
+            let file_path = if crate::git::repo_path::is_quoted_key(line) {
+                line.to_string()
+            } else if line.starts_with('"') && line.ends_with('"') {
                 // Quoted path - remove quotes (no unescaping needed since quotes aren't allowed in file names)
                 line[1..line.len() - 1].to_string()
             } else {
@@ -769,11 +966,14 @@ mod tests {
             crate::authorship::authorship_log::PromptRecord {
                 agent_id: agent_id,
                 human_author: None,
+                human_authors: Vec::new(),
                 messages: vec![],
                 total_additions: 0,
                 total_deletions: 0,
                 accepted_lines: 0,
                 overriden_lines: 0,
+                first_checkpoint_at: None,
+                last_checkpoint_at: None,
             },
         );
 
@@ -835,11 +1035,14 @@ mod tests {
             crate::authorship::authorship_log::PromptRecord {
                 agent_id: agent_id,
                 human_author: None,
+                human_authors: Vec::new(),
                 messages: vec![],
                 total_additions: 0,
                 total_deletions: 0,
                 accepted_lines: 0,
                 overriden_lines: 0,
+                first_checkpoint_at: None,
+                last_checkpoint_at: None,
             },
         );
 
@@ -866,6 +1069,116 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_non_utf8_file_names_roundtrip() {
+        let mut log = AuthorshipLog::new();
+        // Keys of paths that aren't UTF-8 are their git-quoted spelling, with or without spaces
+        for path in ["\"lat\\351n.rs\"", "\"lat\\351n copy.rs\"", "caf\u{e9}.rs"] {
+            let mut file = FileAttestation::new(path.to_string());
+            file.add_entry(AttestationEntry::new(
+                "abc1234".to_string(),
+                vec![LineRange::Single(1)],
+            ));
+            log.attestations.push(file);
+        }
+
+        let serialized = log.serialize_to_string().unwrap();
+        let deserialized = AuthorshipLog::deserialize_from_string(&serialized).unwrap();
+        let paths: Vec<&str> = deserialized
+            .attestations
+            .iter()
+            .map(|file| file.file_path.as_str())
+            .collect();
+        assert_eq!(
+            paths,
+            ["\"lat\\351n.rs\"", "\"lat\\351n copy.rs\"", "caf\u{e9}.rs"]
+        );
+    }
+
+    #[test]
+    fn test_entry_confidence_roundtrip() {
+        let mut log = AuthorshipLog::new();
+        log.metadata.base_commit_sha = "abc123".to_string();
+
+        let mut file = FileAttestation::new("src/lib.rs".to_string());
+        file.add_entry(AttestationEntry::new(
+            "certain".to_string(),
+            vec![LineRange::Range(1, 3)],
+        ));
+        file.add_entry(
+            AttestationEntry::new("moved".to_string(), vec![LineRange::Single(7)])
+                .with_confidence(Some(82)),
+        );
+        log.attestations.push(file);
+
+        let serialized = log.serialize_to_string().unwrap();
+        // Entry lines keep the format older versions can parse
+        assert!(serialized.contains("  moved 7\n"));
+        assert!(serialized.contains("\"confidence\""));
+
+        let deserialized = AuthorshipLog::deserialize_from_string(&serialized).unwrap();
+        assert_eq!(deserialized, log);
+        assert!(deserialized.metadata.confidence.is_empty());
+        assert_eq!(deserialized.get_line_confidence("src/lib.rs", 7), Some(82));
+        assert_eq!(deserialized.get_line_confidence("src/lib.rs", 2), None);
+
+        // Logs without any uncertain entries don't mention confidence at all
+        log.attestations[0].entries[1].confidence = None;
+        assert!(!log.serialize_to_string().unwrap().contains("confidence"));
+    }
+
+    #[test]
+    fn test_entry_column_ranges_roundtrip() {
+        let mut log = AuthorshipLog::new();
+        log.metadata.base_commit_sha = "abc123".to_string();
+
+        let mut file = FileAttestation::new("src/lib.rs".to_string());
+        file.add_entry(
+            AttestationEntry::new("cowriter".to_string(), vec![LineRange::Range(1, 2)])
+                .with_column_ranges(vec![ColumnRange::new(4, 9, 18)]),
+        );
+        log.attestations.push(file);
+
+        let serialized = log.serialize_to_string().unwrap();
+        // Entry lines keep the format older versions can parse
+        assert!(serialized.contains("  cowriter 1-2\n"));
+        assert!(serialized.contains("\"columns\""));
+
+        let deserialized = AuthorshipLog::deserialize_from_string(&serialized).unwrap();
+        assert_eq!(deserialized, log);
+        assert!(deserialized.metadata.columns.is_empty());
+        assert_eq!(
+            deserialized.get_line_column_owner("src/lib.rs", 4),
+            Some("cowriter")
+        );
+        assert_eq!(deserialized.get_line_column_owner("src/lib.rs", 1), None);
+
+        // Logs without co-written lines don't mention columns at all
+        log.attestations[0].entries[0].column_ranges.clear();
+        assert!(!log.serialize_to_string().unwrap().contains("columns"));
+    }
+
+    #[test]
+    fn test_entry_overridden_lines_roundtrip() {
+        let mut log = AuthorshipLog::new();
+        log.metadata.base_commit_sha = "abc123".to_string();
+
+        let mut file = FileAttestation::new("src/lib.rs".to_string());
+        file.add_entry(
+            AttestationEntry::new("kept".to_string(), vec![LineRange::Range(1, 5)])
+                .with_overridden_lines(vec![LineRange::Single(3)]),
+        );
+        log.attestations.push(file);
+
+        let serialized = log.serialize_to_string().unwrap();
+        assert!(serialized.contains("  kept 1-5\n"));
+        assert!(serialized.contains("\"overridden\""));
+
+        let deserialized = AuthorshipLog::deserialize_from_string(&serialized).unwrap();
+        assert_eq!(deserialized, log);
+        assert!(deserialized.metadata.overridden.is_empty());
+    }
+
     #[test]
     fn test_serialize_deserialize_no_attestations() {
         // Test that serialization and deserialization work correctly when there are no attestations
@@ -883,11 +1196,14 @@ mod tests {
             crate::authorship::authorship_log::PromptRecord {
                 agent_id: agent_id,
                 human_author: None,
+                human_authors: Vec::new(),
                 messages: vec![],
                 total_additions: 0,
                 total_deletions: 0,
                 accepted_lines: 0,
                 overriden_lines: 0,
+                first_checkpoint_at: None,
+                last_checkpoint_at: None,
             },
         );
 
@@ -1061,11 +1377,14 @@ mod tests {
             crate::authorship::authorship_log::PromptRecord {
                 agent_id: agent_id.clone(),
                 human_author: Some("alice@example.com".to_string()),
+                human_authors: Vec::new(),
                 messages: transcript.messages().to_vec(),
                 total_additions: 15,
                 total_deletions: 3,
                 accepted_lines: 11,
                 overriden_lines: 0,
+                first_checkpoint_at: None,
+                last_checkpoint_at: None,
             },
         );
 
@@ -1231,11 +1550,14 @@ mod tests {
             crate::authorship::authorship_log::PromptRecord {
                 agent_id: agent1,
                 human_author: Some("bob@example.com".to_string()),
+                human_authors: Vec::new(),
                 messages: transcript1.messages().to_vec(),
                 total_additions: 10,
                 total_deletions: 0,
                 accepted_lines: 10,
                 overriden_lines: 0,
+                first_checkpoint_at: None,
+                last_checkpoint_at: None,
             },
         );
 
@@ -1254,11 +1576,14 @@ mod tests {
             crate::authorship::authorship_log::PromptRecord {
                 agent_id: agent2,
                 human_author: Some("bob@example.com".to_string()),
+                human_authors: Vec::new(),
                 messages: transcript2.messages().to_vec(),
                 total_additions: 20,
                 total_deletions: 0,
                 accepted_lines: 20,
                 overriden_lines: 0,
+                first_checkpoint_at: None,
+                last_checkpoint_at: None,
             },
         );
 
@@ -0,0 +1,336 @@
+use crate::authorship::authorship_log::{LineRange, PromptRecord};
+use crate::authorship::authorship_log_serialization::{
+    AttestationEntry, AuthorshipLog, FileAttestation, generate_short_hash,
+};
+use crate::authorship::commit_trailers::tool_for_co_author;
+use crate::authorship::import::EMPTY_TREE;
+use crate::authorship::working_log::AgentId;
+use crate::error::GitAiError;
+use crate::git::path_scope::PathScope;
+use crate::git::refs::{get_authorship, notes_add};
+use crate::git::repository::Repository;
+
+/// Automation with a `[bot]` identity that doesn't write code with AI
+const NON_AI_BOTS: &[&str] = &[
+    "dependabot",
+    "renovate",
+    "github-actions",
+    "pre-commit-ci",
+    "mergify",
+    "snyk-bot",
+    "imgbot",
+    "allcontributors",
+    "semantic-release-bot",
+    "codecov",
+];
+
+/// Commit message markers agents leave, as (lowercase text, tool)
+const MESSAGE_MARKERS: &[(&str, &str)] = &[
+    ("generated with claude code", "claude"),
+    ("generated with cursor", "cursor"),
+    ("generated by cursor", "cursor"),
+    ("generated with github copilot", "github-copilot"),
+    ("generated by github copilot", "github-copilot"),
+    ("generated with codex", "codex"),
+    ("generated with aider", "aider"),
+];
+
+/// Confidence (percent) given to the lines of a commit, by how it was recognized
+const BOT_AUTHOR_CONFIDENCE: u8 = 80;
+const MESSAGE_MARKER_CONFIDENCE: u8 = 70;
+const CO_AUTHOR_CONFIDENCE: u8 = 50;
+
+/// What a commit looks like it was written with, and why
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Inference {
+    pub tool: String,
+    /// The heuristic that matched, recorded in the note
+    pub reason: String,
+    pub confidence: u8,
+}
+
+/// The identities and message of a commit, as the heuristics see them
+#[derive(Debug, Clone, Default)]
+pub struct CommitIdentity {
+    pub author_name: String,
+    pub author_email: String,
+    pub committer_name: String,
+    pub committer_email: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackfilledCommit {
+    pub sha: String,
+    pub inference: Inference,
+    pub ai_lines: u32,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BackfillReport {
+    pub backfilled: Vec<BackfilledCommit>,
+    /// Commits left alone because they already have a note
+    pub already_attributed: usize,
+    /// Commits no heuristic matched
+    pub unmatched: usize,
+}
+
+/// Guesses whether an agent wrote a commit, strongest signal first: an AI bot as author or
+/// committer, a "Generated with" marker in the message, then a `Co-authored-by:` trailer naming
+/// an agent
+pub fn infer_agent(commit: &CommitIdentity) -> Option<Inference> {
+    for (role, name, email) in [
+        ("author", &commit.author_name, &commit.author_email),
+        ("committer", &commit.committer_name, &commit.committer_email),
+    ] {
+        if let Some(tool) = bot_tool(name, email) {
+            return Some(Inference {
+                tool,
+                reason: format!("{} {}", role, name),
+                confidence: BOT_AUTHOR_CONFIDENCE,
+            });
+        }
+    }
+
+    // Markers are often markdown links, e.g. "Generated with [Claude Code](https://...)"
+    let message = commit.message.to_lowercase().replace(['[', ']'], "");
+    let aider_author = commit.author_name.ends_with("(aider)");
+    if let Some((marker, tool)) = MESSAGE_MARKERS
+        .iter()
+        .find(|(marker, _)| message.contains(marker))
+    {
+        return Some(Inference {
+            tool: tool.to_string(),
+            reason: format!("message \"{}\"", marker),
+            confidence: MESSAGE_MARKER_CONFIDENCE,
+        });
+    }
+    if aider_author || message.starts_with("aider: ") {
+        return Some(Inference {
+            tool: "aider".to_string(),
+            reason: "aider commit".to_string(),
+            confidence: MESSAGE_MARKER_CONFIDENCE,
+        });
+    }
+
+    commit.message.lines().find_map(|line| {
+        let (key, co_author) = line.split_once(':')?;
+        if !key.trim().eq_ignore_ascii_case("co-authored-by") {
+            return None;
+        }
+        let tool = tool_for_co_author(co_author)?;
+        Some(Inference {
+            tool: tool.to_string(),
+            reason: format!("Co-authored-by: {}", co_author.trim()),
+            confidence: CO_AUTHOR_CONFIDENCE,
+        })
+    })
+}
+
+/// The tool behind a `[bot]` identity such as `copilot-swe-agent[bot]`, unless it's known not to
+/// write code with AI
+fn bot_tool(name: &str, email: &str) -> Option<String> {
+    let bot = [name, email]
+        .into_iter()
+        .find_map(|identity| identity.split_once("[bot]").map(|(bot, _)| bot))?
+        .trim()
+        .rsplit(['+', ' '])
+        .next()?
+        .to_lowercase();
+    if bot.is_empty() || NON_AI_BOTS.contains(&bot.as_str()) {
+        return None;
+    }
+    let tool = match bot.as_str() {
+        bot if bot.contains("copilot") => "github-copilot",
+        bot if bot.contains("claude") => "claude",
+        bot if bot.contains("cursor") => "cursor",
+        bot if bot.contains("devin") => "devin",
+        bot if bot.contains("codex") => "codex",
+        bot => bot,
+    };
+    Some(tool.to_string())
+}
+
+/// Writes inferred notes for the commits on HEAD (since `since`, if given) that don't have a
+/// note and that a heuristic recognizes. Every line such a commit adds is given to the agent,
+/// with the heuristic's confidence, and the note is marked as inferred so it can be told from
+/// recorded attribution. Existing notes are never touched.
+pub fn backfill(
+    repo: &Repository,
+    since: Option<&str>,
+    dry_run: bool,
+) -> Result<BackfillReport, GitAiError> {
+    let since_arg = since.map(|since| format!("--since={}", since));
+    let mut args = vec![
+        "log",
+        "--no-merges",
+        "--reverse",
+        "--format=%H%x00%P%x00%an%x00%ae%x00%cn%x00%ce%x00%B%x1e",
+    ];
+    args.extend(since_arg.as_deref());
+    args.push("HEAD");
+    let log = repo.git(&args)?;
+    let scope = PathScope::for_repo(repo);
+
+    let mut report = BackfillReport::default();
+    for record in log.split('\x1e') {
+        let fields: Vec<&str> = record.trim_start_matches('\n').split('\0').collect();
+        let [
+            sha,
+            parents,
+            author_name,
+            author_email,
+            committer_name,
+            committer_email,
+            message,
+        ] = fields[..]
+        else {
+            continue;
+        };
+        if get_authorship(repo, sha).is_some() {
+            report.already_attributed += 1;
+            continue;
+        }
+        let identity = CommitIdentity {
+            author_name: author_name.to_string(),
+            author_email: author_email.to_string(),
+            committer_name: committer_name.to_string(),
+            committer_email: committer_email.to_string(),
+            message: message.to_string(),
+        };
+        let Some(inference) = infer_agent(&identity) else {
+            report.unmatched += 1;
+            continue;
+        };
+
+        let parent = parents
+            .split(' ')
+            .find(|p| !p.is_empty())
+            .unwrap_or(EMPTY_TREE);
+        let mut added = repo.diff_added_lines(parent, sha, None)?;
+        added.retain(|file, lines| scope.contains(file) && !lines.is_empty());
+        let ai_lines: u32 = added.values().map(|lines| lines.len() as u32).sum();
+
+        let agent_id = AgentId {
+            tool: inference.tool.clone(),
+            id: format!("backfill-{}", sha),
+            model: "unknown".to_string(),
+        };
+        let hash = generate_short_hash(&agent_id.id, &agent_id.tool);
+        let mut authorship_log = AuthorshipLog::new();
+        authorship_log.metadata.base_commit_sha = sha.to_string();
+        authorship_log.metadata.inferred = Some(inference.reason.clone());
+        authorship_log.metadata.prompts.insert(
+            hash.clone(),
+            PromptRecord {
+                agent_id,
+                human_author: Some(format!("{} <{}>", author_name, author_email)),
+                human_authors: Vec::new(),
+                messages: Vec::new(),
+                total_additions: ai_lines,
+                total_deletions: 0,
+                accepted_lines: ai_lines,
+                overriden_lines: 0,
+                first_checkpoint_at: None,
+                last_checkpoint_at: None,
+            },
+        );
+        let mut files: Vec<(String, Vec<u32>)> = added.into_iter().collect();
+        files.sort();
+        for (file, lines) in files {
+            let mut file_attestation = FileAttestation::new(file);
+            file_attestation.add_entry(
+                AttestationEntry::new(hash.clone(), LineRange::compress_lines(&lines))
+                    .with_confidence(Some(inference.confidence)),
+            );
+            authorship_log.attestations.push(file_attestation);
+        }
+
+        if !dry_run {
+            let note = authorship_log.serialize_to_string().map_err(|_| {
+                GitAiError::Generic("Failed to serialize authorship log".to_string())
+            })?;
+            notes_add(repo, sha, &note)?;
+        }
+        report.backfilled.push(BackfilledCommit {
+            sha: sha.to_string(),
+            inference,
+            ai_lines,
+        });
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commit(author: (&str, &str), message: &str) -> CommitIdentity {
+        CommitIdentity {
+            author_name: author.0.to_string(),
+            author_email: author.1.to_string(),
+            committer_name: "GitHub".to_string(),
+            committer_email: "noreply@github.com".to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_infer_agent_from_bot_identity() {
+        let inference = infer_agent(&commit(
+            (
+                "copilot-swe-agent[bot]",
+                "198982749+Copilot@users.noreply.github.com",
+            ),
+            "Fix parser",
+        ))
+        .unwrap();
+        assert_eq!(inference.tool, "github-copilot");
+        assert_eq!(inference.confidence, BOT_AUTHOR_CONFIDENCE);
+
+        assert_eq!(
+            infer_agent(&commit(
+                (
+                    "dependabot[bot]",
+                    "49699333+dependabot[bot]@users.noreply.github.com"
+                ),
+                "Bump serde"
+            )),
+            None
+        );
+    }
+
+    #[test]
+    fn test_infer_agent_from_message() {
+        let inference = infer_agent(&commit(
+            ("Jane", "jane@example.com"),
+            "Add cache\n\n🤖 Generated with [Claude Code](https://claude.com/claude-code)\n",
+        ))
+        .unwrap();
+        assert_eq!(inference.tool, "claude");
+        assert_eq!(inference.confidence, MESSAGE_MARKER_CONFIDENCE);
+
+        let inference = infer_agent(&commit(
+            ("Jane (aider)", "jane@example.com"),
+            "feat: Add cache",
+        ))
+        .unwrap();
+        assert_eq!(inference.tool, "aider");
+
+        let inference = infer_agent(&commit(
+            ("Jane", "jane@example.com"),
+            "Add cache\n\nCo-authored-by: Cursor Agent <cursoragent@cursor.com>\n",
+        ))
+        .unwrap();
+        assert_eq!(inference.tool, "cursor");
+        assert_eq!(inference.confidence, CO_AUTHOR_CONFIDENCE);
+
+        assert_eq!(
+            infer_agent(&commit(
+                ("Jane", "jane@example.com"),
+                "Add cache\n\nCo-authored-by: Joe <joe@example.com>\n"
+            )),
+            None
+        );
+    }
+}
@@ -0,0 +1,251 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use serde::Serialize;
+
+use crate::authorship::author_class::AuthorClass;
+use crate::authorship::review::{ReviewMark, reviewer};
+use crate::authorship::working_log::{Checkpoint, CheckpointKind};
+use crate::error::GitAiError;
+use crate::git::path_scope::PathScope;
+use crate::git::refs::get_authorship;
+use crate::git::repo_config::RepoConfig;
+use crate::git::repository::Repository;
+
+/// The flag that lets a commit through the protected path check
+pub const AI_ACK_FLAG: &str = "--ai-ack";
+
+/// A protected file the commit would change with AI lines nobody has marked reviewed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProtectedChange {
+    pub file: String,
+    pub unreviewed_lines: u32,
+}
+
+/// The files under the repository's `ai.protectedPath` pathspecs that AI edited on top of
+/// `base_commit` and that the commit changes (`changed_files`, all files when it gives None),
+/// with their AI lines not yet covered by a `review mark --staged`. Empty when no paths are
+/// protected, in which case `changed_files` isn't called.
+pub fn unreviewed_protected_changes<'a>(
+    repo: &Repository,
+    base_commit: &str,
+    changed_files: impl FnOnce() -> Option<&'a HashSet<String>>,
+) -> Result<Vec<ProtectedChange>, GitAiError> {
+    let protected_paths = RepoConfig::load(repo).protected_paths;
+    if protected_paths.is_empty() {
+        return Ok(Vec::new());
+    }
+    let protected = PathScope::parse(&protected_paths)?;
+    let working_log = repo.storage.working_log_for_base_commit(base_commit);
+    let checkpoints = working_log.read_all_checkpoints()?;
+    let changed_files = changed_files();
+    Ok(protected_changes(
+        &checkpoints,
+        &working_log.read_review_marks(),
+        |file| protected.contains(file) && changed_files.is_none_or(|files| files.contains(file)),
+    ))
+}
+
+fn protected_changes(
+    checkpoints: &[Checkpoint],
+    marks: &[ReviewMark],
+    is_protected: impl Fn(&str) -> bool,
+) -> Vec<ProtectedChange> {
+    let human = CheckpointKind::Human.to_str();
+    let mut ai_edited = BTreeSet::new();
+    // The latest entry of a file has the attribution of its lines as they are now
+    let mut latest = HashMap::new();
+    for checkpoint in checkpoints {
+        for entry in &checkpoint.entries {
+            if !is_protected(&entry.file) {
+                continue;
+            }
+            if checkpoint.kind.is_ai() {
+                ai_edited.insert(entry.file.as_str());
+            }
+            latest.insert(entry.file.as_str(), entry);
+        }
+    }
+
+    ai_edited
+        .into_iter()
+        .filter_map(|file| {
+            let unreviewed_lines = latest[file]
+                .line_attributions
+                .iter()
+                .filter(|attribution| {
+                    attribution.author_id != human
+                        && AuthorClass::from_name(&attribution.author_id).is_none()
+                })
+                .flat_map(|attribution| attribution.start_line..=attribution.end_line)
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .filter(|line| reviewer(marks, file, *line).is_none())
+                .count() as u32;
+            (unreviewed_lines > 0).then(|| ProtectedChange {
+                file: file.to_string(),
+                unreviewed_lines,
+            })
+        })
+        .collect()
+}
+
+/// What the commit hook prints when it refuses a commit, with how to get it through
+pub fn blocked_commit_message(changes: &[ProtectedChange]) -> String {
+    let mut message = String::from(
+        "git-ai: commit blocked: AI-written changes to protected paths haven't been reviewed\n",
+    );
+    for change in changes {
+        message.push_str(&format!(
+            "    {} ({} unreviewed AI {})\n",
+            change.file,
+            change.unreviewed_lines,
+            if change.unreviewed_lines == 1 {
+                "line"
+            } else {
+                "lines"
+            }
+        ));
+    }
+    message.push_str(
+        "\nThese paths are protected by ai.protectedPath in .gitai. To commit, either:\n  \
+         - review the lines, then run `git-ai review mark --staged <file>[:<start>-<end>]`\n  \
+         - or acknowledge the unreviewed AI code with `git commit --ai-ack`\n",
+    );
+    message
+}
+
+/// The repository's `ai.forbiddenPath` pathspecs, where AI mustn't write at all. None when no
+/// paths are forbidden.
+pub fn forbidden_paths(repo: &Repository) -> Result<Option<PathScope>, GitAiError> {
+    let forbidden_paths = RepoConfig::load(repo).forbidden_paths;
+    if forbidden_paths.is_empty() {
+        return Ok(None);
+    }
+    PathScope::parse(&forbidden_paths).map(Some)
+}
+
+/// What a checkpoint prints as soon as an agent edits files AI isn't allowed to write
+pub fn forbidden_edit_warning(files: &[&str]) -> String {
+    let mut message = String::from(
+        "git-ai: warning: an AI agent edited paths where AI authorship isn't allowed\n",
+    );
+    for file in files {
+        message.push_str(&format!("    {}\n", file));
+    }
+    message.push_str(
+        "These paths are forbidden by ai.forbiddenPath in .gitai, and `git-ai ci check` will \
+         fail on them. Revert the agent's changes and write them by hand.\n",
+    );
+    message
+}
+
+/// AI lines a commit's authorship note puts in a forbidden path
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ForbiddenAttestation {
+    pub commit: String,
+    pub file: String,
+    pub lines: u32,
+}
+
+/// The AI attestations the notes of the commits in `base..head` have in forbidden paths, oldest
+/// commit first. Empty when no paths are forbidden.
+pub fn forbidden_attestations(
+    repo: &Repository,
+    base: &str,
+    head: &str,
+) -> Result<Vec<ForbiddenAttestation>, GitAiError> {
+    let Some(forbidden) = forbidden_paths(repo)? else {
+        return Ok(Vec::new());
+    };
+    let commits = repo.git(&["rev-list", "--reverse", &format!("{}..{}", base, head)])?;
+
+    let mut found = Vec::new();
+    for commit in commits.lines() {
+        let Some(log) = get_authorship(repo, commit) else {
+            continue;
+        };
+        for file in &log.attestations {
+            if !forbidden.contains(&file.file_path) {
+                continue;
+            }
+            let lines: u32 = file
+                .entries
+                .iter()
+                .filter(|entry| log.metadata.prompts.contains_key(&entry.hash))
+                .flat_map(|entry| &entry.line_ranges)
+                .map(|range| range.expand().len() as u32)
+                .sum();
+            if lines > 0 {
+                found.push(ForbiddenAttestation {
+                    commit: commit.to_string(),
+                    file: file.file_path.clone(),
+                    lines,
+                });
+            }
+        }
+    }
+    Ok(found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authorship::attribution_tracker::LineAttribution;
+    use crate::authorship::working_log::WorkingLogEntry;
+
+    fn checkpoint(kind: CheckpointKind, file: &str, lines: &[(u32, u32, &str)]) -> Checkpoint {
+        let line_attributions = lines
+            .iter()
+            .map(|(start, end, author)| LineAttribution {
+                start_line: *start,
+                end_line: *end,
+                author_id: author.to_string(),
+                overrode: None,
+                confidence: None,
+            })
+            .collect();
+        Checkpoint::new(
+            kind,
+            String::new(),
+            "Jane".to_string(),
+            vec![WorkingLogEntry::new(
+                file.to_string(),
+                String::new(),
+                Vec::new(),
+                line_attributions,
+            )],
+        )
+    }
+
+    #[test]
+    fn test_protected_changes() {
+        let checkpoints = vec![
+            checkpoint(CheckpointKind::AiAgent, "crypto/aes.rs", &[(1, 4, "a1b2")]),
+            checkpoint(CheckpointKind::AiAgent, "src/lib.rs", &[(1, 2, "a1b2")]),
+            checkpoint(CheckpointKind::Human, "crypto/rsa.rs", &[(1, 3, "human")]),
+            // A human rewrote the AI's first line
+            checkpoint(
+                CheckpointKind::Human,
+                "crypto/aes.rs",
+                &[(1, 1, "human"), (2, 4, "a1b2")],
+            ),
+        ];
+        let is_protected = |file: &str| file.starts_with("crypto/");
+
+        assert_eq!(
+            protected_changes(&checkpoints, &[], is_protected),
+            vec![ProtectedChange {
+                file: "crypto/aes.rs".to_string(),
+                unreviewed_lines: 3
+            }]
+        );
+
+        let mark = ReviewMark {
+            file: "crypto/aes.rs".to_string(),
+            lines: Some((2, 4)),
+            by: "joe".to_string(),
+            at: 0,
+        };
+        assert!(protected_changes(&checkpoints, &[mark], is_protected).is_empty());
+    }
+}
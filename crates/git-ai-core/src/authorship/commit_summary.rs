@@ -0,0 +1,377 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::authorship::attribution_tracker::LineAttribution;
+use crate::authorship::author_class::AuthorClass;
+use crate::authorship::authorship_log::LineRange;
+use crate::authorship::authorship_log_serialization::{
+    AuthorshipLog, format_line_ranges, generate_short_hash,
+};
+use crate::authorship::working_log::CheckpointKind;
+use crate::error::GitAiError;
+use crate::git::repository::Repository;
+
+/// Who wrote the lines a commit is about to add, as far as the working log tells
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommitSummary {
+    pub ai_lines: u32,
+    pub human_lines: u32,
+    /// Human lines the large-paste heuristic flagged as possibly written by an AI
+    pub possible_ai_lines: u32,
+    /// Lines no checkpoint saw written, left unattributed at commit
+    pub unknown_lines: u32,
+    /// Lines of each other author class, e.g. `codegen`
+    pub class_lines: BTreeMap<String, u32>,
+    /// Models of the sessions that wrote AI lines
+    pub models: BTreeSet<String>,
+    /// How many sessions wrote AI lines
+    pub sessions: usize,
+}
+
+impl CommitSummary {
+    /// One line summary, e.g. "42 AI lines (claude-3.7, 2 sessions), 17 human lines"
+    pub fn describe(&self) -> String {
+        let mut summary = format!(
+            "{} AI {}",
+            self.ai_lines,
+            plural(self.ai_lines as usize, "line")
+        );
+        if self.sessions > 0 {
+            let mut details: Vec<String> = self
+                .models
+                .iter()
+                .filter(|model| !model.is_empty() && model.as_str() != "unknown")
+                .cloned()
+                .collect();
+            details.push(format!(
+                "{} {}",
+                self.sessions,
+                plural(self.sessions, "session")
+            ));
+            summary.push_str(&format!(" ({})", details.join(", ")));
+        }
+        summary.push_str(&format!(
+            ", {} human {}",
+            self.human_lines,
+            plural(self.human_lines as usize, "line")
+        ));
+        if self.possible_ai_lines > 0 {
+            summary.push_str(&format!(
+                " ({} possibly pasted from AI)",
+                self.possible_ai_lines
+            ));
+        }
+        if self.unknown_lines > 0 {
+            summary.push_str(&format!(
+                ", {} unknown {}",
+                self.unknown_lines,
+                plural(self.unknown_lines as usize, "line")
+            ));
+        }
+        for (class, lines) in &self.class_lines {
+            summary.push_str(&format!(
+                ", {} {} {}",
+                lines,
+                class,
+                plural(*lines as usize, "line")
+            ));
+        }
+        summary
+    }
+}
+
+fn plural(count: usize, word: &str) -> String {
+    if count == 1 {
+        word.to_string()
+    } else {
+        format!("{}s", word)
+    }
+}
+
+/// Summarizes the staged changes from the working log as it stands, without checkpointing or
+/// reading the files: each file is taken as of its last checkpoint (or its INITIAL attributions),
+/// so it's quick enough to run as the commit message editor opens
+pub fn summarize_staged(repo: &Repository) -> Result<CommitSummary, GitAiError> {
+    let base_commit = repo.head().ok().and_then(|head| head.target().ok());
+    let added_lines = repo.diff_staged_added_lines(base_commit.as_deref())?;
+    Ok(WorkingAttributions::load(repo, base_commit.as_deref()).summarize(&added_lines))
+}
+
+/// Summarizes every uncommitted change to a tracked or staged file from the working log as it
+/// stands, in total and file by file
+pub fn summarize_uncommitted(
+    repo: &Repository,
+) -> Result<(CommitSummary, BTreeMap<String, CommitSummary>), GitAiError> {
+    let base_commit = repo.head().ok().and_then(|head| head.target().ok());
+    let added_lines =
+        repo.diff_workdir_added_lines(base_commit.as_deref().unwrap_or(EMPTY_TREE), None)?;
+    let attributions = WorkingAttributions::load(repo, base_commit.as_deref());
+    let files = added_lines
+        .iter()
+        .filter(|(_, lines)| !lines.is_empty())
+        .map(|(file, lines)| {
+            let file_lines = HashMap::from([(file.clone(), lines.clone())]);
+            (file.clone(), attributions.summarize(&file_lines))
+        })
+        .collect();
+    Ok((attributions.summarize(&added_lines), files))
+}
+
+/// Git's empty tree, to diff against before the first commit
+const EMPTY_TREE: &str = "4b825dc642cb6eb9a060e54bf8d69288fbee4904";
+
+/// Who wrote each line of the changed files, as of their last checkpoint
+struct WorkingAttributions {
+    /// Model of each session, by prompt id
+    models: HashMap<String, String>,
+    file_attributions: HashMap<String, Vec<LineAttribution>>,
+}
+
+impl WorkingAttributions {
+    fn load(repo: &Repository, base_commit: Option<&str>) -> WorkingAttributions {
+        let working_log = repo
+            .storage
+            .working_log_for_base_commit(base_commit.unwrap_or("initial"));
+        let initial = working_log.read_initial_attributions();
+        let checkpoints = working_log.read_all_checkpoints().unwrap_or_default();
+
+        let mut models: HashMap<String, String> = initial
+            .prompts
+            .iter()
+            .map(|(id, record)| (id.clone(), record.agent_id.model.clone()))
+            .collect();
+        let mut file_attributions: HashMap<String, Vec<LineAttribution>> = initial.files;
+        for checkpoint in &checkpoints {
+            if checkpoint.kind != CheckpointKind::Human
+                && let Some(agent_id) = &checkpoint.agent_id
+            {
+                models.insert(
+                    generate_short_hash(&agent_id.id, &agent_id.tool),
+                    agent_id.model.clone(),
+                );
+            }
+            for entry in &checkpoint.entries {
+                file_attributions.insert(entry.file.clone(), entry.line_attributions.clone());
+            }
+        }
+        WorkingAttributions {
+            models,
+            file_attributions,
+        }
+    }
+
+    fn summarize(&self, added_lines: &HashMap<String, Vec<u32>>) -> CommitSummary {
+        let mut summary = CommitSummary::default();
+        let mut sessions = BTreeSet::new();
+        for (file, lines) in added_lines {
+            let line_attributions = self.file_attributions.get(file).map(Vec::as_slice);
+            for line in lines {
+                let author_id = line_attributions.and_then(|attrs| {
+                    attrs
+                        .iter()
+                        .find(|attr| attr.start_line <= *line && *line <= attr.end_line)
+                        .map(|attr| attr.author_id.as_str())
+                });
+                let class = author_id
+                    .filter(|author_id| !self.models.contains_key(*author_id))
+                    .and_then(AuthorClass::from_name);
+                match (author_id, class) {
+                    (_, Some(AuthorClass::PossibleAi)) => {
+                        summary.human_lines += 1;
+                        summary.possible_ai_lines += 1;
+                    }
+                    (_, Some(AuthorClass::Unknown)) => summary.unknown_lines += 1,
+                    (_, Some(class)) => {
+                        *summary.class_lines.entry(class.to_string()).or_default() += 1;
+                    }
+                    (Some(author_id), None) if author_id != CheckpointKind::Human.to_str() => {
+                        summary.ai_lines += 1;
+                        if let Some(model) = self.models.get(author_id) {
+                            summary.models.insert(model.clone());
+                        }
+                        sessions.insert(author_id.to_string());
+                    }
+                    _ => summary.human_lines += 1,
+                }
+            }
+        }
+        summary.sessions = sessions.len();
+        summary
+    }
+}
+
+/// Plain text note summarizing a commit's authorship log, for `git log --notes` on machines
+/// without git-ai: the one line summary, then the lines each session wrote, file by file
+pub fn summary_note(authorship_log: &AuthorshipLog, ai_lines: u32, human_lines: u32) -> String {
+    let prompts = &authorship_log.metadata.prompts;
+    let mut possible_ai_lines = 0;
+    let mut unknown_lines = 0;
+    let mut class_lines = BTreeMap::new();
+    for entry in authorship_log
+        .attestations
+        .iter()
+        .flat_map(|file| &file.entries)
+    {
+        let count: u32 = entry
+            .line_ranges
+            .iter()
+            .map(|range| range.expand().len() as u32)
+            .sum();
+        match authorship_log.author_class(&entry.hash) {
+            Some(AuthorClass::PossibleAi) => possible_ai_lines += count,
+            Some(AuthorClass::Unknown) => unknown_lines += count,
+            Some(class) => *class_lines.entry(class.to_string()).or_default() += count,
+            None => {}
+        }
+    }
+    let summary = CommitSummary {
+        ai_lines,
+        human_lines,
+        possible_ai_lines,
+        unknown_lines,
+        class_lines,
+        models: prompts
+            .values()
+            .map(|record| record.agent_id.model.clone())
+            .collect(),
+        sessions: prompts.len(),
+    };
+    let mut note = format!("git-ai: {}\n", summary.describe());
+
+    for (prompt_id, record) in prompts {
+        let files: Vec<String> = authorship_log
+            .attestations
+            .iter()
+            .filter_map(|file| {
+                let ranges: Vec<LineRange> = file
+                    .entries
+                    .iter()
+                    .filter(|entry| &entry.hash == prompt_id)
+                    .flat_map(|entry| entry.line_ranges.iter().cloned())
+                    .collect();
+                (!ranges.is_empty())
+                    .then(|| format!("  {}: {}", file.file_path, format_line_ranges(&ranges)))
+            })
+            .collect();
+        note.push_str(&format!(
+            "\n{} {} (session {}): {} {}\n",
+            record.agent_id.tool,
+            record.agent_id.model,
+            prompt_id,
+            record.accepted_lines,
+            plural(record.accepted_lines as usize, "line")
+        ));
+        for file in files {
+            note.push_str(&file);
+            note.push('\n');
+        }
+    }
+    note
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe_commit_summary() {
+        let summary = CommitSummary {
+            ai_lines: 42,
+            human_lines: 17,
+            possible_ai_lines: 0,
+            unknown_lines: 0,
+            class_lines: BTreeMap::new(),
+            models: BTreeSet::from(["claude-3.7".to_string()]),
+            sessions: 2,
+        };
+        assert_eq!(
+            summary.describe(),
+            "42 AI lines (claude-3.7, 2 sessions), 17 human lines"
+        );
+        let summary = CommitSummary {
+            possible_ai_lines: 12,
+            ..summary
+        };
+        assert_eq!(
+            summary.describe(),
+            "42 AI lines (claude-3.7, 2 sessions), 17 human lines (12 possibly pasted from AI)"
+        );
+        let summary = CommitSummary {
+            possible_ai_lines: 0,
+            unknown_lines: 3,
+            ..summary
+        };
+        assert_eq!(
+            summary.describe(),
+            "42 AI lines (claude-3.7, 2 sessions), 17 human lines, 3 unknown lines"
+        );
+        let summary = CommitSummary {
+            class_lines: BTreeMap::from([("codegen".to_string(), 1), ("import".to_string(), 8)]),
+            ..summary
+        };
+        assert_eq!(
+            summary.describe(),
+            "42 AI lines (claude-3.7, 2 sessions), 17 human lines, 3 unknown lines, 1 codegen line, 8 import lines"
+        );
+
+        let summary = CommitSummary {
+            ai_lines: 1,
+            human_lines: 1,
+            possible_ai_lines: 0,
+            unknown_lines: 0,
+            class_lines: BTreeMap::new(),
+            models: BTreeSet::from(["unknown".to_string()]),
+            sessions: 1,
+        };
+        assert_eq!(summary.describe(), "1 AI line (1 session), 1 human line");
+
+        assert_eq!(
+            CommitSummary::default().describe(),
+            "0 AI lines, 0 human lines"
+        );
+    }
+
+    #[test]
+    fn test_summary_note() {
+        use crate::authorship::authorship_log::PromptRecord;
+        use crate::authorship::authorship_log_serialization::{AttestationEntry, FileAttestation};
+        use crate::authorship::working_log::AgentId;
+
+        let mut log = AuthorshipLog::new();
+        let agent_id = AgentId {
+            tool: "cursor".to_string(),
+            id: "session_123".to_string(),
+            model: "claude-3.7".to_string(),
+        };
+        let prompt_hash = generate_short_hash(&agent_id.id, &agent_id.tool);
+        log.metadata.prompts.insert(
+            prompt_hash.clone(),
+            PromptRecord {
+                agent_id,
+                human_author: None,
+                human_authors: Vec::new(),
+                messages: vec![],
+                total_additions: 4,
+                total_deletions: 0,
+                accepted_lines: 4,
+                overriden_lines: 0,
+                first_checkpoint_at: None,
+                last_checkpoint_at: None,
+            },
+        );
+        let mut file = FileAttestation::new("src/main.rs".to_string());
+        file.add_entry(AttestationEntry::new(
+            prompt_hash.clone(),
+            vec![LineRange::Range(1, 3), LineRange::Single(7)],
+        ));
+        log.attestations.push(file);
+
+        assert_eq!(
+            summary_note(&log, 4, 2),
+            format!(
+                "git-ai: 4 AI lines (claude-3.7, 1 session), 2 human lines\n\n\
+                 cursor claude-3.7 (session {}): 4 lines\n  src/main.rs: 1-3,7\n",
+                prompt_hash
+            )
+        );
+    }
+}
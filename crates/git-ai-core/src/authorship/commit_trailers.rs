@@ -0,0 +1,113 @@
+use std::collections::HashSet;
+
+use crate::authorship::authorship_log_serialization::generate_short_hash;
+use crate::authorship::working_log::CheckpointKind;
+use crate::error::GitAiError;
+use crate::git::repository::Repository;
+
+/// The `Co-authored-by:` identity of the agents git-ai knows one for
+fn co_author_for_tool(tool: &str) -> Option<&'static str> {
+    match tool {
+        "claude" => Some("Claude <noreply@anthropic.com>"),
+        "cursor" => Some("Cursor Agent <cursoragent@cursor.com>"),
+        "github-copilot" => Some("Copilot <175728472+Copilot@users.noreply.github.com>"),
+        _ => None,
+    }
+}
+
+/// The agent a `Co-authored-by:` identity belongs to, the reverse of `co_author_for_tool`.
+/// Identities are matched by email, since agents have shown up under more than one name.
+pub fn tool_for_co_author(co_author: &str) -> Option<&'static str> {
+    let email = |identity: &str| {
+        identity
+            .split_once('<')
+            .and_then(|(_, rest)| rest.split_once('>'))
+            .map(|(email, _)| email.trim().to_lowercase())
+    };
+    let co_author_email = email(co_author)?;
+    ["claude", "cursor", "github-copilot"]
+        .into_iter()
+        .find(|tool| co_author_for_tool(tool).and_then(email).as_deref() == Some(&co_author_email))
+}
+
+/// The trailers naming the agent sessions that edited `changed_files` (all files when None) on
+/// top of `base_commit`: a `Co-authored-by:` for each agent with a known identity, and a
+/// `Git-AI-Session:` with the prompt id of each session, in the order they first edited
+pub fn commit_trailers(
+    repo: &Repository,
+    base_commit: &str,
+    changed_files: Option<&HashSet<String>>,
+) -> Result<Vec<String>, GitAiError> {
+    let checkpoints = repo
+        .storage
+        .working_log_for_base_commit(base_commit)
+        .read_all_checkpoints()?;
+
+    let mut co_authors = Vec::new();
+    let mut sessions = Vec::new();
+    for checkpoint in &checkpoints {
+        if checkpoint.kind == CheckpointKind::Human {
+            continue;
+        }
+        let Some(agent_id) = checkpoint.agent_id.as_ref() else {
+            continue;
+        };
+        let touches_commit = checkpoint
+            .entries
+            .iter()
+            .any(|entry| changed_files.is_none_or(|files| files.contains(&entry.file)));
+        if !touches_commit {
+            continue;
+        }
+
+        if let Some(co_author) = co_author_for_tool(&agent_id.tool)
+            && !co_authors.contains(&co_author)
+        {
+            co_authors.push(co_author);
+        }
+        let session = generate_short_hash(&agent_id.id, &agent_id.tool);
+        if !sessions.contains(&session) {
+            sessions.push(session);
+        }
+    }
+
+    Ok(co_authors
+        .into_iter()
+        .map(|co_author| format!("Co-authored-by: {}", co_author))
+        .chain(
+            sessions
+                .into_iter()
+                .map(|session| format!("Git-AI-Session: {}", session)),
+        )
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_co_author_for_tool() {
+        assert_eq!(
+            co_author_for_tool("claude"),
+            Some("Claude <noreply@anthropic.com>")
+        );
+        assert!(co_author_for_tool("cursor").is_some());
+        assert!(co_author_for_tool("github-copilot").is_some());
+        assert_eq!(co_author_for_tool("mock_ai"), None);
+    }
+
+    #[test]
+    fn test_tool_for_co_author() {
+        assert_eq!(
+            tool_for_co_author("Claude Sonnet <NoReply@anthropic.com>"),
+            Some("claude")
+        );
+        assert_eq!(
+            tool_for_co_author(co_author_for_tool("github-copilot").unwrap()),
+            Some("github-copilot")
+        );
+        assert_eq!(tool_for_co_author("Jane <jane@example.com>"), None);
+        assert_eq!(tool_for_co_author("Claude"), None);
+    }
+}
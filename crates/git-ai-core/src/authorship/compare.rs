@@ -0,0 +1,302 @@
+//! Contrasts the AI composition of two branches.
+//!
+//! Each side is the work a branch adds on top of a base: `main..feature` when given as a range,
+//! or everything since the merge base of the two branches otherwise. A side's lines are counted
+//! the way `stats` counts a range, squashing its commits into one authorship log, so a line an
+//! AI wrote and a later commit on the branch rewrote is counted once, by its final author.
+
+use crate::authorship::authorship_log::LineRange;
+use crate::authorship::range_authorship::create_authorship_log_for_range;
+use crate::error::GitAiError;
+use crate::git::path_scope::PathScope;
+use crate::git::repository::{Repository, exec_git};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Lines one side adds to a file and how many of them AI wrote
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct FileComposition {
+    pub added_lines: u32,
+    pub ai_lines: u32,
+}
+
+impl FileComposition {
+    pub fn ai_percent(&self) -> f64 {
+        if self.added_lines == 0 {
+            0.0
+        } else {
+            self.ai_lines as f64 * 100.0 / self.added_lines as f64
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BranchComposition {
+    /// The branch (or range) as given
+    pub spec: String,
+    pub base: String,
+    pub head: String,
+    pub commits: usize,
+    pub added_lines: u32,
+    pub ai_lines: u32,
+    pub files: BTreeMap<String, FileComposition>,
+}
+
+impl BranchComposition {
+    pub fn ai_percent(&self) -> f64 {
+        FileComposition {
+            added_lines: self.added_lines,
+            ai_lines: self.ai_lines,
+        }
+        .ai_percent()
+    }
+}
+
+/// A file whose AI share differs between the two sides by at least the threshold
+#[derive(Debug, Clone, Serialize)]
+pub struct FileDifference {
+    pub path: String,
+    /// None when the side doesn't change the file
+    pub a: Option<FileComposition>,
+    pub b: Option<FileComposition>,
+    /// AI share of `a` minus that of `b`, in percentage points
+    pub difference: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BranchComparison {
+    pub a: BranchComposition,
+    pub b: BranchComposition,
+    /// Largest difference first
+    pub differences: Vec<FileDifference>,
+}
+
+/// Compares `a` and `b`, each a branch (or any revision) or a `base..head` range, listing the
+/// files whose AI share differs by at least `threshold` percentage points. A file one side
+/// doesn't change counts as 0% AI on that side.
+pub fn compare_branches(
+    repo: &Repository,
+    a: &str,
+    b: &str,
+    threshold: f64,
+) -> Result<BranchComparison, GitAiError> {
+    let (a_base, a_head) = parse_side(repo, a)?;
+    let (b_base, b_head) = parse_side(repo, b)?;
+    let merge_base = if a_base.is_none() || b_base.is_none() {
+        Some(repo.merge_base(a_head.clone(), b_head.clone())?)
+    } else {
+        None
+    };
+
+    let a = composition(
+        repo,
+        a,
+        a_base.or_else(|| merge_base.clone()).unwrap_or_default(),
+        a_head,
+    )?;
+    let b = composition(repo, b, b_base.or(merge_base).unwrap_or_default(), b_head)?;
+
+    let paths: BTreeSet<&String> = a.files.keys().chain(b.files.keys()).collect();
+    let mut differences: Vec<FileDifference> = paths
+        .into_iter()
+        .filter_map(|path| {
+            let a_file = a.files.get(path).copied();
+            let b_file = b.files.get(path).copied();
+            let difference =
+                a_file.map_or(0.0, |f| f.ai_percent()) - b_file.map_or(0.0, |f| f.ai_percent());
+            (difference.abs() >= threshold && difference != 0.0).then(|| FileDifference {
+                path: path.clone(),
+                a: a_file,
+                b: b_file,
+                difference,
+            })
+        })
+        .collect();
+    differences.sort_by(|x, y| {
+        y.difference
+            .abs()
+            .partial_cmp(&x.difference.abs())
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| x.path.cmp(&y.path))
+    });
+
+    Ok(BranchComparison { a, b, differences })
+}
+
+/// The explicit base (for `base..head`) and the head commit of one side
+fn parse_side(repo: &Repository, spec: &str) -> Result<(Option<String>, String), GitAiError> {
+    if spec.contains("...") {
+        return Err(GitAiError::Generic(format!(
+            "'{}': symmetric ranges aren't supported, use base..head",
+            spec
+        )));
+    }
+    match spec.split_once("..") {
+        Some((base, head)) => Ok((
+            Some(repo.revparse_single(or_head(base))?.id()),
+            repo.revparse_single(or_head(head))?.id(),
+        )),
+        None => Ok((None, repo.revparse_single(spec)?.id())),
+    }
+}
+
+/// `a..` and `..b` leave out HEAD, as in git
+fn or_head(rev: &str) -> &str {
+    if rev.is_empty() { "HEAD" } else { rev }
+}
+
+fn composition(
+    repo: &Repository,
+    spec: &str,
+    base: String,
+    head: String,
+) -> Result<BranchComposition, GitAiError> {
+    let commits: Vec<String> = repo
+        .git(&["rev-list", &format!("{}..{}", base, head)])?
+        .lines()
+        .map(str::to_string)
+        .collect();
+
+    let mut files = added_lines_by_file(repo, &base, &head)?;
+    if !commits.is_empty() {
+        let log = create_authorship_log_for_range(repo, &base, &head, &commits)?;
+        for attestation in &log.attestations {
+            let Some(file) = files.get_mut(&attestation.file_path) else {
+                continue;
+            };
+            let ai_lines: u32 = attestation
+                .entries
+                .iter()
+                .flat_map(|entry| &entry.line_ranges)
+                .map(|range| match range {
+                    LineRange::Single(_) => 1,
+                    LineRange::Range(start, end) => end.saturating_sub(*start) + 1,
+                })
+                .sum();
+            file.ai_lines = ai_lines.min(file.added_lines);
+        }
+    }
+
+    Ok(BranchComposition {
+        spec: spec.to_string(),
+        added_lines: files.values().map(|f| f.added_lines).sum(),
+        ai_lines: files.values().map(|f| f.ai_lines).sum(),
+        commits: commits.len(),
+        base,
+        head,
+        files,
+    })
+}
+
+/// Lines added to each text file between `base` and `head`, within the path scope. Renamed
+/// files are listed under their new name.
+fn added_lines_by_file(
+    repo: &Repository,
+    base: &str,
+    head: &str,
+) -> Result<BTreeMap<String, FileComposition>, GitAiError> {
+    let mut args = repo.global_args_for_exec();
+    args.push("diff".to_string());
+    args.push("--numstat".to_string());
+    args.push("-z".to_string());
+    args.push(base.to_string());
+    args.push(head.to_string());
+    let output = exec_git(&args)?;
+
+    let scope = PathScope::for_repo(repo);
+    let mut files = BTreeMap::new();
+    // Each entry is "added\tdeleted\tpath\0", or "added\tdeleted\t\0old\0new\0" for a rename
+    let mut fields = output.stdout.split(|byte| *byte == 0);
+    while let Some(entry) = fields.next() {
+        let mut parts = entry.splitn(3, |byte| *byte == b'\t');
+        let (Some(added), Some(_deleted), Some(path)) = (parts.next(), parts.next(), parts.next())
+        else {
+            continue;
+        };
+        let path = if path.is_empty() {
+            fields.next();
+            fields.next().unwrap_or_default()
+        } else {
+            path
+        };
+        let path = crate::git::repo_path::key_from_bytes(path);
+        let added = String::from_utf8_lossy(added);
+        // Binary files have "-" counts
+        let Ok(added_lines) = added.parse::<u32>() else {
+            continue;
+        };
+        if added_lines == 0 || !scope.contains(&path) {
+            continue;
+        }
+        files.insert(
+            path,
+            FileComposition {
+                added_lines,
+                ai_lines: 0,
+            },
+        );
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::test_utils::TmpRepo;
+
+    #[test]
+    fn test_compare_branches_highlights_files_with_different_ai_share() {
+        let repo = TmpRepo::new().unwrap();
+        repo.write_file("shared.txt", "base\n", true).unwrap();
+        repo.trigger_checkpoint_with_author("Alice").unwrap();
+        repo.commit_with_message("Base").unwrap();
+        let main = repo.current_branch().unwrap();
+
+        repo.create_branch("agent").unwrap();
+        repo.write_file("shared.txt", "base\nai one\nai two\n", true)
+            .unwrap();
+        repo.write_file("notes.txt", "same\n", true).unwrap();
+        repo.trigger_checkpoint_with_ai("Claude", Some("claude-3-sonnet"), Some("cursor"))
+            .unwrap();
+        repo.commit_with_message("Agent work").unwrap();
+
+        repo.checkout_branch(&main).unwrap();
+        repo.create_branch("by-hand").unwrap();
+        repo.write_file("shared.txt", "base\nhand one\n", true)
+            .unwrap();
+        repo.write_file("notes.txt", "same\n", true).unwrap();
+        repo.trigger_checkpoint_with_author("Alice").unwrap();
+        repo.commit_with_message("Hand work").unwrap();
+
+        let comparison = compare_branches(repo.gitai_repo(), "agent", "by-hand", 20.0).unwrap();
+        assert_eq!(comparison.a.commits, 1);
+        assert_eq!((comparison.a.ai_lines, comparison.a.added_lines), (3, 3));
+        assert_eq!((comparison.b.ai_lines, comparison.b.added_lines), (0, 2));
+
+        let paths: Vec<&str> = comparison
+            .differences
+            .iter()
+            .map(|d| d.path.as_str())
+            .collect();
+        assert_eq!(paths, vec!["notes.txt", "shared.txt"]);
+        assert_eq!(comparison.differences[1].difference, 100.0);
+        assert_eq!(
+            comparison.differences[1].b,
+            Some(FileComposition {
+                added_lines: 1,
+                ai_lines: 0
+            })
+        );
+
+        // With an explicit base, the same branch compared to itself shows no differences
+        let same = compare_branches(
+            repo.gitai_repo(),
+            &format!("{}..agent", main),
+            &format!("{}..agent", main),
+            0.0,
+        )
+        .unwrap();
+        assert!(same.differences.is_empty());
+        assert!(compare_branches(repo.gitai_repo(), "agent...by-hand", "agent", 20.0).is_err());
+    }
+}
@@ -0,0 +1,258 @@
+//! Annotates `git show` / `git log -p` output with the attribution recorded in authorship notes.
+//!
+//! The output is read a line at a time, so `git log -p` over a long history streams into the
+//! pager as usual. `commit <sha>` headers select the note, `+++ b/<path>` the file and
+//! `@@ -a,b +c,d @@` the line numbers, which are the file's lines in that commit, as the note's
+//! are. Added lines the note gives to AI are recolored, and each hunk that adds lines gets a
+//! trailer saying how many of them AI wrote. Commits without a note pass through unchanged.
+
+use crate::authorship::authorship_log::LineRange;
+use crate::authorship::authorship_log_serialization::AuthorshipLog;
+use crate::git::refs::get_authorship;
+use crate::git::repo_path;
+use crate::git::repository::{Repository, parse_hunk_header};
+use std::collections::BTreeSet;
+
+/// Color of added lines written by AI (git colors added lines green)
+const AI_LINE_COLOR: &str = "\x1b[35m";
+const TRAILER_COLOR: &str = "\x1b[90m";
+const RESET: &str = "\x1b[m";
+
+pub struct DiffAnnotator<'a> {
+    repo: &'a Repository,
+    color: bool,
+    /// Note of the commit whose diff is being read
+    log: Option<AuthorshipLog>,
+    /// AI line ranges of the file being read, with the tool and model of each
+    ai_ranges: Vec<(LineRange, String)>,
+    hunk: Option<Hunk>,
+}
+
+#[derive(Default)]
+struct Hunk {
+    old_remaining: u32,
+    new_remaining: u32,
+    /// Line number in the new file of the next context or added line
+    next_line: u32,
+    ai_added: u32,
+    human_added: u32,
+    agents: BTreeSet<String>,
+}
+
+impl Hunk {
+    fn is_complete(&self) -> bool {
+        self.old_remaining == 0 && self.new_remaining == 0
+    }
+}
+
+impl<'a> DiffAnnotator<'a> {
+    /// `color` recolors AI lines and dims the trailers; the input may be colored by git or not
+    pub fn new(repo: &'a Repository, color: bool) -> Self {
+        DiffAnnotator {
+            repo,
+            color,
+            log: None,
+            ai_ranges: Vec::new(),
+            hunk: None,
+        }
+    }
+
+    /// Takes one line of git's output (without its newline) and returns the lines to print in
+    /// its place
+    pub fn line(&mut self, line: &str) -> Vec<String> {
+        let plain = strip_ansi(line);
+        let mut out = Vec::new();
+
+        if self.hunk.as_ref().is_some_and(Hunk::is_complete) {
+            // "\ No newline at end of file" still belongs to the hunk
+            if plain.starts_with('\\') {
+                out.push(line.to_string());
+                return out;
+            }
+            out.extend(self.finish());
+        }
+
+        if let Some(hunk) = self.hunk.as_mut() {
+            match plain.chars().next() {
+                Some('+') => {
+                    let ai = self
+                        .ai_ranges
+                        .iter()
+                        .find(|(range, _)| range.contains(hunk.next_line));
+                    hunk.next_line += 1;
+                    hunk.new_remaining = hunk.new_remaining.saturating_sub(1);
+                    match ai {
+                        Some((_, agent)) => {
+                            hunk.ai_added += 1;
+                            hunk.agents.insert(agent.clone());
+                            if self.color {
+                                out.push(format!("{}{}{}", AI_LINE_COLOR, plain, RESET));
+                                return out;
+                            }
+                        }
+                        None => hunk.human_added += 1,
+                    }
+                }
+                Some('-') => hunk.old_remaining = hunk.old_remaining.saturating_sub(1),
+                Some('\\') => {}
+                _ => {
+                    hunk.next_line += 1;
+                    hunk.old_remaining = hunk.old_remaining.saturating_sub(1);
+                    hunk.new_remaining = hunk.new_remaining.saturating_sub(1);
+                }
+            }
+            out.push(line.to_string());
+            return out;
+        }
+
+        if let Some(sha) = plain.strip_prefix("commit ") {
+            let sha = sha.split_whitespace().next().unwrap_or_default();
+            self.log = get_authorship(self.repo, sha);
+            self.ai_ranges.clear();
+        } else if plain.starts_with("diff --git ") {
+            self.ai_ranges.clear();
+        } else if let Some(header) = plain.strip_prefix("+++ ") {
+            self.ai_ranges = match repo_path::key_from_diff_header(header.as_bytes()) {
+                Some(path) => self.ai_ranges_for(&path),
+                None => Vec::new(),
+            };
+        } else if let Some((_, old_count, new_start, new_count)) = parse_hunk_header(&plain) {
+            self.hunk = Some(Hunk {
+                old_remaining: old_count,
+                new_remaining: new_count,
+                next_line: new_start,
+                ..Hunk::default()
+            });
+        }
+        out.push(line.to_string());
+        out
+    }
+
+    /// Ends the hunk being read, returning its trailer if it has one. Call once more at the end
+    /// of the output.
+    pub fn finish(&mut self) -> Vec<String> {
+        let Some(hunk) = self.hunk.take() else {
+            return Vec::new();
+        };
+        let added = hunk.ai_added + hunk.human_added;
+        if self.log.is_none() || added == 0 {
+            return Vec::new();
+        }
+        let mut trailer = format!("~ AI wrote {} of {} added lines", hunk.ai_added, added);
+        if !hunk.agents.is_empty() {
+            let agents: Vec<&str> = hunk.agents.iter().map(String::as_str).collect();
+            trailer.push_str(&format!(" ({})", agents.join(", ")));
+        }
+        if self.color {
+            trailer = format!("{}{}{}", TRAILER_COLOR, trailer, RESET);
+        }
+        vec![trailer]
+    }
+
+    fn ai_ranges_for(&self, path: &str) -> Vec<(LineRange, String)> {
+        let Some(log) = self.log.as_ref() else {
+            return Vec::new();
+        };
+        let Some(attestation) = log.attestations.iter().find(|a| a.file_path == path) else {
+            return Vec::new();
+        };
+        attestation
+            .entries
+            .iter()
+            .flat_map(|entry| {
+                let agent = log
+                    .metadata
+                    .prompts
+                    .get(&entry.hash)
+                    .map(|prompt| format!("{} {}", prompt.agent_id.tool, prompt.agent_id.model))
+                    .unwrap_or_else(|| "ai".to_string());
+                entry
+                    .line_ranges
+                    .iter()
+                    .map(move |range| (range.clone(), agent.clone()))
+            })
+            .collect()
+    }
+}
+
+/// Annotates a whole diff at once
+pub fn annotate_diff(repo: &Repository, diff: &str, color: bool) -> String {
+    let mut annotator = DiffAnnotator::new(repo, color);
+    let mut out = Vec::new();
+    for line in diff.lines() {
+        out.extend(annotator.line(line));
+    }
+    out.extend(annotator.finish());
+    out.join("\n")
+}
+
+/// Removes SGR color sequences
+fn strip_ansi(line: &str) -> String {
+    let mut plain = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            plain.push(c);
+        }
+    }
+    plain
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::test_utils::TmpRepo;
+
+    #[test]
+    fn test_annotate_diff_marks_ai_lines_and_summarizes_hunks() {
+        let repo = TmpRepo::new().unwrap();
+        repo.write_file("app.txt", "one\ntwo\n", true).unwrap();
+        repo.trigger_checkpoint_with_author("Alice").unwrap();
+        repo.commit_with_message("Human start").unwrap();
+
+        repo.write_file("app.txt", "one\nai one\nai two\ntwo\n", true)
+            .unwrap();
+        repo.trigger_checkpoint_with_ai("Claude", Some("claude-3-sonnet"), Some("cursor"))
+            .unwrap();
+        repo.write_file("app.txt", "one\nai one\nai two\ntwo\nhuman\n", true)
+            .unwrap();
+        repo.trigger_checkpoint_with_author("Alice").unwrap();
+        repo.commit_with_message("Mixed").unwrap();
+
+        let diff = repo
+            .gitai_repo()
+            .git(&["log", "-p", "-U0", "--color=always"])
+            .unwrap();
+        let annotated = annotate_diff(repo.gitai_repo(), &diff, true);
+        let plain = strip_ansi(&annotated);
+
+        assert!(annotated.contains(&format!("{}+ai one{}", AI_LINE_COLOR, RESET)));
+        assert!(!annotated.contains(&format!("{}+human", AI_LINE_COLOR)));
+        let trailers: Vec<&str> = plain.lines().filter(|l| l.starts_with("~ ")).collect();
+        assert_eq!(
+            trailers,
+            vec![
+                "~ AI wrote 2 of 2 added lines (cursor claude-3-sonnet)",
+                "~ AI wrote 0 of 1 added lines",
+                "~ AI wrote 0 of 2 added lines",
+            ]
+        );
+
+        // Without color only the trailers are added
+        let uncolored = repo.gitai_repo().git(&["show", "-U0"]).unwrap();
+        let annotated = annotate_diff(repo.gitai_repo(), &uncolored, false);
+        assert_eq!(
+            annotated
+                .lines()
+                .filter(|l| !l.starts_with("~ "))
+                .collect::<Vec<_>>(),
+            uncolored.lines().collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,80 @@
+//! File content as the text attribution works on.
+//!
+//! Attribution diffs lines of text, but files are bytes and not all of them UTF-8. Replacing
+//! the bytes that aren't (as `from_utf8_lossy` does) makes different content compare equal and
+//! shifts columns. Instead, each line that is valid UTF-8 is kept as it is, and each line that
+//! isn't (latin-1, Windows-1252, a stray byte in an otherwise UTF-8 file) has every byte mapped
+//! to the character with that code point. Lines and columns stay where they are in the file,
+//! and a line decodes the same way whatever the rest of the file holds, so an edit elsewhere
+//! doesn't make unchanged lines look rewritten. Content with NUL bytes is binary and isn't
+//! attributed.
+
+/// The text of a file's content
+pub fn decode(bytes: &[u8]) -> String {
+    if let Ok(text) = std::str::from_utf8(bytes) {
+        return text.to_string();
+    }
+    let mut text = String::with_capacity(bytes.len());
+    for line in bytes.split_inclusive(|byte| *byte == b'\n') {
+        match std::str::from_utf8(line) {
+            Ok(line) => text.push_str(line),
+            Err(_) => text.extend(line.iter().map(|byte| char::from(*byte))),
+        }
+    }
+    text
+}
+
+/// The text of a file's content, or `None` if it's binary
+pub fn decode_text(bytes: &[u8]) -> Option<String> {
+    (!is_binary(bytes)).then(|| decode(bytes))
+}
+
+/// Returns true if content is binary rather than text, by the NUL byte test git uses
+pub fn is_binary(bytes: &[u8]) -> bool {
+    bytes.contains(&0)
+}
+
+/// The text of the file at `path`, empty if it can't be read
+pub fn read(path: &std::path::Path) -> String {
+    std::fs::read(path)
+        .map(|bytes| decode(&bytes))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_utf8_is_unchanged() {
+        assert_eq!(decode("café\nnaïve\n".as_bytes()), "café\nnaïve\n");
+    }
+
+    #[test]
+    fn test_other_encodings_keep_lines_and_columns() {
+        // "café" in latin-1 on the second line
+        let text = decode(b"fn a() {}\nlet s = \"caf\xe9\";\nfn b() {}\n");
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines, ["fn a() {}", "let s = \"caf\u{e9}\";", "fn b() {}"]);
+        assert_eq!(lines[1].chars().count(), b"let s = \"caf\xe9\";".len());
+    }
+
+    #[test]
+    fn test_lines_decode_independently() {
+        // A UTF-8 line decodes the same next to a latin-1 one as it does on its own
+        let mixed = decode(&[&b"na\xc3\xafve\n"[..], b"lat\xedn\n"].concat());
+        assert_eq!(mixed, "naïve\nlat\u{ed}n\n");
+    }
+
+    #[test]
+    fn test_distinct_content_stays_distinct() {
+        // Lossy decoding turns both into "caf\u{fffd}"
+        assert_ne!(decode(b"caf\xe9"), decode(b"caf\xe8"));
+    }
+
+    #[test]
+    fn test_binary_content_isnt_text() {
+        assert_eq!(decode_text(b"\x89PNG\r\n\x1a\n\0\0"), None);
+        assert_eq!(decode_text(b"plain\n"), Some("plain\n".to_string()));
+    }
+}
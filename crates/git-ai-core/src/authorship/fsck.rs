@@ -0,0 +1,387 @@
+//! Consistency checks for authorship notes, with safe automatic repairs.
+//!
+//! Repairs only remove attribution that can't be right (lines past the end of the file, files
+//! the commit doesn't contain) or restore prompts from other notes; they never invent
+//! attribution. Before the first note is rewritten, refs/notes/ai is copied to a backup ref so
+//! a repair can be undone with `git-ai undo-notes`.
+
+use crate::authorship::authorship_log::LineRange;
+use crate::authorship::authorship_log_serialization::AuthorshipLog;
+use crate::authorship::virtual_attribution::VirtualAttributions;
+use crate::error::GitAiError;
+use crate::git::refs::{backup_ai_notes, list_ai_notes, notes_add, show_authorship_note};
+use crate::git::repository::Repository;
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FsckIssue {
+    /// The note isn't a readable authorship log. Never repaired automatically.
+    Unparseable { message: String },
+    /// The file the attestation is for doesn't exist in the commit
+    MissingFile { file: String },
+    /// An entry attributes lines past the end of the file (or line 0)
+    LineOutOfRange {
+        file: String,
+        hash: String,
+        line: u32,
+        line_count: u32,
+    },
+    /// An entry references a prompt the note's metadata doesn't contain
+    MissingPrompt { hash: String },
+}
+
+impl fmt::Display for FsckIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FsckIssue::Unparseable { message } => write!(f, "unparseable note: {}", message),
+            FsckIssue::MissingFile { file } => {
+                write!(f, "{}: file does not exist in the commit", file)
+            }
+            FsckIssue::LineOutOfRange {
+                file,
+                hash,
+                line,
+                line_count,
+            } => write!(
+                f,
+                "{}: prompt {} attributes line {} but the file has {} lines",
+                file, hash, line, line_count
+            ),
+            FsckIssue::MissingPrompt { hash } => {
+                write!(f, "prompt {} is referenced but not recorded", hash)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FsckFinding {
+    pub commit: String,
+    pub issue: FsckIssue,
+    /// Whether `--repair` fixed the issue
+    pub repaired: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FsckReport {
+    pub commits_checked: usize,
+    pub findings: Vec<FsckFinding>,
+    /// Ref holding refs/notes/ai as it was before any note was rewritten
+    pub backup_ref: Option<String>,
+}
+
+impl FsckReport {
+    pub fn unresolved(&self) -> usize {
+        self.findings.iter().filter(|f| !f.repaired).count()
+    }
+}
+
+/// Checks the note of every commit in refs/notes/ai and, with `repair`, rewrites the notes
+/// whose issues can be fixed safely
+pub fn fsck(repo: &Repository, repair: bool) -> Result<FsckReport, GitAiError> {
+    let mut report = FsckReport::default();
+
+    for sha in list_ai_notes(repo)? {
+        report.commits_checked += 1;
+        let Some(content) = show_authorship_note(repo, &sha) else {
+            continue;
+        };
+        let mut log = match AuthorshipLog::deserialize_from_string(&content) {
+            Ok(log) => log,
+            Err(e) => {
+                report.findings.push(FsckFinding {
+                    commit: sha,
+                    issue: FsckIssue::Unparseable {
+                        message: e.to_string(),
+                    },
+                    repaired: false,
+                });
+                continue;
+            }
+        };
+
+        let issues = check_log(repo, &sha, &log);
+        if issues.is_empty() {
+            continue;
+        }
+        let repaired = if repair {
+            repair_log(repo, &mut log, &issues)
+        } else {
+            vec![false; issues.len()]
+        };
+
+        if repaired.contains(&true) {
+            if report.backup_ref.is_none() {
+                report.backup_ref = backup_ai_notes(repo)?;
+            }
+            let serialized = log.serialize_to_string().map_err(|_| {
+                GitAiError::Generic("Failed to serialize authorship log".to_string())
+            })?;
+            notes_add(repo, &sha, &serialized)?;
+        }
+        for (issue, repaired) in issues.into_iter().zip(repaired) {
+            report.findings.push(FsckFinding {
+                commit: sha.clone(),
+                issue,
+                repaired,
+            });
+        }
+    }
+
+    Ok(report)
+}
+
+/// Issues in the authorship log of `commit`
+pub fn check_log(repo: &Repository, commit: &str, log: &AuthorshipLog) -> Vec<FsckIssue> {
+    let mut issues = Vec::new();
+    let mut missing_prompts = BTreeSet::new();
+
+    for attestation in &log.attestations {
+        let Ok(content) = repo.get_file_content(&attestation.file_path, commit) else {
+            issues.push(FsckIssue::MissingFile {
+                file: attestation.file_path.clone(),
+            });
+            continue;
+        };
+        let line_count = count_lines(&content);
+        for entry in &attestation.entries {
+            let out_of_range = entry
+                .line_ranges
+                .iter()
+                .chain(&entry.overridden_lines)
+                .flat_map(|range| match range {
+                    LineRange::Single(line) => [*line, *line],
+                    LineRange::Range(start, end) => [*start, *end],
+                })
+                .filter(|line| *line == 0 || *line > line_count)
+                .max();
+            if let Some(line) = out_of_range {
+                issues.push(FsckIssue::LineOutOfRange {
+                    file: attestation.file_path.clone(),
+                    hash: entry.hash.clone(),
+                    line,
+                    line_count,
+                });
+            }
+        }
+    }
+
+    for attestation in &log.attestations {
+        for entry in &attestation.entries {
+            if !log.metadata.prompts.contains_key(&entry.hash) {
+                missing_prompts.insert(entry.hash.clone());
+            }
+        }
+    }
+    issues.extend(
+        missing_prompts
+            .into_iter()
+            .map(|hash| FsckIssue::MissingPrompt { hash }),
+    );
+    issues
+}
+
+/// Fixes what it safely can in `log`, returning whether each of `issues` was fixed
+pub fn repair_log(repo: &Repository, log: &mut AuthorshipLog, issues: &[FsckIssue]) -> Vec<bool> {
+    issues
+        .iter()
+        .map(|issue| match issue {
+            FsckIssue::Unparseable { .. } => false,
+            FsckIssue::MissingFile { file } => {
+                log.attestations
+                    .retain(|attestation| &attestation.file_path != file);
+                true
+            }
+            FsckIssue::LineOutOfRange {
+                file,
+                hash,
+                line_count,
+                ..
+            } => {
+                let Some(attestation) = log
+                    .attestations
+                    .iter_mut()
+                    .find(|attestation| &attestation.file_path == file)
+                else {
+                    return false;
+                };
+                for entry in attestation
+                    .entries
+                    .iter_mut()
+                    .filter(|entry| &entry.hash == hash)
+                {
+                    entry.line_ranges = clamp_line_ranges(&entry.line_ranges, *line_count);
+                    entry.overridden_lines =
+                        clamp_line_ranges(&entry.overridden_lines, *line_count);
+                    entry
+                        .column_ranges
+                        .retain(|range| range.line >= 1 && range.line <= *line_count);
+                }
+                attestation
+                    .entries
+                    .retain(|entry| !entry.line_ranges.is_empty());
+                log.attestations
+                    .retain(|attestation| !attestation.entries.is_empty());
+                true
+            }
+            FsckIssue::MissingPrompt { hash } => {
+                match VirtualAttributions::find_prompt_in_history_static(repo, hash) {
+                    Ok((_, prompt)) => {
+                        log.metadata.prompts.insert(hash.clone(), prompt);
+                        true
+                    }
+                    Err(_) => false,
+                }
+            }
+        })
+        .collect()
+}
+
+/// `ranges` cut down to lines 1..=line_count
+fn clamp_line_ranges(ranges: &[LineRange], line_count: u32) -> Vec<LineRange> {
+    ranges
+        .iter()
+        .filter_map(|range| {
+            let (start, end) = match range {
+                LineRange::Single(line) => (*line, *line),
+                LineRange::Range(start, end) => (*start, *end),
+            };
+            let (start, end) = (start.max(1), end.min(line_count));
+            if start > end {
+                None
+            } else if start == end {
+                Some(LineRange::Single(start))
+            } else {
+                Some(LineRange::Range(start, end))
+            }
+        })
+        .collect()
+}
+
+fn count_lines(content: &[u8]) -> u32 {
+    let newlines = content.iter().filter(|byte| **byte == b'\n').count() as u32;
+    if content.last().is_some_and(|byte| *byte != b'\n') {
+        newlines + 1
+    } else {
+        newlines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authorship::authorship_log_serialization::AttestationEntry;
+    use crate::git::refs::{AI_NOTES_BACKUP_PREFIX, get_authorship};
+    use crate::git::test_utils::TmpRepo;
+
+    #[test]
+    fn test_clamp_line_ranges() {
+        let ranges = vec![
+            LineRange::Single(0),
+            LineRange::Range(2, 3),
+            LineRange::Range(4, 9),
+            LineRange::Range(5, 6),
+            LineRange::Single(6),
+        ];
+        assert_eq!(
+            clamp_line_ranges(&ranges, 4),
+            vec![LineRange::Range(2, 3), LineRange::Single(4)]
+        );
+    }
+
+    #[test]
+    fn test_fsck_repairs_corrupted_note_and_keeps_backup() {
+        let tmp_repo = TmpRepo::new().unwrap();
+        tmp_repo.write_file("a.txt", "one\n", true).unwrap();
+        tmp_repo
+            .trigger_checkpoint_with_ai("Claude", Some("claude-3-sonnet"), Some("cursor"))
+            .unwrap();
+        tmp_repo.commit_with_message("first").unwrap();
+        tmp_repo.write_file("a.txt", "one\ntwo\n", true).unwrap();
+        tmp_repo
+            .trigger_checkpoint_with_ai("Claude", Some("claude-3-sonnet"), Some("cursor"))
+            .unwrap();
+        let original = tmp_repo.commit_with_message("second").unwrap();
+        let sha = tmp_repo.get_head_commit_sha().unwrap();
+        let repo = tmp_repo.gitai_repo();
+        let hash = original.attestations[0].entries[0].hash.clone();
+
+        assert!(fsck(repo, false).unwrap().findings.is_empty());
+
+        // Attribute lines past the end of a.txt, attest a file that doesn't exist and drop
+        // the prompt (which the first commit's note still holds)
+        let mut corrupted = original.clone();
+        corrupted.attestations[0].entries[0].line_ranges = vec![LineRange::Range(2, 5)];
+        corrupted
+            .get_or_create_file("gone.txt")
+            .add_entry(AttestationEntry::new(
+                hash.clone(),
+                vec![LineRange::Single(1)],
+            ));
+        let prompt = corrupted.metadata.prompts.remove(&hash).unwrap();
+        notes_add(repo, &sha, &corrupted.serialize_to_string().unwrap()).unwrap();
+
+        let report = fsck(repo, false).unwrap();
+        let issues: Vec<_> = report.findings.iter().map(|f| f.issue.clone()).collect();
+        assert_eq!(
+            issues,
+            vec![
+                FsckIssue::LineOutOfRange {
+                    file: "a.txt".to_string(),
+                    hash: hash.clone(),
+                    line: 5,
+                    line_count: 2,
+                },
+                FsckIssue::MissingFile {
+                    file: "gone.txt".to_string(),
+                },
+                FsckIssue::MissingPrompt { hash: hash.clone() },
+            ]
+        );
+        assert_eq!(report.unresolved(), 3);
+        assert!(report.backup_ref.is_none());
+
+        let report = fsck(repo, true).unwrap();
+        assert_eq!(report.unresolved(), 0);
+        let backup_ref = report.backup_ref.unwrap();
+        assert!(backup_ref.starts_with(AI_NOTES_BACKUP_PREFIX));
+
+        let repaired = get_authorship(repo, &sha).unwrap();
+        assert_eq!(repaired.attestations.len(), 1);
+        assert_eq!(
+            repaired.attestations[0].entries[0].line_ranges,
+            vec![LineRange::Single(2)]
+        );
+        // Recovered from the first commit's note, so only the identity is guaranteed to match
+        assert_eq!(repaired.metadata.prompts[&hash].agent_id, prompt.agent_id);
+        assert!(fsck(repo, false).unwrap().findings.is_empty());
+
+        // The backup still holds the corrupted note
+        let backed_up = repo
+            .git(&["notes", &format!("--ref={}", backup_ref), "show", &sha])
+            .unwrap();
+        let backed_up = AuthorshipLog::deserialize_from_string(backed_up.trim()).unwrap();
+        assert!(!backed_up.metadata.prompts.contains_key(&hash));
+    }
+
+    #[test]
+    fn test_fsck_reports_unparseable_note_without_repairing() {
+        let tmp_repo = TmpRepo::new().unwrap();
+        tmp_repo.write_file("a.txt", "one\n", true).unwrap();
+        tmp_repo.commit_with_message("first").unwrap();
+        let sha = tmp_repo.get_head_commit_sha().unwrap();
+        notes_add(tmp_repo.gitai_repo(), &sha, "not an authorship log").unwrap();
+
+        let report = fsck(tmp_repo.gitai_repo(), true).unwrap();
+        assert_eq!(report.findings.len(), 1);
+        assert!(matches!(
+            report.findings[0].issue,
+            FsckIssue::Unparseable { .. }
+        ));
+        assert!(!report.findings[0].repaired);
+        assert!(report.backup_ref.is_none());
+    }
+}
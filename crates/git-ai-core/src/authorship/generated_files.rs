@@ -0,0 +1,205 @@
+//! Detection of generated and vendored files
+//!
+//! Lockfiles, codegen output and vendored dependencies are excluded from checkpoints
+//! and stats so they don't dominate AI authorship numbers. A path is considered
+//! generated when the `linguist-generated` or `linguist-vendored` gitattribute is set
+//! for it, or when it matches one of the built-in default patterns. Explicitly unsetting
+//! either attribute (`-linguist-generated`, `linguist-vendored=false`) opts a path back in.
+
+use crate::error::GitAiError;
+use crate::git::repo_path;
+use crate::git::repository::{Repository, exec_git_stdin};
+use glob::{MatchOptions, Pattern};
+use std::collections::HashMap;
+
+/// Attributes GitHub Linguist uses to mark generated and vendored content
+const LINGUIST_ATTRIBUTES: &[&str] = &["linguist-generated", "linguist-vendored"];
+
+/// Paths treated as generated when no gitattribute says otherwise
+const DEFAULT_GENERATED_PATTERNS: &[&str] = &[
+    // Lockfiles
+    "**/Cargo.lock",
+    "**/package-lock.json",
+    "**/npm-shrinkwrap.json",
+    "**/yarn.lock",
+    "**/pnpm-lock.yaml",
+    "**/bun.lockb",
+    "**/poetry.lock",
+    "**/Pipfile.lock",
+    "**/uv.lock",
+    "**/Gemfile.lock",
+    "**/composer.lock",
+    "**/go.sum",
+    "**/flake.lock",
+    // Vendored dependencies
+    "**/vendor/**",
+    "**/node_modules/**",
+    "**/third_party/**",
+    // Codegen and build output
+    "**/*.min.js",
+    "**/*.min.css",
+    "**/*.map",
+    "**/*.pb.go",
+    "**/*_pb2.py",
+    "**/*_pb2_grpc.py",
+    "**/*.generated.*",
+    "**/*.g.dart",
+];
+
+fn match_options() -> MatchOptions {
+    MatchOptions {
+        case_sensitive: true,
+        require_literal_separator: true,
+        require_literal_leading_dot: false,
+    }
+}
+
+/// Returns true if the path matches one of the built-in generated-file patterns
+pub fn matches_default_generated_pattern(path: &str) -> bool {
+    // Anchor the path so "**/" prefixes also match top-level files
+    let anchored = format!("/{}", path.trim_start_matches('/'));
+    DEFAULT_GENERATED_PATTERNS.iter().any(|pattern| {
+        Pattern::new(&format!("/{}", pattern))
+            .map(|p| p.matches_with(&anchored, match_options()))
+            .unwrap_or(false)
+    })
+}
+
+/// Value of a linguist attribute as reported by `git check-attr`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AttributeState {
+    Set,
+    Unset,
+    Unspecified,
+}
+
+impl AttributeState {
+    fn parse(value: &str) -> Self {
+        match value {
+            "set" | "true" => AttributeState::Set,
+            "unset" | "false" => AttributeState::Unset,
+            _ => AttributeState::Unspecified,
+        }
+    }
+}
+
+/// Decides which repository paths count as generated or vendored
+#[derive(Debug, Clone, Default)]
+pub struct GeneratedFileFilter {
+    /// Per-path linguist attribute state (None when no attribute applies)
+    attribute_states: HashMap<String, Option<bool>>,
+}
+
+impl GeneratedFileFilter {
+    /// Build a filter for the given paths, resolving their gitattributes in a single
+    /// `git check-attr` call.
+    pub fn for_paths<'a, I>(repo: &Repository, paths: I) -> Result<Self, GitAiError>
+    where
+        I: IntoIterator<Item = &'a String>,
+    {
+        let mut stdin_data = Vec::new();
+        let mut count = 0usize;
+        for path in paths {
+            stdin_data.extend(repo_path::key_to_bytes(path));
+            stdin_data.push(0);
+            count += 1;
+        }
+
+        if count == 0 {
+            return Ok(Self::default());
+        }
+
+        let mut args = repo.global_args_for_exec();
+        args.push("check-attr".to_string());
+        args.push("-z".to_string());
+        args.push("--stdin".to_string());
+        args.extend(LINGUIST_ATTRIBUTES.iter().map(|s| s.to_string()));
+
+        let output = exec_git_stdin(&args, &stdin_data)?;
+        Ok(Self::from_check_attr_output(&output.stdout))
+    }
+
+    /// Parse `git check-attr -z` output (`<path> NUL <attribute> NUL <value> NUL` records)
+    fn from_check_attr_output(output: &[u8]) -> Self {
+        let mut attribute_states: HashMap<String, Option<bool>> = HashMap::new();
+        let fields = repo_path::split_nul_fields(output);
+
+        for record in fields.chunks(3) {
+            if record.len() < 3 || record[0].is_empty() {
+                continue;
+            }
+            let state = attribute_states.entry(record[0].to_string()).or_default();
+            match AttributeState::parse(&record[2]) {
+                AttributeState::Set => *state = Some(true),
+                // A set attribute wins over an unset one for the other linguist attribute
+                AttributeState::Unset => {
+                    if state.is_none() {
+                        *state = Some(false);
+                    }
+                }
+                AttributeState::Unspecified => {}
+            }
+        }
+
+        GeneratedFileFilter { attribute_states }
+    }
+
+    /// Returns true if the path should be treated as generated or vendored
+    pub fn is_generated(&self, path: &str) -> bool {
+        match self.attribute_states.get(path).copied().flatten() {
+            Some(explicit) => explicit,
+            None => matches_default_generated_pattern(path),
+        }
+    }
+}
+
+/// Remove generated and vendored paths from a list of repository-relative paths.
+/// Falls back to the default patterns alone if gitattributes can't be resolved.
+pub fn filter_generated_paths(repo: &Repository, paths: Vec<String>) -> Vec<String> {
+    let filter = GeneratedFileFilter::for_paths(repo, paths.iter()).unwrap_or_default();
+    paths
+        .into_iter()
+        .filter(|path| !filter.is_generated(path))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_patterns() {
+        assert!(matches_default_generated_pattern("Cargo.lock"));
+        assert!(matches_default_generated_pattern("web/package-lock.json"));
+        assert!(matches_default_generated_pattern(
+            "vendor/github.com/pkg/errors/errors.go"
+        ));
+        assert!(matches_default_generated_pattern("static/app.min.js"));
+        assert!(matches_default_generated_pattern("api/service.pb.go"));
+        assert!(!matches_default_generated_pattern("src/main.rs"));
+        assert!(!matches_default_generated_pattern("src/vendored.rs"));
+        assert!(!matches_default_generated_pattern("docs/lockfiles.md"));
+    }
+
+    #[test]
+    fn test_check_attr_output_overrides_defaults() {
+        let output = "gen/schema.rs\0linguist-generated\0set\0gen/schema.rs\0linguist-vendored\0unspecified\0\
+                      Cargo.lock\0linguist-generated\0unset\0Cargo.lock\0linguist-vendored\0unspecified\0\
+                      src/lib.rs\0linguist-generated\0unspecified\0src/lib.rs\0linguist-vendored\0unspecified\0\
+                      yarn.lock\0linguist-generated\0unspecified\0yarn.lock\0linguist-vendored\0unspecified\0";
+        let filter = GeneratedFileFilter::from_check_attr_output(output.as_bytes());
+
+        assert!(filter.is_generated("gen/schema.rs"));
+        assert!(!filter.is_generated("Cargo.lock"));
+        assert!(!filter.is_generated("src/lib.rs"));
+        assert!(filter.is_generated("yarn.lock"));
+    }
+
+    #[test]
+    fn test_set_attribute_wins_over_unset() {
+        let output = "lib/x.js\0linguist-generated\0false\0lib/x.js\0linguist-vendored\0true\0";
+        let filter = GeneratedFileFilter::from_check_attr_output(output.as_bytes());
+
+        assert!(filter.is_generated("lib/x.js"));
+    }
+}
@@ -0,0 +1,666 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
+
+use crate::authorship::authorship_log::{LineRange, PromptRecord};
+use crate::authorship::authorship_log_serialization::{
+    AttestationEntry, AuthorshipLog, FileAttestation, generate_short_hash,
+};
+use crate::authorship::commit_summary::CommitSummary;
+use crate::authorship::file_text;
+use crate::authorship::transcript::{AiTranscript, Message};
+use crate::authorship::working_log::AgentId;
+use crate::commands::checkpoint_agent::agent_presets::CursorPreset;
+use crate::error::GitAiError;
+use crate::git::path_scope::PathScope;
+use crate::git::refs::{get_authorship, notes_add};
+use crate::git::repository::Repository;
+
+pub(crate) const EMPTY_TREE: &str = "4b825dc642cb6eb9a060e54bf8d69288fbee4904";
+
+/// Trimmed line -> (timestamp, session) of every edit that wrote it
+type WrittenLines<'a> = HashMap<&'a str, Vec<(i64, usize)>>;
+
+/// An agent whose local history can be imported
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistorySource {
+    Cursor,
+    ClaudeCode,
+    Aider,
+}
+
+impl HistorySource {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "cursor" => Some(HistorySource::Cursor),
+            "claude-code" | "claude" => Some(HistorySource::ClaudeCode),
+            "aider" => Some(HistorySource::Aider),
+            _ => None,
+        }
+    }
+
+    /// Where the agent keeps its history by default: Cursor's global state database, Claude Code's
+    /// projects directory, or aider's chat history in the repository
+    pub fn default_location(&self, workdir: &Path) -> Result<PathBuf, GitAiError> {
+        match self {
+            HistorySource::Cursor => Ok(CursorPreset::cursor_user_dir()?
+                .join("globalStorage")
+                .join("state.vscdb")),
+            HistorySource::ClaudeCode => dirs::home_dir()
+                .map(|home| home.join(".claude").join("projects"))
+                .ok_or_else(|| GitAiError::Generic("Could not find home directory".to_string())),
+            HistorySource::Aider => Ok(workdir.join(".aider.chat.history.md")),
+        }
+    }
+
+    /// The sessions at `location` that edited files in `workdir`
+    pub fn read_sessions(
+        &self,
+        location: &Path,
+        workdir: &Path,
+    ) -> Result<Vec<ImportedSession>, GitAiError> {
+        if !location.exists() {
+            return Err(GitAiError::Generic(format!(
+                "No history found at {}",
+                location.display()
+            )));
+        }
+        match self {
+            HistorySource::Cursor => cursor_sessions(location, workdir),
+            HistorySource::ClaudeCode => claude_code_sessions(location, workdir),
+            HistorySource::Aider => aider_sessions(location),
+        }
+    }
+}
+
+/// Lines an agent wrote to a file, as recorded in its history
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedEdit {
+    /// Path relative to the repository root
+    pub file: String,
+    /// Unix seconds
+    pub timestamp: i64,
+    pub lines: Vec<String>,
+}
+
+/// A session from an agent's history, with the edits it made to the repository
+#[derive(Debug, Clone)]
+pub struct ImportedSession {
+    pub agent_id: AgentId,
+    pub transcript: AiTranscript,
+    pub edits: Vec<RecordedEdit>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportedCommit {
+    pub sha: String,
+    pub summary: CommitSummary,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    /// Commits that got a note
+    pub imported: Vec<ImportedCommit>,
+    /// Commits left alone because they already have a note
+    pub already_attributed: usize,
+    /// Commits none of the sessions' edits match
+    pub unmatched: usize,
+}
+
+/// Backfills notes for the commits on HEAD since `since` (any date `git log --since` takes) that
+/// don't have one, attributing each added line to the latest session that wrote the same line to
+/// the same file before the commit. Commits nothing matches are left without a note, and existing
+/// notes are never touched.
+pub fn import_sessions(
+    repo: &Repository,
+    sessions: &[ImportedSession],
+    since: &str,
+    dry_run: bool,
+) -> Result<ImportReport, GitAiError> {
+    let mut written: HashMap<&str, WrittenLines> = HashMap::new();
+    for (session_index, session) in sessions.iter().enumerate() {
+        for edit in &session.edits {
+            for line in &edit.lines {
+                written
+                    .entry(edit.file.as_str())
+                    .or_default()
+                    .entry(line.trim())
+                    .or_default()
+                    .push((edit.timestamp, session_index));
+            }
+        }
+    }
+
+    let commits = repo.git(&[
+        "log",
+        &format!("--since={}", since),
+        "--no-merges",
+        "--reverse",
+        "--format=%H%x00%P%x00%ct%x00%an <%ae>",
+        "HEAD",
+    ])?;
+    let scope = PathScope::for_repo(repo);
+    let ignore_prompts = crate::config::Config::get().ignore_prompts();
+
+    let mut report = ImportReport::default();
+    for commit in commits.lines() {
+        let fields: Vec<&str> = commit.split('\0').collect();
+        let [sha, parents, time, author] = fields[..] else {
+            continue;
+        };
+        if get_authorship(repo, sha).is_some() {
+            report.already_attributed += 1;
+            continue;
+        }
+        let parent = parents
+            .split(' ')
+            .find(|p| !p.is_empty())
+            .unwrap_or(EMPTY_TREE);
+        let commit_time: i64 = time.parse().unwrap_or(0);
+        let tree = repo.find_commit(sha.to_string())?.tree()?;
+
+        let mut added = repo.diff_added_lines(parent, sha, None)?;
+        added.retain(|file, _| scope.contains(file));
+        let total_added: usize = added.values().map(Vec::len).sum();
+
+        // file -> line -> session
+        let mut attributed: BTreeMap<String, BTreeMap<u32, usize>> = BTreeMap::new();
+        for (file, lines) in &added {
+            let Some(file_written) = written.get(file.as_str()) else {
+                continue;
+            };
+            let Ok(entry) = tree.get_path(Path::new(file)) else {
+                continue;
+            };
+            let content = repo.find_blob(entry.id())?.content()?;
+            let content = file_text::decode(&content);
+            let file_lines: Vec<&str> = content.lines().collect();
+            let sessions_by_line = attribute_lines(lines, &file_lines, file_written, commit_time);
+            if !sessions_by_line.is_empty() {
+                attributed.insert(file.clone(), sessions_by_line);
+            }
+        }
+        if attributed.is_empty() {
+            report.unmatched += 1;
+            continue;
+        }
+
+        let mut authorship_log = AuthorshipLog::new();
+        authorship_log.metadata.base_commit_sha = sha.to_string();
+        let mut summary = CommitSummary::default();
+        for (file, sessions_by_line) in &attributed {
+            let mut lines_by_session: BTreeMap<usize, Vec<u32>> = BTreeMap::new();
+            for (line, session_index) in sessions_by_line {
+                lines_by_session
+                    .entry(*session_index)
+                    .or_default()
+                    .push(*line);
+            }
+            let mut file_attestation = FileAttestation::new(file.clone());
+            for (session_index, lines) in lines_by_session {
+                let session = &sessions[session_index];
+                let hash = generate_short_hash(&session.agent_id.id, &session.agent_id.tool);
+                let record = authorship_log
+                    .metadata
+                    .prompts
+                    .entry(hash.clone())
+                    .or_insert_with(|| PromptRecord {
+                        agent_id: session.agent_id.clone(),
+                        human_author: Some(author.to_string()),
+                        human_authors: Vec::new(),
+                        messages: if ignore_prompts {
+                            Vec::new()
+                        } else {
+                            session.transcript.messages().to_vec()
+                        },
+                        total_additions: 0,
+                        total_deletions: 0,
+                        accepted_lines: 0,
+                        overriden_lines: 0,
+                        first_checkpoint_at: None,
+                        last_checkpoint_at: None,
+                    });
+                record.total_additions += lines.len() as u32;
+                record.accepted_lines += lines.len() as u32;
+                summary.ai_lines += lines.len() as u32;
+                file_attestation.add_entry(AttestationEntry::new(
+                    hash,
+                    LineRange::compress_lines(&lines),
+                ));
+            }
+            authorship_log.attestations.push(file_attestation);
+        }
+        summary.human_lines = total_added as u32 - summary.ai_lines;
+        summary.sessions = authorship_log.metadata.prompts.len();
+        summary.models = authorship_log
+            .metadata
+            .prompts
+            .values()
+            .map(|record| record.agent_id.model.clone())
+            .collect();
+
+        if !dry_run {
+            let note = authorship_log.serialize_to_string().map_err(|_| {
+                GitAiError::Generic("Failed to serialize authorship log".to_string())
+            })?;
+            notes_add(repo, sha, &note)?;
+        }
+        report.imported.push(ImportedCommit {
+            sha: sha.to_string(),
+            summary,
+        });
+    }
+    Ok(report)
+}
+
+/// The session that wrote each of `added` (1-indexed lines of `file_lines`): the latest one to
+/// write the same text before `commit_time`. Lines with no letters or digits, like a closing
+/// brace, only count when the session also wrote a line next to them, so they aren't matched on
+/// their own.
+fn attribute_lines(
+    added: &[u32],
+    file_lines: &[&str],
+    written: &WrittenLines,
+    commit_time: i64,
+) -> BTreeMap<u32, usize> {
+    let writers = |line: u32| -> Vec<(i64, usize)> {
+        let text = file_lines
+            .get(line as usize - 1)
+            .map(|text| text.trim())
+            .unwrap_or_default();
+        written
+            .get(text)
+            .map(|edits| {
+                edits
+                    .iter()
+                    .filter(|(timestamp, _)| *timestamp <= commit_time)
+                    .copied()
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    let is_trivial = |line: u32| {
+        !file_lines
+            .get(line as usize - 1)
+            .is_some_and(|text| text.chars().any(char::is_alphanumeric))
+    };
+
+    let mut attributed = BTreeMap::new();
+    for &line in added {
+        if is_trivial(line) {
+            continue;
+        }
+        if let Some((_, session)) = writers(line).into_iter().max() {
+            attributed.insert(line, session);
+        }
+    }
+
+    // Trivial lines take the session of a neighbouring added line that wrote them, spreading
+    // down and then up through runs of them
+    let trivial: Vec<u32> = added
+        .iter()
+        .copied()
+        .filter(|line| is_trivial(*line))
+        .collect();
+    for order in [trivial.clone(), trivial.iter().rev().copied().collect()] {
+        for line in order {
+            if attributed.contains_key(&line) {
+                continue;
+            }
+            let neighbour = [line.checked_sub(1), Some(line + 1)]
+                .into_iter()
+                .flatten()
+                .find_map(|neighbour| attributed.get(&neighbour).copied());
+            if let Some(session) = neighbour
+                && writers(line).iter().any(|(_, writer)| *writer == session)
+            {
+                attributed.insert(line, session);
+            }
+        }
+    }
+    attributed
+}
+
+/// `path` relative to `workdir`, if it's inside it
+fn repo_relative(path: &str, workdir: &Path) -> Option<String> {
+    let path = Path::new(path);
+    let canonical_workdir = workdir.canonicalize().ok();
+    [Some(workdir), canonical_workdir.as_deref()]
+        .into_iter()
+        .flatten()
+        .find_map(|root| path.strip_prefix(root).ok())
+        .map(|relative| relative.to_string_lossy().replace('\\', "/"))
+}
+
+fn parse_timestamp(timestamp: &str) -> Option<i64> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .ok()
+        .map(|time| time.timestamp())
+}
+
+fn text_lines(text: &str) -> Vec<String> {
+    text.lines().map(str::to_string).collect()
+}
+
+/// Claude Code keeps each session as `<projects>/<project>/<session id>.jsonl`
+fn claude_code_sessions(
+    projects_dir: &Path,
+    workdir: &Path,
+) -> Result<Vec<ImportedSession>, GitAiError> {
+    let mut transcript_paths = Vec::new();
+    for project in std::fs::read_dir(projects_dir)?.flatten() {
+        let Ok(entries) = std::fs::read_dir(project.path()) else {
+            continue;
+        };
+        transcript_paths.extend(
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().is_some_and(|ext| ext == "jsonl")),
+        );
+    }
+    transcript_paths.sort();
+
+    let mut sessions = Vec::new();
+    for path in transcript_paths {
+        let Ok(jsonl) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        let edits = claude_code_edits(&jsonl, workdir);
+        if edits.is_empty() {
+            continue;
+        }
+        let Ok((transcript, model)) = AiTranscript::from_claude_code_jsonl_with_model(&jsonl)
+        else {
+            continue;
+        };
+        let Some(id) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+        sessions.push(ImportedSession {
+            agent_id: AgentId {
+                tool: "claude".to_string(),
+                id: id.to_string(),
+                model: model.unwrap_or_else(|| "unknown".to_string()),
+            },
+            transcript,
+            edits,
+        });
+    }
+    Ok(sessions)
+}
+
+/// The Edit, MultiEdit and Write tool calls of a Claude Code transcript
+fn claude_code_edits(jsonl: &str, workdir: &Path) -> Vec<RecordedEdit> {
+    let mut edits = Vec::new();
+    for line in jsonl.lines() {
+        let Ok(entry) = serde_json::from_str::<serde_json::Value>(line) else {
+            continue;
+        };
+        if entry["type"].as_str() != Some("assistant") {
+            continue;
+        }
+        let Some(timestamp) = entry["timestamp"].as_str().and_then(parse_timestamp) else {
+            continue;
+        };
+        let Some(content) = entry["message"]["content"].as_array() else {
+            continue;
+        };
+        for item in content {
+            if item["type"].as_str() != Some("tool_use") {
+                continue;
+            }
+            let input = &item["input"];
+            let Some(file) = input["file_path"]
+                .as_str()
+                .and_then(|path| repo_relative(path, workdir))
+            else {
+                continue;
+            };
+            let lines = match item["name"].as_str() {
+                Some("Edit") => input["new_string"].as_str().map(text_lines),
+                Some("Write") => input["content"].as_str().map(text_lines),
+                Some("MultiEdit") => input["edits"].as_array().map(|multi| {
+                    multi
+                        .iter()
+                        .filter_map(|edit| edit["new_string"].as_str())
+                        .flat_map(text_lines)
+                        .collect()
+                }),
+                _ => None,
+            };
+            if let Some(lines) = lines {
+                edits.push(RecordedEdit {
+                    file,
+                    timestamp,
+                    lines,
+                });
+            }
+        }
+    }
+    edits
+}
+
+/// Cursor keeps conversations in its global state database, one `composerData:<id>` row each and
+/// one `bubbleId:<id>:<bubble>` row per message
+fn cursor_sessions(global_db: &Path, workdir: &Path) -> Result<Vec<ImportedSession>, GitAiError> {
+    let conn = CursorPreset::open_sqlite_readonly(global_db)?;
+    let query_error = |e: rusqlite::Error| GitAiError::Generic(format!("Query failed: {}", e));
+    let mut statement = conn
+        .prepare("SELECT key, value FROM cursorDiskKV WHERE key LIKE 'bubbleId:%'")
+        .map_err(query_error)?;
+    let rows = statement
+        .query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })
+        .map_err(query_error)?;
+
+    let mut edits_by_composer: BTreeMap<String, Vec<RecordedEdit>> = BTreeMap::new();
+    for (key, value) in rows.flatten() {
+        let Some(composer_id) = key.split(':').nth(1) else {
+            continue;
+        };
+        let Ok(bubble) = serde_json::from_str::<serde_json::Value>(&value) else {
+            continue;
+        };
+        if let Some(edit) = cursor_bubble_edit(&bubble, workdir) {
+            edits_by_composer
+                .entry(composer_id.to_string())
+                .or_default()
+                .push(edit);
+        }
+    }
+
+    let mut sessions = Vec::new();
+    for (composer_id, edits) in edits_by_composer {
+        let (transcript, model) = CursorPreset::fetch_composer_payload(global_db, &composer_id)
+            .and_then(|payload| {
+                CursorPreset::transcript_data_from_composer_payload(
+                    &payload,
+                    global_db,
+                    &composer_id,
+                )
+            })
+            .ok()
+            .flatten()
+            .unwrap_or_else(|| (AiTranscript::new(), "unknown".to_string()));
+        sessions.push(ImportedSession {
+            agent_id: AgentId {
+                tool: "cursor".to_string(),
+                id: composer_id,
+                model,
+            },
+            transcript,
+            edits,
+        });
+    }
+    Ok(sessions)
+}
+
+/// The lines a Cursor tool call wrote, from its raw arguments
+fn cursor_bubble_edit(bubble: &serde_json::Value, workdir: &Path) -> Option<RecordedEdit> {
+    let tool = bubble.get("toolFormerData")?;
+    let args: serde_json::Value = serde_json::from_str(tool.get("rawArgs")?.as_str()?).ok()?;
+    let file = args
+        .get("file_path")
+        .or_else(|| args.get("target_file"))
+        .and_then(|path| path.as_str())
+        .and_then(|path| repo_relative(path, workdir))?;
+    let lines = match tool.get("name")?.as_str()? {
+        "edit_file" => text_lines(args.get("code_edit")?.as_str()?),
+        "search_replace" | "edit_file_v2_search_replace" => {
+            text_lines(args.get("new_string")?.as_str()?)
+        }
+        "write" => text_lines(
+            args.get("contents")
+                .or_else(|| args.get("content"))?
+                .as_str()?,
+        ),
+        "apply_patch" | "edit_file_v2_apply_patch" => args
+            .get("patch")?
+            .as_str()?
+            .lines()
+            .filter(|line| line.starts_with('+') && !line.starts_with("+++"))
+            .map(|line| line[1..].to_string())
+            .collect(),
+        _ => return None,
+    };
+    Some(RecordedEdit {
+        file,
+        timestamp: parse_timestamp(bubble.get("createdAt")?.as_str()?)?,
+        lines,
+    })
+}
+
+/// aider appends every chat to `.aider.chat.history.md`, each starting with
+/// `# aider chat started at <local time>`. Only SEARCH/REPLACE edits are read; the file an edit
+/// applies to is on the line before its code fence.
+fn aider_sessions(history_path: &Path) -> Result<Vec<ImportedSession>, GitAiError> {
+    let history = std::fs::read_to_string(history_path)?;
+    let mut sessions: Vec<ImportedSession> = Vec::new();
+    let mut timestamp = 0;
+    let mut previous: Vec<&str> = Vec::new();
+    let mut lines = history.lines();
+
+    while let Some(line) = lines.next() {
+        if let Some(started) = line.strip_prefix("# aider chat started at ") {
+            timestamp = NaiveDateTime::parse_from_str(started.trim(), "%Y-%m-%d %H:%M:%S")
+                .ok()
+                .and_then(|time| Local.from_local_datetime(&time).earliest())
+                .map(|time| time.timestamp())
+                .unwrap_or(0);
+            sessions.push(ImportedSession {
+                agent_id: AgentId {
+                    tool: "aider".to_string(),
+                    id: started.trim().to_string(),
+                    model: "unknown".to_string(),
+                },
+                transcript: AiTranscript::new(),
+                edits: Vec::new(),
+            });
+            previous.clear();
+            continue;
+        }
+        let Some(session) = sessions.last_mut() else {
+            continue;
+        };
+
+        if let Some(model) = line.strip_prefix("> Model: ") {
+            if let Some(name) = model.split_whitespace().next() {
+                session.agent_id.model = name.to_string();
+            }
+        } else if let Some(prompt) = line.strip_prefix("#### ") {
+            session
+                .transcript
+                .add_message(Message::user(prompt.to_string(), None));
+        } else if line.trim() == "<<<<<<< SEARCH" {
+            // The file name is above the opening fence
+            let file = previous
+                .iter()
+                .rev()
+                .find(|line| !line.trim_start().starts_with("```"))
+                .map(|line| {
+                    line.trim()
+                        .trim_matches(|c| c == '`' || c == '*' || c == ':')
+                })
+                .filter(|file| !file.is_empty())
+                .map(str::to_string);
+            let mut replaced = Vec::new();
+            let mut in_replace = false;
+            for line in lines.by_ref() {
+                match line.trim() {
+                    "=======" => in_replace = true,
+                    ">>>>>>> REPLACE" => break,
+                    _ if in_replace => replaced.push(line.to_string()),
+                    _ => {}
+                }
+            }
+            if let Some(file) = file {
+                session.edits.push(RecordedEdit {
+                    file,
+                    timestamp,
+                    lines: replaced,
+                });
+            }
+        }
+        previous.push(line);
+    }
+
+    sessions.retain(|session| !session.edits.is_empty());
+    let mut ids = BTreeSet::new();
+    for session in &mut sessions {
+        // Two chats started in the same second are still two sessions
+        while !ids.insert(session.agent_id.id.clone()) {
+            session.agent_id.id.push('+');
+        }
+    }
+    Ok(sessions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attribute_lines_matches_latest_session_before_commit() {
+        let file_lines = ["fn helper() {", "    compute()", "}", "fn human() {", "}"];
+        let written: WrittenLines = HashMap::from([
+            ("fn helper() {", vec![(100, 0), (200, 1)]),
+            ("compute()", vec![(100, 0), (300, 1)]),
+            ("}", vec![(100, 0)]),
+        ]);
+
+        // Session 1 wrote line 2 again only after the commit. The first brace follows session 0's
+        // line, the second follows the human's.
+        let attributed = attribute_lines(&[1, 2, 3, 4, 5], &file_lines, &written, 250);
+        assert_eq!(attributed, BTreeMap::from([(1, 1), (2, 0), (3, 0)]));
+
+        let attributed = attribute_lines(&[1, 2, 3, 4, 5], &file_lines, &written, 150);
+        assert_eq!(attributed, BTreeMap::from([(1, 0), (2, 0), (3, 0)]));
+    }
+
+    #[test]
+    fn test_aider_sessions_from_history() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = dir.path().join(".aider.chat.history.md");
+        std::fs::write(
+            &history,
+            "\n# aider chat started at 2025-03-01 10:00:00\n\n> Model: gpt-4o with diff edit \
+             format\n\n#### add a greeting\n\nsrc/app.py\n```python\n<<<<<<< SEARCH\n=======\n\
+             def greet():\n    print(\"hi\")\n>>>>>>> REPLACE\n```\n",
+        )
+        .unwrap();
+
+        let sessions = aider_sessions(&history).unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].agent_id.tool, "aider");
+        assert_eq!(sessions[0].agent_id.model, "gpt-4o");
+        assert_eq!(sessions[0].transcript.messages().len(), 1);
+        assert_eq!(sessions[0].edits[0].file, "src/app.py");
+        assert_eq!(
+            sessions[0].edits[0].lines,
+            vec!["def greet():", "    print(\"hi\")"]
+        );
+    }
+}
@@ -0,0 +1,36 @@
+pub mod acceptance;
+pub mod attribution_gaps;
+pub mod attribution_tracker;
+pub mod author_class;
+pub mod authorship_log;
+pub mod authorship_log_serialization;
+pub mod backfill;
+pub mod commit_policy;
+pub mod commit_summary;
+pub mod commit_trailers;
+pub mod compare;
+pub mod diff_annotation;
+pub mod file_text;
+pub mod fsck;
+pub mod generated_files;
+pub mod import;
+pub mod move_detection;
+pub mod paste_detection;
+pub mod patch;
+pub mod post_commit;
+pub mod pre_commit;
+pub mod prompters;
+pub mod query;
+pub mod range_authorship;
+pub mod rebase_authorship;
+pub mod replay;
+pub mod report;
+pub mod review;
+pub mod rewrite_audit;
+pub mod stats;
+pub mod time_to_commit;
+pub mod timeline;
+pub mod transcript;
+pub mod virtual_attribution;
+pub mod warehouse;
+pub mod working_log;
@@ -2,6 +2,13 @@ use std::collections::HashMap;
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
 
+use crate::utils::debug_log;
+
+/// Most line comparisons `detect_moves` makes before it gives up and returns the moves found so
+/// far. Large refactors of files full of repeated lines would otherwise compare most deleted
+/// lines with most inserted ones; the lines left unmatched are treated as plain edits.
+pub const MAX_MOVE_COMPARISONS: usize = 2_000_000;
+
 /// Represents a single inserted line from diff-match-patch output.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct InsertedLine {
@@ -56,6 +63,21 @@ pub fn detect_moves(
     inserted_lines: &mut [InsertedLine],
     deleted_lines: &mut [DeletedLine],
     threshold: usize,
+) -> Vec<MoveMapping> {
+    detect_moves_within(
+        inserted_lines,
+        deleted_lines,
+        threshold,
+        MAX_MOVE_COMPARISONS,
+    )
+}
+
+/// `detect_moves`, stopping after `max_comparisons` line comparisons
+fn detect_moves_within(
+    inserted_lines: &mut [InsertedLine],
+    deleted_lines: &mut [DeletedLine],
+    threshold: usize,
+    max_comparisons: usize,
 ) -> Vec<MoveMapping> {
     if threshold == 0 {
         return Vec::new();
@@ -72,15 +94,20 @@ pub fn detect_moves(
         return Vec::new();
     }
 
-    let deletion_lookup = build_deletion_lookup(deleted_lines, &deleted_groups);
+    let deletion_lookup = build_deletion_lookup(deleted_lines, &deleted_groups, threshold);
     let mut mappings = Vec::new();
+    let mut comparisons = 0usize;
 
     'insert_groups: for (insert_group_idx, insert_group) in inserted_groups.iter().enumerate() {
         let mut insert_pos = 0;
-        while insert_pos < insert_group.len() {
+        // A match needs `threshold` lines, so fewer left in the group can't start one
+        while insert_pos + threshold <= insert_group.len() {
             let inserted_index = insert_group[insert_pos];
             let inserted_line = &inserted_lines[inserted_index];
-            let hash = hash_normalized(inserted_line.normalized_content());
+            let hash = hash_window(
+                inserted_lines,
+                &insert_group[insert_pos..insert_pos + threshold],
+            );
             let mut advanced = false;
 
             if let Some(candidates) = deletion_lookup.get(&hash) {
@@ -89,6 +116,15 @@ pub fn detect_moves(
                     let delete_index = delete_group[delete_pos];
                     let delete_line = &deleted_lines[delete_index];
 
+                    comparisons += 1;
+                    if comparisons > max_comparisons {
+                        debug_log(&format!(
+                            "Move detection stopped after {} line comparisons, {} moves found",
+                            max_comparisons,
+                            mappings.len()
+                        ));
+                        break 'insert_groups;
+                    }
                     if inserted_line.normalized_content() != delete_line.normalized_content() {
                         continue;
                     }
@@ -103,6 +139,7 @@ pub fn detect_moves(
                         let insert_line = &inserted_lines[insert_idx];
                         let delete_line = &deleted_lines[delete_idx];
 
+                        comparisons += 1;
                         if insert_line.normalized_content() != delete_line.normalized_content() {
                             break;
                         }
@@ -230,15 +267,18 @@ fn build_groups<T: LineRecord>(lines: &[T], threshold: usize) -> Vec<Vec<usize>>
     groups
 }
 
+/// Deleted lines keyed by the hash of the `threshold` lines starting at each, so a common line
+/// like `}` only turns up as a candidate where the lines after it match too
 fn build_deletion_lookup(
     deleted_lines: &[DeletedLine],
     deleted_groups: &[Vec<usize>],
+    threshold: usize,
 ) -> HashMap<u64, Vec<(usize, usize)>> {
     let mut lookup: HashMap<u64, Vec<(usize, usize)>> = HashMap::new();
 
     for (group_idx, group) in deleted_groups.iter().enumerate() {
-        for (line_pos, &line_idx) in group.iter().enumerate() {
-            let hash = hash_normalized(deleted_lines[line_idx].normalized_content());
+        for (line_pos, window) in group.windows(threshold).enumerate() {
+            let hash = hash_window(deleted_lines, window);
             lookup.entry(hash).or_default().push((group_idx, line_pos));
         }
     }
@@ -246,9 +286,11 @@ fn build_deletion_lookup(
     lookup
 }
 
-fn hash_normalized(value: &str) -> u64 {
+fn hash_window<T: LineRecord>(lines: &[T], window: &[usize]) -> u64 {
     let mut hasher = DefaultHasher::new();
-    value.hash(&mut hasher);
+    for &idx in window {
+        lines[idx].normalized_content().hash(&mut hasher);
+    }
     hasher.finish()
 }
 
@@ -478,6 +520,32 @@ mod tests {
         );
     }
 
+    #[test]
+    fn stops_at_the_comparison_budget_keeping_moves_found() {
+        // 500 moved two-line blocks, each followed by a line that changed
+        let mut inserted = Vec::new();
+        let mut deleted = Vec::new();
+        for i in 0..500 {
+            let line_number = 3 * i + 1;
+            for (offset, line) in [format!("a_{}();", i), format!("b_{}();", i)]
+                .iter()
+                .enumerate()
+            {
+                inserted.push(inserted_line(line_number + offset, 20, line));
+                deleted.push(deleted_line(line_number + offset, 21, line));
+            }
+            inserted.push(inserted_line(line_number + 2, 20, &format!("new_{}", i)));
+            deleted.push(deleted_line(line_number + 2, 21, &format!("old_{}", i)));
+        }
+
+        let moves = detect_moves_within(&mut inserted.clone(), &mut deleted.clone(), 2, 10);
+        assert!(!moves.is_empty() && moves.len() < 5, "{}", moves.len());
+        assert_eq!(moves[0].inserted[0].line_number, 1);
+
+        let moves = detect_moves(&mut inserted, &mut deleted, 2);
+        assert_eq!(moves.len(), 500);
+    }
+
     #[test]
     fn no_matches_when_normalized_content_differs() {
         let mut inserted = vec![
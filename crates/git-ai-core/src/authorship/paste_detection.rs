@@ -0,0 +1,197 @@
+//! The opt-in large-paste heuristic. Code pasted from a chat window outside any instrumented
+//! tool shows up in a human checkpoint as one big contiguous insertion made shortly after the
+//! file was last checkpointed (or committed). With `paste_detection` on, those lines are
+//! attributed to the `possible_ai` author class instead of the human, so `stats` and `git-ai status`
+//! can count them on their own.
+
+use std::time::Duration;
+
+use crate::authorship::attribution_tracker::Attribution;
+use crate::authorship::author_class::AuthorClass;
+use crate::authorship::working_log::CheckpointKind;
+
+/// What counts as a large paste
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PasteHeuristic {
+    /// Fewest lines inserted in one block
+    pub min_lines: u32,
+    /// Longest time since the file was last checkpointed or committed
+    pub window: Duration,
+}
+
+impl Default for PasteHeuristic {
+    fn default() -> Self {
+        PasteHeuristic {
+            min_lines: 30,
+            window: Duration::from_secs(120),
+        }
+    }
+}
+
+impl PasteHeuristic {
+    /// Whether a change made `elapsed_secs` after the file's previous version is quick enough
+    pub fn within_window(&self, elapsed_secs: u64) -> bool {
+        elapsed_secs <= self.window.as_secs()
+    }
+
+    /// The blocks of `content` (1-based inclusive line ranges) inserted at once relative to
+    /// `previous`, at least `min_lines` long. Blank lines that happen to match old ones don't
+    /// split a block.
+    pub fn large_insertions(&self, previous: &str, content: &str) -> Vec<(u32, u32)> {
+        let previous_lines: Vec<&str> = previous.lines().collect();
+        let lines: Vec<&str> = content.lines().collect();
+
+        let mut blocks: Vec<(usize, usize)> = Vec::new();
+        for op in similar::capture_diff_slices(similar::Algorithm::Myers, &previous_lines, &lines) {
+            let (new_index, new_len) = match op {
+                similar::DiffOp::Insert {
+                    new_index, new_len, ..
+                }
+                | similar::DiffOp::Replace {
+                    new_index, new_len, ..
+                } => (new_index, new_len),
+                similar::DiffOp::Equal { .. } | similar::DiffOp::Delete { .. } => continue,
+            };
+            let end = new_index + new_len;
+            match blocks.last_mut() {
+                Some((_, last_end))
+                    if lines[*last_end..new_index]
+                        .iter()
+                        .all(|line| line.trim().is_empty()) =>
+                {
+                    *last_end = end
+                }
+                _ => blocks.push((new_index, end)),
+            }
+        }
+
+        blocks
+            .into_iter()
+            .filter(|(start, end)| (end - start) as u32 >= self.min_lines)
+            .map(|(start, end)| (start as u32 + 1, end as u32))
+            .collect()
+    }
+}
+
+/// `attributions` with the human's characters from the checkpoint at `ts` that fall in `blocks`
+/// (line ranges of `content`) given to `AuthorClass::PossibleAi`. Text that kept an earlier
+/// author, like code moved from elsewhere, is left alone.
+pub fn flag_possible_ai(
+    attributions: Vec<Attribution>,
+    content: &str,
+    blocks: &[(u32, u32)],
+    ts: u128,
+) -> Vec<Attribution> {
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(content.match_indices('\n').map(|(idx, _)| idx + 1))
+        .collect();
+    let char_ranges: Vec<(usize, usize)> = blocks
+        .iter()
+        .map(|(start, end)| {
+            let from = line_starts
+                .get(*start as usize - 1)
+                .copied()
+                .unwrap_or(content.len());
+            let to = line_starts
+                .get(*end as usize)
+                .copied()
+                .unwrap_or(content.len());
+            (from, to)
+        })
+        .collect();
+
+    let human = CheckpointKind::Human.to_str();
+    let mut flagged = Vec::with_capacity(attributions.len());
+    for attribution in attributions {
+        if attribution.author_id != human || attribution.ts != ts {
+            flagged.push(attribution);
+            continue;
+        }
+        // Split the attribution at the edges of each block it overlaps
+        let mut cursor = attribution.start;
+        for &(from, to) in &char_ranges {
+            let overlap_start = from.max(cursor);
+            let overlap_end = to.min(attribution.end);
+            if overlap_start >= overlap_end {
+                continue;
+            }
+            if cursor < overlap_start {
+                flagged.push(piece(&attribution, cursor, overlap_start, &human));
+            }
+            flagged.push(piece(
+                &attribution,
+                overlap_start,
+                overlap_end,
+                AuthorClass::PossibleAi.as_str(),
+            ));
+            cursor = overlap_end;
+        }
+        if cursor < attribution.end {
+            flagged.push(piece(&attribution, cursor, attribution.end, &human));
+        }
+    }
+    flagged
+}
+
+fn piece(attribution: &Attribution, start: usize, end: usize, author_id: &str) -> Attribution {
+    Attribution {
+        start,
+        end,
+        author_id: author_id.to_string(),
+        ..attribution.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn heuristic(min_lines: u32) -> PasteHeuristic {
+        PasteHeuristic {
+            min_lines,
+            ..PasteHeuristic::default()
+        }
+    }
+
+    #[test]
+    fn test_large_insertions_ignore_small_edits_and_join_across_blank_lines() {
+        let previous = "fn main() {\n\n}\n";
+        let pasted: String = (1..=4).map(|i| format!("let x{} = {};\n", i, i)).collect();
+        let content = format!("fn main() {{\n{}\n{}}}\n", pasted, pasted);
+
+        // Four lines, the blank line the diff matched with the old one, four more lines
+        assert_eq!(
+            heuristic(9).large_insertions(previous, &content),
+            vec![(2, 10)]
+        );
+        assert!(
+            heuristic(10)
+                .large_insertions(previous, &content)
+                .is_empty()
+        );
+        assert!(
+            heuristic(2)
+                .large_insertions(&content, &content.replace("x1 = 1", "x1 = 2"))
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_flag_possible_ai_only_takes_the_new_human_text_in_blocks() {
+        let content = "keep\npaste 1\npaste 2\nkeep\n";
+        let attributions = vec![
+            Attribution::new(0, 5, "human".to_string(), 1),
+            Attribution::new(5, 21, "human".to_string(), 2),
+            Attribution::new(21, 26, "a1b2".to_string(), 2),
+        ];
+        let flagged = flag_possible_ai(attributions, content, &[(2, 3)], 2);
+        let authors: Vec<(usize, usize, &str)> = flagged
+            .iter()
+            .map(|a| (a.start, a.end, a.author_id.as_str()))
+            .collect();
+        assert_eq!(
+            authors,
+            vec![(0, 5, "human"), (5, 21, "possible_ai"), (21, 26, "a1b2"),]
+        );
+    }
+}
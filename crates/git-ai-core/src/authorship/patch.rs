@@ -0,0 +1,406 @@
+//! Attribution from a unified diff of what an agent changed, for `checkpoint --patch`.
+//!
+//! A checkpoint normally diffs the file against its previous version and credits every change
+//! to the agent. An agent that already produces patches can hand over exactly what it did
+//! instead: the lines a hunk adds are the agent's, everything else keeps its attribution, and
+//! whatever changed between the previous version and the patch's pre-image (a human edit the
+//! agent's patch doesn't cover) is left to the usual diff.
+
+use crate::authorship::attribution_tracker::Attribution;
+use crate::error::GitAiError;
+use crate::git::repo_path;
+use crate::git::repository::parse_hunk_header;
+
+/// The hunks of a patch that touch one file
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilePatch {
+    /// Key of the file the patch leaves behind (see `repo_path`)
+    pub path: String,
+    pub hunks: Vec<Hunk>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hunk {
+    pub old_start: u32,
+    pub old_count: u32,
+    pub new_start: u32,
+    pub new_count: u32,
+    pub lines: Vec<HunkLine>,
+}
+
+/// A line of a hunk, with its newline unless the file ends without one there
+#[derive(Debug, Clone, PartialEq)]
+pub enum HunkLine {
+    Context(String),
+    Removed(String),
+    Added(String),
+}
+
+/// Parses a unified diff (`git diff`, `diff -u`) into the patches of the files it leaves in
+/// place. Deleted files have nothing to attribute and are left out.
+pub fn parse(text: &str) -> Result<Vec<FilePatch>, GitAiError> {
+    let mut patches: Vec<FilePatch> = Vec::new();
+    let mut deleted_file = false;
+    let mut lines = text.split_inclusive('\n').peekable();
+    while let Some(line) = lines.next() {
+        if line.starts_with("--- ") {
+            deleted_file = false;
+            continue;
+        }
+        if let Some(header) = line.strip_prefix("+++ ") {
+            let header = header.trim_end_matches(['\n', '\r']);
+            match repo_path::key_from_diff_header(header.as_bytes()) {
+                Some(path) => patches.push(FilePatch {
+                    path,
+                    hunks: Vec::new(),
+                }),
+                None => deleted_file = true,
+            }
+            continue;
+        }
+        if !line.starts_with("@@ ") {
+            continue;
+        }
+        let (old_start, old_count, new_start, new_count) =
+            parse_hunk_header(line).ok_or_else(|| {
+                GitAiError::Generic(format!("Malformed hunk header: {}", line.trim()))
+            })?;
+        let mut hunk = Hunk {
+            old_start,
+            old_count,
+            new_start,
+            new_count,
+            lines: Vec::new(),
+        };
+        let (mut old_left, mut new_left) = (old_count, new_count);
+        while old_left > 0 || new_left > 0 {
+            let Some(line) = lines.next() else {
+                return Err(GitAiError::Generic(format!(
+                    "Patch ends inside the hunk at line {}",
+                    new_start
+                )));
+            };
+            let (prefix, content) = match line.as_bytes().first() {
+                // Some tools strip the space off empty context lines
+                Some(b'\n') | Some(b'\r') => (b' ', line),
+                Some(prefix) => (*prefix, line.get(1..).unwrap_or_default()),
+                None => break,
+            };
+            let content = content.to_string();
+            match prefix {
+                b' ' => {
+                    old_left = old_left.saturating_sub(1);
+                    new_left = new_left.saturating_sub(1);
+                    hunk.lines.push(HunkLine::Context(content));
+                }
+                b'-' => {
+                    old_left = old_left.saturating_sub(1);
+                    hunk.lines.push(HunkLine::Removed(content));
+                }
+                b'+' => {
+                    new_left = new_left.saturating_sub(1);
+                    hunk.lines.push(HunkLine::Added(content));
+                }
+                b'\\' => strip_last_newline(&mut hunk),
+                _ => {
+                    return Err(GitAiError::Generic(format!(
+                        "Unexpected line in hunk: {}",
+                        line.trim_end()
+                    )));
+                }
+            }
+        }
+        // "\ No newline at end of file" follows the hunk's last line
+        if lines.peek().is_some_and(|line| line.starts_with('\\')) {
+            lines.next();
+            strip_last_newline(&mut hunk);
+        }
+        if deleted_file {
+            continue;
+        }
+        match patches.last_mut() {
+            Some(patch) => patch.hunks.push(hunk),
+            None => {
+                return Err(GitAiError::Generic(
+                    "Hunk without a file header (+++ b/<path>)".to_string(),
+                ));
+            }
+        }
+    }
+    patches.retain(|patch| !patch.hunks.is_empty());
+    Ok(patches)
+}
+
+fn strip_last_newline(hunk: &mut Hunk) {
+    if let Some(HunkLine::Context(content) | HunkLine::Removed(content) | HunkLine::Added(content)) =
+        hunk.lines.last_mut()
+        && content.ends_with('\n')
+    {
+        content.pop();
+        if content.ends_with('\r') {
+            content.pop();
+        }
+    }
+}
+
+impl FilePatch {
+    /// The file as it was before the patch, given `current`, the file after it. Fails if the
+    /// file no longer matches what the patch left behind.
+    pub fn pre_image(&self, current: &str) -> Result<String, GitAiError> {
+        let current_lines: Vec<&str> = current.split_inclusive('\n').collect();
+        let mut pre_image = String::with_capacity(current.len());
+        let mut next = 0;
+        for hunk in &self.hunks {
+            let start = if hunk.new_count == 0 {
+                hunk.new_start as usize
+            } else {
+                (hunk.new_start as usize).saturating_sub(1)
+            };
+            if start < next || start > current_lines.len() {
+                return Err(self.mismatch(hunk.new_start));
+            }
+            current_lines[next..start]
+                .iter()
+                .for_each(|line| pre_image.push_str(line));
+            next = start;
+            for line in &hunk.lines {
+                match line {
+                    HunkLine::Context(content) | HunkLine::Added(content) => {
+                        if current_lines.get(next) != Some(&content.as_str()) {
+                            return Err(self.mismatch(next as u32 + 1));
+                        }
+                        if let HunkLine::Context(content) = line {
+                            pre_image.push_str(content);
+                        }
+                        next += 1;
+                    }
+                    HunkLine::Removed(content) => pre_image.push_str(content),
+                }
+            }
+        }
+        current_lines[next..]
+            .iter()
+            .for_each(|line| pre_image.push_str(line));
+        Ok(pre_image)
+    }
+
+    fn mismatch(&self, line: u32) -> GitAiError {
+        GitAiError::Generic(format!(
+            "{} doesn't match the patch at line {}",
+            self.path, line
+        ))
+    }
+
+    /// Carries `attributions` of `pre_image` through the patch: lines the patch keeps keep
+    /// theirs, removed lines lose theirs, and added lines are `author_id`'s
+    pub fn apply_to_attributions(
+        &self,
+        pre_image: &str,
+        attributions: &[Attribution],
+        author_id: &str,
+        ts: u128,
+    ) -> Vec<Attribution> {
+        let old_lines = line_ranges(pre_image);
+        // For each line of the pre-image, the line it becomes after the patch, if kept
+        let mut old_to_new: Vec<Option<usize>> = Vec::with_capacity(old_lines.len());
+        let mut added = Vec::new();
+        let mut new_index = 0;
+        let mut new_lengths = Vec::new();
+        let keep_until = |old_to_new: &mut Vec<Option<usize>>,
+                          new_lengths: &mut Vec<usize>,
+                          new_index: &mut usize,
+                          until: usize| {
+            while old_to_new.len() < until.min(old_lines.len()) {
+                let (start, end) = old_lines[old_to_new.len()];
+                old_to_new.push(Some(*new_index));
+                new_lengths.push(end - start);
+                *new_index += 1;
+            }
+        };
+        for hunk in &self.hunks {
+            let start = if hunk.old_count == 0 {
+                hunk.old_start as usize
+            } else {
+                (hunk.old_start as usize).saturating_sub(1)
+            };
+            keep_until(&mut old_to_new, &mut new_lengths, &mut new_index, start);
+            for line in &hunk.lines {
+                match line {
+                    HunkLine::Context(content) => {
+                        old_to_new.push(Some(new_index));
+                        new_lengths.push(content.len());
+                        new_index += 1;
+                    }
+                    HunkLine::Removed(_) => old_to_new.push(None),
+                    HunkLine::Added(content) => {
+                        added.push(new_index);
+                        new_lengths.push(content.len());
+                        new_index += 1;
+                    }
+                }
+            }
+        }
+        keep_until(
+            &mut old_to_new,
+            &mut new_lengths,
+            &mut new_index,
+            old_lines.len(),
+        );
+
+        let mut new_starts = Vec::with_capacity(new_lengths.len());
+        let mut offset = 0;
+        for length in &new_lengths {
+            new_starts.push(offset);
+            offset += length;
+        }
+
+        let mut result = Vec::new();
+        for attribution in attributions {
+            for (old_index, (line_start, line_end)) in old_lines.iter().enumerate() {
+                let Some((start, end)) = attribution.intersection(*line_start, *line_end) else {
+                    continue;
+                };
+                let Some(Some(new_index)) = old_to_new.get(old_index) else {
+                    continue;
+                };
+                let new_start = new_starts[*new_index];
+                result.push(
+                    Attribution::new(
+                        new_start + (start - line_start),
+                        new_start + (end - line_start),
+                        attribution.author_id.clone(),
+                        attribution.ts,
+                    )
+                    .with_confidence(attribution.confidence),
+                );
+            }
+        }
+        for new_index in added {
+            let start = new_starts[new_index];
+            let end = start + new_lengths[new_index];
+            if end > start {
+                result.push(Attribution::new(start, end, author_id.to_string(), ts));
+            }
+        }
+        result.sort_by_key(|attribution| (attribution.start, attribution.end));
+
+        // Stitch back together what splitting by line cut apart
+        let mut merged: Vec<Attribution> = Vec::with_capacity(result.len());
+        for attribution in result {
+            match merged.last_mut() {
+                Some(last)
+                    if last.end == attribution.start
+                        && last.author_id == attribution.author_id
+                        && last.ts == attribution.ts
+                        && last.confidence == attribution.confidence =>
+                {
+                    last.end = attribution.end;
+                }
+                _ => merged.push(attribution),
+            }
+        }
+        merged
+    }
+}
+
+/// Byte range of each line of `content`, newline included
+fn line_ranges(content: &str) -> Vec<(usize, usize)> {
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    for line in content.split_inclusive('\n') {
+        ranges.push((start, start + line.len()));
+        start += line.len();
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PATCH: &str = "\
+diff --git a/src/lib.rs b/src/lib.rs
+index 1111111..2222222 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,3 +1,4 @@
+ fn a() {}
+-fn b() {}
++fn b() { todo!() }
++fn c() {}
+ fn d() {}
+";
+
+    #[test]
+    fn test_parse() {
+        let patches = parse(PATCH).unwrap();
+        assert_eq!(patches.len(), 1);
+        assert_eq!(patches[0].path, "src/lib.rs");
+        let hunk = &patches[0].hunks[0];
+        assert_eq!((hunk.old_start, hunk.old_count), (1, 3));
+        assert_eq!((hunk.new_start, hunk.new_count), (1, 4));
+        assert_eq!(hunk.lines[1], HunkLine::Removed("fn b() {}\n".to_string()));
+        assert_eq!(hunk.lines[3], HunkLine::Added("fn c() {}\n".to_string()));
+    }
+
+    #[test]
+    fn test_parse_new_and_deleted_files() {
+        let patch = "\
+--- /dev/null
++++ b/new.rs
+@@ -0,0 +1 @@
++fn new() {}
+\\ No newline at end of file
+--- a/gone.rs
++++ /dev/null
+@@ -1 +0,0 @@
+-fn gone() {}
+";
+        let patches = parse(patch).unwrap();
+        assert_eq!(patches.len(), 1);
+        assert_eq!(patches[0].path, "new.rs");
+        assert_eq!(
+            patches[0].hunks[0].lines,
+            vec![HunkLine::Added("fn new() {}".to_string())]
+        );
+        assert_eq!(patches[0].pre_image("fn new() {}").unwrap(), "");
+    }
+
+    #[test]
+    fn test_pre_image() {
+        let patch = &parse(PATCH).unwrap()[0];
+        let current = "fn a() {}\nfn b() { todo!() }\nfn c() {}\nfn d() {}\nfn e() {}\n";
+        assert_eq!(
+            patch.pre_image(current).unwrap(),
+            "fn a() {}\nfn b() {}\nfn d() {}\nfn e() {}\n"
+        );
+        // The agent's lines aren't there any more
+        assert!(patch.pre_image("fn a() {}\nfn d() {}\n").is_err());
+    }
+
+    #[test]
+    fn test_apply_to_attributions() {
+        let patch = &parse(PATCH).unwrap()[0];
+        let pre_image = "fn a() {}\nfn b() {}\nfn d() {}\n";
+        // A human wrote everything, another agent the end of the last line
+        let attributions = vec![
+            Attribution::new(0, 26, "human".to_string(), 1),
+            Attribution::new(26, 30, "other".to_string(), 2),
+        ];
+        let result = patch.apply_to_attributions(pre_image, &attributions, "agent", 3);
+        let current = "fn a() {}\nfn b() { todo!() }\nfn c() {}\nfn d() {}\n";
+        let spans: Vec<(&str, &str)> = result
+            .iter()
+            .map(|a| (&current[a.start..a.end], a.author_id.as_str()))
+            .collect();
+        assert_eq!(
+            spans,
+            vec![
+                ("fn a() {}\n", "human"),
+                ("fn b() { todo!() }\nfn c() {}\n", "agent"),
+                ("fn d()", "human"),
+                (" {}\n", "other"),
+            ]
+        );
+    }
+}
@@ -1,11 +1,17 @@
-use crate::authorship::authorship_log_serialization::AuthorshipLog;
+use crate::authorship::authorship_log::LineRange;
+use crate::authorship::authorship_log_serialization::{AuthorshipLog, generate_short_hash};
+use crate::authorship::commit_summary::summary_note;
+use crate::authorship::import::EMPTY_TREE;
+use crate::authorship::review::add_review_mark;
 use crate::authorship::stats::{stats_for_commit_stats, write_stats_to_terminal};
 use crate::authorship::virtual_attribution::VirtualAttributions;
 use crate::authorship::working_log::Checkpoint;
 use crate::commands::checkpoint_agent::agent_presets::CursorPreset;
 use crate::error::GitAiError;
-use crate::git::refs::notes_add;
+use crate::git::lfs::skipped_lfs_files;
+use crate::git::refs::{notes_add, notes_add_to_ref};
 use crate::git::repository::Repository;
+use crate::utils::debug_log;
 use std::collections::HashSet;
 
 pub fn post_commit(
@@ -15,6 +21,7 @@ pub fn post_commit(
     human_author: String,
     supress_output: bool,
 ) -> Result<(String, AuthorshipLog), GitAiError> {
+    let _span = tracing::info_span!("post_commit", commit = %commit_sha).entered();
     // Use base_commit parameter if provided, otherwise use "initial" for empty repos
     // This matches the convention in checkpoint.rs
     let parent_sha = base_commit.unwrap_or_else(|| "initial".to_string());
@@ -53,7 +60,7 @@ pub fn post_commit(
         .iter()
         .flat_map(|cp| cp.entries.iter().map(|e| e.file.clone()))
         .collect();
-
+    tracing::debug!(commit = %commit_sha, files = ?pathspecs, "files considered");
 
     // Split VirtualAttributions into committed (authorship log) and uncommitted (INITIAL)
     let (mut authorship_log, initial_attributions) = working_va
@@ -65,6 +72,18 @@ pub fn post_commit(
         )?;
 
     authorship_log.metadata.base_commit_sha = commit_sha.clone();
+    authorship_log.metadata.commit_message_prompt =
+        commit_message_prompt(repo, &parent_working_log, &commit_sha)
+            .filter(|prompt_id| authorship_log.metadata.prompts.contains_key(prompt_id));
+    let from_ref = if parent_sha == "initial" {
+        EMPTY_TREE
+    } else {
+        parent_sha.as_str()
+    };
+    authorship_log.metadata.skipped_files = skipped_lfs_files(repo, from_ref, &commit_sha);
+    authorship_log.metadata.renames = repo
+        .diff_renamed_files(from_ref, &commit_sha)
+        .unwrap_or_default();
 
     // Serialize the authorship log
     let authorship_json = authorship_log
@@ -72,6 +91,14 @@ pub fn post_commit(
         .map_err(|_| GitAiError::Generic("Failed to serialize authorship log".to_string()))?;
 
     notes_add(repo, &commit_sha, &authorship_json)?;
+    crate::observability::usage::record_commit_ai_lines(ai_line_count(&authorship_log));
+
+    // The summary note is only a readable copy, so failing to write it doesn't fail the commit
+    if let Some(notes_ref) = crate::config::Config::get().summary_notes_ref()
+        && let Err(e) = add_summary_note(repo, notes_ref, &commit_sha, &authorship_log)
+    {
+        debug_log(&format!("Failed to add summary note: {}", e));
+    }
 
     // Write INITIAL file for uncommitted AI attributions (if any)
     if !initial_attributions.files.is_empty() {
@@ -80,19 +107,96 @@ pub fn post_commit(
             .write_initial_attributions(initial_attributions.files, initial_attributions.prompts)?;
     }
 
+    // Reviews of lines the commit took go into its review note; the rest wait for a later one
+    let review_marks = working_log.read_review_marks();
+    if !review_marks.is_empty() {
+        let (committed, pending): (Vec<_>, Vec<_>) = review_marks.into_iter().partition(|mark| {
+            authorship_log
+                .attestations
+                .iter()
+                .any(|file| file.file_path == mark.file)
+        });
+        for mark in &committed {
+            add_review_mark(repo, &commit_sha, mark)?;
+        }
+        let new_working_log = repo_storage.working_log_for_base_commit(&commit_sha);
+        for mark in &pending {
+            new_working_log.append_review_mark(mark)?;
+        }
+    }
+
     // // Clean up old working log
     // if !cfg!(debug_assertions) {
     repo_storage.delete_working_log_for_base_commit(&parent_sha)?;
     // }
+    repo_storage.carry_other_users_working_logs(&parent_sha, &commit_sha);
 
     if !supress_output {
         let refname = repo.head()?.name().unwrap().to_string();
-        let stats = stats_for_commit_stats(repo, &commit_sha, &refname)?;
+        let stats = stats_for_commit_stats(repo, &commit_sha, &refname, false)?;
         write_stats_to_terminal(&stats, true);
     }
     Ok((commit_sha.to_string(), authorship_log))
 }
 
+/// Writes the plain text summary of `authorship_log` as the commit's note under `notes_ref`
+fn add_summary_note(
+    repo: &Repository,
+    notes_ref: &str,
+    commit_sha: &str,
+    authorship_log: &AuthorshipLog,
+) -> Result<(), GitAiError> {
+    let stats = stats_for_commit_stats(repo, commit_sha, commit_sha, false)?;
+    let note = summary_note(authorship_log, stats.ai_additions, stats.human_additions);
+    notes_add_to_ref(repo, notes_ref, commit_sha, &note)
+}
+
+/// Lines the authorship log attributes to AI, across all files
+fn ai_line_count(authorship_log: &AuthorshipLog) -> u32 {
+    authorship_log
+        .attestations
+        .iter()
+        .flat_map(|file| &file.entries)
+        .flat_map(|entry| &entry.line_ranges)
+        .map(|range| match range {
+            LineRange::Single(_) => 1,
+            LineRange::Range(start, end) => end - start + 1,
+        })
+        .sum()
+}
+
+/// The prompt whose agent wrote the message of `commit_sha`: the latest AI checkpoint recording
+/// that message. None if no agent wrote it, or a human changed it before committing.
+pub fn commit_message_prompt(
+    repo: &Repository,
+    checkpoints: &[Checkpoint],
+    commit_sha: &str,
+) -> Option<String> {
+    let message = repo
+        .find_commit(commit_sha.to_string())
+        .ok()?
+        .message()
+        .ok()?;
+    checkpoints.iter().rev().find_map(|checkpoint| {
+        let written = checkpoint.commit_message.as_deref()?;
+        let agent_id = checkpoint.agent_id.as_ref()?;
+        (normalize_commit_message(written) == normalize_commit_message(&message))
+            .then(|| generate_short_hash(&agent_id.id, &agent_id.tool))
+    })
+}
+
+/// A commit message the way git stores it: no trailing whitespace on lines, no blank lines around
+/// it
+fn normalize_commit_message(message: &str) -> String {
+    message
+        .lines()
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
 /// Filter out working log entries for untracked files
 fn filter_untracked_files(
     repo: &Repository,
@@ -193,4 +297,24 @@ mod tests {
             "Should have empty attestations when no checkpoints exist"
         );
     }
+
+    #[test]
+    fn test_post_commit_after_stash_round_trip_keeps_ai_lines() {
+        use crate::git::test_utils::ai_lines;
+
+        let (tmp_repo, _, _) = TmpRepo::new_with_base_commit().unwrap();
+        tmp_repo
+            .write_file("feature.rs", "fn feature() {}\n", true)
+            .unwrap();
+        tmp_repo
+            .trigger_checkpoint_with_ai("session_1", Some("test_model"), Some("test_tool"))
+            .unwrap();
+
+        tmp_repo.stash_push(Some("wip")).unwrap();
+        assert!(!tmp_repo.path().join("feature.rs").exists());
+        tmp_repo.stash_pop().unwrap();
+
+        let log = tmp_repo.commit_with_message("Add feature").unwrap();
+        assert_eq!(ai_lines(&log, "feature.rs").len(), 1);
+    }
 }
@@ -0,0 +1,46 @@
+use crate::authorship::working_log::CheckpointKind;
+use crate::commands::checkpoint_agent::plugins::detect_agent;
+use crate::commands::checkpoint_agent::process_ancestry::detect_agent_ancestor;
+use crate::error::GitAiError;
+use crate::git::repository::Repository;
+
+/// Checkpoints the changes about to be committed, returning whose changes they were taken as
+pub fn pre_commit(repo: &Repository, default_author: String) -> Result<CheckpointKind, GitAiError> {
+    // Run checkpoint as human editor, unless an agent plugin recognizes its agent at work, or
+    // an agent that never checkpointed is running the commit.
+    let agent_run_result = detect_agent(&repo.workdir()?).or_else(|| {
+        if has_ai_checkpoints(repo) {
+            None
+        } else {
+            detect_agent_ancestor()
+        }
+    });
+    let kind = agent_run_result
+        .as_ref()
+        .map_or(CheckpointKind::Human, |r| r.checkpoint_kind);
+    let result: Result<(usize, usize, usize), GitAiError> = crate::commands::checkpoint::run(
+        repo,
+        &default_author,
+        kind,
+        false,
+        false,
+        true,
+        agent_run_result,
+        true, // should skip if NO AI CHECKPOINTS
+        // also there's a bug around clearing state...maybe INITAL doesn't get deleted when nuking other stuff
+        false,
+    );
+    result.map(|_| kind)
+}
+
+/// Whether an agent already checkpointed changes on top of HEAD
+fn has_ai_checkpoints(repo: &Repository) -> bool {
+    let base_commit = repo
+        .head()
+        .and_then(|head| head.target())
+        .unwrap_or_else(|_| "initial".to_string());
+    repo.storage
+        .working_log_for_base_commit(&base_commit)
+        .read_all_checkpoints()
+        .is_ok_and(|checkpoints| checkpoints.iter().any(|checkpoint| checkpoint.kind.is_ai()))
+}
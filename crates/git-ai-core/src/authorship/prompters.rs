@@ -0,0 +1,82 @@
+//! AI lines per human who prompted the agents that wrote them.
+//!
+//! Agent checkpoints record who prompted the agent (the git user, unless the agent's session
+//! says otherwise), and each session's record in a commit's note keeps it. While pairing, the
+//! driver is the prompter.
+
+use crate::error::GitAiError;
+use crate::git::refs::get_authorship;
+use crate::git::repository::Repository;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Sessions whose notes don't say who prompted them
+pub const UNKNOWN_PROMPTER: &str = "unknown";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct PrompterStats {
+    /// `Name <email>`
+    pub prompter: String,
+    pub sessions: usize,
+    /// Lines committed as the agents wrote them
+    pub accepted_lines: u32,
+    /// Lines the agents wrote that a human edited before committing
+    pub overriden_lines: u32,
+    /// `tool::model` of the agents prompted
+    pub tools: BTreeSet<String>,
+}
+
+/// AI lines of the commits `rev` selects, anything `git log` takes (`HEAD`, `main..feature`),
+/// per prompter, most lines first
+pub fn by_prompter(repo: &Repository, rev: &str) -> Result<Vec<PrompterStats>, GitAiError> {
+    let output = repo.git(&["log", "--format=%H", rev])?;
+    let mut prompters: BTreeMap<String, PrompterStats> = BTreeMap::new();
+    let mut sessions: BTreeSet<(String, String)> = BTreeSet::new();
+    for commit in output.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        let Some(log) = get_authorship(repo, commit) else {
+            continue;
+        };
+        for (session_id, record) in &log.metadata.prompts {
+            let prompter = record.prompter().unwrap_or(UNKNOWN_PROMPTER).to_string();
+            let stats = prompters
+                .entry(prompter.clone())
+                .or_insert_with(|| PrompterStats {
+                    prompter: prompter.clone(),
+                    ..Default::default()
+                });
+            if sessions.insert((prompter, session_id.clone())) {
+                stats.sessions += 1;
+            }
+            stats.accepted_lines += record.accepted_lines;
+            stats.overriden_lines += record.overriden_lines;
+            stats.tools.insert(format!(
+                "{}::{}",
+                record.agent_id.tool, record.agent_id.model
+            ));
+        }
+    }
+    let mut prompters: Vec<PrompterStats> = prompters.into_values().collect();
+    prompters.sort_by_key(|stats| std::cmp::Reverse(stats.accepted_lines + stats.overriden_lines));
+    Ok(prompters)
+}
+
+pub fn print_prompters(prompters: &[PrompterStats]) {
+    if prompters.is_empty() {
+        println!("No AI sessions in these commits");
+        return;
+    }
+    println!(
+        "{:<40}  {:>8}  {:>8}  {:>10}  agents",
+        "prompted by", "sessions", "accepted", "overridden"
+    );
+    for stats in prompters {
+        println!(
+            "{:<40}  {:>8}  {:>8}  {:>10}  {}",
+            stats.prompter,
+            stats.sessions,
+            stats.accepted_lines,
+            stats.overriden_lines,
+            stats.tools.iter().cloned().collect::<Vec<_>>().join(", ")
+        );
+    }
+}
@@ -0,0 +1,342 @@
+//! Single entry point for "who wrote lines L1..L2 of file F at commit C".
+//!
+//! Combines the three places attribution lives, in order of precedence:
+//! 1. the authorship note of the commit that last touched each line
+//! 2. the working log, for lines that aren't committed yet
+//! 3. plain git blame, for everything neither of those covers
+
+use crate::authorship::authorship_log::PromptRecord;
+use crate::authorship::authorship_log_serialization::{AuthorshipLog, generate_short_hash};
+use crate::authorship::review::{ReviewMark, has_review_marks, review_marks, reviewer};
+use crate::authorship::working_log::{AgentId, CheckpointKind};
+use crate::commands::blame::{BlameHunk, BlameHunkStream, GitAiBlameOptions};
+use crate::error::GitAiError;
+use crate::git::refs::get_reference_as_authorship_log_v3;
+use crate::git::repo_path;
+use crate::git::repo_storage::RepoStorage;
+use crate::git::repository::Repository;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Where the answer for a line came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AttributionSource {
+    /// The authorship note of the commit that introduced the line
+    Note,
+    /// The working log of uncommitted changes
+    WorkingLog,
+    /// Git blame alone; no git-ai data covers the line
+    Blame,
+}
+
+impl AttributionSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AttributionSource::Note => "note",
+            AttributionSource::WorkingLog => "working_log",
+            AttributionSource::Blame => "blame",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum QueriedAuthor {
+    Human {
+        name: String,
+    },
+    Ai {
+        prompt_hash: String,
+        tool: String,
+        model: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct QueriedLine {
+    /// 1-indexed line number in the queried version of the file
+    pub line: u32,
+    pub author: QueriedAuthor,
+    /// Commit that last changed the line, or `None` if it isn't committed yet
+    pub commit: Option<String>,
+    pub source: AttributionSource,
+    /// How certain an AI attribution is, as a percentage (None means it was recorded directly)
+    pub confidence: Option<u8>,
+    /// Whether the note splits the line's characters between authors, as when someone edits
+    /// part of a line another author wrote
+    pub co_written: bool,
+    /// Who marked the line reviewed with `git-ai review mark`, if anyone has
+    pub reviewed_by: Option<String>,
+}
+
+/// Attribution of `line_range` (1-indexed, inclusive; the whole file if `None`) of
+/// `file_path` at `commit`, or in the working directory when `commit` is `None`.
+pub fn query_attribution(
+    repo: &Repository,
+    file_path: &str,
+    commit: Option<&str>,
+    line_range: Option<(u32, u32)>,
+) -> Result<Vec<QueriedLine>, GitAiError> {
+    let mut lines = Vec::new();
+    for hunk in stream_attribution(repo, file_path, commit, line_range)? {
+        lines.extend(hunk?.lines);
+    }
+    lines.sort_by_key(|l| l.line);
+    Ok(lines)
+}
+
+/// Same answer as `query_attribution`, delivered one blame hunk at a time as git resolves
+/// them, so callers can show the lines they care about before the whole range is done.
+pub fn stream_attribution(
+    repo: &Repository,
+    file_path: &str,
+    commit: Option<&str>,
+    line_range: Option<(u32, u32)>,
+) -> Result<AttributionStream, GitAiError> {
+    let newest_commit = match commit {
+        Some(rev) => Some(repo.revparse_single(rev)?.id()),
+        None => None,
+    };
+    let (start_line, end_line) = match line_range {
+        Some(range) => range,
+        None => (1, line_count(repo, file_path, newest_commit.as_deref())?),
+    };
+    if end_line != 0 && (start_line == 0 || start_line > end_line) {
+        return Err(GitAiError::Generic(format!(
+            "Invalid line range: {}:{}",
+            start_line, end_line
+        )));
+    }
+
+    let options = GitAiBlameOptions {
+        newest_commit,
+        ..Default::default()
+    };
+    // An empty file has nothing to blame
+    let hunks = if end_line == 0 {
+        None
+    } else {
+        Some(repo.blame_hunks_stream(file_path, start_line, end_line, &options)?)
+    };
+    Ok(AttributionStream {
+        repo: repo.clone(),
+        file_path: file_path.to_string(),
+        hunks,
+        notes: HashMap::new(),
+        foreign_prompts: HashMap::new(),
+        uncommitted: None,
+        reviews: has_review_marks(repo).then(HashMap::new),
+    })
+}
+
+/// Lines of one blame hunk: consecutive lines last changed by the same commit
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AttributedHunk {
+    pub start_line: u32,
+    pub end_line: u32,
+    pub lines: Vec<QueriedLine>,
+}
+
+/// Attributed hunks in the order git blame resolves them, see `stream_attribution`
+pub struct AttributionStream {
+    repo: Repository,
+    file_path: String,
+    hunks: Option<BlameHunkStream>,
+    notes: HashMap<String, Option<AuthorshipLog>>,
+    foreign_prompts: HashMap<String, Option<PromptRecord>>,
+    uncommitted: Option<UncommittedAttributions>,
+    /// Review marks by commit; None when nothing has been marked reviewed
+    reviews: Option<HashMap<String, Vec<ReviewMark>>>,
+}
+
+impl AttributionStream {
+    fn attribute(&mut self, hunk: &BlameHunk) -> Result<AttributedHunk, GitAiError> {
+        let is_uncommitted = hunk.commit_sha.bytes().all(|b| b == b'0');
+        let mut lines = Vec::new();
+        for offset in 0..=(hunk.range.1 - hunk.range.0) {
+            let line = hunk.range.0 + offset;
+            let orig_line = hunk.orig_range.0 + offset;
+
+            let mut co_written = false;
+            let mut reviewed_by = None;
+            let queried = if is_uncommitted {
+                let uncommitted = match &self.uncommitted {
+                    Some(uncommitted) => uncommitted,
+                    None => self
+                        .uncommitted
+                        .insert(UncommittedAttributions::load(&self.repo, &self.file_path)?),
+                };
+                uncommitted.lookup(line)
+            } else {
+                let note = self
+                    .notes
+                    .entry(hunk.commit_sha.clone())
+                    .or_insert_with(|| {
+                        get_reference_as_authorship_log_v3(&self.repo, &hunk.commit_sha).ok()
+                    });
+                if let Some(reviews) = self.reviews.as_mut() {
+                    let marks = reviews
+                        .entry(hunk.commit_sha.clone())
+                        .or_insert_with(|| review_marks(&self.repo, &hunk.commit_sha));
+                    reviewed_by = reviewer(marks, &self.file_path, orig_line).map(str::to_string);
+                }
+                co_written = note.as_ref().is_some_and(|note| {
+                    note.get_line_column_owner(&self.file_path, orig_line)
+                        .is_some()
+                });
+                note.as_ref().and_then(|note| {
+                    from_note(
+                        &self.repo,
+                        note,
+                        &self.file_path,
+                        orig_line,
+                        &mut self.foreign_prompts,
+                    )
+                })
+            };
+
+            let (author, source, confidence) =
+                queried.unwrap_or_else(|| (human(hunk), AttributionSource::Blame, None));
+            lines.push(QueriedLine {
+                line,
+                author,
+                commit: (!is_uncommitted).then(|| hunk.commit_sha.clone()),
+                source,
+                confidence,
+                co_written,
+                reviewed_by,
+            });
+        }
+        Ok(AttributedHunk {
+            start_line: hunk.range.0,
+            end_line: hunk.range.1,
+            lines,
+        })
+    }
+}
+
+impl Iterator for AttributionStream {
+    type Item = Result<AttributedHunk, GitAiError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let hunk = match self.hunks.as_mut()?.next()? {
+            Ok(hunk) => hunk,
+            Err(e) => return Some(Err(e)),
+        };
+        Some(self.attribute(&hunk))
+    }
+}
+
+type Answer = (QueriedAuthor, AttributionSource, Option<u8>);
+
+fn line_count(repo: &Repository, file_path: &str, commit: Option<&str>) -> Result<u32, GitAiError> {
+    let content = match commit {
+        Some(sha) => repo.get_file_content(file_path, sha)?,
+        None => std::fs::read(repo.workdir()?.join(repo_path::key_to_path(file_path)))?,
+    };
+    Ok(String::from_utf8_lossy(&content).lines().count() as u32)
+}
+
+fn human(hunk: &BlameHunk) -> QueriedAuthor {
+    QueriedAuthor::Human {
+        name: hunk.original_author.clone(),
+    }
+}
+
+fn ai(prompt_hash: String, agent_id: &AgentId) -> QueriedAuthor {
+    QueriedAuthor::Ai {
+        prompt_hash,
+        tool: agent_id.tool.clone(),
+        model: agent_id.model.clone(),
+    }
+}
+
+fn from_note(
+    repo: &Repository,
+    note: &AuthorshipLog,
+    file_path: &str,
+    orig_line: u32,
+    foreign_prompts: &mut HashMap<String, Option<PromptRecord>>,
+) -> Option<Answer> {
+    let (_, prompt_hash, prompt) =
+        note.get_line_attribution(repo, file_path, orig_line, foreign_prompts)?;
+    let (prompt_hash, prompt) = prompt_hash.zip(prompt)?;
+    Some((
+        ai(prompt_hash, &prompt.agent_id),
+        AttributionSource::Note,
+        note.get_line_confidence(file_path, orig_line),
+    ))
+}
+
+/// Line attributions of the latest checkpoint of a file, usable only while the file on disk
+/// still matches what that checkpoint saw.
+struct UncommittedAttributions {
+    line_authors: Vec<(u32, u32, String, Option<u8>)>,
+    agents: HashMap<String, AgentId>,
+    human_name: Option<String>,
+}
+
+impl UncommittedAttributions {
+    fn load(repo: &Repository, file_path: &str) -> Result<Self, GitAiError> {
+        let human_name = repo
+            .config_get_str("user.name")
+            .ok()
+            .flatten()
+            .filter(|name| !name.trim().is_empty());
+        let mut attributions = Self {
+            line_authors: Vec::new(),
+            agents: HashMap::new(),
+            human_name,
+        };
+
+        let base_commit = match repo.head().and_then(|head| head.target()) {
+            Ok(sha) => sha,
+            Err(_) => "initial".to_string(),
+        };
+        let working_log = RepoStorage::for_repo_path(repo.path(), &repo.workdir()?)
+            .working_log_for_base_commit(&base_commit);
+        let checkpoints = working_log.read_all_checkpoints()?;
+
+        for checkpoint in &checkpoints {
+            if let Some(agent_id) = &checkpoint.agent_id {
+                attributions.agents.insert(
+                    generate_short_hash(&agent_id.id, &agent_id.tool),
+                    agent_id.clone(),
+                );
+            }
+        }
+
+        let latest_entry = checkpoints
+            .iter()
+            .rev()
+            .find_map(|c| c.entries.iter().find(|e| e.file == file_path));
+        let current_content = working_log.read_current_file_content(file_path).ok();
+        if let Some(entry) = latest_entry
+            && current_content.is_some()
+            && working_log.get_file_version(&entry.blob_sha).ok() == current_content
+        {
+            attributions.line_authors = entry
+                .line_attributions
+                .iter()
+                .map(|a| (a.start_line, a.end_line, a.author_id.clone(), a.confidence))
+                .collect();
+        }
+        Ok(attributions)
+    }
+
+    fn lookup(&self, line: u32) -> Option<Answer> {
+        let (_, _, author_id, confidence) = self
+            .line_authors
+            .iter()
+            .find(|(start, end, _, _)| (*start..=*end).contains(&line))?;
+        let author = match self.agents.get(author_id) {
+            Some(agent_id) => ai(author_id.clone(), agent_id),
+            None if author_id == CheckpointKind::Human.to_str().as_str() => QueriedAuthor::Human {
+                name: self.human_name.clone()?,
+            },
+            None => return None,
+        };
+        Some((author, AttributionSource::WorkingLog, *confidence))
+    }
+}
@@ -1,11 +1,15 @@
-use std::collections::HashMap;
 use std::collections::HashSet;
+use std::sync::Arc;
 
 use serde::Deserialize;
 use serde::Serialize;
 
+use crate::authorship::file_text;
+use crate::authorship::generated_files::GeneratedFileFilter;
 use crate::authorship::stats::{CommitStats, stats_for_commit_stats, stats_from_authorship_log};
+use crate::authorship::virtual_attribution::FileContents;
 use crate::error::GitAiError;
+use crate::git::path_scope::PathScope;
 use crate::git::refs::{CommitAuthorship, get_commits_with_notes_from_list};
 use crate::git::repository::{CommitRange, Repository};
 use crate::utils::debug_log;
@@ -28,6 +32,7 @@ pub struct RangeAuthorshipStatsData {
 pub fn range_authorship(
     commit_range: CommitRange,
     pre_fetch_contents: bool,
+    include_generated: bool,
 ) -> Result<RangeAuthorshipStats, GitAiError> {
     if let Err(e) = commit_range.is_valid() {
         return Err(e);
@@ -100,7 +105,8 @@ pub fn range_authorship(
     let commit_authorship = get_commits_with_notes_from_list(repository, &commit_shas)?;
 
     // Calculate range stats - now just pass start, end, and commits
-    let range_stats = calculate_range_stats_direct(repository, commit_range_clone)?;
+    let range_stats =
+        calculate_range_stats_direct(repository, commit_range_clone, include_generated)?;
 
     Ok(RangeAuthorshipStats {
         authorship_stats: RangeAuthorshipStatsData {
@@ -146,7 +152,7 @@ pub fn range_authorship(
 
 /// Create an in-memory authorship log for a commit range by treating it as a squash
 /// Similar to rewrite_authorship_after_squash_or_rebase but tailored for ranges
-fn create_authorship_log_for_range(
+pub(crate) fn create_authorship_log_for_range(
     repo: &Repository,
     start_sha: &str,
     end_sha: &str,
@@ -161,8 +167,9 @@ fn create_authorship_log_for_range(
         start_sha, end_sha
     ));
 
-    // Step 1: Get list of changed files between the two commits
-    let changed_files = repo.diff_changed_files(start_sha, end_sha)?;
+    // Step 1: Get list of changed files between the two commits, within the path scope
+    let changed_files: Vec<String> =
+        PathScope::for_repo(repo).retain(repo.diff_changed_files(start_sha, end_sha)?);
 
     if changed_files.is_empty() {
         // No files changed, return empty authorship log
@@ -177,6 +184,14 @@ fn create_authorship_log_for_range(
                     ),
                     base_commit_sha: end_sha.to_string(),
                     prompts: std::collections::BTreeMap::new(),
+                    confidence: std::collections::BTreeMap::new(),
+                    columns: std::collections::BTreeMap::new(),
+                    overridden: std::collections::BTreeMap::new(),
+                    commit_message_prompt: None,
+                    inferred: None,
+                    skipped_files: std::collections::BTreeMap::new(),
+                    renames: std::collections::BTreeMap::new(),
+                    author_classes: std::collections::BTreeSet::new(),
                 },
             },
         );
@@ -237,19 +252,19 @@ fn get_committed_files_content(
     repo: &Repository,
     commit_sha: &str,
     pathspecs: &[String],
-) -> Result<HashMap<String, String>, GitAiError> {
+) -> Result<FileContents, GitAiError> {
     let commit = repo.find_commit(commit_sha.to_string())?;
     let tree = commit.tree()?;
 
-    let mut files = HashMap::new();
+    let mut files = FileContents::new();
 
     for file_path in pathspecs {
         match tree.get_path(std::path::Path::new(file_path)) {
             Ok(entry) => {
                 if let Ok(blob) = repo.find_blob(entry.id()) {
                     let blob_content = blob.content().unwrap_or_default();
-                    let content = String::from_utf8_lossy(&blob_content).to_string();
-                    files.insert(file_path.clone(), content);
+                    let content = file_text::decode(&blob_content);
+                    files.insert(file_path.clone(), Arc::from(content));
                 }
             }
             Err(_) => {
@@ -266,43 +281,38 @@ fn get_git_diff_stats_for_range(
     repo: &Repository,
     start_sha: &str,
     end_sha: &str,
+    include_generated: bool,
 ) -> Result<(u32, u32), GitAiError> {
     // Use git diff --numstat to get diff statistics for the range
     let mut args = repo.global_args_for_exec();
     args.push("diff".to_string());
     args.push("--numstat".to_string());
+    // A rename's "old => new" name can't be matched against the scope or generated files
+    args.push("--no-renames".to_string());
     args.push(format!("{}..{}", start_sha, end_sha));
 
     let output = crate::git::repository::exec_git(&args)?;
-    let stdout = String::from_utf8(output.stdout)?;
 
-    let mut added_lines = 0u32;
-    let mut deleted_lines = 0u32;
-
-    // Parse numstat output
-    for line in stdout.lines() {
-        if line.trim().is_empty() {
-            continue;
-        }
-
-        // Parse numstat format: "added\tdeleted\tfilename"
-        let parts: Vec<&str> = line.split('\t').collect();
-        if parts.len() >= 2 {
-            // Parse added lines
-            if let Ok(added) = parts[0].parse::<u32>() {
-                added_lines += added;
-            }
+    // Parse numstat format: "added\tdeleted\tfilename" ("-" for binary files)
+    let scope = PathScope::for_repo(repo);
+    let mut file_stats: Vec<(String, u32, u32)> = output
+        .stdout
+        .split(|byte| *byte == b'\n')
+        .filter_map(crate::git::repo_path::parse_numstat_line)
+        .filter(|(_, _, path)| path.is_empty() || scope.contains(path))
+        .map(|(added, deleted, path)| (path, added.unwrap_or(0), deleted.unwrap_or(0)))
+        .collect();
 
-            // Parse deleted lines (handle "-" for binary files)
-            if parts[1] != "-" {
-                if let Ok(deleted) = parts[1].parse::<u32>() {
-                    deleted_lines += deleted;
-                }
-            }
-        }
+    if !include_generated {
+        let filter = GeneratedFileFilter::for_paths(repo, file_stats.iter().map(|f| &f.0))
+            .unwrap_or_default();
+        file_stats.retain(|(path, _, _)| !filter.is_generated(path));
     }
 
-    Ok((added_lines, deleted_lines))
+    Ok((
+        file_stats.iter().map(|f| f.1).sum(),
+        file_stats.iter().map(|f| f.2).sum(),
+    ))
 }
 
 /// Calculate AI vs human line contributions for a commit range
@@ -310,21 +320,33 @@ fn get_git_diff_stats_for_range(
 fn calculate_range_stats_direct(
     repo: &Repository,
     commit_range: CommitRange,
+    include_generated: bool,
 ) -> Result<CommitStats, GitAiError> {
     let start_sha = commit_range.start_oid.clone();
     let end_sha = commit_range.end_oid.clone();
     // Special case: single commit range (start == end)
     if start_sha == end_sha {
-        return stats_for_commit_stats(repo, &end_sha, &commit_range.refname);
+        return stats_for_commit_stats(repo, &end_sha, &commit_range.refname, include_generated);
     }
 
     // Step 1: Get git diff stats between start and end
     let (git_diff_added_lines, git_diff_deleted_lines) =
-        get_git_diff_stats_for_range(repo, &start_sha, &end_sha)?;
+        get_git_diff_stats_for_range(repo, &start_sha, &end_sha, include_generated)?;
 
     // Step 2: Create in-memory authorship log for the range, filtered to only commits in the range
     let commit_shas = commit_range.clone().all_commits();
-    let authorship_log = create_authorship_log_for_range(repo, &start_sha, &end_sha, &commit_shas)?;
+    let mut authorship_log =
+        create_authorship_log_for_range(repo, &start_sha, &end_sha, &commit_shas)?;
+    if !include_generated {
+        let filter = GeneratedFileFilter::for_paths(
+            repo,
+            authorship_log.attestations.iter().map(|a| &a.file_path),
+        )
+        .unwrap_or_default();
+        authorship_log
+            .attestations
+            .retain(|attestation| !filter.is_generated(&attestation.file_path));
+    }
 
     // Step 3: Calculate stats from the authorship log
     let stats = stats_from_authorship_log(
@@ -1,11 +1,26 @@
+use crate::authorship::attribution_tracker::{
+    Attribution, AttributionConfig, AttributionTracker, LineAttribution,
+};
 use crate::authorship::authorship_log_serialization::AuthorshipLog;
+use crate::authorship::file_text;
 use crate::authorship::post_commit;
+use crate::authorship::rewrite_audit::RewriteAudit;
+use crate::authorship::virtual_attribution::FileContents;
+use crate::config::Config;
 use crate::error::GitAiError;
+use crate::git::diff_tree_to_tree::DiffStatus;
+use crate::git::lfs::smudge_blob_content;
+use crate::git::path_scope::PathScope;
 use crate::git::refs::get_reference_as_authorship_log_v3;
+use crate::git::repo_path;
 use crate::git::repository::Repository;
 use crate::git::rewrite_log::RewriteLogEvent;
 use crate::utils::debug_log;
 use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// Lines per file whose attribution a rewrite restored instead of carrying through the diff
+type RestoredLines = HashMap<String, HashSet<u32>>;
 
 // Process events in the rewrite log and call the correct rewrite functions in this file
 pub fn rewrite_authorship_if_needed(
@@ -15,6 +30,7 @@ pub fn rewrite_authorship_if_needed(
     _full_log: &Vec<RewriteLogEvent>,
     supress_output: bool,
 ) -> Result<(), GitAiError> {
+    let _span = tracing::info_span!("rewrite").entered();
     match last_event {
         RewriteLogEvent::Commit { commit } => {
             // This is going to become the regualar post-commit
@@ -64,6 +80,7 @@ pub fn rewrite_authorship_if_needed(
                 &rebase_complete.original_head,
                 &rebase_complete.original_commits,
                 &rebase_complete.new_commits,
+                rebase_complete.onto.as_deref(),
                 &commit_author,
             )?;
 
@@ -283,6 +300,7 @@ pub fn rewrite_authorship_after_rebase_v2(
     original_head: &str,
     original_commits: &[String],
     new_commits: &[String],
+    onto: Option<&str>,
     _human_author: &str,
 ) -> Result<(), GitAiError> {
     // Handle edge case: no commits to process
@@ -305,11 +323,19 @@ pub fn rewrite_authorship_after_rebase_v2(
         new_commits.len()
     ));
 
-    // Filter out commits that already have authorship logs (these are commits from the target branch)
-    // Only process newly created rebased commits
+    // Only process newly created rebased commits. With the onto commit known, new_commits are
+    // just the replayed ones, and any the rebase kept as they were already have their logs.
+    // Without it they can include commits of the target branch, which already have logs
     let commits_to_process: Vec<String> = new_commits
         .iter()
         .filter(|commit| {
+            if onto.is_some() {
+                let kept = original_commits.contains(commit);
+                if kept {
+                    debug_log(&format!("Skipping commit {} (kept by the rebase)", commit));
+                }
+                return !kept;
+            }
             let has_log = get_reference_as_authorship_log_v3(repo, commit).is_ok();
             if has_log {
                 debug_log(&format!(
@@ -371,7 +397,15 @@ pub fn rewrite_authorship_after_rebase_v2(
         )
     };
 
-    // Step 3: Process each new commit in order (oldest to newest)
+    let mut rewritten_from = pair_rewritten_commits(repo, original_commits, new_commits);
+    let mut audits = Vec::new();
+
+    // Step 3: Process each new commit in order (oldest to newest), reading the next commit's
+    // changed files while the current one is transformed
+    let tracked_files: Arc<HashSet<String>> = Arc::new(pathspecs.iter().cloned().collect());
+    let mut prefetched = commits_to_process
+        .first()
+        .map(|commit| prefetch_changed_files_content(repo, commit, &tracked_files));
     for (idx, new_commit) in commits_to_process.iter().enumerate() {
         debug_log(&format!(
             "Processing commit {}/{}: {}",
@@ -380,57 +414,31 @@ pub fn rewrite_authorship_after_rebase_v2(
             new_commit
         ));
 
-        // Get the DIFF for this commit (what actually changed)
-        let commit_obj = repo.find_commit(new_commit.clone())?;
-        let parent_obj = commit_obj.parent(0)?;
-
-        let commit_tree = commit_obj.tree()?;
-        let parent_tree = parent_obj.tree()?;
-
-        let diff = repo.diff_tree_to_tree(Some(&parent_tree), Some(&commit_tree), None, None)?;
-
-        // Identify which tracked files actually changed in this commit
-        let mut changed_files_in_commit = std::collections::HashSet::new();
-        let mut new_content_for_changed_files = HashMap::new();
-
-        for delta in diff.deltas() {
-            let file_path = delta
-                .new_file()
-                .path()
-                .or(delta.old_file().path())
-                .ok_or_else(|| GitAiError::Generic("File path not available".to_string()))?;
-            let file_path_str = file_path.to_string_lossy().to_string();
-
-            // Only process files we're tracking
-            if !pathspecs.contains(&file_path_str) {
-                continue;
-            }
-
-            changed_files_in_commit.insert(file_path_str.clone());
-
-            // Get new content for this file from the commit
-            let new_content = if let Ok(entry) = commit_tree.get_path(file_path) {
-                if let Ok(blob) = repo.find_blob(entry.id()) {
-                    let content = blob.content()?;
-                    String::from_utf8_lossy(&content).to_string()
-                } else {
-                    String::new()
-                }
-            } else {
-                String::new()
-            };
-
-            new_content_for_changed_files.insert(file_path_str, new_content);
-        }
+        // The content of the tracked files this commit changed (what actually changed)
+        let new_content_for_changed_files = prefetched
+            .take()
+            .map(smol::block_on)
+            .transpose()?
+            .unwrap_or_default();
+        prefetched = commits_to_process
+            .get(idx + 1)
+            .map(|commit| prefetch_changed_files_content(repo, commit, &tracked_files));
 
         // Only transform attributions for files that actually changed
         // For unchanged files, we'll preserve them as-is
-        if !changed_files_in_commit.is_empty() {
-            current_va = transform_attributions_to_final_state(
+        let mut restored_lines = RestoredLines::new();
+        if !new_content_for_changed_files.is_empty() {
+            (current_va, restored_lines) = transform_attributions_to_final_state(
                 &current_va,
                 new_content_for_changed_files.clone(),
                 Some(&original_head_state_va),
             )?;
+            fill_in_rerere_resolutions(
+                repo,
+                &mut current_va,
+                &new_content_for_changed_files,
+                &mut restored_lines,
+            );
         }
 
         // Build complete content state for authorship log (all tracked files)
@@ -469,8 +477,18 @@ pub fn rewrite_authorship_after_rebase_v2(
             new_commit,
             authorship_log.attestations.len()
         ));
+
+        audits.push(audit_rewritten_commit(
+            repo,
+            "rebase",
+            new_commit,
+            rewritten_from.remove(new_commit).unwrap_or_default(),
+            &authorship_log,
+            &restored_lines,
+        ));
     }
 
+    save_rewrite_audits(repo, &audits);
     Ok(())
 }
 
@@ -564,7 +582,15 @@ pub fn rewrite_authorship_after_cherry_pick(
         )
     };
 
-    // Step 3: Process each new commit in order (oldest to newest)
+    let mut rewritten_from = pair_rewritten_commits(repo, source_commits, new_commits);
+    let mut audits = Vec::new();
+
+    // Step 3: Process each new commit in order (oldest to newest), reading the next commit's
+    // changed files while the current one is transformed
+    let tracked_files: Arc<HashSet<String>> = Arc::new(pathspecs.iter().cloned().collect());
+    let mut prefetched = new_commits
+        .first()
+        .map(|commit| prefetch_changed_files_content(repo, commit, &tracked_files));
     for (idx, new_commit) in new_commits.iter().enumerate() {
         debug_log(&format!(
             "Processing cherry-picked commit {}/{}: {}",
@@ -573,17 +599,18 @@ pub fn rewrite_authorship_after_cherry_pick(
             new_commit
         ));
 
-        // Get the DIFF for this commit (what actually changed)
-        let commit_obj = repo.find_commit(new_commit.clone())?;
-        let parent_obj = commit_obj.parent(0)?;
-
-        let commit_tree = commit_obj.tree()?;
-        let parent_tree = parent_obj.tree()?;
-
-        let diff = repo.diff_tree_to_tree(Some(&parent_tree), Some(&commit_tree), None, None)?;
+        // The content of the tracked files this commit changed (what actually changed)
+        let changed_files_content = prefetched
+            .take()
+            .map(smol::block_on)
+            .transpose()?
+            .unwrap_or_default();
+        prefetched = new_commits
+            .get(idx + 1)
+            .map(|commit| prefetch_changed_files_content(repo, commit, &tracked_files));
 
         // Build new content by applying the diff to current content
-        let mut new_content_state = HashMap::new();
+        let mut new_content_state = FileContents::new();
 
         // Start with all files from current VA
         for file in current_va.files() {
@@ -593,41 +620,22 @@ pub fn rewrite_authorship_after_cherry_pick(
         }
 
         // Apply changes from this commit's diff
-        for delta in diff.deltas() {
-            let file_path = delta
-                .new_file()
-                .path()
-                .or(delta.old_file().path())
-                .ok_or_else(|| GitAiError::Generic("File path not available".to_string()))?;
-            let file_path_str = file_path.to_string_lossy().to_string();
-
-            // Only process files we're tracking
-            if !pathspecs.contains(&file_path_str) {
-                continue;
-            }
-
-            // Get new content for this file from the commit
-            let new_content = if let Ok(entry) = commit_tree.get_path(file_path) {
-                if let Ok(blob) = repo.find_blob(entry.id()) {
-                    let content = blob.content()?;
-                    String::from_utf8_lossy(&content).to_string()
-                } else {
-                    String::new()
-                }
-            } else {
-                String::new()
-            };
-
-            new_content_state.insert(file_path_str, new_content);
-        }
+        new_content_state.extend(changed_files_content);
 
         // Transform attributions based on the new content state
         // Pass source_head state to restore attributions for content that existed before cherry-pick
-        current_va = transform_attributions_to_final_state(
+        let mut restored_lines;
+        (current_va, restored_lines) = transform_attributions_to_final_state(
             &current_va,
             new_content_state.clone(),
             Some(&source_head_state_va),
         )?;
+        fill_in_rerere_resolutions(
+            repo,
+            &mut current_va,
+            &new_content_state,
+            &mut restored_lines,
+        );
 
         // Convert to AuthorshipLog, but filter to only files that exist in this commit
         let mut authorship_log = current_va.to_authorship_log()?;
@@ -655,31 +663,176 @@ pub fn rewrite_authorship_after_cherry_pick(
             new_commit,
             authorship_log.attestations.len()
         ));
+
+        audits.push(audit_rewritten_commit(
+            repo,
+            "cherry-pick",
+            new_commit,
+            rewritten_from.remove(new_commit).unwrap_or_default(),
+            &authorship_log,
+            &restored_lines,
+        ));
     }
 
+    save_rewrite_audits(repo, &audits);
     Ok(())
 }
 
+/// The commit each of `new_commits` was rewritten from. Commits are paired in order when the
+/// rewrite kept their number; otherwise (squashed, dropped or split commits) by subject.
+fn pair_rewritten_commits(
+    repo: &Repository,
+    original_commits: &[String],
+    new_commits: &[String],
+) -> HashMap<String, Vec<String>> {
+    if original_commits.len() == new_commits.len() {
+        return new_commits
+            .iter()
+            .cloned()
+            .zip(original_commits.iter().map(|commit| vec![commit.clone()]))
+            .collect();
+    }
+
+    let summary = |commit: &String| repo.find_commit(commit.clone()).ok()?.summary().ok();
+    let mut unpaired: Vec<(String, String)> = original_commits
+        .iter()
+        .filter_map(|commit| Some((commit.clone(), summary(commit)?)))
+        .collect();
+    let mut pairs = HashMap::new();
+    for new_commit in new_commits {
+        let Some(new_summary) = summary(new_commit) else {
+            continue;
+        };
+        if let Some(idx) = unpaired.iter().position(|(_, s)| *s == new_summary) {
+            let (original, _) = unpaired.remove(idx);
+            pairs.insert(new_commit.clone(), vec![original]);
+        }
+    }
+    pairs
+}
+
+/// Audit of how `authorship_log`, just written for `new_commit`, compares with the logs of the
+/// commits it was rewritten from
+fn audit_rewritten_commit(
+    repo: &Repository,
+    operation: &str,
+    new_commit: &str,
+    rewritten_from: Vec<String>,
+    authorship_log: &AuthorshipLog,
+    restored_lines: &RestoredLines,
+) -> RewriteAudit {
+    let original_logs: Vec<AuthorshipLog> = rewritten_from
+        .iter()
+        .filter_map(|commit| get_reference_as_authorship_log_v3(repo, commit).ok())
+        .collect();
+    RewriteAudit::new(
+        operation,
+        new_commit,
+        rewritten_from,
+        &original_logs,
+        authorship_log,
+        restored_lines,
+    )
+}
+
+/// Audits are only for explaining a rewrite after the fact, so failing to save them doesn't fail it
+fn save_rewrite_audits(repo: &Repository, audits: &[RewriteAudit]) {
+    if let Err(e) = repo.storage.write_rewrite_audits(audits) {
+        debug_log(&format!("Failed to save rewrite audits: {}", e));
+    }
+}
+
+/// Conflicts resolved while rebasing or cherry-picking, by hand or replayed by rerere, were
+/// recorded with the attribution of their resolution. For files whose new content is a recorded
+/// resolution, lines the transform left unattributed (new in the resolution) take the
+/// resolution's attribution. Those lines are added to `restored_lines`.
+fn fill_in_rerere_resolutions(
+    repo: &Repository,
+    va: &mut crate::authorship::virtual_attribution::VirtualAttributions,
+    new_contents: &FileContents,
+    restored_lines: &mut RestoredLines,
+) {
+    use crate::authorship::attribution_tracker::{
+        LineAttribution, line_attributions_to_attributions,
+    };
+
+    for (file_path, content) in new_contents {
+        let Some(resolution) = repo
+            .storage
+            .rerere_resolutions_for_file(file_path)
+            .into_iter()
+            .map(|(_, resolution)| resolution)
+            .find(|resolution| {
+                *resolution.content == **content && !resolution.line_attributions.is_empty()
+            })
+        else {
+            continue;
+        };
+
+        let mut line_attrs = va
+            .get_line_attributions(file_path)
+            .cloned()
+            .unwrap_or_default();
+        let attributed: std::collections::HashSet<u32> = line_attrs
+            .iter()
+            .flat_map(|line_attr| line_attr.start_line..=line_attr.end_line)
+            .collect();
+        for line_attr in &resolution.line_attributions {
+            for line in line_attr.start_line..=line_attr.end_line {
+                if !attributed.contains(&line) {
+                    restored_lines
+                        .entry(file_path.clone())
+                        .or_default()
+                        .insert(line);
+                    line_attrs.push(
+                        LineAttribution::new(
+                            line,
+                            line,
+                            line_attr.author_id.clone(),
+                            line_attr.overrode.clone(),
+                        )
+                        .with_confidence(line_attr.confidence),
+                    );
+                }
+            }
+        }
+        line_attrs.sort_by_key(|line_attr| line_attr.start_line);
+
+        debug_log(&format!(
+            "Filling in attribution of the recorded resolution of {}",
+            file_path
+        ));
+        let char_attrs = line_attributions_to_attributions(&line_attrs, content, va.timestamp());
+        va.attributions
+            .insert(file_path.clone(), (char_attrs, line_attrs));
+        for (prompt_id, record) in resolution.prompts {
+            va.prompts
+                .entry(prompt_id)
+                .or_default()
+                .entry(String::new())
+                .or_insert(record);
+        }
+    }
+}
+
 /// Get file contents from a commit tree for specified pathspecs
 fn get_committed_files_content(
     repo: &Repository,
     commit_sha: &str,
     pathspecs: &[String],
-) -> Result<HashMap<String, String>, GitAiError> {
-    use std::collections::HashMap;
-
+) -> Result<FileContents, GitAiError> {
     let commit = repo.find_commit(commit_sha.to_string())?;
     let tree = commit.tree()?;
 
-    let mut files = HashMap::new();
+    let mut files = FileContents::new();
 
     for file_path in pathspecs {
         match tree.get_path(std::path::Path::new(file_path)) {
             Ok(entry) => {
                 if let Ok(blob) = repo.find_blob(entry.id()) {
                     let blob_content = blob.content().unwrap_or_default();
-                    let content = String::from_utf8_lossy(&blob_content).to_string();
-                    files.insert(file_path.clone(), content);
+                    let content = file_text::decode(&blob_content);
+                    files.insert(file_path.clone(), Arc::from(content));
                 }
             }
             Err(_) => {
@@ -706,6 +859,7 @@ pub fn rewrite_authorship_after_commit_amend(
     let working_log = repo.storage.working_log_for_base_commit(original_commit);
     let touched_files = working_log.all_touched_files()?;
     pathspecs.extend(touched_files);
+    let pathspecs: HashSet<String> = PathScope::for_repo(repo).retain(pathspecs);
 
     // Check if original commit has an authorship log with prompts
     let has_existing_log = get_reference_as_authorship_log_v3(repo, original_commit).is_ok();
@@ -756,12 +910,58 @@ pub fn rewrite_authorship_after_commit_amend(
     // Update base commit SHA
     authorship_log.metadata.base_commit_sha = amended_commit.to_string();
 
+    // An agent may have written the amended message. Otherwise an unchanged message keeps the
+    // original commit's attribution
+    let original_log = get_reference_as_authorship_log_v3(repo, original_commit).ok();
+    let checkpoints = working_log.read_all_checkpoints().unwrap_or_default();
+    let commit_message_prompt =
+        post_commit::commit_message_prompt(repo, &checkpoints, amended_commit).or_else(|| {
+            let original_message = repo
+                .find_commit(original_commit.to_string())
+                .ok()?
+                .message()
+                .ok()?;
+            (original_message == amended_commit_obj.message().ok()?).then_some(
+                original_log
+                    .as_ref()?
+                    .metadata
+                    .commit_message_prompt
+                    .clone()?,
+            )
+        });
+    if let Some(prompt_id) = commit_message_prompt {
+        // A session that only wrote the message has no lines to bring its prompt along
+        if let Some(record) = original_log
+            .as_ref()
+            .and_then(|log| log.metadata.prompts.get(&prompt_id))
+        {
+            authorship_log
+                .metadata
+                .prompts
+                .entry(prompt_id.clone())
+                .or_insert_with(|| record.clone());
+        }
+        if authorship_log.metadata.prompts.contains_key(&prompt_id) {
+            authorship_log.metadata.commit_message_prompt = Some(prompt_id);
+        }
+    }
+
     // Save authorship log
     let authorship_json = authorship_log
         .serialize_to_string()
         .map_err(|_| GitAiError::Generic("Failed to serialize authorship log".to_string()))?;
     crate::git::refs::notes_add(repo, amended_commit, &authorship_json)?;
 
+    let audit = RewriteAudit::new(
+        "amend",
+        amended_commit,
+        vec![original_commit.to_string()],
+        original_log.as_slice(),
+        &authorship_log,
+        &RestoredLines::new(),
+    );
+    save_rewrite_audits(repo, &[audit]);
+
     // Save INITIAL file for uncommitted attributions
     if !initial_attributions.files.is_empty() {
         let new_working_log = repo.storage.working_log_for_base_commit(amended_commit);
@@ -849,11 +1049,139 @@ pub fn reconstruct_working_log_after_reset(
         pathspecs.len()
     ));
 
+    carry_working_log_to_commit(repo, target_commit_sha, old_head_sha, &pathspecs)
+}
+
+/// Working log a HEAD move that bypassed our hooks (an IDE's git, plain git in a script) left
+/// keyed to the old commit, if HEAD has none of its own. Only HEAD's previous position counts,
+/// and only if it's related to HEAD, so switching to an unrelated branch leaves the other
+/// branch's uncommitted attributions with it. Migrates it onto `head_sha` the same way a reset
+/// does, but leaves the old working log for the caller to delete once the checkpoint is
+/// written. Returns the old base commit.
+pub fn recover_orphaned_working_log(
+    repo: &Repository,
+    head_sha: &str,
+) -> Result<Option<String>, GitAiError> {
+    if repo
+        .storage
+        .working_log_for_base_commit(head_sha)
+        .has_data()
+    {
+        return Ok(None);
+    }
+    let Some(orphan_sha) = previous_head(repo, head_sha) else {
+        return Ok(None);
+    };
+    if !repo
+        .storage
+        .working_log_for_base_commit(&orphan_sha)
+        .has_data()
+        || !moved_along_history(repo, &orphan_sha, head_sha)
+    {
+        return Ok(None);
+    }
+
+    debug_log(&format!(
+        "Recovering working log orphaned at {} onto {}",
+        orphan_sha, head_sha
+    ));
+
+    // Files the orphaned working log touched plus everything that differs between the commits
+    let orphan_log = repo.storage.working_log_for_base_commit(&orphan_sha);
+    let mut pathspecs: HashSet<String> = orphan_log.all_touched_files()?;
+    pathspecs.extend(orphan_log.read_initial_attributions().files.into_keys());
+    pathspecs.extend(get_files_changed_between_commits(
+        repo,
+        head_sha,
+        &orphan_sha,
+    )?);
+    let mut pathspecs: Vec<String> = pathspecs.into_iter().collect();
+    pathspecs.sort();
+
+    write_carried_working_log(repo, head_sha, &orphan_sha, &pathspecs)?;
+    Ok(Some(orphan_sha))
+}
+
+/// Where HEAD pointed before its last move, per the reflog, unless the reflog doesn't end at
+/// `head_sha`
+fn previous_head(repo: &Repository, head_sha: &str) -> Option<String> {
+    let reflog = repo
+        .git(&["reflog", "show", "-2", "--format=%H", "HEAD"])
+        .ok()?;
+    let mut entries = reflog.lines();
+    let current = entries.next()?;
+    let previous = entries.next()?;
+    if current != head_sha || previous == head_sha {
+        return None;
+    }
+    Some(previous.to_string())
+}
+
+/// True if one commit is an ancestor of the other, or the rewrite log records a rewrite of
+/// `old_head` into `new_head`
+fn moved_along_history(repo: &Repository, old_head: &str, new_head: &str) -> bool {
+    let is_ancestor = |ancestor: &str, descendant: &str| {
+        repo.git(&["merge-base", "--is-ancestor", ancestor, descendant])
+            .is_ok()
+    };
+    if is_ancestor(old_head, new_head) || is_ancestor(new_head, old_head) {
+        return true;
+    }
+    let events = repo.storage.read_rewrite_events().unwrap_or_default();
+    events.iter().any(|event| {
+        let (from, to) = match event {
+            RewriteLogEvent::CommitAmend { commit_amend } => (
+                commit_amend.original_commit.as_str(),
+                commit_amend.amended_commit_sha.as_str(),
+            ),
+            RewriteLogEvent::RebaseComplete { rebase_complete } => (
+                rebase_complete.original_head.as_str(),
+                rebase_complete.new_head.as_str(),
+            ),
+            RewriteLogEvent::CherryPickComplete {
+                cherry_pick_complete,
+            } => (
+                cherry_pick_complete.original_head.as_str(),
+                cherry_pick_complete.new_head.as_str(),
+            ),
+            RewriteLogEvent::Reset { reset } => {
+                (reset.old_head_sha.as_str(), reset.new_head_sha.as_str())
+            }
+            _ => return false,
+        };
+        from == old_head && to == new_head
+    })
+}
+
+/// Rebuilds the working log of `target_commit_sha` from the working log of `old_head_sha`
+/// (with blame of both commits filling in) for `pathspecs` as they are in the working
+/// directory, then deletes the old working log. Everything ends up uncommitted (INITIAL).
+fn carry_working_log_to_commit(
+    repo: &Repository,
+    target_commit_sha: &str,
+    old_head_sha: &str,
+    pathspecs: &[String],
+) -> Result<(), GitAiError> {
+    write_carried_working_log(repo, target_commit_sha, old_head_sha, pathspecs)?;
+    repo.storage
+        .delete_working_log_for_base_commit(old_head_sha)
+}
+
+/// `carry_working_log_to_commit` without deleting the old working log
+fn write_carried_working_log(
+    repo: &Repository,
+    target_commit_sha: &str,
+    old_head_sha: &str,
+    pathspecs: &[String],
+) -> Result<(), GitAiError> {
+    let pathspecs: Vec<String> = PathScope::for_repo(repo).retain(pathspecs.iter().cloned());
+    let pathspecs = pathspecs.as_slice();
+
     // Step 2: Build VirtualAttributions from old_head with working log applied
     // from_working_log_for_commit now runs blame (gets ALL prompts) AND applies working log
     let repo_clone = repo.clone();
     let old_head_clone = old_head_sha.to_string();
-    let pathspecs_clone = pathspecs.clone();
+    let pathspecs_clone = pathspecs.to_vec();
 
     let old_head_va = smol::block_on(async {
         crate::authorship::virtual_attribution::VirtualAttributions::from_working_log_for_commit(
@@ -874,7 +1202,7 @@ pub fn reconstruct_working_log_after_reset(
     // Step 3: Build VirtualAttributions from target_commit
     let repo_clone = repo.clone();
     let target_clone = target_commit_sha.to_string();
-    let pathspecs_clone = pathspecs.clone();
+    let pathspecs_clone = pathspecs.to_vec();
 
     let target_va = smol::block_on(async {
         crate::authorship::virtual_attribution::VirtualAttributions::new_for_base_commit(
@@ -892,18 +1220,13 @@ pub fn reconstruct_working_log_after_reset(
     ));
 
     // Step 4: Build final state from working directory
-    use std::collections::HashMap;
-    let mut final_state: HashMap<String, String> = HashMap::new();
+    let mut final_state = FileContents::new();
 
     let workdir = repo.workdir()?;
-    for file_path in &pathspecs {
-        let abs_path = workdir.join(file_path);
-        let content = if abs_path.exists() {
-            std::fs::read_to_string(&abs_path).unwrap_or_default()
-        } else {
-            String::new()
-        };
-        final_state.insert(file_path.clone(), content);
+    for file_path in pathspecs {
+        let abs_path = workdir.join(repo_path::key_to_path(file_path));
+        let content = file_text::read(&abs_path);
+        final_state.insert(file_path.clone(), Arc::from(content));
     }
 
     debug_log(&format!(
@@ -950,10 +1273,6 @@ pub fn reconstruct_working_log_after_reset(
             .write_initial_attributions(initial_attributions.files, initial_attributions.prompts)?;
     }
 
-    // Delete old working log
-    repo.storage
-        .delete_working_log_for_base_commit(old_head_sha)?;
-
     debug_log(&format!(
         "✓ Wrote INITIAL attributions to working log for {}",
         target_commit_sha
@@ -962,7 +1281,7 @@ pub fn reconstruct_working_log_after_reset(
     Ok(())
 }
 
-/// Get all file paths modified across a list of commits
+/// Get all file paths modified across a list of commits, limited to the repository's path scope
 fn get_pathspecs_from_commits(
     repo: &Repository,
     commits: &[String],
@@ -974,19 +1293,91 @@ fn get_pathspecs_from_commits(
         pathspecs.extend(files);
     }
 
-    Ok(pathspecs.into_iter().collect())
+    Ok(PathScope::for_repo(repo).retain(pathspecs))
+}
+
+/// Starts reading the content of the tracked files `commit` changes in the background, so a
+/// rewrite can read the next commit's files while it transforms the current one
+fn prefetch_changed_files_content(
+    repo: &Repository,
+    commit: &str,
+    tracked_files: &Arc<HashSet<String>>,
+) -> smol::Task<Result<FileContents, GitAiError>> {
+    let repo = repo.clone();
+    let commit = commit.to_string();
+    let tracked_files = Arc::clone(tracked_files);
+    smol::unblock(move || changed_files_content(&repo, &commit, &tracked_files))
+}
+
+/// The content in `commit` of the tracked files it changed from its first parent, empty for the
+/// ones it deleted. The blobs the diff names are read all at once.
+fn changed_files_content(
+    repo: &Repository,
+    commit: &str,
+    tracked_files: &HashSet<String>,
+) -> Result<FileContents, GitAiError> {
+    let commit_obj = repo.find_commit(commit.to_string())?;
+    let parent_obj = commit_obj.parent(0)?;
+
+    let commit_tree = commit_obj.tree()?;
+    let parent_tree = parent_obj.tree()?;
+
+    let diff = repo.diff_tree_to_tree(Some(&parent_tree), Some(&commit_tree), None, None)?;
+
+    let mut contents = FileContents::new();
+    // (path, blob) of each tracked file the commit has
+    let mut present: Vec<(String, String)> = Vec::new();
+    for delta in diff.deltas() {
+        let file_path = delta
+            .new_file()
+            .path()
+            .or(delta.old_file().path())
+            .ok_or_else(|| GitAiError::Generic("File path not available".to_string()))?
+            .to_string_lossy()
+            .to_string();
+
+        // Only process files we're tracking
+        if !tracked_files.contains(&file_path) {
+            continue;
+        }
+
+        // A file the commit deleted has no content in it; a submodule has none to read
+        if delta.new_file().mode() == "160000" {
+            continue;
+        } else if delta.status() == DiffStatus::Deleted {
+            contents.insert(file_path, Arc::from(""));
+        } else {
+            present.push((file_path, delta.new_file().id().to_string()));
+        }
+    }
+
+    let oids: Vec<String> = present.iter().map(|(_, oid)| oid.clone()).collect();
+    let blobs = repo.read_blobs(&oids)?;
+    for (file_path, oid) in present {
+        // Files with the same content share a blob
+        let blob = blobs.get(&oid).cloned().unwrap_or_default();
+        let content =
+            smudge_blob_content(repo, &file_path, &format!("{}:{}", commit, file_path), blob);
+        contents.insert(file_path, Arc::from(file_text::decode(&content)));
+    }
+    Ok(contents)
 }
 
-/// Transform VirtualAttributions to match a new final state (single-source variant)
+/// Transform VirtualAttributions to match a new final state (single-source variant). Also
+/// returns, per file, the lines whose attribution was restored from `original_head_state`.
 fn transform_attributions_to_final_state(
     source_va: &crate::authorship::virtual_attribution::VirtualAttributions,
-    final_state: HashMap<String, String>,
+    final_state: FileContents,
     original_head_state: Option<&crate::authorship::virtual_attribution::VirtualAttributions>,
-) -> Result<crate::authorship::virtual_attribution::VirtualAttributions, GitAiError> {
-    use crate::authorship::attribution_tracker::AttributionTracker;
+) -> Result<
+    (
+        crate::authorship::virtual_attribution::VirtualAttributions,
+        RestoredLines,
+    ),
+    GitAiError,
+> {
     use crate::authorship::virtual_attribution::VirtualAttributions;
 
-    let tracker = AttributionTracker::new();
     let ts = source_va.timestamp();
     let repo = source_va.repo().clone();
     let base_commit = source_va.base_commit().to_string();
@@ -994,6 +1385,11 @@ fn transform_attributions_to_final_state(
     let mut attributions = HashMap::new();
     let mut file_contents = HashMap::new();
 
+    // Files don't depend on each other, so they're transformed concurrently
+    const MAX_CONCURRENT: usize = 30;
+    let semaphore = Arc::new(smol::lock::Semaphore::new(MAX_CONCURRENT));
+    let mut tasks = Vec::new();
+
     // Process each file in the final state
     for (file_path, final_content) in final_state {
         // Skip empty files (they don't exist in this commit yet)
@@ -1015,128 +1411,40 @@ fn transform_attributions_to_final_state(
             continue;
         }
 
-        // Get source attributions and content
-        let source_attrs = source_va.get_char_attributions(&file_path);
-        let source_content = source_va.get_file_content(&file_path);
-
-        // Transform to final state
-        let mut transformed_attrs = if let (Some(attrs), Some(content)) =
-            (source_attrs, source_content)
-        {
-            // Use a dummy author for new insertions
-            let dummy_author = "__DUMMY__";
-
-            let transformed =
-                tracker.update_attributions(content, &final_content, attrs, dummy_author, ts)?;
-
-            // Keep all attributions initially (including dummy ones)
-            transformed
-        } else {
-            Vec::new()
-        };
-
-        // Try to restore attributions from original_head_state using line-content matching
-        // This handles commit splitting where content from original_head gets re-applied
-        if let Some(original_state) = original_head_state {
-            if let Some(original_content) = original_state.get_file_content(&file_path) {
-                if original_content == &final_content {
-                    // The final content matches the original content exactly!
-                    // Use the original attributions
-                    if let Some(original_attrs) = original_state.get_char_attributions(&file_path) {
-                        transformed_attrs = original_attrs.clone();
-                    }
-                } else {
-                    // Use line-content matching to restore attributions for lines that existed before
-                    // Build a map of line content -> author from original state
-                    let mut original_line_to_author: HashMap<String, String> = HashMap::new();
-
-                    if let Some(original_line_attrs) =
-                        original_state.get_line_attributions(&file_path)
-                    {
-                        let original_lines: Vec<&str> = original_content.lines().collect();
-
-                        for line_attr in original_line_attrs {
-                            // LineAttribution is 1-indexed
-                            for line_num in line_attr.start_line..=line_attr.end_line {
-                                let line_idx = (line_num as usize).saturating_sub(1);
-                                if line_idx < original_lines.len() {
-                                    let line_content = original_lines[line_idx].to_string();
-                                    // Store all non-human attributions (AI attributions)
-                                    // VirtualAttributions normalizes humans to "human" via return_human_authors_as_human flag
-                                    // AI authors keep their tool names (mock_ai, Claude, GPT, etc.) or prompt hashes
-                                    if line_attr.author_id != "human" {
-                                        original_line_to_author
-                                            .insert(line_content, line_attr.author_id.clone());
-                                    }
-                                }
-                            }
-                        }
-                    }
+        let source = source_va
+            .get_char_attributions(&file_path)
+            .cloned()
+            .zip(source_va.get_file_content(&file_path).cloned());
+        let original = original_head_state.and_then(|state| {
+            Some(OriginalFileState {
+                content: state.get_file_content(&file_path)?.clone(),
+                char_attrs: state.get_char_attributions(&file_path).cloned(),
+                line_attrs: state.get_line_attributions(&file_path).cloned(),
+            })
+        });
+        let semaphore = Arc::clone(&semaphore);
+
+        let task = smol::spawn(async move {
+            // Acquire semaphore permit to limit concurrency
+            let _permit = semaphore.acquire().await;
+
+            smol::unblock(move || {
+                let (char_attrs, line_attrs, restored) =
+                    transform_file_to_final_state(&final_content, source, original, ts)?;
+                Ok::<_, GitAiError>((file_path, final_content, char_attrs, line_attrs, restored))
+            })
+            .await
+        });
+        tasks.push(task);
+    }
 
-                    // Now update char attributions based on line content matching
-                    let dummy_author = "__DUMMY__";
-                    let final_lines: Vec<&str> = final_content.lines().collect();
-
-                    // Convert char attributions to line attributions to process line by line
-                    let temp_line_attrs =
-                        crate::authorship::attribution_tracker::attributions_to_line_attributions(
-                            &transformed_attrs,
-                            &final_content,
-                        );
-
-                    // For each line with dummy attribution, try to restore from original
-                    for (line_idx, line_content) in final_lines.iter().enumerate() {
-                        // Check if this line has a dummy attribution
-                        let line_num = (line_idx + 1) as u32; // LineAttribution is 1-indexed
-                        let has_dummy = temp_line_attrs.iter().any(|la| {
-                            la.start_line <= line_num
-                                && la.end_line >= line_num
-                                && la.author_id == dummy_author
-                        });
-
-                        if has_dummy {
-                            // Try to find this line content in original state
-                            if let Some(original_author) =
-                                original_line_to_author.get(*line_content)
-                            {
-                                // Update all char attributions on this line
-                                // Find the char range for this line
-                                let line_start_char: usize = final_lines[..line_idx]
-                                    .iter()
-                                    .map(|l| l.len() + 1) // +1 for newline
-                                    .sum();
-                                let line_end_char = line_start_char + line_content.len();
-
-                                // Update attributions that overlap with this line
-                                for attr in &mut transformed_attrs {
-                                    if attr.author_id == dummy_author
-                                        && attr.start < line_end_char
-                                        && attr.end > line_start_char
-                                    {
-                                        attr.author_id = original_author.clone();
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
+    let mut restored_lines = RestoredLines::new();
+    for result in smol::block_on(futures::future::join_all(tasks)) {
+        let (file_path, final_content, char_attrs, line_attrs, restored) = result?;
+        if !restored.is_empty() {
+            restored_lines.insert(file_path.clone(), restored);
         }
-
-        // Now filter out any remaining dummy attributions
-        let dummy_author = "__DUMMY__";
-        transformed_attrs = transformed_attrs
-            .into_iter()
-            .filter(|attr| attr.author_id != dummy_author)
-            .collect();
-
-        // Convert to line attributions
-        let line_attrs = crate::authorship::attribution_tracker::attributions_to_line_attributions(
-            &transformed_attrs,
-            &final_content,
-        );
-
-        attributions.insert(file_path.clone(), (transformed_attrs, line_attrs));
+        attributions.insert(file_path.clone(), (char_attrs, line_attrs));
         file_contents.insert(file_path, final_content);
     }
 
@@ -1178,12 +1486,282 @@ fn transform_attributions_to_final_state(
         }
     }
 
-    Ok(VirtualAttributions::new_with_prompts(
-        repo,
-        base_commit,
-        attributions,
-        file_contents,
-        prompts,
-        ts,
+    Ok((
+        VirtualAttributions::new_with_prompts(
+            repo,
+            base_commit,
+            attributions,
+            file_contents,
+            prompts,
+            ts,
+        ),
+        restored_lines,
     ))
 }
+
+/// A file's state at the original head, which lines of its transformed state are restored from
+struct OriginalFileState {
+    content: Arc<str>,
+    char_attrs: Option<Vec<Attribution>>,
+    line_attrs: Option<Vec<LineAttribution>>,
+}
+
+/// A transformed file's char and line attributions, and which of its lines were restored
+type TransformedFile = (Vec<Attribution>, Vec<LineAttribution>, HashSet<u32>);
+
+/// Transforms one file's `source` attributions and content to `final_content`, restoring lines
+/// from the file's `original` state where the transform can't attribute them. Also returns the
+/// lines whose attribution was restored.
+fn transform_file_to_final_state(
+    final_content: &str,
+    source: Option<(Vec<Attribution>, Arc<str>)>,
+    original: Option<OriginalFileState>,
+    ts: u128,
+) -> Result<TransformedFile, GitAiError> {
+    let tracker = AttributionTracker::with_config(
+        AttributionConfig::default()
+            .with_move_timestamp_policy(Config::get().move_timestamp_policy()),
+    );
+
+    // Transform to final state
+    let mut transformed_attrs = if let Some((attrs, content)) = source {
+        // Use a dummy author for new insertions
+        let dummy_author = "__DUMMY__";
+
+        let transformed =
+            tracker.update_attributions(&content, final_content, &attrs, dummy_author, ts)?;
+
+        // Keep all attributions initially (including dummy ones)
+        transformed
+    } else {
+        Vec::new()
+    };
+
+    // Lines the transform attributed, to tell them apart from restored ones
+    let mut carried_lines: Option<HashSet<u32>> = None;
+
+    // Try to restore attributions from original_head_state using line-content matching
+    // This handles commit splitting where content from original_head gets re-applied
+    if let Some(original) = original {
+        // Convert char attributions to line attributions to process line by line
+        let temp_line_attrs =
+            crate::authorship::attribution_tracker::attributions_to_line_attributions(
+                &transformed_attrs,
+                final_content,
+            );
+        carried_lines = Some(
+            temp_line_attrs
+                .iter()
+                .filter(|la| la.author_id != "__DUMMY__")
+                .flat_map(|la| la.start_line..=la.end_line)
+                .collect(),
+        );
+
+        let original_content = original.content;
+        if *original_content == *final_content {
+            // The final content matches the original content exactly!
+            // Use the original attributions
+            if let Some(original_attrs) = original.char_attrs {
+                transformed_attrs = original_attrs;
+            }
+        } else {
+            // Use line-content matching to restore attributions for lines that existed before
+            // Build a map of line content -> author from original state
+            let mut original_line_to_author: HashMap<String, String> = HashMap::new();
+
+            if let Some(original_line_attrs) = original.line_attrs {
+                let original_lines: Vec<&str> = original_content.lines().collect();
+
+                for line_attr in &original_line_attrs {
+                    // LineAttribution is 1-indexed
+                    for line_num in line_attr.start_line..=line_attr.end_line {
+                        let line_idx = (line_num as usize).saturating_sub(1);
+                        if line_idx < original_lines.len() {
+                            let line_content = original_lines[line_idx].to_string();
+                            // Store all non-human attributions (AI attributions)
+                            // VirtualAttributions normalizes humans to "human" via return_human_authors_as_human flag
+                            // AI authors keep their tool names (mock_ai, Claude, GPT, etc.) or prompt hashes
+                            if line_attr.author_id != "human" {
+                                original_line_to_author
+                                    .insert(line_content, line_attr.author_id.clone());
+                            }
+                        }
+                    }
+                }
+            }
+
+            // Now update char attributions based on line content matching
+            let dummy_author = "__DUMMY__";
+            let final_lines: Vec<&str> = final_content.lines().collect();
+
+            // For each line with dummy attribution, try to restore from original
+            for (line_idx, line_content) in final_lines.iter().enumerate() {
+                // Check if this line has a dummy attribution
+                let line_num = (line_idx + 1) as u32; // LineAttribution is 1-indexed
+                let has_dummy = temp_line_attrs.iter().any(|la| {
+                    la.start_line <= line_num
+                        && la.end_line >= line_num
+                        && la.author_id == dummy_author
+                });
+
+                if has_dummy {
+                    // Try to find this line content in original state
+                    if let Some(original_author) = original_line_to_author.get(*line_content) {
+                        // Update all char attributions on this line
+                        // Find the char range for this line
+                        let line_start_char: usize = final_lines[..line_idx]
+                            .iter()
+                            .map(|l| l.len() + 1) // +1 for newline
+                            .sum();
+                        let line_end_char = line_start_char + line_content.len();
+
+                        // Update attributions that overlap with this line
+                        for attr in &mut transformed_attrs {
+                            if attr.author_id == dummy_author
+                                && attr.start < line_end_char
+                                && attr.end > line_start_char
+                            {
+                                attr.author_id = original_author.clone();
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // Now filter out any remaining dummy attributions
+    let dummy_author = "__DUMMY__";
+    transformed_attrs = transformed_attrs
+        .into_iter()
+        .filter(|attr| attr.author_id != dummy_author)
+        .collect();
+
+    // Convert to line attributions
+    let line_attrs =
+        crate::authorship::attribution_tracker::attributions_to_line_attributions_with_policy(
+            &transformed_attrs,
+            final_content,
+            Config::get().override_policy(),
+        );
+    let restored_lines = match carried_lines {
+        Some(carried_lines) => line_attrs
+            .iter()
+            .flat_map(|la| la.start_line..=la.end_line)
+            .filter(|line| !carried_lines.contains(line))
+            .collect(),
+        None => HashSet::new(),
+    };
+
+    Ok((transformed_attrs, line_attrs, restored_lines))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::git::test_utils::{RebaseTodo, TmpRepo, ai_lines};
+    use std::collections::BTreeSet;
+
+    fn commit_lines(tmp_repo: &TmpRepo, contents: &str, ai: Option<&str>, message: &str) -> String {
+        tmp_repo.write_file("app.rs", contents, true).unwrap();
+        match ai {
+            Some(session) => tmp_repo
+                .trigger_checkpoint_with_ai(session, Some("test_model"), Some("test_tool"))
+                .unwrap(),
+            None => tmp_repo
+                .trigger_checkpoint_with_author("test_user")
+                .unwrap(),
+        };
+        tmp_repo.commit_with_message(message).unwrap();
+        tmp_repo.head_commit_sha().unwrap()
+    }
+
+    #[test]
+    fn test_commit_amend_keeps_ai_lines_and_adds_new_ones() {
+        let tmp_repo = TmpRepo::new().unwrap();
+        commit_lines(&tmp_repo, "human\n", None, "base");
+        commit_lines(&tmp_repo, "human\nai one\n", Some("session_1"), "add ai");
+
+        tmp_repo
+            .write_file("app.rs", "human\nai one\nai two\n", true)
+            .unwrap();
+        tmp_repo
+            .trigger_checkpoint_with_ai("session_2", Some("test_model"), Some("test_tool"))
+            .unwrap();
+        let log = tmp_repo.commit_amend("add ai, amended").unwrap();
+
+        assert_eq!(ai_lines(&log, "app.rs"), BTreeSet::from([2, 3]));
+        assert_eq!(log.metadata.prompts.len(), 2);
+    }
+
+    #[test]
+    fn test_interactive_rebase_squash_keeps_ai_lines() {
+        let tmp_repo = TmpRepo::new().unwrap();
+        let base = commit_lines(&tmp_repo, "a\n", None, "base");
+        let first = commit_lines(&tmp_repo, "a\nai\n", Some("session_1"), "ai");
+        let second = commit_lines(&tmp_repo, "a\nai\nhuman\n", None, "human");
+
+        tmp_repo
+            .rebase_interactive(
+                &base,
+                &[RebaseTodo::Pick(first), RebaseTodo::Squash(second)],
+            )
+            .unwrap();
+
+        let log = tmp_repo.get_authorship_log().unwrap();
+        assert_eq!(ai_lines(&log, "app.rs"), BTreeSet::from([2]));
+    }
+
+    #[test]
+    fn test_interactive_rebase_drop_keeps_ai_lines_of_remaining_commits() {
+        let tmp_repo = TmpRepo::new().unwrap();
+        let base = commit_lines(&tmp_repo, "a\n", None, "base");
+        let human = commit_lines(&tmp_repo, "human\na\n", None, "human");
+        let ai = commit_lines(&tmp_repo, "human\na\nai\n", Some("session_1"), "ai");
+
+        tmp_repo
+            .rebase_interactive(&base, &[RebaseTodo::Drop(human), RebaseTodo::Pick(ai)])
+            .unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(tmp_repo.path().join("app.rs")).unwrap(),
+            "a\nai\n"
+        );
+        let log = tmp_repo.get_authorship_log().unwrap();
+        assert_eq!(ai_lines(&log, "app.rs"), BTreeSet::from([2]));
+    }
+
+    #[test]
+    fn test_checkpoint_recovers_working_log_orphaned_by_external_checkout() {
+        let tmp_repo = TmpRepo::new().unwrap();
+        let base = commit_lines(&tmp_repo, "human\n", None, "base");
+        let moved_from = commit_lines(&tmp_repo, "human\nmore\n", None, "more");
+
+        tmp_repo
+            .write_file("ai.rs", "ai one\nai two\n", true)
+            .unwrap();
+        tmp_repo
+            .trigger_checkpoint_with_ai("session_1", Some("test_model"), Some("test_tool"))
+            .unwrap();
+
+        // Plain git, so no hook sees HEAD move and the working log stays keyed to `moved_from`
+        tmp_repo
+            .git_command(&["checkout", "--quiet", &base])
+            .unwrap();
+        let storage = &tmp_repo.gitai_repo().storage;
+        assert_eq!(storage.working_logs_with_data(), vec![moved_from.clone()]);
+
+        tmp_repo
+            .trigger_checkpoint_with_author("test_user")
+            .unwrap();
+        assert!(
+            !storage
+                .working_logs_with_data()
+                .iter()
+                .any(|sha| sha == &moved_from)
+        );
+
+        let log = tmp_repo.commit_with_message("add ai").unwrap();
+        assert_eq!(ai_lines(&log, "ai.rs"), BTreeSet::from([1, 2]));
+        assert_eq!(log.metadata.prompts.len(), 1);
+    }
+}
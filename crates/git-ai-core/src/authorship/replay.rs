@@ -0,0 +1,237 @@
+//! Re-simulates attribution over a repository's recent history, for regression and
+//! performance testing on real code rather than fixtures.
+//!
+//! Each commit's diff against its first parent is fed to `AttributionTracker` as a synthetic
+//! checkpoint by the commit's author, carrying attributions forward from commit to commit.
+//! Every result is checked with `check_attribution_invariants` and every update is timed.
+
+use crate::authorship::attribution_tracker::{
+    Attribution, AttributionConfig, AttributionTracker, attributions_to_line_attributions,
+    check_attribution_invariants,
+};
+use crate::authorship::file_text;
+use crate::config::Config;
+use crate::error::GitAiError;
+use crate::git::repository::Repository;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::Instant;
+
+/// Author of content that predates the replayed range
+pub const REPLAY_BASE_AUTHOR: &str = "base";
+
+/// How many of the slowest updates a report keeps
+const SLOWEST_UPDATES: usize = 10;
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReplayReport {
+    /// Commits replayed, oldest first
+    pub commits: Vec<String>,
+    /// Attribution updates performed (one per changed text file per commit)
+    pub updates: usize,
+    /// Changed files skipped because they aren't UTF-8 text
+    pub skipped_files: usize,
+    /// Time spent in `update_attributions`, in milliseconds
+    pub update_ms: f64,
+    /// Wall-clock time of the whole replay, including git, in milliseconds
+    pub total_ms: f64,
+    /// Slowest updates, slowest first
+    pub slowest: Vec<ReplayUpdate>,
+    pub violations: Vec<ReplayViolation>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayUpdate {
+    pub commit: String,
+    pub file: String,
+    /// Size of the new content in bytes
+    pub bytes: usize,
+    pub ms: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayViolation {
+    pub commit: String,
+    pub file: String,
+    pub message: String,
+}
+
+struct ReplayCommit {
+    sha: String,
+    parent: Option<String>,
+    author: String,
+    timestamp_ms: u128,
+}
+
+/// Replays the last `commit_count` first-parent commits of HEAD
+pub fn replay_history(repo: &Repository, commit_count: usize) -> Result<ReplayReport, GitAiError> {
+    let started = Instant::now();
+    let tracker = AttributionTracker::with_config(
+        AttributionConfig::default()
+            .with_move_timestamp_policy(Config::get().move_timestamp_policy()),
+    );
+    let mut report = ReplayReport::default();
+    // Latest content and attributions of every file touched so far
+    let mut files: HashMap<String, (String, Vec<Attribution>)> = HashMap::new();
+
+    for commit in recent_commits(repo, commit_count)? {
+        let changed = match &commit.parent {
+            Some(parent) => repo.diff_changed_files(parent, &commit.sha)?,
+            None => repo
+                .list_commit_files(&commit.sha, None)?
+                .into_iter()
+                .collect(),
+        };
+
+        for file in changed {
+            let Some(new_content) = text_at(repo, &commit.sha, &file)? else {
+                report.skipped_files += 1;
+                files.remove(&file);
+                continue;
+            };
+            let (old_content, old_attributions) = match files.remove(&file) {
+                Some(state) => state,
+                None => {
+                    let content = match &commit.parent {
+                        Some(parent) => text_at(repo, parent, &file)?.unwrap_or_default(),
+                        None => String::new(),
+                    };
+                    let attributions = if content.is_empty() {
+                        Vec::new()
+                    } else {
+                        vec![Attribution::new(
+                            0,
+                            content.len(),
+                            REPLAY_BASE_AUTHOR.to_string(),
+                            0,
+                        )]
+                    };
+                    (content, attributions)
+                }
+            };
+
+            let update_started = Instant::now();
+            let attributions = tracker.update_attributions(
+                &old_content,
+                &new_content,
+                &old_attributions,
+                &commit.author,
+                commit.timestamp_ms,
+            );
+            let ms = update_started.elapsed().as_secs_f64() * 1000.0;
+            report.updates += 1;
+            report.update_ms += ms;
+            report.slowest.push(ReplayUpdate {
+                commit: commit.sha.clone(),
+                file: file.clone(),
+                bytes: new_content.len(),
+                ms,
+            });
+
+            let violation = match &attributions {
+                Ok(attributions) => check_attribution_invariants(&new_content, attributions, true)
+                    .and_then(|_| check_line_attributions(&new_content, attributions)),
+                Err(e) => Err(format!("update_attributions failed: {}", e)),
+            };
+            if let Err(message) = violation {
+                report.violations.push(ReplayViolation {
+                    commit: commit.sha.clone(),
+                    file: file.clone(),
+                    message,
+                });
+            }
+            files.insert(file, (new_content, attributions.unwrap_or_default()));
+        }
+        report.commits.push(commit.sha);
+    }
+
+    report
+        .slowest
+        .sort_by(|a, b| b.ms.partial_cmp(&a.ms).unwrap_or(std::cmp::Ordering::Equal));
+    report.slowest.truncate(SLOWEST_UPDATES);
+    report.total_ms = started.elapsed().as_secs_f64() * 1000.0;
+    Ok(report)
+}
+
+/// The last `count` first-parent commits of HEAD, oldest first
+fn recent_commits(repo: &Repository, count: usize) -> Result<Vec<ReplayCommit>, GitAiError> {
+    let output = repo.git(&[
+        "log",
+        "--first-parent",
+        "--reverse",
+        "-n",
+        &count.to_string(),
+        "--format=%H%x00%P%x00%an%x00%ct",
+        "HEAD",
+    ])?;
+    Ok(output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\0');
+            let sha = fields.next()?.to_string();
+            let parent = fields.next()?.split_whitespace().next().map(str::to_string);
+            let author = fields.next()?.to_string();
+            let seconds: u128 = fields.next()?.parse().ok()?;
+            Some(ReplayCommit {
+                sha,
+                parent,
+                author,
+                timestamp_ms: seconds * 1000,
+            })
+        })
+        .collect())
+}
+
+/// Content of `file` at `commit`: empty if it doesn't exist there, `None` if it isn't text
+fn text_at(repo: &Repository, commit: &str, file: &str) -> Result<Option<String>, GitAiError> {
+    let bytes = repo.get_file_content(file, commit).unwrap_or_default();
+    Ok(file_text::decode_text(&bytes))
+}
+
+/// Line attributions derived from `attributions` must stay within the file
+fn check_line_attributions(content: &str, attributions: &[Attribution]) -> Result<(), String> {
+    let line_count = content.lines().count() as u32;
+    for line in attributions_to_line_attributions(&attributions.to_vec(), content) {
+        if line.start_line == 0 || line.start_line > line.end_line || line.end_line > line_count {
+            return Err(format!(
+                "line attribution {}-{} is out of bounds for {} lines",
+                line.start_line, line.end_line, line_count
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::test_utils::TmpRepo;
+
+    #[test]
+    fn test_replay_history_reports_updates_without_violations() {
+        let tmp_repo = TmpRepo::new().unwrap();
+        tmp_repo
+            .write_file("src/lib.rs", "fn one() {}\n", true)
+            .unwrap();
+        tmp_repo.commit_with_message("one").unwrap();
+        tmp_repo
+            .write_file("src/lib.rs", "fn one() {}\nfn two() {}\n", true)
+            .unwrap();
+        tmp_repo.write_file("logo.bin", "\0\u{1}", true).unwrap();
+        tmp_repo.commit_with_message("two").unwrap();
+        tmp_repo
+            .write_file("src/lib.rs", "fn two() {}\nfn one() {}\n// é\n", true)
+            .unwrap();
+        tmp_repo.commit_with_message("three").unwrap();
+
+        let report = replay_history(tmp_repo.gitai_repo(), 10).unwrap();
+        assert_eq!(report.commits.len(), 3);
+        assert_eq!(report.updates, 3);
+        assert_eq!(report.skipped_files, 1);
+        assert!(report.violations.is_empty(), "{:?}", report.violations);
+        assert_eq!(report.slowest.len(), 3);
+
+        let report = replay_history(tmp_repo.gitai_repo(), 1).unwrap();
+        assert_eq!(report.commits, vec![tmp_repo.head_commit_sha().unwrap()]);
+    }
+}
@@ -0,0 +1,104 @@
+//! The periodic summary `git-ai notify` posts: how much of the code committed over the last few
+//! days AI wrote, how that moved from the period before, and which agents wrote it.
+
+use crate::authorship::acceptance::AcceptanceCounts;
+use crate::authorship::stats::{CommitStats, stats_for_commit_stats};
+use crate::error::GitAiError;
+use crate::git::refs::get_authorship;
+use crate::git::repository::Repository;
+use serde::Serialize;
+use std::collections::BTreeSet;
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PeriodReport {
+    /// Branch or revision the commits were read from
+    pub rev: String,
+    pub days: u32,
+    /// Unix times the period starts and ends at
+    pub since: i64,
+    pub until: i64,
+    /// Non-merge commits in the period
+    pub commits: usize,
+    /// The period's commits' stats, summed
+    pub stats: CommitStats,
+    /// Share of the lines added in the period that AI wrote; None if none were added
+    pub ai_percent: Option<u32>,
+    /// The same share over the period of as many days before this one
+    pub previous_ai_percent: Option<u32>,
+    /// The AI share of each day's added lines, oldest first
+    pub daily_ai_percent: Vec<Option<u32>>,
+    /// AI sessions with lines in the period's commits
+    pub sessions: usize,
+    pub acceptance: AcceptanceCounts,
+}
+
+fn ai_percent(stats: &CommitStats) -> Option<u32> {
+    (stats.git_diff_added_lines > 0).then(|| {
+        (stats.ai_additions.min(stats.git_diff_added_lines) as u64 * 100
+            / stats.git_diff_added_lines as u64) as u32
+    })
+}
+
+/// The report of the commits `rev` reaches that were made in the `days` days before `now`
+/// (unix time)
+pub fn period_report(
+    repo: &Repository,
+    rev: &str,
+    days: u32,
+    now: i64,
+) -> Result<PeriodReport, GitAiError> {
+    let since = now - days as i64 * SECONDS_PER_DAY;
+    let previous_since = since - days as i64 * SECONDS_PER_DAY;
+    let output = repo.git(&[
+        "log",
+        "--no-merges",
+        "--format=%H %ct",
+        &format!("--since={}", previous_since),
+        rev,
+    ])?;
+
+    let mut report = PeriodReport {
+        rev: rev.to_string(),
+        days,
+        since,
+        until: now,
+        ..Default::default()
+    };
+    let mut previous = CommitStats::default();
+    let mut daily = vec![CommitStats::default(); days as usize];
+    let mut sessions = BTreeSet::new();
+    for line in output.lines() {
+        let Some((sha, time)) = line.split_once(' ') else {
+            continue;
+        };
+        let time: i64 = time.trim().parse().unwrap_or(0);
+        if time > now || time < previous_since {
+            continue;
+        }
+        let stats = stats_for_commit_stats(repo, sha, rev, false)?;
+        if time < since {
+            previous.add(&stats);
+            continue;
+        }
+
+        report.commits += 1;
+        report.stats.add(&stats);
+        let day = (((time - since) / SECONDS_PER_DAY) as usize).min(daily.len() - 1);
+        daily[day].add(&stats);
+        if let Some(log) = get_authorship(repo, sha) {
+            for (hash, record) in &log.metadata.prompts {
+                sessions.insert(hash.clone());
+                report.acceptance.total_additions += record.total_additions;
+                report.acceptance.accepted_lines += record.accepted_lines;
+                report.acceptance.overriden_lines += record.overriden_lines;
+            }
+        }
+    }
+    report.ai_percent = ai_percent(&report.stats);
+    report.previous_ai_percent = ai_percent(&previous);
+    report.daily_ai_percent = daily.iter().map(ai_percent).collect();
+    report.sessions = sessions.len();
+    Ok(report)
+}
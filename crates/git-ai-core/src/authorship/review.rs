@@ -0,0 +1,344 @@
+use serde::{Deserialize, Serialize};
+
+use crate::authorship::authorship_log::LineRange;
+use crate::authorship::authorship_log_serialization::AuthorshipLog;
+use crate::authorship::generated_files::GeneratedFileFilter;
+use crate::authorship::query::{QueriedAuthor, query_attribution};
+use crate::error::GitAiError;
+use crate::git::path_scope::PathScope;
+use crate::git::refs::{AI_REVIEW_REFNAME, notes_add_to_ref, ref_exists, show_note};
+use crate::git::repository::Repository;
+
+/// Words in a path that make a change to it riskier, as (word, reason). Paths are split into
+/// words at `/`, `.`, `_` and `-`, and a trailing "s" is ignored.
+const SENSITIVE_WORDS: &[(&str, &str)] = &[
+    ("auth", "auth"),
+    ("authn", "auth"),
+    ("authz", "auth"),
+    ("oauth", "auth"),
+    ("authentication", "auth"),
+    ("authorization", "auth"),
+    ("login", "auth"),
+    ("permission", "access control"),
+    ("security", "security"),
+    ("crypto", "crypto"),
+    ("cryptography", "crypto"),
+    ("secret", "secrets"),
+    ("password", "secrets"),
+    ("credential", "secrets"),
+    ("payment", "payments"),
+    ("billing", "payments"),
+    ("migration", "migrations"),
+    ("sql", "migrations"),
+    ("workflow", "ci"),
+    ("dockerfile", "infrastructure"),
+    ("terraform", "infrastructure"),
+    ("tf", "infrastructure"),
+];
+
+/// How much more a line in a sensitive path weighs than any other line
+const SENSITIVE_WEIGHT: u32 = 3;
+
+/// Branches a branch is compared against when no base is given, in order of preference
+const DEFAULT_BASES: &[&str] = &[
+    "origin/HEAD",
+    "main",
+    "master",
+    "origin/main",
+    "origin/master",
+];
+
+/// Consecutive lines added by the range that AI wrote and no human has touched since
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AiHunk {
+    pub file: String,
+    /// 1-indexed, inclusive, in the file at the head of the range
+    pub start: u32,
+    pub end: u32,
+    pub lines: u32,
+    /// Commit that added the first line
+    pub commit: String,
+    pub prompt: String,
+    pub tool: String,
+    pub model: String,
+    /// Why the path makes the hunk riskier, if it does
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sensitive: Option<String>,
+    /// Higher is reviewed first: the line count, weighted up for sensitive paths
+    pub risk: u32,
+}
+
+/// The base and head commits of `spec`: `base..head`, or a branch (HEAD if None) from where it
+/// forked off `base`, else off the repository's default branch
+pub fn review_range(
+    repo: &Repository,
+    spec: Option<&str>,
+    base: Option<&str>,
+) -> Result<(String, String), GitAiError> {
+    let spec = spec.unwrap_or("HEAD");
+    if spec.contains("...") {
+        return Err(GitAiError::Generic(format!(
+            "'{}': symmetric ranges aren't supported, use base..head",
+            spec
+        )));
+    }
+    if let Some((from, to)) = spec.split_once("..") {
+        // `a..` and `..b` leave out HEAD, as in git
+        let or_head = |rev: &str| {
+            if rev.is_empty() {
+                "HEAD".to_string()
+            } else {
+                rev.to_string()
+            }
+        };
+        return Ok((
+            repo.revparse_single(&or_head(from))?.id(),
+            repo.revparse_single(&or_head(to))?.id(),
+        ));
+    }
+
+    let head = repo.revparse_single(spec)?.id();
+    let base = match base {
+        Some(base) => repo.revparse_single(base)?.id(),
+        None => DEFAULT_BASES
+            .iter()
+            .find_map(|base| repo.revparse_single(base).ok())
+            .ok_or_else(|| {
+                GitAiError::Generic(
+                    "No default branch to compare with, pass --base or a base..head range"
+                        .to_string(),
+                )
+            })?
+            .id(),
+    };
+    Ok((repo.merge_base(base, head.clone())?, head))
+}
+
+/// The AI hunks `base..head` adds that no human has modified or marked reviewed, riskiest
+/// first. A run of added lines counts as one hunk; it's left out if a human wrote or edited any
+/// of its lines, since they've then been through it, or once every line is marked reviewed.
+pub fn unreviewed_ai_hunks(
+    repo: &Repository,
+    base: &str,
+    head: &str,
+    include_generated: bool,
+) -> Result<Vec<AiHunk>, GitAiError> {
+    let mut added = repo.diff_added_lines(base, head, None)?;
+    let scope = PathScope::for_repo(repo);
+    added.retain(|path, lines| scope.contains(path) && !lines.is_empty());
+    if !include_generated {
+        let filter = GeneratedFileFilter::for_paths(repo, added.keys()).unwrap_or_default();
+        added.retain(|path, _| !filter.is_generated(path));
+    }
+
+    let mut hunks = Vec::new();
+    for (path, mut lines) in added {
+        lines.sort_unstable();
+        let range = (lines[0], lines[lines.len() - 1]);
+        let attributed = query_attribution(repo, &path, Some(head), Some(range))?;
+        let sensitive = sensitive_reason(&path);
+
+        for run in consecutive_runs(&lines) {
+            let authors: Vec<_> = attributed
+                .iter()
+                .filter(|queried| run.contains(&queried.line))
+                .collect();
+            if authors.len() != run.len()
+                || authors.iter().any(|queried| {
+                    queried.co_written || matches!(queried.author, QueriedAuthor::Human { .. })
+                })
+                || authors.iter().all(|queried| queried.reviewed_by.is_some())
+            {
+                continue;
+            }
+            let first = authors[0];
+            let QueriedAuthor::Ai {
+                prompt_hash,
+                tool,
+                model,
+            } = &first.author
+            else {
+                continue;
+            };
+            let line_count = run.len() as u32;
+            hunks.push(AiHunk {
+                file: path.clone(),
+                start: run[0],
+                end: run[run.len() - 1],
+                lines: line_count,
+                commit: first.commit.clone().unwrap_or_default(),
+                prompt: prompt_hash.clone(),
+                tool: tool.clone(),
+                model: model.clone(),
+                sensitive: sensitive.map(str::to_string),
+                risk: risk(line_count, sensitive.is_some()),
+            });
+        }
+    }
+    hunks.sort_by(|a, b| {
+        b.risk
+            .cmp(&a.risk)
+            .then_with(|| a.file.cmp(&b.file))
+            .then_with(|| a.start.cmp(&b.start))
+    });
+    Ok(hunks)
+}
+
+/// Sorted line numbers split where they stop being consecutive: [1, 2, 5] is [[1, 2], [5]]
+fn consecutive_runs(lines: &[u32]) -> Vec<&[u32]> {
+    let mut runs = Vec::new();
+    let mut start = 0;
+    for i in 1..=lines.len() {
+        if i == lines.len() || lines[i] != lines[i - 1] + 1 {
+            runs.push(&lines[start..i]);
+            start = i;
+        }
+    }
+    runs
+}
+
+fn sensitive_reason(path: &str) -> Option<&'static str> {
+    let path = path.to_lowercase();
+    path.split(['/', '.', '_', '-'])
+        .map(|word| word.strip_suffix('s').unwrap_or(word))
+        .find_map(|word| {
+            SENSITIVE_WORDS
+                .iter()
+                .find(|(sensitive, _)| *sensitive == word)
+                .map(|(_, reason)| *reason)
+        })
+}
+
+fn risk(lines: u32, sensitive: bool) -> u32 {
+    if sensitive {
+        lines * SENSITIVE_WEIGHT
+    } else {
+        lines
+    }
+}
+
+/// A reviewer's sign-off on lines a commit added. A commit's marks are kept in its note under
+/// refs/notes/ai-review, one JSON object per line, so notes from different reviewers merge with
+/// `git notes merge -s cat_sort_uniq`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReviewMark {
+    pub file: String,
+    /// 1-indexed, inclusive, in the file as of the marked commit; None for the whole file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lines: Option<(u32, u32)>,
+    pub by: String,
+    /// Unix time of the mark
+    pub at: i64,
+}
+
+impl ReviewMark {
+    pub fn covers(&self, file: &str, line: u32) -> bool {
+        self.file == file
+            && self
+                .lines
+                .is_none_or(|(start, end)| start <= line && line <= end)
+    }
+}
+
+/// The review marks of a commit, oldest first. Lines that don't parse are skipped.
+pub fn review_marks(repo: &Repository, commit: &str) -> Vec<ReviewMark> {
+    show_note(repo, AI_REVIEW_REFNAME, commit)
+        .map(|note| {
+            note.lines()
+                .filter_map(|line| serde_json::from_str(line).ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Whether anyone has marked anything reviewed, so readers can skip looking for marks
+pub fn has_review_marks(repo: &Repository) -> bool {
+    ref_exists(repo, &format!("refs/notes/{}", AI_REVIEW_REFNAME))
+}
+
+/// Adds a mark to the commit's review note, keeping the marks already there
+pub fn add_review_mark(
+    repo: &Repository,
+    commit: &str,
+    mark: &ReviewMark,
+) -> Result<(), GitAiError> {
+    let mut note = show_note(repo, AI_REVIEW_REFNAME, commit).unwrap_or_default();
+    if !note.is_empty() {
+        note.push('\n');
+    }
+    note.push_str(&serde_json::to_string(mark)?);
+    note.push('\n');
+    notes_add_to_ref(repo, AI_REVIEW_REFNAME, commit, &note)
+}
+
+/// Who last marked the line reviewed, from the marks of the commit that added it
+pub fn reviewer<'a>(marks: &'a [ReviewMark], file: &str, line: u32) -> Option<&'a str> {
+    marks
+        .iter()
+        .rev()
+        .find(|mark| mark.covers(file, line))
+        .map(|mark| mark.by.as_str())
+}
+
+/// How many of the AI lines in a commit's authorship log its review marks cover
+pub fn reviewed_ai_lines(log: &AuthorshipLog, marks: &[ReviewMark]) -> u32 {
+    let mut reviewed = 0;
+    for file in &log.attestations {
+        for entry in &file.entries {
+            if !log.metadata.prompts.contains_key(&entry.hash) {
+                continue;
+            }
+            reviewed += entry
+                .line_ranges
+                .iter()
+                .flat_map(LineRange::expand)
+                .filter(|line| {
+                    !entry.overridden_lines.iter().any(|r| r.contains(*line))
+                        && reviewer(marks, &file.file_path, *line).is_some()
+                })
+                .count() as u32;
+        }
+    }
+    reviewed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_consecutive_runs() {
+        let runs = consecutive_runs(&[1, 2, 3, 7, 9, 10]);
+        assert_eq!(runs, vec![&[1, 2, 3][..], &[7][..], &[9, 10][..]]);
+        assert!(consecutive_runs(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_sensitive_paths_weigh_more() {
+        assert_eq!(sensitive_reason("src/Auth/login.rs"), Some("auth"));
+        assert_eq!(
+            sensitive_reason("db/migrations/001_init.sql"),
+            Some("migrations")
+        );
+        assert_eq!(sensitive_reason(".github/workflows/ci.yml"), Some("ci"));
+        assert_eq!(sensitive_reason("src/authorship/tokens.rs"), None);
+        assert!(risk(5, true) > risk(10, false));
+    }
+
+    #[test]
+    fn test_reviewer_of_line() {
+        let mark = |lines, by: &str| ReviewMark {
+            file: "src/lib.rs".to_string(),
+            lines,
+            by: by.to_string(),
+            at: 0,
+        };
+        let marks = vec![mark(None, "jane"), mark(Some((3, 5)), "joe")];
+        assert_eq!(reviewer(&marks, "src/lib.rs", 1), Some("jane"));
+        assert_eq!(reviewer(&marks, "src/lib.rs", 4), Some("joe"));
+        assert_eq!(reviewer(&marks, "src/main.rs", 4), None);
+
+        let json = serde_json::to_string(&marks[1]).unwrap();
+        assert_eq!(serde_json::from_str::<ReviewMark>(&json).unwrap(), marks[1]);
+    }
+}
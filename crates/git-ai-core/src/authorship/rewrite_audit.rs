@@ -0,0 +1,252 @@
+//! Audit trail of history rewrites. For each commit a rebase, cherry-pick or amend wrote a new
+//! authorship log for, records how the attestations of that log came about compared with the log
+//! of the commit it was rewritten from. `git-ai explain-rewrite` prints it. Audits are kept
+//! locally in `.git/ai/rewrite_audits`, not in the notes.
+
+use crate::authorship::authorship_log::LineRange;
+use crate::authorship::authorship_log_serialization::AuthorshipLog;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+/// How a rewrite moved the lines of one attestation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RewriteOutcome {
+    /// Attested on the same line by the original commit
+    Copied,
+    /// Carried through the rewrite's diff from blame of the history before the rewrite, onto a
+    /// line the original commit didn't attest for the same author
+    Reconstructed,
+    /// Left unattributed by the diff, then matched by content to the history before the rewrite
+    /// or taken from a recorded conflict resolution
+    Restored,
+    /// Attested by the original commit but by no line of the rewritten one
+    Dropped,
+}
+
+impl RewriteOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RewriteOutcome::Copied => "copied",
+            RewriteOutcome::Reconstructed => "reconstructed",
+            RewriteOutcome::Restored => "restored",
+            RewriteOutcome::Dropped => "dropped",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RewriteAuditEntry {
+    pub file_path: String,
+    /// Prompt session hash or author class of the attestation
+    pub hash: String,
+    pub outcome: RewriteOutcome,
+    /// Lines of the rewritten commit. Dropped lines are only counted, as they no longer exist.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub lines: Vec<LineRange>,
+    pub line_count: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RewriteAudit {
+    /// The commit the rewrite created
+    pub commit: String,
+    /// `rebase`, `cherry-pick` or `amend`
+    pub operation: String,
+    /// Commits from before the rewrite that became `commit`. Empty when the rewrite didn't map
+    /// commits one to one and none could be matched, in which case nothing counts as copied or
+    /// dropped.
+    pub rewritten_from: Vec<String>,
+    pub timestamp: u64,
+    pub entries: Vec<RewriteAuditEntry>,
+}
+
+impl RewriteAudit {
+    /// Audits `log`, written for `commit`, against `original_logs`, the logs of the commits in
+    /// `rewritten_from`. `restored` has, per file, the lines of `commit` whose attribution came
+    /// from a fallback instead of through the diff.
+    pub fn new(
+        operation: &str,
+        commit: &str,
+        rewritten_from: Vec<String>,
+        original_logs: &[AuthorshipLog],
+        log: &AuthorshipLog,
+        restored: &HashMap<String, HashSet<u32>>,
+    ) -> Self {
+        // Lines and line count of each (file, hash) before the rewrite
+        let mut before: BTreeMap<(String, String), (BTreeSet<u32>, u32)> = BTreeMap::new();
+        for original_log in original_logs {
+            for (key, lines) in attested_lines(original_log) {
+                let (all_lines, count) = before.entry(key).or_default();
+                *count += lines.len() as u32;
+                all_lines.extend(lines);
+            }
+        }
+
+        let mut entries = Vec::new();
+        let mut after_counts: HashMap<(String, String), u32> = HashMap::new();
+        for ((file_path, hash), lines) in attested_lines(log) {
+            let key = (file_path.clone(), hash.clone());
+            after_counts.insert(key.clone(), lines.len() as u32);
+
+            let restored_in_file = restored.get(&file_path);
+            let before_lines = before.get(&key).map(|(before_lines, _)| before_lines);
+            let mut by_outcome: BTreeMap<RewriteOutcome, Vec<u32>> = BTreeMap::new();
+            for line in lines {
+                let outcome = if restored_in_file.is_some_and(|restored| restored.contains(&line)) {
+                    RewriteOutcome::Restored
+                } else if before_lines.is_some_and(|before_lines| before_lines.contains(&line)) {
+                    RewriteOutcome::Copied
+                } else {
+                    RewriteOutcome::Reconstructed
+                };
+                by_outcome.entry(outcome).or_default().push(line);
+            }
+            for (outcome, lines) in by_outcome {
+                entries.push(RewriteAuditEntry {
+                    file_path: file_path.clone(),
+                    hash: hash.clone(),
+                    outcome,
+                    line_count: lines.len() as u32,
+                    lines: LineRange::compress_lines(&lines),
+                });
+            }
+        }
+
+        for ((file_path, hash), (_, count)) in before {
+            let after = after_counts
+                .get(&(file_path.clone(), hash.clone()))
+                .copied()
+                .unwrap_or(0);
+            if count > after {
+                entries.push(RewriteAuditEntry {
+                    file_path,
+                    hash,
+                    outcome: RewriteOutcome::Dropped,
+                    lines: Vec::new(),
+                    line_count: count - after,
+                });
+            }
+        }
+        entries.sort_by(|a, b| {
+            (&a.file_path, a.outcome, &a.hash).cmp(&(&b.file_path, b.outcome, &b.hash))
+        });
+
+        RewriteAudit {
+            commit: commit.to_string(),
+            operation: operation.to_string(),
+            rewritten_from,
+            timestamp: crate::utils::now_secs(),
+            entries,
+        }
+    }
+
+    /// Lines per outcome, over all entries
+    pub fn totals(&self) -> BTreeMap<RewriteOutcome, u32> {
+        let mut totals = BTreeMap::new();
+        for entry in &self.entries {
+            *totals.entry(entry.outcome).or_default() += entry.line_count;
+        }
+        totals
+    }
+}
+
+/// The sorted lines each (file, hash) attests in `log`
+fn attested_lines(log: &AuthorshipLog) -> BTreeMap<(String, String), BTreeSet<u32>> {
+    let mut lines: BTreeMap<(String, String), BTreeSet<u32>> = BTreeMap::new();
+    for file in &log.attestations {
+        for entry in &file.entries {
+            lines
+                .entry((file.file_path.clone(), entry.hash.clone()))
+                .or_default()
+                .extend(entry.line_ranges.iter().flat_map(|range| range.expand()));
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authorship::authorship_log_serialization::{AttestationEntry, FileAttestation};
+
+    fn log(attestations: &[(&str, &str, Vec<LineRange>)]) -> AuthorshipLog {
+        let mut log = AuthorshipLog::new();
+        for (file_path, hash, ranges) in attestations {
+            let mut file = FileAttestation::new(file_path.to_string());
+            file.entries
+                .push(AttestationEntry::new(hash.to_string(), ranges.clone()));
+            log.attestations.push(file);
+        }
+        log
+    }
+
+    fn outcomes(audit: &RewriteAudit) -> Vec<(&str, RewriteOutcome, u32)> {
+        audit
+            .entries
+            .iter()
+            .map(|entry| (entry.file_path.as_str(), entry.outcome, entry.line_count))
+            .collect()
+    }
+
+    #[test]
+    fn test_audit_classifies_attested_lines() {
+        let original = log(&[
+            ("a.rs", "aaaaaaa", vec![LineRange::Range(1, 3)]),
+            ("b.rs", "bbbbbbb", vec![LineRange::Range(1, 2)]),
+            ("c.rs", "ccccccc", vec![LineRange::Single(4)]),
+        ]);
+        let rewritten = log(&[
+            ("a.rs", "aaaaaaa", vec![LineRange::Range(1, 3)]),
+            ("b.rs", "bbbbbbb", vec![LineRange::Range(5, 6)]),
+            ("c.rs", "ccccccc", vec![LineRange::Range(3, 5)]),
+        ]);
+        let restored = HashMap::from([("c.rs".to_string(), HashSet::from([5]))]);
+
+        let audit = RewriteAudit::new(
+            "rebase",
+            "new",
+            vec!["old".to_string()],
+            &[original],
+            &rewritten,
+            &restored,
+        );
+        assert_eq!(
+            outcomes(&audit),
+            vec![
+                ("a.rs", RewriteOutcome::Copied, 3),
+                ("b.rs", RewriteOutcome::Reconstructed, 2),
+                ("c.rs", RewriteOutcome::Copied, 1),
+                ("c.rs", RewriteOutcome::Reconstructed, 1),
+                ("c.rs", RewriteOutcome::Restored, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_audit_counts_dropped_lines() {
+        let original = log(&[
+            ("a.rs", "aaaaaaa", vec![LineRange::Range(1, 4)]),
+            ("gone.rs", "bbbbbbb", vec![LineRange::Single(1)]),
+        ]);
+        let rewritten = log(&[("a.rs", "aaaaaaa", vec![LineRange::Range(1, 2)])]);
+
+        let audit = RewriteAudit::new(
+            "amend",
+            "new",
+            vec!["old".to_string()],
+            &[original],
+            &rewritten,
+            &HashMap::new(),
+        );
+        assert_eq!(
+            outcomes(&audit),
+            vec![
+                ("a.rs", RewriteOutcome::Copied, 2),
+                ("a.rs", RewriteOutcome::Dropped, 2),
+                ("gone.rs", RewriteOutcome::Dropped, 1),
+            ]
+        );
+        assert_eq!(audit.totals()[&RewriteOutcome::Dropped], 3);
+    }
+}
@@ -1,6 +1,13 @@
+use crate::authorship::author_class::AuthorClass;
 use crate::authorship::authorship_log::LineRange;
+use crate::authorship::authorship_log_serialization::AuthorshipLog;
+use crate::authorship::generated_files::GeneratedFileFilter;
+use crate::authorship::review::{has_review_marks, review_marks, reviewed_ai_lines};
 use crate::authorship::transcript::Message;
+use crate::config::Config;
 use crate::error::GitAiError;
+use crate::git::lfs::{LfsFilter, LfsMode, smudged_numstat};
+use crate::git::path_scope::PathScope;
 use crate::git::refs::get_authorship;
 use crate::git::repository::Repository;
 use serde::{Deserialize, Serialize};
@@ -22,7 +29,7 @@ pub struct ToolModelHeadlineStats {
     pub time_waiting_for_ai: u64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct CommitStats {
     #[serde(default)]
     pub human_additions: u32, // Number of lines committed with human attribution (full and/or mixed)
@@ -43,13 +50,66 @@ pub struct CommitStats {
     #[serde(default)]
     pub git_diff_added_lines: u32,
     #[serde(default)]
+    pub ai_low_confidence: u32, // Number of AI lines whose attribution isn't certain (fuzzy moves, reconstructed from history)
+    #[serde(default)]
+    pub ai_overridden_kept: u32, // Number of mixed lines that kept their AI attribution under the override policy
+    #[serde(default)]
+    pub ai_reviewed: u32, // Number of AI lines a human has marked reviewed with `git-ai review mark`
+    #[serde(default)]
+    pub possible_ai_additions: u32, // Number of human lines the large-paste heuristic flagged as possibly pasted from an AI
+    #[serde(default)]
+    pub unknown_additions: u32, // Number of lines no checkpoint saw written, left unattributed at commit
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub class_additions: BTreeMap<String, u32>, // Number of lines of each author class other than unknown (codegen, import...)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ai_commit_message: Option<String>, // Tool and model of the agent that wrote the commit message, if one did
+    #[serde(default)]
     pub tool_model_breakdown: BTreeMap<String, ToolModelHeadlineStats>,
 }
 
+impl CommitStats {
+    /// Adds the counts of `other`, a commit in another repository, to these. The commit message
+    /// agent is per commit, so it's dropped.
+    pub fn add(&mut self, other: &CommitStats) {
+        self.human_additions += other.human_additions;
+        self.mixed_additions += other.mixed_additions;
+        self.ai_additions += other.ai_additions;
+        self.ai_accepted += other.ai_accepted;
+        self.total_ai_additions += other.total_ai_additions;
+        self.total_ai_deletions += other.total_ai_deletions;
+        self.time_waiting_for_ai += other.time_waiting_for_ai;
+        self.git_diff_deleted_lines += other.git_diff_deleted_lines;
+        self.git_diff_added_lines += other.git_diff_added_lines;
+        self.ai_low_confidence += other.ai_low_confidence;
+        self.ai_overridden_kept += other.ai_overridden_kept;
+        self.ai_reviewed += other.ai_reviewed;
+        self.possible_ai_additions += other.possible_ai_additions;
+        self.unknown_additions += other.unknown_additions;
+        for (class, lines) in &other.class_additions {
+            *self.class_additions.entry(class.clone()).or_default() += lines;
+        }
+        self.ai_commit_message = None;
+        for (tool_model, stats) in &other.tool_model_breakdown {
+            let total = self
+                .tool_model_breakdown
+                .entry(tool_model.clone())
+                .or_default();
+            total.ai_additions += stats.ai_additions;
+            total.mixed_additions += stats.mixed_additions;
+            total.ai_accepted += stats.ai_accepted;
+            total.total_ai_additions += stats.total_ai_additions;
+            total.total_ai_deletions += stats.total_ai_deletions;
+            total.time_waiting_for_ai += stats.time_waiting_for_ai;
+        }
+    }
+}
+
 pub fn stats_command(
     repo: &Repository,
     commit_sha: Option<&str>,
     json: bool,
+    include_generated: bool,
+    include_inferred: bool,
 ) -> Result<(), GitAiError> {
     let (target, refname) = if let Some(sha) = commit_sha {
         // Validate that the commit exists using revparse_single
@@ -77,7 +137,10 @@ pub fn stats_command(
         target, refname
     );
 
-    let stats = stats_for_commit_stats(repo, &target, &refname)?;
+    // Notes `git-ai backfill` inferred can be left out, counting the commit as human
+    let authorship_log = get_authorship(repo, &target)
+        .filter(|log| include_inferred || log.metadata.inferred.is_none());
+    let stats = stats_for_commit_with_log(repo, &target, authorship_log, include_generated)?;
 
     if json {
         let json_str = serde_json::to_string(&stats)?;
@@ -289,6 +352,55 @@ pub fn write_stats_to_terminal(stats: &CommitStats, print: bool) -> String {
             println!("{}", ai_acceptance_str);
         }
     }
+    if stats.ai_reviewed > 0 {
+        let reviewed_str = format!(
+            "     \x1b[90m{} of {} AI lines reviewed\x1b[0m",
+            stats.ai_reviewed, stats.ai_accepted
+        );
+        output.push_str(&reviewed_str);
+        output.push('\n');
+        if print {
+            println!("{}", reviewed_str);
+        }
+    }
+    if stats.possible_ai_additions > 0 {
+        let possible_ai_str = format!(
+            "     \x1b[90m{} human lines possibly pasted from AI\x1b[0m",
+            stats.possible_ai_additions
+        );
+        output.push_str(&possible_ai_str);
+        output.push('\n');
+        if print {
+            println!("{}", possible_ai_str);
+        }
+    }
+    if stats.unknown_additions > 0 {
+        let unknown_str = format!(
+            "     \x1b[90m{} lines of unknown authorship\x1b[0m",
+            stats.unknown_additions
+        );
+        output.push_str(&unknown_str);
+        output.push('\n');
+        if print {
+            println!("{}", unknown_str);
+        }
+    }
+    for (class, lines) in &stats.class_additions {
+        let class_str = format!("     \x1b[90m{} {} lines\x1b[0m", lines, class);
+        output.push_str(&class_str);
+        output.push('\n');
+        if print {
+            println!("{}", class_str);
+        }
+    }
+    if let Some(tool_model) = &stats.ai_commit_message {
+        let commit_message_str = format!("     \x1b[90mcommit message by {}\x1b[0m", tool_model);
+        output.push_str(&commit_message_str);
+        output.push('\n');
+        if print {
+            println!("{}", commit_message_str);
+        }
+    }
     return output;
 }
 
@@ -423,6 +535,29 @@ pub fn write_stats_to_markdown(stats: &CommitStats) -> String {
         format!("{} second{}", seconds, if seconds == 1 { "" } else { "s" })
     };
     output.push_str(&format!("- {} waiting for AI \n", time_str));
+    if stats.mixed_additions > 0 {
+        let kept = if stats.ai_overridden_kept > 0 {
+            format!(" ({} kept AI attribution)", stats.ai_overridden_kept)
+        } else {
+            String::new()
+        };
+        output.push_str(&format!(
+            "- {} AI line{} edited by humans before committing{}\n",
+            stats.mixed_additions,
+            if stats.mixed_additions == 1 { "" } else { "s" },
+            kept
+        ));
+    }
+    if stats.ai_low_confidence > 0 {
+        output.push_str(&format!(
+            "- {} AI line{} with approximate attribution (moved with edits or rebuilt from history)\n",
+            stats.ai_low_confidence,
+            if stats.ai_low_confidence == 1 { "" } else { "s" }
+        ));
+    }
+    if let Some(tool_model) = &stats.ai_commit_message {
+        output.push_str(&format!("- Commit message written by {}\n", tool_model));
+    }
     // Find top model by accepted lines
     if !stats.tool_model_breakdown.is_empty() {
         if let Some((model_name, model_stats)) = stats
@@ -457,6 +592,13 @@ pub fn stats_from_authorship_log(
         total_ai_additions: 0,
         total_ai_deletions: 0,
         time_waiting_for_ai: 0,
+        ai_low_confidence: 0,
+        ai_overridden_kept: 0,
+        ai_reviewed: 0,
+        possible_ai_additions: 0,
+        unknown_additions: 0,
+        class_additions: BTreeMap::new(),
+        ai_commit_message: None,
         tool_model_breakdown: BTreeMap::new(),
         git_diff_deleted_lines,
         git_diff_added_lines,
@@ -477,10 +619,46 @@ pub fn stats_from_authorship_log(
                     })
                     .sum();
 
+                match log.author_class(&entry.hash) {
+                    Some(AuthorClass::PossibleAi) => {
+                        commit_stats.possible_ai_additions += lines_in_entry;
+                        continue;
+                    }
+                    Some(AuthorClass::Unknown) => {
+                        commit_stats.unknown_additions += lines_in_entry;
+                        continue;
+                    }
+                    Some(class) => {
+                        *commit_stats
+                            .class_additions
+                            .entry(class.to_string())
+                            .or_default() += lines_in_entry;
+                        continue;
+                    }
+                    None => {}
+                }
+
                 // Check if this is an AI-generated entry
                 if let Some(prompt_record) = log.metadata.prompts.get(&entry.hash) {
+                    // Lines a human edited count as mixed even when they kept the AI attribution
+                    let overridden_in_entry: u32 = entry
+                        .overridden_lines
+                        .iter()
+                        .map(|range| match range {
+                            crate::authorship::authorship_log::LineRange::Single(_) => 1,
+                            crate::authorship::authorship_log::LineRange::Range(start, end) => {
+                                end - start + 1
+                            }
+                        })
+                        .sum();
+                    let lines_in_entry = lines_in_entry.saturating_sub(overridden_in_entry);
+                    commit_stats.ai_overridden_kept += overridden_in_entry;
+
                     // Count accepted lines (lines that were accepted by the user without any human edits)
                     commit_stats.ai_accepted += lines_in_entry;
+                    if entry.confidence.is_some() {
+                        commit_stats.ai_low_confidence += lines_in_entry;
+                    }
 
                     let key = format!(
                         "{}::{}",
@@ -492,6 +670,18 @@ pub fn stats_from_authorship_log(
             }
         }
 
+        commit_stats.ai_commit_message = log
+            .metadata
+            .commit_message_prompt
+            .as_ref()
+            .and_then(|prompt_id| log.metadata.prompts.get(prompt_id))
+            .map(|prompt_record| {
+                format!(
+                    "{}::{}",
+                    prompt_record.agent_id.tool, prompt_record.agent_id.model
+                )
+            });
+
         for prompt_record in log.metadata.prompts.values() {
             commit_stats.total_ai_additions += prompt_record.total_additions;
             commit_stats.total_ai_deletions += prompt_record.total_deletions;
@@ -531,7 +721,10 @@ pub fn stats_from_authorship_log(
     // This includes mixed lines (AI-generated but human-edited) as human additions
     commit_stats.human_additions = std::cmp::max(
         0,
-        git_diff_added_lines.saturating_sub(commit_stats.ai_accepted),
+        git_diff_added_lines
+            .saturating_sub(commit_stats.ai_accepted)
+            .saturating_sub(commit_stats.unknown_additions)
+            .saturating_sub(commit_stats.class_additions.values().sum()),
     );
 
     commit_stats
@@ -541,67 +734,120 @@ pub fn stats_for_commit_stats(
     repo: &Repository,
     commit_sha: &str,
     _refname: &str,
+    include_generated: bool,
+) -> Result<CommitStats, GitAiError> {
+    stats_for_commit_with_log(
+        repo,
+        commit_sha,
+        get_authorship(repo, &commit_sha),
+        include_generated,
+    )
+}
+
+/// Stats of a commit given its authorship log, for callers that read or filter the log
+/// themselves (None counts every added line as human)
+pub fn stats_for_commit_with_log(
+    repo: &Repository,
+    commit_sha: &str,
+    mut authorship_log: Option<AuthorshipLog>,
+    include_generated: bool,
 ) -> Result<CommitStats, GitAiError> {
     // Step 1: get the diff between this commit and its parent ON refname (if more than one parent)
     // If initial than everything is additions
     // We want the count here git shows +111 -55
-    let (git_diff_added_lines, git_diff_deleted_lines) = get_git_diff_stats(repo, commit_sha)?;
+    let mut file_diff_stats = get_git_diff_stats_by_file(repo, commit_sha)?;
+
+    // Generated and vendored files are left out of both sides of the stats
+    if !include_generated {
+        let mut paths: Vec<String> = file_diff_stats.iter().map(|f| f.0.clone()).collect();
+        if let Some(log) = authorship_log.as_ref() {
+            paths.extend(log.attestations.iter().map(|a| a.file_path.clone()));
+        }
+        let filter = GeneratedFileFilter::for_paths(repo, paths.iter()).unwrap_or_default();
+        file_diff_stats.retain(|(path, _, _)| !filter.is_generated(path));
+        if let Some(log) = authorship_log.as_mut() {
+            log.attestations
+                .retain(|attestation| !filter.is_generated(&attestation.file_path));
+        }
+    }
+
+    // LFS files are pointers in the commit, so they're either left out too or counted on
+    // their smudged content
+    let mut paths: Vec<String> = file_diff_stats.iter().map(|f| f.0.clone()).collect();
+    if let Some(log) = authorship_log.as_ref() {
+        paths.extend(log.attestations.iter().map(|a| a.file_path.clone()));
+    }
+    let lfs = LfsFilter::for_paths(repo, paths.iter()).unwrap_or_default();
+    if !lfs.is_empty() {
+        match Config::get().lfs_mode() {
+            LfsMode::Skip => {
+                file_diff_stats.retain(|(path, _, _)| !lfs.is_lfs(path));
+                if let Some(log) = authorship_log.as_mut() {
+                    log.attestations
+                        .retain(|attestation| !lfs.is_lfs(&attestation.file_path));
+                }
+            }
+            LfsMode::Smudge => {
+                for (path, added, deleted) in file_diff_stats.iter_mut() {
+                    if lfs.is_lfs(path) {
+                        (*added, *deleted) = smudged_numstat(repo, commit_sha, path);
+                    }
+                }
+            }
+        }
+    }
 
-    // Step 2: get the authorship log for this commit
-    let authorship_log = get_authorship(repo, &commit_sha);
+    // So is everything outside the path scope of a monorepo
+    let scope = PathScope::for_repo(repo);
+    if !scope.is_everything() {
+        file_diff_stats.retain(|(path, _, _)| scope.contains(path));
+        if let Some(log) = authorship_log.as_mut() {
+            log.attestations
+                .retain(|attestation| scope.contains(&attestation.file_path));
+        }
+    }
+
+    let git_diff_added_lines = file_diff_stats.iter().map(|f| f.1).sum();
+    let git_diff_deleted_lines = file_diff_stats.iter().map(|f| f.2).sum();
 
     // Step 3: Calculate stats from authorship log
-    Ok(stats_from_authorship_log(
+    let mut stats = stats_from_authorship_log(
         authorship_log.as_ref(),
         git_diff_added_lines,
         git_diff_deleted_lines,
-    ))
+    );
+    if let Some(log) = authorship_log.as_ref().filter(|_| has_review_marks(repo)) {
+        stats.ai_reviewed = reviewed_ai_lines(log, &review_marks(repo, commit_sha));
+    }
+    Ok(stats)
 }
 
-/// Get git diff statistics between commit and its parent
-pub fn get_git_diff_stats(repo: &Repository, commit_sha: &str) -> Result<(u32, u32), GitAiError> {
+/// Get per-file git diff statistics between commit and its parent as (path, added, deleted)
+pub fn get_git_diff_stats_by_file(
+    repo: &Repository,
+    commit_sha: &str,
+) -> Result<Vec<(String, u32, u32)>, GitAiError> {
     // Use git show --numstat to get diff statistics
     let mut args = repo.global_args_for_exec();
     args.push("show".to_string());
     args.push("--numstat".to_string());
+    // A rename's "old => new" name would match neither attestations nor path filters
+    args.push("--no-renames".to_string());
     args.push("--format=".to_string()); // No format, just the numstat
     args.push(commit_sha.to_string());
 
     let output = crate::git::repository::exec_git(&args)?;
-    let stdout = String::from_utf8(output.stdout)?;
-
-    let mut added_lines = 0u32;
-    let mut deleted_lines = 0u32;
-
-    // Parse numstat output
-    for line in stdout.lines() {
-        if line.trim().is_empty() {
-            continue;
-        }
 
-        // Skip the commit message lines (they don't start with numbers)
-        if !line.chars().next().map_or(false, |c| c.is_ascii_digit()) {
-            continue;
-        }
+    // Parse numstat format: "added\tdeleted\tfilename" ("-" for binary files). Lines that
+    // don't start with a count, like the commit message, aren't numstat lines.
+    let file_stats = output
+        .stdout
+        .split(|byte| *byte == b'\n')
+        .filter_map(crate::git::repo_path::parse_numstat_line)
+        .map(|(added, deleted, path)| (path, added.unwrap_or(0), deleted.unwrap_or(0)))
+        .collect();
 
-        // Parse numstat format: "added\tdeleted\tfilename"
-        let parts: Vec<&str> = line.split('\t').collect();
-        if parts.len() >= 2 {
-            // Parse added lines
-            if let Ok(added) = parts[0].parse::<u32>() {
-                added_lines += added;
-            }
-
-            // Parse deleted lines (handle "-" for binary files)
-            if parts[1] != "-" {
-                if let Ok(deleted) = parts[1].parse::<u32>() {
-                    deleted_lines += deleted;
-                }
-            }
-        }
-    }
-
-    Ok((added_lines, deleted_lines))
+    Ok(file_stats)
 }
 
 /// Calculate time waiting for AI from transcript messages
@@ -673,6 +919,13 @@ mod tests {
             git_diff_added_lines: 80,
             total_ai_additions: 100,
             total_ai_deletions: 0,
+            ai_low_confidence: 0,
+            ai_overridden_kept: 0,
+            ai_reviewed: 0,
+            possible_ai_additions: 0,
+            unknown_additions: 0,
+            class_additions: BTreeMap::new(),
+            ai_commit_message: None,
             tool_model_breakdown: BTreeMap::new(),
         };
 
@@ -690,6 +943,13 @@ mod tests {
             git_diff_added_lines: 100,
             total_ai_additions: 100,
             total_ai_deletions: 0,
+            ai_low_confidence: 0,
+            ai_overridden_kept: 0,
+            ai_reviewed: 0,
+            possible_ai_additions: 0,
+            unknown_additions: 0,
+            class_additions: BTreeMap::new(),
+            ai_commit_message: None,
             tool_model_breakdown: BTreeMap::new(),
         };
 
@@ -707,6 +967,13 @@ mod tests {
             git_diff_added_lines: 75,
             total_ai_additions: 0,
             total_ai_deletions: 0,
+            ai_low_confidence: 0,
+            ai_overridden_kept: 0,
+            ai_reviewed: 0,
+            possible_ai_additions: 0,
+            unknown_additions: 0,
+            class_additions: BTreeMap::new(),
+            ai_commit_message: None,
             tool_model_breakdown: BTreeMap::new(),
         };
 
@@ -724,6 +991,13 @@ mod tests {
             git_diff_added_lines: 102,
             total_ai_additions: 100,
             total_ai_deletions: 0,
+            ai_low_confidence: 0,
+            ai_overridden_kept: 0,
+            ai_reviewed: 0,
+            possible_ai_additions: 0,
+            unknown_additions: 0,
+            class_additions: BTreeMap::new(),
+            ai_commit_message: None,
             tool_model_breakdown: BTreeMap::new(),
         };
 
@@ -741,6 +1015,13 @@ mod tests {
             git_diff_added_lines: 0,
             total_ai_additions: 0,
             total_ai_deletions: 0,
+            ai_low_confidence: 0,
+            ai_overridden_kept: 0,
+            ai_reviewed: 0,
+            possible_ai_additions: 0,
+            unknown_additions: 0,
+            class_additions: BTreeMap::new(),
+            ai_commit_message: None,
             tool_model_breakdown: BTreeMap::new(),
         };
 
@@ -761,6 +1042,13 @@ mod tests {
             git_diff_added_lines: 80,
             total_ai_additions: 100,
             total_ai_deletions: 0,
+            ai_low_confidence: 0,
+            ai_overridden_kept: 0,
+            ai_reviewed: 0,
+            possible_ai_additions: 0,
+            unknown_additions: 0,
+            class_additions: BTreeMap::new(),
+            ai_commit_message: None,
             tool_model_breakdown: BTreeMap::new(),
         };
 
@@ -778,6 +1066,13 @@ mod tests {
             git_diff_added_lines: 100,
             total_ai_additions: 100,
             total_ai_deletions: 0,
+            ai_low_confidence: 0,
+            ai_overridden_kept: 0,
+            ai_reviewed: 0,
+            possible_ai_additions: 0,
+            unknown_additions: 0,
+            class_additions: BTreeMap::new(),
+            ai_commit_message: None,
             tool_model_breakdown: BTreeMap::new(),
         };
 
@@ -795,6 +1090,13 @@ mod tests {
             git_diff_added_lines: 75,
             total_ai_additions: 0,
             total_ai_deletions: 0,
+            ai_low_confidence: 0,
+            ai_overridden_kept: 0,
+            ai_reviewed: 0,
+            possible_ai_additions: 0,
+            unknown_additions: 0,
+            class_additions: BTreeMap::new(),
+            ai_commit_message: None,
             tool_model_breakdown: BTreeMap::new(),
         };
 
@@ -812,6 +1114,13 @@ mod tests {
             git_diff_added_lines: 102,
             total_ai_additions: 100,
             total_ai_deletions: 0,
+            ai_low_confidence: 0,
+            ai_overridden_kept: 0,
+            ai_reviewed: 0,
+            possible_ai_additions: 0,
+            unknown_additions: 0,
+            class_additions: BTreeMap::new(),
+            ai_commit_message: None,
             tool_model_breakdown: BTreeMap::new(),
         };
 
@@ -829,6 +1138,13 @@ mod tests {
             git_diff_added_lines: 0,
             total_ai_additions: 0,
             total_ai_deletions: 0,
+            ai_low_confidence: 0,
+            ai_overridden_kept: 0,
+            ai_reviewed: 0,
+            possible_ai_additions: 0,
+            unknown_additions: 0,
+            class_additions: BTreeMap::new(),
+            ai_commit_message: None,
             tool_model_breakdown: BTreeMap::new(),
         };
 
@@ -836,6 +1152,50 @@ mod tests {
         assert_debug_snapshot!(deletion_only_output);
     }
 
+    #[test]
+    fn test_stats_count_overridden_lines_that_kept_ai_attribution() {
+        use crate::authorship::authorship_log::{LineRange, PromptRecord};
+        use crate::authorship::authorship_log_serialization::{
+            AttestationEntry, AuthorshipLog, FileAttestation,
+        };
+        use crate::authorship::working_log::AgentId;
+
+        let mut log = AuthorshipLog::new();
+        log.metadata.prompts.insert(
+            "abc1234".to_string(),
+            PromptRecord {
+                agent_id: AgentId {
+                    tool: "cursor".to_string(),
+                    id: "session".to_string(),
+                    model: "gpt-4".to_string(),
+                },
+                human_author: None,
+                human_authors: Vec::new(),
+                messages: vec![],
+                total_additions: 4,
+                total_deletions: 0,
+                accepted_lines: 4,
+                overriden_lines: 1,
+                first_checkpoint_at: None,
+                last_checkpoint_at: None,
+            },
+        );
+        let mut file = FileAttestation::new("src/lib.rs".to_string());
+        file.add_entry(
+            AttestationEntry::new("abc1234".to_string(), vec![LineRange::Range(1, 4)])
+                .with_overridden_lines(vec![LineRange::Single(2)]),
+        );
+        log.attestations.push(file);
+
+        let stats = stats_from_authorship_log(Some(&log), 6, 0);
+
+        assert_eq!(stats.ai_accepted, 3, "The edited line isn't accepted as-is");
+        assert_eq!(stats.mixed_additions, 1);
+        assert_eq!(stats.ai_overridden_kept, 1);
+        assert_eq!(stats.ai_additions, 4);
+        assert_eq!(stats.human_additions, 3);
+    }
+
     #[test]
     fn test_stats_for_simple_ai_commit() {
         let tmp_repo = TmpRepo::new().unwrap();
@@ -861,7 +1221,8 @@ mod tests {
         let head_sha = tmp_repo.get_head_commit_sha().unwrap();
 
         // Test our stats function
-        let stats = stats_for_commit_stats(&tmp_repo.gitai_repo(), &head_sha, "HEAD").unwrap();
+        let stats =
+            stats_for_commit_stats(&tmp_repo.gitai_repo(), &head_sha, "HEAD", false).unwrap();
 
         // Verify the stats
         assert_eq!(
@@ -913,7 +1274,8 @@ mod tests {
         tmp_repo.commit_with_message("Mixed commit").unwrap();
 
         let head_sha = tmp_repo.get_head_commit_sha().unwrap();
-        let stats = stats_for_commit_stats(&tmp_repo.gitai_repo(), &head_sha, "HEAD").unwrap();
+        let stats =
+            stats_for_commit_stats(&tmp_repo.gitai_repo(), &head_sha, "HEAD", false).unwrap();
 
         // Verify the stats
         assert_eq!(stats.human_additions, 2, "Human added 2 lines");
@@ -944,7 +1306,8 @@ mod tests {
         tmp_repo.commit_with_message("Initial commit").unwrap();
 
         let head_sha = tmp_repo.get_head_commit_sha().unwrap();
-        let stats = stats_for_commit_stats(&tmp_repo.gitai_repo(), &head_sha, "HEAD").unwrap();
+        let stats =
+            stats_for_commit_stats(&tmp_repo.gitai_repo(), &head_sha, "HEAD", false).unwrap();
 
         // For initial commit, everything should be additions
         assert_eq!(
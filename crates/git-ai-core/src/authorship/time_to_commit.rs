@@ -0,0 +1,204 @@
+//! How long AI sessions' edits waited before they were committed, per agent and model.
+//!
+//! Every commit's authorship note records when each session that wrote its lines first and last
+//! checkpointed. The time from those checkpoints to the commit is what the session's suggestions
+//! spent being reviewed, tested and reworked; long waits tend to go with heavy human rework.
+//! Notes written before git-ai recorded checkpoint times are skipped.
+
+use crate::error::GitAiError;
+use crate::git::refs::get_authorship;
+use crate::git::repository::Repository;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// One session's lines in one commit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionTimeToCommit {
+    pub commit: String,
+    pub tool: String,
+    pub model: String,
+    /// Seconds from the session's first checkpoint to the commit
+    pub from_first_checkpoint: u64,
+    /// Seconds from the session's last checkpoint to the commit
+    pub from_last_checkpoint: u64,
+}
+
+/// Time to commit of one agent and model's sessions, in seconds
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ToolModelTimeToCommit {
+    pub tool: String,
+    pub model: String,
+    pub sessions: usize,
+    /// Median time from a session's last checkpoint to the commit
+    pub median_secs: u64,
+    /// Longest time from a session's last checkpoint to the commit
+    pub max_secs: u64,
+    /// Median time from a session's first checkpoint to the commit
+    pub median_from_first_secs: u64,
+}
+
+/// Sessions of the commits `rev` selects, anything `git log` takes (`HEAD`, `main..feature`)
+pub fn session_times(repo: &Repository, rev: &str) -> Result<Vec<SessionTimeToCommit>, GitAiError> {
+    let output = repo.git(&["log", "--format=%H %ct", rev])?;
+    let mut sessions = Vec::new();
+    for line in output.lines() {
+        let Some((commit, committed_at)) = line.split_once(' ') else {
+            continue;
+        };
+        let Ok(committed_at) = committed_at.trim().parse::<u64>() else {
+            continue;
+        };
+        let Some(log) = get_authorship(repo, commit) else {
+            continue;
+        };
+        for record in log.metadata.prompts.values() {
+            let (Some(first), Some(last)) = (record.first_checkpoint_at, record.last_checkpoint_at)
+            else {
+                continue;
+            };
+            sessions.push(SessionTimeToCommit {
+                commit: commit.to_string(),
+                tool: record.agent_id.tool.clone(),
+                model: record.agent_id.model.clone(),
+                from_first_checkpoint: committed_at.saturating_sub(first),
+                from_last_checkpoint: committed_at.saturating_sub(last),
+            });
+        }
+    }
+    Ok(sessions)
+}
+
+/// Groups `sessions` by agent and model, slowest median first
+pub fn by_tool_model(sessions: &[SessionTimeToCommit]) -> Vec<ToolModelTimeToCommit> {
+    let mut groups: BTreeMap<(&str, &str), Vec<&SessionTimeToCommit>> = BTreeMap::new();
+    for session in sessions {
+        groups
+            .entry((session.tool.as_str(), session.model.as_str()))
+            .or_default()
+            .push(session);
+    }
+    let mut stats: Vec<ToolModelTimeToCommit> = groups
+        .into_iter()
+        .map(|((tool, model), sessions)| {
+            let from_last: Vec<u64> = sessions.iter().map(|s| s.from_last_checkpoint).collect();
+            let from_first: Vec<u64> = sessions.iter().map(|s| s.from_first_checkpoint).collect();
+            ToolModelTimeToCommit {
+                tool: tool.to_string(),
+                model: model.to_string(),
+                sessions: sessions.len(),
+                median_secs: median(from_last.clone()),
+                max_secs: from_last.into_iter().max().unwrap_or(0),
+                median_from_first_secs: median(from_first),
+            }
+        })
+        .collect();
+    stats.sort_by_key(|stat| std::cmp::Reverse(stat.median_secs));
+    stats
+}
+
+fn median(mut values: Vec<u64>) -> u64 {
+    if values.is_empty() {
+        return 0;
+    }
+    values.sort_unstable();
+    let mid = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        (values[mid - 1] + values[mid]) / 2
+    } else {
+        values[mid]
+    }
+}
+
+/// Seconds as the largest two units, e.g. "2h 5m", "45s"
+pub fn format_duration(secs: u64) -> String {
+    let (days, hours, minutes, seconds) = (
+        secs / 86_400,
+        secs % 86_400 / 3_600,
+        secs % 3_600 / 60,
+        secs % 60,
+    );
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else if minutes > 0 {
+        format!("{}m {}s", minutes, seconds)
+    } else {
+        format!("{}s", seconds)
+    }
+}
+
+pub fn print_time_to_commit(stats: &[ToolModelTimeToCommit]) {
+    if stats.is_empty() {
+        println!("No AI sessions with checkpoint times in these commits");
+        return;
+    }
+    println!(
+        "{:<32}  {:>8}  {:>12}  {:>12}  {:>12}",
+        "agent::model", "sessions", "median", "max", "from first"
+    );
+    for stat in stats {
+        println!(
+            "{:<32}  {:>8}  {:>12}  {:>12}  {:>12}",
+            format!("{}::{}", stat.tool, stat.model),
+            stat.sessions,
+            format_duration(stat.median_secs),
+            format_duration(stat.max_secs),
+            format_duration(stat.median_from_first_secs)
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(tool: &str, from_first: u64, from_last: u64) -> SessionTimeToCommit {
+        SessionTimeToCommit {
+            commit: "abc".to_string(),
+            tool: tool.to_string(),
+            model: "model".to_string(),
+            from_first_checkpoint: from_first,
+            from_last_checkpoint: from_last,
+        }
+    }
+
+    #[test]
+    fn test_by_tool_model_takes_medians_slowest_first() {
+        let stats = by_tool_model(&[
+            session("cursor", 100, 10),
+            session("claude", 900, 600),
+            session("cursor", 200, 30),
+            session("cursor", 300, 20),
+        ]);
+        assert_eq!(
+            stats,
+            vec![
+                ToolModelTimeToCommit {
+                    tool: "claude".to_string(),
+                    model: "model".to_string(),
+                    sessions: 1,
+                    median_secs: 600,
+                    max_secs: 600,
+                    median_from_first_secs: 900,
+                },
+                ToolModelTimeToCommit {
+                    tool: "cursor".to_string(),
+                    model: "model".to_string(),
+                    sessions: 3,
+                    median_secs: 20,
+                    max_secs: 30,
+                    median_from_first_secs: 200,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(45), "45s");
+        assert_eq!(format_duration(125), "2m 5s");
+        assert_eq!(format_duration(7_500), "2h 5m");
+        assert_eq!(format_duration(90_000), "1d 1h");
+    }
+}
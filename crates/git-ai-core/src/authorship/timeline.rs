@@ -0,0 +1,239 @@
+//! How one file's AI/human composition evolved, commit by commit.
+//!
+//! Walks the commits that touched the file (following renames), oldest first, and carries the
+//! origin of every line through each commit's line diff: unchanged lines keep their origin, and
+//! lines a commit adds are AI if its authorship note attests them and human otherwise. That
+//! gives both what each commit added and how much AI content survived at each point, which a
+//! blame of the latest version alone can't show.
+
+use crate::authorship::authorship_log::LineRange;
+use crate::authorship::file_text;
+use crate::error::GitAiError;
+use crate::git::refs::get_authorship;
+use crate::git::repository::Repository;
+use serde::Serialize;
+use similar::{ChangeTag, TextDiff};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TimelineEntry {
+    pub commit: String,
+    /// Path of the file in this commit, which differs from the requested one before a rename
+    pub path: String,
+    pub author: String,
+    /// Author time (unix timestamp)
+    pub timestamp: i64,
+    pub summary: String,
+    pub ai_added: u32,
+    pub human_added: u32,
+    pub deleted: u32,
+    /// Lines in the file after this commit
+    pub total_lines: u32,
+    /// Lines in the file after this commit that AI wrote, in this commit or an earlier one
+    pub ai_lines: u32,
+}
+
+impl TimelineEntry {
+    /// Share of the file's lines written by AI after this commit
+    pub fn ai_percent(&self) -> f64 {
+        if self.total_lines == 0 {
+            0.0
+        } else {
+            self.ai_lines as f64 * 100.0 / self.total_lines as f64
+        }
+    }
+}
+
+/// Replays the history of `file_path` (relative to the repository root) up to `rev`
+pub fn file_timeline(
+    repo: &Repository,
+    file_path: &str,
+    rev: &str,
+) -> Result<Vec<TimelineEntry>, GitAiError> {
+    let mut timeline = Vec::new();
+    // Content and origin (true for AI) of each line as of the last replayed commit
+    let mut content = String::new();
+    let mut ai_origin: Vec<bool> = Vec::new();
+
+    for commit in commits_touching(repo, file_path, rev)? {
+        let bytes = repo
+            .get_file_content(&commit.path, &commit.sha)
+            .unwrap_or_default();
+        let Some(new_content) = file_text::decode_text(&bytes) else {
+            return Err(GitAiError::Generic(format!(
+                "{} is not a text file in {}",
+                commit.path, commit.sha
+            )));
+        };
+
+        let attested = attested_ai_lines(repo, &commit.sha, &commit.path);
+        let mut entry = TimelineEntry {
+            commit: commit.sha,
+            path: commit.path,
+            author: commit.author,
+            timestamp: commit.timestamp,
+            summary: commit.summary,
+            ai_added: 0,
+            human_added: 0,
+            deleted: 0,
+            total_lines: 0,
+            ai_lines: 0,
+        };
+
+        let mut new_origin = Vec::new();
+        for change in TextDiff::from_lines(&content, &new_content).iter_all_changes() {
+            match change.tag() {
+                ChangeTag::Equal => {
+                    let ai = change
+                        .old_index()
+                        .and_then(|i| ai_origin.get(i).copied())
+                        .unwrap_or(false);
+                    new_origin.push(ai);
+                }
+                ChangeTag::Delete => entry.deleted += 1,
+                ChangeTag::Insert => {
+                    let line = change.new_index().map_or(0, |i| i as u32 + 1);
+                    let ai = attested.iter().any(|range| range.contains(line));
+                    if ai {
+                        entry.ai_added += 1;
+                    } else {
+                        entry.human_added += 1;
+                    }
+                    new_origin.push(ai);
+                }
+            }
+        }
+
+        entry.total_lines = new_origin.len() as u32;
+        entry.ai_lines = new_origin.iter().filter(|ai| **ai).count() as u32;
+        timeline.push(entry);
+        content = new_content;
+        ai_origin = new_origin;
+    }
+
+    Ok(timeline)
+}
+
+struct FileCommit {
+    sha: String,
+    path: String,
+    author: String,
+    timestamp: i64,
+    summary: String,
+}
+
+/// Commits reachable from `rev` that changed `file_path`, oldest first, with the file's path in
+/// each of them
+fn commits_touching(
+    repo: &Repository,
+    file_path: &str,
+    rev: &str,
+) -> Result<Vec<FileCommit>, GitAiError> {
+    let output = repo.git(&[
+        "log",
+        "--follow",
+        "--name-only",
+        "--format=%x01%H%x00%an%x00%at%x00%s",
+        rev,
+        "--",
+        file_path,
+    ])?;
+    let mut commits: Vec<FileCommit> = output
+        .split('\x01')
+        .filter_map(|record| {
+            let mut lines = record.lines();
+            let mut fields = lines.next()?.splitn(4, '\0');
+            let sha = fields.next()?.to_string();
+            let author = fields.next()?.to_string();
+            let timestamp = fields.next()?.parse().ok()?;
+            let summary = fields.next().unwrap_or_default().to_string();
+            let path = lines.find(|line| !line.is_empty())?;
+            let path = crate::git::repo_path::key_from_git_output(path.as_bytes());
+            Some(FileCommit {
+                sha,
+                path,
+                author,
+                timestamp,
+                summary,
+            })
+        })
+        .collect();
+    if commits.is_empty() {
+        return Err(GitAiError::Generic(format!(
+            "no commits touching {} in {}",
+            file_path, rev
+        )));
+    }
+    // --follow doesn't combine with --reverse, so reverse here
+    commits.reverse();
+    Ok(commits)
+}
+
+/// Lines of `path` that the note of `commit` attributes to AI
+fn attested_ai_lines(repo: &Repository, commit: &str, path: &str) -> Vec<LineRange> {
+    get_authorship(repo, commit)
+        .and_then(|log| {
+            log.attestations
+                .into_iter()
+                .find(|attestation| attestation.file_path == path)
+        })
+        .map(|attestation| {
+            attestation
+                .entries
+                .into_iter()
+                .flat_map(|entry| entry.line_ranges)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::test_utils::TmpRepo;
+
+    #[test]
+    fn test_timeline_tracks_added_and_surviving_ai_lines() {
+        let repo = TmpRepo::new().unwrap();
+        repo.write_file("app.txt", "one\ntwo\n", true).unwrap();
+        repo.trigger_checkpoint_with_author("Alice").unwrap();
+        repo.commit_with_message("Human start").unwrap();
+
+        repo.write_file("app.txt", "one\ntwo\nai one\nai two\n", true)
+            .unwrap();
+        repo.trigger_checkpoint_with_ai("Claude", Some("claude-3-sonnet"), Some("cursor"))
+            .unwrap();
+        repo.commit_with_message("AI lines").unwrap();
+
+        repo.write_file("unrelated.txt", "x\n", true).unwrap();
+        repo.trigger_checkpoint_with_author("Alice").unwrap();
+        repo.commit_with_message("Unrelated").unwrap();
+
+        // A human rewrites one of the AI lines and renames the file
+        repo.write_file("app.txt", "one\ntwo\nai one\nhuman two\n", true)
+            .unwrap();
+        repo.trigger_checkpoint_with_author("Alice").unwrap();
+        repo.commit_with_message("Human edit").unwrap();
+        repo.git_command(&["mv", "app.txt", "main.txt"]).unwrap();
+        repo.commit_with_message("Rename").unwrap();
+
+        let timeline = file_timeline(repo.gitai_repo(), "main.txt", "HEAD").unwrap();
+        let summaries: Vec<&str> = timeline.iter().map(|e| e.summary.as_str()).collect();
+        assert_eq!(
+            summaries,
+            vec!["Human start", "AI lines", "Human edit", "Rename"]
+        );
+        let counts: Vec<(u32, u32, u32, u32)> = timeline
+            .iter()
+            .map(|e| (e.ai_added, e.human_added, e.deleted, e.ai_lines))
+            .collect();
+        assert_eq!(
+            counts,
+            vec![(0, 2, 0, 0), (2, 0, 0, 2), (0, 1, 1, 1), (0, 0, 0, 1)]
+        );
+        assert_eq!(timeline[0].path, "app.txt");
+        assert_eq!(timeline[3].path, "main.txt");
+        assert_eq!(timeline[3].ai_percent(), 25.0);
+
+        assert!(file_timeline(repo.gitai_repo(), "missing.txt", "HEAD").is_err());
+    }
+}
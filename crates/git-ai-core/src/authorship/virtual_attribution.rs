@@ -1,14 +1,25 @@
 use crate::authorship::attribution_tracker::{
-    Attribution, LineAttribution, line_attributions_to_attributions,
+    Attribution, BLAME_IN_CONTEXT_CONFIDENCE, LineAttribution, attributions_to_column_ranges,
+    combine_confidence, line_attributions_to_attributions,
 };
-use crate::authorship::authorship_log::{LineRange, PromptRecord};
+use crate::authorship::authorship_log::{ColumnRange, LineRange, PromptRecord};
+use crate::authorship::file_text;
 use crate::authorship::working_log::CheckpointKind;
 use crate::commands::blame::GitAiBlameOptions;
+use crate::config::Config;
 use crate::error::GitAiError;
+use crate::git::lfs::{smudge_added_lines, smudge_blob_content};
+use crate::git::refs::AI_AUTHORSHIP_REFNAME;
+use crate::git::repo_path;
+use crate::git::repo_storage::{AttributionSnapshot, SnapshotFile};
 use crate::git::repository::Repository;
+use crate::utils::{debug_log, now_millis};
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::Arc;
-use std::time::{SystemTime, UNIX_EPOCH};
+
+/// File path -> content. Contents are shared rather than copied as they pass from one
+/// attribution state to the next, since a rebase can carry every tracked file through each commit
+pub type FileContents = HashMap<String, Arc<str>>;
 
 pub struct VirtualAttributions {
     repo: Repository,
@@ -16,7 +27,7 @@ pub struct VirtualAttributions {
     // Maps file path -> (char attributions, line attributions)
     pub attributions: HashMap<String, (Vec<Attribution>, Vec<LineAttribution>)>,
     // Maps file path -> file content
-    file_contents: HashMap<String, String>,
+    file_contents: FileContents,
     // Prompt records mapping prompt_id -> (commit_sha -> PromptRecord)
     // Same prompt can appear in multiple commits, allowing us to track and sort them
     pub prompts: BTreeMap<String, BTreeMap<String, PromptRecord>>,
@@ -25,16 +36,33 @@ pub struct VirtualAttributions {
 }
 
 impl VirtualAttributions {
-    /// Create a new VirtualAttributions for the given base commit with initial pathspecs
+    /// Create a new VirtualAttributions for the given base commit with initial pathspecs.
+    /// Reuses the snapshot a previous command stored for the commit when it covers the
+    /// pathspecs, and stores one for the next command otherwise.
     pub async fn new_for_base_commit(
         repo: Repository,
         base_commit: String,
         pathspecs: &[String],
     ) -> Result<Self, GitAiError> {
-        let ts = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_millis();
+        let ts = now_millis();
+        tracing::debug!(base_commit = %base_commit, files = ?pathspecs, "files considered");
+
+        let snapshot_key = attribution_snapshot_key(&repo, &base_commit);
+        if let Some(key) = &snapshot_key
+            && let Some(snapshot) = repo.storage.read_attribution_snapshot(key)
+            && pathspecs
+                .iter()
+                .all(|pathspec| snapshot.pathspecs.contains(pathspec))
+        {
+            debug_log(&format!("Reusing attribution snapshot for {}", base_commit));
+            return Ok(Self::from_snapshot(
+                repo,
+                base_commit,
+                snapshot,
+                pathspecs,
+                ts,
+            ));
+        }
 
         let mut virtual_attrs = VirtualAttributions {
             repo,
@@ -53,9 +81,87 @@ impl VirtualAttributions {
         // After running blame, discover and load any missing prompts from blamed commits
         virtual_attrs.discover_and_load_foreign_prompts()?;
 
+        if let Some(key) = snapshot_key
+            && !virtual_attrs.repo.is_bare()
+            && let Err(e) = virtual_attrs
+                .repo
+                .storage
+                .write_attribution_snapshot(&virtual_attrs.snapshot(key, pathspecs))
+        {
+            debug_log(&format!("Failed to write attribution snapshot: {}", e));
+        }
+
         Ok(virtual_attrs)
     }
 
+    /// The pathspecs' attributions from a stored snapshot, with char attributions rebuilt from
+    /// the line attributions at `ts`
+    fn from_snapshot(
+        repo: Repository,
+        base_commit: String,
+        mut snapshot: AttributionSnapshot,
+        pathspecs: &[String],
+        ts: u128,
+    ) -> Self {
+        let mut attributions = HashMap::new();
+        let mut file_contents = FileContents::new();
+        for pathspec in pathspecs {
+            let Some(file) = snapshot.files.remove(pathspec) else {
+                continue;
+            };
+            let char_attributions =
+                line_attributions_to_attributions(&file.line_attributions, &file.content, ts);
+            attributions.insert(
+                pathspec.clone(),
+                (char_attributions, file.line_attributions),
+            );
+            file_contents.insert(pathspec.clone(), Arc::from(file.content));
+        }
+
+        // Only the prompts of the files asked for, as if they'd been discovered afresh
+        let authors: HashSet<&str> = attributions
+            .values()
+            .flat_map(|(char_attrs, _)| char_attrs)
+            .map(|attr| attr.author_id.as_str())
+            .collect();
+        let prompts = snapshot
+            .prompts
+            .into_iter()
+            .filter(|(id, _)| authors.contains(id.as_str()))
+            .collect();
+
+        VirtualAttributions {
+            repo,
+            base_commit,
+            attributions,
+            file_contents,
+            prompts,
+            ts,
+        }
+    }
+
+    fn snapshot(&self, key: String, pathspecs: &[String]) -> AttributionSnapshot {
+        AttributionSnapshot {
+            key,
+            pathspecs: pathspecs.iter().cloned().collect(),
+            files: self
+                .attributions
+                .iter()
+                .filter_map(|(file, (_, line_attrs))| {
+                    let content = self.file_contents.get(file)?;
+                    Some((
+                        file.clone(),
+                        SnapshotFile {
+                            content: content.to_string(),
+                            line_attributions: line_attrs.clone(),
+                        },
+                    ))
+                })
+                .collect(),
+            prompts: self.prompts.clone(),
+        }
+    }
+
     /// Discover and load prompts from blamed commits that aren't in our prompts map
     fn discover_and_load_foreign_prompts(&mut self) -> Result<(), GitAiError> {
         use std::collections::HashSet;
@@ -143,7 +249,7 @@ impl VirtualAttributions {
 
     /// Static version of find_prompt_in_history for use in async context
     /// Returns (commit_sha, PromptRecord) for the most recent commit containing this prompt
-    fn find_prompt_in_history_static(
+    pub(crate) fn find_prompt_in_history_static(
         repo: &Repository,
         prompt_id: &str,
     ) -> Result<(String, crate::authorship::authorship_log::PromptRecord), GitAiError> {
@@ -151,12 +257,12 @@ impl VirtualAttributions {
         let shas = crate::git::refs::grep_ai_notes(&repo, &format!("\"{}\"", prompt_id))
             .unwrap_or_default();
 
-        // Check the most recent commit with this prompt ID
-        if let Some(latest_sha) = shas.first() {
-            if let Ok(log) = crate::git::refs::get_reference_as_authorship_log_v3(&repo, latest_sha)
-            {
+        // Check the most recent commit that holds this prompt (the ID can also show up
+        // in other metadata, such as entry confidences)
+        for sha in &shas {
+            if let Ok(log) = crate::git::refs::get_reference_as_authorship_log_v3(&repo, sha) {
                 if let Some(prompt) = log.metadata.prompts.get(prompt_id) {
-                    return Ok((latest_sha.clone(), prompt.clone()));
+                    return Ok((sha.clone(), prompt.clone()));
                 }
             }
         }
@@ -210,7 +316,7 @@ impl VirtualAttributions {
                 Ok(Some((file_path, content, char_attrs, line_attrs))) => {
                     self.attributions
                         .insert(file_path.clone(), (char_attrs, line_attrs));
-                    self.file_contents.insert(file_path, content);
+                    self.file_contents.insert(file_path, Arc::from(content));
                 }
                 Ok(None) => {
                     // File had no changes or couldn't be processed, skip
@@ -245,6 +351,52 @@ impl VirtualAttributions {
             .map(|(_, line_attrs)| line_attrs)
     }
 
+    /// Line attributions of `content`, another version of a file (such as the one staged in the
+    /// index), for the lines it has unchanged from this file's content
+    pub fn line_attributions_for_content(
+        &self,
+        file_path: &str,
+        content: &str,
+    ) -> Vec<LineAttribution> {
+        let (Some((_, line_attrs)), Some(working_content)) = (
+            self.attributions.get(file_path),
+            self.file_contents.get(file_path),
+        ) else {
+            return Vec::new();
+        };
+        let alignment = align_working_lines(content, working_content);
+
+        let mut mapped: Vec<LineAttribution> = Vec::new();
+        for line_attr in line_attrs {
+            for working_line in line_attr.start_line..=line_attr.end_line {
+                let Some(LineMatch::Same(line)) = alignment.get(working_line as usize - 1) else {
+                    continue;
+                };
+                match mapped.last_mut() {
+                    Some(last)
+                        if last.end_line + 1 == *line
+                            && last.author_id == line_attr.author_id
+                            && last.overrode == line_attr.overrode
+                            && last.confidence == line_attr.confidence =>
+                    {
+                        last.end_line = *line;
+                    }
+                    _ => mapped.push(
+                        LineAttribution::new(
+                            *line,
+                            *line,
+                            line_attr.author_id.clone(),
+                            line_attr.overrode.clone(),
+                        )
+                        .with_confidence(line_attr.confidence),
+                    ),
+                }
+            }
+        }
+        mapped.sort_by_key(|attr| attr.start_line);
+        mapped
+    }
+
     /// List all tracked files
     pub fn files(&self) -> Vec<String> {
         self.attributions.keys().cloned().collect()
@@ -266,7 +418,7 @@ impl VirtualAttributions {
     }
 
     /// Get the file content for a tracked file
-    pub fn get_file_content(&self, file_path: &str) -> Option<&String> {
+    pub fn get_file_content(&self, file_path: &str) -> Option<&Arc<str>> {
         self.file_contents.get(file_path)
     }
 
@@ -311,7 +463,12 @@ impl VirtualAttributions {
         let mut attributions: HashMap<String, (Vec<Attribution>, Vec<LineAttribution>)> =
             HashMap::new();
         let mut prompts = BTreeMap::new();
-        let mut file_contents: HashMap<String, String> = HashMap::new();
+        let mut file_contents = FileContents::new();
+        // The working copy of a file, read once however many checkpoints touched it
+        let read_working_copy = |file_path: &str| -> Option<Arc<str>> {
+            let abs_path = repo.workdir().ok()?.join(repo_path::key_to_path(file_path));
+            Some(Arc::from(file_text::read(&abs_path)))
+        };
 
         // Track additions and deletions per session_id for metrics
         let mut session_additions: HashMap<String, u32> = HashMap::new();
@@ -329,14 +486,8 @@ impl VirtualAttributions {
         // Process INITIAL attributions
         for (file_path, line_attrs) in &initial_attributions.files {
             // Get the latest file content from working directory
-            if let Ok(workdir) = repo.workdir() {
-                let abs_path = workdir.join(file_path);
-                let file_content = if abs_path.exists() {
-                    std::fs::read_to_string(&abs_path).unwrap_or_default()
-                } else {
-                    String::new()
-                };
-                file_contents.insert(file_path.clone(), file_content.clone());
+            if let Some(file_content) = read_working_copy(file_path) {
+                file_contents.insert(file_path.clone(), Arc::clone(&file_content));
 
                 // Convert line attributions to character attributions
                 let char_attrs = line_attributions_to_attributions(&line_attrs, &file_content, 0);
@@ -354,13 +505,18 @@ impl VirtualAttributions {
                         &agent_id.tool,
                     );
                 // For working log checkpoints, use empty string as commit_sha since they're uncommitted
-                prompts
+                let record = prompts
                     .entry(author_id.clone())
                     .or_insert_with(BTreeMap::new)
                     .entry(String::new())
                     .or_insert_with(|| crate::authorship::authorship_log::PromptRecord {
                         agent_id: agent_id.clone(),
-                        human_author: human_author.clone(),
+                        // Whoever prompted the agent, if the checkpoint knows
+                        human_author: checkpoint
+                            .human_author
+                            .clone()
+                            .or_else(|| human_author.clone()),
+                        human_authors: Vec::new(),
                         messages: checkpoint
                             .transcript
                             .as_ref()
@@ -370,7 +526,19 @@ impl VirtualAttributions {
                         total_deletions: 0,
                         accepted_lines: 0,
                         overriden_lines: 0,
+                        first_checkpoint_at: Some(checkpoint.timestamp),
+                        last_checkpoint_at: None,
                     });
+                record.last_checkpoint_at = Some(checkpoint.timestamp);
+                // Anyone who paired on any part of the session was involved in it
+                for participant in &checkpoint.participants {
+                    if !record.human_authors.contains(participant) {
+                        record.human_authors.push(participant.clone());
+                    }
+                }
+                record
+                    .human_authors
+                    .sort_by_key(|participant| participant.role);
 
                 // Track additions and deletions from checkpoint line_stats
                 *session_additions.entry(author_id.clone()).or_insert(0) +=
@@ -382,20 +550,29 @@ impl VirtualAttributions {
             // Collect attributions from checkpoint entries
             for entry in &checkpoint.entries {
                 // Get the latest file content from working directory
-                if let Ok(workdir) = repo.workdir() {
-                    let abs_path = workdir.join(&entry.file);
-                    let file_content = if abs_path.exists() {
-                        std::fs::read_to_string(&abs_path).unwrap_or_default()
-                    } else {
-                        String::new()
-                    };
+                if !file_contents.contains_key(&entry.file)
+                    && let Some(file_content) = read_working_copy(&entry.file)
+                {
                     file_contents.insert(entry.file.clone(), file_content);
                 }
 
                 // Use the line attributions from the checkpoint
                 let line_attrs = entry.line_attributions.clone();
-                let file_content = file_contents.get(&entry.file).cloned().unwrap_or_default();
-                let char_attrs = line_attributions_to_attributions(&line_attrs, &file_content, 0);
+                let file_content = file_contents
+                    .get(&entry.file)
+                    .map(|content| &**content)
+                    .unwrap_or_default();
+                // Keep the checkpoint's character-level attributions while the file is unchanged
+                // since, so co-written lines can still be told apart
+                let checkpoint_content_matches = !entry.attributions.is_empty()
+                    && working_log
+                        .get_file_version(&entry.blob_sha)
+                        .is_ok_and(|content| content == file_content);
+                let char_attrs = if checkpoint_content_matches {
+                    entry.attributions.clone()
+                } else {
+                    line_attributions_to_attributions(&line_attrs, file_content, 0)
+                };
 
                 attributions.insert(entry.file.clone(), (char_attrs, line_attrs));
             }
@@ -458,7 +635,7 @@ impl VirtualAttributions {
         repo: Repository,
         base_commit: String,
         attributions: HashMap<String, (Vec<Attribution>, Vec<LineAttribution>)>,
-        file_contents: HashMap<String, String>,
+        file_contents: FileContents,
         ts: u128,
     ) -> Self {
         VirtualAttributions {
@@ -475,7 +652,7 @@ impl VirtualAttributions {
         repo: Repository,
         base_commit: String,
         attributions: HashMap<String, (Vec<Attribution>, Vec<LineAttribution>)>,
-        file_contents: HashMap<String, String>,
+        file_contents: FileContents,
         prompts: BTreeMap<String, BTreeMap<String, PromptRecord>>,
         ts: u128,
     ) -> Self {
@@ -489,6 +666,13 @@ impl VirtualAttributions {
         }
     }
 
+    /// Paths of the attributed files, in order
+    fn sorted_file_paths(&self) -> Vec<&String> {
+        let mut file_paths: Vec<&String> = self.attributions.keys().collect();
+        file_paths.sort();
+        file_paths
+    }
+
     /// Convert this VirtualAttributions to an AuthorshipLog
     pub fn to_authorship_log(
         &self,
@@ -510,25 +694,49 @@ impl VirtualAttributions {
             })
             .collect();
 
-        // Process each file
-        for (file_path, (_, line_attrs)) in &self.attributions {
+        // Process each file in path order so the same state always serializes the same way
+        for file_path in self.sorted_file_paths() {
+            let (char_attrs, line_attrs) = &self.attributions[file_path];
             if line_attrs.is_empty() {
                 continue;
             }
 
-            // Group line attributions by author
-            let mut author_lines: HashMap<String, Vec<u32>> = HashMap::new();
+            // Column ranges for lines co-written by several authors
+            let mut author_columns = self
+                .file_contents
+                .get(file_path)
+                .map(|content| attributions_to_column_ranges(char_attrs, content))
+                .unwrap_or_default();
+
+            // Group line attributions by author, keeping the lowest confidence seen
+            let mut author_lines: BTreeMap<String, Vec<u32>> = BTreeMap::new();
+            let mut author_confidence: HashMap<String, Option<u8>> = HashMap::new();
+            // Lines a human edited that kept their AI attribution
+            let mut author_overridden: HashMap<String, Vec<u32>> = HashMap::new();
             for line_attr in line_attrs {
+                let kept_after_override =
+                    line_attr.overrode.as_deref() == Some(line_attr.author_id.as_str());
                 for line in line_attr.start_line..=line_attr.end_line {
                     author_lines
                         .entry(line_attr.author_id.clone())
                         .or_default()
                         .push(line);
+                    if kept_after_override {
+                        author_overridden
+                            .entry(line_attr.author_id.clone())
+                            .or_default()
+                            .push(line);
+                    }
                 }
+                let confidence = author_confidence
+                    .entry(line_attr.author_id.clone())
+                    .or_insert(line_attr.confidence);
+                *confidence = combine_confidence(*confidence, line_attr.confidence);
             }
 
             // Create attestation entries for each author
             for (author_id, mut lines) in author_lines {
+                let confidence = author_confidence.get(&author_id).copied().flatten();
                 lines.sort();
                 lines.dedup();
 
@@ -573,15 +781,28 @@ impl VirtualAttributions {
                 }
 
                 // Create attestation entry
+                let columns = author_columns.remove(&author_id).unwrap_or_default();
+                let overridden = author_overridden
+                    .remove(&author_id)
+                    .map(|mut lines| {
+                        lines.sort_unstable();
+                        lines.dedup();
+                        LineRange::compress_lines(&lines)
+                    })
+                    .unwrap_or_default();
                 let entry = crate::authorship::authorship_log_serialization::AttestationEntry::new(
                     author_id, ranges,
-                );
+                )
+                .with_confidence(confidence)
+                .with_column_ranges(columns)
+                .with_overridden_lines(overridden);
 
                 // Add to authorship log
                 let file_attestation = authorship_log.get_or_create_file(file_path);
                 file_attestation.add_entry(entry);
             }
         }
+        authorship_log.record_author_classes();
 
         Ok(authorship_log)
     }
@@ -600,7 +821,8 @@ fn collect_committed_hunks(
     if parent_sha == "initial" {
         // For initial commit, use git diff against the empty tree
         let empty_tree = "4b825dc642cb6eb9a060e54bf8d69288fbee4904"; // Git's empty tree hash
-        let added_lines = repo.diff_added_lines(empty_tree, commit_sha, pathspecs)?;
+        let mut added_lines = repo.diff_added_lines(empty_tree, commit_sha, pathspecs)?;
+        smudge_added_lines(repo, empty_tree, commit_sha, &mut added_lines);
 
         for (file_path, lines) in added_lines {
             if !lines.is_empty() {
@@ -611,7 +833,8 @@ fn collect_committed_hunks(
     }
 
     // Use git diff to get added lines directly
-    let added_lines = repo.diff_added_lines(parent_sha, commit_sha, pathspecs)?;
+    let mut added_lines = repo.diff_added_lines(parent_sha, commit_sha, pathspecs)?;
+    smudge_added_lines(repo, parent_sha, commit_sha, &mut added_lines);
 
     for (file_path, lines) in added_lines {
         if !lines.is_empty() {
@@ -622,76 +845,53 @@ fn collect_committed_hunks(
     Ok(committed_hunks)
 }
 
-/// Helper function to collect unstaged line ranges (lines in working directory but not in commit)
-/// Returns (unstaged_hunks, pure_insertion_hunks)
-/// pure_insertion_hunks contains lines that were purely inserted (old_count=0), not modifications
-fn collect_unstaged_hunks(
-    repo: &Repository,
-    commit_sha: &str,
-    pathspecs: Option<&HashSet<String>>,
-) -> Result<
-    (
-        HashMap<String, Vec<LineRange>>,
-        HashMap<String, Vec<LineRange>>,
-    ),
-    GitAiError,
-> {
-    let mut unstaged_hunks: HashMap<String, Vec<LineRange>> = HashMap::new();
-    let mut pure_insertion_hunks: HashMap<String, Vec<LineRange>> = HashMap::new();
-
-    // Use git diff to get added lines in working directory vs commit, with insertion tracking
-    let (added_lines, insertion_lines) =
-        repo.diff_workdir_added_lines_with_insertions(commit_sha, pathspecs)?;
-
-    for (file_path, lines) in added_lines {
-        if !lines.is_empty() {
-            unstaged_hunks.insert(file_path, LineRange::compress_lines(&lines));
-        }
-    }
-
-    for (file_path, lines) in insertion_lines {
-        if !lines.is_empty() {
-            pure_insertion_hunks.insert(file_path, LineRange::compress_lines(&lines));
-        }
-    }
-
-    // Check for untracked files in pathspecs that git diff didn't find
-    // These are files that exist in the working directory but aren't tracked by git
-    if let Some(paths) = pathspecs {
-        if let Ok(workdir) = repo.workdir() {
-            for pathspec in paths {
-                // Skip if we already found this file in git diff
-                if unstaged_hunks.contains_key(pathspec) {
-                    continue;
-                }
+/// How a line of a file's working copy relates to the version of the file in a commit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LineMatch {
+    /// The commit has the same line, at this line number
+    Same(u32),
+    /// The commit has a different version of the line here, e.g. the working copy edited it
+    /// again after it was staged
+    Edited(u32),
+    /// The line is only in the working copy
+    Unstaged,
+}
 
-                // Check if file exists in the commit - if it does, it's tracked and git diff should handle it
-                // Only process truly untracked files (files that don't exist in the commit tree)
-                if file_exists_in_commit(repo, commit_sha, pathspec).unwrap_or(false) {
-                    continue;
+/// Aligns the working copy's lines with the commit's using a line diff, so repeated lines and
+/// lines moved by unstaged deletions land where they are in the commit. Lines are compared
+/// without their line endings, so a last line that only gained a newline is the same line.
+/// Replaced runs pair up line by line; working lines beyond the commit's side are unstaged.
+fn align_working_lines(committed: &str, working: &str) -> Vec<LineMatch> {
+    let committed_lines: Vec<&str> = committed.lines().collect();
+    let working_lines: Vec<&str> = working.lines().collect();
+    let mut alignment = vec![LineMatch::Unstaged; working_lines.len()];
+    for op in
+        similar::capture_diff_slices(similar::Algorithm::Myers, &committed_lines, &working_lines)
+    {
+        match op {
+            similar::DiffOp::Equal {
+                old_index,
+                new_index,
+                len,
+            } => {
+                for i in 0..len {
+                    alignment[new_index + i] = LineMatch::Same((old_index + i + 1) as u32);
                 }
-
-                // Check if file exists in working directory
-                let file_path = workdir.join(pathspec);
-                if file_path.exists() && file_path.is_file() {
-                    // Try to read the file
-                    if let Ok(content) = std::fs::read_to_string(&file_path) {
-                        // Count the lines - all lines are "unstaged" since the file is untracked
-                        let line_count = content.lines().count() as u32;
-                        if line_count > 0 {
-                            // Create a range covering all lines (1-indexed)
-                            let range = vec![LineRange::Range(1, line_count)];
-                            unstaged_hunks.insert(pathspec.clone(), range.clone());
-                            // Untracked files are pure insertions (the entire file is new)
-                            pure_insertion_hunks.insert(pathspec.clone(), range);
-                        }
-                    }
+            }
+            similar::DiffOp::Replace {
+                old_index,
+                old_len,
+                new_index,
+                new_len,
+            } => {
+                for i in 0..old_len.min(new_len) {
+                    alignment[new_index + i] = LineMatch::Edited((old_index + i + 1) as u32);
                 }
             }
+            similar::DiffOp::Delete { .. } | similar::DiffOp::Insert { .. } => {}
         }
     }
-
-    Ok((unstaged_hunks, pure_insertion_hunks))
+    alignment
 }
 
 impl VirtualAttributions {
@@ -735,123 +935,148 @@ impl VirtualAttributions {
         let mut initial_files: StdHashMap<String, Vec<LineAttribution>> = StdHashMap::new();
         let mut referenced_prompts: HashSet<String> = HashSet::new();
 
-        // Get committed hunks (in commit coordinates) and unstaged hunks (in working directory coordinates)
+        // Lines this commit added, in commit coordinates
         let committed_hunks = collect_committed_hunks(repo, parent_sha, commit_sha, pathspecs)?;
-        let (mut unstaged_hunks, pure_insertion_hunks) =
-            collect_unstaged_hunks(repo, commit_sha, pathspecs)?;
-
-        // IMPORTANT: If a line appears in both committed_hunks and unstaged_hunks, it means:
-        // - The line was committed in this commit (in commit coordinates)
-        // - The line was then modified again in the working directory (in workdir coordinates)
-        // Since both use the same line numbering after the commit (workdir coordinates = commit coordinates
-        // for the committed state), we can directly compare line numbers.
-        // We should treat these lines as committed, not unstaged, because the attribution belongs
-        // to the commit even if there's a subsequent unstaged modification.
-        //
-        // HOWEVER: If a line is a PURE INSERTION (old_count=0), it means a new line was inserted
-        // at that position, pushing existing lines down. In this case, the line number overlap
-        // doesn't mean the same line - it's a different line at the same position!
-        // We should NOT filter out pure insertions even if they overlap with committed line numbers.
-        for (file_path, committed_ranges) in &committed_hunks {
-            if let Some(unstaged_ranges) = unstaged_hunks.get_mut(file_path) {
-                // Expand both to line numbers for comparison
-                let committed_lines: std::collections::HashSet<u32> =
-                    committed_ranges.iter().flat_map(|r| r.expand()).collect();
-
-                // Get pure insertion lines for this file (these should NOT be filtered out)
-                let pure_insertion_lines: std::collections::HashSet<u32> = pure_insertion_hunks
-                    .get(file_path)
-                    .map(|ranges| ranges.iter().flat_map(|r| r.expand()).collect())
-                    .unwrap_or_default();
-
-                // Filter out any unstaged lines that were also committed
-                // (these are lines that were committed, then modified again in workdir)
-                // BUT keep pure insertions even if they overlap with committed line numbers
-                let mut filtered_unstaged_lines: Vec<u32> = unstaged_ranges
-                    .iter()
-                    .flat_map(|r| r.expand())
-                    .filter(|line| {
-                        // Keep the line if it's NOT in committed, OR if it's a pure insertion
-                        !committed_lines.contains(line) || pure_insertion_lines.contains(line)
-                    })
-                    .collect();
-
-                if filtered_unstaged_lines.is_empty() {
-                    unstaged_ranges.clear();
-                } else {
-                    filtered_unstaged_lines.sort_unstable();
-                    filtered_unstaged_lines.dedup();
-                    *unstaged_ranges = LineRange::compress_lines(&filtered_unstaged_lines);
-                }
-            }
-        }
-
-        // Remove files with no unstaged hunks
-        unstaged_hunks.retain(|_, ranges| !ranges.is_empty());
-
-        // Process each file
-        for (file_path, (_, line_attrs)) in &self.attributions {
+        let commit_tree = repo.find_commit(commit_sha.to_string())?.tree()?;
+        // Snapshots from `git add -p` live in the working log these attributions came from, which
+        // for an amend is the original commit's rather than the parent's
+        let staged = repo
+            .storage
+            .staged_attributions_for_base_commit(&self.base_commit);
+
+        // Process each file in path order so the same state always serializes the same way
+        for file_path in self.sorted_file_paths() {
+            let (char_attrs, line_attrs) = &self.attributions[file_path];
             if line_attrs.is_empty() {
                 continue;
             }
 
-            // Get unstaged lines for this file (in working directory coordinates)
-            let mut unstaged_lines: Vec<u32> = Vec::new();
-            if let Some(unstaged_ranges) = unstaged_hunks.get(file_path) {
-                for range in unstaged_ranges {
-                    unstaged_lines.extend(range.expand());
-                }
-                unstaged_lines.sort_unstable();
-            }
+            // Line attributions are in working copy coordinates. Align the working copy with the
+            // commit's version of the file to tell which lines were committed and where they are
+            let committed_blob = commit_tree
+                .get_path(std::path::Path::new(file_path))
+                .ok()
+                .map(|entry| entry.id());
+            let committed_content = committed_blob
+                .as_ref()
+                .and_then(|blob_id| {
+                    let content = repo.find_blob(blob_id.clone()).ok()?.content().ok()?;
+                    Some(smudge_blob_content(repo, file_path, blob_id, content))
+                })
+                .map(|content| file_text::decode(&content))
+                .unwrap_or_default();
+            let working_content: Arc<str> = match self.file_contents.get(file_path) {
+                Some(content) => Arc::clone(content),
+                None => repo
+                    .workdir()
+                    .ok()
+                    .map(|workdir| {
+                        file_text::read(&workdir.join(repo_path::key_to_path(file_path)))
+                    })
+                    .unwrap_or_default()
+                    .into(),
+            };
+            let alignment = align_working_lines(&committed_content, &working_content);
 
-            // Split line attributions into committed and uncommitted
-            // VirtualAttributions has line numbers in working directory coordinates,
-            // so we need to convert to commit coordinates before comparing with committed hunks
-            let mut committed_lines_map: StdHashMap<String, Vec<u32>> = StdHashMap::new();
-            let mut uncommitted_lines_map: StdHashMap<String, Vec<u32>> = StdHashMap::new();
+            // Attribution recorded when the committed version was staged with `git add -p`
+            let staged_lines = staged
+                .files
+                .get(file_path)
+                .filter(|file| committed_blob.as_deref() == Some(file.blob.as_str()))
+                .map(|file| &file.line_attributions);
 
-            // Get the committed hunks for this file (if any) - these are in commit coordinates
             let file_committed_hunks = committed_hunks.get(file_path);
+            let is_committed = |commit_line: u32| {
+                file_committed_hunks
+                    .is_some_and(|hunks| hunks.iter().any(|hunk| hunk.contains(commit_line)))
+            };
+
+            let mut committed_lines_map: BTreeMap<String, Vec<u32>> = BTreeMap::new();
+            let mut uncommitted_lines_map: BTreeMap<String, Vec<u32>> = BTreeMap::new();
+            // Lowest confidence per author on each side of the split
+            let mut committed_confidence: StdHashMap<String, Option<u8>> = StdHashMap::new();
+            let mut uncommitted_confidence: StdHashMap<String, Option<u8>> = StdHashMap::new();
+            // Committed lines a human edited that kept their AI attribution
+            let mut committed_overridden: StdHashMap<String, Vec<u32>> = StdHashMap::new();
+
+            let mut commit_line = |line_attr: &LineAttribution, commit_line_num: u32| {
+                committed_lines_map
+                    .entry(line_attr.author_id.clone())
+                    .or_default()
+                    .push(commit_line_num);
+                let confidence = committed_confidence
+                    .entry(line_attr.author_id.clone())
+                    .or_insert(line_attr.confidence);
+                *confidence = combine_confidence(*confidence, line_attr.confidence);
+                if line_attr.overrode.as_deref() == Some(line_attr.author_id.as_str()) {
+                    committed_overridden
+                        .entry(line_attr.author_id.clone())
+                        .or_default()
+                        .push(commit_line_num);
+                }
+            };
 
             for line_attr in line_attrs {
-                // Check each line individually
                 for workdir_line_num in line_attr.start_line..=line_attr.end_line {
-                    // Check if this line is unstaged (in working directory but not in commit)
-                    let is_unstaged = unstaged_lines.binary_search(&workdir_line_num).is_ok();
+                    let Some(line_match) = alignment.get(workdir_line_num as usize - 1) else {
+                        continue;
+                    };
+                    let committed_as = match *line_match {
+                        // With a staged snapshot, the committed version is attributed from it
+                        LineMatch::Same(_) if staged_lines.is_some() => None,
+                        LineMatch::Same(line) => Some(line),
+                        // The working copy edited a line after it was staged. Without a snapshot
+                        // of the staged version, its author is the best guess for the commit;
+                        // with one, the edit is carried over like any other unstaged change
+                        LineMatch::Edited(line) if staged_lines.is_none() && is_committed(line) => {
+                            Some(line)
+                        }
+                        LineMatch::Edited(_) | LineMatch::Unstaged => {
+                            uncommitted_lines_map
+                                .entry(line_attr.author_id.clone())
+                                .or_default()
+                                .push(workdir_line_num);
+                            let confidence = uncommitted_confidence
+                                .entry(line_attr.author_id.clone())
+                                .or_insert(line_attr.confidence);
+                            *confidence = combine_confidence(*confidence, line_attr.confidence);
+                            referenced_prompts.insert(line_attr.author_id.clone());
+                            None
+                        }
+                    };
+                    // Lines that were already in the parent commit are neither committed nor
+                    // carried over
+                    if let Some(line) = committed_as.filter(|line| is_committed(*line)) {
+                        commit_line(line_attr, line);
+                    }
+                }
+            }
 
-                    if is_unstaged {
-                        // Line is unstaged, mark as uncommitted
-                        uncommitted_lines_map
-                            .entry(line_attr.author_id.clone())
-                            .or_default()
-                            .push(workdir_line_num);
-                        referenced_prompts.insert(line_attr.author_id.clone());
-                    } else {
-                        // Convert working directory line number to commit line number
-                        // by subtracting the count of unstaged lines before this line
-                        let adjustment = unstaged_lines
-                            .iter()
-                            .filter(|&&l| l < workdir_line_num)
-                            .count() as u32;
-                        let commit_line_num = workdir_line_num - adjustment;
-
-                        // Check if this commit line number is in any committed hunk
-                        let is_committed = if let Some(hunks) = file_committed_hunks {
-                            hunks.iter().any(|hunk| hunk.contains(commit_line_num))
-                        } else {
-                            false
-                        };
+            if let Some(staged_lines) = staged_lines {
+                for line_attr in staged_lines {
+                    for line in line_attr.start_line..=line_attr.end_line {
+                        if is_committed(line) {
+                            commit_line(line_attr, line);
+                        }
+                    }
+                }
+            }
 
-                        if is_committed {
-                            // Line was committed in this commit (use commit coordinates)
-                            committed_lines_map
-                                .entry(line_attr.author_id.clone())
+            // Column ranges of co-written lines that were committed, in commit coordinates. Only
+            // lines committed exactly as they are in the working copy keep their columns
+            let mut committed_columns: StdHashMap<String, Vec<ColumnRange>> = StdHashMap::new();
+            if let Some(content) = self.file_contents.get(file_path) {
+                for (author_id, ranges) in attributions_to_column_ranges(char_attrs, content) {
+                    for range in ranges {
+                        if let Some(LineMatch::Same(commit_line_num)) =
+                            alignment.get(range.line as usize - 1)
+                            && is_committed(*commit_line_num)
+                        {
+                            committed_columns
+                                .entry(author_id.clone())
                                 .or_default()
-                                .push(commit_line_num);
-                        } else {
+                                .push(ColumnRange::new(*commit_line_num, range.start, range.end));
                         }
-                        // Note: Lines that are neither unstaged nor in committed_hunks are lines that
-                        // already existed in the parent commit. They are discarded (not added to uncommitted).
                     }
                 }
             }
@@ -860,6 +1085,16 @@ impl VirtualAttributions {
             if !committed_lines_map.is_empty() {
                 // Create attestation entries from committed lines
                 for (author_id, mut lines) in committed_lines_map {
+                    let confidence = committed_confidence.get(&author_id).copied().flatten();
+                    let column_ranges = committed_columns.remove(&author_id).unwrap_or_default();
+                    let overridden = committed_overridden
+                        .remove(&author_id)
+                        .map(|mut lines| {
+                            lines.sort_unstable();
+                            lines.dedup();
+                            LineRange::compress_lines(&lines)
+                        })
+                        .unwrap_or_default();
                     lines.sort();
                     lines.dedup();
 
@@ -906,7 +1141,10 @@ impl VirtualAttributions {
                     let entry =
                         crate::authorship::authorship_log_serialization::AttestationEntry::new(
                             author_id, ranges,
-                        );
+                        )
+                        .with_confidence(confidence)
+                        .with_column_ranges(column_ranges)
+                        .with_overridden_lines(overridden);
 
                     let file_attestation = authorship_log.get_or_create_file(file_path);
                     file_attestation.add_entry(entry);
@@ -918,6 +1156,7 @@ impl VirtualAttributions {
                 // Convert the map into line attributions
                 let mut uncommitted_line_attrs = Vec::new();
                 for (author_id, mut lines) in uncommitted_lines_map {
+                    let confidence = uncommitted_confidence.get(&author_id).copied().flatten();
                     lines.sort();
                     lines.dedup();
 
@@ -934,24 +1173,25 @@ impl VirtualAttributions {
                             range_end = line;
                         } else {
                             // End current range and start new one
-                            uncommitted_line_attrs.push(LineAttribution {
-                                start_line: range_start,
-                                end_line: range_end,
-                                author_id: author_id.clone(),
-                                overrode: None,
-                            });
+                            uncommitted_line_attrs.push(
+                                LineAttribution::new(
+                                    range_start,
+                                    range_end,
+                                    author_id.clone(),
+                                    None,
+                                )
+                                .with_confidence(confidence),
+                            );
                             range_start = line;
                             range_end = line;
                         }
                     }
 
                     // Add the last range
-                    uncommitted_line_attrs.push(LineAttribution {
-                        start_line: range_start,
-                        end_line: range_end,
-                        author_id: author_id.clone(),
-                        overrode: None,
-                    });
+                    uncommitted_line_attrs.push(
+                        LineAttribution::new(range_start, range_end, author_id.clone(), None)
+                            .with_confidence(confidence),
+                    );
                 }
 
                 initial_files.insert(file_path.clone(), uncommitted_line_attrs);
@@ -973,6 +1213,7 @@ impl VirtualAttributions {
             files: initial_files,
             prompts: initial_prompts,
         };
+        authorship_log.record_author_classes();
 
         Ok((authorship_log, initial_attributions))
     }
@@ -1124,12 +1365,14 @@ impl VirtualAttributions {
             let file_content = self
                 .file_contents
                 .get(file_path)
-                .cloned()
+                .map(|content| &**content)
                 .unwrap_or_default();
-            *line_attrs = crate::authorship::attribution_tracker::attributions_to_line_attributions(
-                char_attrs,
-                &file_content,
-            );
+            *line_attrs =
+                crate::authorship::attribution_tracker::attributions_to_line_attributions_with_policy(
+                    char_attrs,
+                    file_content,
+                    Config::get().override_policy(),
+                );
         }
     }
 }
@@ -1137,11 +1380,14 @@ impl VirtualAttributions {
 pub fn merge_attributions_favoring_first(
     primary: VirtualAttributions,
     secondary: VirtualAttributions,
-    final_state: HashMap<String, String>,
+    final_state: FileContents,
 ) -> Result<VirtualAttributions, GitAiError> {
-    use crate::authorship::attribution_tracker::AttributionTracker;
+    use crate::authorship::attribution_tracker::{AttributionConfig, AttributionTracker};
 
-    let tracker = AttributionTracker::new();
+    let tracker = AttributionTracker::with_config(
+        AttributionConfig::default()
+            .with_move_timestamp_policy(Config::get().move_timestamp_policy()),
+    );
     let ts = primary.ts;
     let repo = primary.repo.clone();
     let base_commit = primary.base_commit.clone();
@@ -1203,9 +1449,10 @@ pub fn merge_attributions_favoring_first(
 
         // Convert to line attributions
         let merged_line_attrs =
-            crate::authorship::attribution_tracker::attributions_to_line_attributions(
+            crate::authorship::attribution_tracker::attributions_to_line_attributions_with_policy(
                 &merged_char_attrs,
                 final_content,
+                Config::get().override_policy(),
             );
 
         merged
@@ -1213,7 +1460,7 @@ pub fn merge_attributions_favoring_first(
             .insert(file_path.clone(), (merged_char_attrs, merged_line_attrs));
         merged
             .file_contents
-            .insert(file_path, final_content.clone());
+            .insert(file_path, Arc::clone(final_content));
     }
 
     // Save total_additions and total_deletions from the newest PromptRecord
@@ -1321,12 +1568,10 @@ fn merge_char_attributions(
         // Create attributions for uncovered ranges
         for (start, end) in uncovered_ranges {
             if start < end {
-                result.push(Attribution::new(
-                    start,
-                    end,
-                    attr.author_id.clone(),
-                    attr.ts,
-                ));
+                result.push(
+                    Attribution::new(start, end, attr.author_id.clone(), attr.ts)
+                        .with_confidence(attr.confidence),
+                );
             }
         }
     }
@@ -1351,23 +1596,26 @@ fn compute_attributions_for_file(
     ai_blame_opts.newest_commit = Some(base_commit.to_string());
 
     // Run blame at the base commit
-    let ai_blame = repo.blame(file_path, &ai_blame_opts);
+    let ai_blame = repo.blame_with_confidence(file_path, &ai_blame_opts);
 
     match ai_blame {
-        Ok((blames, _)) => {
-            // Convert blame results to line attributions
+        Ok((blames, _, confidences)) => {
+            // Convert blame results to line attributions. These are reconstructed from
+            // history rather than recorded by a checkpoint, so they're never fully certain.
             let mut line_attributions = Vec::new();
             for (line, author) in blames {
                 // Skip human-only lines as they don't need tracking
                 if author == CheckpointKind::Human.to_str() {
                     continue;
                 }
-                line_attributions.push(LineAttribution {
-                    start_line: line,
-                    end_line: line,
-                    author_id: author.clone(),
-                    overrode: None,
-                });
+                let confidence = combine_confidence(
+                    confidences.get(&line).copied(),
+                    Some(BLAME_IN_CONTEXT_CONFIDENCE),
+                );
+                line_attributions.push(
+                    LineAttribution::new(line, line, author.clone(), None)
+                        .with_confidence(confidence),
+                );
             }
 
             // Get the file content at this commit to convert to character attributions
@@ -1392,6 +1640,18 @@ fn compute_attributions_for_file(
     }
 }
 
+/// What an attribution snapshot of `base_commit` depends on: the commit, its tree and the
+/// authorship notes blame reads. None when the commit can't be resolved.
+fn attribution_snapshot_key(repo: &Repository, base_commit: &str) -> Option<String> {
+    let commit = repo.find_commit(base_commit.to_string()).ok()?;
+    let tree = commit.tree().ok()?;
+    let notes = repo
+        .revparse_single(&format!("refs/notes/{}", AI_AUTHORSHIP_REFNAME))
+        .map(|notes| notes.id())
+        .unwrap_or_default();
+    Some(format!("{}:{}:{}", commit.id(), tree.id(), notes))
+}
+
 fn get_file_content_at_commit(
     repo: &Repository,
     commit_sha: &str,
@@ -1403,8 +1663,13 @@ fn get_file_content_at_commit(
     match tree.get_path(std::path::Path::new(file_path)) {
         Ok(entry) => {
             if let Ok(blob) = repo.find_blob(entry.id()) {
-                let blob_content = blob.content().unwrap_or_default();
-                Ok(String::from_utf8_lossy(&blob_content).to_string())
+                let blob_content = smudge_blob_content(
+                    repo,
+                    file_path,
+                    &entry.id(),
+                    blob.content().unwrap_or_default(),
+                );
+                Ok(file_text::decode(&blob_content))
             } else {
                 Ok(String::new())
             }
@@ -1413,17 +1678,6 @@ fn get_file_content_at_commit(
     }
 }
 
-/// Check if a file exists in a commit's tree
-fn file_exists_in_commit(
-    repo: &Repository,
-    commit_sha: &str,
-    file_path: &str,
-) -> Result<bool, GitAiError> {
-    let commit = repo.find_commit(commit_sha.to_string())?;
-    let tree = commit.tree()?;
-    Ok(tree.get_path(std::path::Path::new(file_path)).is_ok())
-}
-
 #[cfg(test)]
 mod tests {
 
@@ -1483,4 +1737,75 @@ mod tests {
 
         assert!(!virtual_attributions.files().is_empty());
     }
+
+    #[test]
+    fn test_same_state_serializes_byte_identically() {
+        let tmp_repo = crate::git::test_utils::TmpRepo::new().unwrap();
+
+        // Each call builds fresh HashMaps, which iterate in a different order every time
+        let build = || {
+            let mut attributions = HashMap::new();
+            let mut file_contents = HashMap::new();
+            let mut prompts = BTreeMap::new();
+            for file_idx in 0..8 {
+                let path = format!("src/file_{}.rs", file_idx);
+                let content = "a\nb\nc\nd\ne\nf\n".to_string();
+                let line_attrs: Vec<LineAttribution> = (0..6)
+                    .map(|line| {
+                        let author = format!("ai{:05}", (file_idx + line) % 4);
+                        LineAttribution::new(line + 1, line + 1, author, None)
+                    })
+                    .collect();
+                let char_attrs = line_attributions_to_attributions(&line_attrs, &content, 1);
+                attributions.insert(path.clone(), (char_attrs, line_attrs));
+                file_contents.insert(path, Arc::from(content));
+            }
+            for author_idx in 0..4 {
+                let record = PromptRecord {
+                    agent_id: crate::authorship::working_log::AgentId {
+                        tool: "cursor".to_string(),
+                        id: format!("session-{}", author_idx),
+                        model: "gpt-4".to_string(),
+                    },
+                    human_author: None,
+                    human_authors: Vec::new(),
+                    messages: vec![],
+                    total_additions: 0,
+                    total_deletions: 0,
+                    accepted_lines: 0,
+                    overriden_lines: 0,
+                    first_checkpoint_at: None,
+                    last_checkpoint_at: None,
+                };
+                prompts.insert(
+                    format!("ai{:05}", author_idx),
+                    BTreeMap::from([(String::new(), record)]),
+                );
+            }
+            VirtualAttributions::new_with_prompts(
+                tmp_repo.gitai_repo().clone(),
+                "base".to_string(),
+                attributions,
+                file_contents,
+                prompts,
+                1,
+            )
+        };
+
+        let first = build().to_authorship_log().unwrap();
+        let second = build().to_authorship_log().unwrap();
+        assert_eq!(
+            first.serialize_to_string().unwrap(),
+            second.serialize_to_string().unwrap()
+        );
+
+        let paths: Vec<&str> = first
+            .attestations
+            .iter()
+            .map(|f| f.file_path.as_str())
+            .collect();
+        let mut sorted_paths = paths.clone();
+        sorted_paths.sort();
+        assert_eq!(paths, sorted_paths);
+    }
 }
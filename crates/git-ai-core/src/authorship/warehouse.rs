@@ -0,0 +1,378 @@
+//! Authorship data as flat tables for a data warehouse, exported a batch of new commits at a time.
+//!
+//! Each export reads the commits reachable from a revision but not from the tips earlier exports
+//! stopped at (the watermark), so a nightly job only reads what landed since the last run. Rows
+//! are only ever appended: a commit's rows are final once exported, and a session gets its row
+//! the first time one of its prompts is seen.
+
+use crate::authorship::authorship_log::LineRange;
+use crate::error::GitAiError;
+use crate::git::refs::get_authorship;
+use crate::git::repository::Repository;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::collections::BTreeSet;
+
+/// The tables, in the order they're written
+pub const TABLES: [&str; 4] = ["commits", "attestations", "prompts", "sessions"];
+
+/// Where the last export of a repository stopped
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Watermark {
+    /// Commits exported along with their history. A force-push leaves the old tip here too, so
+    /// its commits aren't exported again.
+    pub tips: Vec<String>,
+    /// Sessions that already have a row
+    pub sessions: BTreeSet<String>,
+    /// RFC 3339 time of the export
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exported_at: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CommitRow {
+    pub repo: String,
+    pub sha: String,
+    pub parents: Vec<String>,
+    /// `Name <email>`
+    pub author: String,
+    /// RFC 3339
+    pub committed_at: String,
+    pub subject: String,
+    pub has_authorship: bool,
+    /// Lines of the commit's files AI wrote, as its note attests them
+    pub ai_lines: u32,
+    pub prompts: u32,
+}
+
+/// One line range of a file a prompt wrote, as of the commit
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AttestationRow {
+    pub repo: String,
+    pub commit: String,
+    pub file: String,
+    pub session_id: String,
+    /// 1-indexed, inclusive
+    pub start_line: u32,
+    pub end_line: u32,
+    /// Lines of the range a human edited without taking over the attribution
+    pub overridden_lines: u32,
+    /// How certain the attribution is, as a percentage; null when it was recorded directly
+    pub confidence: Option<u8>,
+}
+
+/// One session's record in one commit's note
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PromptRow {
+    pub repo: String,
+    pub commit: String,
+    pub session_id: String,
+    pub tool: String,
+    pub model: String,
+    pub prompter: Option<String>,
+    pub total_additions: u32,
+    pub total_deletions: u32,
+    pub accepted_lines: u32,
+    pub overridden_lines: u32,
+    pub messages: u32,
+    pub first_checkpoint_at: Option<String>,
+    pub last_checkpoint_at: Option<String>,
+}
+
+/// A session, as of the first commit it wrote lines in
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SessionRow {
+    pub repo: String,
+    pub session_id: String,
+    pub tool: String,
+    pub model: String,
+    /// The session's id in the agent's own terms
+    pub agent_session_id: String,
+    pub prompter: Option<String>,
+    pub first_commit: String,
+    pub first_committed_at: String,
+}
+
+/// The rows of one export and the watermark to record once they're written
+#[derive(Debug, Clone, Default)]
+pub struct WarehouseBatch {
+    pub commits: Vec<CommitRow>,
+    pub attestations: Vec<AttestationRow>,
+    pub prompts: Vec<PromptRow>,
+    pub sessions: Vec<SessionRow>,
+    pub watermark: Watermark,
+}
+
+impl WarehouseBatch {
+    /// Each table's rows as newline-delimited JSON, in [`TABLES`] order
+    pub fn tables(&self) -> Result<Vec<(&'static str, String)>, GitAiError> {
+        Ok(vec![
+            (TABLES[0], ndjson(&self.commits)?),
+            (TABLES[1], ndjson(&self.attestations)?),
+            (TABLES[2], ndjson(&self.prompts)?),
+            (TABLES[3], ndjson(&self.sessions)?),
+        ])
+    }
+}
+
+fn ndjson<T: Serialize>(rows: &[T]) -> Result<String, GitAiError> {
+    let mut out = String::new();
+    for row in rows {
+        out.push_str(&serde_json::to_string(row)?);
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+fn rfc3339(seconds: i64) -> String {
+    chrono::DateTime::from_timestamp(seconds, 0)
+        .unwrap_or_default()
+        .format("%Y-%m-%dT%H:%M:%SZ")
+        .to_string()
+}
+
+/// First and last line of `range`
+fn bounds(range: &LineRange) -> (u32, u32) {
+    match range {
+        LineRange::Single(line) => (*line, *line),
+        LineRange::Range(start, end) => (*start, *end),
+    }
+}
+
+/// The rows of the commits `rev` reaches that `previous` doesn't, oldest first, with the
+/// watermark covering both
+pub fn export_batch(
+    repo: &Repository,
+    repo_id: &str,
+    rev: &str,
+    previous: &Watermark,
+) -> Result<WarehouseBatch, GitAiError> {
+    let tip = repo.revparse_single(rev)?.peel_to_commit()?.id();
+    // Tips gc'd since the last export can't be excluded; their commits are gone anyway
+    let known_tips: Vec<&str> = previous
+        .tips
+        .iter()
+        .map(String::as_str)
+        .filter(|sha| repo.revparse_single(sha).is_ok())
+        .collect();
+
+    let mut args = vec![
+        "log",
+        "--reverse",
+        "--format=%H%x00%P%x00%ct%x00%an <%ae>%x00%s",
+        &tip,
+    ];
+    if !known_tips.is_empty() {
+        args.push("--not");
+        args.extend(known_tips.iter().copied());
+    }
+    let output = repo.git(&args)?;
+
+    let mut batch = WarehouseBatch {
+        watermark: Watermark {
+            sessions: previous.sessions.clone(),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    for line in output.lines() {
+        let mut fields = line.splitn(5, '\0');
+        let (Some(sha), Some(parents), Some(time), Some(author)) =
+            (fields.next(), fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+        let committed_at = rfc3339(time.parse().unwrap_or(0));
+        let mut commit = CommitRow {
+            repo: repo_id.to_string(),
+            sha: sha.to_string(),
+            parents: parents.split_whitespace().map(str::to_string).collect(),
+            author: author.to_string(),
+            committed_at: committed_at.clone(),
+            subject: fields.next().unwrap_or("").to_string(),
+            has_authorship: false,
+            ai_lines: 0,
+            prompts: 0,
+        };
+        let Some(log) = get_authorship(repo, sha) else {
+            batch.commits.push(commit);
+            continue;
+        };
+        commit.has_authorship = true;
+        commit.prompts = log.metadata.prompts.len() as u32;
+
+        for file in &log.attestations {
+            for entry in &file.entries {
+                let is_ai = log.metadata.prompts.contains_key(&entry.hash);
+                for range in &entry.line_ranges {
+                    let (start_line, end_line) = bounds(range);
+                    if is_ai {
+                        commit.ai_lines += end_line.saturating_sub(start_line) + 1;
+                    }
+                    batch.attestations.push(AttestationRow {
+                        repo: repo_id.to_string(),
+                        commit: sha.to_string(),
+                        file: file.file_path.clone(),
+                        session_id: entry.hash.clone(),
+                        start_line,
+                        end_line,
+                        overridden_lines: range
+                            .expand()
+                            .into_iter()
+                            .filter(|line| entry.overridden_lines.iter().any(|r| r.contains(*line)))
+                            .count() as u32,
+                        confidence: entry.confidence,
+                    });
+                }
+            }
+        }
+
+        for (session_id, record) in &log.metadata.prompts {
+            let prompter = record.prompter().map(str::to_string);
+            batch.prompts.push(PromptRow {
+                repo: repo_id.to_string(),
+                commit: sha.to_string(),
+                session_id: session_id.clone(),
+                tool: record.agent_id.tool.clone(),
+                model: record.agent_id.model.clone(),
+                prompter: prompter.clone(),
+                total_additions: record.total_additions,
+                total_deletions: record.total_deletions,
+                accepted_lines: record.accepted_lines,
+                overridden_lines: record.overriden_lines,
+                messages: record.messages.len() as u32,
+                first_checkpoint_at: record.first_checkpoint_at.map(|t| rfc3339(t as i64)),
+                last_checkpoint_at: record.last_checkpoint_at.map(|t| rfc3339(t as i64)),
+            });
+            if batch.watermark.sessions.insert(session_id.clone()) {
+                batch.sessions.push(SessionRow {
+                    repo: repo_id.to_string(),
+                    session_id: session_id.clone(),
+                    tool: record.agent_id.tool.clone(),
+                    model: record.agent_id.model.clone(),
+                    agent_session_id: record.agent_id.id.clone(),
+                    prompter,
+                    first_commit: sha.to_string(),
+                    first_committed_at: committed_at.clone(),
+                });
+            }
+        }
+        batch.commits.push(commit);
+    }
+
+    // Keep the earlier tips the new one doesn't reach, such as a branch's tip before a
+    // force-push, so their commits stay exported
+    let mut tips = vec![tip.clone()];
+    for known in known_tips {
+        let reached = repo
+            .git(&["merge-base", "--is-ancestor", known, &tip])
+            .is_ok();
+        if !reached && known != tip {
+            tips.push(known.to_string());
+        }
+    }
+    batch.watermark.tips = tips;
+    batch.watermark.exported_at = Some(chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string());
+    Ok(batch)
+}
+
+/// The columns of `table`, as a BigQuery JSON schema
+pub fn table_schema(table: &str) -> Value {
+    let columns: &[(&str, &str, &str)] = match table {
+        "commits" => &[
+            ("repo", "STRING", "REQUIRED"),
+            ("sha", "STRING", "REQUIRED"),
+            ("parents", "STRING", "REPEATED"),
+            ("author", "STRING", "REQUIRED"),
+            ("committed_at", "TIMESTAMP", "REQUIRED"),
+            ("subject", "STRING", "REQUIRED"),
+            ("has_authorship", "BOOLEAN", "REQUIRED"),
+            ("ai_lines", "INTEGER", "REQUIRED"),
+            ("prompts", "INTEGER", "REQUIRED"),
+        ],
+        "attestations" => &[
+            ("repo", "STRING", "REQUIRED"),
+            ("commit", "STRING", "REQUIRED"),
+            ("file", "STRING", "REQUIRED"),
+            ("session_id", "STRING", "REQUIRED"),
+            ("start_line", "INTEGER", "REQUIRED"),
+            ("end_line", "INTEGER", "REQUIRED"),
+            ("overridden_lines", "INTEGER", "REQUIRED"),
+            ("confidence", "INTEGER", "NULLABLE"),
+        ],
+        "prompts" => &[
+            ("repo", "STRING", "REQUIRED"),
+            ("commit", "STRING", "REQUIRED"),
+            ("session_id", "STRING", "REQUIRED"),
+            ("tool", "STRING", "REQUIRED"),
+            ("model", "STRING", "REQUIRED"),
+            ("prompter", "STRING", "NULLABLE"),
+            ("total_additions", "INTEGER", "REQUIRED"),
+            ("total_deletions", "INTEGER", "REQUIRED"),
+            ("accepted_lines", "INTEGER", "REQUIRED"),
+            ("overridden_lines", "INTEGER", "REQUIRED"),
+            ("messages", "INTEGER", "REQUIRED"),
+            ("first_checkpoint_at", "TIMESTAMP", "NULLABLE"),
+            ("last_checkpoint_at", "TIMESTAMP", "NULLABLE"),
+        ],
+        "sessions" => &[
+            ("repo", "STRING", "REQUIRED"),
+            ("session_id", "STRING", "REQUIRED"),
+            ("tool", "STRING", "REQUIRED"),
+            ("model", "STRING", "REQUIRED"),
+            ("agent_session_id", "STRING", "REQUIRED"),
+            ("prompter", "STRING", "NULLABLE"),
+            ("first_commit", "STRING", "REQUIRED"),
+            ("first_committed_at", "TIMESTAMP", "REQUIRED"),
+        ],
+        _ => &[],
+    };
+    Value::Array(
+        columns
+            .iter()
+            .map(|(name, kind, mode)| json!({ "name": name, "type": kind, "mode": mode }))
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::test_utils::TmpRepo;
+
+    #[test]
+    fn test_export_batch_resumes_from_watermark() {
+        let tmp_repo = TmpRepo::new().unwrap();
+        let mut file = tmp_repo.write_file("test.txt", "Line1\n", true).unwrap();
+        tmp_repo
+            .trigger_checkpoint_with_author("test_user")
+            .unwrap();
+        tmp_repo.commit_with_message("Initial commit").unwrap();
+        let repo = tmp_repo.gitai_repo();
+
+        let first = export_batch(repo, "demo", "HEAD", &Watermark::default()).unwrap();
+        assert_eq!(first.commits.len(), 1);
+        assert!(first.sessions.is_empty());
+
+        file.append("Line 2\n").unwrap();
+        tmp_repo
+            .trigger_checkpoint_with_ai("Claude", Some("claude-3-sonnet"), Some("cursor"))
+            .unwrap();
+        tmp_repo.commit_with_message("AI adds a line").unwrap();
+
+        let second = export_batch(repo, "demo", "HEAD", &first.watermark).unwrap();
+        assert_eq!(second.commits.len(), 1);
+        let commit = &second.commits[0];
+        assert_eq!(commit.subject, "AI adds a line");
+        assert_eq!(commit.parents, vec![first.commits[0].sha.clone()]);
+        assert_eq!(commit.ai_lines, 1);
+        assert_eq!(second.attestations[0].start_line, 2);
+        assert_eq!(second.prompts[0].tool, "cursor");
+        assert_eq!(second.sessions.len(), 1);
+        assert_eq!(second.watermark.tips, vec![commit.sha.clone()]);
+
+        let third = export_batch(repo, "demo", "HEAD", &second.watermark).unwrap();
+        assert!(third.commits.is_empty());
+        assert!(third.sessions.is_empty());
+    }
+}
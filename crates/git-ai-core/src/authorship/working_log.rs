@@ -1,8 +1,11 @@
 use crate::authorship::attribution_tracker::{Attribution, LineAttribution};
+use crate::authorship::authorship_log::Participant;
 use crate::authorship::transcript::AiTranscript;
+use crate::error::GitAiError;
+use crate::utils::now_secs;
 use serde::{Deserialize, Serialize};
 use std::fmt;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::str::FromStr;
 
 pub const CHECKPOINT_API_VERSION: &str = "checkpoint/1.0.0";
 
@@ -49,6 +52,8 @@ pub enum CheckpointKind {
     Human,
     AiAgent,
     AiTab,
+    /// Lines from neither a person nor an agent, attributed to the checkpoint's `author_class`
+    AuthorClass,
 }
 
 impl fmt::Display for CheckpointKind {
@@ -57,25 +62,38 @@ impl fmt::Display for CheckpointKind {
     }
 }
 
-impl CheckpointKind {
-    #[allow(dead_code)]
-    pub fn from_str(s: &str) -> Self {
+impl FromStr for CheckpointKind {
+    type Err = GitAiError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
-            "human" => CheckpointKind::Human,
-            "ai_agent" => CheckpointKind::AiAgent,
-            "ai_tab" => CheckpointKind::AiTab,
-            _ => panic!("Invalid checkpoint kind: {}", s),
+            "human" => Ok(CheckpointKind::Human),
+            "ai_agent" => Ok(CheckpointKind::AiAgent),
+            "ai_tab" => Ok(CheckpointKind::AiTab),
+            "author_class" => Ok(CheckpointKind::AuthorClass),
+            _ => Err(GitAiError::Generic(format!(
+                "Invalid checkpoint kind: {}",
+                s
+            ))),
         }
     }
+}
 
+impl CheckpointKind {
     pub fn to_str(&self) -> String {
         match self {
             CheckpointKind::Human => "human".to_string(),
             CheckpointKind::AiAgent => "ai_agent".to_string(),
             CheckpointKind::AiTab => "ai_tab".to_string(),
+            CheckpointKind::AuthorClass => "author_class".to_string(),
         }
     }
 
+    /// True for the kinds an AI agent or completion makes, which carry a prompt session
+    pub fn is_ai(&self) -> bool {
+        matches!(self, CheckpointKind::AiAgent | CheckpointKind::AiTab)
+    }
+
     /// Default value to prevent crashes on old versions
     pub fn serde_default() -> Self {
         CheckpointKind::Human
@@ -106,10 +124,22 @@ pub struct Checkpoint {
     pub timestamp: u64,
     pub transcript: Option<AiTranscript>,
     pub agent_id: Option<AgentId>,
+    /// The commit message the agent wrote, if this checkpoint records one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit_message: Option<String>,
     #[serde(default)]
     pub line_stats: CheckpointLineStats,
     #[serde(default)]
     pub api_version: String,
+    /// The humans pairing on the agent's session when it made the checkpoint
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub participants: Vec<Participant>,
+    /// The human who prompted the agent for this checkpoint, `Name <email>`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub human_author: Option<String>,
+    /// The class an `AuthorClass` checkpoint's lines belong to, e.g. `codegen`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author_class: Option<String>,
 }
 
 impl Checkpoint {
@@ -119,10 +149,7 @@ impl Checkpoint {
         author: String,
         entries: Vec<WorkingLogEntry>,
     ) -> Self {
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs();
+        let timestamp = now_secs();
 
         Self {
             kind,
@@ -132,8 +159,12 @@ impl Checkpoint {
             timestamp,
             transcript: None,
             agent_id: None,
+            commit_message: None,
             line_stats: CheckpointLineStats::default(),
             api_version: CHECKPOINT_API_VERSION.to_string(),
+            participants: Vec::new(),
+            human_author: None,
+            author_class: None,
         }
     }
 }
@@ -142,6 +173,7 @@ impl Checkpoint {
 mod tests {
     use super::*;
     use crate::authorship::transcript::Message;
+    use std::time::{SystemTime, UNIX_EPOCH};
 
     #[test]
     fn test_checkpoint_serialization() {
@@ -295,4 +327,17 @@ mod tests {
         assert_eq!(deserialized_agent.tool, "cursor");
         assert_eq!(deserialized_agent.id, "session-abc123");
     }
+
+    #[test]
+    fn test_checkpoint_kind_from_str() {
+        for kind in [
+            CheckpointKind::Human,
+            CheckpointKind::AiAgent,
+            CheckpointKind::AiTab,
+            CheckpointKind::AuthorClass,
+        ] {
+            assert_eq!(kind.to_str().parse::<CheckpointKind>().unwrap(), kind);
+        }
+        assert!("reviewer".parse::<CheckpointKind>().is_err());
+    }
 }
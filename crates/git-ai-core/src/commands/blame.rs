@@ -1,16 +1,20 @@
-use crate::authorship::authorship_log::PromptRecord;
+use crate::authorship::authorship_log::{PromptRecord, author_name};
 use crate::authorship::authorship_log_serialization::AuthorshipLog;
+use crate::authorship::file_text;
+use crate::authorship::generated_files::GeneratedFileFilter;
+use crate::authorship::review::{ReviewMark, has_review_marks, review_marks, reviewer};
 use crate::authorship::working_log::CheckpointKind;
 use crate::error::GitAiError;
 use crate::git::refs::get_reference_as_authorship_log_v3;
 use crate::git::repository::Repository;
-use crate::git::repository::exec_git;
+use crate::git::repository::{GitStream, exec_git, spawn_git_stream};
 #[cfg(windows)]
 use crate::utils::normalize_to_posix;
 use chrono::{DateTime, FixedOffset, TimeZone, Utc};
 use std::collections::HashMap;
 use std::fs;
-use std::io::{self, IsTerminal, Write};
+use std::io::{self, BufRead, BufReader, IsTerminal, Lines, Write};
+use std::process::ChildStdout;
 
 #[derive(Debug, Clone)]
 pub struct BlameHunk {
@@ -107,6 +111,21 @@ pub struct GitAiBlameOptions {
 
     // No output
     pub no_output: bool,
+
+    // Only attribute lines to AI when the attestation confidence is at least this percentage
+    pub min_confidence: Option<u8>,
+
+    // Also blame the paths listed on stdin, one per line
+    pub paths_from_stdin: bool,
+
+    // With several files, print their lines without per-file headers, each naming its file
+    pub interleave: bool,
+
+    // Name the human who prompted the agent next to each AI line
+    pub show_prompter: bool,
+
+    // With several files, also blame generated and vendored ones
+    pub include_generated: bool,
 }
 
 impl Default for GitAiBlameOptions {
@@ -143,16 +162,118 @@ impl Default for GitAiBlameOptions {
             use_prompt_hashes_as_names: false,
             return_human_authors_as_human: false,
             no_output: false,
+            min_confidence: None,
+            paths_from_stdin: false,
+            interleave: false,
+            show_prompter: false,
+            include_generated: false,
         }
     }
 }
 
+/// Line authors, prompt records and the confidence of uncertain AI lines (line -> percent)
+pub type BlameWithConfidence = (
+    HashMap<u32, String>,
+    HashMap<String, PromptRecord>,
+    HashMap<u32, u8>,
+);
+
 impl Repository {
     pub fn blame(
         &self,
         file_path: &str,
         options: &GitAiBlameOptions,
     ) -> Result<(HashMap<u32, String>, HashMap<String, PromptRecord>), GitAiError> {
+        let (line_authors, prompt_records, _) = self.blame_with_confidence(file_path, options)?;
+        Ok((line_authors, prompt_records))
+    }
+
+    /// Like `blame`, but also returns the confidence of AI-attributed lines whose
+    /// attestation isn't fully certain
+    pub fn blame_with_confidence(
+        &self,
+        file_path: &str,
+        options: &GitAiBlameOptions,
+    ) -> Result<BlameWithConfidence, GitAiError> {
+        let mut output = String::new();
+        let blame = self.blame_file(file_path, options, &mut BlameCache::new(self), &mut output)?;
+        page_output(&output);
+        Ok(blame)
+    }
+
+    /// Blames each of `file_paths` in one go, looking up each commit's notes and review marks
+    /// once for all of them. With several files the default format puts each file's lines under
+    /// a `==> <file> <==` header, or with `options.interleave` prints them without headers, each
+    /// line naming its file. A file that can't be blamed is reported and skipped.
+    pub fn blame_files(
+        &self,
+        file_paths: &[String],
+        options: &GitAiBlameOptions,
+    ) -> Result<(), GitAiError> {
+        let mut options = options.clone();
+        if options.interleave {
+            options.show_name = true;
+        }
+        // Generated and vendored files would drown out the rest of a multi-file blame, so they
+        // are only blamed when named on their own
+        let mut file_paths = file_paths.to_vec();
+        if file_paths.len() > 1 && !options.include_generated {
+            let filter =
+                GeneratedFileFilter::for_paths(self, file_paths.iter()).unwrap_or_default();
+            let before = file_paths.len();
+            file_paths.retain(|path| !filter.is_generated(path));
+            let skipped = before - file_paths.len();
+            if skipped > 0 {
+                eprintln!(
+                    "Skipped {} generated file{} (use --include-generated to blame them)",
+                    skipped,
+                    if skipped == 1 { "" } else { "s" }
+                );
+            }
+        }
+        let headers = file_paths.len() > 1
+            && !options.interleave
+            && !(options.porcelain || options.line_porcelain || options.incremental);
+
+        let mut cache = BlameCache::new(self);
+        let mut output = String::new();
+        let mut failed = 0;
+        for file_path in &file_paths {
+            let mut file_output = String::new();
+            if let Err(e) = self.blame_file(file_path, &options, &mut cache, &mut file_output) {
+                eprintln!("error: {}: {}", file_path, e);
+                failed += 1;
+                continue;
+            }
+            if headers {
+                if !output.is_empty() {
+                    output.push('\n');
+                }
+                output.push_str(&format!("==> {} <==\n", file_path));
+            }
+            output.push_str(&file_output);
+        }
+        page_output(&output);
+
+        if failed > 0 {
+            return Err(GitAiError::Generic(format!(
+                "{} of {} files couldn't be blamed",
+                failed,
+                file_paths.len()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Blames one file. The default format is appended to `output` for the caller to page;
+    /// porcelain and incremental output are printed as they're built.
+    fn blame_file(
+        &self,
+        file_path: &str,
+        options: &GitAiBlameOptions,
+        cache: &mut BlameCache,
+        output: &mut String,
+    ) -> Result<BlameWithConfidence, GitAiError> {
         // Use repo root for file system operations
         let repo_root = self.workdir().or_else(|e| {
             Err(GitAiError::Generic(format!(
@@ -227,7 +348,7 @@ impl Repository {
                 Ok(entry) => {
                     if let Ok(blob) = self.find_blob(entry.id()) {
                         let blob_content = blob.content().unwrap_or_default();
-                        let content = String::from_utf8_lossy(&blob_content).to_string();
+                        let content = file_text::decode(&blob_content);
                         let lines_count = content.lines().count() as u32;
                         (content, lines_count)
                     } else {
@@ -255,7 +376,7 @@ impl Repository {
                 )));
             }
 
-            let content = fs::read_to_string(&abs_file_path)?;
+            let content = file_text::decode(&fs::read(&abs_file_path)?);
             let lines_count = content.lines().count() as u32;
             (content, lines_count)
         };
@@ -287,11 +408,11 @@ impl Repository {
         }
 
         // Step 2: Overlay AI authorship information
-        let (line_authors, prompt_records) =
-            overlay_ai_authorship(self, &all_blame_hunks, &relative_file_path, options)?;
+        let ((line_authors, prompt_records, line_confidences), mixed_authors) =
+            overlay_ai_authorship(self, &all_blame_hunks, &relative_file_path, options, cache)?;
 
         if options.no_output {
-            return Ok((line_authors, prompt_records));
+            return Ok((line_authors, prompt_records, line_confidences));
         }
 
         // Output based on format
@@ -314,17 +435,18 @@ impl Repository {
                 options,
             )?;
         } else {
-            output_default_format(
+            output.push_str(&output_default_format(
                 self,
                 &line_authors,
+                &mixed_authors,
                 &relative_file_path,
                 &lines,
                 &line_ranges,
                 options,
-            )?;
+            )?);
         }
 
-        Ok((line_authors, prompt_records))
+        Ok((line_authors, prompt_records, line_confidences))
     }
 
     pub fn blame_hunks(
@@ -334,61 +456,19 @@ impl Repository {
         end_line: u32,
         options: &GitAiBlameOptions,
     ) -> Result<Vec<BlameHunk>, GitAiError> {
-        // Build git blame --line-porcelain command
-        let mut args = self.global_args_for_exec();
-        args.push("blame".to_string());
-        args.push("--line-porcelain".to_string());
-
-        // Match previous behavior: ignore whitespace
-        args.push("-w".to_string());
-
-        // Respect ignore options in use
-        for rev in &options.ignore_revs {
-            args.push("--ignore-rev".to_string());
-            args.push(rev.clone());
-        }
-        if let Some(file) = &options.ignore_revs_file {
-            args.push("--ignore-revs-file".to_string());
-            args.push(file.clone());
-        }
-
-        // Limit to specified range
-        args.push("-L".to_string());
-        args.push(format!("{},{}", start_line, end_line));
-
-        // Support newest_commit option (equivalent to libgit2's newest_commit)
-        // This limits blame to only consider commits up to and including the specified commit
-        if let Some(ref commit) = options.newest_commit {
-            args.push(commit.clone());
-        }
-
-        // Separator then file path
-        args.push("--".to_string());
-        args.push(file_path.to_string());
-
+        let _span = tracing::info_span!("blame", file = file_path).entered();
+        let args = self.blame_args("--line-porcelain", file_path, start_line, end_line, options);
         let output = exec_git(&args)?;
-        let stdout = String::from_utf8(output.stdout)?;
-
-        // Parser state for current hunk
-        #[derive(Default)]
-        struct CurMeta {
-            author: String,
-            author_mail: String,
-            author_time: i64,
-            author_tz: String,
-            committer: String,
-            committer_mail: String,
-            committer_time: i64,
-            committer_tz: String,
-            boundary: bool,
-        }
+        // Content lines are echoed back, so they decode as the file did
+        let stdout = file_text::decode(&output.stdout);
+        let abbrev_len = abbrev_len(options);
 
         let mut hunks: Vec<BlameHunk> = Vec::new();
         let mut cur_commit: Option<String> = None;
         let mut cur_final_start: u32 = 0;
         let mut cur_orig_start: u32 = 0;
         let mut cur_group_size: u32 = 0;
-        let mut cur_meta = CurMeta::default();
+        let mut cur_meta = BlameCommitMeta::default();
 
         for line in stdout.lines() {
             if line.is_empty() {
@@ -401,53 +481,7 @@ impl Repository {
             }
 
             // Metadata lines
-            if let Some(rest) = line.strip_prefix("author ") {
-                cur_meta.author = rest.to_string();
-                continue;
-            }
-            if let Some(rest) = line.strip_prefix("author-mail ") {
-                // Usually in form: <mail>
-                cur_meta.author_mail = rest
-                    .trim()
-                    .trim_start_matches('<')
-                    .trim_end_matches('>')
-                    .to_string();
-                continue;
-            }
-            if let Some(rest) = line.strip_prefix("author-time ") {
-                if let Ok(t) = rest.trim().parse::<i64>() {
-                    cur_meta.author_time = t;
-                }
-                continue;
-            }
-            if let Some(rest) = line.strip_prefix("author-tz ") {
-                cur_meta.author_tz = rest.trim().to_string();
-                continue;
-            }
-            if let Some(rest) = line.strip_prefix("committer ") {
-                cur_meta.committer = rest.to_string();
-                continue;
-            }
-            if let Some(rest) = line.strip_prefix("committer-mail ") {
-                cur_meta.committer_mail = rest
-                    .trim()
-                    .trim_start_matches('<')
-                    .trim_end_matches('>')
-                    .to_string();
-                continue;
-            }
-            if let Some(rest) = line.strip_prefix("committer-time ") {
-                if let Ok(t) = rest.trim().parse::<i64>() {
-                    cur_meta.committer_time = t;
-                }
-                continue;
-            }
-            if let Some(rest) = line.strip_prefix("committer-tz ") {
-                cur_meta.committer_tz = rest.trim().to_string();
-                continue;
-            }
-            if line == "boundary" {
-                cur_meta.boundary = true;
+            if cur_meta.apply(line) {
                 continue;
             }
 
@@ -469,46 +503,13 @@ impl Repository {
             // If we encounter a new hunk header (4 fields), flush previous hunk first
             if p4.is_some() {
                 if let Some(prev_sha) = cur_commit.take() {
-                    // Push the previous hunk
-                    let start = cur_final_start;
-                    let end = if cur_group_size > 0 {
-                        start + cur_group_size - 1
-                    } else {
-                        start
-                    };
-                    let orig_start = cur_orig_start;
-                    let orig_end = if cur_group_size > 0 {
-                        orig_start + cur_group_size - 1
-                    } else {
-                        orig_start
-                    };
-
-                    let abbrev_len = if options.long_rev {
-                        40
-                    } else {
-                        options.abbrev.unwrap_or(7) as usize
-                    };
-                    let abbrev = if abbrev_len < prev_sha.len() {
-                        prev_sha[..abbrev_len].to_string()
-                    } else {
-                        prev_sha.clone()
-                    };
-
-                    hunks.push(BlameHunk {
-                        range: (start, end),
-                        orig_range: (orig_start, orig_end),
-                        commit_sha: prev_sha,
-                        abbrev_sha: abbrev,
-                        original_author: cur_meta.author.clone(),
-                        author_email: cur_meta.author_mail.clone(),
-                        author_time: cur_meta.author_time,
-                        author_tz: cur_meta.author_tz.clone(),
-                        committer: cur_meta.committer.clone(),
-                        committer_email: cur_meta.committer_mail.clone(),
-                        committer_time: cur_meta.committer_time,
-                        committer_tz: cur_meta.committer_tz.clone(),
-                        is_boundary: cur_meta.boundary,
-                    });
+                    hunks.push(cur_meta.hunk(
+                        prev_sha,
+                        cur_final_start,
+                        cur_orig_start,
+                        cur_group_size,
+                        abbrev_len,
+                    ));
                 }
 
                 // Start new hunk
@@ -521,7 +522,7 @@ impl Repository {
                 cur_final_start = final_start;
                 cur_group_size = group;
                 // Reset metadata for the new hunk
-                cur_meta = CurMeta::default();
+                cur_meta = BlameCommitMeta::default();
             } else {
                 // 3-field header: continuation line within current hunk
                 // Nothing to do for grouping since we use recorded group_size
@@ -538,48 +539,264 @@ impl Repository {
 
         // Flush the final hunk if present
         if let Some(prev_sha) = cur_commit.take() {
-            let start = cur_final_start;
-            let end = if cur_group_size > 0 {
-                start + cur_group_size - 1
-            } else {
-                start
-            };
-            let orig_start = cur_orig_start;
-            let orig_end = if cur_group_size > 0 {
-                orig_start + cur_group_size - 1
-            } else {
-                orig_start
-            };
+            hunks.push(cur_meta.hunk(
+                prev_sha,
+                cur_final_start,
+                cur_orig_start,
+                cur_group_size,
+                abbrev_len,
+            ));
+        }
 
-            let abbrev_len = if options.long_rev {
-                40
-            } else {
-                options.abbrev.unwrap_or(7) as usize
+        Ok(hunks)
+    }
+
+    /// Like `blame_hunks`, but yields each hunk as soon as git resolves it instead of
+    /// waiting for the whole range. Hunks arrive in resolution order, not line order.
+    pub fn blame_hunks_stream(
+        &self,
+        file_path: &str,
+        start_line: u32,
+        end_line: u32,
+        options: &GitAiBlameOptions,
+    ) -> Result<BlameHunkStream, GitAiError> {
+        let args = self.blame_args("--incremental", file_path, start_line, end_line, options);
+        let mut git = spawn_git_stream(&args)?;
+        let stdout = git
+            .take_stdout()
+            .ok_or_else(|| GitAiError::Generic("Failed to capture git blame output".to_string()))?;
+
+        Ok(BlameHunkStream {
+            git,
+            lines: BufReader::new(stdout).lines(),
+            commits: HashMap::new(),
+            abbrev_len: abbrev_len(options),
+            done: false,
+        })
+    }
+
+    fn blame_args(
+        &self,
+        format: &str,
+        file_path: &str,
+        start_line: u32,
+        end_line: u32,
+        options: &GitAiBlameOptions,
+    ) -> Vec<String> {
+        let mut args = self.global_args_for_exec();
+        args.push("blame".to_string());
+        args.push(format.to_string());
+
+        // Match previous behavior: ignore whitespace
+        args.push("-w".to_string());
+
+        // Respect ignore options in use
+        for rev in &options.ignore_revs {
+            args.push("--ignore-rev".to_string());
+            args.push(rev.clone());
+        }
+        if let Some(file) = &options.ignore_revs_file {
+            args.push("--ignore-revs-file".to_string());
+            args.push(file.clone());
+        }
+
+        // Limit to specified range
+        args.push("-L".to_string());
+        args.push(format!("{},{}", start_line, end_line));
+
+        // Support newest_commit option (equivalent to libgit2's newest_commit)
+        // This limits blame to only consider commits up to and including the specified commit
+        if let Some(ref commit) = options.newest_commit {
+            args.push(commit.clone());
+        }
+
+        // Separator then file path
+        args.push("--".to_string());
+        args.push(file_path.to_string());
+        args
+    }
+}
+
+fn abbrev_len(options: &GitAiBlameOptions) -> usize {
+    if options.long_rev {
+        40
+    } else {
+        options.abbrev.unwrap_or(7) as usize
+    }
+}
+
+/// Per-commit metadata lines of git's porcelain and incremental blame formats
+#[derive(Debug, Clone, Default)]
+struct BlameCommitMeta {
+    author: String,
+    author_mail: String,
+    author_time: i64,
+    author_tz: String,
+    committer: String,
+    committer_mail: String,
+    committer_time: i64,
+    committer_tz: String,
+    boundary: bool,
+}
+
+impl BlameCommitMeta {
+    /// Record `line` if it is a metadata line; returns whether it was one
+    fn apply(&mut self, line: &str) -> bool {
+        if let Some(rest) = line.strip_prefix("author ") {
+            self.author = rest.to_string();
+        } else if let Some(rest) = line.strip_prefix("author-mail ") {
+            // Usually in form: <mail>
+            self.author_mail = rest
+                .trim()
+                .trim_start_matches('<')
+                .trim_end_matches('>')
+                .to_string();
+        } else if let Some(rest) = line.strip_prefix("author-time ") {
+            if let Ok(t) = rest.trim().parse::<i64>() {
+                self.author_time = t;
+            }
+        } else if let Some(rest) = line.strip_prefix("author-tz ") {
+            self.author_tz = rest.trim().to_string();
+        } else if let Some(rest) = line.strip_prefix("committer ") {
+            self.committer = rest.to_string();
+        } else if let Some(rest) = line.strip_prefix("committer-mail ") {
+            self.committer_mail = rest
+                .trim()
+                .trim_start_matches('<')
+                .trim_end_matches('>')
+                .to_string();
+        } else if let Some(rest) = line.strip_prefix("committer-time ") {
+            if let Ok(t) = rest.trim().parse::<i64>() {
+                self.committer_time = t;
+            }
+        } else if let Some(rest) = line.strip_prefix("committer-tz ") {
+            self.committer_tz = rest.trim().to_string();
+        } else if line == "boundary" {
+            self.boundary = true;
+        } else {
+            return false;
+        }
+        true
+    }
+
+    fn hunk(
+        &self,
+        sha: String,
+        final_start: u32,
+        orig_start: u32,
+        group_size: u32,
+        abbrev_len: usize,
+    ) -> BlameHunk {
+        let span = group_size.saturating_sub(1);
+        let abbrev = if abbrev_len < sha.len() {
+            sha[..abbrev_len].to_string()
+        } else {
+            sha.clone()
+        };
+        BlameHunk {
+            range: (final_start, final_start + span),
+            orig_range: (orig_start, orig_start + span),
+            commit_sha: sha,
+            abbrev_sha: abbrev,
+            original_author: self.author.clone(),
+            author_email: self.author_mail.clone(),
+            author_time: self.author_time,
+            author_tz: self.author_tz.clone(),
+            committer: self.committer.clone(),
+            committer_email: self.committer_mail.clone(),
+            committer_time: self.committer_time,
+            committer_tz: self.committer_tz.clone(),
+            is_boundary: self.boundary,
+        }
+    }
+}
+
+/// Hunks of a running `git blame --incremental`, see `Repository::blame_hunks_stream`.
+/// Dropping the stream early stops git.
+pub struct BlameHunkStream {
+    /// Killed when the stream is dropped before git is done
+    git: GitStream,
+    lines: Lines<BufReader<ChildStdout>>,
+    /// git prints a commit's metadata only with its first hunk
+    commits: HashMap<String, BlameCommitMeta>,
+    abbrev_len: usize,
+    done: bool,
+}
+
+impl Iterator for BlameHunkStream {
+    type Item = Result<BlameHunk, GitAiError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        // (sha, orig_start, final_start, group_size) of the entry being read
+        let mut header: Option<(String, u32, u32, u32)> = None;
+        loop {
+            let line = match self.lines.next() {
+                Some(Ok(line)) => line,
+                Some(Err(e)) => {
+                    self.done = true;
+                    return Some(Err(e.into()));
+                }
+                None => {
+                    self.done = true;
+                    return self.git.finish().err().map(Err);
+                }
             };
-            let abbrev = if abbrev_len < prev_sha.len() {
-                prev_sha[..abbrev_len].to_string()
-            } else {
-                prev_sha.clone()
+
+            let Some((sha, orig_start, final_start, group_size)) = &header else {
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                if let [sha, orig, fin, size] = fields[..]
+                    && sha.chars().all(|c| c.is_ascii_hexdigit())
+                {
+                    header = Some((
+                        sha.to_string(),
+                        orig.parse().unwrap_or(0),
+                        fin.parse().unwrap_or(0),
+                        size.parse().unwrap_or(1),
+                    ));
+                }
+                continue;
             };
 
-            hunks.push(BlameHunk {
-                range: (start, end),
-                orig_range: (orig_start, orig_end),
-                commit_sha: prev_sha,
-                abbrev_sha: abbrev,
-                original_author: cur_meta.author.clone(),
-                author_email: cur_meta.author_mail.clone(),
-                author_time: cur_meta.author_time,
-                author_tz: cur_meta.author_tz.clone(),
-                committer: cur_meta.committer.clone(),
-                committer_email: cur_meta.committer_mail.clone(),
-                committer_time: cur_meta.committer_time,
-                committer_tz: cur_meta.committer_tz.clone(),
-                is_boundary: cur_meta.boundary,
-            });
+            // Every entry ends with the path it was found under
+            if line.starts_with("filename ") {
+                let meta = self.commits.get(sha).cloned().unwrap_or_default();
+                return Some(Ok(meta.hunk(
+                    sha.clone(),
+                    *final_start,
+                    *orig_start,
+                    *group_size,
+                    self.abbrev_len,
+                )));
+            }
+            self.commits.entry(sha.clone()).or_default().apply(&line);
         }
+    }
+}
 
-        Ok(hunks)
+/// Display names for lines co-written by a human and an AI (line -> "author+author"), or
+/// reviewed AI lines (line -> "author (reviewed by <user>)")
+type MixedLineAuthors = HashMap<u32, String>;
+
+/// Notes and review marks looked up while blaming, shared by the files of one invocation
+struct BlameCache {
+    /// Authorship log of each commit, to avoid repeated lookups (None if it has none)
+    authorship_logs: HashMap<String, Option<AuthorshipLog>>,
+    /// Foreign prompts, to avoid repeated grepping
+    foreign_prompts: HashMap<String, Option<PromptRecord>>,
+    /// Review marks by commit, only looked up once something has been marked reviewed
+    review_marks: Option<HashMap<String, Vec<ReviewMark>>>,
+}
+
+impl BlameCache {
+    fn new(repo: &Repository) -> Self {
+        BlameCache {
+            authorship_logs: HashMap::new(),
+            foreign_prompts: HashMap::new(),
+            review_marks: has_review_marks(repo).then(HashMap::new),
+        }
     }
 }
 
@@ -588,14 +805,16 @@ fn overlay_ai_authorship(
     blame_hunks: &[BlameHunk],
     file_path: &str,
     options: &GitAiBlameOptions,
-) -> Result<(HashMap<u32, String>, HashMap<String, PromptRecord>), GitAiError> {
+    cache: &mut BlameCache,
+) -> Result<(BlameWithConfidence, MixedLineAuthors), GitAiError> {
     let mut line_authors: HashMap<u32, String> = HashMap::new();
     let mut prompt_records: HashMap<String, PromptRecord> = HashMap::new();
+    let mut line_confidences: HashMap<u32, u8> = HashMap::new();
+    let mut mixed_authors: MixedLineAuthors = HashMap::new();
 
-    // Group hunks by commit SHA to avoid repeated lookups
-    let mut commit_authorship_cache: HashMap<String, Option<AuthorshipLog>> = HashMap::new();
-    // Cache for foreign prompts to avoid repeated grepping
-    let mut foreign_prompts_cache: HashMap<String, Option<PromptRecord>> = HashMap::new();
+    let commit_authorship_cache = &mut cache.authorship_logs;
+    let foreign_prompts_cache = &mut cache.foreign_prompts;
+    let review_cache = &mut cache.review_marks;
 
     for hunk in blame_hunks {
         // Check if we've already looked up this commit's authorship
@@ -620,22 +839,58 @@ fn overlay_ai_authorship(
                 let current_line_num = hunk.range.0 + i;
                 let orig_line_num = hunk.orig_range.0 + i;
 
-                if let Some((author, prompt_hash, prompt)) = authorship_log.get_line_attribution(
-                    repo,
-                    file_path,
-                    orig_line_num,
-                    &mut foreign_prompts_cache,
-                ) {
+                let confidence = authorship_log.get_line_confidence(file_path, orig_line_num);
+                let below_min_confidence = match (options.min_confidence, confidence) {
+                    (Some(min), Some(confidence)) => confidence < min,
+                    _ => false,
+                };
+
+                let attribution = if below_min_confidence {
+                    None
+                } else {
+                    authorship_log.get_line_attribution(
+                        repo,
+                        file_path,
+                        orig_line_num,
+                        foreign_prompts_cache,
+                    )
+                };
+
+                // Another author's characters on this line, if it was co-written
+                let mut co_author: Option<String> = None;
+                let mut is_ai_line = false;
+                let mut attribution_prompter: Option<String> = None;
+
+                if let Some((author, prompt_hash, prompt)) = attribution {
                     // If this line is AI-assisted, display the tool name; otherwise the human username
                     if let Some(prompt_record) = prompt {
+                        is_ai_line = true;
                         let prompt_hash = prompt_hash.unwrap();
+                        if authorship_log.get_line_column_owner(file_path, orig_line_num)
+                            == Some(prompt_hash.as_str())
+                        {
+                            co_author = Some(hunk.original_author.clone());
+                        }
                         if options.use_prompt_hashes_as_names {
                             line_authors.insert(current_line_num, prompt_hash.clone());
                         } else {
                             line_authors
                                 .insert(current_line_num, prompt_record.agent_id.tool.clone());
                         }
+                        attribution_prompter = prompt_record.prompter().map(|prompter| {
+                            if options.show_email {
+                                prompter.to_string()
+                            } else {
+                                author_name(prompter).to_string()
+                            }
+                        });
                         prompt_records.insert(prompt_hash, prompt_record.clone());
+                        if let Some(confidence) = confidence {
+                            line_confidences.insert(current_line_num, confidence);
+                        }
+                    } else if let Some(class) = prompt_hash {
+                        // An author class's lines are neither the human's nor an agent's
+                        line_authors.insert(current_line_num, class);
                     } else {
                         if options.return_human_authors_as_human {
                             line_authors.insert(
@@ -655,6 +910,49 @@ fn overlay_ai_authorship(
                         line_authors.insert(current_line_num, hunk.original_author.clone());
                     }
                 }
+
+                // A human-owned line may still contain characters written by an AI
+                if !is_ai_line && !below_min_confidence {
+                    co_author = authorship_log
+                        .get_line_column_owner(file_path, orig_line_num)
+                        .and_then(|hash| authorship_log.metadata.prompts.get(hash))
+                        .map(|record| record.agent_id.tool.clone());
+                }
+
+                if let (Some(co_author), Some(author)) =
+                    (co_author, line_authors.get(&current_line_num))
+                {
+                    mixed_authors.insert(current_line_num, format!("{}+{}", author, co_author));
+                }
+
+                // The human behind the agent, when asked for
+                if let Some(prompter) = attribution_prompter.filter(|_| options.show_prompter) {
+                    let author = mixed_authors
+                        .get(&current_line_num)
+                        .or_else(|| line_authors.get(&current_line_num))
+                        .cloned()
+                        .unwrap_or_default();
+                    mixed_authors.insert(
+                        current_line_num,
+                        format!("{} (prompted by {})", author, prompter),
+                    );
+                }
+
+                // AI lines someone has signed off on with `git-ai review mark`
+                if let Some(reviews) = review_cache.as_mut().filter(|_| is_ai_line) {
+                    let marks = reviews
+                        .entry(hunk.commit_sha.clone())
+                        .or_insert_with(|| review_marks(repo, &hunk.commit_sha));
+                    if let Some(by) = reviewer(marks, file_path, orig_line_num) {
+                        let author = mixed_authors
+                            .get(&current_line_num)
+                            .or_else(|| line_authors.get(&current_line_num))
+                            .cloned()
+                            .unwrap_or_default();
+                        mixed_authors
+                            .insert(current_line_num, format!("{} (reviewed by {})", author, by));
+                    }
+                }
             }
         } else {
             // No authorship log, use original author for all lines in hunk
@@ -668,7 +966,10 @@ fn overlay_ai_authorship(
         }
     }
 
-    Ok((line_authors, prompt_records))
+    Ok((
+        (line_authors, prompt_records, line_confidences),
+        mixed_authors,
+    ))
 }
 
 fn output_porcelain_format(
@@ -870,11 +1171,12 @@ fn output_incremental_format(
 fn output_default_format(
     repo: &Repository,
     line_authors: &HashMap<u32, String>,
+    mixed_authors: &MixedLineAuthors,
     file_path: &str,
     lines: &[&str],
     line_ranges: &[(u32, u32)],
     options: &GitAiBlameOptions,
-) -> Result<(), GitAiError> {
+) -> Result<String, GitAiError> {
     let mut output = String::new();
 
     // Build a map from line number to BlameHunk for fast lookup
@@ -897,8 +1199,10 @@ fn output_default_format(
     for (start_line, end_line) in line_ranges {
         let h = repo.blame_hunks(file_path, *start_line, *end_line, options)?;
         for hunk in h {
-            let author = line_authors
-                .get(&hunk.range.0)
+            let author = (hunk.range.0..=hunk.range.1)
+                .filter_map(|line_num| mixed_authors.get(&line_num))
+                .max_by_key(|author| author.len())
+                .or_else(|| line_authors.get(&hunk.range.0))
                 .unwrap_or(&hunk.original_author);
             let author_display = if options.suppress_author {
                 "".to_string()
@@ -948,7 +1252,10 @@ fn output_default_format(
                 };
 
                 // Get the author for this line (AI authorship or original)
-                let author = line_authors.get(&line_num).unwrap_or(&hunk.original_author);
+                let author = mixed_authors
+                    .get(&line_num)
+                    .or_else(|| line_authors.get(&line_num))
+                    .unwrap_or(&hunk.original_author);
 
                 // Format date according to options
                 let date_str = format_blame_date(hunk.author_time, &hunk.author_tz, options);
@@ -1044,6 +1351,15 @@ fn output_default_format(
         output.push_str(stats);
     }
 
+    Ok(output)
+}
+
+/// Prints blame output, through the pager when stdout is a terminal
+fn page_output(output: &str) {
+    if output.is_empty() {
+        return;
+    }
+
     // Output handling - respect pager environment variables
     let pager = std::env::var("GIT_PAGER")
         .or_else(|_| std::env::var("PAGER"))
@@ -1080,7 +1396,6 @@ fn output_default_format(
         // Not a terminal, output directly
         print!("{}", output);
     }
-    Ok(())
 }
 
 fn format_blame_date(author_time: i64, author_tz: &str, options: &GitAiBlameOptions) -> String {
@@ -1114,9 +1429,9 @@ fn format_blame_date(author_time: i64, author_tz: &str, options: &GitAiBlameOpti
     }
 }
 
-pub fn parse_blame_args(args: &[String]) -> Result<(String, GitAiBlameOptions), GitAiError> {
+pub fn parse_blame_args(args: &[String]) -> Result<(Vec<String>, GitAiBlameOptions), GitAiError> {
     let mut options = GitAiBlameOptions::default();
-    let mut file_path = None;
+    let mut file_paths = Vec::new();
     let mut i = 0;
 
     while i < args.len() {
@@ -1320,15 +1635,45 @@ pub fn parse_blame_args(args: &[String]) -> Result<(String, GitAiBlameOptions),
                 i += 2;
             }
 
-            // File path (non-option argument)
-            arg if !arg.starts_with('-') => {
-                if file_path.is_none() {
-                    file_path = Some(arg.to_string());
-                } else {
+            // AI attribution options
+            "--min-confidence" => {
+                if i + 1 >= args.len() {
                     return Err(GitAiError::Generic(
-                        "Multiple file paths specified".to_string(),
+                        "Missing argument for --min-confidence".to_string(),
                     ));
                 }
+                match args[i + 1].parse::<u8>() {
+                    Ok(n) if n <= 100 => options.min_confidence = Some(n),
+                    _ => {
+                        return Err(GitAiError::Generic(
+                            "Invalid percentage for --min-confidence".to_string(),
+                        ));
+                    }
+                }
+                i += 2;
+            }
+
+            // Multi-file options
+            "--stdin" => {
+                options.paths_from_stdin = true;
+                i += 1;
+            }
+            "--interleave" => {
+                options.interleave = true;
+                i += 1;
+            }
+            "--show-prompter" => {
+                options.show_prompter = true;
+                i += 1;
+            }
+            "--include-generated" => {
+                options.include_generated = true;
+                i += 1;
+            }
+
+            // File paths (non-option arguments)
+            arg if !arg.starts_with('-') => {
+                file_paths.push(arg.to_string());
                 i += 1;
             }
 
@@ -1339,10 +1684,11 @@ pub fn parse_blame_args(args: &[String]) -> Result<(String, GitAiBlameOptions),
         }
     }
 
-    let file_path =
-        file_path.ok_or_else(|| GitAiError::Generic("No file path specified".to_string()))?;
+    if file_paths.is_empty() && !options.paths_from_stdin {
+        return Err(GitAiError::Generic("No file path specified".to_string()));
+    }
 
-    Ok((file_path, options))
+    Ok((file_paths, options))
 }
 
 fn parse_line_range(range_str: &str) -> Option<(u32, u32)> {
@@ -1,18 +1,35 @@
-use crate::authorship::attribution_tracker::{Attribution, AttributionTracker, LineAttribution};
+use crate::authorship::attribution_gaps::UnattributedChanges;
+use crate::authorship::attribution_tracker::{
+    Attribution, AttributionConfig, AttributionTracker, LineAttribution,
+};
+use crate::authorship::author_class::AuthorClass;
+use crate::authorship::commit_policy::{forbidden_edit_warning, forbidden_paths};
+use crate::authorship::file_text;
+use crate::authorship::generated_files::filter_generated_paths;
+use crate::authorship::paste_detection::flag_possible_ai;
+use crate::authorship::patch::FilePatch;
+use crate::authorship::rebase_authorship::recover_orphaned_working_log;
+use crate::authorship::working_log::AgentId;
 use crate::authorship::working_log::CheckpointKind;
 use crate::authorship::working_log::{Checkpoint, WorkingLogEntry};
 use crate::commands::blame::GitAiBlameOptions;
-use crate::commands::checkpoint_agent::agent_presets::AgentRunResult;
+use crate::commands::checkpoint_agent::agent_presets::{AgentEditPhase, AgentRunResult};
+use crate::commands::hooks::commit_hooks::get_commit_default_author;
+use crate::commands::hooks::shadow_hooks;
+use crate::config::Config;
 use crate::error::GitAiError;
-use crate::git::repo_storage::{PersistedWorkingLog, RepoStorage};
+use crate::git::lfs::{filter_lfs_paths, smudge_blob_content};
+use crate::git::path_scope::PathScope;
+use crate::git::repo_storage::{
+    PendingAgentEdit, PersistedWorkingLog, RepoStorage, WORKING_LOG_LOCK_TIMEOUT,
+};
 use crate::git::repository::Repository;
 use crate::git::status::{EntryKind, StatusCode};
-use crate::utils::{debug_log, normalize_to_posix};
+use crate::utils::{debug_log, normalize_to_posix, now_millis};
 use sha2::{Digest, Sha256};
 use similar::{ChangeTag, TextDiff};
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use std::time::{SystemTime, UNIX_EPOCH};
 
 pub fn run(
     repo: &Repository,
@@ -23,7 +40,9 @@ pub fn run(
     quiet: bool,
     agent_run_result: Option<AgentRunResult>,
     is_pre_commit: bool,
+    include_generated: bool,
 ) -> Result<(usize, usize, usize), GitAiError> {
+    let _span = tracing::info_span!("checkpoint", kind = %kind.to_str()).entered();
     // Robustly handle zero-commit repos
     let base_commit = match repo.head() {
         Ok(head) => match head.target() {
@@ -41,6 +60,27 @@ pub fn run(
         ));
     }
 
+    // Held until the checkpoint is written, so concurrent checkpoints don't interleave
+    let _lock = repo.storage.lock_working_logs(WORKING_LOG_LOCK_TIMEOUT)?;
+
+    if let Err(e) = repo.recover_interrupted_rewrite() {
+        debug_log(&format!("Failed to recover interrupted rewrite: {}", e));
+    }
+
+    // Pick up a working log left keyed to an old commit by a HEAD move our hooks didn't see.
+    // Mid-rebase, HEAD moves are expected and the rebase hooks own the working log.
+    let rebase_in_progress = ["rebase-merge", "rebase-apply"]
+        .iter()
+        .any(|dir| repo.path().join(dir).exists());
+    let recovered_from = if !reset && base_commit != "initial" && !rebase_in_progress {
+        recover_orphaned_working_log(repo, &base_commit).unwrap_or_else(|e| {
+            debug_log(&format!("Failed to recover orphaned working log: {}", e));
+            None
+        })
+    } else {
+        None
+    };
+
     // Initialize the new storage system
     let repo_storage = RepoStorage::for_repo_path(repo.path(), &repo.workdir()?);
     let mut working_log = repo_storage.working_log_for_base_commit(&base_commit);
@@ -54,10 +94,7 @@ pub fn run(
     }
 
     // Get the current timestamp in milliseconds since the Unix epoch
-    let ts = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_millis();
+    let ts = now_millis();
 
     // Extract edited filepaths from agent_run_result if available
     // For human checkpoints, use will_edit_filepaths to narrow git status scope
@@ -124,13 +161,38 @@ pub fn run(
         })
     });
 
-    let files = get_all_tracked_files(
-        repo,
-        &base_commit,
-        &working_log,
-        pathspec_filter,
-        is_pre_commit,
-    )?;
+    // An agent that only wrote the commit message hasn't edited any files
+    let commit_message = agent_run_result
+        .as_ref()
+        .filter(|result| result.checkpoint_kind.is_ai())
+        .and_then(|result| result.commit_message.clone());
+    let message_only = commit_message.is_some() && pathspec_filter.is_none();
+
+    let mut files = if message_only {
+        Vec::new()
+    } else {
+        get_all_tracked_files(
+            repo,
+            &base_commit,
+            &working_log,
+            pathspec_filter,
+            is_pre_commit,
+        )?
+    };
+
+    // Lockfiles, codegen output and vendored code are not attributed unless asked for
+    if !include_generated {
+        files = filter_generated_paths(repo, files);
+    }
+
+    // Files tracked with LFS are pointers in the tree, so they're left out unless smudged
+    files = filter_lfs_paths(repo, files);
+
+    // Nor is anything outside the path scope of a monorepo
+    let scope = PathScope::for_repo(repo);
+    if !scope.is_everything() {
+        files = scope.retain(files);
+    }
 
     let mut checkpoints = if reset {
         // If reset flag is set, start with an empty working log
@@ -196,36 +258,58 @@ pub fn run(
     }
 
     // Save current file states and get content hashes
-    let file_content_hashes = save_current_file_states(&working_log, &files)?;
+    let file_states = save_current_file_states(&working_log, &files)?;
 
     // Order file hashes by key and create a hash of the ordered hashes
-    let mut ordered_hashes: Vec<_> = file_content_hashes.iter().collect();
-    ordered_hashes.sort_by_key(|(file_path, _)| *file_path);
+    let mut ordered_states: Vec<_> = file_states.iter().collect();
+    ordered_states.sort_by_key(|(file_path, _)| *file_path);
 
     let mut combined_hasher = Sha256::new();
-    for (file_path, hash) in ordered_hashes {
+    for (file_path, state) in ordered_states {
         combined_hasher.update(file_path.as_bytes());
-        combined_hasher.update(hash.as_bytes());
+        combined_hasher.update(state.blob_sha.as_bytes());
     }
     let combined_hash = format!("{:x}", combined_hasher.finalize());
 
     // Note: foreign prompts from INITIAL file are read in post_commit.rs
     // when converting working log -> authorship log
 
+    let edit_phase = agent_run_result
+        .as_ref()
+        .and_then(|result| result.edit_phase.map(|phase| (phase, &result.agent_id)));
+
+    // The end of a paired edit is attributed against what its beginning saw, so checkpoints made
+    // while the agent was writing don't take its lines away from it
+    let edit_began = match edit_phase {
+        Some((AgentEditPhase::End, agent_id)) => working_log
+            .take_pending_agent_edit(&agent_session(agent_id))
+            .filter(|began| began.checkpoint_count <= checkpoints.len())
+            .or_else(|| {
+                debug_log(&format!(
+                    "No begin-agent checkpoint for {}; attributing since the last checkpoint",
+                    agent_session(agent_id)
+                ));
+                None
+            }),
+        _ => None,
+    };
+
     // Get checkpoint entries using unified function that handles both initial and subsequent checkpoints
     let entries = smol::block_on(get_checkpoint_entries(
         kind,
         repo,
         &working_log,
         &files,
-        &file_content_hashes,
+        &file_states,
         &checkpoints,
+        edit_began.as_ref(),
         agent_run_result.as_ref(),
+        is_pre_commit,
         ts,
     ))?;
 
     // Skip adding checkpoint if there are no changes
-    if !entries.is_empty() {
+    if !entries.is_empty() || commit_message.is_some() {
         let mut checkpoint = Checkpoint::new(
             kind.clone(),
             combined_hash.clone(),
@@ -234,28 +318,85 @@ pub fn run(
         );
 
         // Compute and set line stats
-        checkpoint.line_stats =
-            compute_line_stats(repo, &working_log, &files, &entries, &checkpoints, kind)?;
+        checkpoint.line_stats = compute_line_stats(
+            repo,
+            &working_log,
+            &files,
+            &file_states,
+            &entries,
+            &checkpoints,
+            kind,
+        )?;
 
         // Set transcript and agent_id if provided and not a human checkpoint
-        if kind != CheckpointKind::Human
+        if kind.is_ai()
             && let Some(agent_run) = &agent_run_result
         {
             checkpoint.transcript = Some(agent_run.transcript.clone().unwrap_or_default());
             checkpoint.agent_id = Some(agent_run.agent_id.clone());
+            checkpoint.commit_message = commit_message.clone();
+            checkpoint.participants = repo.storage.read_pairing();
+            checkpoint.human_author = Some(
+                agent_run
+                    .human_author
+                    .clone()
+                    .unwrap_or_else(|| get_commit_default_author(repo, &[])),
+            );
+        }
+        if kind == CheckpointKind::AuthorClass {
+            checkpoint.author_class = agent_run_result
+                .as_ref()
+                .and_then(|result| result.author_class.clone());
         }
 
         // Append checkpoint to the working log
         working_log.append_checkpoint(&checkpoint)?;
+        if repo.storage.shadowed {
+            shadow_hooks::record_checkpoint(repo, &checkpoint)?;
+        }
         checkpoints.push(checkpoint);
     }
 
-    let agent_tool = if kind != CheckpointKind::Human
-        && let Some(agent_run_result) = &agent_run_result
-    {
-        Some(agent_run_result.agent_id.tool.as_str())
-    } else {
-        None
+    // The orphaned working log is only dropped once its migration is followed by a checkpoint
+    if let Some(orphan_sha) = recovered_from {
+        repo.storage
+            .delete_working_log_for_base_commit(&orphan_sha)?;
+    }
+
+    if let Some((AgentEditPhase::Begin, agent_id)) = edit_phase {
+        working_log.write_pending_agent_edit(
+            &agent_session(agent_id),
+            &PendingAgentEdit {
+                checkpoint_count: checkpoints.len(),
+                files: pathspec_filter.cloned().unwrap_or_default(),
+            },
+        )?;
+    }
+
+    // Warn the moment an agent writes where AI isn't allowed, rather than at commit or in CI
+    if kind.is_ai() && !entries.is_empty() {
+        match forbidden_paths(repo) {
+            Ok(Some(forbidden)) => {
+                let files: Vec<&str> = entries
+                    .iter()
+                    .map(|entry| entry.file.as_str())
+                    .filter(|file| forbidden.contains(file))
+                    .collect();
+                if !files.is_empty() {
+                    eprint!("{}", forbidden_edit_warning(&files));
+                }
+            }
+            Ok(None) => {}
+            Err(e) => debug_log(&format!("Failed to check forbidden paths: {}", e)),
+        }
+    }
+
+    let agent_tool = match &agent_run_result {
+        Some(agent_run_result) if kind.is_ai() => Some(agent_run_result.agent_id.tool.as_str()),
+        Some(agent_run_result) if kind == CheckpointKind::AuthorClass => {
+            agent_run_result.author_class.as_deref()
+        }
+        _ => None,
     };
 
     // Print summary with new format
@@ -396,7 +537,7 @@ fn get_all_tracked_files(
 
     let has_ai_checkpoints = if let Ok(working_log_data) = working_log.read_all_checkpoints() {
         working_log_data.iter().any(|checkpoint| {
-            checkpoint.kind == CheckpointKind::AiAgent || checkpoint.kind == CheckpointKind::AiTab
+            checkpoint.kind.is_ai() || checkpoint.kind == CheckpointKind::AuthorClass
         })
     } else {
         false
@@ -426,24 +567,32 @@ fn get_all_tracked_files(
     Ok(results_for_tracked_files)
 }
 
+/// A file as this checkpoint sees it. The content is read once and shared by every step that
+/// needs it, rather than read again per step.
+struct FileState {
+    blob_sha: String,
+    content: Arc<str>,
+}
+
 fn save_current_file_states(
     working_log: &PersistedWorkingLog,
     files: &[String],
-) -> Result<HashMap<String, String>, GitAiError> {
-    let mut file_content_hashes = HashMap::new();
+) -> Result<HashMap<String, FileState>, GitAiError> {
+    let mut file_states = HashMap::new();
 
     for file_path in files {
         // Read file content using working_log, which respects dirty_files
-        let content = working_log
+        let content: Arc<str> = working_log
             .read_current_file_content(file_path)
-            .unwrap_or_else(|_| String::new());
+            .unwrap_or_else(|_| String::new())
+            .into();
 
         // Persist the file content and get the content hash
-        let content_hash = working_log.persist_file_version(&content)?;
-        file_content_hashes.insert(file_path.clone(), content_hash);
+        let blob_sha = working_log.persist_file_version(&content)?;
+        file_states.insert(file_path.clone(), FileState { blob_sha, content });
     }
 
-    Ok(file_content_hashes)
+    Ok(file_states)
 }
 
 fn get_checkpoint_entry_for_file(
@@ -453,16 +602,15 @@ fn get_checkpoint_entry_for_file(
     working_log: PersistedWorkingLog,
     previous_checkpoints: Arc<Vec<Checkpoint>>,
     file_content_hash: String,
+    current_content: Arc<str>,
     author_id: Arc<String>,
     head_commit_sha: Arc<Option<String>>,
     head_tree_id: Arc<Option<String>>,
+    head_commit_time: Option<u64>,
     initial_attributions: Arc<HashMap<String, Vec<LineAttribution>>>,
+    patch: Option<FilePatch>,
     ts: u128,
 ) -> Result<Option<WorkingLogEntry>, GitAiError> {
-    let current_content = working_log
-        .read_current_file_content(&file_path)
-        .unwrap_or_default();
-
     // Try to get previous state from checkpoints first
     let from_checkpoint = previous_checkpoints.iter().rev().find_map(|checkpoint| {
         checkpoint
@@ -475,9 +623,15 @@ fn get_checkpoint_entry_for_file(
                         .get_file_version(&entry.blob_sha)
                         .unwrap_or_default(),
                     entry.attributions.clone(),
+                    checkpoint.timestamp,
                 )
             })
     });
+    // When the file's previous version was recorded, for the large-paste heuristic
+    let previous_version_secs = from_checkpoint
+        .as_ref()
+        .map(|(_, _, timestamp)| *timestamp)
+        .or(head_commit_time);
 
     // Get INITIAL attributions for this file (needed early for the skip check)
     let initial_attrs_for_file = initial_attributions
@@ -486,7 +640,7 @@ fn get_checkpoint_entry_for_file(
         .unwrap_or_default();
 
     let is_from_checkpoint = from_checkpoint.is_some();
-    let (previous_content, prev_attributions) = if let Some((content, attrs)) = from_checkpoint {
+    let (previous_content, prev_attributions) = if let Some((content, attrs, _)) = from_checkpoint {
         // File exists in a previous checkpoint - use that
         (content, attrs)
     } else {
@@ -499,8 +653,13 @@ fn get_checkpoint_entry_for_file(
                 match tree.get_path(std::path::Path::new(&file_path)) {
                     Ok(entry) => {
                         if let Ok(blob) = repo.find_blob(entry.id()) {
-                            let blob_content = blob.content().unwrap_or_default();
-                            String::from_utf8_lossy(&blob_content).to_string()
+                            let blob_content = smudge_blob_content(
+                                &repo,
+                                &file_path,
+                                &entry.id(),
+                                blob.content().unwrap_or_default(),
+                            );
+                            file_text::decode(&blob_content)
                         } else {
                             String::new()
                         }
@@ -516,7 +675,7 @@ fn get_checkpoint_entry_for_file(
 
         // Skip if no changes, UNLESS we have INITIAL attributions for this file
         // (in which case we need to create an entry to record those attributions)
-        if current_content == previous_content && initial_attrs_for_file.is_empty() {
+        if *current_content == *previous_content && initial_attrs_for_file.is_empty() {
             return Ok(None);
         }
 
@@ -534,14 +693,14 @@ fn get_checkpoint_entry_for_file(
         ai_blame_opts.return_human_authors_as_human = true;
         ai_blame_opts.use_prompt_hashes_as_names = true;
         ai_blame_opts.newest_commit = head_commit_sha.as_ref().clone();
-        let ai_blame = repo.blame(&file_path, &ai_blame_opts);
+        let ai_blame = repo.blame_with_confidence(&file_path, &ai_blame_opts);
 
         // Start with INITIAL attributions (they win)
         let mut prev_line_attributions = initial_attrs_for_file.clone();
 
         // Add blame results for lines NOT covered by INITIAL
         let mut blamed_lines: HashSet<u32> = HashSet::new();
-        if let Ok((blames, _)) = ai_blame {
+        if let Ok((blames, _, confidences)) = ai_blame {
             for (line, author) in blames {
                 blamed_lines.insert(line);
                 // Skip if INITIAL already has this line
@@ -554,12 +713,10 @@ fn get_checkpoint_entry_for_file(
                     continue;
                 }
 
-                prev_line_attributions.push(LineAttribution {
-                    start_line: line,
-                    end_line: line,
-                    author_id: author.clone(),
-                    overrode: None,
-                });
+                prev_line_attributions.push(
+                    LineAttribution::new(line, line, author.clone(), None)
+                        .with_confidence(confidences.get(&line).copied()),
+                );
             }
         }
 
@@ -568,12 +725,12 @@ fn get_checkpoint_entry_for_file(
             let total_lines = current_content.lines().count() as u32;
             for line_num in 1..=total_lines {
                 if !initial_covered_lines.contains(&line_num) && !blamed_lines.contains(&line_num) {
-                    prev_line_attributions.push(LineAttribution {
-                        start_line: line_num,
-                        end_line: line_num,
-                        author_id: author_id.as_ref().clone(),
-                        overrode: None,
-                    });
+                    prev_line_attributions.push(LineAttribution::new(
+                        line_num,
+                        line_num,
+                        author_id.as_ref().clone(),
+                        None,
+                    ));
                 }
             }
         }
@@ -581,9 +738,9 @@ fn get_checkpoint_entry_for_file(
         // For INITIAL attributions, we need to use current_content (not previous_content)
         // because INITIAL line numbers refer to the current state of the file
         let content_for_line_conversion = if !initial_attrs_for_file.is_empty() {
-            &current_content
+            &*current_content
         } else {
-            &previous_content
+            previous_content.as_str()
         };
 
         // Convert any line attributions to character attributions
@@ -598,7 +755,7 @@ fn get_checkpoint_entry_for_file(
         // We need to pass current_content as previous_content so the attributions are preserved.
         // The tracker will see no changes and preserve the INITIAL attributions.
         let adjusted_previous = if !initial_attrs_for_file.is_empty() {
-            current_content.clone()
+            current_content.to_string()
         } else {
             previous_content
         };
@@ -608,19 +765,70 @@ fn get_checkpoint_entry_for_file(
 
     // Skip if no changes (but we already checked this earlier, accounting for INITIAL attributions)
     // For files from previous checkpoints, check if content has changed
-    if is_from_checkpoint && current_content == previous_content {
+    if is_from_checkpoint && *current_content == *previous_content {
         return Ok(None);
     }
 
-    let entry = make_entry_for_file(
-        &file_path,
-        &file_content_hash,
-        author_id.as_ref(),
-        &previous_content,
-        &prev_attributions,
-        &current_content,
-        ts,
-    )?;
+    // A large paste soon after the file's previous version may be AI code written outside any
+    // instrumented tool
+    let paste_heuristic = Config::get().paste_heuristic().filter(|heuristic| {
+        kind == CheckpointKind::Human
+            && previous_version_secs.is_some_and(|secs| {
+                heuristic.within_window(((ts / 1000) as u64).saturating_sub(secs))
+            })
+    });
+
+    let patched_entry = patch.and_then(|patch| {
+        attributions_from_patch(
+            &previous_content,
+            &prev_attributions,
+            &current_content,
+            &patch,
+            author_id.as_ref(),
+            ts,
+        )
+        .map_err(|e| {
+            debug_log(&format!(
+                "Ignoring the agent's patch for {}, diffing instead: {}",
+                file_path, e
+            ))
+        })
+        .ok()
+        .map(|attributions| {
+            entry_with_attributions(
+                file_path.clone(),
+                file_content_hash.clone(),
+                attributions,
+                &current_content,
+            )
+        })
+    });
+    let entry = match patched_entry {
+        Some(entry) => entry,
+        None => make_entry_for_file(
+            &file_path,
+            &file_content_hash,
+            author_id.as_ref(),
+            &previous_content,
+            &prev_attributions,
+            &current_content,
+            ts,
+        )?,
+    };
+    let entry = match paste_heuristic {
+        Some(heuristic) => entry_with_attributions(
+            entry.file,
+            entry.blob_sha,
+            flag_possible_ai(
+                entry.attributions,
+                &current_content,
+                &heuristic.large_insertions(&previous_content, &current_content),
+                ts,
+            ),
+            &current_content,
+        ),
+        None => entry,
+    };
 
     Ok(Some(entry))
 }
@@ -630,9 +838,11 @@ async fn get_checkpoint_entries(
     repo: &Repository,
     working_log: &PersistedWorkingLog,
     files: &[String],
-    file_content_hashes: &HashMap<String, String>,
+    file_states: &HashMap<String, FileState>,
     previous_checkpoints: &[Checkpoint],
+    edit_began: Option<&PendingAgentEdit>,
     agent_run_result: Option<&AgentRunResult>,
+    is_pre_commit: bool,
     ts: u128,
 ) -> Result<Vec<WorkingLogEntry>, GitAiError> {
     // Read INITIAL attributions from working log (empty if file doesn't exist)
@@ -640,7 +850,12 @@ async fn get_checkpoint_entries(
     let initial_attributions = initial_data.files;
 
     // Determine author_id based on checkpoint kind and agent_id
-    let author_id = if kind != CheckpointKind::Human {
+    let author_id = if kind == CheckpointKind::AuthorClass {
+        // Lines of a class belong to the class, not to any session
+        agent_run_result
+            .and_then(|result| result.author_class.clone())
+            .unwrap_or_else(|| AuthorClass::Unknown.to_string())
+    } else if kind != CheckpointKind::Human {
         // For AI checkpoints, use session hash
         agent_run_result
             .map(|result| {
@@ -650,6 +865,10 @@ async fn get_checkpoint_entries(
                 )
             })
             .unwrap_or_else(|| kind.to_str())
+    } else if is_pre_commit && Config::get().unattributed_changes() == UnattributedChanges::Unknown
+    {
+        // Nothing saw these edits happen, so the commit can't vouch for them either way
+        AuthorClass::Unknown.to_string()
     } else {
         // For human checkpoints, use checkpoint kind string
         kind.to_str()
@@ -666,6 +885,10 @@ async fn get_checkpoint_entries(
         .as_ref()
         .and_then(|c| c.tree().ok())
         .map(|t| t.id().to_string());
+    let head_commit_time = head_commit
+        .as_ref()
+        .and_then(|c| c.time().ok())
+        .map(|time| time.seconds().max(0) as u64);
 
     const MAX_CONCURRENT: usize = 30;
 
@@ -673,6 +896,12 @@ async fn get_checkpoint_entries(
     let semaphore = Arc::new(smol::lock::Semaphore::new(MAX_CONCURRENT));
 
     // Move checkpoint data to Arc once, outside the loop to avoid repeated allocations
+    let checkpoints_at_begin = edit_began.map(|began| {
+        (
+            began,
+            Arc::new(previous_checkpoints[..began.checkpoint_count].to_vec()),
+        )
+    });
     let previous_checkpoints = Arc::new(previous_checkpoints.to_vec());
 
     // Move other repeated allocations outside the loop
@@ -680,6 +909,15 @@ async fn get_checkpoint_entries(
     let head_commit_sha = Arc::new(head_commit_sha);
     let head_tree_id = Arc::new(head_tree_id);
     let initial_attributions = Arc::new(initial_attributions);
+    let patches: HashMap<String, FilePatch> = agent_run_result
+        .and_then(|result| result.patches.as_ref())
+        .map(|patches| {
+            patches
+                .iter()
+                .map(|patch| (patch.path.clone(), patch.clone()))
+                .collect()
+        })
+        .unwrap_or_default();
 
     // Spawn tasks for each file
     let mut tasks = Vec::new();
@@ -688,15 +926,24 @@ async fn get_checkpoint_entries(
         let file_path = file_path.clone();
         let repo = repo.clone();
         let working_log = working_log.clone();
-        let previous_checkpoints = Arc::clone(&previous_checkpoints);
+        // Files the agent said it would edit start from where its edit began
+        let previous_checkpoints = match &checkpoints_at_begin {
+            Some((began, at_begin))
+                if began.files.is_empty() || began.files.contains(&file_path) =>
+            {
+                Arc::clone(at_begin)
+            }
+            _ => Arc::clone(&previous_checkpoints),
+        };
         let author_id = Arc::clone(&author_id);
         let head_commit_sha = Arc::clone(&head_commit_sha);
         let head_tree_id = Arc::clone(&head_tree_id);
-        let blob_sha = file_content_hashes
+        let (blob_sha, current_content) = file_states
             .get(&file_path)
-            .cloned()
+            .map(|state| (state.blob_sha.clone(), Arc::clone(&state.content)))
             .unwrap_or_default();
         let initial_attributions = Arc::clone(&initial_attributions);
+        let patch = patches.get(&file_path).cloned();
         let semaphore = Arc::clone(&semaphore);
         let kind = kind.clone();
 
@@ -713,10 +960,13 @@ async fn get_checkpoint_entries(
                     working_log,
                     previous_checkpoints,
                     blob_sha,
+                    current_content,
                     author_id.clone(),
                     head_commit_sha.clone(),
                     head_tree_id.clone(),
+                    head_commit_time,
                     initial_attributions.clone(),
+                    patch,
                     ts,
                 )
             })
@@ -751,7 +1001,10 @@ fn make_entry_for_file(
     content: &str,
     ts: u128,
 ) -> Result<WorkingLogEntry, GitAiError> {
-    let tracker = AttributionTracker::new();
+    let tracker = AttributionTracker::with_config(
+        AttributionConfig::default()
+            .with_move_timestamp_policy(Config::get().move_timestamp_policy()),
+    );
     let filled_in_prev_attributions = tracker.attribute_unattributed_ranges(
         previous_content,
         previous_attributions,
@@ -767,24 +1020,74 @@ fn make_entry_for_file(
     )?;
     // TODO Consider discarding any "uncontentious" attributions for the human author. Any human attributions that do not share a line with any other author's attributions can be discarded.
     // let filtered_attributions = crate::authorship::attribution_tracker::discard_uncontentious_attributions_for_author(&new_attributions, &CheckpointKind::Human.to_str());
-    let line_attributions =
-        crate::authorship::attribution_tracker::attributions_to_line_attributions(
-            &new_attributions,
-            content,
-        );
-    Ok(WorkingLogEntry::new(
+    Ok(entry_with_attributions(
         file_path.to_string(),
         blob_sha.to_string(),
         new_attributions,
-        line_attributions,
+        content,
     ))
 }
 
+/// Attributions for `content` from the agent's own account of its edit: the lines its patch adds
+/// are the agent's, and whatever else changed since the previous version (the difference between
+/// it and the patch's pre-image) was someone else's
+fn attributions_from_patch(
+    previous_content: &str,
+    previous_attributions: &[Attribution],
+    content: &str,
+    patch: &FilePatch,
+    author_id: &str,
+    ts: u128,
+) -> Result<Vec<Attribution>, GitAiError> {
+    let pre_image = patch.pre_image(content)?;
+    let tracker = AttributionTracker::with_config(
+        AttributionConfig::default()
+            .with_move_timestamp_policy(Config::get().move_timestamp_policy()),
+    );
+    let human = CheckpointKind::Human.to_str();
+    let filled_in_prev_attributions = tracker.attribute_unattributed_ranges(
+        previous_content,
+        previous_attributions,
+        &human,
+        ts - 1,
+    );
+    let pre_image_attributions = tracker.update_attributions(
+        previous_content,
+        &pre_image,
+        &filled_in_prev_attributions,
+        &human,
+        ts - 1,
+    )?;
+    Ok(patch.apply_to_attributions(&pre_image, &pre_image_attributions, author_id, ts))
+}
+
+/// Pairs an agent's begin-agent and end-agent checkpoints
+fn agent_session(agent_id: &AgentId) -> String {
+    format!("{}:{}", agent_id.tool, agent_id.id)
+}
+
+/// A working log entry for `content` with the line attributions derived from `attributions`
+fn entry_with_attributions(
+    file_path: String,
+    blob_sha: String,
+    attributions: Vec<Attribution>,
+    content: &str,
+) -> WorkingLogEntry {
+    let line_attributions =
+        crate::authorship::attribution_tracker::attributions_to_line_attributions_with_policy(
+            &attributions,
+            content,
+            Config::get().override_policy(),
+        );
+    WorkingLogEntry::new(file_path, blob_sha, attributions, line_attributions)
+}
+
 /// Compute line statistics by diffing files against their previous versions
 fn compute_line_stats(
     repo: &Repository,
     working_log: &PersistedWorkingLog,
     files: &[String],
+    file_states: &HashMap<String, FileState>,
     _entries: &[WorkingLogEntry],
     previous_checkpoints: &[Checkpoint],
     _kind: CheckpointKind,
@@ -810,9 +1113,10 @@ fn compute_line_stats(
 
     // good candidate for parallelization
     for file_path in files {
-        let current_content = working_log
-            .read_current_file_content(file_path)
-            .unwrap_or_else(|_| String::new());
+        let current_content = file_states
+            .get(file_path)
+            .map(|state| &*state.content)
+            .unwrap_or_default();
 
         // Get previous content
         let previous_content = if let Some((prev_hash, _)) = previous_file_state.get(file_path) {
@@ -830,8 +1134,13 @@ fn compute_line_stats(
                 match tree.get_path(std::path::Path::new(file_path)) {
                     Ok(entry) => {
                         if let Ok(blob) = repo.find_blob(entry.id()) {
-                            let blob_content = blob.content().unwrap_or_default();
-                            String::from_utf8_lossy(&blob_content).to_string()
+                            let blob_content = smudge_blob_content(
+                                repo,
+                                file_path,
+                                &entry.id(),
+                                blob.content().unwrap_or_default(),
+                            );
+                            file_text::decode(&blob_content)
                         } else {
                             String::new()
                         }
@@ -844,7 +1153,7 @@ fn compute_line_stats(
         };
 
         // Use TextDiff to count line changes
-        let diff = TextDiff::from_lines(&previous_content, &current_content);
+        let diff = TextDiff::from_lines(previous_content.as_str(), current_content);
 
         for change in diff.iter_all_changes() {
             match change.tag() {
@@ -882,7 +1191,70 @@ fn compute_line_stats(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::git::test_utils::TmpRepo;
+    use crate::git::test_utils::{MockClock, TmpRepo};
+
+    #[test]
+    fn test_checkpoint_timestamps_follow_mock_clock() {
+        let clock = MockClock::ticking(1_700_000_000_000, 1);
+        let (tmp_repo, mut file, _) = TmpRepo::new_with_base_commit().unwrap();
+        let base_commit = tmp_repo.head_commit_sha().unwrap();
+
+        file.append("Human line\n").unwrap();
+        tmp_repo.trigger_checkpoint_with_author("Aidan").unwrap();
+        clock.advance(60_000);
+        file.append("AI line\n").unwrap();
+        tmp_repo
+            .trigger_checkpoint_with_ai("Claude", Some("claude-3-sonnet"), Some("cursor"))
+            .unwrap();
+
+        let checkpoints = tmp_repo
+            .gitai_repo()
+            .storage
+            .working_log_for_base_commit(&base_commit)
+            .read_all_checkpoints()
+            .unwrap();
+        assert_eq!(checkpoints.len(), 2);
+        assert_eq!(checkpoints[0].timestamp, 1_700_000_000);
+        assert_eq!(checkpoints[1].timestamp, 1_700_000_060);
+
+        // Every attribution was stamped by the mock clock, the new AI line after the advance
+        let ts: Vec<u128> = checkpoints[1].entries[0]
+            .attributions
+            .iter()
+            .map(|attribution| attribution.ts)
+            .collect();
+        assert!(ts.iter().all(|ts| *ts >= 1_700_000_000_000));
+        let newest = ts.iter().max().unwrap();
+        assert!((1_700_000_060_000..1_700_000_061_000).contains(newest));
+    }
+
+    #[test]
+    fn test_commit_time_follows_mock_clock() {
+        let clock = MockClock::at(1_700_000_000_000);
+        let tmp_repo = TmpRepo::new().unwrap();
+        tmp_repo.write_file("a.txt", "one\n", true).unwrap();
+        tmp_repo.commit_with_message("first").unwrap();
+        clock.advance(3_600_000);
+        tmp_repo.write_file("a.txt", "one\ntwo\n", true).unwrap();
+        tmp_repo.commit_with_message("second").unwrap();
+
+        let head_time = || {
+            git2::Repository::open(tmp_repo.path())
+                .unwrap()
+                .head()
+                .unwrap()
+                .peel_to_commit()
+                .unwrap()
+                .time()
+                .seconds()
+        };
+        assert_eq!(head_time(), 1_700_003_600);
+
+        drop(clock);
+        tmp_repo.write_file("a.txt", "three\n", true).unwrap();
+        tmp_repo.commit_with_message("third").unwrap();
+        assert_eq!(head_time(), 1_672_574_400);
+    }
 
     #[test]
     fn test_checkpoint_with_staged_changes() {
@@ -1041,6 +1413,7 @@ mod tests {
             transcript: Some(AiTranscript { messages: vec![] }),
             checkpoint_kind: CheckpointKind::AiAgent,
             repo_working_dir: None,
+            commit_message: None,
             edited_filepaths: Some(vec![
                 "/tmp/outside_file.txt".to_string(),
                 "../outside_parent.txt".to_string(),
@@ -1048,6 +1421,10 @@ mod tests {
             ]),
             will_edit_filepaths: None,
             dirty_files: None,
+            patches: None,
+            edit_phase: None,
+            author_class: None,
+            human_author: None,
         };
 
         // Run checkpoint - should not crash even with paths outside repo
@@ -1,5 +1,6 @@
 use crate::{
     authorship::{
+        patch::FilePatch,
         transcript::{AiTranscript, Message},
         working_log::{AgentId, CheckpointKind},
     },
@@ -25,6 +26,27 @@ pub struct AgentRunResult {
     pub edited_filepaths: Option<Vec<String>>,
     pub will_edit_filepaths: Option<Vec<String>>,
     pub dirty_files: Option<HashMap<String, String>>,
+    /// The commit message the agent wrote, for the commit it's about to make
+    pub commit_message: Option<String>,
+    /// Exactly what the agent changed, when it reports its edits as a patch
+    pub patches: Option<Vec<FilePatch>>,
+    /// Set by `checkpoint begin-agent` and `end-agent`, which bracket a single edit
+    pub edit_phase: Option<AgentEditPhase>,
+    /// The human prompting the agent, `Name <email>`, when the agent's session says who.
+    /// Otherwise the checkpoint takes the git user.
+    pub human_author: Option<String>,
+    /// Set by `checkpoint --class`, for lines neither a person nor an agent wrote
+    pub author_class: Option<String>,
+}
+
+/// Which side of an agent's edit a paired checkpoint records
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AgentEditPhase {
+    /// The files before the agent touches them; anything changed since the last checkpoint is
+    /// someone else's
+    Begin,
+    /// The files after the edit, attributed against what `Begin` saw
+    End,
 }
 
 pub trait AgentCheckpointPreset {
@@ -104,6 +126,11 @@ impl AgentCheckpointPreset for ClaudePreset {
                 edited_filepaths: None,
                 will_edit_filepaths: file_path_as_vec,
                 dirty_files: None,
+                commit_message: None,
+                patches: None,
+                edit_phase: None,
+                author_class: None,
+                human_author: None,
             });
         }
 
@@ -116,6 +143,11 @@ impl AgentCheckpointPreset for ClaudePreset {
             edited_filepaths: file_path_as_vec,
             will_edit_filepaths: None,
             dirty_files: None,
+            commit_message: None,
+            patches: None,
+            edit_phase: None,
+            author_class: None,
+            human_author: None,
         })
     }
 }
@@ -186,6 +218,11 @@ impl AgentCheckpointPreset for CursorPreset {
                 edited_filepaths: None,
                 will_edit_filepaths: None,
                 dirty_files: None,
+                commit_message: None,
+                patches: None,
+                edit_phase: None,
+                author_class: None,
+                human_author: None,
             });
         }
 
@@ -255,6 +292,11 @@ impl AgentCheckpointPreset for CursorPreset {
             edited_filepaths,
             will_edit_filepaths: None,
             dirty_files: None,
+            commit_message: None,
+            patches: None,
+            edit_phase: None,
+            author_class: None,
+            human_author: None,
         })
     }
 }
@@ -334,7 +376,7 @@ impl CursorPreset {
         Ok(transcript_data)
     }
 
-    fn cursor_user_dir() -> Result<PathBuf, GitAiError> {
+    pub(crate) fn cursor_user_dir() -> Result<PathBuf, GitAiError> {
         #[cfg(target_os = "windows")]
         {
             // Windows: %APPDATA%\Cursor\User
@@ -363,7 +405,7 @@ impl CursorPreset {
         }
     }
 
-    fn open_sqlite_readonly(path: &Path) -> Result<Connection, GitAiError> {
+    pub(crate) fn open_sqlite_readonly(path: &Path) -> Result<Connection, GitAiError> {
         Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
             .map_err(|e| GitAiError::Generic(format!("Failed to open {:?}: {}", path, e)))
     }
@@ -623,6 +665,11 @@ impl AgentCheckpointPreset for GithubCopilotPreset {
             edited_filepaths,
             will_edit_filepaths: None,
             dirty_files,
+            commit_message: None,
+            patches: None,
+            edit_phase: None,
+            author_class: None,
+            human_author: None,
         })
     }
 }
@@ -924,6 +971,11 @@ impl AgentCheckpointPreset for AiTabPreset {
                 edited_filepaths: None,
                 will_edit_filepaths,
                 dirty_files,
+                commit_message: None,
+                patches: None,
+                edit_phase: None,
+                author_class: None,
+                human_author: None,
             });
         }
 
@@ -935,6 +987,11 @@ impl AgentCheckpointPreset for AiTabPreset {
             edited_filepaths,
             will_edit_filepaths: None,
             dirty_files,
+            commit_message: None,
+            patches: None,
+            edit_phase: None,
+            author_class: None,
+            human_author: None,
         })
     }
 }
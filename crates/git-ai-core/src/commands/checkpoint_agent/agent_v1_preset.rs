@@ -24,6 +24,11 @@ enum AgentV1Input {
         agent_name: String,
         model: String,
         conversation_id: String,
+        #[serde(default)]
+        commit_message: Option<String>,
+        /// Who prompted the agent, `Name <email>`
+        #[serde(default)]
+        human_author: Option<String>,
     },
     // AiTab
 }
@@ -40,7 +45,17 @@ impl AgentCheckpointPreset for AgentV1Preset {
             )
         })?;
 
-        let agent_v1_input: AgentV1Input = serde_json::from_str(&hook_input_json).map_err(|e| {
+        Self::parse(&hook_input_json)
+    }
+}
+
+impl AgentV1Preset {
+    /// Turn an `agent-v1` JSON payload into a run result. Agent plugins answer in the same
+    /// format.
+    pub fn parse(
+        hook_input_json: &str,
+    ) -> Result<super::agent_presets::AgentRunResult, crate::error::GitAiError> {
+        let agent_v1_input: AgentV1Input = serde_json::from_str(hook_input_json).map_err(|e| {
             crate::error::GitAiError::PresetError(format!(
                 "Invalid AgentV1Input JSON. Format is documented here: https://github.com/acunniffe/git-ai/blob/main/docs/add-your-agent.mdx: \n\n Error: {}",
                 e
@@ -63,6 +78,11 @@ impl AgentCheckpointPreset for AgentV1Preset {
                 repo_working_dir: Some(repo_working_dir),
                 edited_filepaths: None,
                 dirty_files: None,
+                commit_message: None,
+                patches: None,
+                edit_phase: None,
+                author_class: None,
+                human_author: None,
             }),
             AgentV1Input::AiAgent {
                 edited_filepaths,
@@ -71,6 +91,8 @@ impl AgentCheckpointPreset for AgentV1Preset {
                 model,
                 conversation_id,
                 repo_working_dir,
+                commit_message,
+                human_author,
             } => Ok(AgentRunResult {
                 agent_id: AgentId {
                     tool: agent_name,
@@ -83,6 +105,11 @@ impl AgentCheckpointPreset for AgentV1Preset {
                 edited_filepaths: edited_filepaths,
                 will_edit_filepaths: None,
                 dirty_files: None,
+                commit_message,
+                patches: None,
+                edit_phase: None,
+                author_class: None,
+                human_author,
             }),
         }
     }
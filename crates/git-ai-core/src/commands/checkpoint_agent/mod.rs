@@ -0,0 +1,121 @@
+pub mod agent_presets;
+pub mod agent_v1_preset;
+pub mod plugins;
+pub mod process_ancestry;
+
+use crate::authorship::working_log::{AgentId, CheckpointKind};
+use crate::commands::checkpoint_agent::agent_presets::{
+    AgentCheckpointFlags, AgentCheckpointPreset, AgentRunResult, AiTabPreset, ClaudePreset,
+    CursorPreset, GithubCopilotPreset,
+};
+use crate::commands::checkpoint_agent::agent_v1_preset::AgentV1Preset;
+use crate::error::GitAiError;
+use crate::git::find_repository_in_path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Runs the checkpoint preset (or agent plugin) called `name`, as `git-ai checkpoint <name>`
+/// does: `args` are the rest of the command line (`mock_ai` reads its session, paths and
+/// `--human-author` from them), and `working_dir` is where `mock_ai` without paths looks for
+/// changed files. None if `name` is neither a preset nor a plugin.
+pub fn run_checkpoint_preset(
+    name: &str,
+    args: &[String],
+    hook_input: Option<String>,
+    commit_message: Option<String>,
+    working_dir: &str,
+) -> Result<Option<AgentRunResult>, GitAiError> {
+    let flags = AgentCheckpointFlags { hook_input };
+    let agent_run = match name {
+        "claude" => ClaudePreset.run(flags)?,
+        "cursor" => CursorPreset.run(flags)?,
+        "github-copilot" => GithubCopilotPreset.run(flags)?,
+        "ai_tab" => AiTabPreset.run(flags)?,
+        "agent-v1" => AgentV1Preset.run(flags)?,
+        "mock_ai" => {
+            let mock_agent_id = args
+                .windows(2)
+                .find(|pair| pair[0] == "--session")
+                .map(|pair| pair[1].clone())
+                .unwrap_or_else(|| {
+                    format!(
+                        "ai-thread-{}",
+                        SystemTime::now()
+                            .duration_since(UNIX_EPOCH)
+                            .map(|d| d.as_nanos())
+                            .unwrap_or_else(|_| 0)
+                    )
+                });
+
+            // Collect all remaining args (after mock_ai and flags) as pathspecs
+            let edited_filepaths = if args.len() > 1 {
+                let mut paths = Vec::new();
+                let mut rest = args[1..].iter();
+                while let Some(arg) = rest.next() {
+                    // Skip flags, and the values of flags that take one
+                    if [
+                        "--commit-message",
+                        "--patch",
+                        "--content",
+                        "--session",
+                        "--human-author",
+                    ]
+                    .contains(&arg.as_str())
+                    {
+                        rest.next();
+                    } else if !arg.starts_with("--") {
+                        paths.push(arg.clone());
+                    }
+                }
+                if paths.is_empty() { None } else { Some(paths) }
+            } else {
+                Some(get_all_files_for_mock_ai(working_dir))
+            };
+
+            AgentRunResult {
+                agent_id: AgentId {
+                    tool: "mock_ai".to_string(),
+                    id: mock_agent_id,
+                    model: "unknown".to_string(),
+                },
+                checkpoint_kind: CheckpointKind::AiAgent,
+                transcript: None,
+                repo_working_dir: None,
+                edited_filepaths,
+                will_edit_filepaths: None,
+                dirty_files: None,
+                commit_message: commit_message.clone(),
+                patches: None,
+                edit_phase: None,
+                author_class: None,
+                human_author: args
+                    .windows(2)
+                    .find(|pair| pair[0] == "--human-author")
+                    .map(|pair| pair[1].clone()),
+            }
+        }
+        name => match plugins::find_plugin(name) {
+            Some(plugin) => plugin.run(flags)?,
+            None => return Ok(None),
+        },
+    };
+    Ok(Some(agent_run))
+}
+
+/// Changed files `mock_ai` attributes when it isn't given paths
+pub fn get_all_files_for_mock_ai(working_dir: &str) -> Vec<String> {
+    // Find the git repository
+    let repo = match find_repository_in_path(&working_dir) {
+        Ok(repo) => repo,
+        Err(e) => {
+            eprintln!("Failed to find repository: {}", e);
+            return Vec::new();
+        }
+    };
+    match repo.get_staged_and_unstaged_filenames() {
+        Ok(filenames) => {
+            println!("filenames for mock_ai: {:?}", filenames);
+            filenames.into_iter().collect()
+        }
+        Err(_) => Vec::new(),
+    }
+}
@@ -0,0 +1,395 @@
+//! Agent plugins: executables in the plugins directory that teach `git-ai checkpoint` about
+//! agents this crate doesn't ship a preset for.
+//!
+//! A plugin named `acme-agent` is invoked two ways:
+//! - `acme-agent checkpoint`, for `git-ai checkpoint acme-agent`. The hook input (if any) is
+//!   written to its stdin and it prints an `agent-v1` payload.
+//! - `acme-agent detect`, from the repository's working directory, when a checkpoint runs
+//!   without a preset (including the one before every commit) and the plugin is listed in
+//!   `detect_plugins`. It prints an `agent-v1` payload naming the files its agent edited if it
+//!   recognizes its agent in the environment, and nothing otherwise.
+
+use crate::commands::checkpoint_agent::agent_presets::{
+    AgentCheckpointFlags, AgentCheckpointPreset, AgentRunResult,
+};
+use crate::commands::checkpoint_agent::agent_v1_preset::AgentV1Preset;
+use crate::config::Config;
+use crate::error::GitAiError;
+use crate::utils::debug_log;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use std::time::{Duration, Instant};
+
+/// Version of the plugin protocol, passed to plugins as `GIT_AI_PLUGIN_PROTOCOL`
+pub const PLUGIN_PROTOCOL_VERSION: &str = "1";
+const PLUGIN_TIMEOUT: Duration = Duration::from_secs(10);
+/// How long all the detecting plugins together may hold up a checkpoint or commit
+const DETECT_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AgentPlugin {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+impl AgentPlugin {
+    /// Ask the plugin whether its agent is at work in `repo_working_dir`, giving it `timeout`
+    /// to answer. A misbehaving plugin counts as "no", so it can never block a commit, and so
+    /// does an answer that names no edited files, as it would claim every pending change.
+    pub fn detect(&self, repo_working_dir: &Path, timeout: Duration) -> Option<AgentRunResult> {
+        let output = match self.invoke("detect", None, Some(repo_working_dir), timeout) {
+            Ok(output) => output,
+            Err(e) => {
+                debug_log(&format!(
+                    "Agent plugin '{}' detect failed: {}",
+                    self.name, e
+                ));
+                return None;
+            }
+        };
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        if !output.status.success() || stdout.trim().is_empty() {
+            return None;
+        }
+        match AgentV1Preset::parse(&stdout) {
+            Ok(result)
+                if result
+                    .edited_filepaths
+                    .as_ref()
+                    .is_some_and(|paths| !paths.is_empty()) =>
+            {
+                Some(result)
+            }
+            Ok(_) => {
+                debug_log(&format!(
+                    "Agent plugin '{}' detected its agent but named no edited files",
+                    self.name
+                ));
+                None
+            }
+            Err(e) => {
+                debug_log(&format!(
+                    "Agent plugin '{}' detect printed an invalid payload: {}",
+                    self.name, e
+                ));
+                None
+            }
+        }
+    }
+
+    fn invoke(
+        &self,
+        command: &str,
+        input: Option<String>,
+        current_dir: Option<&Path>,
+        timeout: Duration,
+    ) -> Result<Output, GitAiError> {
+        let mut cmd = Command::new(&self.path);
+        cmd.arg(command)
+            .env("GIT_AI_PLUGIN_PROTOCOL", PLUGIN_PROTOCOL_VERSION)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        if let Some(dir) = current_dir {
+            cmd.current_dir(dir);
+        }
+        let mut child = cmd.spawn()?;
+
+        // Feed and drain the pipes on their own threads so a plugin that writes a lot (or
+        // never reads its stdin) can't deadlock us
+        let mut stdin = child.stdin.take();
+        let input = input.unwrap_or_default();
+        std::thread::spawn(move || {
+            if let Some(stdin) = stdin.as_mut() {
+                let _ = stdin.write_all(input.as_bytes());
+            }
+        });
+        let stdout = drain(child.stdout.take());
+        let stderr = drain(child.stderr.take());
+
+        let deadline = Instant::now() + timeout;
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
+            if Instant::now() >= deadline {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(GitAiError::PresetError(format!(
+                    "Agent plugin '{}' did not finish within {} ms",
+                    self.name,
+                    timeout.as_millis()
+                )));
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        };
+
+        Ok(Output {
+            status,
+            stdout: stdout.join().unwrap_or_default(),
+            stderr: stderr.join().unwrap_or_default(),
+        })
+    }
+}
+
+impl AgentCheckpointPreset for AgentPlugin {
+    fn run(&self, flags: AgentCheckpointFlags) -> Result<AgentRunResult, GitAiError> {
+        let output = self.invoke("checkpoint", flags.hook_input, None, PLUGIN_TIMEOUT)?;
+        if !output.status.success() {
+            return Err(GitAiError::PresetError(format!(
+                "Agent plugin '{}' failed ({}): {}",
+                self.name,
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        AgentV1Preset::parse(&String::from_utf8_lossy(&output.stdout))
+    }
+}
+
+fn drain<R: Read + Send + 'static>(pipe: Option<R>) -> std::thread::JoinHandle<Vec<u8>> {
+    std::thread::spawn(move || {
+        let mut buffer = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buffer);
+        }
+        buffer
+    })
+}
+
+/// Executables in `dir`, sorted by name. The name is the file name without its extension.
+pub fn discover_plugins(dir: &Path) -> Vec<AgentPlugin> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut plugins: Vec<AgentPlugin> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| is_plugin_executable(path))
+        .filter_map(|path| {
+            let name = path.file_stem()?.to_string_lossy().to_string();
+            (!name.starts_with('.') && !name.starts_with('-')).then_some(AgentPlugin { name, path })
+        })
+        .collect();
+    plugins.sort_by(|a, b| a.name.cmp(&b.name));
+    plugins
+}
+
+/// The configured plugin called `name`, if there is one
+pub fn find_plugin(name: &str) -> Option<AgentPlugin> {
+    let dir = Config::get().plugins_dir()?;
+    discover_plugins(dir)
+        .into_iter()
+        .find(|plugin| plugin.name == name)
+}
+
+/// The first of the plugins listed in `detect_plugins` that recognizes its agent in
+/// `repo_working_dir`, in the order they are listed
+pub fn detect_agent(repo_working_dir: &Path) -> Option<AgentRunResult> {
+    let config = Config::get();
+    if config.detect_plugins().is_empty() {
+        return None;
+    }
+    let plugins = discover_plugins(config.plugins_dir()?);
+    let detecting: Vec<AgentPlugin> = config
+        .detect_plugins()
+        .iter()
+        .filter_map(|name| plugins.iter().find(|plugin| &plugin.name == name))
+        .cloned()
+        .collect();
+    detect_first(&detecting, repo_working_dir, DETECT_TIMEOUT)
+}
+
+/// Asks all of `plugins` at once, each with `timeout` to answer
+fn detect_first(
+    plugins: &[AgentPlugin],
+    repo_working_dir: &Path,
+    timeout: Duration,
+) -> Option<AgentRunResult> {
+    let handles: Vec<_> = plugins
+        .iter()
+        .map(|plugin| {
+            let plugin = plugin.clone();
+            let dir = repo_working_dir.to_path_buf();
+            std::thread::spawn(move || plugin.detect(&dir, timeout))
+        })
+        .collect();
+    handles
+        .into_iter()
+        .map(|handle| handle.join().ok().flatten())
+        .collect::<Vec<_>>()
+        .into_iter()
+        .flatten()
+        .next()
+}
+
+#[cfg(unix)]
+fn is_plugin_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(windows)]
+fn is_plugin_executable(path: &Path) -> bool {
+    let extension = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase());
+    path.is_file() && matches!(extension.as_deref(), Some("exe" | "bat" | "cmd"))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::authorship::working_log::CheckpointKind;
+    use std::os::unix::fs::PermissionsExt;
+
+    fn write_plugin(dir: &Path, name: &str, script: &str) -> PathBuf {
+        let path = dir.join(name);
+        std::fs::write(&path, format!("#!/bin/sh\n{}", script)).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_discovers_only_executables() {
+        let dir = tempfile::tempdir().unwrap();
+        write_plugin(dir.path(), "zeta-agent", "exit 0\n");
+        write_plugin(dir.path(), "acme-agent.sh", "exit 0\n");
+        std::fs::write(dir.path().join("README.md"), "not a plugin").unwrap();
+
+        let names: Vec<String> = discover_plugins(dir.path())
+            .into_iter()
+            .map(|plugin| plugin.name)
+            .collect();
+        assert_eq!(names, vec!["acme-agent", "zeta-agent"]);
+        assert!(discover_plugins(&dir.path().join("missing")).is_empty());
+    }
+
+    #[test]
+    fn test_checkpoint_passes_hook_input_and_parses_agent_v1_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_plugin(
+            dir.path(),
+            "acme-agent",
+            r#"[ "$1" = checkpoint ] && [ "$GIT_AI_PLUGIN_PROTOCOL" = 1 ] || exit 2
+conversation=$(cat)
+cat <<EOF
+{"type": "ai_agent", "repo_working_dir": "/work", "edited_filepaths": ["src/lib.rs"],
+ "transcript": {"messages": []}, "agent_name": "acme", "model": "acme-1",
+ "conversation_id": "$conversation"}
+EOF
+"#,
+        );
+        let plugin = AgentPlugin {
+            name: "acme-agent".to_string(),
+            path,
+        };
+
+        let result = plugin
+            .run(AgentCheckpointFlags {
+                hook_input: Some("conv-42".to_string()),
+            })
+            .unwrap();
+        assert_eq!(result.checkpoint_kind, CheckpointKind::AiAgent);
+        assert_eq!(result.agent_id.tool, "acme");
+        assert_eq!(result.agent_id.model, "acme-1");
+        assert_eq!(result.agent_id.id, "conv-42");
+        assert_eq!(
+            result.edited_filepaths,
+            Some(vec!["src/lib.rs".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_checkpoint_reports_plugin_failure() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_plugin(dir.path(), "broken", "echo 'no session' >&2\nexit 3\n");
+        let plugin = AgentPlugin {
+            name: "broken".to_string(),
+            path,
+        };
+
+        let err = plugin
+            .run(AgentCheckpointFlags { hook_input: None })
+            .unwrap_err();
+        assert!(err.to_string().contains("no session"));
+    }
+
+    #[test]
+    fn test_detect_runs_in_working_dir_and_ignores_silent_or_broken_plugins() {
+        let dir = tempfile::tempdir().unwrap();
+        let work = tempfile::tempdir().unwrap();
+        std::fs::write(work.path().join(".acme-session"), "").unwrap();
+        let detecting = AgentPlugin {
+            name: "acme-agent".to_string(),
+            path: write_plugin(
+                dir.path(),
+                "acme-agent",
+                r#"[ "$1" = detect ] && [ -f .acme-session ] || exit 0
+echo '{"type": "ai_agent", "repo_working_dir": "'"$PWD"'", "transcript": {"messages": []},
+ "agent_name": "acme", "model": "acme-1", "conversation_id": "session",
+ "edited_filepaths": ["src/lib.rs"]}'
+"#,
+            ),
+        };
+        let silent = AgentPlugin {
+            name: "silent".to_string(),
+            path: write_plugin(dir.path(), "silent", "exit 0\n"),
+        };
+        let garbage = AgentPlugin {
+            name: "garbage".to_string(),
+            path: write_plugin(dir.path(), "garbage", "echo '{not json'\n"),
+        };
+
+        let detected = detecting.detect(work.path(), PLUGIN_TIMEOUT).unwrap();
+        assert_eq!(detected.agent_id.tool, "acme");
+        assert!(silent.detect(work.path(), PLUGIN_TIMEOUT).is_none());
+        assert!(garbage.detect(work.path(), PLUGIN_TIMEOUT).is_none());
+        assert!(detecting.detect(dir.path(), PLUGIN_TIMEOUT).is_none());
+    }
+
+    #[test]
+    fn test_detect_asks_plugins_at_once_within_the_timeout() {
+        let dir = tempfile::tempdir().unwrap();
+        let work = tempfile::tempdir().unwrap();
+        let payload = |tool: &str, files: &str| {
+            format!(
+                r#"echo '{{"type": "ai_agent", "repo_working_dir": "/work", "transcript": {{"messages": []}},
+ "agent_name": "{}", "model": "m", "conversation_id": "s"{}}}'
+"#,
+                tool, files
+            )
+        };
+        let plugin = |name: &str, script: String| AgentPlugin {
+            name: name.to_string(),
+            path: write_plugin(dir.path(), name, &script),
+        };
+        let slow = plugin(
+            "slow",
+            format!(
+                "sleep 5\n{}",
+                payload("slow", r#", "edited_filepaths": ["a"]"#)
+            ),
+        );
+        let everything = plugin("everything", payload("everything", ""));
+        let acme = plugin(
+            "acme",
+            format!(
+                "sleep 0.2\n{}",
+                payload("acme", r#", "edited_filepaths": ["a"]"#)
+            ),
+        );
+
+        let started = Instant::now();
+        let detected = detect_first(
+            &[slow, everything, acme],
+            work.path(),
+            Duration::from_secs(1),
+        )
+        .unwrap();
+        assert_eq!(detected.agent_id.tool, "acme");
+        assert!(started.elapsed() < Duration::from_secs(3));
+    }
+}
@@ -0,0 +1,221 @@
+//! Agents that commit without ever calling `git-ai checkpoint`. Before a commit whose working
+//! log has no AI checkpoints, the processes the proxied `git` was started by are looked up,
+//! and if one of them is a known agent (`agent_processes` in the config), the pending changes
+//! are checkpointed as that agent's instead of the committer's.
+
+use crate::authorship::working_log::{AgentId, CheckpointKind};
+use crate::commands::checkpoint_agent::agent_presets::AgentRunResult;
+use crate::config::Config;
+use crate::utils::debug_log;
+
+/// How far up the process tree to look, to stay cheap on deep or looping trees
+const MAX_ANCESTORS: usize = 32;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AncestorProcess {
+    pub pid: u32,
+    /// The process name and the program it was started as, without directories
+    pub names: Vec<String>,
+}
+
+/// An agent checkpoint for the nearest ancestor process that is a configured agent, if any
+pub fn detect_agent_ancestor() -> Option<AgentRunResult> {
+    let agent_processes = Config::get().agent_processes();
+    if agent_processes.is_empty() {
+        return None;
+    }
+    let ancestors = ancestor_processes();
+    let (process, tool) = find_agent(&ancestors, agent_processes)?;
+    debug_log(&format!(
+        "Found agent '{}' in the processes running git (pid {})",
+        tool, process.pid
+    ));
+    Some(AgentRunResult {
+        agent_id: AgentId {
+            tool: tool.clone(),
+            // One session per run of the agent
+            id: format!("process-{}", process.pid),
+            model: "unknown".to_string(),
+        },
+        checkpoint_kind: CheckpointKind::AiAgent,
+        transcript: None,
+        repo_working_dir: None,
+        edited_filepaths: None,
+        will_edit_filepaths: None,
+        dirty_files: None,
+        commit_message: None,
+        patches: None,
+        edit_phase: None,
+        author_class: None,
+        human_author: None,
+    })
+}
+
+/// The nearest of `ancestors` named like one of `agent_processes`, with the name it matched
+fn find_agent<'a>(
+    ancestors: &'a [AncestorProcess],
+    agent_processes: &'a [String],
+) -> Option<(&'a AncestorProcess, &'a String)> {
+    ancestors.iter().find_map(|process| {
+        agent_processes
+            .iter()
+            .find(|agent| {
+                process
+                    .names
+                    .iter()
+                    .any(|name| name.eq_ignore_ascii_case(agent))
+            })
+            .map(|agent| (process, agent))
+    })
+}
+
+/// `path` without its directories or a Windows `.exe`
+fn program_name(path: &str) -> String {
+    let name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+    let name = name
+        .strip_suffix(".exe")
+        .or_else(|| name.strip_suffix(".EXE"))
+        .unwrap_or(name);
+    name.to_string()
+}
+
+/// The processes above this one, parent first
+#[cfg(target_os = "linux")]
+fn ancestor_processes() -> Vec<AncestorProcess> {
+    let mut ancestors = Vec::new();
+    let mut pid = std::os::unix::process::parent_id();
+    while pid > 1 && ancestors.len() < MAX_ANCESTORS {
+        let Some(stat) = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok() else {
+            break;
+        };
+        let mut names = Vec::new();
+        if let Ok(comm) = std::fs::read_to_string(format!("/proc/{}/comm", pid)) {
+            names.push(comm.trim().to_string());
+        }
+        if let Ok(cmdline) = std::fs::read(format!("/proc/{}/cmdline", pid))
+            && let Some(argv0) = cmdline.split(|byte| *byte == 0).next()
+            && !argv0.is_empty()
+        {
+            names.push(program_name(&String::from_utf8_lossy(argv0)));
+        }
+        ancestors.push(AncestorProcess { pid, names });
+        match parent_from_stat(&stat) {
+            Some(parent) if parent != pid => pid = parent,
+            _ => break,
+        }
+    }
+    ancestors
+}
+
+/// The parent pid in `/proc/<pid>/stat`. The process name before it is in parentheses and may
+/// hold spaces and parentheses itself, so fields are counted from the last `)`.
+#[cfg(target_os = "linux")]
+fn parent_from_stat(stat: &str) -> Option<u32> {
+    let (_, fields) = stat.rsplit_once(')')?;
+    fields.split_whitespace().nth(1)?.parse().ok()
+}
+
+/// The processes above this one, parent first, from a single `ps` listing
+#[cfg(all(unix, not(target_os = "linux")))]
+fn ancestor_processes() -> Vec<AncestorProcess> {
+    let output = match std::process::Command::new("ps")
+        .args(["-A", "-o", "pid=", "-o", "ppid=", "-o", "comm="])
+        .output()
+    {
+        Ok(output) if output.status.success() => output,
+        _ => return Vec::new(),
+    };
+    ancestors_from_ps(
+        &String::from_utf8_lossy(&output.stdout),
+        std::os::unix::process::parent_id(),
+    )
+}
+
+#[cfg(not(unix))]
+fn ancestor_processes() -> Vec<AncestorProcess> {
+    Vec::new()
+}
+
+/// Follows `ps -o pid=,ppid=,comm=` output up from `pid`
+#[cfg(any(test, all(unix, not(target_os = "linux"))))]
+fn ancestors_from_ps(listing: &str, mut pid: u32) -> Vec<AncestorProcess> {
+    let parents: std::collections::HashMap<u32, (u32, &str)> = listing
+        .lines()
+        .filter_map(|line| {
+            // comm is the last field, and may hold spaces
+            let (pid, rest) = line.trim_start().split_once(char::is_whitespace)?;
+            let (ppid, comm) = rest.trim_start().split_once(char::is_whitespace)?;
+            Some((pid.parse().ok()?, (ppid.parse().ok()?, comm.trim())))
+        })
+        .collect();
+
+    let mut ancestors = Vec::new();
+    while pid > 1 && ancestors.len() < MAX_ANCESTORS {
+        let Some((parent, comm)) = parents.get(&pid) else {
+            break;
+        };
+        ancestors.push(AncestorProcess {
+            pid,
+            names: vec![program_name(comm)],
+        });
+        if *parent == pid {
+            break;
+        }
+        pid = *parent;
+    }
+    ancestors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(pid: u32, names: &[&str]) -> AncestorProcess {
+        AncestorProcess {
+            pid,
+            names: names.iter().map(|name| name.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_finds_the_nearest_configured_agent() {
+        let ancestors = vec![
+            process(40, &["sh", "sh"]),
+            process(30, &["node", "aider"]),
+            process(20, &["claude", "claude"]),
+        ];
+        let agents = vec!["claude".to_string(), "Aider".to_string()];
+        let (process, tool) = find_agent(&ancestors, &agents).unwrap();
+        assert_eq!((process.pid, tool.as_str()), (30, "Aider"));
+        assert!(find_agent(&ancestors, &["codex".to_string()]).is_none());
+    }
+
+    #[test]
+    fn test_follows_ps_listing_up_from_the_parent() {
+        let listing = "    1     0 /sbin/launchd\n  \
+                       200     1 /Applications/Cursor Agent.app/cursor-agent\n  \
+                       300   200 /bin/zsh\n  \
+                       400   300 /usr/local/bin/git\n";
+        let ancestors = ancestors_from_ps(listing, 300);
+        assert_eq!(
+            ancestors,
+            vec![process(300, &["zsh"]), process(200, &["cursor-agent"])]
+        );
+    }
+
+    #[test]
+    fn test_program_name_drops_directories_and_exe() {
+        assert_eq!(program_name("/usr/local/bin/codex"), "codex");
+        assert_eq!(program_name(r"C:\Tools\claude.exe"), "claude");
+        assert_eq!(program_name("aider"), "aider");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_parent_from_stat_skips_names_with_parentheses() {
+        assert_eq!(
+            parent_from_stat("4242 (my (odd) agent) S 4100 4242 4100 0 -1"),
+            Some(4100)
+        );
+    }
+}
@@ -0,0 +1,98 @@
+use crate::{
+    error::GitAiError,
+    git::{
+        cli_parser::ParsedGitInvocation,
+        repo_storage::{OperationSnapshot, WORKING_LOG_LOCK_TIMEOUT},
+        repository::Repository,
+    },
+    utils::debug_log,
+};
+
+/// `git merge --abort`, `git rebase --abort` or `git cherry-pick --abort`
+pub fn is_abort(parsed_args: &ParsedGitInvocation) -> bool {
+    matches!(
+        parsed_args.command.as_deref(),
+        Some("merge" | "rebase" | "cherry-pick")
+    ) && parsed_args.has_command_flag("--abort")
+}
+
+/// Records HEAD's working log and the newest rewrite event as `operation` (a merge, rebase or
+/// cherry-pick) starts, so aborting it can put them back
+pub fn snapshot_before_operation(repository: &Repository, operation: &str) {
+    let Some(original_head) = repository.head().ok().and_then(|head| head.target().ok()) else {
+        return;
+    };
+    let last_event = repository
+        .storage
+        .read_rewrite_events()
+        .ok()
+        .and_then(|events| events.into_iter().next());
+
+    let snapshot = OperationSnapshot {
+        operation: operation.to_string(),
+        original_head,
+        last_event,
+    };
+    match repository.storage.write_operation_snapshot(&snapshot) {
+        Ok(_) => debug_log(&format!("✓ Snapshotted working log before {}", operation)),
+        Err(e) => debug_log(&format!(
+            "✗ Failed to snapshot working log before {}: {}",
+            operation, e
+        )),
+    }
+}
+
+pub fn post_abort_hook(
+    parsed_args: &ParsedGitInvocation,
+    repository: &mut Repository,
+    exit_status: std::process::ExitStatus,
+) {
+    if !exit_status.success() {
+        debug_log("Abort failed, leaving the operation's state alone");
+        return;
+    }
+
+    let Some(snapshot) = repository.storage.read_operation_snapshot() else {
+        debug_log("No snapshot of the aborted operation, nothing to roll back");
+        return;
+    };
+
+    // The snapshot must be of the operation aborted here. One started without git-ai (or that
+    // already finished) leaves an older snapshot behind, which doesn't match where HEAD is back at
+    let head = repository.head().ok().and_then(|head| head.target().ok());
+    if parsed_args.command.as_deref() != Some(snapshot.operation.as_str())
+        || head.as_deref() != Some(snapshot.original_head.as_str())
+    {
+        debug_log(&format!(
+            "Snapshot of {} from {} doesn't match the aborted operation, skipping roll back",
+            snapshot.operation, snapshot.original_head
+        ));
+        return;
+    }
+
+    match roll_back_operation(repository, &snapshot) {
+        Ok(_) => debug_log(&format!(
+            "✓ Rolled back aborted {} from {}",
+            snapshot.operation, snapshot.original_head
+        )),
+        Err(e) => debug_log(&format!("✗ Failed to roll back aborted operation: {}", e)),
+    }
+}
+
+/// Drops the rewrite events logged since the operation started and puts the original head's
+/// working log back to how it was then
+fn roll_back_operation(
+    repository: &Repository,
+    snapshot: &OperationSnapshot,
+) -> Result<(), GitAiError> {
+    let _lock = repository
+        .storage
+        .lock_working_logs(WORKING_LOG_LOCK_TIMEOUT)?;
+    repository
+        .storage
+        .roll_back_rewrite_events_after(snapshot.last_event.as_ref())?;
+    repository
+        .storage
+        .restore_operation_snapshot_working_log(snapshot)?;
+    repository.storage.clear_operation_snapshot()
+}
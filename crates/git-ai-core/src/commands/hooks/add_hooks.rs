@@ -0,0 +1,194 @@
+use crate::{
+    authorship::{
+        file_text,
+        virtual_attribution::VirtualAttributions,
+        working_log::{AgentId, CheckpointKind},
+    },
+    commands::{checkpoint_agent::agent_presets::AgentRunResult, hooks::commit_hooks},
+    config::Config,
+    error::GitAiError,
+    git::{
+        cli_parser::ParsedGitInvocation,
+        repo_path,
+        repo_storage::StagedFile,
+        repository::{Repository, exec_git},
+    },
+    utils::debug_log,
+};
+
+/// `git add -p`, `-i` and `-e` stage part of a file's changes
+fn stages_part_of_files(parsed_args: &ParsedGitInvocation) -> bool {
+    parsed_args.command_args.iter().any(|arg| {
+        matches!(
+            arg.as_str(),
+            "-p" | "--patch" | "-i" | "--interactive" | "-e" | "--edit"
+        )
+    })
+}
+
+/// The paths `git add` stages, from the work tree root. Without pathspecs on the command line
+/// (`git add -A`, `-u`, `--pathspec-from-file`) that's the whole tree.
+fn added_paths(parsed_args: &ParsedGitInvocation, repository: &Repository) -> Vec<String> {
+    let args = &parsed_args.command_args;
+    if args
+        .iter()
+        .any(|arg| arg.starts_with("--pathspec-from-file"))
+    {
+        return vec![".".to_string()];
+    }
+    let mut after_separator = false;
+    let mut paths = Vec::new();
+    for arg in args {
+        if arg == "--" && !after_separator {
+            after_separator = true;
+        } else if after_separator || !arg.starts_with('-') {
+            match repository.to_repo_path(arg) {
+                // The root of the work tree
+                path if path.is_empty() => paths.push(".".to_string()),
+                path => paths.push(path),
+            }
+        }
+    }
+    if paths.is_empty() {
+        paths.push(".".to_string());
+    }
+    paths
+}
+
+pub fn pre_add_hook(parsed_args: &ParsedGitInvocation, repository: &mut Repository) {
+    let stages_part = stages_part_of_files(parsed_args);
+    if !stages_part && !Config::get().checkpoint_on_add() {
+        return;
+    }
+
+    // Bring the working log up to date, so it covers every line that can be staged. Without
+    // hooks of their own, users get a checkpoint boundary each time they stage rather than one
+    // for everything at commit.
+    let human_author = commit_hooks::get_commit_default_author(repository, &[]);
+    let agent_run_result = if stages_part {
+        None
+    } else {
+        Some(AgentRunResult {
+            agent_id: AgentId {
+                tool: "git-add".to_string(),
+                id: String::new(),
+                model: "unknown".to_string(),
+            },
+            checkpoint_kind: CheckpointKind::Human,
+            transcript: None,
+            repo_working_dir: None,
+            edited_filepaths: None,
+            will_edit_filepaths: Some(added_paths(parsed_args, repository)),
+            dirty_files: None,
+            commit_message: None,
+            patches: None,
+            edit_phase: None,
+            author_class: None,
+            human_author: None,
+        })
+    };
+    let _result = crate::commands::checkpoint::run(
+        repository,
+        &human_author,
+        CheckpointKind::Human,
+        false,
+        false,
+        true,
+        agent_run_result,
+        // Staging vouches for the edits, so they aren't the commit's unattributed changes
+        stages_part,
+        false,
+    );
+
+    if stages_part {
+        repository.require_pre_command_head();
+    }
+}
+
+pub fn post_add_hook(
+    parsed_args: &ParsedGitInvocation,
+    repository: &mut Repository,
+    exit_status: std::process::ExitStatus,
+) {
+    if !exit_status.success() || !stages_part_of_files(parsed_args) {
+        return;
+    }
+
+    if let Err(e) = record_staged_attributions(repository) {
+        debug_log(&format!("Failed to record staged attributions: {}", e));
+    }
+}
+
+/// Records the attribution of each file's staged version in the working log's STAGED file, so
+/// the commit is attributed from what was staged even if the working copy changes again before
+/// it
+fn record_staged_attributions(repository: &Repository) -> Result<(), GitAiError> {
+    let base_commit = repository
+        .pre_command_base_commit
+        .clone()
+        .unwrap_or_else(|| "initial".to_string());
+    let working_va =
+        VirtualAttributions::from_just_working_log(repository.clone(), base_commit.clone(), None)?;
+    let files = working_va.files();
+    if files.is_empty() {
+        return Ok(());
+    }
+    // An amend commits the staged lines HEAD already had too, so they need their attribution
+    let working_va = if base_commit == "initial" {
+        working_va
+    } else {
+        smol::block_on(VirtualAttributions::from_working_log_for_commit(
+            repository.clone(),
+            base_commit.clone(),
+            &files,
+            None,
+        ))?
+    };
+
+    let working_log = repository.storage.working_log_for_base_commit(&base_commit);
+    let mut staged = working_log.read_staged_attributions();
+    for (file_path, blob) in index_blobs(repository, &files)? {
+        let content = repository.find_blob(blob.clone())?.content()?;
+        let line_attributions =
+            working_va.line_attributions_for_content(&file_path, &file_text::decode(&content));
+        staged.files.insert(
+            file_path,
+            StagedFile {
+                blob,
+                line_attributions,
+            },
+        );
+    }
+    working_log.write_staged_attributions(&staged)
+}
+
+/// Blob ids of `files` in the index, skipping unmerged entries
+pub fn index_blobs(
+    repository: &Repository,
+    files: &[String],
+) -> Result<Vec<(String, String)>, GitAiError> {
+    let mut args = repository.global_args_for_exec();
+    args.extend(["ls-files", "--stage", "-z"].map(String::from));
+    // A path that isn't UTF-8 can't be passed as an argument, so it's picked from the full listing
+    let narrowed = !files.iter().any(|file| repo_path::is_quoted_key(file));
+    if narrowed {
+        args.push("--".to_string());
+        args.extend(files.iter().cloned());
+    }
+    let output = exec_git(&args)?;
+
+    // Each entry is "<mode> <blob> <stage>\t<path>"
+    Ok(output
+        .stdout
+        .split(|byte| *byte == 0)
+        .filter_map(|entry| {
+            let tab = entry.iter().position(|byte| *byte == b'\t')?;
+            let info = std::str::from_utf8(&entry[..tab]).ok()?;
+            let path = repo_path::key_from_bytes(&entry[tab + 1..]);
+            let mut fields = info.split(' ');
+            let blob = fields.nth(1)?;
+            (fields.next()? == "0" && (narrowed || files.contains(&path)))
+                .then(|| (path, blob.to_string()))
+        })
+        .collect())
+}
@@ -1,5 +1,6 @@
 use crate::authorship::rebase_authorship::walk_commits_to_base;
-use crate::commands::git_handlers::CommandHooksContext;
+use crate::commands::hooks::CommandHooksContext;
+use crate::commands::hooks::abort_hooks;
 use crate::commands::hooks::commit_hooks::get_commit_default_author;
 use crate::git::cli_parser::{ParsedGitInvocation, is_dry_run};
 use crate::git::repository::Repository;
@@ -49,6 +50,8 @@ pub fn pre_cherry_pick_hook(
                     source_commits
                 ));
 
+                abort_hooks::snapshot_before_operation(repository, "cherry-pick");
+
                 // Log the cherry-pick start event
                 let start_event = RewriteLogEvent::cherry_pick_start(
                     crate::git::rewrite_log::CherryPickStartEvent::new(
@@ -0,0 +1,169 @@
+use std::path::PathBuf;
+
+use crate::config::Config;
+use crate::git::cli_parser::{ParsedGitInvocation, invocation_dir};
+use crate::git::refs::ref_exists;
+use crate::git::repository::find_repository_in_path;
+use crate::git::sync_authorship::{
+    NotesSyncPolicy, configure_notes_fetch_refspecs, fetch_authorship_notes,
+};
+use crate::utils::debug_log;
+
+/// `git clone` options that take a value as the next argument
+const OPTIONS_WITH_VALUE: &[&str] = &[
+    "-o",
+    "--origin",
+    "-b",
+    "--branch",
+    "-u",
+    "--upload-pack",
+    "--reference",
+    "--reference-if-able",
+    "--separate-git-dir",
+    "--depth",
+    "--shallow-since",
+    "--shallow-exclude",
+    "-c",
+    "--config",
+    "--server-option",
+    "-j",
+    "--jobs",
+    "--template",
+    "--filter",
+    "--bundle-uri",
+    "--ref-format",
+    "--revision",
+];
+
+/// A fresh clone has the code but not refs/notes/ai, so it looks like nothing in it was ever
+/// written by AI. Fetches the notes right after the clone and adds the notes fetch refspec, so
+/// plain `git fetch` keeps them up to date from then on. Runs outside of any repository, so it's
+/// called from `run_git` rather than the per-repository hooks.
+pub fn post_clone_hook(parsed_args: &ParsedGitInvocation, exit_status: std::process::ExitStatus) {
+    if !exit_status.success() {
+        return;
+    }
+    let args = &parsed_args.command_args;
+    // Bare and mirror clones have no work tree for git-ai to attribute
+    if args.iter().any(|arg| arg == "--bare" || arg == "--mirror") {
+        return;
+    }
+    let Some(target) = clone_target_dir(parsed_args) else {
+        debug_log("couldn't work out the clone's directory; skipping authorship fetch");
+        return;
+    };
+    let repository = match find_repository_in_path(&target.to_string_lossy()) {
+        Ok(repository) => repository,
+        Err(e) => {
+            debug_log(&format!(
+                "failed to open clone at {}: {}",
+                target.display(),
+                e
+            ));
+            return;
+        }
+    };
+    if !Config::get().is_allowed_repository(&Some(repository.clone())) {
+        return;
+    }
+
+    let remote = option_value(args, "-o", "--origin").unwrap_or_else(|| "origin".to_string());
+    if !NotesSyncPolicy::for_hook(&repository, &remote).fetches() {
+        return;
+    }
+    let quiet = args.iter().any(|arg| arg == "-q" || arg == "--quiet");
+    if let Err(e) = fetch_authorship_notes(&repository, &remote) {
+        debug_log(&format!("authorship fetch after clone failed: {}", e));
+    } else if !quiet && ref_exists(&repository, "refs/notes/ai") {
+        eprintln!("git-ai: fetched authorship notes from {}", remote);
+    }
+    if let Err(e) = configure_notes_fetch_refspecs(&repository) {
+        debug_log(&format!(
+            "failed to configure notes refspecs after clone: {}",
+            e
+        ));
+    }
+}
+
+/// The directory `git clone` created: the one given after the repository, or else the
+/// repository's "humanish" name, as git derives it
+pub fn clone_target_dir(parsed_args: &ParsedGitInvocation) -> Option<PathBuf> {
+    let mut positionals = Vec::new();
+    let mut args = parsed_args.command_args.iter();
+    let mut end_of_opts = false;
+    while let Some(arg) = args.next() {
+        if end_of_opts || !arg.starts_with('-') {
+            positionals.push(arg.as_str());
+        } else if arg == "--" {
+            end_of_opts = true;
+        } else if OPTIONS_WITH_VALUE.contains(&arg.as_str()) {
+            args.next();
+        }
+    }
+    let dir = match positionals.as_slice() {
+        [_, dir, ..] => PathBuf::from(dir),
+        [url] => PathBuf::from(humanish_name(url)?),
+        [] => return None,
+    };
+    // `git -C <path> clone` clones relative to <path>
+    Some(invocation_dir(&parsed_args.global_args).join(dir))
+}
+
+/// The last component of `url` without a trailing `.git` or `/.git`, e.g. `repo` for
+/// `git@host:org/repo.git`
+fn humanish_name(url: &str) -> Option<String> {
+    let trimmed = url.trim_end_matches('/');
+    let trimmed = trimmed.strip_suffix("/.git").unwrap_or(trimmed);
+    let trimmed = trimmed.trim_end_matches('/');
+    let last = trimmed.rsplit(['/', ':', '\\']).next()?;
+    let name = last.strip_suffix(".git").unwrap_or(last);
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// The value of option `short`/`long` in `args`, either as the next argument or after `=`
+fn option_value(args: &[String], short: &str, long: &str) -> Option<String> {
+    let mut value = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == short || arg == long {
+            value = iter.next().cloned();
+        } else if let Some(rest) = arg.strip_prefix(&format!("{}=", long)) {
+            value = Some(rest.to_string());
+        }
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::cli_parser::parse_git_cli_args;
+
+    fn target(args: &[&str]) -> Option<PathBuf> {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        clone_target_dir(&parse_git_cli_args(&args))
+    }
+
+    #[test]
+    fn test_clone_target_dir_follows_git() {
+        let cwd = std::env::current_dir().unwrap();
+        assert_eq!(
+            target(&["clone", "https://example.com/org/repo.git"]),
+            Some(cwd.join("repo"))
+        );
+        assert_eq!(
+            target(&["clone", "git@example.com:repo.git/"]),
+            Some(cwd.join("repo"))
+        );
+        assert_eq!(target(&["clone", "/srv/repo/.git"]), Some(cwd.join("repo")));
+        assert_eq!(
+            target(&["clone", "--depth", "1", "-b", "main", "../upstream", "work"]),
+            Some(cwd.join("work"))
+        );
+        assert_eq!(
+            target(&["-C", "/tmp", "clone", "--origin=up", "../upstream"]),
+            Some(PathBuf::from("/tmp/upstream"))
+        );
+        assert_eq!(target(&["clone"]), None);
+    }
+}
@@ -0,0 +1,411 @@
+use crate::authorship::attribution_gaps::{
+    UnattributedChanges, attribution_gaps, blocked_gap_message, gap_warning,
+};
+use crate::authorship::commit_policy::{
+    AI_ACK_FLAG, blocked_commit_message, unreviewed_protected_changes,
+};
+use crate::authorship::commit_trailers;
+use crate::authorship::pre_commit;
+use crate::authorship::working_log::CheckpointKind;
+use crate::commands::hooks::CommandHooksContext;
+use crate::commands::hooks::add_hooks;
+use crate::config::Config;
+use crate::error::GitAiError;
+use crate::git::cli_parser::{CommitScope, ParsedGitInvocation, commit_scope, is_dry_run};
+use crate::git::repo_storage::StagedAttributions;
+use crate::git::repository::Repository;
+use crate::git::rewrite_log::RewriteLogEvent;
+use crate::utils::debug_log;
+use std::cell::OnceCell;
+use std::collections::{HashMap, HashSet};
+
+pub fn commit_pre_command_hook(
+    parsed_args: &ParsedGitInvocation,
+    repository: &mut Repository,
+    command_hooks_context: &mut CommandHooksContext,
+) -> bool {
+    if is_dry_run(&parsed_args.command_args) {
+        return false;
+    }
+
+    // store HEAD context for post-command hook
+    repository.require_pre_command_head();
+
+    // A commit of just some paths leaves the rest of the index staged. Keep what `git add -p`
+    // recorded about it, since the commit replaces the working log it's stored in
+    if commit_scope(&parsed_args.command_args).leaves_staged_changes() {
+        let base_commit = repository
+            .pre_command_base_commit
+            .clone()
+            .unwrap_or_else(|| "initial".to_string());
+        command_hooks_context.staged_attributions = Some(
+            repository
+                .storage
+                .staged_attributions_for_base_commit(&base_commit),
+        );
+    }
+
+    let default_author = get_commit_default_author(&repository, &parsed_args.command_args);
+
+    let base_commit = repository
+        .pre_command_base_commit
+        .clone()
+        .unwrap_or_else(|| "initial".to_string());
+    // Listed only when needed, since it runs git
+    let changed_files = OnceCell::new();
+    let changed_files = || {
+        changed_files
+            .get_or_init(|| files_in_commit(repository, &base_commit, &parsed_args.command_args))
+            .as_ref()
+    };
+
+    // Measured before the pre-commit checkpoint, which covers whatever it finds
+    let unattributed_changes = Config::get().unattributed_changes();
+    let gaps = if Config::get().attribution_gap_warnings()
+        || unattributed_changes != UnattributedChanges::Human
+    {
+        match attribution_gaps(repository, &base_commit, changed_files()) {
+            Ok(gaps) => gaps.filter(|gaps| gaps.uncovered_lines() > 0),
+            Err(e) => {
+                debug_log(&format!("Failed to check for attribution gaps: {}", e));
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    if unattributed_changes == UnattributedChanges::Block
+        && let Some(gaps) = &gaps
+    {
+        eprint!("{}", blocked_gap_message(gaps));
+        std::process::exit(1);
+    }
+
+    // Run pre-commit logic
+    match pre_commit::pre_commit(&repository, default_author.clone()) {
+        // An agent recognized at commit time takes the uncovered lines, so they're no gap
+        Ok(CheckpointKind::Human) => {
+            let warn = match unattributed_changes {
+                UnattributedChanges::Warn => true,
+                UnattributedChanges::Human => Config::get().attribution_gap_warnings(),
+                UnattributedChanges::Block | UnattributedChanges::Unknown => false,
+            };
+            if let Some(gaps) = gaps.filter(|gaps| {
+                warn && (unattributed_changes == UnattributedChanges::Warn || gaps.is_suspicious())
+            }) {
+                eprint!("{}", gap_warning(&gaps));
+            }
+        }
+        Ok(_) => {}
+        Err(e) => {
+            if e.to_string()
+                .contains("Cannot run checkpoint on bare repositories")
+            {
+                eprintln!(
+                    "Cannot run checkpoint on bare repositories (skipping git-ai pre-commit hook)"
+                );
+                return false;
+            }
+            eprintln!("Pre-commit failed: {}", e);
+            std::process::exit(1);
+        }
+    }
+
+    if !command_hooks_context.ai_ack {
+        match unreviewed_protected_changes(repository, &base_commit, changed_files) {
+            Ok(changes) if !changes.is_empty() => {
+                eprint!("{}", blocked_commit_message(&changes));
+                std::process::exit(1);
+            }
+            Ok(_) => {}
+            Err(e) => debug_log(&format!("Failed to check protected paths: {}", e)),
+        }
+    }
+
+    if Config::get().commit_trailers() {
+        match commit_trailers::commit_trailers(repository, &base_commit, changed_files()) {
+            Ok(trailers) => command_hooks_context.commit_trailers = trailers,
+            Err(e) => debug_log(&format!("Failed to work out commit trailers: {}", e)),
+        }
+    }
+    return true;
+}
+
+/// The files the commit is about to change, as far as they can be told before it runs. None on
+/// the first commit, or when they can't be listed
+fn files_in_commit(
+    repository: &Repository,
+    base_commit: &str,
+    args: &[String],
+) -> Option<HashSet<String>> {
+    if base_commit == "initial" {
+        return None;
+    }
+    let diff_names = |extra: &[&str]| -> Option<Vec<String>> {
+        let mut diff_args = vec!["diff", "--name-only", "--no-renames"];
+        diff_args.extend_from_slice(extra);
+        let output = repository.git(&diff_args).ok()?;
+        Some(crate::git::repo_path::parse_name_only(output.as_bytes()))
+    };
+
+    // `-a` stages every tracked change, and `-p` may pick any of them
+    let all = args.iter().any(|arg| {
+        arg == "--all"
+            || arg.strip_prefix('-').is_some_and(|cluster| {
+                !cluster.starts_with('-')
+                    && cluster
+                        .split(|flag| "mFCct".contains(flag))
+                        .next()
+                        .is_some_and(|flags| flags.contains('a'))
+            })
+    });
+    let mut files = HashSet::new();
+    match commit_scope(args) {
+        CommitScope::Index if !all => files.extend(diff_names(&["--cached", base_commit])?),
+        CommitScope::Index | CommitScope::Patch => files.extend(diff_names(&[base_commit])?),
+        CommitScope::Paths { paths, include } => {
+            if include {
+                files.extend(diff_names(&["--cached", base_commit])?);
+            }
+            let mut path_args = vec![base_commit, "--"];
+            path_args.extend(paths.iter().map(String::as_str));
+            files.extend(diff_names(&path_args)?);
+        }
+    }
+    Some(files)
+}
+
+/// The git invocation to run, with a `--trailer` for each of `trailers` ahead of the command's
+/// own arguments so they stay clear of any pathspec
+/// Takes `--ai-ack` out of a commit's arguments, returning whether it was there. Arguments after
+/// `--` are paths, so they're left alone.
+pub fn take_ai_ack(parsed_args: &mut ParsedGitInvocation) -> bool {
+    if parsed_args.command.as_deref() != Some("commit") {
+        return false;
+    }
+    let options_end = parsed_args
+        .command_args
+        .iter()
+        .position(|arg| arg == "--")
+        .unwrap_or(parsed_args.command_args.len());
+    let before = parsed_args.command_args.len();
+    let mut index = 0;
+    parsed_args.command_args.retain(|arg| {
+        index += 1;
+        index > options_end || arg != AI_ACK_FLAG
+    });
+    parsed_args.command_args.len() != before
+}
+
+pub fn invocation_with_trailers(
+    parsed_args: &ParsedGitInvocation,
+    trailers: &[String],
+) -> Vec<String> {
+    if trailers.is_empty() {
+        return parsed_args.to_invocation_vec();
+    }
+    let mut parsed_args = parsed_args.clone();
+    parsed_args.command_args.splice(
+        0..0,
+        trailers
+            .iter()
+            .flat_map(|trailer| ["--trailer".to_string(), trailer.clone()]),
+    );
+    parsed_args.to_invocation_vec()
+}
+
+pub fn commit_post_command_hook(
+    parsed_args: &ParsedGitInvocation,
+    exit_status: std::process::ExitStatus,
+    repository: &mut Repository,
+    command_hooks_context: &mut CommandHooksContext,
+) {
+    if is_dry_run(&parsed_args.command_args) {
+        return;
+    }
+
+    if !exit_status.success() {
+        return;
+    }
+
+    if let Some(pre_commit_hook_result) = command_hooks_context.pre_commit_hook_result {
+        if !pre_commit_hook_result {
+            debug_log("Skipping git-ai post-commit hook because pre-commit hook failed");
+            return;
+        }
+    }
+
+    let supress_output = parsed_args.has_command_flag("--porcelain")
+        || parsed_args.has_command_flag("--quiet")
+        || parsed_args.has_command_flag("-q")
+        || parsed_args.has_command_flag("--no-status");
+
+    let original_commit = repository.pre_command_base_commit.clone();
+    let new_sha = repository.head().ok().map(|h| h.target().ok()).flatten();
+
+    // empty repo, commit did not land
+    if new_sha.is_none() {
+        return;
+    }
+
+    let commit_author = get_commit_default_author(repository, &parsed_args.command_args);
+    let new_sha = new_sha.unwrap();
+    if parsed_args.has_command_flag("--amend") && original_commit.is_some() {
+        repository.handle_rewrite_log_event(
+            RewriteLogEvent::commit_amend(original_commit.unwrap(), new_sha.clone()),
+            commit_author,
+            supress_output,
+            true,
+        );
+    } else {
+        repository.handle_rewrite_log_event(
+            RewriteLogEvent::commit(original_commit, new_sha.clone()),
+            commit_author,
+            supress_output,
+            true,
+        );
+    }
+
+    if let Some(staged) = command_hooks_context.staged_attributions.take()
+        && let Err(e) = carry_staged_attributions(repository, staged, &new_sha)
+    {
+        debug_log(&format!("Failed to carry over staged attributions: {}", e));
+    }
+}
+
+/// Moves the STAGED attributions of files that are still staged after the commit into the new
+/// commit's working log
+fn carry_staged_attributions(
+    repository: &Repository,
+    mut staged: StagedAttributions,
+    commit_sha: &str,
+) -> Result<(), GitAiError> {
+    if staged.files.is_empty() {
+        return Ok(());
+    }
+
+    let files: Vec<String> = staged.files.keys().cloned().collect();
+    let index_blobs: HashMap<String, String> = add_hooks::index_blobs(repository, &files)?
+        .into_iter()
+        .collect();
+    let tree = repository.find_commit(commit_sha.to_string())?.tree()?;
+    staged.files.retain(|file_path, file| {
+        let committed_blob = tree
+            .get_path(std::path::Path::new(file_path))
+            .ok()
+            .map(|entry| entry.id());
+        index_blobs.get(file_path) == Some(&file.blob)
+            && committed_blob.as_deref() != Some(file.blob.as_str())
+    });
+    if staged.files.is_empty() {
+        return Ok(());
+    }
+
+    repository
+        .storage
+        .working_log_for_base_commit(commit_sha)
+        .write_staged_attributions(&staged)
+}
+
+pub fn get_commit_default_author(repo: &Repository, args: &[String]) -> String {
+    // According to git commit manual, --author flag overrides all other author information
+    if let Some(author_spec) = extract_author_from_args(args) {
+        if let Ok(Some(resolved_author)) = repo.resolve_author_spec(&author_spec) {
+            if !resolved_author.trim().is_empty() {
+                return resolved_author.trim().to_string();
+            }
+        }
+    }
+
+    // Normal precedence when --author is not specified:
+    // Name precedence: GIT_AUTHOR_NAME env > user.name config > extract from EMAIL env > "unknown"
+    // Email precedence: GIT_AUTHOR_EMAIL env > user.email config > EMAIL env > None
+
+    let mut author_name: Option<String> = None;
+    let mut author_email: Option<String> = None;
+
+    // Check GIT_AUTHOR_NAME environment variable
+    if let Ok(name) = std::env::var("GIT_AUTHOR_NAME") {
+        if !name.trim().is_empty() {
+            author_name = Some(name.trim().to_string());
+        }
+    }
+
+    // Fall back to git config user.name
+    if author_name.is_none() {
+        if let Ok(Some(name)) = repo.config_get_str("user.name") {
+            if !name.trim().is_empty() {
+                author_name = Some(name.trim().to_string());
+            }
+        }
+    }
+
+    // Check GIT_AUTHOR_EMAIL environment variable
+    if let Ok(email) = std::env::var("GIT_AUTHOR_EMAIL") {
+        if !email.trim().is_empty() {
+            author_email = Some(email.trim().to_string());
+        }
+    }
+
+    // Fall back to git config user.email
+    if author_email.is_none() {
+        if let Ok(Some(email)) = repo.config_get_str("user.email") {
+            if !email.trim().is_empty() {
+                author_email = Some(email.trim().to_string());
+            }
+        }
+    }
+
+    // Check EMAIL environment variable as fallback for both name and email
+    if author_name.is_none() || author_email.is_none() {
+        if let Ok(email) = std::env::var("EMAIL") {
+            if !email.trim().is_empty() {
+                // Extract name part from email if we don't have a name yet
+                if author_name.is_none() {
+                    if let Some(at_pos) = email.find('@') {
+                        let name_part = &email[..at_pos];
+                        if !name_part.is_empty() {
+                            author_name = Some(name_part.to_string());
+                        }
+                    }
+                }
+                // Use as email if we don't have an email yet
+                if author_email.is_none() {
+                    author_email = Some(email.trim().to_string());
+                }
+            }
+        }
+    }
+
+    // Format the author string based on what we have
+    match (author_name, author_email) {
+        (Some(name), Some(email)) => format!("{} <{}>", name, email),
+        (Some(name), None) => name,
+        (None, Some(email)) => email,
+        (None, None) => {
+            eprintln!("Warning: No author information found. Using 'unknown' as author.");
+            "unknown".to_string()
+        }
+    }
+}
+
+fn extract_author_from_args(args: &[String]) -> Option<String> {
+    let mut i = 0;
+    while i < args.len() {
+        let arg = &args[i];
+
+        // Handle --author=<author> format
+        if let Some(author_value) = arg.strip_prefix("--author=") {
+            return Some(author_value.to_string());
+        }
+
+        // Handle --author <author> format (separate arguments)
+        if arg == "--author" && i + 1 < args.len() {
+            return Some(args[i + 1].clone());
+        }
+
+        i += 1;
+    }
+    None
+}
@@ -1,16 +1,15 @@
-use crate::commands::git_handlers::CommandHooksContext;
-use crate::commands::upgrade;
+use crate::commands::hooks::CommandHooksContext;
 use crate::git::cli_parser::{ParsedGitInvocation, is_dry_run};
 use crate::git::repository::{Repository, find_repository};
-use crate::git::sync_authorship::{fetch_authorship_notes, fetch_remote_from_args};
+use crate::git::sync_authorship::{
+    NotesSyncPolicy, fetch_authorship_notes, fetch_remote_from_args,
+};
 use crate::utils::debug_log;
 
 pub fn fetch_pull_pre_command_hook(
     parsed_args: &ParsedGitInvocation,
     repository: &Repository,
 ) -> Option<std::thread::JoinHandle<()>> {
-    upgrade::maybe_schedule_background_update_check();
-
     // Early return for dry-run
     if is_dry_run(&parsed_args.command_args) {
         return None;
@@ -26,6 +25,13 @@ pub fn fetch_pull_pre_command_hook(
             return None;
         }
     };
+    if !NotesSyncPolicy::for_hook(repository, &remote).fetches() {
+        debug_log(&format!(
+            "remote.{}.aiNotes doesn't fetch notes; skipping authorship fetch",
+            remote
+        ));
+        return None;
+    }
 
     // Clone what we need for the background thread
     let global_args = repository.global_args_for_exec();
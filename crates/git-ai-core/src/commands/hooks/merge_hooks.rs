@@ -1,5 +1,5 @@
 use crate::{
-    commands::hooks::commit_hooks::get_commit_default_author,
+    commands::hooks::{abort_hooks, commit_hooks::get_commit_default_author},
     git::{
         cli_parser::{ParsedGitInvocation, is_dry_run},
         repository::Repository,
@@ -7,6 +7,14 @@ use crate::{
     },
 };
 
+pub fn pre_merge_hook(parsed_args: &ParsedGitInvocation, repository: &mut Repository) {
+    // Only a merge that's starting has a working log worth putting back if it's aborted
+    if parsed_args.has_command_flag("--continue") || parsed_args.has_command_flag("--quit") {
+        return;
+    }
+    abort_hooks::snapshot_before_operation(repository, "merge");
+}
+
 pub fn post_merge_hook(
     parsed_args: &ParsedGitInvocation,
     exit_status: std::process::ExitStatus,
@@ -0,0 +1,27 @@
+pub mod abort_hooks;
+pub mod add_hooks;
+pub mod cherry_pick_hooks;
+pub mod clone_hooks;
+pub mod commit_hooks;
+pub mod fetch_hooks;
+pub mod merge_hooks;
+pub mod push_hooks;
+pub mod rebase_hooks;
+pub mod rerere_hooks;
+pub mod reset_hooks;
+pub mod shadow_hooks;
+
+use crate::git::repo_storage::StagedAttributions;
+
+/// State the pre-command hooks of a git command hand to its post-command hooks
+pub struct CommandHooksContext {
+    pub pre_commit_hook_result: Option<bool>,
+    pub rebase_original_head: Option<String>,
+    pub _rebase_onto: Option<String>,
+    pub push_authorship_handle: Option<std::thread::JoinHandle<()>>,
+    pub fetch_authorship_handle: Option<std::thread::JoinHandle<()>>,
+    pub staged_attributions: Option<StagedAttributions>,
+    pub commit_trailers: Vec<String>,
+    /// Whether the commit was run with `--ai-ack`, letting AI changes to protected paths through
+    pub ai_ack: bool,
+}
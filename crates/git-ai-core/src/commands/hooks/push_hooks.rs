@@ -1,16 +1,13 @@
-use crate::commands::git_handlers::CommandHooksContext;
-use crate::commands::upgrade;
+use crate::commands::hooks::CommandHooksContext;
 use crate::git::cli_parser::{ParsedGitInvocation, is_dry_run};
 use crate::git::repository::{Repository, find_repository};
-use crate::git::sync_authorship::push_authorship_notes;
+use crate::git::sync_authorship::{NotesSyncPolicy, push_authorship_notes};
 use crate::utils::debug_log;
 
 pub fn push_pre_command_hook(
     parsed_args: &ParsedGitInvocation,
     repository: &Repository,
 ) -> Option<std::thread::JoinHandle<()>> {
-    upgrade::maybe_schedule_background_update_check();
-
     // Early returns for cases where we shouldn't push authorship notes
     if is_dry_run(&parsed_args.command_args)
         || parsed_args
@@ -22,30 +19,17 @@ pub fn push_pre_command_hook(
         return None;
     }
 
-    let remotes = repository.remotes().ok();
-    let remote_names: Vec<String> = remotes
-        .as_ref()
-        .map(|r| {
-            (0..r.len())
-                .filter_map(|i| r.get(i).map(|s| s.to_string()))
-                .collect()
-        })
-        .unwrap_or_default();
-
-    // Push authorship refs to the appropriate remote
-    let positional_remote = extract_remote_from_push_args(&parsed_args.command_args, &remote_names);
-
-    let specified_remote = positional_remote.or_else(|| {
-        parsed_args
-            .command_args
-            .iter()
-            .find(|a| remote_names.iter().any(|r| r == *a))
-            .cloned()
-    });
+    let remote = push_remote(parsed_args, repository);
 
-    let remote = specified_remote
-        .or_else(|| repository.upstream_remote().ok().flatten())
-        .or_else(|| repository.get_default_remote().ok().flatten());
+    if let Some(remote) = remote.as_ref()
+        && !NotesSyncPolicy::for_hook(repository, remote).pushes()
+    {
+        debug_log(&format!(
+            "remote.{}.aiNotes doesn't push notes; skipping authorship push",
+            remote
+        ));
+        return None;
+    }
 
     if let Some(remote) = remote {
         debug_log(&format!(
@@ -75,6 +59,34 @@ pub fn push_pre_command_hook(
     }
 }
 
+/// The remote a push goes to, which its authorship notes are pushed to as well
+pub fn push_remote(parsed_args: &ParsedGitInvocation, repository: &Repository) -> Option<String> {
+    let remotes = repository.remotes().ok();
+    let remote_names: Vec<String> = remotes
+        .as_ref()
+        .map(|r| {
+            (0..r.len())
+                .filter_map(|i| r.get(i).map(|s| s.to_string()))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    // Push authorship refs to the appropriate remote
+    let positional_remote = extract_remote_from_push_args(&parsed_args.command_args, &remote_names);
+
+    let specified_remote = positional_remote.or_else(|| {
+        parsed_args
+            .command_args
+            .iter()
+            .find(|a| remote_names.iter().any(|r| r == *a))
+            .cloned()
+    });
+
+    specified_remote
+        .or_else(|| repository.upstream_remote().ok().flatten())
+        .or_else(|| repository.get_default_remote().ok().flatten())
+}
+
 pub fn push_post_command_hook(
     _repository: &Repository,
     _parsed_args: &ParsedGitInvocation,
@@ -1,10 +1,12 @@
 use crate::authorship::rebase_authorship::walk_commits_to_base;
-use crate::commands::git_handlers::CommandHooksContext;
+use crate::commands::hooks::CommandHooksContext;
+use crate::commands::hooks::abort_hooks;
 use crate::commands::hooks::commit_hooks::get_commit_default_author;
 use crate::git::cli_parser::ParsedGitInvocation;
 use crate::git::cli_parser::is_dry_run;
+use crate::git::cli_parser::{RebaseArgs, parse_rebase_args};
 use crate::git::repository::Repository;
-use crate::git::rewrite_log::RewriteLogEvent;
+use crate::git::rewrite_log::{RebaseStartEvent, RewriteLogEvent};
 use crate::utils::debug_log;
 
 pub fn pre_rebase_hook(
@@ -36,30 +38,48 @@ pub fn pre_rebase_hook(
 
     if !is_continuing {
         // Starting a new rebase - capture original HEAD and log Start event
-        if let Ok(head) = repository.head() {
-            if let Ok(target) = head.target() {
-                debug_log(&format!("Starting new rebase from HEAD: {}", target));
-                command_hooks_context.rebase_original_head = Some(target.clone());
-
-                // Determine if interactive
-                let is_interactive = parsed_args.has_command_flag("-i")
-                    || parsed_args.has_command_flag("--interactive");
-
-                debug_log(&format!("Interactive rebase: {}", is_interactive));
-
-                // Log the rebase start event
-                let start_event = RewriteLogEvent::rebase_start(
-                    crate::git::rewrite_log::RebaseStartEvent::new(target.clone(), is_interactive),
-                );
-
-                // Write to rewrite log
-                match repository.storage.append_rewrite_event(start_event) {
-                    Ok(_) => debug_log("✓ Logged RebaseStart event"),
-                    Err(e) => debug_log(&format!("✗ Failed to log RebaseStart event: {}", e)),
-                }
+        let rebase_args = parse_rebase_args(&parsed_args.command_args);
+
+        // git checks out `<branch>` first, so that's what gets rebased
+        let original_head = match &rebase_args.branch {
+            Some(branch) => resolve_commit(repository, branch),
+            None => repository.head().ok().and_then(|head| head.target().ok()),
+        };
+
+        if let Some(target) = original_head {
+            debug_log(&format!("Starting new rebase from HEAD: {}", target));
+            command_hooks_context.rebase_original_head = Some(target.clone());
+
+            // Determine if interactive
+            let is_interactive =
+                parsed_args.has_command_flag("-i") || parsed_args.has_command_flag("--interactive");
+
+            debug_log(&format!("Interactive rebase: {}", is_interactive));
+
+            let (upstream, onto) = resolve_rebase_range(repository, &rebase_args, &target);
+            debug_log(&format!(
+                "Rebase range: upstream={:?}, onto={:?}",
+                upstream, onto
+            ));
+
+            abort_hooks::snapshot_before_operation(repository, "rebase");
+
+            // Log the rebase start event
+            let start_event =
+                RewriteLogEvent::rebase_start(crate::git::rewrite_log::RebaseStartEvent::new(
+                    target.clone(),
+                    is_interactive,
+                    upstream,
+                    onto,
+                ));
+
+            // Write to rewrite log
+            match repository.storage.append_rewrite_event(start_event) {
+                Ok(_) => debug_log("✓ Logged RebaseStart event"),
+                Err(e) => debug_log(&format!("✗ Failed to log RebaseStart event: {}", e)),
             }
         } else {
-            debug_log("Could not read HEAD for new rebase");
+            debug_log("Could not resolve the head of the new rebase");
         }
     } else {
         debug_log("Continuing existing rebase (will read original head from log in post-hook)");
@@ -100,7 +120,10 @@ pub fn handle_rebase_post_command(
     // Rebase is done (completed or aborted)
     // Try to find the original head from context OR from the rewrite log
     let original_head_from_context = context.rebase_original_head.clone();
-    let original_head_from_log = find_rebase_start_event_original_head(repository);
+    let start_event = find_rebase_start_event(repository);
+    let original_head_from_log = start_event
+        .as_ref()
+        .map(|start_event| start_event.original_head.clone());
 
     debug_log(&format!(
         "Original head: context={:?}, log={:?}",
@@ -133,7 +156,20 @@ pub fn handle_rebase_post_command(
             "Processing completed rebase from {}",
             original_head
         ));
-        process_completed_rebase(repository, &original_head, parsed_args);
+        // The range is only known from the start event of this rebase
+        let (upstream, onto) = match start_event {
+            Some(start_event) if start_event.original_head == original_head => {
+                (start_event.upstream, start_event.onto)
+            }
+            _ => (None, None),
+        };
+        process_completed_rebase(
+            repository,
+            &original_head,
+            upstream.as_deref(),
+            onto.as_deref(),
+            parsed_args,
+        );
     } else {
         debug_log("⚠ Rebase completed but couldn't determine original head");
     }
@@ -164,15 +200,15 @@ fn has_active_rebase_start_event(repository: &Repository) -> bool {
     false // No rebase events found
 }
 
-/// Find the original head from the most recent Rebase Start event in the log
-fn find_rebase_start_event_original_head(repository: &Repository) -> Option<String> {
+/// Find the most recent Rebase Start event in the log
+fn find_rebase_start_event(repository: &Repository) -> Option<RebaseStartEvent> {
     let events = repository.storage.read_rewrite_events().ok()?;
 
     // Find the most recent Start event (events are newest-first)
     for event in events {
         match event {
             RewriteLogEvent::RebaseStart { rebase_start } => {
-                return Some(rebase_start.original_head.clone());
+                return Some(rebase_start);
             }
             _ => continue,
         }
@@ -184,6 +220,8 @@ fn find_rebase_start_event_original_head(repository: &Repository) -> Option<Stri
 fn process_completed_rebase(
     repository: &mut Repository,
     original_head: &str,
+    upstream: Option<&str>,
+    onto: Option<&str>,
     parsed_args: &ParsedGitInvocation,
 ) {
     debug_log(&format!(
@@ -221,7 +259,7 @@ fn process_completed_rebase(
         original_head, new_head
     ));
     let (original_commits, new_commits) =
-        match build_rebase_commit_mappings(repository, original_head, &new_head) {
+        match build_rebase_commit_mappings(repository, original_head, &new_head, upstream, onto) {
             Ok(mappings) => {
                 debug_log(&format!(
                     "✓ Built mappings: {} original commits -> {} new commits",
@@ -263,6 +301,8 @@ fn process_completed_rebase(
             is_interactive,
             original_commits.clone(),
             new_commits.clone(),
+            upstream.map(str::to_string),
+            onto.map(str::to_string),
         ));
 
     debug_log("Creating RebaseComplete event and rewriting authorship...");
@@ -278,24 +318,98 @@ fn process_completed_rebase(
     debug_log("✓ Rebase authorship rewrite complete");
 }
 
+/// Resolves the `<upstream>` and onto commits of a rebase of `original_head` from its args. Both
+/// are `None` when git picks the upstream itself (from the branch's configured upstream)
+fn resolve_rebase_range(
+    repository: &Repository,
+    rebase_args: &RebaseArgs,
+    original_head: &str,
+) -> (Option<String>, Option<String>) {
+    if rebase_args.upstream.is_none() && !rebase_args.root {
+        return (None, None);
+    }
+    let upstream = match &rebase_args.upstream {
+        Some(upstream) => match resolve_commit(repository, upstream) {
+            Some(upstream) => Some(upstream),
+            None => return (None, None),
+        },
+        None => None,
+    };
+    let onto = if let Some(onto) = &rebase_args.onto {
+        resolve_commit(repository, onto)
+    } else if rebase_args.keep_base {
+        upstream.as_ref().and_then(|upstream| {
+            repository
+                .merge_base(upstream.clone(), original_head.to_string())
+                .ok()
+        })
+    } else {
+        upstream.clone()
+    };
+    (upstream, onto)
+}
+
+fn resolve_commit(repository: &Repository, spec: &str) -> Option<String> {
+    repository
+        .revparse_single(spec)
+        .and_then(|object| object.peel_to_commit())
+        .map(|commit| commit.id())
+        .ok()
+}
+
+/// Like `walk_commits_to_base`, but walks down to the root commit when there's no base
+fn walk_commits_to(
+    repository: &Repository,
+    head: &str,
+    base: Option<&str>,
+) -> Result<Vec<String>, crate::error::GitAiError> {
+    if let Some(base) = base {
+        return walk_commits_to_base(repository, head, base);
+    }
+    let mut commits = Vec::new();
+    let mut current = repository.find_commit(head.to_string())?;
+    loop {
+        commits.push(current.id());
+        if current.parent_count()? == 0 {
+            return Ok(commits);
+        }
+        current = current.parent(0)?;
+    }
+}
+
 fn build_rebase_commit_mappings(
     repository: &Repository,
     original_head: &str,
     new_head: &str,
+    upstream: Option<&str>,
+    onto: Option<&str>,
 ) -> Result<(Vec<String>, Vec<String>), crate::error::GitAiError> {
-    // Get commits from new_head and original_head
-    let new_head_commit = repository.find_commit(new_head.to_string())?;
-    let original_head_commit = repository.find_commit(original_head.to_string())?;
-
-    // Find merge base between original and new
-    let merge_base = repository.merge_base(original_head_commit.id(), new_head_commit.id())?;
+    let (original_base, new_base) = match onto {
+        // The rebase replayed the commits not reachable from upstream (all of them with
+        // `--root`) onto `onto`
+        Some(onto) => {
+            let original_base = match upstream {
+                Some(upstream) => {
+                    Some(repository.merge_base(upstream.to_string(), original_head.to_string())?)
+                }
+                None => None,
+            };
+            (original_base, Some(onto.to_string()))
+        }
+        // Without the range, use the merge base of the original and new heads for both
+        None => {
+            let merge_base =
+                repository.merge_base(original_head.to_string(), new_head.to_string())?;
+            (Some(merge_base.clone()), Some(merge_base))
+        }
+    };
 
-    // Walk from original_head to merge_base to get the commits that were rebased
-    let original_commits = walk_commits_to_base(repository, original_head, &merge_base)?;
+    // Walk from original_head to its base to get the commits that were rebased
+    let original_commits = walk_commits_to(repository, original_head, original_base.as_deref())?;
 
-    // Walk from new_head to merge_base to get the actual rebased commits
+    // Walk from new_head to its base to get the actual rebased commits
     // This correctly handles squashing, dropping, and other interactive rebase operations
-    let new_commits = walk_commits_to_base(repository, new_head, &merge_base)?;
+    let new_commits = walk_commits_to(repository, new_head, new_base.as_deref())?;
 
     // Reverse both so they're in chronological order (oldest first)
     let mut original_commits = original_commits;
@@ -304,10 +418,11 @@ fn build_rebase_commit_mappings(
     new_commits.reverse();
 
     debug_log(&format!(
-        "Commit mapping: {} original -> {} new (merge_base: {})",
+        "Commit mapping: {} original -> {} new (original base: {:?}, new base: {:?})",
         original_commits.len(),
         new_commits.len(),
-        merge_base
+        original_base,
+        new_base
     ));
 
     // Always pass all commits through - let the authorship rewriting logic
@@ -0,0 +1,221 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use crate::{
+    authorship::{
+        attribution_tracker::{LineAttribution, line_attributions_to_attributions},
+        file_text,
+        virtual_attribution::VirtualAttributions,
+    },
+    commands::hooks::abort_hooks,
+    error::GitAiError,
+    git::{
+        cli_parser::ParsedGitInvocation, repo_path, repo_storage::RerereResolution,
+        repository::Repository, status::EntryKind,
+    },
+    utils::debug_log,
+};
+
+/// Commands that can stop on conflicts, which rerere may resolve from a recorded resolution
+fn can_stop_on_conflicts(parsed_args: &ParsedGitInvocation) -> bool {
+    matches!(
+        parsed_args.command.as_deref(),
+        Some("merge" | "rebase" | "cherry-pick" | "revert" | "pull")
+    ) && !abort_hooks::is_abort(parsed_args)
+}
+
+/// Commands that conclude a conflicted merge step, which is when rerere records its resolution
+fn concludes_conflicts(parsed_args: &ParsedGitInvocation) -> bool {
+    parsed_args.command.as_deref() == Some("commit")
+        || (can_stop_on_conflicts(parsed_args) && parsed_args.has_command_flag("--continue"))
+}
+
+pub fn pre_rerere_hook(parsed_args: &ParsedGitInvocation, repository: &Repository) {
+    if !concludes_conflicts(parsed_args) {
+        return;
+    }
+    if let Err(e) = record_resolutions(repository) {
+        debug_log(&format!("Failed to record rerere resolutions: {}", e));
+    }
+}
+
+pub fn post_rerere_hook(parsed_args: &ParsedGitInvocation, repository: &Repository) {
+    if !can_stop_on_conflicts(parsed_args) {
+        return;
+    }
+    if let Err(e) = reuse_replayed_resolutions(repository) {
+        debug_log(&format!("Failed to reuse rerere resolutions: {}", e));
+    }
+}
+
+/// The conflicts rerere is tracking in the current merge step, as (rr-cache id, file) pairs from
+/// MERGE_RR. Empty when rerere isn't enabled or there are no conflicts.
+fn merge_rr_entries(repository: &Repository) -> Vec<(String, String)> {
+    let Ok(content) = std::fs::read_to_string(repository.path().join("MERGE_RR")) else {
+        return Vec::new();
+    };
+    content
+        .split('\0')
+        .filter_map(|entry| entry.split_once('\t'))
+        .map(|(id, file)| (id.to_string(), file.to_string()))
+        .collect()
+}
+
+/// When rerere last recorded or replayed its resolution of the conflict `id` (`<hash>` or
+/// `<hash>.<variant>`), as replaying a resolution touches its postimage. None once rerere has
+/// forgotten the resolution.
+fn postimage_modified(repository: &Repository, id: &str) -> Option<SystemTime> {
+    let (hash, postimage) = match id.split_once('.') {
+        Some((hash, variant)) => (hash, format!("postimage.{}", variant)),
+        None => (id, "postimage".to_string()),
+    };
+    std::fs::metadata(
+        repository
+            .path()
+            .join("rr-cache")
+            .join(hash)
+            .join(postimage),
+    )
+    .and_then(|metadata| metadata.modified())
+    .ok()
+}
+
+/// The working copy of `file`, unless it's missing or still has conflict markers
+fn resolved_content(repository: &Repository, file: &str) -> Option<String> {
+    let bytes = std::fs::read(
+        repository
+            .workdir()
+            .ok()?
+            .join(repo_path::key_to_path(file)),
+    )
+    .ok()?;
+    let content = file_text::decode(&bytes);
+    let conflicted = content
+        .lines()
+        .any(|line| line.starts_with("<<<<<<<") || line.starts_with(">>>>>>>"));
+    (!conflicted).then_some(content)
+}
+
+/// Records the attribution of each resolved conflict rerere is about to record, from HEAD's
+/// working log
+fn record_resolutions(repository: &Repository) -> Result<(), GitAiError> {
+    let entries = merge_rr_entries(repository);
+    if entries.is_empty() {
+        return Ok(());
+    }
+    let head = repository.head()?.target()?;
+    let working_va = VirtualAttributions::from_just_working_log(repository.clone(), head, None)?;
+
+    for (id, file) in entries {
+        let Some(content) = resolved_content(repository, &file) else {
+            continue;
+        };
+        let line_attributions = working_va.line_attributions_for_content(&file, &content);
+        let prompts = line_attributions
+            .iter()
+            .filter_map(|line_attr| {
+                let record = working_va
+                    .prompts()
+                    .get(&line_attr.author_id)?
+                    .values()
+                    .next()?;
+                Some((line_attr.author_id.clone(), record.clone()))
+            })
+            .collect();
+        repository.storage.write_rerere_resolution(
+            &id,
+            &RerereResolution {
+                file: file.clone(),
+                content,
+                line_attributions,
+                prompts,
+            },
+        )?;
+        debug_log(&format!(
+            "Recorded attribution of resolution {} of {}",
+            id, file
+        ));
+    }
+    Ok(())
+}
+
+/// Seeds HEAD's working log with the attribution of the resolutions rerere replayed, so they're
+/// committed as whoever wrote them rather than as new work
+fn reuse_replayed_resolutions(repository: &Repository) -> Result<(), GitAiError> {
+    // rerere drops the conflicts it resolves from MERGE_RR, but leaves them unmerged in the index
+    // with no conflict markers left
+    let replayed_files: Vec<(String, String)> = repository
+        .status(None, true)?
+        .into_iter()
+        .filter(|entry| entry.kind == EntryKind::Unmerged)
+        .filter_map(|entry| {
+            let content = resolved_content(repository, &entry.path)?;
+            Some((entry.path, content))
+        })
+        .collect();
+    if replayed_files.is_empty() {
+        return Ok(());
+    }
+    let head = repository.head()?.target()?;
+    let working_log = repository.storage.working_log_for_base_commit(&head);
+    let mut initial = working_log.read_initial_attributions();
+    let mut reused = false;
+
+    for (file, content) in replayed_files {
+        // The resolution rerere just replayed is the one it touched last
+        let Some((_, id, resolution)) = repository
+            .storage
+            .rerere_resolutions_for_file(&file)
+            .into_iter()
+            .filter_map(|(id, resolution)| {
+                Some((postimage_modified(repository, &id)?, id, resolution))
+            })
+            .max_by_key(|(modified, _, _)| *modified)
+        else {
+            continue;
+        };
+        let line_attributions =
+            replayed_line_attributions(repository, &head, &resolution, &content);
+        if line_attributions.is_empty() {
+            continue;
+        }
+        debug_log(&format!(
+            "Reusing attribution of resolution {} of {}",
+            id, file
+        ));
+        initial.files.insert(file, line_attributions);
+        initial.prompts.extend(resolution.prompts);
+        reused = true;
+    }
+
+    if reused {
+        working_log.write_initial_attributions(initial.files, initial.prompts)?;
+    }
+    Ok(())
+}
+
+/// The recorded resolution's attribution, for the lines `content` has unchanged from it
+fn replayed_line_attributions(
+    repository: &Repository,
+    head: &str,
+    resolution: &RerereResolution,
+    content: &str,
+) -> Vec<LineAttribution> {
+    let char_attrs =
+        line_attributions_to_attributions(&resolution.line_attributions, &resolution.content, 0);
+    let resolution_va = VirtualAttributions::new(
+        repository.clone(),
+        head.to_string(),
+        HashMap::from([(
+            resolution.file.clone(),
+            (char_attrs, resolution.line_attributions.clone()),
+        )]),
+        HashMap::from([(
+            resolution.file.clone(),
+            Arc::from(resolution.content.as_str()),
+        )]),
+        0,
+    );
+    resolution_va.line_attributions_for_content(&resolution.file, content)
+}
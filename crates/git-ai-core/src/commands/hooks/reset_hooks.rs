@@ -1,7 +1,11 @@
 use crate::{
     authorship::working_log::CheckpointKind,
     commands::hooks::commit_hooks,
-    git::{cli_parser::ParsedGitInvocation, repository::Repository, rewrite_log::ResetKind},
+    git::{
+        cli_parser::{ParsedGitInvocation, invocation_dir},
+        repository::Repository,
+        rewrite_log::ResetKind,
+    },
     utils::debug_log,
 };
 
@@ -20,6 +24,7 @@ pub fn pre_reset_hook(parsed_args: &ParsedGitInvocation, repository: &mut Reposi
         true,
         None,
         true,
+        false,
     );
 
     // Capture HEAD before reset happens
@@ -39,11 +44,15 @@ pub fn post_reset_hook(
     // Extract tree-ish (what we're resetting TO)
     let tree_ish = extract_tree_ish(parsed_args);
 
-    // Extract pathspecs
-    let pathspecs = extract_pathspecs(parsed_args).unwrap_or_else(|e| {
-        debug_log(&format!("Failed to extract pathspecs: {}", e));
-        Vec::new()
-    });
+    // Extract pathspecs, as paths from the work tree root (git reads them relative to -C)
+    let pathspecs: Vec<String> = extract_pathspecs(parsed_args)
+        .unwrap_or_else(|e| {
+            debug_log(&format!("Failed to extract pathspecs: {}", e));
+            Vec::new()
+        })
+        .iter()
+        .map(|pathspec| repository.to_repo_path(pathspec))
+        .collect();
 
     debug_log(&format!(
         "Reset: tree-ish='{}', pathspecs={:?}",
@@ -409,6 +418,15 @@ fn extract_tree_ish(parsed_args: &ParsedGitInvocation) -> String {
 fn extract_pathspecs(parsed_args: &ParsedGitInvocation) -> Result<Vec<String>, std::io::Error> {
     // Check for --pathspec-from-file flag
     if let Some(file_path) = get_pathspec_from_file_path(parsed_args) {
+        // Like pathspecs, a relative file is relative to the directory git runs in
+        let file_path = if file_path == "-" {
+            file_path
+        } else {
+            invocation_dir(&parsed_args.global_args)
+                .join(file_path)
+                .to_string_lossy()
+                .to_string()
+        };
         return read_pathspecs_from_file(&file_path, is_pathspec_nul(parsed_args));
     }
 
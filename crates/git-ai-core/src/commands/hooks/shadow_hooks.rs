@@ -0,0 +1,266 @@
+//! Shadow mode (`GIT_AI_MODE=shadow`): git runs exactly as it would without git-ai, while git-ai
+//! runs its real checkpoint and commit pipeline on scratch state under `.git/ai/shadow`. Notes it
+//! would have written, and the checkpoints it recorded, are appended to `.git/ai/shadow_log`
+//! instead. Nothing under refs/, refs/notes/ or the real working logs is touched.
+
+use crate::authorship::post_commit::post_commit;
+use crate::authorship::rebase_authorship::rewrite_authorship_after_commit_amend;
+use crate::authorship::working_log::Checkpoint;
+use crate::commands::hooks::{add_hooks, commit_hooks, push_hooks};
+use crate::error::GitAiError;
+use crate::git::cli_parser::{ParsedGitInvocation, is_dry_run};
+use crate::git::repository::Repository;
+use crate::git::sync_authorship::fetch_remote_from_args;
+use crate::utils::debug_log;
+use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The git command being shadowed; unset for `git-ai checkpoint`
+static COMMAND: OnceLock<String> = OnceLock::new();
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShadowEntry {
+    pub timestamp: u64,
+    /// The git command, or `checkpoint` for an editor or agent hook
+    pub command: String,
+    /// `checkpoint`, `note`, `rewrite`, `push_notes` or `fetch_notes`
+    pub action: String,
+    /// The agent a checkpoint would have been attributed to; none for a human checkpoint
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent: Option<String>,
+    /// The paths a checkpoint would have covered
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub paths: Vec<String>,
+    /// The checkpoint that would have been appended to the working log
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checkpoint: Option<Checkpoint>,
+    /// The commit a note would have been written for, or that a rewrite ended at
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    /// The notes ref a note would have been written under
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes_ref: Option<String>,
+    /// The note that would have been written
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    /// HEAD before the command
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub old_head: Option<String>,
+    /// The remote notes would have been pushed to or fetched from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote: Option<String>,
+}
+
+impl ShadowEntry {
+    pub fn new(action: &str) -> Self {
+        ShadowEntry {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_secs())
+                .unwrap_or(0),
+            command: COMMAND
+                .get()
+                .cloned()
+                .unwrap_or_else(|| "checkpoint".to_string()),
+            action: action.to_string(),
+            agent: None,
+            paths: Vec::new(),
+            checkpoint: None,
+            commit: None,
+            notes_ref: None,
+            note: None,
+            old_head: None,
+            remote: None,
+        }
+    }
+}
+
+/// What a shadowed command's post-command step needs from before git ran
+pub struct ShadowContext {
+    head: Option<String>,
+}
+
+pub fn record(repository: &Repository, entry: &ShadowEntry) -> Result<(), GitAiError> {
+    let mut line = serde_json::to_string(entry)?;
+    line.push('\n');
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&repository.storage.shadow_log)?;
+    file.write_all(line.as_bytes())?;
+    Ok(())
+}
+
+fn record_or_log(repository: &Repository, entry: &ShadowEntry) {
+    if let Err(e) = record(repository, entry) {
+        debug_log(&format!("failed to write shadow log entry: {}", e));
+    }
+}
+
+/// Logs a checkpoint the pipeline appended to the shadow working log
+pub fn record_checkpoint(
+    repository: &Repository,
+    checkpoint: &Checkpoint,
+) -> Result<(), GitAiError> {
+    let mut entry = ShadowEntry::new("checkpoint");
+    entry.agent = checkpoint
+        .agent_id
+        .as_ref()
+        .map(|agent_id| agent_id.tool.clone());
+    entry.paths = checkpoint
+        .entries
+        .iter()
+        .map(|file| file.file.clone())
+        .collect();
+    entry.checkpoint = Some(checkpoint.clone());
+    record(repository, &entry)
+}
+
+/// Logs a note in place of writing it. `notes_add_to_ref` calls this in shadow mode.
+pub fn record_note(
+    repository: &Repository,
+    notes_ref: &str,
+    commit_sha: &str,
+    note: &str,
+) -> Result<(), GitAiError> {
+    let mut entry = ShadowEntry::new("note");
+    entry.commit = Some(commit_sha.to_string());
+    entry.notes_ref = Some(short_notes_ref(notes_ref).to_string());
+    entry.note = Some(note.to_string());
+    record(repository, &entry)
+}
+
+/// The last note logged for `commit_sha` under `notes_ref`, if any
+pub fn logged_note(repository: &Repository, notes_ref: &str, commit_sha: &str) -> Option<String> {
+    let log = std::fs::read_to_string(&repository.storage.shadow_log).ok()?;
+    let notes_ref = short_notes_ref(notes_ref);
+    log.lines()
+        .rev()
+        .filter_map(|line| serde_json::from_str::<ShadowEntry>(line).ok())
+        .find(|entry| {
+            entry.commit.as_deref() == Some(commit_sha)
+                && entry.notes_ref.as_deref() == Some(notes_ref)
+        })
+        .and_then(|entry| entry.note)
+}
+
+/// `ai` for both `ai` and `refs/notes/ai`, as git reads `--ref`
+fn short_notes_ref(notes_ref: &str) -> &str {
+    notes_ref.strip_prefix("refs/notes/").unwrap_or(notes_ref)
+}
+
+fn head_sha(repository: &Repository) -> Option<String> {
+    repository.head().ok().and_then(|head| head.target().ok())
+}
+
+fn is_ancestor(repository: &Repository, ancestor: &str, descendant: &str) -> bool {
+    repository
+        .git(&["merge-base", "--is-ancestor", ancestor, descendant])
+        .is_ok()
+}
+
+fn is_merge_commit(repository: &Repository, sha: &str) -> bool {
+    repository
+        .git(&["rev-parse", "--verify", "--quiet", &format!("{}^2", sha)])
+        .is_ok()
+}
+
+pub fn shadow_pre_command_hook(
+    parsed_args: &ParsedGitInvocation,
+    repository: &mut Repository,
+) -> ShadowContext {
+    let command = parsed_args.command.as_deref().unwrap_or_default();
+    let _ = COMMAND.set(command.to_string());
+    match command {
+        // The real hook, checkpointing into the shadow working log
+        "add" => add_hooks::pre_add_hook(parsed_args, repository),
+        "push"
+            if !is_dry_run(&parsed_args.command_args)
+                && !parsed_args
+                    .command_args
+                    .iter()
+                    .any(|arg| arg == "-d" || arg == "--delete" || arg == "--mirror") =>
+        {
+            if let Some(remote) = push_hooks::push_remote(parsed_args, repository) {
+                let mut entry = ShadowEntry::new("push_notes");
+                entry.remote = Some(remote);
+                record_or_log(repository, &entry);
+            }
+        }
+        "fetch" | "pull" if !is_dry_run(&parsed_args.command_args) => {
+            if let Ok(remote) = fetch_remote_from_args(repository, parsed_args) {
+                let mut entry = ShadowEntry::new("fetch_notes");
+                entry.remote = Some(remote);
+                record_or_log(repository, &entry);
+            }
+        }
+        _ => {}
+    }
+
+    ShadowContext {
+        head: head_sha(repository),
+    }
+}
+
+pub fn shadow_post_command_hook(
+    context: ShadowContext,
+    parsed_args: &ParsedGitInvocation,
+    exit_status: std::process::ExitStatus,
+    repository: &mut Repository,
+) {
+    let command = parsed_args.command.as_deref().unwrap_or_default();
+    if command == "add" {
+        add_hooks::post_add_hook(parsed_args, repository, exit_status);
+        return;
+    }
+    if !matches!(
+        command,
+        "commit" | "merge" | "cherry-pick" | "rebase" | "reset" | "pull"
+    ) {
+        return;
+    }
+    // A rebase stopped on a conflict has still moved HEAD, so a failed command can have
+    // rewritten history too
+    let Some(new_head) = head_sha(repository) else {
+        return;
+    };
+    if context.head.as_deref() == Some(new_head.as_str()) {
+        return;
+    }
+
+    // Commits and amends go through the real pipeline, which logs the note it would write
+    if command == "commit" && exit_status.success() {
+        let author = commit_hooks::get_commit_default_author(repository, &parsed_args.command_args);
+        let result = match context.head.as_deref() {
+            Some(old_head) if parsed_args.has_command_flag("--amend") => {
+                rewrite_authorship_after_commit_amend(repository, old_head, &new_head, author)
+                    .map(|_| ())
+            }
+            _ => post_commit(repository, context.head.clone(), new_head, author, true).map(|_| ()),
+        };
+        if let Err(e) = result {
+            debug_log(&format!("shadow post-commit failed: {}", e));
+        }
+        return;
+    }
+
+    let rewritten = match context.head.as_deref() {
+        Some(old_head) => !is_ancestor(repository, old_head, &new_head),
+        None => false,
+    };
+    let action = match command {
+        "cherry-pick" if exit_status.success() => "note",
+        "merge" | "pull" if is_merge_commit(repository, &new_head) => "note",
+        "reset" => "rewrite",
+        "rebase" | "pull" if rewritten => "rewrite",
+        _ => return,
+    };
+
+    let mut entry = ShadowEntry::new(action);
+    entry.commit = Some(new_head);
+    entry.old_head = context.head;
+    record_or_log(repository, &entry);
+}
@@ -0,0 +1,7 @@
+//! The parts of git-ai's commands the attribution pipeline is made of: checkpoints, the agent
+//! presets that feed them, blame, and the hooks run around proxied git commands.
+
+pub mod blame;
+pub mod checkpoint;
+pub mod checkpoint_agent;
+pub mod hooks;
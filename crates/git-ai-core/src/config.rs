@@ -0,0 +1,929 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use glob::Pattern;
+use serde::Deserialize;
+
+use crate::authorship::attribution_gaps::UnattributedChanges;
+use crate::authorship::attribution_tracker::{MoveTimestampPolicy, OverridePolicy};
+use crate::authorship::paste_detection::PasteHeuristic;
+use crate::error::GitAiError;
+use crate::git::lfs::LfsMode;
+use crate::git::repository::Repository;
+
+/// Centralized configuration for the application
+pub struct Config {
+    git_path: String,
+    ignore_prompts: bool,
+    allow_repositories: Vec<Pattern>,
+    exclude_repositories: Vec<Pattern>,
+    telemetry_oss_disabled: bool,
+    telemetry_enterprise_dsn: Option<String>,
+    usage_telemetry: bool,
+    usage_telemetry_endpoint: Option<String>,
+    disable_version_checks: bool,
+    disable_auto_updates: bool,
+    update_channel: UpdateChannel,
+    update_check_interval: Duration,
+    offline: bool,
+    move_timestamp_policy: MoveTimestampPolicy,
+    override_policy: OverridePolicy,
+    plugins_dir: Option<PathBuf>,
+    detect_plugins: Vec<String>,
+    git_timeout: Option<Duration>,
+    git_lock_retries: u32,
+    annotate_diffs: bool,
+    commit_trailers: bool,
+    checkpoint_on_add: bool,
+    summary_notes_ref: Option<String>,
+    shadow_mode: bool,
+    agent_processes: Vec<String>,
+    paste_heuristic: Option<PasteHeuristic>,
+    attribution_gap_warnings: bool,
+    lfs_mode: LfsMode,
+    unattributed_changes: UnattributedChanges,
+    author_classes: Vec<String>,
+    working_log_user: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UpdateChannel {
+    Latest,
+    Next,
+}
+
+impl UpdateChannel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UpdateChannel::Latest => "latest",
+            UpdateChannel::Next => "next",
+        }
+    }
+
+    fn from_str(input: &str) -> Option<Self> {
+        match input.trim().to_lowercase().as_str() {
+            "latest" | "stable" => Some(UpdateChannel::Latest),
+            "next" | "beta" => Some(UpdateChannel::Next),
+            _ => None,
+        }
+    }
+}
+
+impl Default for UpdateChannel {
+    fn default() -> Self {
+        UpdateChannel::Latest
+    }
+}
+#[derive(Deserialize)]
+struct FileConfig {
+    #[serde(default)]
+    git_path: Option<String>,
+    #[serde(default)]
+    ignore_prompts: Option<bool>,
+    #[serde(default)]
+    allow_repositories: Option<Vec<String>>,
+    #[serde(default)]
+    exclude_repositories: Option<Vec<String>>,
+    #[serde(default)]
+    telemetry_oss: Option<String>,
+    #[serde(default)]
+    telemetry_enterprise_dsn: Option<String>,
+    #[serde(default)]
+    usage_telemetry: Option<bool>,
+    #[serde(default)]
+    usage_telemetry_endpoint: Option<String>,
+    #[serde(default)]
+    disable_version_checks: Option<bool>,
+    #[serde(default)]
+    disable_auto_updates: Option<bool>,
+    #[serde(default)]
+    update_channel: Option<String>,
+    #[serde(default)]
+    update_check_interval_hours: Option<u64>,
+    #[serde(default)]
+    offline: Option<bool>,
+    #[serde(default)]
+    move_timestamp_policy: Option<String>,
+    #[serde(default)]
+    override_policy: Option<String>,
+    #[serde(default)]
+    override_threshold: Option<u8>,
+    #[serde(default)]
+    plugins_dir: Option<String>,
+    #[serde(default)]
+    detect_plugins: Option<Vec<String>>,
+    #[serde(default)]
+    git_timeout_secs: Option<u64>,
+    #[serde(default)]
+    git_lock_retries: Option<u32>,
+    #[serde(default)]
+    annotate_diffs: Option<bool>,
+    #[serde(default)]
+    commit_trailers: Option<bool>,
+    #[serde(default)]
+    checkpoint_on_add: Option<bool>,
+    #[serde(default)]
+    summary_notes: Option<bool>,
+    #[serde(default)]
+    summary_notes_ref: Option<String>,
+    #[serde(default)]
+    mode: Option<String>,
+    #[serde(default)]
+    agent_processes: Option<Vec<String>>,
+    #[serde(default)]
+    paste_detection: Option<bool>,
+    #[serde(default)]
+    paste_min_lines: Option<u32>,
+    #[serde(default)]
+    paste_window_secs: Option<u64>,
+    #[serde(default)]
+    attribution_gap_warnings: Option<bool>,
+    #[serde(default)]
+    lfs_files: Option<String>,
+    #[serde(default)]
+    unattributed_changes: Option<String>,
+    #[serde(default)]
+    author_classes: Option<Vec<String>>,
+}
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+impl Config {
+    /// Initialize the global configuration exactly once.
+    /// Safe to call multiple times; subsequent calls are no-ops.
+    #[allow(dead_code)]
+    pub fn init() {
+        let _ = CONFIG.get_or_init(|| build_config());
+    }
+
+    /// Access the global configuration. Lazily initializes if not already initialized.
+    pub fn get() -> &'static Config {
+        CONFIG.get_or_init(|| build_config())
+    }
+
+    /// Returns the command to invoke git.
+    pub fn git_cmd(&self) -> &str {
+        &self.git_path
+    }
+
+    #[allow(dead_code)]
+    pub fn get_ignore_prompts(&self) -> bool {
+        self.ignore_prompts
+    }
+
+    pub fn is_allowed_repository(&self, repository: &Option<Repository>) -> bool {
+        // First check if repository is in exclusion list - exclusions take precedence
+        if !self.exclude_repositories.is_empty()
+            && let Some(repository) = repository
+        {
+            if let Some(remotes) = repository.remotes_with_urls().ok() {
+                // If any remote matches the exclusion patterns, deny access
+                if remotes.iter().any(|remote| {
+                    self.exclude_repositories
+                        .iter()
+                        .any(|pattern| pattern.matches(&remote.1))
+                }) {
+                    return false;
+                }
+            }
+        }
+
+        // If allowlist is empty, allow everything (unless excluded above)
+        if self.allow_repositories.is_empty() {
+            return true;
+        }
+
+        // If allowlist is defined, only allow repos whose remotes match the patterns
+        if let Some(repository) = repository {
+            match repository.remotes_with_urls().ok() {
+                Some(remotes) => remotes.iter().any(|remote| {
+                    self.allow_repositories
+                        .iter()
+                        .any(|pattern| pattern.matches(&remote.1))
+                }),
+                None => false, // Can't verify, deny by default when allowlist is active
+            }
+        } else {
+            false // No repository provided, deny by default when allowlist is active
+        }
+    }
+
+    /// Returns whether prompts should be ignored (currently unused by internal APIs).
+    #[allow(dead_code)]
+    pub fn ignore_prompts(&self) -> bool {
+        self.ignore_prompts
+    }
+
+    /// Returns true if OSS telemetry is disabled.
+    pub fn is_telemetry_oss_disabled(&self) -> bool {
+        self.telemetry_oss_disabled
+    }
+
+    /// Returns the telemetry_enterprise_dsn if set.
+    pub fn telemetry_enterprise_dsn(&self) -> Option<&str> {
+        self.telemetry_enterprise_dsn.as_deref()
+    }
+
+    /// Returns true if the user opted in to anonymous usage counters (off unless set).
+    pub fn usage_telemetry_enabled(&self) -> bool {
+        self.usage_telemetry
+    }
+
+    /// Returns where `git-ai telemetry send` posts usage counters, if overridden.
+    pub fn usage_telemetry_endpoint(&self) -> Option<&str> {
+        self.usage_telemetry_endpoint.as_deref()
+    }
+
+    pub fn version_checks_disabled(&self) -> bool {
+        self.disable_version_checks
+    }
+
+    pub fn auto_updates_disabled(&self) -> bool {
+        self.disable_auto_updates
+    }
+
+    pub fn update_channel(&self) -> UpdateChannel {
+        self.update_channel
+    }
+
+    /// Returns the minimum time between background update checks.
+    pub fn update_check_interval(&self) -> Duration {
+        self.update_check_interval
+    }
+
+    /// Returns true if git-ai must not make network requests of its own (update checks,
+    /// upgrades, telemetry). Set with `offline` in the config file or `GIT_AI_OFFLINE=1`.
+    pub fn is_offline(&self) -> bool {
+        self.offline
+    }
+
+    /// Returns the timestamp policy applied to text moved within a file.
+    pub fn move_timestamp_policy(&self) -> MoveTimestampPolicy {
+        self.move_timestamp_policy
+    }
+
+    /// Returns the policy for AI lines that a human later edits.
+    pub fn override_policy(&self) -> OverridePolicy {
+        self.override_policy
+    }
+
+    /// Returns the directory agent plugins are discovered from.
+    pub fn plugins_dir(&self) -> Option<&Path> {
+        self.plugins_dir.as_deref()
+    }
+
+    /// Returns the names of the agent plugins asked whether their agent is at work when a
+    /// checkpoint runs without a preset. Set with `detect_plugins` in the config file or
+    /// `GIT_AI_DETECT_PLUGINS` (comma-separated); empty, the default, asks none.
+    pub fn detect_plugins(&self) -> &[String] {
+        &self.detect_plugins
+    }
+
+    /// Returns how long git commands run by git-ai may take before being killed (None for no limit).
+    pub fn git_timeout(&self) -> Option<Duration> {
+        self.git_timeout
+    }
+
+    /// Returns how many times a git command blocked by another process's `.lock` file is retried.
+    pub fn git_lock_retries(&self) -> u32 {
+        self.git_lock_retries
+    }
+
+    /// Returns true if `git show` and `git log -p` in a terminal should show the attribution
+    /// of added lines. Set with `annotate_diffs` in the config file or `GIT_AI_ANNOTATE_DIFFS=1`.
+    pub fn annotate_diffs(&self) -> bool {
+        self.annotate_diffs
+    }
+
+    /// Returns true if commits should get `Co-authored-by:` and `Git-AI-Session:` trailers for
+    /// the agents that wrote them. Set with `commit_trailers` in the config file or
+    /// `GIT_AI_COMMIT_TRAILERS=1`.
+    pub fn commit_trailers(&self) -> bool {
+        self.commit_trailers
+    }
+
+    /// Returns true if `git add` should checkpoint the paths it stages as human edits first, for
+    /// users without editor or agent hooks. Set with `checkpoint_on_add` in the config file or
+    /// `GIT_AI_CHECKPOINT_ON_ADD=1`.
+    pub fn checkpoint_on_add(&self) -> bool {
+        self.checkpoint_on_add
+    }
+
+    /// Returns the notes ref that commits should also get a plain text attribution summary under,
+    /// or None if they shouldn't. Set with `summary_notes` in the config file or
+    /// `GIT_AI_SUMMARY_NOTES=1`; the ref is `summary_notes_ref`, by default `refs/notes/commits`.
+    pub fn summary_notes_ref(&self) -> Option<&str> {
+        self.summary_notes_ref.as_deref()
+    }
+
+    /// Returns true if git-ai should run git untouched and log the checkpoints and notes it would
+    /// have written instead of writing them. Set with `"mode": "shadow"` in the config file or
+    /// `GIT_AI_MODE=shadow`.
+    pub fn is_shadow_mode(&self) -> bool {
+        self.shadow_mode
+    }
+
+    /// Returns the process names of agents to look for among the processes running a commit
+    /// that no agent checkpointed. Set with `agent_processes` in the config file or
+    /// `GIT_AI_AGENT_PROCESSES` (comma-separated); empty turns the lookup off.
+    pub fn agent_processes(&self) -> &[String] {
+        &self.agent_processes
+    }
+
+    /// Returns what counts as a large paste in a human checkpoint, or None if pastes shouldn't
+    /// be flagged as possible AI. Set with `paste_detection` in the config file or
+    /// `GIT_AI_PASTE_DETECTION=1`, tuned with `paste_min_lines` and `paste_window_secs`.
+    pub fn paste_heuristic(&self) -> Option<PasteHeuristic> {
+        self.paste_heuristic
+    }
+
+    /// Returns true if a commit should warn when much of what it adds was never checkpointed.
+    /// On unless `attribution_gap_warnings` is false in the config file or
+    /// `GIT_AI_ATTRIBUTION_GAP_WARNINGS=0`.
+    pub fn attribution_gap_warnings(&self) -> bool {
+        self.attribution_gap_warnings
+    }
+
+    /// Returns what happens to files tracked with Git LFS (see `git::lfs`). Set with `lfs_files`
+    /// in the config file or `GIT_AI_LFS_FILES`, to `skip` (default) or `smudge`.
+    pub fn lfs_mode(&self) -> LfsMode {
+        self.lfs_mode
+    }
+
+    /// Returns what a commit does with lines no checkpoint recorded (see `attribution_gaps`).
+    /// Set with `unattributed_changes` in the config file or `GIT_AI_UNATTRIBUTED_CHANGES`, to
+    /// `human` (default), `warn`, `block` or `unknown`.
+    pub fn unattributed_changes(&self) -> UnattributedChanges {
+        self.unattributed_changes
+    }
+
+    /// Returns the author classes checkpoints can declare besides the built-in `codegen`,
+    /// `import` and `unknown` (see `author_class`). Set with `author_classes` in the config file
+    /// or `GIT_AI_AUTHOR_CLASSES` (comma-separated).
+    pub fn author_classes(&self) -> &[String] {
+        &self.author_classes
+    }
+
+    /// Returns the user whose working logs to use on a checkout several people commit from
+    /// under one OS account, set with `GIT_AI_USER` (see `RepoStorage`)
+    pub fn working_log_user(&self) -> Option<&str> {
+        self.working_log_user.as_deref()
+    }
+}
+
+fn build_config() -> Config {
+    let file_cfg = load_file_config();
+    let ignore_prompts = file_cfg
+        .as_ref()
+        .and_then(|c| c.ignore_prompts)
+        .unwrap_or(false);
+    let allow_repositories = file_cfg
+        .as_ref()
+        .and_then(|c| c.allow_repositories.clone())
+        .unwrap_or(vec![])
+        .into_iter()
+        .filter_map(|pattern_str| {
+            Pattern::new(&pattern_str)
+                .map_err(|e| {
+                    eprintln!(
+                        "Warning: Invalid glob pattern in allow_repositories '{}': {}",
+                        pattern_str, e
+                    );
+                })
+                .ok()
+        })
+        .collect();
+    let exclude_repositories = file_cfg
+        .as_ref()
+        .and_then(|c| c.exclude_repositories.clone())
+        .unwrap_or(vec![])
+        .into_iter()
+        .filter_map(|pattern_str| {
+            Pattern::new(&pattern_str)
+                .map_err(|e| {
+                    eprintln!(
+                        "Warning: Invalid glob pattern in exclude_repositories '{}': {}",
+                        pattern_str, e
+                    );
+                })
+                .ok()
+        })
+        .collect();
+    let telemetry_oss_disabled = file_cfg
+        .as_ref()
+        .and_then(|c| c.telemetry_oss.clone())
+        .filter(|s| s == "off")
+        .is_some();
+    let telemetry_enterprise_dsn = file_cfg
+        .as_ref()
+        .and_then(|c| c.telemetry_enterprise_dsn.clone())
+        .filter(|s| !s.is_empty());
+    let usage_telemetry = file_cfg
+        .as_ref()
+        .and_then(|c| c.usage_telemetry)
+        .unwrap_or(false);
+    let usage_telemetry_endpoint = file_cfg
+        .as_ref()
+        .and_then(|c| c.usage_telemetry_endpoint.clone())
+        .filter(|s| !s.is_empty());
+    
+    // Default to disabled (true) unless this is an OSS build
+    // OSS builds set OSS_BUILD env var at compile time to "1", which enables auto-updates by default
+    let auto_update_flags_default_disabled = option_env!("OSS_BUILD").is_none() || option_env!("OSS_BUILD").unwrap() != "1";
+    
+    let offline = env::var("GIT_AI_OFFLINE")
+        .ok()
+        .map(|value| parse_bool_env(&value))
+        .or_else(|| file_cfg.as_ref().and_then(|c| c.offline))
+        .unwrap_or(false);
+    // `update_channel: "none"` turns update checks off like `disable_version_checks`
+    let channel_none = file_cfg
+        .as_ref()
+        .and_then(|c| c.update_channel.as_deref())
+        .is_some_and(|channel| channel.trim().eq_ignore_ascii_case("none"));
+
+    let disable_version_checks = offline
+        || channel_none
+        || file_cfg
+            .as_ref()
+            .and_then(|c| c.disable_version_checks)
+            .unwrap_or(auto_update_flags_default_disabled);
+    let disable_auto_updates = offline
+        || channel_none
+        || file_cfg
+            .as_ref()
+            .and_then(|c| c.disable_auto_updates)
+            .unwrap_or(auto_update_flags_default_disabled);
+    let update_channel = file_cfg
+        .as_ref()
+        .and_then(|c| c.update_channel.as_deref())
+        .and_then(UpdateChannel::from_str)
+        .unwrap_or_default();
+    let update_check_interval = Duration::from_secs(
+        file_cfg
+            .as_ref()
+            .and_then(|c| c.update_check_interval_hours)
+            .unwrap_or(24)
+            * 3600,
+    );
+    let move_timestamp_policy = file_cfg
+        .as_ref()
+        .and_then(|c| c.move_timestamp_policy.as_deref())
+        .and_then(MoveTimestampPolicy::parse)
+        .unwrap_or_default();
+    let override_threshold = file_cfg
+        .as_ref()
+        .and_then(|c| c.override_threshold)
+        .unwrap_or(50);
+    let override_policy = file_cfg
+        .as_ref()
+        .and_then(|c| c.override_policy.as_deref())
+        .and_then(|policy| OverridePolicy::parse(policy, override_threshold))
+        .unwrap_or_default();
+
+    let plugins_dir = file_cfg
+        .as_ref()
+        .and_then(|c| c.plugins_dir.as_deref())
+        .map(str::trim)
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            config_file_path().and_then(|path| path.parent().map(|dir| dir.join("plugins")))
+        });
+
+    let detect_plugins = match env::var("GIT_AI_DETECT_PLUGINS") {
+        Ok(value) => value.split(',').map(str::to_string).collect(),
+        Err(_) => file_cfg
+            .as_ref()
+            .and_then(|c| c.detect_plugins.clone())
+            .unwrap_or_default(),
+    }
+    .into_iter()
+    .map(|name| name.trim().to_string())
+    .filter(|name| !name.is_empty())
+    .collect();
+
+    let git_timeout = match file_cfg.as_ref().and_then(|c| c.git_timeout_secs) {
+        Some(0) => None,
+        Some(secs) => Some(Duration::from_secs(secs)),
+        None => Some(Duration::from_secs(120)),
+    };
+    let git_lock_retries = file_cfg
+        .as_ref()
+        .and_then(|c| c.git_lock_retries)
+        .unwrap_or(5);
+    let annotate_diffs = env::var("GIT_AI_ANNOTATE_DIFFS")
+        .ok()
+        .map(|value| parse_bool_env(&value))
+        .or_else(|| file_cfg.as_ref().and_then(|c| c.annotate_diffs))
+        .unwrap_or(false);
+    let commit_trailers = env::var("GIT_AI_COMMIT_TRAILERS")
+        .ok()
+        .map(|value| parse_bool_env(&value))
+        .or_else(|| file_cfg.as_ref().and_then(|c| c.commit_trailers))
+        .unwrap_or(false);
+    let checkpoint_on_add = env::var("GIT_AI_CHECKPOINT_ON_ADD")
+        .ok()
+        .map(|value| parse_bool_env(&value))
+        .or_else(|| file_cfg.as_ref().and_then(|c| c.checkpoint_on_add))
+        .unwrap_or(false);
+    let summary_notes = env::var("GIT_AI_SUMMARY_NOTES")
+        .ok()
+        .map(|value| parse_bool_env(&value))
+        .or_else(|| file_cfg.as_ref().and_then(|c| c.summary_notes))
+        .unwrap_or(false);
+    let summary_notes_ref = summary_notes.then(|| {
+        file_cfg
+            .as_ref()
+            .and_then(|c| c.summary_notes_ref.as_deref())
+            .map(str::trim)
+            .filter(|notes_ref| !notes_ref.is_empty())
+            .unwrap_or("refs/notes/commits")
+            .to_string()
+    });
+    let shadow_mode = env::var("GIT_AI_MODE")
+        .ok()
+        .or_else(|| file_cfg.as_ref().and_then(|c| c.mode.clone()))
+        .is_some_and(|mode| mode.trim().eq_ignore_ascii_case("shadow"));
+
+    let agent_processes = match env::var("GIT_AI_AGENT_PROCESSES") {
+        Ok(value) => value.split(',').map(str::to_string).collect(),
+        Err(_) => file_cfg
+            .as_ref()
+            .and_then(|c| c.agent_processes.clone())
+            .unwrap_or_default(),
+    }
+    .into_iter()
+    .map(|name| name.trim().to_string())
+    .filter(|name| !name.is_empty())
+    .collect();
+
+    let paste_detection = env::var("GIT_AI_PASTE_DETECTION")
+        .ok()
+        .map(|value| parse_bool_env(&value))
+        .or_else(|| file_cfg.as_ref().and_then(|c| c.paste_detection))
+        .unwrap_or(false);
+    let paste_heuristic = paste_detection.then(|| {
+        let defaults = PasteHeuristic::default();
+        PasteHeuristic {
+            min_lines: file_cfg
+                .as_ref()
+                .and_then(|c| c.paste_min_lines)
+                .filter(|lines| *lines > 0)
+                .unwrap_or(defaults.min_lines),
+            window: file_cfg
+                .as_ref()
+                .and_then(|c| c.paste_window_secs)
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.window),
+        }
+    });
+    let attribution_gap_warnings = env::var("GIT_AI_ATTRIBUTION_GAP_WARNINGS")
+        .ok()
+        .map(|value| parse_bool_env(&value))
+        .or_else(|| file_cfg.as_ref().and_then(|c| c.attribution_gap_warnings))
+        .unwrap_or(true);
+    let unattributed_changes = env::var("GIT_AI_UNATTRIBUTED_CHANGES")
+        .ok()
+        .and_then(|value| UnattributedChanges::parse(&value))
+        .or_else(|| {
+            file_cfg
+                .as_ref()
+                .and_then(|c| c.unattributed_changes.as_deref())
+                .and_then(UnattributedChanges::parse)
+        })
+        .unwrap_or_default();
+    let author_classes = match env::var("GIT_AI_AUTHOR_CLASSES") {
+        Ok(value) => value.split(',').map(str::to_string).collect(),
+        Err(_) => file_cfg
+            .as_ref()
+            .and_then(|c| c.author_classes.clone())
+            .unwrap_or_default(),
+    }
+    .into_iter()
+    .map(|class| class.trim().to_lowercase())
+    .filter(|class| !class.is_empty())
+    .collect();
+    let lfs_mode = env::var("GIT_AI_LFS_FILES")
+        .ok()
+        .and_then(|value| LfsMode::parse(&value))
+        .or_else(|| {
+            file_cfg
+                .as_ref()
+                .and_then(|c| c.lfs_files.as_deref())
+                .and_then(LfsMode::parse)
+        })
+        .unwrap_or_default();
+    let working_log_user = env::var("GIT_AI_USER")
+        .ok()
+        .map(|user| user.trim().to_string())
+        .filter(|user| !user.is_empty());
+
+    let git_path = resolve_git_path(&file_cfg);
+
+    Config {
+        git_path,
+        ignore_prompts,
+        allow_repositories,
+        exclude_repositories,
+        telemetry_oss_disabled,
+        telemetry_enterprise_dsn,
+        usage_telemetry,
+        usage_telemetry_endpoint,
+        disable_version_checks,
+        disable_auto_updates,
+        update_channel,
+        update_check_interval,
+        offline,
+        move_timestamp_policy,
+        override_policy,
+        plugins_dir,
+        detect_plugins,
+        git_timeout,
+        git_lock_retries,
+        annotate_diffs,
+        commit_trailers,
+        checkpoint_on_add,
+        summary_notes_ref,
+        shadow_mode,
+        agent_processes,
+        paste_heuristic,
+        attribution_gap_warnings,
+        lfs_mode,
+        unattributed_changes,
+        author_classes,
+        working_log_user,
+    }
+}
+
+/// "1", "true", "yes" and "on" (any case) are true; anything else is false
+fn parse_bool_env(value: &str) -> bool {
+    matches!(
+        value.trim().to_lowercase().as_str(),
+        "1" | "true" | "yes" | "on"
+    )
+}
+
+fn resolve_git_path(file_cfg: &Option<FileConfig>) -> String {
+    // 1) From config file
+    if let Some(cfg) = file_cfg {
+        if let Some(path) = cfg.git_path.as_ref() {
+            let trimmed = path.trim();
+            if !trimmed.is_empty() {
+                let p = Path::new(trimmed);
+                if is_executable(p) {
+                    return trimmed.to_string();
+                }
+            }
+        }
+    }
+
+    // 2) Probe common locations across platforms
+    let candidates: &[&str] = &[
+        // macOS Homebrew (ARM and Intel)
+        "/opt/homebrew/bin/git",
+        "/usr/local/bin/git",
+        // Common Unix paths
+        "/usr/bin/git",
+        "/bin/git",
+        "/usr/local/sbin/git",
+        "/usr/sbin/git",
+        // Windows Git for Windows
+        r"C:\\Program Files\\Git\\bin\\git.exe",
+        r"C:\\Program Files (x86)\\Git\\bin\\git.exe",
+    ];
+
+    if let Some(found) = candidates.iter().map(Path::new).find(|p| is_executable(p)) {
+        return found.to_string_lossy().to_string();
+    }
+
+    // 3) Fatal error: no real git found
+    eprintln!(
+        "Fatal: Could not locate a real 'git' binary.\n\
+         Expected a valid 'git_path' in {cfg_path} or in standard locations.\n\
+         Please install Git or update your config JSON.",
+        cfg_path = config_file_path()
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_else(|| "~/.git-ai/config.json".to_string()),
+    );
+    std::process::exit(1);
+}
+
+fn load_file_config() -> Option<FileConfig> {
+    let path = config_file_path()?;
+    let data = fs::read(&path).ok()?;
+    serde_json::from_slice::<FileConfig>(&data).ok()
+}
+
+/// Why the config file is being ignored, if it exists but can't be read or parsed
+pub fn config_file_problem() -> Option<String> {
+    let path = config_file_path()?;
+    let data = match fs::read(&path) {
+        Ok(data) => data,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return None,
+        Err(e) => return Some(format!("{}: {}", path.display(), e)),
+    };
+    serde_json::from_slice::<FileConfig>(&data)
+        .err()
+        .map(|e| format!("{}: {}", path.display(), e))
+}
+
+/// Writes a starting config file pinning the real git, unless there already is one. For CI it
+/// also turns off update checks, since runners are rebuilt from images. Returns the path when
+/// it wrote one.
+pub fn write_default_config_file(ci: bool) -> Result<Option<PathBuf>, GitAiError> {
+    let path = config_file_path()
+        .ok_or_else(|| GitAiError::Generic("no home directory for the config file".to_string()))?;
+    if path.exists() {
+        return Ok(None);
+    }
+    let mut config = serde_json::json!({ "git_path": Config::get().git_cmd() });
+    if ci {
+        config["disable_version_checks"] = serde_json::json!(true);
+        config["disable_auto_updates"] = serde_json::json!(true);
+    }
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(
+        &path,
+        format!("{}\n", serde_json::to_string_pretty(&config)?),
+    )?;
+    Ok(Some(path))
+}
+
+pub fn config_file_path() -> Option<PathBuf> {
+    #[cfg(windows)]
+    {
+        let home = env::var("USERPROFILE").ok()?;
+        Some(Path::new(&home).join(".git-ai").join("config.json"))
+    }
+    #[cfg(not(windows))]
+    {
+        let home = env::var("HOME").ok()?;
+        Some(Path::new(&home).join(".git-ai").join("config.json"))
+    }
+}
+
+fn is_executable(path: &Path) -> bool {
+    if !path.exists() || !path.is_file() {
+        return false;
+    }
+    // Basic check: existence is sufficient for our purposes; OS will enforce exec perms.
+    // On Unix we could check permissions, but many filesystems differ. Keep it simple.
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_config(
+        allow_repositories: Vec<String>,
+        exclude_repositories: Vec<String>,
+    ) -> Config {
+        Config {
+            git_path: "/usr/bin/git".to_string(),
+            ignore_prompts: false,
+            allow_repositories: allow_repositories
+                .into_iter()
+                .filter_map(|s| Pattern::new(&s).ok())
+                .collect(),
+            exclude_repositories: exclude_repositories
+                .into_iter()
+                .filter_map(|s| Pattern::new(&s).ok())
+                .collect(),
+            telemetry_oss_disabled: false,
+            telemetry_enterprise_dsn: None,
+            usage_telemetry: false,
+            usage_telemetry_endpoint: None,
+            disable_version_checks: false,
+            disable_auto_updates: false,
+            update_channel: UpdateChannel::Latest,
+            update_check_interval: Duration::from_secs(24 * 3600),
+            offline: false,
+            move_timestamp_policy: MoveTimestampPolicy::PreserveOriginal,
+            override_policy: OverridePolicy::LatestEdit,
+            plugins_dir: None,
+            detect_plugins: Vec::new(),
+            git_timeout: Some(Duration::from_secs(120)),
+            git_lock_retries: 5,
+            annotate_diffs: false,
+            commit_trailers: false,
+            checkpoint_on_add: false,
+            summary_notes_ref: None,
+            shadow_mode: false,
+            agent_processes: Vec::new(),
+            paste_heuristic: None,
+            attribution_gap_warnings: false,
+            lfs_mode: LfsMode::Skip,
+            unattributed_changes: UnattributedChanges::Human,
+            author_classes: Vec::new(),
+            working_log_user: None,
+        }
+    }
+
+    #[test]
+    fn test_exclusion_takes_precedence_over_allow() {
+        let config = create_test_config(
+            vec!["https://github.com/allowed/repo".to_string()],
+            vec!["https://github.com/allowed/repo".to_string()],
+        );
+
+        // Test with None repository - should return false when allowlist is active
+        assert!(!config.is_allowed_repository(&None));
+    }
+
+    #[test]
+    fn test_empty_allowlist_allows_everything() {
+        let config = create_test_config(vec![], vec![]);
+
+        // With empty allowlist, should allow everything
+        assert!(config.is_allowed_repository(&None));
+    }
+
+    #[test]
+    fn test_exclude_without_allow() {
+        let config =
+            create_test_config(vec![], vec!["https://github.com/excluded/repo".to_string()]);
+
+        // With empty allowlist but exclusions, should allow everything (exclusions only matter when checking remotes)
+        assert!(config.is_allowed_repository(&None));
+    }
+
+    #[test]
+    fn test_allow_without_exclude() {
+        let config =
+            create_test_config(vec!["https://github.com/allowed/repo".to_string()], vec![]);
+
+        // With allowlist but no exclusions, should deny when no repository provided
+        assert!(!config.is_allowed_repository(&None));
+    }
+
+    #[test]
+    fn test_glob_pattern_wildcard_in_allow() {
+        let config = create_test_config(vec!["https://github.com/myorg/*".to_string()], vec![]);
+
+        // Test that the pattern would match (note: we can't easily test with real Repository objects,
+        // but the pattern compilation is tested by the fact that create_test_config succeeds)
+        assert!(!config.allow_repositories.is_empty());
+        assert!(config.allow_repositories[0].matches("https://github.com/myorg/repo1"));
+        assert!(config.allow_repositories[0].matches("https://github.com/myorg/repo2"));
+        assert!(!config.allow_repositories[0].matches("https://github.com/other/repo"));
+    }
+
+    #[test]
+    fn test_glob_pattern_wildcard_in_exclude() {
+        let config = create_test_config(vec![], vec!["https://github.com/private/*".to_string()]);
+
+        // Test pattern matching
+        assert!(!config.exclude_repositories.is_empty());
+        assert!(config.exclude_repositories[0].matches("https://github.com/private/repo1"));
+        assert!(config.exclude_repositories[0].matches("https://github.com/private/secret"));
+        assert!(!config.exclude_repositories[0].matches("https://github.com/public/repo"));
+    }
+
+    #[test]
+    fn test_exact_match_still_works() {
+        let config = create_test_config(vec!["https://github.com/exact/match".to_string()], vec![]);
+
+        // Test that exact matches still work (glob treats them as literals)
+        assert!(!config.allow_repositories.is_empty());
+        assert!(config.allow_repositories[0].matches("https://github.com/exact/match"));
+        assert!(!config.allow_repositories[0].matches("https://github.com/exact/other"));
+    }
+
+    #[test]
+    fn test_complex_glob_patterns() {
+        let config = create_test_config(vec!["*@github.com:company/*".to_string()], vec![]);
+
+        // Test more complex patterns with wildcards
+        assert!(!config.allow_repositories.is_empty());
+        assert!(config.allow_repositories[0].matches("git@github.com:company/repo"));
+        assert!(config.allow_repositories[0].matches("user@github.com:company/project"));
+        assert!(!config.allow_repositories[0].matches("git@github.com:other/repo"));
+    }
+
+    #[test]
+    fn test_update_channel_aliases() {
+        assert_eq!(
+            UpdateChannel::from_str("stable"),
+            Some(UpdateChannel::Latest)
+        );
+        assert_eq!(UpdateChannel::from_str(" Beta "), Some(UpdateChannel::Next));
+        assert_eq!(
+            UpdateChannel::from_str("latest"),
+            Some(UpdateChannel::Latest)
+        );
+        // "none" disables checks rather than naming a channel
+        assert_eq!(UpdateChannel::from_str("none"), None);
+
+        assert!(parse_bool_env("1"));
+        assert!(parse_bool_env("TRUE"));
+        assert!(!parse_bool_env("0"));
+        assert!(!parse_bool_env(""));
+    }
+}
@@ -0,0 +1,383 @@
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+#[derive(Debug)]
+pub enum GitAiError {
+    #[cfg(feature = "test-support")]
+    GitError(git2::Error),
+    IoError(std::io::Error),
+    /// Errors from invoking the git CLI that exited with a non-zero status
+    GitCliError {
+        code: Option<i32>,
+        stderr: String,
+        args: Vec<String>,
+    },
+    /// A git CLI invocation was killed for running past the configured timeout
+    GitTimeout {
+        args: Vec<String>,
+        timeout_secs: u64,
+    },
+    /// The git binary couldn't be started
+    GitNotFound {
+        git_cmd: String,
+    },
+    /// The path isn't inside a git repository
+    RepositoryNotFound {
+        path: String,
+    },
+    /// The commit has no authorship note
+    NoAuthorshipLog {
+        commit: String,
+    },
+    /// The commit's authorship note exists but can't be read
+    InvalidAuthorshipLog {
+        commit: String,
+        reason: String,
+    },
+    /// Another git-ai process held a repository lock for longer than we were willing to wait
+    LockTimeout {
+        path: String,
+        waited_secs: u64,
+    },
+    /// The repository's `.gitai` requires a newer git-ai than the one installed
+    VersionTooOld {
+        required: String,
+        installed: String,
+    },
+    JsonError(serde_json::Error),
+    Utf8Error(std::str::Utf8Error),
+    FromUtf8Error(std::string::FromUtf8Error),
+    PresetError(String),
+    Generic(String),
+}
+
+impl fmt::Display for GitAiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(feature = "test-support")]
+            GitAiError::GitError(e) => write!(f, "Git error: {}", e),
+            GitAiError::IoError(e) => write!(f, "IO error: {}", e),
+            GitAiError::GitCliError { code, stderr, args } => match code {
+                Some(c) => write!(
+                    f,
+                    "Git CLI ({}) failed with exit code {}: {}",
+                    args.join(" "),
+                    c,
+                    stderr
+                ),
+                None => write!(f, "Git CLI ({}) failed: {}", args.join(" "), stderr),
+            },
+            GitAiError::GitTimeout { args, timeout_secs } => write!(
+                f,
+                "Git CLI ({}) didn't finish within {}s and was killed",
+                args.join(" "),
+                timeout_secs
+            ),
+            GitAiError::GitNotFound { git_cmd } => {
+                write!(f, "Git executable not found: {}", git_cmd)
+            }
+            GitAiError::RepositoryNotFound { path } => {
+                write!(f, "Not a git repository: {}", path)
+            }
+            GitAiError::NoAuthorshipLog { commit } => {
+                write!(f, "No authorship note found for {}", commit)
+            }
+            GitAiError::InvalidAuthorshipLog { commit, reason } => {
+                write!(f, "Invalid authorship note for {}: {}", commit, reason)
+            }
+            GitAiError::LockTimeout { path, waited_secs } => write!(
+                f,
+                "Timed out after {}s waiting for {} (held by another git-ai process)",
+                waited_secs, path
+            ),
+            GitAiError::VersionTooOld {
+                required,
+                installed,
+            } => write!(
+                f,
+                "This repository requires git-ai {} or newer, but {} is installed. Run `git-ai upgrade` to update",
+                required, installed
+            ),
+            GitAiError::JsonError(e) => write!(f, "JSON error: {}", e),
+            GitAiError::Utf8Error(e) => write!(f, "UTF-8 error: {}", e),
+            GitAiError::FromUtf8Error(e) => write!(f, "From UTF-8 error: {}", e),
+            GitAiError::PresetError(e) => write!(f, "{}", e),
+            GitAiError::Generic(e) => write!(f, "Generic error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for GitAiError {}
+
+impl GitAiError {
+    /// Stable, machine-readable code for the kind of error. Scripts match on these, so an
+    /// existing code must never be renamed or reused.
+    pub fn code(&self) -> &'static str {
+        match self {
+            #[cfg(feature = "test-support")]
+            GitAiError::GitError(_) => "git_failed",
+            GitAiError::IoError(_) => "io",
+            GitAiError::GitCliError { .. } => "git_failed",
+            GitAiError::GitTimeout { .. } => "git_timeout",
+            GitAiError::GitNotFound { .. } => "git_not_found",
+            GitAiError::RepositoryNotFound { .. } => "repository_not_found",
+            GitAiError::NoAuthorshipLog { .. } => "no_authorship_log",
+            GitAiError::InvalidAuthorshipLog { .. } => "invalid_authorship_log",
+            GitAiError::LockTimeout { .. } => "lock_timeout",
+            GitAiError::VersionTooOld { .. } => "version_too_old",
+            GitAiError::JsonError(_) => "json",
+            GitAiError::Utf8Error(_) | GitAiError::FromUtf8Error(_) => "utf8",
+            GitAiError::PresetError(_) => "preset",
+            GitAiError::Generic(_) => "generic",
+        }
+    }
+
+    /// Process exit code for the error (see "Exit codes" in docs/reference.mdx)
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            GitAiError::NoAuthorshipLog { .. } => 2,
+            GitAiError::InvalidAuthorshipLog { .. } => 3,
+            GitAiError::RepositoryNotFound { .. } => 4,
+            GitAiError::GitCliError { .. } => 5,
+            #[cfg(feature = "test-support")]
+            GitAiError::GitError(_) => 5,
+            GitAiError::GitNotFound { .. } => 6,
+            GitAiError::LockTimeout { .. } => 7,
+            GitAiError::GitTimeout { .. } => 8,
+            GitAiError::VersionTooOld { .. } => 9,
+            _ => 1,
+        }
+    }
+
+    /// `{"error": {"code", "message", "context", "exit_code"}}`
+    pub fn to_json(&self, context: &str) -> serde_json::Value {
+        serde_json::json!({
+            "error": {
+                "code": self.code(),
+                "message": self.to_string(),
+                "context": context,
+                "exit_code": self.exit_code(),
+            }
+        })
+    }
+}
+
+/// How `exit_with_error` prints errors, set once from `--error-format`
+static JSON_ERRORS: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorFormat {
+    Text,
+    Json,
+}
+
+impl std::str::FromStr for ErrorFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(ErrorFormat::Text),
+            "json" => Ok(ErrorFormat::Json),
+            other => Err(format!(
+                "invalid error format '{}', expected text or json",
+                other
+            )),
+        }
+    }
+}
+
+pub fn set_error_format(format: ErrorFormat) {
+    JSON_ERRORS.store(format == ErrorFormat::Json, Ordering::Relaxed);
+}
+
+/// Prints `err` to stderr (as "<context>: <message>", or as a JSON object with
+/// `--error-format json`) and exits with its exit code
+pub fn exit_with_error(context: &str, err: &GitAiError) -> ! {
+    crate::observability::usage::record_error(err);
+    if JSON_ERRORS.load(Ordering::Relaxed) {
+        eprintln!("{}", err.to_json(context));
+    } else {
+        eprintln!("{}: {}", context, err);
+    }
+    std::process::exit(err.exit_code());
+}
+
+#[cfg(feature = "test-support")]
+impl From<git2::Error> for GitAiError {
+    fn from(err: git2::Error) -> Self {
+        GitAiError::GitError(err)
+    }
+}
+
+impl From<std::io::Error> for GitAiError {
+    fn from(err: std::io::Error) -> Self {
+        GitAiError::IoError(err)
+    }
+}
+
+impl From<serde_json::Error> for GitAiError {
+    fn from(err: serde_json::Error) -> Self {
+        GitAiError::JsonError(err)
+    }
+}
+
+impl From<std::str::Utf8Error> for GitAiError {
+    fn from(err: std::str::Utf8Error) -> Self {
+        GitAiError::Utf8Error(err)
+    }
+}
+
+impl From<std::string::FromUtf8Error> for GitAiError {
+    fn from(err: std::string::FromUtf8Error) -> Self {
+        GitAiError::FromUtf8Error(err)
+    }
+}
+
+impl Clone for GitAiError {
+    fn clone(&self) -> Self {
+        match self {
+            #[cfg(feature = "test-support")]
+            GitAiError::GitError(e) => GitAiError::Generic(format!("Git error: {}", e)),
+            GitAiError::IoError(e) => {
+                GitAiError::IoError(std::io::Error::new(e.kind(), e.to_string()))
+            }
+            GitAiError::GitCliError { code, stderr, args } => GitAiError::GitCliError {
+                code: *code,
+                stderr: stderr.clone(),
+                args: args.clone(),
+            },
+            GitAiError::GitTimeout { args, timeout_secs } => GitAiError::GitTimeout {
+                args: args.clone(),
+                timeout_secs: *timeout_secs,
+            },
+            GitAiError::GitNotFound { git_cmd } => GitAiError::GitNotFound {
+                git_cmd: git_cmd.clone(),
+            },
+            GitAiError::RepositoryNotFound { path } => {
+                GitAiError::RepositoryNotFound { path: path.clone() }
+            }
+            GitAiError::NoAuthorshipLog { commit } => GitAiError::NoAuthorshipLog {
+                commit: commit.clone(),
+            },
+            GitAiError::InvalidAuthorshipLog { commit, reason } => {
+                GitAiError::InvalidAuthorshipLog {
+                    commit: commit.clone(),
+                    reason: reason.clone(),
+                }
+            }
+            GitAiError::LockTimeout { path, waited_secs } => GitAiError::LockTimeout {
+                path: path.clone(),
+                waited_secs: *waited_secs,
+            },
+            GitAiError::VersionTooOld {
+                required,
+                installed,
+            } => GitAiError::VersionTooOld {
+                required: required.clone(),
+                installed: installed.clone(),
+            },
+            GitAiError::JsonError(e) => GitAiError::Generic(format!("JSON error: {}", e)),
+            GitAiError::Utf8Error(e) => GitAiError::Utf8Error(*e),
+            GitAiError::FromUtf8Error(e) => GitAiError::FromUtf8Error(e.clone()),
+            GitAiError::PresetError(s) => GitAiError::PresetError(s.clone()),
+            GitAiError::Generic(s) => GitAiError::Generic(s.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_typed_errors_have_stable_codes_and_exit_codes() {
+        let cases = [
+            (GitAiError::Generic("x".to_string()), "generic", 1),
+            (
+                GitAiError::NoAuthorshipLog {
+                    commit: "abc".to_string(),
+                },
+                "no_authorship_log",
+                2,
+            ),
+            (
+                GitAiError::InvalidAuthorshipLog {
+                    commit: "abc".to_string(),
+                    reason: "bad".to_string(),
+                },
+                "invalid_authorship_log",
+                3,
+            ),
+            (
+                GitAiError::RepositoryNotFound {
+                    path: "/tmp".to_string(),
+                },
+                "repository_not_found",
+                4,
+            ),
+            (
+                GitAiError::GitCliError {
+                    code: Some(128),
+                    stderr: String::new(),
+                    args: Vec::new(),
+                },
+                "git_failed",
+                5,
+            ),
+            (
+                GitAiError::GitNotFound {
+                    git_cmd: "git".to_string(),
+                },
+                "git_not_found",
+                6,
+            ),
+            (
+                GitAiError::LockTimeout {
+                    path: ".git/ai/working_logs.lock".to_string(),
+                    waited_secs: 10,
+                },
+                "lock_timeout",
+                7,
+            ),
+            (
+                GitAiError::GitTimeout {
+                    args: vec!["fetch".to_string()],
+                    timeout_secs: 120,
+                },
+                "git_timeout",
+                8,
+            ),
+            (
+                GitAiError::VersionTooOld {
+                    required: "2.0.0".to_string(),
+                    installed: "1.0.0".to_string(),
+                },
+                "version_too_old",
+                9,
+            ),
+        ];
+        for (error, code, exit_code) in cases {
+            assert_eq!(error.code(), code);
+            assert_eq!(error.exit_code(), exit_code);
+            assert_eq!(error.clone().code(), code);
+        }
+    }
+
+    #[test]
+    fn test_to_json() {
+        let error = GitAiError::NoAuthorshipLog {
+            commit: "abc".to_string(),
+        };
+        assert_eq!(
+            error.to_json("Failed to show authorship"),
+            serde_json::json!({
+                "error": {
+                    "code": "no_authorship_log",
+                    "message": "No authorship note found for abc",
+                    "context": "Failed to show authorship",
+                    "exit_code": 2,
+                }
+            })
+        );
+    }
+}
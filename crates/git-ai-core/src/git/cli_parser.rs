@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 /// Parse the arguments that come *after* the `git` executable.
 /// Example input corresponds to: `git -C .. commit -m foo`  => args = ["-C","..","commit","-m","foo"]
 ///
@@ -461,10 +463,206 @@ pub fn parse_git_cli_args(args: &[String]) -> ParsedGitInvocation {
     }
 }
 
+/// The directory git runs in for these global args: the current directory with every `-C`
+/// applied in turn, as git does (`-C a -C b` is `a/b`, an absolute path starts over and an empty
+/// one is ignored)
+pub fn invocation_dir(global_args: &[String]) -> PathBuf {
+    let mut dir = std::env::current_dir().unwrap_or_default();
+    let mut args = global_args.iter();
+    while let Some(arg) = args.next() {
+        let path = if arg == "-C" {
+            match args.next() {
+                Some(path) => path.as_str(),
+                None => break,
+            }
+        } else if let Some(path) = arg.strip_prefix("-C") {
+            path
+        } else {
+            continue;
+        };
+        if !path.is_empty() {
+            dir = dir.join(path);
+        }
+    }
+    dir
+}
+
 pub fn is_dry_run(args: &[String]) -> bool {
     args.iter().any(|arg| arg == "--dry-run")
 }
 
+/// What a `git commit` takes the commit's changes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommitScope {
+    /// The index as staged (also with `-a`, which stages tracked changes first)
+    Index,
+    /// Hunks picked interactively with `-p` / `--patch` or `--interactive`
+    Patch,
+    /// The working copies of `paths`, without the rest of the index (`--only`, the default when
+    /// paths are given), or on top of it with `-i` / `--include`
+    Paths { paths: Vec<String>, include: bool },
+}
+
+impl CommitScope {
+    /// Whether changes staged before the commit can still be staged after it. `-p` starts from
+    /// the index, so only a commit of just some paths leaves the rest of the index alone
+    pub fn leaves_staged_changes(&self) -> bool {
+        matches!(self, CommitScope::Paths { include: false, .. })
+    }
+}
+
+/// Classifies the args of a `git commit` (the command's args, after `commit`) by what the commit
+/// takes its changes from
+pub fn commit_scope(args: &[String]) -> CommitScope {
+    // Short flags of `git commit` that take the next token (or the rest of their cluster)
+    const SHORT_WITH_VALUE: &[char] = &['m', 'F', 'C', 'c', 't'];
+    const LONG_WITH_VALUE: &[&str] = &[
+        "--message",
+        "--file",
+        "--reuse-message",
+        "--reedit-message",
+        "--fixup",
+        "--squash",
+        "--author",
+        "--date",
+        "--template",
+        "--cleanup",
+        "--trailer",
+        "--pathspec-from-file",
+    ];
+
+    let mut patch = false;
+    let mut include = false;
+    let mut from_file = false;
+    let mut paths = Vec::new();
+    let mut i = 0;
+    while i < args.len() {
+        let arg = args[i].as_str();
+        i += 1;
+        if arg == "--" {
+            paths.extend(args[i..].iter().cloned());
+            break;
+        }
+        if let Some(long) = arg.strip_prefix("--") {
+            match long.split('=').next().unwrap_or_default() {
+                "patch" | "interactive" => patch = true,
+                "include" => include = true,
+                "pathspec-from-file" => from_file = true,
+                _ => {}
+            }
+            if !long.contains('=') && LONG_WITH_VALUE.contains(&arg) {
+                i += 1;
+            }
+        } else if let Some(cluster) = arg.strip_prefix('-').filter(|c| !c.is_empty()) {
+            for (pos, flag) in cluster.char_indices() {
+                match flag {
+                    'p' => patch = true,
+                    'i' => include = true,
+                    _ => {}
+                }
+                if SHORT_WITH_VALUE.contains(&flag) {
+                    // The value is the rest of the cluster, or the next token
+                    if pos + flag.len_utf8() == cluster.len() {
+                        i += 1;
+                    }
+                    break;
+                }
+            }
+        } else {
+            paths.push(arg.to_string());
+        }
+    }
+
+    if patch {
+        CommitScope::Patch
+    } else if !paths.is_empty() || from_file {
+        CommitScope::Paths { paths, include }
+    } else {
+        CommitScope::Index
+    }
+}
+
+/// What a `git rebase` replays and where onto, as given on its command line
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RebaseArgs {
+    /// `<upstream>`, or `None` with `--root` or when the branch's configured upstream is used
+    pub upstream: Option<String>,
+    /// `--onto <newbase>`, or `None` when rebasing onto `<upstream>`
+    pub onto: Option<String>,
+    /// `<branch>`, which git checks out before rebasing
+    pub branch: Option<String>,
+    /// `--root`: every commit reachable from the branch is replayed
+    pub root: bool,
+    /// `--keep-base`: replayed onto the merge base of `<upstream>` and the branch
+    pub keep_base: bool,
+}
+
+/// Parses the args of a `git rebase` (the command's args, after `rebase`)
+pub fn parse_rebase_args(args: &[String]) -> RebaseArgs {
+    // Short flags of `git rebase` that take the next token (or the rest of their cluster)
+    const SHORT_WITH_VALUE: &[char] = &['s', 'X', 'x', 'C'];
+    const LONG_WITH_VALUE: &[&str] = &[
+        "--onto",
+        "--strategy",
+        "--strategy-option",
+        "--exec",
+        "--whitespace",
+        "--empty",
+    ];
+
+    let mut rebase_args = RebaseArgs::default();
+    let mut positional = Vec::new();
+    let mut i = 0;
+    while i < args.len() {
+        let arg = args[i].as_str();
+        i += 1;
+        if arg == "--" {
+            positional.extend(args[i..].iter().cloned());
+            break;
+        }
+        if let Some(long) = arg.strip_prefix("--") {
+            let (name, value) = match long.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_string())),
+                None => (long, None),
+            };
+            let value = match value {
+                None if LONG_WITH_VALUE.contains(&arg) => {
+                    let next = args.get(i).cloned();
+                    i += 1;
+                    next
+                }
+                value => value,
+            };
+            match name {
+                "onto" => rebase_args.onto = value,
+                "root" => rebase_args.root = true,
+                "keep-base" => rebase_args.keep_base = true,
+                _ => {}
+            }
+        } else if let Some(cluster) = arg.strip_prefix('-').filter(|c| !c.is_empty()) {
+            for (pos, flag) in cluster.char_indices() {
+                if SHORT_WITH_VALUE.contains(&flag) {
+                    // The value is the rest of the cluster, or the next token
+                    if pos + flag.len_utf8() == cluster.len() {
+                        i += 1;
+                    }
+                    break;
+                }
+            }
+        } else {
+            positional.push(arg.to_string());
+        }
+    }
+
+    // With `--root` there's no upstream, so the only positional is the branch
+    let mut positional = positional.into_iter();
+    if !rebase_args.root {
+        rebase_args.upstream = positional.next();
+    }
+    rebase_args.branch = positional.next();
+    rebase_args
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -536,4 +734,139 @@ mod tests {
         let parsed = parse_git_cli_args(&args);
         assert_eq!(parsed.pos_command(0), Some("abc".to_string()));
     }
+
+    fn commit_args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_commit_scope_index() {
+        assert_eq!(
+            commit_scope(&commit_args(&["-m", "msg"])),
+            CommitScope::Index
+        );
+        assert_eq!(
+            commit_scope(&commit_args(&["-am", "msg"])),
+            CommitScope::Index
+        );
+        assert_eq!(
+            commit_scope(&commit_args(&[
+                "--message",
+                "path-like",
+                "--author",
+                "A <a@b>"
+            ])),
+            CommitScope::Index
+        );
+        assert_eq!(
+            commit_scope(&commit_args(&["--amend", "--no-edit"])),
+            CommitScope::Index
+        );
+    }
+
+    #[test]
+    fn test_commit_scope_patch() {
+        assert_eq!(commit_scope(&commit_args(&["-p"])), CommitScope::Patch);
+        assert_eq!(
+            commit_scope(&commit_args(&["-pm", "msg"])),
+            CommitScope::Patch
+        );
+        assert_eq!(
+            commit_scope(&commit_args(&["--interactive", "-m", "msg"])),
+            CommitScope::Patch
+        );
+        assert!(!commit_scope(&commit_args(&["-p", "--", "a.txt"])).leaves_staged_changes());
+        // A message that looks like a flag cluster is not one
+        assert_eq!(
+            commit_scope(&commit_args(&["-m", "-p"])),
+            CommitScope::Index
+        );
+    }
+
+    #[test]
+    fn test_commit_scope_paths() {
+        assert_eq!(
+            commit_scope(&commit_args(&["-m", "msg", "--", "a.txt", "b.txt"])),
+            CommitScope::Paths {
+                paths: vec!["a.txt".to_string(), "b.txt".to_string()],
+                include: false,
+            }
+        );
+        assert_eq!(
+            commit_scope(&commit_args(&["--only", "a.txt", "-mmsg"])),
+            CommitScope::Paths {
+                paths: vec!["a.txt".to_string()],
+                include: false,
+            }
+        );
+        let include = commit_scope(&commit_args(&["-i", "a.txt", "-m", "msg"]));
+        assert_eq!(
+            include,
+            CommitScope::Paths {
+                paths: vec!["a.txt".to_string()],
+                include: true,
+            }
+        );
+        assert!(!include.leaves_staged_changes());
+        assert!(commit_scope(&commit_args(&["-m", "msg", "--", "a.txt"])).leaves_staged_changes());
+    }
+
+    fn rebase_args(args: &[&str]) -> RebaseArgs {
+        parse_rebase_args(&args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn test_parse_rebase_args_upstream_and_branch() {
+        assert_eq!(rebase_args(&[]), RebaseArgs::default());
+        assert_eq!(
+            rebase_args(&["-i", "main"]),
+            RebaseArgs {
+                upstream: Some("main".to_string()),
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            rebase_args(&["-X", "theirs", "--exec", "make", "main", "feature"]),
+            RebaseArgs {
+                upstream: Some("main".to_string()),
+                branch: Some("feature".to_string()),
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            rebase_args(&["--keep-base", "-sort", "main"]),
+            RebaseArgs {
+                upstream: Some("main".to_string()),
+                keep_base: true,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_rebase_args_onto() {
+        let expected = RebaseArgs {
+            upstream: Some("old-base".to_string()),
+            onto: Some("new-base".to_string()),
+            branch: Some("feature".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            rebase_args(&["--onto", "new-base", "old-base", "feature"]),
+            expected
+        );
+        assert_eq!(
+            rebase_args(&["old-base", "--onto=new-base", "feature"]),
+            expected
+        );
+        assert_eq!(
+            rebase_args(&["--root", "--onto", "new-base", "feature"]),
+            RebaseArgs {
+                onto: Some("new-base".to_string()),
+                branch: Some("feature".to_string()),
+                root: true,
+                ..Default::default()
+            }
+        );
+    }
 }
@@ -0,0 +1,163 @@
+//! Parses CODEOWNERS files, as GitHub and GitLab read them: one rule per line, a gitignore-style
+//! pattern followed by owners (`@user`, `@org/team` or an email), and the last matching rule
+//! wins. GitLab `[Section]` headers are skipped, so their rules read as one list.
+
+use crate::error::GitAiError;
+use crate::git::repository::Repository;
+use glob::{MatchOptions, Pattern};
+
+/// Where CODEOWNERS is looked for, in GitHub's order
+pub const CODEOWNERS_LOCATIONS: &[&str] = &[".github/CODEOWNERS", "CODEOWNERS", "docs/CODEOWNERS"];
+
+#[derive(Debug, Clone)]
+pub struct OwnershipRule {
+    /// The pattern as written in the file
+    pub pattern: String,
+    /// Empty when the rule removes ownership from the paths it matches
+    pub owners: Vec<String>,
+    globs: Vec<Pattern>,
+}
+
+impl OwnershipRule {
+    fn parse(pattern: &str, owners: Vec<String>) -> Result<OwnershipRule, GitAiError> {
+        // A slash at the start or in the middle anchors the pattern to the root; otherwise it
+        // matches at any depth
+        let dir_only = pattern.ends_with('/');
+        let trimmed = pattern.trim_end_matches('/');
+        let anchored = trimmed.starts_with('/') || trimmed.contains('/');
+        let trimmed = trimmed.trim_start_matches('/');
+
+        let base = if anchored || trimmed.starts_with("**") {
+            trimmed.to_string()
+        } else {
+            format!("**/{}", trimmed)
+        };
+        // Matching a directory matches everything under it
+        let mut sources = vec![format!("{}/**", base)];
+        if !dir_only {
+            sources.push(base);
+        }
+        let globs = sources
+            .iter()
+            .map(|source| {
+                Pattern::new(source).map_err(|e| {
+                    GitAiError::Generic(format!("invalid CODEOWNERS pattern '{}': {}", pattern, e))
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(OwnershipRule {
+            pattern: pattern.to_string(),
+            owners,
+            globs,
+        })
+    }
+
+    pub fn matches(&self, path: &str) -> bool {
+        let options = MatchOptions {
+            case_sensitive: true,
+            require_literal_separator: true,
+            require_literal_leading_dot: false,
+        };
+        self.globs
+            .iter()
+            .any(|glob| glob.matches_with(path, options))
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CodeOwners {
+    pub rules: Vec<OwnershipRule>,
+}
+
+impl CodeOwners {
+    /// Reads the first CODEOWNERS file found at `rev`, or None if there isn't one
+    pub fn load(repo: &Repository, rev: &str) -> Result<Option<CodeOwners>, GitAiError> {
+        let tree = repo.revparse_single(rev)?.peel_to_commit()?.tree()?;
+        for location in CODEOWNERS_LOCATIONS {
+            let Ok(entry) = tree.get_path(std::path::Path::new(location)) else {
+                continue;
+            };
+            let content = repo.find_blob(entry.id())?.content()?;
+            return CodeOwners::parse(&String::from_utf8_lossy(&content)).map(Some);
+        }
+        Ok(None)
+    }
+
+    pub fn parse(content: &str) -> Result<CodeOwners, GitAiError> {
+        let mut rules = Vec::new();
+        for line in content.lines() {
+            let line = line.split(" #").next().unwrap_or_default().trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with('[') {
+                continue;
+            }
+            let mut fields = line.split_whitespace();
+            let Some(pattern) = fields.next() else {
+                continue;
+            };
+            let owners = fields.map(str::to_string).collect();
+            rules.push(OwnershipRule::parse(pattern, owners)?);
+        }
+        Ok(CodeOwners { rules })
+    }
+
+    /// The rule that decides who owns `path`: the last one that matches
+    pub fn rule_for(&self, path: &str) -> Option<&OwnershipRule> {
+        self.rules.iter().rev().find(|rule| rule.matches(path))
+    }
+
+    /// The owners of `path`, empty if no rule gives it any
+    pub fn owners_of(&self, path: &str) -> &[String] {
+        self.rule_for(path)
+            .map(|rule| rule.owners.as_slice())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codeowners_last_match_wins() {
+        let owners = CodeOwners::parse(
+            "# Default owners\n\
+             *       @acme/core\n\
+             *.md    @acme/docs\n\
+             /services/payments/  @acme/payments jane@example.com\n\
+             /services/payments/vendor/\n\
+             apps/*/ui @acme/frontend # comment\n",
+        )
+        .unwrap();
+
+        assert_eq!(owners.owners_of("src/main.rs"), ["@acme/core"]);
+        assert_eq!(owners.owners_of("docs/guide/intro.md"), ["@acme/docs"]);
+        assert_eq!(
+            owners.owners_of("services/payments/api/charge.rs"),
+            ["@acme/payments", "jane@example.com"]
+        );
+        assert_eq!(
+            owners.owners_of("services/payments/README.md"),
+            ["@acme/payments", "jane@example.com"]
+        );
+        assert!(
+            owners
+                .owners_of("services/payments/vendor/lib.rs")
+                .is_empty()
+        );
+        assert_eq!(
+            owners.owners_of("apps/web/ui/button.tsx"),
+            ["@acme/frontend"]
+        );
+        assert_eq!(owners.owners_of("apps/web/api/route.ts"), ["@acme/core"]);
+    }
+
+    #[test]
+    fn test_codeowners_unanchored_patterns() {
+        let owners = CodeOwners::parse("build/ @acme/infra\nMakefile @acme/infra\n").unwrap();
+        assert_eq!(owners.owners_of("build/out.txt"), ["@acme/infra"]);
+        assert_eq!(owners.owners_of("tools/build/out.txt"), ["@acme/infra"]);
+        assert_eq!(owners.owners_of("tools/Makefile"), ["@acme/infra"]);
+        assert!(owners.owners_of("tools/build").is_empty());
+        assert!(owners.owners_of("src/lib.rs").is_empty());
+    }
+}
@@ -1,6 +1,7 @@
 use crate::error::GitAiError;
-use crate::git::repository::{Repository, Tree, exec_git};
-use std::collections::HashSet;
+use crate::git::repo_path;
+use crate::git::repository::{Repository, Tree, exec_git, push_pathspecs};
+use std::collections::{BTreeMap, HashSet};
 use std::path::{Path, PathBuf};
 
 #[allow(dead_code)]
@@ -153,19 +154,49 @@ impl Repository {
         args.push(old_oid);
         args.push(new_oid);
 
-        // Add pathspecs if provided
-        if let Some(paths) = pathspecs {
-            args.push("--".to_string());
-            for path in paths {
-                args.push(path.clone());
-            }
-        }
+        let narrowed = push_pathspecs(&mut args, pathspecs);
 
         let output = exec_git(&args)?;
-        let deltas = parse_diff_raw(&output.stdout)?;
+        let mut deltas = parse_diff_raw(&output.stdout)?;
+        if !narrowed && let Some(paths) = pathspecs {
+            deltas.retain(|delta| {
+                [delta.old_file.path(), delta.new_file.path()]
+                    .into_iter()
+                    .flatten()
+                    .any(|path| paths.contains(path.to_string_lossy().as_ref()))
+            });
+        }
 
         Ok(Diff { deltas })
     }
+
+    /// Files renamed between two commits (or trees), old path -> new path
+    pub fn diff_renamed_files(
+        &self,
+        from_ref: &str,
+        to_ref: &str,
+    ) -> Result<BTreeMap<String, String>, GitAiError> {
+        let mut args = self.global_args_for_exec();
+        args.push("diff".to_string());
+        args.push("--raw".to_string());
+        args.push("-z".to_string());
+        args.push("--no-abbrev".to_string());
+        args.push("-M".to_string());
+        args.push(from_ref.to_string());
+        args.push(to_ref.to_string());
+
+        let output = exec_git(&args)?;
+        Ok(parse_diff_raw(&output.stdout)?
+            .into_iter()
+            .filter(|delta| delta.status == DiffStatus::Renamed)
+            .filter_map(|delta| {
+                // git prints the source path first, which parse_diff_raw reads into new_file
+                let old_path = delta.new_file.path()?.to_string_lossy().to_string();
+                let new_path = delta.old_file.path()?.to_string_lossy().to_string();
+                Some((old_path, new_path))
+            })
+            .collect())
+    }
 }
 
 /// Parse the raw output from git diff --raw -z
@@ -193,11 +224,10 @@ fn parse_diff_raw(data: &[u8]) -> Result<Vec<DiffDelta>, GitAiError> {
         // When using -z, the path is the NEXT part after the NUL separator
         let path = match parts.next() {
             Some(p) => {
-                let path_str = std::str::from_utf8(p)?;
-                if path_str.is_empty() {
+                if p.is_empty() {
                     continue; // Skip records without a path
                 }
-                path_str
+                repo_path::key_from_bytes(p)
             }
             None => continue, // No path found
         };
@@ -241,10 +271,9 @@ fn parse_diff_raw(data: &[u8]) -> Result<Vec<DiffDelta>, GitAiError> {
             let old_path_bytes = parts
                 .next()
                 .ok_or_else(|| GitAiError::Generic("Missing old path for rename/copy".into()))?;
-            let old_path_str = std::str::from_utf8(old_path_bytes)?;
-            (path.to_string(), Some(old_path_str.to_string()))
+            (path, Some(repo_path::key_from_bytes(old_path_bytes)))
         } else {
-            (path.to_string(), None)
+            (path, None)
         };
 
         // Construct the old_file and new_file
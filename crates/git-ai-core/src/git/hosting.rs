@@ -0,0 +1,174 @@
+//! Where hosting providers keep the heads of pull and merge requests.
+//!
+//! A squash merge made by the host leaves the merged branch's commits reachable only through
+//! the provider's review refs once the branch is deleted: GitHub's `refs/pull/<n>/head`,
+//! GitLab's `refs/merge-requests/<n>/head` and Bitbucket Server's `refs/pull-requests/<n>/from`
+//! (Bitbucket Cloud doesn't publish one). Fetched heads are kept under a local ref per provider
+//! so later runs and `git gc` don't lose them.
+
+use crate::error::GitAiError;
+use crate::git::repository::Repository;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HostingProvider {
+    GitHub,
+    GitLab,
+    Bitbucket,
+}
+
+impl HostingProvider {
+    pub const ALL: [HostingProvider; 3] = [
+        HostingProvider::GitHub,
+        HostingProvider::GitLab,
+        HostingProvider::Bitbucket,
+    ];
+
+    pub fn parse(value: &str) -> Result<HostingProvider, GitAiError> {
+        match value.to_ascii_lowercase().as_str() {
+            "github" => Ok(HostingProvider::GitHub),
+            "gitlab" => Ok(HostingProvider::GitLab),
+            "bitbucket" => Ok(HostingProvider::Bitbucket),
+            _ => Err(GitAiError::Generic(format!(
+                "Unknown hosting provider '{}' (expected github, gitlab or bitbucket)",
+                value
+            ))),
+        }
+    }
+
+    /// The provider `remote` is hosted on, judged by its URL. Defaults to GitHub, so
+    /// self-hosted GitLab or Bitbucket under another hostname needs naming explicitly.
+    pub fn detect(repo: &Repository, remote: &str) -> HostingProvider {
+        let url = repo
+            .remotes_with_urls()
+            .unwrap_or_default()
+            .into_iter()
+            .find(|(name, _)| name == remote)
+            .map(|(_, url)| url.to_ascii_lowercase())
+            .unwrap_or_default();
+        if url.contains("gitlab") {
+            HostingProvider::GitLab
+        } else if url.contains("bitbucket") {
+            HostingProvider::Bitbucket
+        } else {
+            HostingProvider::GitHub
+        }
+    }
+
+    /// What the provider calls a review, for messages
+    pub fn review_name(&self) -> &'static str {
+        match self {
+            HostingProvider::GitLab => "merge request",
+            HostingProvider::GitHub | HostingProvider::Bitbucket => "pull request",
+        }
+    }
+
+    /// The remote ref holding the head of review `number`
+    pub fn head_ref(&self, number: u32) -> String {
+        match self {
+            HostingProvider::GitHub => format!("refs/pull/{}/head", number),
+            HostingProvider::GitLab => format!("refs/merge-requests/{}/head", number),
+            HostingProvider::Bitbucket => format!("refs/pull-requests/{}/from", number),
+        }
+    }
+
+    /// The local ref a fetched review head is kept under
+    pub fn local_ref(&self, number: u32) -> String {
+        match self {
+            HostingProvider::GitHub => format!("refs/github/pr/{}", number),
+            HostingProvider::GitLab => format!("refs/gitlab/mr/{}", number),
+            HostingProvider::Bitbucket => format!("refs/bitbucket/pr/{}", number),
+        }
+    }
+
+    /// Local refs review heads may be found under: the provider's own refs, when the remote's
+    /// are fetched directly, and the ones [`fetch_review_head`] keeps
+    pub fn ref_prefixes(&self) -> [&'static str; 2] {
+        match self {
+            HostingProvider::GitHub => ["refs/pull", "refs/github/pr"],
+            HostingProvider::GitLab => ["refs/merge-requests", "refs/gitlab/mr"],
+            HostingProvider::Bitbucket => ["refs/pull-requests", "refs/bitbucket/pr"],
+        }
+    }
+
+    /// The review number the provider writes into a squash commit's message: `Title (#12)` on
+    /// GitHub, `See merge request group/project!12` on GitLab, `Merged in branch (pull request
+    /// #12)` on Bitbucket
+    pub fn number_from_message(&self, message: &str) -> Option<u32> {
+        match self {
+            HostingProvider::GitHub => {
+                let subject = message.lines().next()?.trim_end();
+                let (_, number) = subject.strip_suffix(')')?.rsplit_once("(#")?;
+                number.parse().ok()
+            }
+            HostingProvider::GitLab => message.lines().find_map(|line| {
+                let (_, reference) = line.trim().split_once("See merge request ")?;
+                let (_, number) = reference.rsplit_once('!')?;
+                number.parse().ok()
+            }),
+            HostingProvider::Bitbucket => message.lines().find_map(|line| {
+                let (_, rest) = line.split_once("(pull request #")?;
+                let (number, _) = rest.split_once(')')?;
+                number.parse().ok()
+            }),
+        }
+    }
+}
+
+/// Fetches the head of review `number` from `remote` into the provider's local ref and
+/// returns its SHA
+pub fn fetch_review_head(
+    repo: &Repository,
+    provider: HostingProvider,
+    remote: &str,
+    number: u32,
+) -> Result<String, GitAiError> {
+    let local_ref = provider.local_ref(number);
+    repo.git(&[
+        "fetch",
+        remote,
+        &format!("+{}:{}", provider.head_ref(number), local_ref),
+    ])?;
+    Ok(repo.git(&["rev-parse", &local_ref])?.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_number_from_message() {
+        let github = HostingProvider::GitHub;
+        assert_eq!(
+            github.number_from_message("Add login (#12)\n\nbody"),
+            Some(12)
+        );
+        assert_eq!(github.number_from_message("Fix (#3) typo"), None);
+
+        let gitlab = HostingProvider::GitLab;
+        let message = "Add login\n\nSee merge request group/project!34";
+        assert_eq!(gitlab.number_from_message(message), Some(34));
+        assert_eq!(gitlab.number_from_message("Add login (#12)"), None);
+
+        let bitbucket = HostingProvider::Bitbucket;
+        let message = "Merged in feature/login (pull request #56)\n\nAdd login";
+        assert_eq!(bitbucket.number_from_message(message), Some(56));
+    }
+
+    #[test]
+    fn test_review_refs() {
+        assert_eq!(HostingProvider::GitHub.head_ref(1), "refs/pull/1/head");
+        assert_eq!(
+            HostingProvider::GitLab.head_ref(2),
+            "refs/merge-requests/2/head"
+        );
+        assert_eq!(
+            HostingProvider::Bitbucket.head_ref(3),
+            "refs/pull-requests/3/from"
+        );
+        assert_eq!(
+            HostingProvider::parse("GitLab").unwrap(),
+            HostingProvider::GitLab
+        );
+        assert!(HostingProvider::parse("gitea").is_err());
+    }
+}
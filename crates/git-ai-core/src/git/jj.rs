@@ -0,0 +1,528 @@
+//! Compatibility with Jujutsu (jj) colocated repositories. jj rewrites commits and moves refs
+//! without running git, so none of git-ai's hooks see its rebases, squashes and new commits.
+//! Instead, git-ai reads jj's operation log for the operations since it last looked and turns
+//! what each one did to the repository's mutable commits into the rewrite events git's own
+//! commands log, so attribution follows. The last operation it looked at is kept in `.git/ai`,
+//! and jj only runs when jj's current operation is another one.
+
+use crate::commands::hooks::commit_hooks;
+use crate::error::GitAiError;
+use crate::git::repository::Repository;
+use crate::git::rewrite_log::{RebaseCompleteEvent, RewriteLogEvent};
+use crate::utils::debug_log;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::process::Command;
+
+/// The `jj` binary to run, if not the one on the PATH
+const JJ_ENV_VAR: &str = "GIT_AI_JJ";
+/// Most operations replayed at once; anything older is skipped
+const MAX_OPERATIONS: usize = 100;
+/// jj's root commit, which isn't a git commit
+const ROOT_COMMIT: &str = "0000000000000000000000000000000000000000";
+const COMMIT_TEMPLATE: &str = r#"change_id ++ " " ++ commit_id ++ " " ++ if(current_working_copy, "@", "-") ++ " " ++ parents.map(|c| c.commit_id()).join(",") ++ "\n""#;
+
+/// A commit as one jj operation left it
+#[derive(Debug, Clone, PartialEq)]
+pub struct JjCommit {
+    pub change_id: String,
+    pub commit_id: String,
+    /// Whether it's the working-copy commit, `@`
+    pub working_copy: bool,
+    pub parents: Vec<String>,
+}
+
+/// Whether the repository's work tree is also a jj repository
+pub fn is_colocated(repo: &Repository) -> bool {
+    repo.workdir()
+        .map(|workdir| workdir.join(".jj").is_dir())
+        .unwrap_or(false)
+}
+
+fn run_jj(repo: &Repository, args: &[&str]) -> Result<String, GitAiError> {
+    let program = std::env::var(JJ_ENV_VAR).unwrap_or_else(|_| "jj".to_string());
+    let workdir = repo.workdir()?;
+    let output = Command::new(&program)
+        .arg("--repository")
+        .arg(&workdir)
+        .args(["--ignore-working-copy", "--no-pager", "--color=never"])
+        .args(args)
+        .output()
+        .map_err(|e| {
+            GitAiError::Generic(format!(
+                "Failed to run '{}' (install jj or set {}): {}",
+                program, JJ_ENV_VAR, e
+            ))
+        })?;
+    if !output.status.success() {
+        return Err(GitAiError::Generic(format!(
+            "jj {} failed: {}",
+            args.first().unwrap_or(&""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// The ids of jj's current operation heads, read from its store without running jj: one, unless
+/// concurrent operations are waiting for jj to merge them. None if the store can't be read.
+fn operation_heads(repo: &Repository) -> Option<Vec<String>> {
+    let jj_dir = repo.workdir().ok()?.join(".jj");
+    // A secondary workspace's `.jj/repo` is a file with the path of the main one's
+    let mut store = jj_dir.join("repo");
+    if store.is_file() {
+        store = jj_dir.join(fs::read_to_string(&store).ok()?.trim());
+    }
+    let mut heads: Vec<String> = fs::read_dir(store.join("op_heads").join("heads"))
+        .ok()?
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        .collect();
+    heads.sort();
+    Some(heads)
+}
+
+/// The ids of jj's most recent operations, newest first
+fn operation_ids(repo: &Repository) -> Result<Vec<String>, GitAiError> {
+    let limit = MAX_OPERATIONS.to_string();
+    let output = run_jj(
+        repo,
+        &[
+            "op",
+            "log",
+            "--no-graph",
+            "--limit",
+            &limit,
+            "-T",
+            r#"id ++ "\n""#,
+        ],
+    )?;
+    Ok(output
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+pub fn parse_commits(output: &str) -> Vec<JjCommit> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let change_id = fields.next()?.to_string();
+            let commit_id = fields.next()?.to_string();
+            let working_copy = fields.next()? == "@";
+            let parents = fields
+                .next()
+                .map(|parents| {
+                    parents
+                        .split(',')
+                        .filter(|parent| !parent.is_empty())
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default();
+            Some(JjCommit {
+                change_id,
+                commit_id,
+                working_copy,
+                parents,
+            })
+        })
+        .collect()
+}
+
+/// The mutable commits as of an operation. Immutable ones are never rewritten.
+fn commits_at(repo: &Repository, operation: &str) -> Result<Vec<JjCommit>, GitAiError> {
+    let output = run_jj(
+        repo,
+        &[
+            "log",
+            "--at-op",
+            operation,
+            "--no-graph",
+            "-r",
+            "mutable()",
+            "-T",
+            COMMIT_TEMPLATE,
+        ],
+    )?;
+    Ok(parse_commits(&output))
+}
+
+/// Which of `change_ids` still exist as of an operation, mutable or not
+fn present_changes(
+    repo: &Repository,
+    operation: &str,
+    change_ids: &[&str],
+) -> Result<HashSet<String>, GitAiError> {
+    if change_ids.is_empty() {
+        return Ok(HashSet::new());
+    }
+    let revset = change_ids
+        .iter()
+        .map(|change_id| format!("present({})", change_id))
+        .collect::<Vec<_>>()
+        .join(" | ");
+    let output = run_jj(
+        repo,
+        &[
+            "log",
+            "--at-op",
+            operation,
+            "--no-graph",
+            "-r",
+            &revset,
+            "-T",
+            COMMIT_TEMPLATE,
+        ],
+    )?;
+    Ok(parse_commits(&output)
+        .into_iter()
+        .map(|commit| commit.change_id)
+        .filter(|change_id| change_ids.contains(&change_id.as_str()))
+        .collect())
+}
+
+/// Commits by change, leaving out divergent changes, which have more than one
+fn by_change(commits: &[JjCommit]) -> HashMap<&str, &JjCommit> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for commit in commits {
+        *counts.entry(commit.change_id.as_str()).or_default() += 1;
+    }
+    commits
+        .iter()
+        .filter(|commit| counts[commit.change_id.as_str()] == 1)
+        .map(|commit| (commit.change_id.as_str(), commit))
+        .collect()
+}
+
+fn git_parent(commit: &JjCommit) -> Option<String> {
+    commit
+        .parents
+        .first()
+        .filter(|parent| parent.as_str() != ROOT_COMMIT)
+        .cloned()
+}
+
+/// `commit` and its ancestors among `commits`, parents first, keeping those in `wanted`
+fn ancestors_in<'a>(
+    commit: &'a str,
+    commits: &HashMap<&str, &'a JjCommit>,
+    wanted: &HashSet<&str>,
+    seen: &mut HashSet<&'a str>,
+    out: &mut Vec<&'a str>,
+) {
+    if !seen.insert(commit) {
+        return;
+    }
+    if let Some(jj_commit) = commits.get(commit) {
+        for parent in &jj_commit.parents {
+            ancestors_in(parent, commits, wanted, seen, out);
+        }
+    }
+    if wanted.contains(commit) {
+        out.push(commit);
+    }
+}
+
+/// The rewrite events for what one operation did, given the mutable commits before and after
+/// it and the changes it abandoned
+pub fn events_between(
+    before: &[JjCommit],
+    after: &[JjCommit],
+    abandoned: &HashSet<String>,
+) -> Vec<RewriteLogEvent> {
+    let before_changes = by_change(before);
+    let after_changes = by_change(after);
+    let mut events = Vec::new();
+    let mut handled: HashSet<&str> = HashSet::new();
+
+    // git's HEAD is the working copy's parent, so the working log belongs to that commit
+    if let Some(working_copy) = before_changes.values().find(|commit| commit.working_copy) {
+        handled.insert(working_copy.change_id.as_str());
+        match after_changes.get(working_copy.change_id.as_str()) {
+            // `jj commit` or `jj new`: the working copy became a commit of its own
+            Some(now) if !now.working_copy && now.parents == working_copy.parents => {
+                events.push(RewriteLogEvent::commit(
+                    git_parent(working_copy),
+                    now.commit_id.clone(),
+                ));
+            }
+            // `jj squash`: the working copy was folded into its parent, like an amend
+            None if abandoned.contains(&working_copy.change_id) => {
+                let parent = before_changes.values().find(|commit| {
+                    Some(&commit.commit_id) == working_copy.parents.first()
+                        && working_copy.parents.len() == 1
+                });
+                if let Some(parent) = parent
+                    && let Some(rewritten) = after_changes.get(parent.change_id.as_str())
+                    && rewritten.commit_id != parent.commit_id
+                {
+                    handled.insert(parent.change_id.as_str());
+                    events.push(RewriteLogEvent::commit_amend(
+                        parent.commit_id.clone(),
+                        rewritten.commit_id.clone(),
+                    ));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Everything else rewritten (rebased, described, split, squashed into) or abandoned
+    let mut new_commit_for: HashMap<&str, &str> = HashMap::new();
+    let mut olds: HashSet<&str> = HashSet::new();
+    for (change_id, commit) in &before_changes {
+        if handled.contains(change_id) {
+            continue;
+        }
+        match after_changes.get(change_id) {
+            Some(now) if now.commit_id != commit.commit_id => {
+                new_commit_for.insert(commit.commit_id.as_str(), now.commit_id.as_str());
+                olds.insert(commit.commit_id.as_str());
+            }
+            None if abandoned.contains(*change_id) => {
+                olds.insert(commit.commit_id.as_str());
+            }
+            _ => {}
+        }
+    }
+    if new_commit_for.is_empty() {
+        return events;
+    }
+
+    let before_commits: HashMap<&str, &JjCommit> = before
+        .iter()
+        .map(|commit| (commit.commit_id.as_str(), commit))
+        .collect();
+    let after_commits: HashMap<&str, &JjCommit> = after
+        .iter()
+        .map(|commit| (commit.commit_id.as_str(), commit))
+        .collect();
+
+    // Each rewritten line of history is replayed like a rebase of its tip
+    let mut not_tips: HashSet<&str> = HashSet::new();
+    for old in &olds {
+        if let Some(commit) = before_commits.get(old) {
+            let mut seen = HashSet::new();
+            let mut ancestors = Vec::new();
+            for parent in &commit.parents {
+                ancestors_in(parent, &before_commits, &olds, &mut seen, &mut ancestors);
+            }
+            not_tips.extend(ancestors);
+        }
+    }
+    let mut tips: Vec<&str> = olds.difference(&not_tips).copied().collect();
+    tips.sort();
+    for tip in tips {
+        let mut original_commits = Vec::new();
+        ancestors_in(
+            tip,
+            &before_commits,
+            &olds,
+            &mut HashSet::new(),
+            &mut original_commits,
+        );
+        let new_commits: Vec<String> = original_commits
+            .iter()
+            .filter_map(|old| new_commit_for.get(old).map(|new| new.to_string()))
+            .collect();
+        let (Some(first_new), Some(new_head)) = (new_commits.first(), new_commits.last()) else {
+            continue;
+        };
+        let onto = after_commits
+            .get(first_new.as_str())
+            .and_then(|commit| git_parent(commit));
+        events.push(RewriteLogEvent::rebase_complete(RebaseCompleteEvent::new(
+            tip.to_string(),
+            new_head.clone(),
+            false,
+            original_commits.iter().map(|old| old.to_string()).collect(),
+            new_commits.clone(),
+            None,
+            onto,
+        )));
+    }
+    events
+}
+
+/// The rewrite events for the jj operations since the last call, oldest first. The first call
+/// in a repository only notes where the operation log is.
+fn pending_events(repo: &Repository) -> Result<Vec<RewriteLogEvent>, GitAiError> {
+    let last_seen = fs::read_to_string(&repo.storage.jj_operation)
+        .ok()
+        .map(|id| id.trim().to_string());
+    if let Some(last_seen) = &last_seen
+        && operation_heads(repo).is_some_and(|heads| heads == [last_seen.as_str()])
+    {
+        return Ok(Vec::new());
+    }
+
+    let operations = operation_ids(repo)?;
+    let Some(latest) = operations.first() else {
+        return Ok(Vec::new());
+    };
+    fs::write(&repo.storage.jj_operation, latest)?;
+
+    let Some(last_seen) = last_seen else {
+        return Ok(Vec::new());
+    };
+    let Some(position) = operations.iter().position(|id| *id == last_seen) else {
+        debug_log("jj operation log has moved past the last operation seen; skipping");
+        return Ok(Vec::new());
+    };
+
+    let mut events = Vec::new();
+    let mut before = commits_at(repo, &last_seen)?;
+    for operation in operations[..position].iter().rev() {
+        let after = commits_at(repo, operation)?;
+        let after_changes: HashSet<&str> = after.iter().map(|c| c.change_id.as_str()).collect();
+        let missing: Vec<&str> = before
+            .iter()
+            .map(|commit| commit.change_id.as_str())
+            .filter(|change_id| !after_changes.contains(change_id))
+            .collect();
+        let present = present_changes(repo, operation, &missing)?;
+        let abandoned = missing
+            .into_iter()
+            .filter(|change_id| !present.contains(*change_id))
+            .map(str::to_string)
+            .collect();
+        events.extend(events_between(&before, &after, &abandoned));
+        before = after;
+    }
+    Ok(events)
+}
+
+/// Brings attribution up to date with what jj did since the last git or git-ai command, in a
+/// jj colocated repository. Does nothing anywhere else.
+pub fn sync_operations(repo: &mut Repository) {
+    if !is_colocated(repo) {
+        return;
+    }
+    let events = match pending_events(repo) {
+        Ok(events) => events,
+        Err(e) => {
+            debug_log(&format!("Failed to read the jj operation log: {}", e));
+            return;
+        }
+    };
+    if events.is_empty() {
+        return;
+    }
+    let author = commit_hooks::get_commit_default_author(repo, &[]);
+    for event in events {
+        repo.handle_rewrite_log_event(event, author.clone(), true, true);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commit(change_id: &str, commit_id: &str, parents: &[&str]) -> JjCommit {
+        JjCommit {
+            change_id: change_id.to_string(),
+            commit_id: commit_id.to_string(),
+            working_copy: false,
+            parents: parents.iter().map(|parent| parent.to_string()).collect(),
+        }
+    }
+
+    fn working_copy(change_id: &str, commit_id: &str, parents: &[&str]) -> JjCommit {
+        JjCommit {
+            working_copy: true,
+            ..commit(change_id, commit_id, parents)
+        }
+    }
+
+    #[test]
+    fn test_parse_commits() {
+        let output = "kxyz 1111 - 0000\nwabc 2222 @ 1111\nmerg 3333 - 1111,2222\n";
+        assert_eq!(
+            parse_commits(output),
+            vec![
+                commit("kxyz", "1111", &["0000"]),
+                working_copy("wabc", "2222", &["1111"]),
+                commit("merg", "3333", &["1111", "2222"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_working_copy_committed() {
+        let before = vec![
+            commit("a", "a1", &["base"]),
+            working_copy("w", "w1", &["a1"]),
+        ];
+        let after = vec![
+            commit("a", "a1", &["base"]),
+            commit("w", "w2", &["a1"]),
+            working_copy("n", "n1", &["w2"]),
+        ];
+        assert_eq!(
+            events_between(&before, &after, &HashSet::new()),
+            vec![RewriteLogEvent::commit(
+                Some("a1".to_string()),
+                "w2".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_working_copy_squashed_into_parent() {
+        let before = vec![
+            commit("a", "a1", &["base"]),
+            working_copy("w", "w1", &["a1"]),
+        ];
+        let after = vec![
+            commit("a", "a2", &["base"]),
+            working_copy("n", "n1", &["a2"]),
+        ];
+        let abandoned = HashSet::from(["w".to_string()]);
+        assert_eq!(
+            events_between(&before, &after, &abandoned),
+            vec![RewriteLogEvent::commit_amend(
+                "a1".to_string(),
+                "a2".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_rebase_and_squash_of_a_stack() {
+        // a <- b <- c rebased onto `main2`, with c squashed into b
+        let before = vec![
+            commit("a", "a1", &["main1"]),
+            commit("b", "b1", &["a1"]),
+            commit("c", "c1", &["b1"]),
+            working_copy("w", "w1", &["c1"]),
+        ];
+        let after = vec![
+            commit("a", "a2", &["main2"]),
+            commit("b", "b2", &["a2"]),
+            working_copy("w", "w2", &["b2"]),
+        ];
+        let abandoned = HashSet::from(["c".to_string()]);
+        assert_eq!(
+            events_between(&before, &after, &abandoned),
+            vec![RewriteLogEvent::rebase_complete(RebaseCompleteEvent::new(
+                "c1".to_string(),
+                "b2".to_string(),
+                false,
+                vec!["a1".to_string(), "b1".to_string(), "c1".to_string()],
+                vec!["a2".to_string(), "b2".to_string()],
+                None,
+                Some("main2".to_string()),
+            ))]
+        );
+    }
+
+    #[test]
+    fn test_working_copy_snapshot_is_not_a_rewrite() {
+        let before = vec![working_copy("w", "w1", &["a1"])];
+        let after = vec![working_copy("w", "w2", &["a1"])];
+        assert!(events_between(&before, &after, &HashSet::new()).is_empty());
+    }
+}
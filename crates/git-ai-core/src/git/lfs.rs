@@ -0,0 +1,255 @@
+//! Git LFS support
+//!
+//! A path tracked with LFS (`filter=lfs` in gitattributes) is a small pointer in every tree
+//! and the real file in the working tree, so comparing the two attributes pointer text or
+//! rewrites the whole file. `lfs_files` in the config decides what git-ai does with them:
+//!
+//! - `skip` (default): LFS paths are left out of checkpoints and stats, and each commit's
+//!   authorship log lists the ones it changed under `skipped_files`.
+//! - `smudge`: pointers read from trees are run through git's filters, so both sides of every
+//!   comparison are the real file. Needs git-lfs installed and may download objects.
+
+use crate::error::GitAiError;
+use crate::git::repo_path;
+use crate::git::repository::{Repository, exec_git, exec_git_stdin};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// What `skipped_files` in the authorship log records for an LFS path
+pub const SKIPPED_LFS: &str = "lfs";
+
+/// First line of every LFS pointer file
+const POINTER_HEADER: &[u8] = b"version https://git-lfs.github.com/spec/v1\n";
+/// Pointers are a few short lines; the spec caps them well under this
+const MAX_POINTER_SIZE: usize = 1024;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LfsMode {
+    /// LFS paths aren't attributed (default)
+    #[default]
+    Skip,
+    /// LFS pointers are smudged into file content before they're compared
+    Smudge,
+}
+
+impl LfsMode {
+    pub fn parse(input: &str) -> Option<Self> {
+        match input.trim().to_lowercase().as_str() {
+            "skip" => Some(LfsMode::Skip),
+            "smudge" => Some(LfsMode::Smudge),
+            _ => None,
+        }
+    }
+}
+
+/// Returns true if `content` is an LFS pointer rather than file content
+pub fn is_lfs_pointer(content: &[u8]) -> bool {
+    content.len() <= MAX_POINTER_SIZE && content.starts_with(POINTER_HEADER)
+}
+
+/// The paths LFS tracks, out of those it was built for
+#[derive(Debug, Clone, Default)]
+pub struct LfsFilter {
+    paths: HashSet<String>,
+}
+
+impl LfsFilter {
+    /// Build a filter for the given paths, resolving their `filter` gitattribute in a single
+    /// `git check-attr` call
+    pub fn for_paths<'a, I>(repo: &Repository, paths: I) -> Result<Self, GitAiError>
+    where
+        I: IntoIterator<Item = &'a String>,
+    {
+        let mut stdin_data = Vec::new();
+        for path in paths {
+            stdin_data.extend(repo_path::key_to_bytes(path));
+            stdin_data.push(0);
+        }
+        if stdin_data.is_empty() {
+            return Ok(Self::default());
+        }
+
+        let mut args = repo.global_args_for_exec();
+        args.push("check-attr".to_string());
+        args.push("-z".to_string());
+        args.push("--stdin".to_string());
+        args.push("filter".to_string());
+
+        let output = exec_git_stdin(&args, &stdin_data)?;
+        Ok(Self::from_check_attr_output(&output.stdout))
+    }
+
+    /// Parse `git check-attr -z` output (`<path> NUL filter NUL <value> NUL` records)
+    fn from_check_attr_output(output: &[u8]) -> Self {
+        let fields = repo_path::split_nul_fields(output);
+        let paths = fields
+            .chunks(3)
+            .filter(|record| record.len() == 3 && !record[0].is_empty() && record[2] == "lfs")
+            .map(|record| record[0].to_string())
+            .collect();
+        LfsFilter { paths }
+    }
+
+    pub fn is_lfs(&self, path: &str) -> bool {
+        self.paths.contains(path)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+}
+
+/// Remove LFS paths from a list of repository-relative paths when they're skipped. Keeps
+/// everything if gitattributes can't be resolved.
+pub fn filter_lfs_paths(repo: &Repository, paths: Vec<String>) -> Vec<String> {
+    if crate::config::Config::get().lfs_mode() != LfsMode::Skip {
+        return paths;
+    }
+    let filter = LfsFilter::for_paths(repo, paths.iter()).unwrap_or_default();
+    if filter.is_empty() {
+        return paths;
+    }
+    paths
+        .into_iter()
+        .filter(|path| !filter.is_lfs(path))
+        .collect()
+}
+
+/// The LFS paths a commit changed, as recorded under `skipped_files` in its authorship log.
+/// Empty unless LFS paths are skipped.
+pub fn skipped_lfs_files(
+    repo: &Repository,
+    from_ref: &str,
+    commit_sha: &str,
+) -> BTreeMap<String, String> {
+    if crate::config::Config::get().lfs_mode() != LfsMode::Skip {
+        return BTreeMap::new();
+    }
+    let changed = repo
+        .diff_changed_files(from_ref, commit_sha)
+        .unwrap_or_default();
+    let filter = LfsFilter::for_paths(repo, changed.iter()).unwrap_or_default();
+    changed
+        .into_iter()
+        .filter(|path| filter.is_lfs(path))
+        .map(|path| (path, SKIPPED_LFS.to_string()))
+        .collect()
+}
+
+/// `content` of the blob `blob_id` at `path`, smudged into the file it points to if it's an LFS
+/// pointer and pointers are smudged. Falls back to the pointer if smudging fails.
+pub fn smudge_blob_content(
+    repo: &Repository,
+    path: &str,
+    blob_id: &str,
+    content: Vec<u8>,
+) -> Vec<u8> {
+    if crate::config::Config::get().lfs_mode() != LfsMode::Smudge || !is_lfs_pointer(&content) {
+        return content;
+    }
+    let mut args = repo.global_args_for_exec();
+    args.push("cat-file".to_string());
+    args.push("--filters".to_string());
+    args.push(format!("--path={}", path));
+    args.push(blob_id.to_string());
+    match exec_git(&args) {
+        Ok(output) => output.stdout,
+        Err(e) => {
+            crate::utils::debug_log(&format!("failed to smudge LFS pointer {}: {}", path, e));
+            content
+        }
+    }
+}
+
+/// Lines added and deleted in an LFS file by `commit_sha`, counted on smudged content rather
+/// than on the pointers `git show --numstat` compares
+pub fn smudged_numstat(repo: &Repository, commit_sha: &str, path: &str) -> (u32, u32) {
+    let (added, deleted) =
+        smudged_line_changes(repo, &format!("{}^", commit_sha), commit_sha, path);
+    (added.len() as u32, deleted)
+}
+
+/// Replaces the added lines of LFS files in `added_lines` (from a diff of `from_ref` to
+/// `to_ref`, which compares pointers) with the lines added to their smudged content, when
+/// pointers are smudged
+pub fn smudge_added_lines(
+    repo: &Repository,
+    from_ref: &str,
+    to_ref: &str,
+    added_lines: &mut HashMap<String, Vec<u32>>,
+) {
+    if crate::config::Config::get().lfs_mode() != LfsMode::Smudge || added_lines.is_empty() {
+        return;
+    }
+    let filter = LfsFilter::for_paths(repo, added_lines.keys()).unwrap_or_default();
+    for (path, lines) in added_lines.iter_mut() {
+        if filter.is_lfs(path) {
+            *lines = smudged_line_changes(repo, from_ref, to_ref, path).0;
+        }
+    }
+}
+
+/// The 1-based line numbers an LFS file gained between `from_ref` and `to_ref`, and how many it
+/// lost, comparing smudged content. A side without the file is empty.
+fn smudged_line_changes(
+    repo: &Repository,
+    from_ref: &str,
+    to_ref: &str,
+    path: &str,
+) -> (Vec<u32>, u32) {
+    let content_at = |rev: &str| -> String {
+        let mut args = repo.global_args_for_exec();
+        args.push("cat-file".to_string());
+        args.push("--filters".to_string());
+        args.push(format!("{}:{}", rev, path));
+        exec_git(&args)
+            .map(|output| String::from_utf8_lossy(&output.stdout).to_string())
+            .unwrap_or_default()
+    };
+    let old = content_at(from_ref);
+    let new = content_at(to_ref);
+    let mut added = Vec::new();
+    let mut deleted = 0;
+    for change in similar::TextDiff::from_lines(&old, &new).iter_all_changes() {
+        match (change.tag(), change.new_index()) {
+            (similar::ChangeTag::Insert, Some(index)) => added.push(index as u32 + 1),
+            (similar::ChangeTag::Delete, _) => deleted += 1,
+            _ => {}
+        }
+    }
+    (added, deleted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_lfs_pointer() {
+        let pointer = b"version https://git-lfs.github.com/spec/v1\n\
+                        oid sha256:4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393\n\
+                        size 12345\n";
+        assert!(is_lfs_pointer(pointer));
+        assert!(!is_lfs_pointer(b"fn main() {}\n"));
+        let mut large = pointer.to_vec();
+        large.extend(std::iter::repeat_n(b'x', MAX_POINTER_SIZE));
+        assert!(!is_lfs_pointer(&large));
+    }
+
+    #[test]
+    fn test_check_attr_output_keeps_lfs_paths() {
+        let output = "assets/model.bin\0filter\0lfs\0src/lib.rs\0filter\0unspecified\0\
+                      data.csv\0filter\0crypt\0";
+        let filter = LfsFilter::from_check_attr_output(output.as_bytes());
+
+        assert!(filter.is_lfs("assets/model.bin"));
+        assert!(!filter.is_lfs("src/lib.rs"));
+        assert!(!filter.is_lfs("data.csv"));
+    }
+
+    #[test]
+    fn test_parse_mode() {
+        assert_eq!(LfsMode::parse(" Smudge "), Some(LfsMode::Smudge));
+        assert_eq!(LfsMode::parse("skip"), Some(LfsMode::Skip));
+        assert_eq!(LfsMode::parse("ignore"), None);
+    }
+}
@@ -1,12 +1,20 @@
 pub mod cli_parser;
+pub mod codeowners;
 pub mod diff_tree_to_tree;
+pub mod hosting;
+pub mod jj;
+pub mod lfs;
+pub mod path_scope;
 pub mod refs;
+pub mod repo_config;
+pub mod repo_path;
 pub mod repository;
 pub use repository::{find_repository, find_repository_in_path, from_bare_repository};
 pub mod repo_storage;
 pub mod rewrite_log;
 pub mod status;
 pub mod sync_authorship;
+pub mod workspace;
 
 #[cfg(feature = "test-support")]
 pub mod test_utils;
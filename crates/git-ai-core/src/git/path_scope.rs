@@ -0,0 +1,209 @@
+//! Limits git-ai to part of a monorepo.
+//!
+//! A scope is a list of pathspecs: `services/payments` (a directory or file and everything
+//! under it), globs such as `services/*/api/**`, and exclusions written `:!path` or
+//! `:(exclude)path`. Paths outside the scope are never checkpointed, counted in stats or
+//! carried through rebases, cherry-picks, amends and resets, so their attribution isn't
+//! computed at all.
+//!
+//! The scope comes from the global `git-ai --path <spec>` option, else from `ai.defaultPathspec`
+//! in git config or in `.gitai` (each repeatable). With none of those everything is in scope.
+
+use crate::error::GitAiError;
+use crate::git::repo_config::RepoConfig;
+use crate::git::repository::{Repository, exec_git};
+use glob::{MatchOptions, Pattern};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+
+/// Pathspecs given with `--path` on the command line, which override any configured default
+static CLI_PATHSPECS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Scope per repository, so the config is read once per process
+static REPO_SCOPES: OnceLock<Mutex<HashMap<PathBuf, PathScope>>> = OnceLock::new();
+
+pub fn add_cli_pathspec(spec: &str) -> Result<(), GitAiError> {
+    // Parse now so a bad glob fails before the command starts
+    PathSpec::parse(spec)?;
+    CLI_PATHSPECS.lock().unwrap().push(spec.to_string());
+    Ok(())
+}
+
+#[derive(Debug, Clone)]
+struct PathSpec {
+    /// Directory or file the spec names literally, without a trailing slash
+    literal: Option<String>,
+    glob: Option<Pattern>,
+    exclude: bool,
+}
+
+impl PathSpec {
+    fn parse(spec: &str) -> Result<PathSpec, GitAiError> {
+        let (spec, exclude) = match spec
+            .strip_prefix(":(exclude)")
+            .or_else(|| spec.strip_prefix(":!"))
+            .or_else(|| spec.strip_prefix(":^"))
+        {
+            Some(rest) => (rest, true),
+            None => (spec, false),
+        };
+        let spec = spec.trim_start_matches("./").trim_end_matches('/');
+        if spec.is_empty() {
+            return Err(GitAiError::Generic("empty pathspec".to_string()));
+        }
+
+        let is_glob = spec.contains(['*', '?', '[']);
+        let glob =
+            if is_glob {
+                Some(Pattern::new(spec).map_err(|e| {
+                    GitAiError::Generic(format!("invalid pathspec '{}': {}", spec, e))
+                })?)
+            } else {
+                None
+            };
+        Ok(PathSpec {
+            literal: (!is_glob).then(|| spec.to_string()),
+            glob,
+            exclude,
+        })
+    }
+
+    fn matches(&self, path: &str) -> bool {
+        if let Some(literal) = &self.literal {
+            return path == literal
+                || path
+                    .strip_prefix(literal.as_str())
+                    .is_some_and(|rest| rest.starts_with('/'));
+        }
+        let options = MatchOptions {
+            case_sensitive: true,
+            require_literal_separator: true,
+            require_literal_leading_dot: false,
+        };
+        self.glob
+            .as_ref()
+            .is_some_and(|glob| glob.matches_with(path, options))
+    }
+}
+
+/// The part of the repository git-ai works on
+#[derive(Debug, Clone, Default)]
+pub struct PathScope {
+    specs: Vec<PathSpec>,
+}
+
+impl PathScope {
+    pub fn parse<S: AsRef<str>>(specs: &[S]) -> Result<PathScope, GitAiError> {
+        Ok(PathScope {
+            specs: specs
+                .iter()
+                .map(|spec| PathSpec::parse(spec.as_ref()))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    /// The scope for commands run in `repo`: `--path`, else `ai.defaultPathspec`, else everything
+    pub fn for_repo(repo: &Repository) -> PathScope {
+        let cli = CLI_PATHSPECS.lock().unwrap().clone();
+        if !cli.is_empty() {
+            return PathScope::parse(&cli).unwrap_or_default();
+        }
+
+        let scopes = REPO_SCOPES.get_or_init(|| Mutex::new(HashMap::new()));
+        let key = repo.path().to_path_buf();
+        if let Some(scope) = scopes.lock().unwrap().get(&key) {
+            return scope.clone();
+        }
+        let scope = configured_pathspecs(repo)
+            .and_then(|specs| PathScope::parse(&specs))
+            .unwrap_or_else(|e| {
+                eprintln!("warning: ignoring ai.defaultPathspec: {}", e);
+                PathScope::default()
+            });
+        scopes.lock().unwrap().insert(key, scope.clone());
+        scope
+    }
+
+    /// Whether the scope covers the whole repository
+    pub fn is_everything(&self) -> bool {
+        self.specs.is_empty()
+    }
+
+    /// Whether a repository-relative path (with `/` separators) is in scope
+    pub fn contains(&self, path: &str) -> bool {
+        if self
+            .specs
+            .iter()
+            .any(|spec| spec.exclude && spec.matches(path))
+        {
+            return false;
+        }
+        let mut includes = self.specs.iter().filter(|spec| !spec.exclude).peekable();
+        // Only exclusions: everything else is in scope, as with git
+        includes.peek().is_none() || includes.any(|spec| spec.matches(path))
+    }
+
+    /// Keeps the paths that are in scope
+    pub fn retain<I: FromIterator<String>>(&self, paths: impl IntoIterator<Item = String>) -> I {
+        paths
+            .into_iter()
+            .filter(|path| self.contains(path))
+            .collect()
+    }
+}
+
+/// `ai.defaultPathspec` from git config, else from `.gitai`
+fn configured_pathspecs(repo: &Repository) -> Result<Vec<String>, GitAiError> {
+    let mut args = repo.global_args_for_exec();
+    args.push("config".to_string());
+    args.push("--get-all".to_string());
+    args.push("ai.defaultPathspec".to_string());
+    let from_git_config = match exec_git(&args) {
+        Ok(output) => String::from_utf8(output.stdout)?
+            .lines()
+            .map(str::to_string)
+            .collect(),
+        // Exit code 1: not set
+        Err(GitAiError::GitCliError { code: Some(1), .. }) => Vec::new(),
+        Err(e) => return Err(e),
+    };
+    if !from_git_config.is_empty() {
+        return Ok(from_git_config);
+    }
+    Ok(RepoConfig::load(repo).default_pathspecs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_literal_pathspec_matches_directory_and_file() {
+        let scope = PathScope::parse(&["services/payments/"]).unwrap();
+        assert!(scope.contains("services/payments"));
+        assert!(scope.contains("services/payments/src/lib.rs"));
+        assert!(!scope.contains("services/payments-v2/lib.rs"));
+        assert!(!scope.contains("services/billing/lib.rs"));
+    }
+
+    #[test]
+    fn test_glob_and_exclude_pathspecs() {
+        let scope = PathScope::parse(&["services/*/api/**", ":!services/legacy"]).unwrap();
+        assert!(scope.contains("services/payments/api/handler.rs"));
+        assert!(!scope.contains("services/payments/core/handler.rs"));
+        assert!(!scope.contains("services/legacy/api/handler.rs"));
+
+        let recursive = PathScope::parse(&["services/payments/**"]).unwrap();
+        assert!(recursive.contains("services/payments/src/lib.rs"));
+        assert!(!recursive.contains("services/billing/src/lib.rs"));
+
+        let excludes_only = PathScope::parse(&[":(exclude)vendor"]).unwrap();
+        assert!(excludes_only.contains("src/main.rs"));
+        assert!(!excludes_only.contains("vendor/lib.rs"));
+
+        assert!(PathScope::default().is_everything());
+        assert!(PathScope::default().contains("anything.rs"));
+        assert!(PathScope::parse(&["src/[a"]).is_err());
+    }
+}
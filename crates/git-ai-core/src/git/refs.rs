@@ -1,23 +1,50 @@
 use crate::authorship::authorship_log_serialization::{AUTHORSHIP_LOG_VERSION, AuthorshipLog};
 use crate::authorship::working_log::Checkpoint;
+use crate::commands::hooks::shadow_hooks;
 use crate::error::GitAiError;
 use crate::git::repository::{Repository, exec_git, exec_git_stdin};
 use crate::utils::debug_log;
+use crate::utils::is_newer_version;
 use serde_json;
 use std::collections::{HashMap, HashSet};
 
 // Modern refspecs without force to enable proper merging
 pub const AI_AUTHORSHIP_REFNAME: &str = "ai";
 pub const AI_AUTHORSHIP_PUSH_REFSPEC: &str = "refs/notes/ai:refs/notes/ai";
+/// Review marks from `git-ai review mark`, kept apart from the authorship notes
+pub const AI_REVIEW_REFNAME: &str = "ai-review";
+/// Where refs/notes/ai is saved, as `<prefix><notes commit>`, before it's rewritten
+pub const AI_NOTES_BACKUP_PREFIX: &str = "refs/notes/ai-backup/";
 
 pub fn notes_add(
     repo: &Repository,
     commit_sha: &str,
     note_content: &str,
 ) -> Result<(), GitAiError> {
+    notes_add_to_ref(repo, AI_AUTHORSHIP_REFNAME, commit_sha, note_content)
+}
+
+/// Adds a note under any notes ref (`commits` and `refs/notes/commits` are the same ref),
+/// replacing the commit's existing note there
+pub fn notes_add_to_ref(
+    repo: &Repository,
+    notes_ref: &str,
+    commit_sha: &str,
+    note_content: &str,
+) -> Result<(), GitAiError> {
+    let _span = tracing::info_span!("notes_write", notes_ref, commit = commit_sha).entered();
+    tracing::debug!(
+        notes_ref,
+        commit = commit_sha,
+        note = note_content,
+        "note written"
+    );
+    if repo.storage.shadowed {
+        return shadow_hooks::record_note(repo, notes_ref, commit_sha, note_content);
+    }
     let mut args = repo.global_args_for_exec();
     args.push("notes".to_string());
-    args.push("--ref=ai".to_string());
+    args.push(format!("--ref={}", notes_ref));
     args.push("add".to_string());
     args.push("-f".to_string()); // Always force overwrite
     args.push("-F".to_string());
@@ -42,7 +69,7 @@ pub enum CommitAuthorship {
     Log {
         sha: String,
         git_author: String,
-        authorship_log: AuthorshipLog,
+        authorship_log: Box<AuthorshipLog>,
     },
 }
 pub fn get_commits_with_notes_from_list(
@@ -103,7 +130,7 @@ pub fn get_commits_with_notes_from_list(
             result.push(CommitAuthorship::Log {
                 sha: sha.clone(),
                 git_author,
-                authorship_log,
+                authorship_log: Box::new(authorship_log),
             });
         } else {
             result.push(CommitAuthorship::NoLog {
@@ -118,9 +145,20 @@ pub fn get_commits_with_notes_from_list(
 
 // Show an authorship note and return its JSON content if found, or None if it doesn't exist.
 pub fn show_authorship_note(repo: &Repository, commit_sha: &str) -> Option<String> {
+    show_note(repo, AI_AUTHORSHIP_REFNAME, commit_sha)
+}
+
+/// The commit's note under any notes ref, or None if it has none there
+pub fn show_note(repo: &Repository, notes_ref: &str, commit_sha: &str) -> Option<String> {
+    // Shadow mode's notes were only logged, and later steps build on them as if written
+    if repo.storage.shadowed
+        && let Some(note) = shadow_hooks::logged_note(repo, notes_ref, commit_sha)
+    {
+        return Some(note);
+    }
     let mut args = repo.global_args_for_exec();
     args.push("notes".to_string());
-    args.push("--ref=ai".to_string());
+    args.push(format!("--ref={}", notes_ref));
     args.push("show".to_string());
     args.push(commit_sha.to_string());
 
@@ -146,8 +184,10 @@ pub fn get_reference_as_working_log(
     repo: &Repository,
     commit_sha: &str,
 ) -> Result<Vec<Checkpoint>, GitAiError> {
-    let content = show_authorship_note(repo, commit_sha)
-        .ok_or_else(|| GitAiError::Generic("No authorship note found".to_string()))?;
+    let content =
+        show_authorship_note(repo, commit_sha).ok_or_else(|| GitAiError::NoAuthorshipLog {
+            commit: commit_sha.to_string(),
+        })?;
     let working_log = serde_json::from_str(&content)?;
     Ok(working_log)
 }
@@ -156,25 +196,40 @@ pub fn get_reference_as_authorship_log_v3(
     repo: &Repository,
     commit_sha: &str,
 ) -> Result<AuthorshipLog, GitAiError> {
-    let content = show_authorship_note(repo, commit_sha)
-        .ok_or_else(|| GitAiError::Generic("No authorship note found".to_string()))?;
+    let content =
+        show_authorship_note(repo, commit_sha).ok_or_else(|| GitAiError::NoAuthorshipLog {
+            commit: commit_sha.to_string(),
+        })?;
 
     // Try to deserialize as AuthorshipLog
     let authorship_log = match AuthorshipLog::deserialize_from_string(&content) {
         Ok(log) => log,
-        Err(_) => {
-            return Err(GitAiError::Generic(
-                "Failed to parse authorship log".to_string(),
-            ));
+        Err(e) => {
+            return Err(GitAiError::InvalidAuthorshipLog {
+                commit: commit_sha.to_string(),
+                reason: e.to_string(),
+            });
         }
     };
 
     // Check version compatibility
     if authorship_log.metadata.schema_version != AUTHORSHIP_LOG_VERSION {
-        return Err(GitAiError::Generic(format!(
-            "Unsupported authorship log version: {} (expected: {})",
-            authorship_log.metadata.schema_version, AUTHORSHIP_LOG_VERSION
-        )));
+        let schema = |version: &str| version.trim_start_matches("authorship/").to_string();
+        let upgrade_hint = if is_newer_version(
+            &schema(&authorship_log.metadata.schema_version),
+            &schema(AUTHORSHIP_LOG_VERSION),
+        ) {
+            "; it was written by a newer git-ai, run `git-ai upgrade` to read it"
+        } else {
+            ""
+        };
+        return Err(GitAiError::InvalidAuthorshipLog {
+            commit: commit_sha.to_string(),
+            reason: format!(
+                "unsupported version {} (expected {}){}",
+                authorship_log.metadata.schema_version, AUTHORSHIP_LOG_VERSION, upgrade_hint
+            ),
+        });
     }
 
     Ok(authorship_log)
@@ -221,6 +276,27 @@ mod tests {
         );
         assert!(non_existent_content.is_none());
     }
+
+    #[test]
+    fn test_get_reference_as_authorship_log_v3_typed_errors() {
+        let tmp_repo = TmpRepo::new().unwrap();
+        tmp_repo.write_file("a.txt", "one\n", true).unwrap();
+        tmp_repo.commit_with_message("Initial commit").unwrap();
+        let sha = tmp_repo.get_head_commit_sha().unwrap();
+        let repo = tmp_repo.gitai_repo();
+
+        notes_add(repo, &sha, "not an authorship log").unwrap();
+        assert!(matches!(
+            get_reference_as_authorship_log_v3(repo, &sha),
+            Err(GitAiError::InvalidAuthorshipLog { commit, .. }) if commit == sha
+        ));
+
+        repo.git(&["notes", "--ref=ai", "remove", &sha]).unwrap();
+        assert!(matches!(
+            get_reference_as_authorship_log_v3(repo, &sha),
+            Err(GitAiError::NoAuthorshipLog { commit }) if commit == sha
+        ));
+    }
 }
 
 /// Sanitize a remote name to create a safe ref name
@@ -294,6 +370,97 @@ pub fn copy_ref(repo: &Repository, source_ref: &str, dest_ref: &str) -> Result<(
     Ok(())
 }
 
+/// Saves refs/notes/ai to `refs/notes/ai-backup/<notes commit>` before something rewrites or
+/// removes notes, so `git-ai undo-notes` can put them back. Returns the backup ref, or None if
+/// there are no notes yet.
+pub fn backup_ai_notes(repo: &Repository) -> Result<Option<String>, GitAiError> {
+    let notes_ref = format!("refs/notes/{}", AI_AUTHORSHIP_REFNAME);
+    let Ok(tip) = repo.git(&["rev-parse", "--verify", "--quiet", &notes_ref]) else {
+        return Ok(None);
+    };
+    let tip = tip.trim();
+    let backup_ref = format!("{}{}", AI_NOTES_BACKUP_PREFIX, tip);
+    if !ref_exists(repo, &backup_ref) {
+        copy_ref(repo, tip, &backup_ref)?;
+    }
+    Ok(Some(backup_ref))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NotesBackup {
+    pub refname: String,
+    /// The notes commit the backup points at
+    pub target: String,
+    /// When that state of the notes was written, in ISO 8601
+    pub date: String,
+}
+
+/// Backups of refs/notes/ai, most recent first
+pub fn list_ai_notes_backups(repo: &Repository) -> Result<Vec<NotesBackup>, GitAiError> {
+    let output = repo.git(&[
+        "for-each-ref",
+        "--sort=-creatordate",
+        "--format=%(refname) %(objectname) %(creatordate:iso-strict)",
+        AI_NOTES_BACKUP_PREFIX,
+    ])?;
+    Ok(output
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(3, ' ');
+            Some(NotesBackup {
+                refname: parts.next()?.to_string(),
+                target: parts.next()?.to_string(),
+                date: parts.next()?.to_string(),
+            })
+        })
+        .collect())
+}
+
+/// Points refs/notes/ai at `target` (a notes commit), backing up the current notes first.
+/// Returns the backup of the notes being replaced.
+pub fn restore_ai_notes(repo: &Repository, target: &str) -> Result<Option<String>, GitAiError> {
+    let backup = backup_ai_notes(repo)?;
+    copy_ref(
+        repo,
+        target,
+        &format!("refs/notes/{}", AI_AUTHORSHIP_REFNAME),
+    )?;
+    Ok(backup)
+}
+
+/// SHAs of every commit that has a note in refs/notes/ai
+pub fn list_ai_notes(repo: &Repository) -> Result<Vec<String>, GitAiError> {
+    let output = repo.git(&["notes", &format!("--ref={}", AI_AUTHORSHIP_REFNAME), "list"])?;
+    // Each line is "<note blob> <annotated commit>"
+    Ok(output
+        .lines()
+        .filter_map(|line| line.split_whitespace().nth(1))
+        .map(str::to_string)
+        .collect())
+}
+
+/// Commits with a note in both refs/notes/ai and `other_ref` where the two notes differ, i.e.
+/// the ones a notes merge has to resolve
+pub fn conflicting_notes(repo: &Repository, other_ref: &str) -> Result<Vec<String>, GitAiError> {
+    let notes_by_commit = |notes_ref: &str| -> Result<HashMap<String, String>, GitAiError> {
+        let output = repo.git(&["notes", &format!("--ref={}", notes_ref), "list"])?;
+        Ok(output
+            .lines()
+            .filter_map(|line| line.split_once(' '))
+            .map(|(blob, commit)| (commit.to_string(), blob.to_string()))
+            .collect())
+    };
+    let ours = notes_by_commit(AI_AUTHORSHIP_REFNAME)?;
+    let theirs = notes_by_commit(other_ref)?;
+    let mut conflicts: Vec<String> = ours
+        .into_iter()
+        .filter(|(commit, blob)| theirs.get(commit).is_some_and(|other| other != blob))
+        .map(|(commit, _)| commit)
+        .collect();
+    conflicts.sort();
+    Ok(conflicts)
+}
+
 /// Search AI notes for a pattern and return matching commit SHAs ordered by commit date (newest first)
 /// Uses git grep to search through refs/notes/ai
 pub fn grep_ai_notes(repo: &Repository, pattern: &str) -> Result<Vec<String>, GitAiError> {
@@ -0,0 +1,226 @@
+//! Settings a team commits to the repository in `.gitai`, a git-config format file at the root
+//! of the working tree:
+//!
+//! ```ini
+//! [ai]
+//!     requiredVersion = 1.4.0
+//!     versionPolicy = refuse
+//!     defaultPathspec = services/payments
+//!     licensePattern = do-not-copy: internal use only
+//!     teamMember = @acme/payments: jane@example.com
+//!     protectedPath = crypto/**
+//!     forbiddenPath = secrets
+//! ```
+
+use crate::error::GitAiError;
+use crate::git::repository::{Repository, exec_git};
+use crate::utils::debug_log;
+use crate::utils::{is_newer_version, semver_from_tag};
+
+pub const REPO_CONFIG_FILE: &str = ".gitai";
+
+/// What to do when the installed git-ai is older than the repository's `ai.requiredVersion`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VersionPolicy {
+    /// Print a warning and carry on
+    #[default]
+    Warn,
+    /// Don't write attribution: git-ai commands fail, and git commands run without git-ai hooks
+    Refuse,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RepoConfig {
+    pub required_version: Option<String>,
+    pub version_policy: VersionPolicy,
+    /// Part of the repository git-ai works on (see `path_scope`), empty for all of it
+    pub default_pathspecs: Vec<String>,
+    /// Extra checks for `scan --licenses`, as (check name, text to look for)
+    pub license_patterns: Vec<(String, String)>,
+    /// Who belongs to which CODEOWNERS team, for `own`, as (team, email)
+    pub team_members: Vec<(String, String)>,
+    /// Pathspecs where AI changes can only be committed once reviewed or acknowledged
+    pub protected_paths: Vec<String>,
+    /// Pathspecs where AI authorship isn't allowed at all
+    pub forbidden_paths: Vec<String>,
+}
+
+impl RepoConfig {
+    /// Reads `.gitai` from the repository's working tree. A missing or unreadable file gives
+    /// the defaults.
+    pub fn load(repo: &Repository) -> RepoConfig {
+        let Ok(workdir) = repo.workdir() else {
+            return RepoConfig::default();
+        };
+        let path = workdir.join(REPO_CONFIG_FILE);
+        if !path.is_file() {
+            return RepoConfig::default();
+        }
+        let args = vec![
+            "config".to_string(),
+            "--file".to_string(),
+            path.to_string_lossy().to_string(),
+            "--get-regexp".to_string(),
+            r"^ai\.".to_string(),
+        ];
+        match exec_git(&args) {
+            Ok(output) => RepoConfig::parse(&String::from_utf8_lossy(&output.stdout)),
+            Err(e) => {
+                debug_log(&format!("Failed to read {}: {}", path.display(), e));
+                RepoConfig::default()
+            }
+        }
+    }
+
+    /// Parses `git config --get-regexp` output (lowercased keys, one "key value" per line)
+    fn parse(output: &str) -> RepoConfig {
+        let mut config = RepoConfig::default();
+        for line in output.lines() {
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            match key {
+                "ai.requiredversion" => {
+                    // Allow "1.4.0", "v1.4.0" and ">=1.4.0"
+                    let version = semver_from_tag(value.trim().trim_start_matches(">="));
+                    config.required_version = Some(version).filter(|v| !v.is_empty());
+                }
+                "ai.defaultpathspec" if !value.trim().is_empty() => {
+                    config.default_pathspecs.push(value.trim().to_string());
+                }
+                "ai.protectedpath" if !value.trim().is_empty() => {
+                    config.protected_paths.push(value.trim().to_string());
+                }
+                "ai.forbiddenpath" if !value.trim().is_empty() => {
+                    config.forbidden_paths.push(value.trim().to_string());
+                }
+                "ai.licensepattern" => {
+                    // "<check>: <text>", or just the text under a generic check name
+                    let (check, text) = match value.split_once(':') {
+                        Some((check, text)) if !check.trim().contains(' ') => {
+                            (check.trim(), text.trim())
+                        }
+                        _ => ("custom", value.trim()),
+                    };
+                    if !text.is_empty() {
+                        config
+                            .license_patterns
+                            .push((check.to_string(), text.to_string()));
+                    }
+                }
+                "ai.teammember" => {
+                    // "<team>: <email>"
+                    let Some((team, email)) = value.split_once(':') else {
+                        continue;
+                    };
+                    if !team.trim().is_empty() && !email.trim().is_empty() {
+                        config
+                            .team_members
+                            .push((team.trim().to_string(), email.trim().to_string()));
+                    }
+                }
+                "ai.versionpolicy" => {
+                    config.version_policy = match value.trim().to_lowercase().as_str() {
+                        "refuse" => VersionPolicy::Refuse,
+                        _ => VersionPolicy::Warn,
+                    }
+                }
+                _ => {}
+            }
+        }
+        config
+    }
+
+    /// Checks the installed git-ai against `ai.requiredVersion`. Too old gives a
+    /// `VersionTooOld` error, which callers turn into a warning or a refusal per the policy.
+    pub fn check_installed_version(&self) -> Result<(), GitAiError> {
+        check_version(self.required_version.as_deref(), env!("CARGO_PKG_VERSION"))
+    }
+}
+
+fn check_version(required: Option<&str>, installed: &str) -> Result<(), GitAiError> {
+    match required {
+        Some(required) if is_newer_version(required, installed) => Err(GitAiError::VersionTooOld {
+            required: required.to_string(),
+            installed: installed.to_string(),
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Runs the repository's version check: None to go ahead (after printing a warning if the
+/// installed git-ai is too old), or the error when the repository refuses older versions
+pub fn enforce_required_version(repo: &Repository) -> Option<GitAiError> {
+    let config = RepoConfig::load(repo);
+    let err = config.check_installed_version().err()?;
+    match config.version_policy {
+        VersionPolicy::Warn => {
+            eprintln!("warning: {}", err);
+            None
+        }
+        VersionPolicy::Refuse => Some(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_repo_config() {
+        let config = RepoConfig::parse("ai.requiredversion >=v1.4.0\nai.versionpolicy Refuse\n");
+        assert_eq!(config.required_version.as_deref(), Some("1.4.0"));
+        assert_eq!(config.version_policy, VersionPolicy::Refuse);
+
+        let config = RepoConfig::parse(
+            "ai.defaultpathspec services/payments\nai.defaultpathspec :!services/payments/vendor\n",
+        );
+        assert_eq!(
+            config.default_pathspecs,
+            vec!["services/payments", ":!services/payments/vendor"]
+        );
+
+        let config = RepoConfig::parse(
+            "ai.licensepattern do-not-copy: Internal use only\nai.licensepattern Acme Corp\n",
+        );
+        assert_eq!(
+            config.license_patterns,
+            vec![
+                ("do-not-copy".to_string(), "Internal use only".to_string()),
+                ("custom".to_string(), "Acme Corp".to_string())
+            ]
+        );
+
+        let config = RepoConfig::parse(
+            "ai.teammember @acme/payments: jane@example.com\nai.teammember no-email\n",
+        );
+        assert_eq!(
+            config.team_members,
+            vec![("@acme/payments".to_string(), "jane@example.com".to_string())]
+        );
+
+        let config = RepoConfig::parse("ai.protectedpath crypto/**\nai.protectedpath migrations\n");
+        assert_eq!(config.protected_paths, vec!["crypto/**", "migrations"]);
+
+        let config = RepoConfig::parse("ai.forbiddenpath secrets\nai.forbiddenpath  \n");
+        assert_eq!(config.forbidden_paths, vec!["secrets"]);
+
+        let config = RepoConfig::parse("ai.other x\n");
+        assert_eq!(config, RepoConfig::default());
+    }
+
+    #[test]
+    fn test_check_version() {
+        assert!(check_version(None, "1.0.0").is_ok());
+        assert!(check_version(Some("1.0.0"), "1.0.0").is_ok());
+        assert!(check_version(Some("0.9.12"), "1.0.0").is_ok());
+        match check_version(Some("1.0.1"), "1.0.0") {
+            Err(GitAiError::VersionTooOld {
+                required,
+                installed,
+            }) => {
+                assert_eq!(required, "1.0.1");
+                assert_eq!(installed, "1.0.0");
+            }
+            other => panic!("expected VersionTooOld, got {:?}", other),
+        }
+    }
+}
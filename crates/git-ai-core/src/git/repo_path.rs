@@ -0,0 +1,218 @@
+//! Paths from the work tree root, as git-ai keys files by them.
+//!
+//! Checkpoints, attributions and authorship logs key files by path as a `String`, but git paths
+//! are bytes and reach us in two spellings: raw (in `-z` output, or everywhere with
+//! `core.quotepath=false`) and C-quoted, like `"caf\303\251.rs"` (line-based output quotes
+//! non-ASCII and special characters by default). Both decode to the same key here: the path
+//! itself when it's UTF-8, else git's quoted spelling of it, which converts back to the exact
+//! bytes with [`key_to_bytes`] when the file is read or passed to git.
+
+use std::path::PathBuf;
+
+/// The key for a path git gave as raw bytes
+pub fn key_from_bytes(bytes: &[u8]) -> String {
+    match std::str::from_utf8(bytes) {
+        Ok(path) => path.to_string(),
+        Err(_) => quote(bytes),
+    }
+}
+
+/// The key for a path field of git's line-based output, quoted or not
+pub fn key_from_git_output(field: &[u8]) -> String {
+    match unquote(field) {
+        Some(bytes) => key_from_bytes(&bytes),
+        None => key_from_bytes(field),
+    }
+}
+
+/// Paths listed one per line by `--name-only`, quoted or not
+pub fn parse_name_only(output: &[u8]) -> Vec<String> {
+    output
+        .split(|byte| *byte == b'\n')
+        .filter(|line| !line.is_empty())
+        .map(key_from_git_output)
+        .collect()
+}
+
+/// The fields of `-z` output, each decoded as a key (fields that aren't paths are ASCII and come
+/// through unchanged)
+pub fn split_nul_fields(output: &[u8]) -> Vec<String> {
+    output
+        .split(|byte| *byte == 0)
+        .map(key_from_bytes)
+        .collect()
+}
+
+/// Lines added and deleted (`None` for a binary file's `-`) and the path of a `--numstat` line,
+/// or `None` if it isn't one
+pub fn parse_numstat_line(line: &[u8]) -> Option<(Option<u32>, Option<u32>, String)> {
+    let mut fields = line.splitn(3, |byte| *byte == b'\t');
+    let count = |field: &[u8]| std::str::from_utf8(field).ok()?.parse::<u32>().ok();
+    let added = fields.next()?;
+    let deleted = fields.next()?;
+    if !added
+        .first()
+        .is_some_and(|byte| byte.is_ascii_digit() || *byte == b'-')
+    {
+        return None;
+    }
+    let path = fields.next().map(key_from_git_output).unwrap_or_default();
+    Some((count(added), count(deleted), path))
+}
+
+/// The path a `+++`/`---` line of a diff names, without its `a/`/`b/` prefix, or `None` for
+/// `/dev/null`. `header` is what follows `+++ `.
+pub fn key_from_diff_header(header: &[u8]) -> Option<String> {
+    let header = header.strip_suffix(b"\t").unwrap_or(header);
+    if header == b"/dev/null" {
+        return None;
+    }
+    let bytes = unquote(header).unwrap_or_else(|| header.to_vec());
+    let path = bytes
+        .strip_prefix(b"a/")
+        .or_else(|| bytes.strip_prefix(b"b/"))
+        .unwrap_or(&bytes);
+    Some(key_from_bytes(path))
+}
+
+/// Returns true if `key` stands for a path that isn't UTF-8
+pub fn is_quoted_key(key: &str) -> bool {
+    unquote(key.as_bytes()).is_some_and(|bytes| std::str::from_utf8(&bytes).is_err())
+}
+
+/// The bytes of the path `key` stands for
+pub fn key_to_bytes(key: &str) -> Vec<u8> {
+    match unquote(key.as_bytes()) {
+        Some(bytes) if std::str::from_utf8(&bytes).is_err() => bytes,
+        _ => key.as_bytes().to_vec(),
+    }
+}
+
+/// The path `key` stands for, relative to the work tree root
+#[cfg(unix)]
+pub fn key_to_path(key: &str) -> PathBuf {
+    use std::os::unix::ffi::OsStringExt;
+    PathBuf::from(std::ffi::OsString::from_vec(key_to_bytes(key)))
+}
+
+/// The path `key` stands for, relative to the work tree root. Paths that aren't UTF-8 can't be
+/// spelled on Windows, so the key is used as is.
+#[cfg(not(unix))]
+pub fn key_to_path(key: &str) -> PathBuf {
+    PathBuf::from(key)
+}
+
+/// `bytes` quoted the way git quotes paths with `core.quotepath` on
+fn quote(bytes: &[u8]) -> String {
+    let mut quoted = String::from("\"");
+    for &byte in bytes {
+        match byte {
+            b'"' => quoted.push_str("\\\""),
+            b'\\' => quoted.push_str("\\\\"),
+            b'\t' => quoted.push_str("\\t"),
+            b'\n' => quoted.push_str("\\n"),
+            0x20..=0x7e => quoted.push(byte as char),
+            _ => quoted.push_str(&format!("\\{:03o}", byte)),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// The bytes of a C-quoted path, or `None` if `field` isn't one
+fn unquote(field: &[u8]) -> Option<Vec<u8>> {
+    let inner = field.strip_prefix(b"\"")?.strip_suffix(b"\"")?;
+    let mut bytes = Vec::with_capacity(inner.len());
+    let mut iter = inner.iter().copied();
+    while let Some(byte) = iter.next() {
+        if byte == b'"' {
+            return None;
+        }
+        if byte != b'\\' {
+            bytes.push(byte);
+            continue;
+        }
+        let escaped = match iter.next()? {
+            b'a' => 0x07,
+            b'b' => 0x08,
+            b't' => b'\t',
+            b'n' => b'\n',
+            b'v' => 0x0b,
+            b'f' => 0x0c,
+            b'r' => b'\r',
+            b'"' => b'"',
+            b'\\' => b'\\',
+            digit @ b'0'..=b'3' => {
+                let mut value = (digit - b'0') as u32;
+                for _ in 0..2 {
+                    match iter.next()? {
+                        digit @ b'0'..=b'7' => value = value * 8 + (digit - b'0') as u32,
+                        _ => return None,
+                    }
+                }
+                value as u8
+            }
+            _ => return None,
+        };
+        bytes.push(escaped);
+    }
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quoted_and_raw_output_give_the_same_key() {
+        assert_eq!(key_from_git_output(b"src/lib.rs"), "src/lib.rs");
+        assert_eq!(key_from_git_output(b"\"caf\\303\\251.rs\""), "café.rs");
+        assert_eq!(key_from_git_output("café.rs".as_bytes()), "café.rs");
+        assert_eq!(key_from_git_output(b"\"tab\\there.rs\""), "tab\there.rs");
+
+        // Latin-1, not UTF-8: keyed by git's quoted spelling either way
+        assert_eq!(key_from_git_output(b"lat\xe9n.rs"), "\"lat\\351n.rs\"");
+        assert_eq!(key_from_git_output(b"\"lat\\351n.rs\""), "\"lat\\351n.rs\"");
+    }
+
+    #[test]
+    fn test_keys_convert_back_to_bytes() {
+        assert_eq!(key_to_bytes("café.rs"), "café.rs".as_bytes());
+        assert_eq!(key_to_bytes("\"lat\\351n.rs\""), b"lat\xe9n.rs");
+        assert!(is_quoted_key("\"lat\\351n.rs\""));
+        // A UTF-8 name that happens to look quoted is itself
+        assert_eq!(key_to_bytes("\"quoted\".rs"), b"\"quoted\".rs");
+        assert_eq!(key_to_bytes("\"caf\\303\\251\""), b"\"caf\\303\\251\"");
+        assert!(!is_quoted_key("\"caf\\303\\251\""));
+    }
+
+    #[test]
+    fn test_parse_numstat_line() {
+        assert_eq!(
+            parse_numstat_line(b"3\t1\t\"caf\\303\\251.rs\""),
+            Some((Some(3), Some(1), "café.rs".into()))
+        );
+        assert_eq!(
+            parse_numstat_line(b"-\t-\tlogo.png"),
+            Some((None, None, "logo.png".into()))
+        );
+        assert_eq!(parse_numstat_line(b"commit message"), None);
+    }
+
+    #[test]
+    fn test_key_from_diff_header() {
+        assert_eq!(
+            key_from_diff_header(b"b/src/lib.rs"),
+            Some("src/lib.rs".into())
+        );
+        assert_eq!(
+            key_from_diff_header(b"\"b/caf\\303\\251.rs\""),
+            Some("café.rs".into())
+        );
+        assert_eq!(
+            key_from_diff_header(b"b/with space.rs\t"),
+            Some("with space.rs".into())
+        );
+        assert_eq!(key_from_diff_header(b"/dev/null"), None);
+    }
+}
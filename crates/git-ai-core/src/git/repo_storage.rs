@@ -0,0 +1,1828 @@
+use crate::authorship::attribution_tracker::LineAttribution;
+use crate::authorship::authorship_log::{Participant, PromptRecord};
+use crate::authorship::file_text;
+use crate::authorship::review::ReviewMark;
+use crate::authorship::rewrite_audit::RewriteAudit;
+use crate::authorship::working_log::{CHECKPOINT_API_VERSION, Checkpoint, CheckpointKind};
+use crate::config::Config;
+use crate::error::GitAiError;
+use crate::git::repo_path;
+use crate::git::rewrite_log::{RewriteLogEvent, append_event_to_file, serialize_events_to_jsonl};
+use crate::utils::{debug_log, normalize_to_posix, strip_verbatim_prefix, write_atomic};
+use serde::{Deserialize, Serialize, Serializer};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// How long a checkpoint waits for another process's checkpoint to finish before giving up
+pub const WORKING_LOG_LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Initial attributions data structure stored in the INITIAL file
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct InitialAttributions {
+    /// Map of file path to line attributions
+    #[serde(serialize_with = "serialize_sorted")]
+    pub files: HashMap<String, Vec<LineAttribution>>,
+    /// Map of author_id (hash) to PromptRecord for prompt tracking
+    #[serde(serialize_with = "serialize_sorted")]
+    pub prompts: HashMap<String, PromptRecord>,
+}
+
+/// Attribution of files as `git add -p` (or `-i`, `-e`) staged them, stored in the STAGED file.
+/// When a commit's version of a file is the staged blob recorded here, its attribution comes from
+/// here: lines edited again in the working tree after staging no longer say who wrote the
+/// version that was committed.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct StagedAttributions {
+    #[serde(serialize_with = "serialize_sorted")]
+    pub files: HashMap<String, StagedFile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StagedFile {
+    /// Blob id of the staged content
+    pub blob: String,
+    /// Line attributions of the staged content
+    pub line_attributions: Vec<LineAttribution>,
+}
+
+/// What `checkpoint begin-agent` saw before an agent's edit, kept in the AGENT_EDITS file until
+/// the matching `checkpoint end-agent`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PendingAgentEdit {
+    /// Checkpoints in the working log when the edit began
+    pub checkpoint_count: usize,
+    /// Files the agent said it would edit; empty when it didn't say
+    pub files: Vec<String>,
+}
+
+/// Snapshot keys contain characters that don't belong in file names, so files are named by hash
+fn snapshot_file_name(key: &str) -> String {
+    format!("{:x}", Sha256::digest(key.as_bytes()))
+}
+
+/// Serialize a map with its keys in order, so the same data always produces the same file
+fn serialize_sorted<S: Serializer, V: Serialize>(
+    map: &HashMap<String, V>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    map.iter().collect::<BTreeMap<_, _>>().serialize(serializer)
+}
+
+/// A rewrite of notes and working logs (after a commit, rebase, amend, ...) in progress. Written
+/// before the rewrite starts and removed when it ends, so one still on disk later means the
+/// process doing the rewrite died part-way. Alongside it is a snapshot of the working logs the
+/// rewrite may change, as they were before it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RewriteJournal {
+    pub pid: u32,
+    pub started_at: u64,
+    pub event: RewriteLogEvent,
+    pub commit_author: String,
+    /// Base commits of the snapshotted working logs
+    #[serde(default)]
+    pub working_logs: Vec<String>,
+}
+
+impl RewriteJournal {
+    pub fn new(event: RewriteLogEvent, commit_author: String) -> Self {
+        let working_logs = rewritten_working_logs(&event);
+        RewriteJournal {
+            pid: std::process::id(),
+            started_at: crate::utils::now_secs(),
+            event,
+            commit_author,
+            working_logs,
+        }
+    }
+}
+
+/// Base commits of the working logs rewriting authorship for `event` reads, replaces or deletes
+fn rewritten_working_logs(event: &RewriteLogEvent) -> Vec<String> {
+    let mut shas = match event {
+        RewriteLogEvent::Commit { commit } => vec![
+            commit
+                .base_commit
+                .clone()
+                .unwrap_or_else(|| "initial".to_string()),
+            commit.commit_sha.clone(),
+        ],
+        RewriteLogEvent::CommitAmend { commit_amend } => vec![
+            commit_amend.original_commit.clone(),
+            commit_amend.amended_commit_sha.clone(),
+        ],
+        RewriteLogEvent::MergeSquash { merge_squash } => vec![merge_squash.base_head.clone()],
+        RewriteLogEvent::RebaseComplete { rebase_complete } => {
+            let mut shas = vec![
+                rebase_complete.original_head.clone(),
+                rebase_complete.new_head.clone(),
+            ];
+            shas.extend(rebase_complete.original_commits.iter().cloned());
+            shas.extend(rebase_complete.new_commits.iter().cloned());
+            shas
+        }
+        RewriteLogEvent::CherryPickComplete {
+            cherry_pick_complete,
+        } => {
+            let mut shas = vec![
+                cherry_pick_complete.original_head.clone(),
+                cherry_pick_complete.new_head.clone(),
+            ];
+            shas.extend(cherry_pick_complete.source_commits.iter().cloned());
+            shas.extend(cherry_pick_complete.new_commits.iter().cloned());
+            shas
+        }
+        _ => Vec::new(),
+    };
+    shas.sort();
+    shas.dedup();
+    shas
+}
+
+/// State from before a merge, rebase or cherry-pick started, so aborting it can put the working
+/// log and the rewrite log back. Alongside it is a snapshot of the original head's working log.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OperationSnapshot {
+    /// The git command that started the operation (`merge`, `rebase` or `cherry-pick`)
+    pub operation: String,
+    pub original_head: String,
+    /// Newest rewrite-log event when the operation started (None if the log was empty)
+    pub last_event: Option<RewriteLogEvent>,
+}
+
+/// Attribution of a conflict resolution that `git rerere` recorded, keyed by its rr-cache id. When
+/// rerere replays the resolution, the lines it puts back come with this attribution instead of
+/// counting as new work by whoever ran the merge or rebase.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RerereResolution {
+    pub file: String,
+    /// The resolved file as it was when the resolution was recorded
+    pub content: String,
+    pub line_attributions: Vec<LineAttribution>,
+    #[serde(serialize_with = "serialize_sorted")]
+    pub prompts: HashMap<String, PromptRecord>,
+}
+
+/// How many attribution snapshots are kept; older ones are removed as new ones are written
+const MAX_ATTRIBUTION_SNAPSHOTS: usize = 4;
+
+/// How many rewrite audits are kept; older ones are removed as new ones are written
+const MAX_REWRITE_AUDITS: usize = 1000;
+
+/// What `VirtualAttributions::new_for_base_commit` computed for a commit, kept so the next
+/// command that needs the same attributions (stats after a rebase, say) doesn't blame every file
+/// again. Only line attributions are stored; char attributions are rebuilt from them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AttributionSnapshot {
+    /// The commit, its tree and the authorship notes the attributions were computed from
+    pub key: String,
+    /// Every file that was blamed, including ones missing from the commit
+    pub pathspecs: BTreeSet<String>,
+    pub files: BTreeMap<String, SnapshotFile>,
+    pub prompts: BTreeMap<String, BTreeMap<String, PromptRecord>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotFile {
+    pub content: String,
+    pub line_attributions: Vec<LineAttribution>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RepoStorage {
+    pub repo_path: PathBuf,
+    pub repo_workdir: PathBuf,
+    pub working_logs: PathBuf,
+    pub rewrite_log: PathBuf,
+    pub rewrite_journal: PathBuf,
+    pub rewrite_journal_working_logs: PathBuf,
+    pub operation_snapshot: PathBuf,
+    pub operation_snapshot_working_log: PathBuf,
+    pub rerere_resolutions: PathBuf,
+    pub attribution_snapshots: PathBuf,
+    /// How each rewrite moved attribution, one file per rewritten commit
+    pub rewrite_audits: PathBuf,
+    pub pairing: PathBuf,
+    pub working_logs_lock: PathBuf,
+    pub logs: PathBuf,
+    /// What git-ai would have done in shadow mode, one JSON entry per line
+    pub shadow_log: PathBuf,
+    /// True in shadow mode, where the working state above lives under `.git/ai/shadow` and
+    /// notes are written to `shadow_log` instead of refs/notes
+    pub shadowed: bool,
+    /// The last jj operation whose rewrites were applied, in a jj colocated repository
+    pub jj_operation: PathBuf,
+}
+
+impl RepoStorage {
+    pub fn for_repo_path(repo_path: &Path, repo_workdir: &Path) -> RepoStorage {
+        let config = Self::paths_for(repo_path, repo_workdir);
+
+        // @todo - @acunniffe, make this lazy on a read or write.
+        // it's probably fine to run this when Repository is loaded but there
+        // are many git commands for which it is not needed
+        config.ensure_config_directory().unwrap();
+        return config;
+    }
+
+    /// Storage for a bare repository. Unlike `for_repo_path`, nothing is created up front, so
+    /// opening a read-only server-side mirror doesn't write to it or fail.
+    pub fn for_bare_repo_path(repo_path: &Path) -> RepoStorage {
+        Self::paths_for(repo_path, repo_path)
+    }
+
+    fn paths_for(repo_path: &Path, repo_workdir: &Path) -> RepoStorage {
+        let shadowed = Config::get().is_shadow_mode();
+        // Shadow mode runs the real pipeline on scratch state, so turning it off later finds
+        // none of it
+        let ai_dir = if shadowed {
+            repo_path.join("ai").join("shadow")
+        } else {
+            repo_path.join("ai")
+        };
+        let user_dir = match working_log_user(repo_path) {
+            Some(user) => ai_dir.join("users").join(user),
+            None => ai_dir.clone(),
+        };
+        let working_logs_dir = user_dir.join("working_logs");
+        let rewrite_log_file = ai_dir.join("rewrite_log");
+        let rewrite_journal_file = ai_dir.join("rewrite_journal");
+        let rewrite_journal_working_logs_dir = user_dir.join("rewrite_journal_working_logs");
+        let operation_snapshot_file = ai_dir.join("operation_snapshot");
+        let operation_snapshot_working_log_dir = user_dir.join("operation_snapshot_working_log");
+        let rerere_resolutions_dir = ai_dir.join("rerere");
+        let attribution_snapshots_dir = ai_dir.join("attribution_snapshots");
+        let rewrite_audits_dir = ai_dir.join("rewrite_audits");
+        let pairing_file = ai_dir.join("pairing");
+        let working_logs_lock_file = ai_dir.join("working_logs.lock");
+        let logs_dir = repo_path.join("ai").join("logs");
+        let shadow_log_file = repo_path.join("ai").join("shadow_log");
+        let jj_operation_file = ai_dir.join("jj_operation");
+
+        RepoStorage {
+            repo_path: repo_path.to_path_buf(),
+            repo_workdir: repo_workdir.to_path_buf(),
+            working_logs: working_logs_dir,
+            rewrite_log: rewrite_log_file,
+            rewrite_journal: rewrite_journal_file,
+            rewrite_journal_working_logs: rewrite_journal_working_logs_dir,
+            operation_snapshot: operation_snapshot_file,
+            operation_snapshot_working_log: operation_snapshot_working_log_dir,
+            rerere_resolutions: rerere_resolutions_dir,
+            attribution_snapshots: attribution_snapshots_dir,
+            rewrite_audits: rewrite_audits_dir,
+            pairing: pairing_file,
+            working_logs_lock: working_logs_lock_file,
+            logs: logs_dir,
+            shadow_log: shadow_log_file,
+            shadowed,
+            jj_operation: jj_operation_file,
+        }
+    }
+
+    /// Where the working state lives: `.git/ai`, or `.git/ai/shadow` in shadow mode
+    fn state_dir(&self) -> PathBuf {
+        let ai_dir = self.repo_path.join("ai");
+        if self.shadowed {
+            ai_dir.join("shadow")
+        } else {
+            ai_dir
+        }
+    }
+
+    fn ensure_config_directory(&self) -> Result<(), GitAiError> {
+        let ai_dir = self.repo_path.join("ai");
+
+        fs::create_dir_all(ai_dir)?;
+
+        // Create working_logs directory
+        fs::create_dir_all(&self.working_logs)?;
+
+        // Create logs directory for Sentry events
+        fs::create_dir_all(&self.logs)?;
+
+        if !&self.rewrite_log.exists() && !&self.rewrite_log.is_file() {
+            write_atomic(&self.rewrite_log, b"")?;
+        }
+
+        Ok(())
+    }
+
+    /// Takes the repo-wide lock on the working logs, waiting up to `timeout` for another process
+    /// (an editor plugin and an agent hook checkpointing at once, say) to release it. The lock is
+    /// advisory and released when the returned guard is dropped, or when its process dies.
+    pub fn lock_working_logs(&self, timeout: Duration) -> Result<WorkingLogLock, GitAiError> {
+        let started = Instant::now();
+        loop {
+            if let Some(file) = try_lock_file(&self.working_logs_lock)? {
+                return Ok(WorkingLogLock { _file: file });
+            }
+            if started.elapsed() >= timeout {
+                return Err(GitAiError::LockTimeout {
+                    path: self.working_logs_lock.display().to_string(),
+                    waited_secs: timeout.as_secs(),
+                });
+            }
+            std::thread::sleep(Duration::from_millis(25));
+        }
+    }
+
+    /* Working Log Persistance */
+
+    pub fn working_log_for_base_commit(&self, sha: &str) -> PersistedWorkingLog {
+        let working_log_dir = self.working_logs.join(sha);
+        fs::create_dir_all(&working_log_dir).unwrap();
+        let canonical_workdir = self
+            .repo_workdir
+            .canonicalize()
+            .unwrap_or_else(|_| self.repo_workdir.clone());
+        PersistedWorkingLog::new(
+            working_log_dir,
+            sha,
+            self.repo_workdir.clone(),
+            canonical_workdir,
+            None,
+        )
+    }
+
+    /// Base commits of the working logs that hold checkpoints or INITIAL attributions, most
+    /// recently written first
+    pub fn working_logs_with_data(&self) -> Vec<String> {
+        let Ok(entries) = fs::read_dir(&self.working_logs) else {
+            return Vec::new();
+        };
+        let mut logs: Vec<(std::time::SystemTime, String)> = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let dir = entry.path();
+                let modified = ["checkpoints.jsonl", "INITIAL"]
+                    .iter()
+                    .filter_map(|name| fs::metadata(dir.join(name)).ok())
+                    .filter(|metadata| metadata.len() > 0)
+                    .filter_map(|metadata| metadata.modified().ok())
+                    .max()?;
+                Some((modified, entry.file_name().to_string_lossy().to_string()))
+            })
+            .collect();
+        logs.sort_by(|a, b| b.cmp(a));
+        logs.into_iter().map(|(_, sha)| sha).collect()
+    }
+
+    /// STAGED attributions of the working log for `sha`, without creating the working log
+    pub fn staged_attributions_for_base_commit(&self, sha: &str) -> StagedAttributions {
+        read_staged_attributions(&self.working_logs.join(sha).join("STAGED"))
+    }
+
+    #[allow(dead_code)]
+    pub fn delete_working_log_for_base_commit(&self, sha: &str) -> Result<(), GitAiError> {
+        let working_log_dir = self.working_logs.join(sha);
+        if working_log_dir.exists() {
+            fs::remove_dir_all(&working_log_dir)?;
+        }
+        Ok(())
+    }
+
+    /// Moves the other users' working logs for `from_sha` to `to_sha` once a commit has moved
+    /// HEAD of the shared checkout, so their checkpoints are still there when they commit.
+    /// They stay in their own working logs: nothing of theirs goes into this user's commit.
+    pub fn carry_other_users_working_logs(&self, from_sha: &str, to_sha: &str) {
+        let ai_dir = self.state_dir();
+        let mut dirs = vec![ai_dir.join("working_logs")];
+        if let Ok(entries) = fs::read_dir(ai_dir.join("users")) {
+            dirs.extend(
+                entries
+                    .filter_map(|entry| entry.ok())
+                    .map(|entry| entry.path().join("working_logs")),
+            );
+        }
+        for dir in dirs.into_iter().filter(|dir| *dir != self.working_logs) {
+            let from = dir.join(from_sha);
+            let to = dir.join(to_sha);
+            if !from.is_dir() || to.exists() {
+                continue;
+            }
+            // Another user's directory may not be writable by this one
+            if let Err(e) = fs::rename(&from, &to) {
+                debug_log(&format!(
+                    "Failed to carry working log {} forward: {}",
+                    from.display(),
+                    e
+                ));
+            }
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn delete_all_working_logs(&self) -> Result<(), GitAiError> {
+        if self.working_logs.exists() {
+            fs::remove_dir_all(&self.working_logs)?;
+            // Recreate the empty directory structure
+            fs::create_dir_all(&self.working_logs)?;
+        }
+        Ok(())
+    }
+
+    /* Rewrite Log Persistance */
+
+    /// Append a rewrite event to the rewrite log file and return the full log
+    pub fn append_rewrite_event(
+        &self,
+        event: RewriteLogEvent,
+    ) -> Result<Vec<RewriteLogEvent>, GitAiError> {
+        append_event_to_file(&self.rewrite_log, event)?;
+        self.read_rewrite_events()
+    }
+
+    /// Read all rewrite events from the rewrite log file
+    pub fn read_rewrite_events(&self) -> Result<Vec<RewriteLogEvent>, GitAiError> {
+        if !self.rewrite_log.exists() {
+            return Ok(Vec::new());
+        }
+
+        let content = fs::read_to_string(&self.rewrite_log)?;
+        crate::git::rewrite_log::deserialize_events_from_jsonl(&content)
+    }
+
+    /// Drops the rewrite events newer than `last_event` (all of them if it's None). Does nothing
+    /// if `last_event` is no longer in the log.
+    pub fn roll_back_rewrite_events_after(
+        &self,
+        last_event: Option<&RewriteLogEvent>,
+    ) -> Result<(), GitAiError> {
+        let events = self.read_rewrite_events()?;
+        let newer = match last_event {
+            Some(last_event) => match events.iter().position(|event| event == last_event) {
+                Some(position) => position,
+                None => return Ok(()),
+            },
+            None => events.len(),
+        };
+        if newer == 0 {
+            return Ok(());
+        }
+
+        let jsonl = serialize_events_to_jsonl(&events[newer..])?;
+        write_atomic(&self.rewrite_log, jsonl.as_bytes())
+    }
+
+    /* Operation Snapshot */
+
+    /// Snapshots the working log of `snapshot.original_head`, then writes `snapshot`
+    pub fn write_operation_snapshot(&self, snapshot: &OperationSnapshot) -> Result<(), GitAiError> {
+        if self.operation_snapshot_working_log.exists() {
+            fs::remove_dir_all(&self.operation_snapshot_working_log)?;
+        }
+        let working_log_dir = self.working_logs.join(&snapshot.original_head);
+        if working_log_dir.exists() {
+            link_tree(&working_log_dir, &self.operation_snapshot_working_log)?;
+        }
+        write_atomic(&self.operation_snapshot, &serde_json::to_vec(snapshot)?)
+    }
+
+    /// The snapshot taken when the last merge, rebase or cherry-pick started, if any. An
+    /// unreadable snapshot is treated as none.
+    pub fn read_operation_snapshot(&self) -> Option<OperationSnapshot> {
+        let content = fs::read_to_string(&self.operation_snapshot).ok()?;
+        match serde_json::from_str(&content) {
+            Ok(snapshot) => Some(snapshot),
+            Err(e) => {
+                debug_log(&format!("Ignoring unreadable operation snapshot: {}", e));
+                None
+            }
+        }
+    }
+
+    /// Puts the original head's working log back to the snapshot
+    pub fn restore_operation_snapshot_working_log(
+        &self,
+        snapshot: &OperationSnapshot,
+    ) -> Result<(), GitAiError> {
+        let working_log_dir = self.working_logs.join(&snapshot.original_head);
+        if working_log_dir.exists() {
+            fs::remove_dir_all(&working_log_dir)?;
+        }
+        if self.operation_snapshot_working_log.exists() {
+            link_tree(&self.operation_snapshot_working_log, &working_log_dir)?;
+        }
+        Ok(())
+    }
+
+    pub fn clear_operation_snapshot(&self) -> Result<(), GitAiError> {
+        match fs::remove_file(&self.operation_snapshot) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        }
+        if self.operation_snapshot_working_log.exists() {
+            fs::remove_dir_all(&self.operation_snapshot_working_log)?;
+        }
+        Ok(())
+    }
+
+    /* Pairing */
+
+    /// Who is pairing on agent sessions in this repository, as `git-ai pair` set it. Empty when
+    /// nobody is, or the file can't be read.
+    pub fn read_pairing(&self) -> Vec<Participant> {
+        fs::read_to_string(&self.pairing)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn write_pairing(&self, participants: &[Participant]) -> Result<(), GitAiError> {
+        write_atomic(&self.pairing, &serde_json::to_vec(participants)?)
+    }
+
+    pub fn clear_pairing(&self) -> Result<(), GitAiError> {
+        match fs::remove_file(&self.pairing) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /* Rerere Resolutions */
+
+    pub fn write_rerere_resolution(
+        &self,
+        id: &str,
+        resolution: &RerereResolution,
+    ) -> Result<(), GitAiError> {
+        fs::create_dir_all(&self.rerere_resolutions)?;
+        write_atomic(
+            &self.rerere_resolutions.join(id),
+            &serde_json::to_vec(resolution)?,
+        )
+    }
+
+    /// The attribution recorded for rerere's resolution `id`, if any. An unreadable record is
+    /// treated as none.
+    pub fn read_rerere_resolution(&self, id: &str) -> Option<RerereResolution> {
+        let content = fs::read_to_string(self.rerere_resolutions.join(id)).ok()?;
+        match serde_json::from_str(&content) {
+            Ok(resolution) => Some(resolution),
+            Err(e) => {
+                debug_log(&format!(
+                    "Ignoring unreadable rerere resolution {}: {}",
+                    id, e
+                ));
+                None
+            }
+        }
+    }
+
+    /// Every recorded rerere resolution of `file`, with its rr-cache id
+    pub fn rerere_resolutions_for_file(&self, file: &str) -> Vec<(String, RerereResolution)> {
+        let Ok(entries) = fs::read_dir(&self.rerere_resolutions) else {
+            return Vec::new();
+        };
+        entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let id = entry.file_name().to_string_lossy().to_string();
+                let resolution = self.read_rerere_resolution(&id)?;
+                Some((id, resolution))
+            })
+            .filter(|(_, resolution)| resolution.file == file)
+            .collect()
+    }
+
+    /* Attribution Snapshots */
+
+    /// Writes `snapshot`, replacing any with the same key and removing the oldest ones past
+    /// `MAX_ATTRIBUTION_SNAPSHOTS`
+    pub fn write_attribution_snapshot(
+        &self,
+        snapshot: &AttributionSnapshot,
+    ) -> Result<(), GitAiError> {
+        fs::create_dir_all(&self.attribution_snapshots)?;
+        let path = self
+            .attribution_snapshots
+            .join(snapshot_file_name(&snapshot.key));
+        write_atomic(&path, &serde_json::to_vec(snapshot)?)?;
+
+        // The one just written always stays, even if others share its modification time
+        let mut others: Vec<(std::time::SystemTime, PathBuf)> =
+            fs::read_dir(&self.attribution_snapshots)?
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.path() != path)
+                .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+                .collect();
+        others.sort_by(|a, b| b.cmp(a));
+        for (_, path) in others.into_iter().skip(MAX_ATTRIBUTION_SNAPSHOTS - 1) {
+            let _ = fs::remove_file(path);
+        }
+        Ok(())
+    }
+
+    /// The snapshot stored for `key`, if any. An unreadable snapshot is treated as none.
+    pub fn read_attribution_snapshot(&self, key: &str) -> Option<AttributionSnapshot> {
+        let content = fs::read(self.attribution_snapshots.join(snapshot_file_name(key))).ok()?;
+        match serde_json::from_slice::<AttributionSnapshot>(&content) {
+            Ok(snapshot) if snapshot.key == key => Some(snapshot),
+            Ok(_) => None,
+            Err(e) => {
+                debug_log(&format!("Ignoring unreadable attribution snapshot: {}", e));
+                None
+            }
+        }
+    }
+
+    /* Rewrite Audits */
+
+    /// Writes the audits of one rewrite, replacing earlier audits of the same commits and
+    /// removing the oldest ones past `MAX_REWRITE_AUDITS`
+    pub fn write_rewrite_audits(&self, audits: &[RewriteAudit]) -> Result<(), GitAiError> {
+        if audits.is_empty() {
+            return Ok(());
+        }
+        fs::create_dir_all(&self.rewrite_audits)?;
+        for audit in audits {
+            write_atomic(
+                &self.rewrite_audits.join(&audit.commit),
+                &serde_json::to_vec(audit)?,
+            )?;
+        }
+
+        let mut all: Vec<(std::time::SystemTime, PathBuf)> = fs::read_dir(&self.rewrite_audits)?
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+            .collect();
+        if all.len() > MAX_REWRITE_AUDITS {
+            all.sort_by(|a, b| b.cmp(a));
+            for (_, path) in all.into_iter().skip(MAX_REWRITE_AUDITS) {
+                let _ = fs::remove_file(path);
+            }
+        }
+        Ok(())
+    }
+
+    /// The audit of the rewrite that created `commit_sha`, if any. An unreadable audit is
+    /// treated as none.
+    pub fn read_rewrite_audit(&self, commit_sha: &str) -> Option<RewriteAudit> {
+        let content = fs::read_to_string(self.rewrite_audits.join(commit_sha)).ok()?;
+        match serde_json::from_str(&content) {
+            Ok(audit) => Some(audit),
+            Err(e) => {
+                debug_log(&format!(
+                    "Ignoring unreadable rewrite audit of {}: {}",
+                    commit_sha, e
+                ));
+                None
+            }
+        }
+    }
+
+    /* Rewrite Journal */
+
+    /// Snapshots the working logs listed in `journal`, then writes it
+    pub fn write_rewrite_journal(&self, journal: &RewriteJournal) -> Result<(), GitAiError> {
+        if self.rewrite_journal_working_logs.exists() {
+            fs::remove_dir_all(&self.rewrite_journal_working_logs)?;
+        }
+        fs::create_dir_all(&self.rewrite_journal_working_logs)?;
+        // Hard links are enough: the rewrite only replaces (write_atomic) or deletes working-log
+        // files. Appends in place come from checkpoints taken meanwhile, which a restored working
+        // log should keep anyway.
+        for sha in &journal.working_logs {
+            let working_log_dir = self.working_logs.join(sha);
+            if working_log_dir.exists() {
+                link_tree(
+                    &working_log_dir,
+                    &self.rewrite_journal_working_logs.join(sha),
+                )?;
+            }
+        }
+        write_atomic(&self.rewrite_journal, &serde_json::to_vec(journal)?)
+    }
+
+    /// Puts the working logs listed in `journal` back to the snapshot taken when it was written.
+    /// One that didn't exist then is removed.
+    pub fn restore_rewrite_journal_working_logs(
+        &self,
+        journal: &RewriteJournal,
+    ) -> Result<(), GitAiError> {
+        if !self.rewrite_journal_working_logs.exists() {
+            return Ok(());
+        }
+        for sha in &journal.working_logs {
+            self.delete_working_log_for_base_commit(sha)?;
+            let snapshot = self.rewrite_journal_working_logs.join(sha);
+            if snapshot.exists() {
+                link_tree(&snapshot, &self.working_logs.join(sha))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The journal of an unfinished rewrite, if any. An unreadable journal is treated as none.
+    pub fn read_rewrite_journal(&self) -> Option<RewriteJournal> {
+        let content = fs::read_to_string(&self.rewrite_journal).ok()?;
+        match serde_json::from_str(&content) {
+            Ok(journal) => Some(journal),
+            Err(e) => {
+                debug_log(&format!("Ignoring unreadable rewrite journal: {}", e));
+                None
+            }
+        }
+    }
+
+    pub fn clear_rewrite_journal(&self) -> Result<(), GitAiError> {
+        match fs::remove_file(&self.rewrite_journal) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        }
+        if self.rewrite_journal_working_logs.exists() {
+            fs::remove_dir_all(&self.rewrite_journal_working_logs)?;
+        }
+        Ok(())
+    }
+}
+
+/// Guard for the working-log lock taken by `RepoStorage::lock_working_logs`
+#[derive(Debug)]
+pub struct WorkingLogLock {
+    _file: fs::File,
+}
+
+/// Opens `path` and takes an exclusive lock on it without blocking; None if it's held elsewhere
+#[cfg(not(windows))]
+fn try_lock_file(path: &Path) -> Result<Option<fs::File>, GitAiError> {
+    use std::os::unix::io::AsRawFd;
+
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path)?;
+    if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } == 0 {
+        return Ok(Some(file));
+    }
+    let err = std::io::Error::last_os_error();
+    if err.kind() == std::io::ErrorKind::WouldBlock {
+        Ok(None)
+    } else {
+        Err(err.into())
+    }
+}
+
+/// Opens `path` with no sharing, which fails while another handle has it open
+#[cfg(windows)]
+fn try_lock_file(path: &Path) -> Result<Option<fs::File>, GitAiError> {
+    use std::os::windows::fs::OpenOptionsExt;
+
+    const ERROR_SHARING_VIOLATION: i32 = 32;
+    match fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .share_mode(0)
+        .open(path)
+    {
+        Ok(file) => Ok(Some(file)),
+        Err(e) if e.raw_os_error() == Some(ERROR_SHARING_VIOLATION) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Recreates the directory tree `from` at `to`, hard-linking files (copying where linking fails)
+fn link_tree(from: &Path, to: &Path) -> Result<(), GitAiError> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            link_tree(&entry.path(), &target)?;
+        } else if fs::hard_link(entry.path(), &target).is_err() {
+            fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+/// The user whose working logs this process uses, or `None` for the checkout's owner, whose
+/// working logs are directly under `.git/ai`. When several people commit from one checkout (a
+/// shared build machine, a devcontainer), each gets their own under `.git/ai/users/<user>`, so
+/// one person's AI checkpoints don't end up attested in another's commit. People sharing an
+/// OS account tell themselves apart with `GIT_AI_USER` (their `user.email`, say); on unix, an
+/// OS user other than the one owning the git directory is told apart by uid.
+fn working_log_user(repo_path: &Path) -> Option<String> {
+    if let Some(user) = Config::get().working_log_user() {
+        let user = user
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || "._@+-".contains(c) {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        return Some(user);
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        let owner = fs::metadata(repo_path).ok()?.uid();
+        let uid = unsafe { libc::geteuid() };
+        if uid != owner {
+            return Some(format!("uid-{}", uid));
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = repo_path;
+    None
+}
+
+/// `path` relative to `workdir`, ignoring a `\\?\` prefix on either side
+fn strip_workdir_prefix(path: &Path, workdir: &Path) -> Option<PathBuf> {
+    let path = strip_verbatim_prefix(path);
+    let workdir = strip_verbatim_prefix(workdir);
+    path.strip_prefix(&workdir).ok().map(Path::to_path_buf)
+}
+
+#[derive(Clone)]
+pub struct PersistedWorkingLog {
+    pub dir: PathBuf,
+    #[allow(dead_code)]
+    pub base_commit: String,
+    pub repo_workdir: PathBuf,
+    /// Canonical (absolute, resolved) version of workdir for reliable path comparisons
+    /// On Windows, this uses the \\?\ verbatim prefix format
+    pub canonical_workdir: PathBuf,
+    pub dirty_files: Option<HashMap<String, String>>,
+}
+
+impl PersistedWorkingLog {
+    pub fn new(
+        dir: PathBuf,
+        base_commit: &str,
+        repo_root: PathBuf,
+        canonical_workdir: PathBuf,
+        dirty_files: Option<HashMap<String, String>>,
+    ) -> Self {
+        Self {
+            dir,
+            base_commit: base_commit.to_string(),
+            repo_workdir: repo_root,
+            canonical_workdir,
+            dirty_files,
+        }
+    }
+
+    pub fn set_dirty_files(&mut self, dirty_files: Option<HashMap<String, String>>) {
+        let normalized_dirty_files = dirty_files.map(|map| {
+            map.into_iter()
+                .map(|(file_path, content)| {
+                    let relative_path = self.to_repo_relative_path(&file_path);
+                    let normalized_path = normalize_to_posix(&relative_path);
+                    (normalized_path, content)
+                })
+                .collect::<HashMap<_, _>>()
+        });
+
+        self.dirty_files = normalized_dirty_files;
+    }
+
+    pub fn reset_working_log(&self) -> Result<(), GitAiError> {
+        // Clear all blobs by removing the blobs directory
+        let blobs_dir = self.dir.join("blobs");
+        if blobs_dir.exists() {
+            fs::remove_dir_all(&blobs_dir)?;
+        }
+
+        // Clear checkpoints by truncating the JSONL file
+        let checkpoints_file = self.dir.join("checkpoints.jsonl");
+        write_atomic(&checkpoints_file, b"")?;
+
+        Ok(())
+    }
+
+    /// Whether there is anything to carry into the next commit: checkpoints or INITIAL attributions
+    pub fn has_data(&self) -> bool {
+        ["checkpoints.jsonl", "INITIAL"]
+            .iter()
+            .any(|name| fs::metadata(self.dir.join(name)).is_ok_and(|metadata| metadata.len() > 0))
+    }
+
+    /* blob storage */
+    pub fn get_file_version(&self, sha: &str) -> Result<String, GitAiError> {
+        let blob_path = self.dir.join("blobs").join(sha);
+        Ok(fs::read_to_string(blob_path)?)
+    }
+
+    pub fn persist_file_version(&self, content: &str) -> Result<String, GitAiError> {
+        // Create SHA256 hash of the content
+        let mut hasher = Sha256::new();
+        hasher.update(content.as_bytes());
+        let sha = format!("{:x}", hasher.finalize());
+
+        // Ensure blobs directory exists
+        let blobs_dir = self.dir.join("blobs");
+        fs::create_dir_all(&blobs_dir)?;
+
+        // Write content to blob file
+        let blob_path = blobs_dir.join(&sha);
+        write_atomic(&blob_path, content.as_bytes())?;
+
+        Ok(sha)
+    }
+
+    pub fn to_repo_absolute_path(&self, file_path: &str) -> PathBuf {
+        if Path::new(file_path).is_absolute() {
+            return PathBuf::from(file_path);
+        }
+        self.repo_workdir.join(repo_path::key_to_path(file_path))
+    }
+
+    /// `file_path` relative to the repository root with `/` separators, or unchanged if it's
+    /// already relative or outside the repository. Absolute paths may use either separator, a
+    /// `\\?\` prefix or, when the workdir is on a network share, a `\\server\share` root.
+    pub fn to_repo_relative_path(&self, file_path: &str) -> String {
+        let path = Path::new(file_path);
+        if !path.is_absolute() {
+            return file_path.to_string();
+        }
+
+        // Try without canonicalizing first, then with both sides resolved (symlinks, 8.3 names)
+        let relative = strip_workdir_prefix(path, &self.repo_workdir).or_else(|| {
+            let canonical_path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+            strip_workdir_prefix(&canonical_path, &self.canonical_workdir)
+        });
+
+        match relative {
+            Some(relative) => normalize_to_posix(&relative.to_string_lossy()),
+            None => file_path.to_string(),
+        }
+    }
+
+    pub fn read_current_file_content(&self, file_path: &str) -> Result<String, GitAiError> {
+        // First try to read from dirty_files (using raw path)
+        if let Some(ref dirty_files) = self.dirty_files {
+            if let Some(content) = dirty_files.get(&file_path.to_string()) {
+                return Ok(content.clone());
+            }
+        }
+
+        let file_path = self.to_repo_absolute_path(file_path);
+
+        // Fall back to reading from filesystem
+        match fs::read(&file_path) {
+            Ok(bytes) => Ok(file_text::decode(&bytes)),
+            Err(_) => Ok(String::new()),
+        }
+    }
+
+    /* append checkpoint */
+    pub fn append_checkpoint(&self, checkpoint: &Checkpoint) -> Result<(), GitAiError> {
+        let checkpoints_file = self.dir.join("checkpoints.jsonl");
+
+        // Serialize checkpoint to JSON and append to JSONL file
+        let json_line = serde_json::to_string(checkpoint)?;
+
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(&checkpoints_file)?;
+
+        // A crash part-way through an earlier append can leave a half-written last line; cut it
+        // off so the new line starts on a line of its own
+        let len = file.metadata()?.len();
+        if len > 0 {
+            let mut last = [0u8; 1];
+            file.seek(SeekFrom::Start(len - 1))?;
+            file.read_exact(&mut last)?;
+            if last[0] != b'\n' {
+                let mut content = Vec::new();
+                file.seek(SeekFrom::Start(0))?;
+                file.read_to_end(&mut content)?;
+                let keep = content
+                    .iter()
+                    .rposition(|b| *b == b'\n')
+                    .map_or(0, |i| i + 1);
+                file.set_len(keep as u64)?;
+            }
+        }
+
+        let mut line = json_line.into_bytes();
+        line.push(b'\n');
+        file.write_all(&line)?;
+        file.sync_data()?;
+
+        Ok(())
+    }
+
+    pub fn read_all_checkpoints(&self) -> Result<Vec<Checkpoint>, GitAiError> {
+        let checkpoints_file = self.dir.join("checkpoints.jsonl");
+
+        if !checkpoints_file.exists() {
+            return Ok(Vec::new());
+        }
+
+        let content = fs::read_to_string(&checkpoints_file)?;
+        let mut checkpoints = Vec::new();
+
+        // Parse JSONL file - each line is a separate JSON object
+        let complete = content.ends_with('\n');
+        let mut lines = content.lines().peekable();
+        while let Some(line) = lines.next() {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let checkpoint: Checkpoint = match serde_json::from_str(line) {
+                Ok(checkpoint) => checkpoint,
+                // Half-written by an append that didn't finish; the next append cuts it off
+                Err(_) if !complete && lines.peek().is_none() => break,
+                Err(e) => {
+                    return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, e).into());
+                }
+            };
+
+            if checkpoint.api_version != CHECKPOINT_API_VERSION {
+                debug_log(&format!(
+                    "unsupported checkpoint api version: {} (silently skipping checkpoint)",
+                    checkpoint.api_version
+                ));
+                continue;
+            }
+
+            checkpoints.push(checkpoint);
+        }
+
+        Ok(checkpoints)
+    }
+
+    pub fn all_touched_files(&self) -> Result<HashSet<String>, GitAiError> {
+        let checkpoints = self.read_all_checkpoints()?;
+        let mut touched_files = HashSet::new();
+        for checkpoint in checkpoints {
+            for entry in checkpoint.entries {
+                touched_files.insert(entry.file);
+            }
+        }
+        Ok(touched_files)
+    }
+
+    #[allow(dead_code)]
+    pub fn all_ai_touched_files(&self) -> Result<HashSet<String>, GitAiError> {
+        let checkpoints = self.read_all_checkpoints()?;
+        let mut touched_files = HashSet::new();
+        for checkpoint in checkpoints {
+            // Only include files from AI checkpoints (AiAgent or AiTab)
+            match checkpoint.kind {
+                CheckpointKind::AiAgent | CheckpointKind::AiTab => {
+                    for entry in checkpoint.entries {
+                        touched_files.insert(entry.file);
+                    }
+                }
+                CheckpointKind::Human | CheckpointKind::AuthorClass => {
+                    // Skip human and author class checkpoints
+                }
+            }
+        }
+        Ok(touched_files)
+    }
+
+    /* INITIAL attributions file */
+
+    /// Write initial attributions to the INITIAL file.
+    /// This seeds the working log with known attributions from rewrite operations.
+    /// Only writes files that have non-empty attributions.
+    pub fn write_initial_attributions(
+        &self,
+        attributions: HashMap<String, Vec<LineAttribution>>,
+        prompts: HashMap<String, PromptRecord>,
+    ) -> Result<(), GitAiError> {
+        // Filter out empty attributions
+        let filtered: HashMap<String, Vec<LineAttribution>> = attributions
+            .into_iter()
+            .filter(|(_, attrs)| !attrs.is_empty())
+            .collect();
+
+        if filtered.is_empty() {
+            // Don't create an INITIAL file if there are no attributions
+            return Ok(());
+        }
+
+        let initial_data = InitialAttributions {
+            files: filtered,
+            prompts,
+        };
+
+        let initial_file = self.dir.join("INITIAL");
+        let json = serde_json::to_string_pretty(&initial_data)?;
+        write_atomic(&initial_file, json.as_bytes())?;
+
+        Ok(())
+    }
+
+    /// Read initial attributions from the INITIAL file.
+    /// Returns empty attributions and prompts if the file doesn't exist.
+    pub fn read_initial_attributions(&self) -> InitialAttributions {
+        let initial_file = self.dir.join("INITIAL");
+
+        if !initial_file.exists() {
+            return InitialAttributions::default();
+        }
+
+        match fs::read_to_string(&initial_file) {
+            Ok(content) => match serde_json::from_str(&content) {
+                Ok(initial_data) => initial_data,
+                Err(e) => {
+                    debug_log(&format!(
+                        "Failed to parse INITIAL file: {}. Returning empty.",
+                        e
+                    ));
+                    InitialAttributions::default()
+                }
+            },
+            Err(e) => {
+                debug_log(&format!(
+                    "Failed to read INITIAL file: {}. Returning empty.",
+                    e
+                ));
+                InitialAttributions::default()
+            }
+        }
+    }
+
+    /* STAGED attributions file */
+
+    pub fn write_staged_attributions(&self, staged: &StagedAttributions) -> Result<(), GitAiError> {
+        let json = serde_json::to_string_pretty(staged)?;
+        write_atomic(&self.dir.join("STAGED"), json.as_bytes())?;
+        Ok(())
+    }
+
+    /// Returns empty attributions if the STAGED file doesn't exist or can't be read
+    pub fn read_staged_attributions(&self) -> StagedAttributions {
+        read_staged_attributions(&self.dir.join("STAGED"))
+    }
+
+    /* AGENT_EDITS file */
+
+    /// Records that `session` began an edit, replacing any edit it began and never ended
+    pub fn write_pending_agent_edit(
+        &self,
+        session: &str,
+        edit: &PendingAgentEdit,
+    ) -> Result<(), GitAiError> {
+        let mut edits = self.read_pending_agent_edits();
+        edits.insert(session.to_string(), edit.clone());
+        let json = serde_json::to_string_pretty(&edits)?;
+        write_atomic(&self.dir.join("AGENT_EDITS"), json.as_bytes())?;
+        Ok(())
+    }
+
+    /// Removes and returns the edit `session` began, if any
+    pub fn take_pending_agent_edit(&self, session: &str) -> Option<PendingAgentEdit> {
+        let mut edits = self.read_pending_agent_edits();
+        let edit = edits.remove(session)?;
+        let written = if edits.is_empty() {
+            fs::remove_file(self.dir.join("AGENT_EDITS")).map_err(GitAiError::from)
+        } else {
+            serde_json::to_string_pretty(&edits)
+                .map_err(GitAiError::from)
+                .and_then(|json| write_atomic(&self.dir.join("AGENT_EDITS"), json.as_bytes()))
+        };
+        if let Err(e) = written {
+            debug_log(&format!("Failed to update AGENT_EDITS: {}", e));
+        }
+        Some(edit)
+    }
+
+    fn read_pending_agent_edits(&self) -> BTreeMap<String, PendingAgentEdit> {
+        fs::read_to_string(self.dir.join("AGENT_EDITS"))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /* REVIEWS file */
+
+    /// Records a review of uncommitted lines, which the next commit carries into its review note
+    pub fn append_review_mark(&self, mark: &ReviewMark) -> Result<(), GitAiError> {
+        let reviews_file = self.dir.join("REVIEWS");
+        let mut content = match fs::read(&reviews_file) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+        content.extend_from_slice(serde_json::to_string(mark)?.as_bytes());
+        content.push(b'\n');
+        write_atomic(&reviews_file, &content)?;
+        Ok(())
+    }
+
+    /// Reviews of uncommitted lines, oldest first. Lines that don't parse are skipped.
+    pub fn read_review_marks(&self) -> Vec<ReviewMark> {
+        fs::read_to_string(self.dir.join("REVIEWS"))
+            .map(|content| {
+                content
+                    .lines()
+                    .filter_map(|line| serde_json::from_str(line).ok())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+fn read_staged_attributions(path: &Path) -> StagedAttributions {
+    if !path.exists() {
+        return StagedAttributions::default();
+    }
+    fs::read_to_string(path)
+        .map_err(GitAiError::from)
+        .and_then(|content| Ok(serde_json::from_str(&content)?))
+        .unwrap_or_else(|e| {
+            debug_log(&format!(
+                "Failed to read STAGED file: {}. Returning empty.",
+                e
+            ));
+            StagedAttributions::default()
+        })
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::git::test_utils::TmpRepo;
+
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_ensure_config_directory_creates_structure() {
+        // Create a temporary repository
+        let tmp_repo = TmpRepo::new().expect("Failed to create tmp repo");
+
+        // Create RepoStorage
+        let _repo_storage =
+            RepoStorage::for_repo_path(tmp_repo.repo().path(), &tmp_repo.repo().workdir().unwrap());
+
+        // Verify .git/ai directory exists
+        let ai_dir = tmp_repo.repo().path().join("ai");
+        assert!(ai_dir.exists(), ".git/ai directory should exist");
+        assert!(ai_dir.is_dir(), ".git/ai should be a directory");
+
+        // Verify working_logs directory exists
+        let working_logs_dir = ai_dir.join("working_logs");
+        assert!(
+            working_logs_dir.exists(),
+            "working_logs directory should exist"
+        );
+        assert!(
+            working_logs_dir.is_dir(),
+            "working_logs should be a directory"
+        );
+
+        // Verify rewrite_log file exists and is empty
+        let rewrite_log_file = ai_dir.join("rewrite_log");
+        assert!(rewrite_log_file.exists(), "rewrite_log file should exist");
+        assert!(rewrite_log_file.is_file(), "rewrite_log should be a file");
+
+        let content = fs::read_to_string(&rewrite_log_file).expect("Failed to read rewrite_log");
+        assert_eq!(content, "", "rewrite_log should be empty by default");
+    }
+
+    #[test]
+    fn test_ensure_config_directory_handles_existing_files() {
+        // Create a temporary repository
+        let tmp_repo = TmpRepo::new().expect("Failed to create tmp repo");
+
+        // Create RepoStorage
+        let repo_storage = RepoStorage::for_repo_path(
+            &tmp_repo.repo().path(),
+            &tmp_repo.repo().workdir().unwrap(),
+        );
+
+        // Add some content to rewrite_log
+        let rewrite_log_file = tmp_repo.repo().path().join("ai").join("rewrite_log");
+        fs::write(&rewrite_log_file, "existing content").expect("Failed to write to rewrite_log");
+
+        // Second call - should not overwrite existing file
+        repo_storage
+            .ensure_config_directory()
+            .expect("Failed to ensure config directory again");
+
+        // Verify the content is preserved
+        let content = fs::read_to_string(&rewrite_log_file).expect("Failed to read rewrite_log");
+        assert_eq!(
+            content, "existing content",
+            "Existing rewrite_log content should be preserved"
+        );
+
+        // Verify directories still exist
+        let ai_dir = tmp_repo.repo().path().join("ai");
+        let working_logs_dir = ai_dir.join("working_logs");
+        assert!(ai_dir.exists(), ".git/ai directory should still exist");
+        assert!(
+            working_logs_dir.exists(),
+            "working_logs directory should still exist"
+        );
+    }
+
+    #[test]
+    fn test_persisted_working_log_blob_storage() {
+        // Create a temporary repository
+        let tmp_repo = TmpRepo::new().expect("Failed to create tmp repo");
+
+        // Create RepoStorage and PersistedWorkingLog
+        let repo_storage =
+            RepoStorage::for_repo_path(tmp_repo.repo().path(), &tmp_repo.repo().workdir().unwrap());
+        let working_log = repo_storage.working_log_for_base_commit("test-commit-sha");
+
+        // Test persisting a file version
+        let content = "Hello, World!\nThis is a test file.";
+        let sha = working_log
+            .persist_file_version(content)
+            .expect("Failed to persist file version");
+
+        // Verify the SHA is not empty
+        assert!(!sha.is_empty(), "SHA should not be empty");
+
+        // Test retrieving the file version
+        let retrieved_content = working_log
+            .get_file_version(&sha)
+            .expect("Failed to get file version");
+
+        assert_eq!(
+            content, retrieved_content,
+            "Retrieved content should match original"
+        );
+
+        // Verify the blob file exists
+        let blob_path = working_log.dir.join("blobs").join(&sha);
+        assert!(blob_path.exists(), "Blob file should exist");
+        assert!(blob_path.is_file(), "Blob should be a file");
+
+        // Test persisting the same content again should return the same SHA
+        let sha2 = working_log
+            .persist_file_version(content)
+            .expect("Failed to persist file version again");
+
+        assert_eq!(sha, sha2, "Same content should produce same SHA");
+    }
+
+    #[test]
+    fn test_persisted_working_log_checkpoint_storage() {
+        use crate::authorship::working_log::CheckpointKind;
+
+        // Create a temporary repository
+        let tmp_repo = TmpRepo::new().expect("Failed to create tmp repo");
+
+        // Create RepoStorage and PersistedWorkingLog
+        let repo_storage =
+            RepoStorage::for_repo_path(tmp_repo.repo().path(), &tmp_repo.repo().workdir().unwrap());
+        let working_log = repo_storage.working_log_for_base_commit("test-commit-sha");
+
+        // Create a test checkpoint
+        let checkpoint = Checkpoint::new(
+            CheckpointKind::Human,
+            "test-diff".to_string(),
+            "test-author".to_string(),
+            vec![], // empty entries for simplicity
+        );
+
+        // Test appending checkpoint
+        working_log
+            .append_checkpoint(&checkpoint)
+            .expect("Failed to append checkpoint");
+
+        // Test reading all checkpoints
+        let checkpoints = working_log
+            .read_all_checkpoints()
+            .expect("Failed to read checkpoints");
+
+        assert_eq!(checkpoints.len(), 1, "Should have one checkpoint");
+        assert_eq!(checkpoints[0].author, "test-author");
+
+        // Verify the JSONL file exists
+        let checkpoints_file = working_log.dir.join("checkpoints.jsonl");
+        assert!(checkpoints_file.exists(), "Checkpoints file should exist");
+
+        // Test appending another checkpoint
+        let checkpoint2 = Checkpoint::new(
+            CheckpointKind::Human,
+            "test-diff-2".to_string(),
+            "test-author-2".to_string(),
+            vec![],
+        );
+
+        working_log
+            .append_checkpoint(&checkpoint2)
+            .expect("Failed to append second checkpoint");
+
+        let checkpoints = working_log
+            .read_all_checkpoints()
+            .expect("Failed to read checkpoints after second append");
+
+        assert_eq!(checkpoints.len(), 2, "Should have two checkpoints");
+        assert_eq!(checkpoints[1].author, "test-author-2");
+    }
+
+    #[test]
+    fn test_read_all_checkpoints_filters_incompatible_versions() {
+        use crate::authorship::working_log::CheckpointKind;
+
+        // Create a temporary repository
+        let tmp_repo = TmpRepo::new().expect("Failed to create tmp repo");
+
+        // Create RepoStorage and PersistedWorkingLog
+        let repo_storage =
+            RepoStorage::for_repo_path(tmp_repo.repo().path(), &tmp_repo.repo().workdir().unwrap());
+        let working_log = repo_storage.working_log_for_base_commit("test-commit-sha");
+
+        // Build three checkpoints: missing version, wrong version, and correct version
+        let base_checkpoint = Checkpoint::new(
+            CheckpointKind::Human,
+            "diff --git a/file b/file".to_string(),
+            "base-author".to_string(),
+            vec![],
+        );
+
+        let missing_version_json = {
+            let mut value = serde_json::to_value(&base_checkpoint).unwrap();
+            if let serde_json::Value::Object(ref mut map) = value {
+                map.remove("api_version");
+            }
+            serde_json::to_string(&value).unwrap()
+        };
+
+        let mut wrong_version_checkpoint = base_checkpoint.clone();
+        wrong_version_checkpoint.api_version = "checkpoint/0.9.0".to_string();
+        let wrong_version_json = serde_json::to_string(&wrong_version_checkpoint).unwrap();
+
+        let mut correct_checkpoint = base_checkpoint.clone();
+        correct_checkpoint.author = "correct-author".to_string();
+        let correct_json = serde_json::to_string(&correct_checkpoint).unwrap();
+
+        let checkpoints_file = working_log.dir.join("checkpoints.jsonl");
+        let combined = [missing_version_json, wrong_version_json, correct_json].join("\n");
+        fs::write(&checkpoints_file, combined).expect("Failed to write checkpoints.jsonl");
+
+        let checkpoints = working_log
+            .read_all_checkpoints()
+            .expect("Failed to read checkpoints");
+
+        assert_eq!(
+            checkpoints.len(),
+            1,
+            "Only the correct version should remain"
+        );
+        assert_eq!(checkpoints[0].author, "correct-author");
+        assert_eq!(checkpoints[0].api_version, CHECKPOINT_API_VERSION);
+    }
+
+    #[test]
+    fn test_persisted_working_log_reset() {
+        use crate::authorship::working_log::CheckpointKind;
+
+        // Create a temporary repository
+        let tmp_repo = TmpRepo::new().expect("Failed to create tmp repo");
+
+        // Create RepoStorage and PersistedWorkingLog
+        let repo_storage =
+            RepoStorage::for_repo_path(tmp_repo.repo().path(), &tmp_repo.repo().workdir().unwrap());
+        let working_log = repo_storage.working_log_for_base_commit("test-commit-sha");
+
+        // Add some blobs
+        let content = "Test content";
+        let sha = working_log
+            .persist_file_version(content)
+            .expect("Failed to persist file version");
+
+        // Add some checkpoints
+        let checkpoint = Checkpoint::new(
+            CheckpointKind::Human,
+            "test-diff".to_string(),
+            "test-author".to_string(),
+            vec![],
+        );
+        working_log
+            .append_checkpoint(&checkpoint)
+            .expect("Failed to append checkpoint");
+
+        // Verify they exist
+        assert!(working_log.dir.join("blobs").join(&sha).exists());
+        let checkpoints = working_log
+            .read_all_checkpoints()
+            .expect("Failed to read checkpoints");
+        assert_eq!(checkpoints.len(), 1);
+
+        // Reset the working log
+        working_log
+            .reset_working_log()
+            .expect("Failed to reset working log");
+
+        // Verify blobs are cleared
+        assert!(
+            !working_log.dir.join("blobs").exists(),
+            "Blobs directory should be removed"
+        );
+
+        // Verify checkpoints are cleared
+        let checkpoints = working_log
+            .read_all_checkpoints()
+            .expect("Failed to read checkpoints after reset");
+        assert_eq!(
+            checkpoints.len(),
+            0,
+            "Should have no checkpoints after reset"
+        );
+
+        // Verify checkpoints.jsonl exists but is empty
+        let checkpoints_file = working_log.dir.join("checkpoints.jsonl");
+        assert!(
+            checkpoints_file.exists(),
+            "Checkpoints file should still exist"
+        );
+        let content =
+            fs::read_to_string(&checkpoints_file).expect("Failed to read checkpoints file");
+        assert!(
+            content.trim().is_empty(),
+            "Checkpoints file should be empty"
+        );
+    }
+
+    #[test]
+    fn test_working_log_for_base_commit_creates_directory() {
+        // Create a temporary repository
+        let tmp_repo = TmpRepo::new().expect("Failed to create tmp repo");
+
+        // Create RepoStorage
+        let repo_storage =
+            RepoStorage::for_repo_path(tmp_repo.repo().path(), &tmp_repo.repo().workdir().unwrap());
+
+        // Create working log for a specific commit
+        let commit_sha = "abc123def456";
+        let working_log = repo_storage.working_log_for_base_commit(commit_sha);
+
+        // Verify the directory was created
+        assert!(
+            working_log.dir.exists(),
+            "Working log directory should exist"
+        );
+        assert!(
+            working_log.dir.is_dir(),
+            "Working log should be a directory"
+        );
+
+        // Verify it's in the correct location
+        let expected_path = tmp_repo
+            .repo()
+            .path()
+            .join("ai")
+            .join("working_logs")
+            .join(commit_sha);
+        assert_eq!(
+            working_log.dir, expected_path,
+            "Working log directory should be in correct location"
+        );
+    }
+
+    #[test]
+    fn test_worktree_commits_share_notes_with_main_checkout() {
+        let (tmp_repo, _, _) = TmpRepo::new_with_base_commit().unwrap();
+        let worktree = tmp_repo.add_worktree("feature").unwrap();
+
+        worktree
+            .write_file("feature.rs", "fn feature() {}\n", true)
+            .unwrap();
+        worktree
+            .trigger_checkpoint_with_ai("session_1", Some("test_model"), Some("test_tool"))
+            .unwrap();
+        let log = worktree.commit_with_message("Add feature").unwrap();
+        assert_eq!(log.attestations.len(), 1);
+
+        let sha = worktree.head_commit_sha().unwrap();
+        assert_ne!(sha, tmp_repo.head_commit_sha().unwrap());
+        let from_main = crate::git::refs::get_authorship(tmp_repo.gitai_repo(), &sha).unwrap();
+        assert_eq!(from_main.attestations.len(), 1);
+        assert_eq!(from_main.attestations[0].file_path, "feature.rs");
+    }
+
+    #[test]
+    fn test_working_log_lock_is_exclusive_until_dropped() {
+        let tmp_repo = TmpRepo::new().expect("Failed to create tmp repo");
+        let repo_storage = RepoStorage::for_repo_path(
+            &tmp_repo.repo().path(),
+            &tmp_repo.repo().workdir().unwrap(),
+        );
+
+        let lock = repo_storage
+            .lock_working_logs(Duration::ZERO)
+            .expect("Failed to take lock");
+        match repo_storage.lock_working_logs(Duration::from_millis(100)) {
+            Err(GitAiError::LockTimeout { path, .. }) => {
+                assert!(path.ends_with("working_logs.lock"))
+            }
+            other => panic!("expected LockTimeout, got {:?}", other),
+        }
+
+        drop(lock);
+        repo_storage
+            .lock_working_logs(Duration::ZERO)
+            .expect("Lock should be free once dropped");
+    }
+
+    #[test]
+    fn test_attribution_snapshots_are_read_by_key_and_pruned() {
+        let tmp_repo = TmpRepo::new().expect("Failed to create tmp repo");
+        let repo_storage = RepoStorage::for_repo_path(
+            &tmp_repo.repo().path(),
+            &tmp_repo.repo().workdir().unwrap(),
+        );
+        let snapshot = |key: &str| AttributionSnapshot {
+            key: key.to_string(),
+            pathspecs: BTreeSet::from(["src/lib.rs".to_string()]),
+            files: BTreeMap::from([(
+                "src/lib.rs".to_string(),
+                SnapshotFile {
+                    content: "fn lib() {}\n".to_string(),
+                    line_attributions: vec![LineAttribution::new(1, 1, "a1b2".to_string(), None)],
+                },
+            )]),
+            prompts: BTreeMap::new(),
+        };
+
+        repo_storage
+            .write_attribution_snapshot(&snapshot("abc:def:"))
+            .unwrap();
+        assert_eq!(
+            repo_storage.read_attribution_snapshot("abc:def:"),
+            Some(snapshot("abc:def:"))
+        );
+        assert_eq!(repo_storage.read_attribution_snapshot("abc:def:123"), None);
+
+        for i in 0..MAX_ATTRIBUTION_SNAPSHOTS + 2 {
+            repo_storage
+                .write_attribution_snapshot(&snapshot(&format!("commit{}:tree:", i)))
+                .unwrap();
+        }
+        let kept = fs::read_dir(&repo_storage.attribution_snapshots)
+            .unwrap()
+            .count();
+        assert_eq!(kept, MAX_ATTRIBUTION_SNAPSHOTS);
+        let newest = format!("commit{}:tree:", MAX_ATTRIBUTION_SNAPSHOTS + 1);
+        assert!(repo_storage.read_attribution_snapshot(&newest).is_some());
+    }
+
+    #[test]
+    fn test_append_checkpoint_leaves_no_temp_files() {
+        let tmp_repo = TmpRepo::new().expect("Failed to create tmp repo");
+        let repo_storage = RepoStorage::for_repo_path(
+            &tmp_repo.repo().path(),
+            &tmp_repo.repo().workdir().unwrap(),
+        );
+        let working_log = repo_storage.working_log_for_base_commit("test-commit-sha");
+
+        for i in 0..3 {
+            let checkpoint = Checkpoint::new(
+                CheckpointKind::Human,
+                format!("diff {}", i),
+                "test-author".to_string(),
+                vec![],
+            );
+            working_log
+                .append_checkpoint(&checkpoint)
+                .expect("Failed to append checkpoint");
+        }
+
+        assert_eq!(working_log.read_all_checkpoints().unwrap().len(), 3);
+        let leftovers: Vec<_> = fs::read_dir(&working_log.dir)
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name().to_string_lossy().ends_with(".tmp"))
+            .collect();
+        assert!(
+            leftovers.is_empty(),
+            "temp files left behind: {:?}",
+            leftovers
+        );
+    }
+
+    #[test]
+    fn test_append_checkpoint_cuts_off_half_written_line() {
+        let tmp_repo = TmpRepo::new().expect("Failed to create tmp repo");
+        let repo_storage = RepoStorage::for_repo_path(
+            &tmp_repo.repo().path(),
+            &tmp_repo.repo().workdir().unwrap(),
+        );
+        let working_log = repo_storage.working_log_for_base_commit("test-commit-sha");
+        let checkpoint = |diff: &str| {
+            Checkpoint::new(
+                CheckpointKind::Human,
+                diff.to_string(),
+                "test-author".to_string(),
+                vec![],
+            )
+        };
+
+        working_log.append_checkpoint(&checkpoint("first")).unwrap();
+        // An append that died part-way through
+        let checkpoints_file = working_log.dir.join("checkpoints.jsonl");
+        let mut file = fs::OpenOptions::new()
+            .append(true)
+            .open(&checkpoints_file)
+            .unwrap();
+        file.write_all(b"{\"kind\":\"Hu").unwrap();
+        drop(file);
+        assert_eq!(working_log.read_all_checkpoints().unwrap().len(), 1);
+
+        working_log
+            .append_checkpoint(&checkpoint("second"))
+            .unwrap();
+        let diffs: Vec<String> = working_log
+            .read_all_checkpoints()
+            .unwrap()
+            .into_iter()
+            .map(|checkpoint| checkpoint.diff)
+            .collect();
+        assert_eq!(diffs, vec!["first", "second"]);
+    }
+
+    #[test]
+    fn test_recover_interrupted_rewrite_restores_working_logs_and_keeps_notes() {
+        let (tmp_repo, _, _) = TmpRepo::new_with_base_commit().unwrap();
+        let repo = tmp_repo.gitai_repo();
+        let head = tmp_repo.head_commit_sha().unwrap();
+
+        let working_log = repo.storage.working_log_for_base_commit(&head);
+        let hash = working_log.persist_file_version("before rewrite").unwrap();
+
+        // A pid that has certainly exited
+        let mut child = std::process::Command::new("true").spawn().unwrap();
+        child.wait().unwrap();
+        let start_event =
+            crate::git::rewrite_log::RebaseStartEvent::new(head.clone(), false, None, None);
+        let journal = RewriteJournal {
+            pid: child.id(),
+            working_logs: vec![head.clone()],
+            ..RewriteJournal::new(
+                RewriteLogEvent::rebase_start(start_event),
+                "Test User <test@example.com>".to_string(),
+            )
+        };
+        repo.storage.write_rewrite_journal(&journal).unwrap();
+
+        // The interrupted rewrite got as far as dropping the working log, then notes arrived
+        // from elsewhere (a fetch, another process)
+        repo.storage
+            .delete_working_log_for_base_commit(&head)
+            .unwrap();
+        repo.git(&["notes", "--ref=ai", "add", "-f", "-m", "fetched", &head])
+            .unwrap();
+        let notes_after_crash = repo.git(&["rev-parse", "refs/notes/ai"]).unwrap();
+
+        let recovered = repo.recover_interrupted_rewrite().unwrap();
+        assert_eq!(recovered, Some(journal));
+        assert_eq!(
+            repo.git(&["rev-parse", "refs/notes/ai"]).unwrap(),
+            notes_after_crash
+        );
+        let working_log = repo.storage.working_log_for_base_commit(&head);
+        assert_eq!(
+            working_log.get_file_version(&hash).unwrap(),
+            "before rewrite"
+        );
+        assert!(repo.storage.read_rewrite_journal().is_none());
+        assert!(!repo.storage.rewrite_journal_working_logs.exists());
+        assert_eq!(repo.recover_interrupted_rewrite().unwrap(), None);
+    }
+
+    #[test]
+    fn test_to_repo_relative_path_normalizes_absolute_paths() {
+        let tmp_repo = TmpRepo::new().expect("Failed to create tmp repo");
+        let workdir = tmp_repo.repo().workdir().unwrap();
+        let repo_storage = RepoStorage::for_repo_path(tmp_repo.repo().path(), &workdir);
+        let working_log = repo_storage.working_log_for_base_commit("abc123def456");
+
+        let nested = workdir.join("src").join("app.rs");
+        assert_eq!(
+            working_log.to_repo_relative_path(&nested.to_string_lossy()),
+            "src/app.rs"
+        );
+
+        // Canonical form of the same path (\\?\C:\... on Windows), even before the file exists
+        let canonical = workdir.canonicalize().unwrap().join("src").join("app.rs");
+        assert_eq!(
+            working_log.to_repo_relative_path(&canonical.to_string_lossy()),
+            "src/app.rs"
+        );
+
+        assert_eq!(
+            working_log.to_repo_relative_path("src/app.rs"),
+            "src/app.rs"
+        );
+        let outside = workdir.parent().unwrap().join("elsewhere.rs");
+        assert_eq!(
+            working_log.to_repo_relative_path(&outside.to_string_lossy()),
+            outside.to_string_lossy()
+        );
+    }
+}
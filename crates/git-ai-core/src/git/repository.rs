@@ -1,14 +1,23 @@
 use crate::authorship::authorship_log_serialization::AuthorshipLog;
+use crate::authorship::file_text;
 use crate::authorship::rebase_authorship::rewrite_authorship_if_needed;
 use crate::config;
 use crate::error::GitAiError;
-use crate::git::refs::get_authorship;
-use crate::git::repo_storage::RepoStorage;
+use crate::git::cli_parser::invocation_dir;
+use crate::git::refs::{AI_AUTHORSHIP_REFNAME, backup_ai_notes, get_authorship};
+use crate::git::repo_path;
+use crate::git::repo_storage::{RepoStorage, RewriteJournal, WORKING_LOG_LOCK_TIMEOUT};
 use crate::git::rewrite_log::RewriteLogEvent;
 use crate::git::sync_authorship::{fetch_authorship_notes, push_authorship_notes};
+use crate::utils::{debug_log, now_secs};
 use std::collections::{HashMap, HashSet};
+use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
-use std::process::{Command, Output};
+use std::process::{Child, ChildStdout, Command, ExitStatus, Output, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, mpsc};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
 
 pub struct Object<'a> {
     repo: &'a Repository,
@@ -417,6 +426,19 @@ impl<'a> Commit<'a> {
         Ok(String::from_utf8(output.stdout)?.trim().to_string())
     }
 
+    // Get the full commit message, with surrounding whitespace trimmed.
+    pub fn message(&self) -> Result<String, GitAiError> {
+        let mut args = self.repo.global_args_for_exec();
+        args.push("show".to_string());
+        args.push("-s".to_string());
+        args.push("--no-notes".to_string());
+        args.push("--encoding=UTF-8".to_string());
+        args.push("--format=%B".to_string());
+        args.push(self.oid.clone());
+        let output = exec_git(&args)?;
+        Ok(String::from_utf8(output.stdout)?.trim().to_string())
+    }
+
     // Get the author of this commit.
     #[allow(dead_code)]
     pub fn author(&self) -> Result<Signature<'a>, GitAiError> {
@@ -606,9 +628,12 @@ impl<'a> Tree<'a> {
         // Use recursive to locate files in nested paths and return blob entries
         args.push("-r".to_string());
         args.push(self.oid.clone());
-        args.push("--".to_string());
         let path_str = path.to_string_lossy().to_string();
-        args.push(path_str.clone());
+        // A path that isn't UTF-8 can't be passed as an argument, so it's found in the full listing
+        if !repo_path::is_quoted_key(&path_str) {
+            args.push("--".to_string());
+            args.push(path_str.clone());
+        }
 
         let output = exec_git(&args)?;
         let bytes = output.stdout;
@@ -637,7 +662,10 @@ impl<'a> Tree<'a> {
                 continue;
             }
 
-            let file_path = String::from_utf8_lossy(file_bytes).to_string();
+            let file_path = repo_path::key_from_bytes(file_bytes);
+            if repo_path::is_quoted_key(&path_str) && file_path != path_str {
+                continue;
+            }
 
             // Prefer exact path match if multiple records somehow appear
             if found_entry.is_none() || file_path == path_str {
@@ -796,6 +824,8 @@ impl<'a> Iterator for References<'a> {
 
 #[derive(Debug, Clone)]
 pub struct Repository {
+    /// Passed to every git command, so replace refs, grafts and --no-replace-objects apply to
+    /// history walks, blame and object reads the same way they do in git itself
     global_args: Vec<String>,
     git_dir: PathBuf,
     pub storage: RepoStorage,
@@ -805,6 +835,11 @@ pub struct Repository {
     /// Canonical (absolute, resolved) version of workdir for reliable path comparisons
     /// On Windows, this uses the \\?\ UNC prefix format
     canonical_workdir: PathBuf,
+    /// No work tree (a server-side mirror); only commands that read from trees and notes work
+    bare: bool,
+    /// Where git ran from, relative to the work tree root (`src/` for `git -C src`), which is
+    /// what relative pathspecs on the command line are relative to
+    prefix: String,
 }
 
 impl Repository {
@@ -820,10 +855,14 @@ impl Repository {
     /// Execute an arbitrary git command and return stdout as string
     #[allow(dead_code)]
     pub fn git(&self, args: &[&str]) -> Result<String, GitAiError> {
+        Ok(String::from_utf8(self.git_bytes(args)?)?)
+    }
+
+    /// Like `git`, for output that may not be UTF-8, such as paths listed with `-z`
+    pub fn git_bytes(&self, args: &[&str]) -> Result<Vec<u8>, GitAiError> {
         let mut full_args = self.global_args_for_exec();
         full_args.extend(args.iter().map(|s| s.to_string()));
-        let output = exec_git(&full_args)?;
-        Ok(String::from_utf8(output.stdout)?)
+        Ok(exec_git(&full_args)?.stdout)
     }
 
     pub fn require_pre_command_head(&mut self) {
@@ -849,6 +888,10 @@ impl Repository {
         supress_output: bool,
         apply_side_effects: bool,
     ) {
+        tracing::debug!(
+            event = %serde_json::to_string(&rewrite_log_event).unwrap_or_default(),
+            "rewrite log event"
+        );
         let log = self
             .storage
             .append_rewrite_event(rewrite_log_event.clone())
@@ -856,6 +899,34 @@ impl Repository {
             .expect("Error writing .git/ai/rewrite_log");
 
         if apply_side_effects {
+            // Let an in-flight checkpoint finish before its working log is rewritten. Failing the
+            // git command would be worse than the race, so on timeout go ahead without the lock.
+            let _lock = self
+                .storage
+                .lock_working_logs(WORKING_LOG_LOCK_TIMEOUT)
+                .map_err(|e| debug_log(&format!("Rewriting without working-log lock: {}", e)))
+                .ok();
+
+            if let Err(e) = self.recover_interrupted_rewrite() {
+                debug_log(&format!("Failed to recover interrupted rewrite: {}", e));
+            }
+
+            // Rewrites overwrite existing notes; keep the old ones for `git-ai undo-notes`
+            if matches!(
+                rewrite_log_event,
+                RewriteLogEvent::CommitAmend { .. }
+                    | RewriteLogEvent::MergeSquash { .. }
+                    | RewriteLogEvent::RebaseComplete { .. }
+                    | RewriteLogEvent::CherryPickComplete { .. }
+            ) && let Err(e) = backup_ai_notes(self)
+            {
+                debug_log(&format!("Failed to back up notes before rewrite: {}", e));
+            }
+
+            // Journal the rewrite so a crash part-way through can be redone
+            let journal = RewriteJournal::new(rewrite_log_event.clone(), commit_author.clone());
+            let journaled = self.storage.write_rewrite_journal(&journal).is_ok();
+
             match rewrite_authorship_if_needed(
                 self,
                 &rewrite_log_event,
@@ -866,9 +937,61 @@ impl Repository {
                 Ok(_) => (),
                 Err(_) => {}
             }
+
+            if journaled {
+                let _ = self.storage.clear_rewrite_journal();
+            }
         }
     }
 
+    /// If a rewrite was interrupted (its journal is still on disk and the process that wrote it
+    /// is gone), rolls the working logs back to where they were before the rewrite and runs the
+    /// rewrite again. The notes aren't rolled back: the rewrite writes each of its commits' notes
+    /// whole, so running it again on top of the current notes replaces any it half-wrote and
+    /// keeps notes fetched or written since. Returns the journal of the recovered rewrite.
+    pub fn recover_interrupted_rewrite(&self) -> Result<Option<RewriteJournal>, GitAiError> {
+        let Some(journal) = self.storage.read_rewrite_journal() else {
+            return Ok(None);
+        };
+        // A live writer (other than a recycled pid, hence the age limit) is still working on it
+        let stale = now_secs().saturating_sub(journal.started_at) > REWRITE_JOURNAL_MAX_AGE_SECS;
+        if journal.pid != std::process::id() && process_is_running(journal.pid) && !stale {
+            return Ok(None);
+        }
+
+        debug_log(&format!(
+            "Recovering interrupted rewrite started at {}: {:?}",
+            journal.started_at, journal.event
+        ));
+        self.storage
+            .restore_rewrite_journal_working_logs(&journal)?;
+
+        let log = self.storage.read_rewrite_events()?;
+        let result = rewrite_authorship_if_needed(
+            self,
+            &journal.event,
+            journal.commit_author.clone(),
+            &log,
+            true,
+        );
+        self.storage.clear_rewrite_journal()?;
+        result?;
+        Ok(Some(journal))
+    }
+
+    /// The commit refs/notes/ai points at, if it exists
+    pub fn notes_ref_target(&self) -> Option<String> {
+        self.git(&[
+            "rev-parse",
+            "--verify",
+            "--quiet",
+            &format!("refs/notes/{}", AI_AUTHORSHIP_REFNAME),
+        ])
+        .ok()
+        .map(|oid| oid.trim().to_string())
+        .filter(|oid| !oid.is_empty())
+    }
+
     // Internal util to get the git object type for a given OID
     fn object_type(&self, oid: &str) -> Result<String, GitAiError> {
         let mut args = self.global_args_for_exec();
@@ -906,18 +1029,63 @@ impl Repository {
     }
 
     // Returns the path to the .git folder for normal repositories or the repository itself for bare repositories.
-    // TODO Test on bare repositories.
     pub fn path(&self) -> &Path {
         self.git_dir.as_path()
     }
 
     // Get the path of the working directory for this repository.
-    // If this repository is bare, then None is returned.
+    // If this repository is bare, then an error is returned.
     pub fn workdir(&self) -> Result<PathBuf, GitAiError> {
-        // TODO Remove Result since this is determined at initialization now
+        if self.bare {
+            return Err(GitAiError::Generic(format!(
+                "{} is a bare repository and has no work tree",
+                self.git_dir.display()
+            )));
+        }
         Ok(self.workdir.clone())
     }
 
+    pub fn is_bare(&self) -> bool {
+        self.bare
+    }
+
+    /// A path or pathspec from the command line as a path from the work tree root, the way git
+    /// resolves it: relative to where git ran (after `-C`), from the root with `:/` or `:(top)`,
+    /// or absolute
+    pub fn to_repo_path(&self, path: &str) -> String {
+        let (path, from_root) = if let Some(rest) = path.strip_prefix(":(top)") {
+            (rest, true)
+        } else if let Some(rest) = path.strip_prefix(":/") {
+            (rest, true)
+        } else {
+            (path, false)
+        };
+        let joined = if Path::new(path).is_absolute() {
+            let absolute = Path::new(path);
+            let relative = absolute
+                .strip_prefix(&self.workdir)
+                .or_else(|_| absolute.strip_prefix(&self.canonical_workdir))
+                .unwrap_or(absolute);
+            relative.to_string_lossy().to_string()
+        } else if from_root {
+            path.to_string()
+        } else {
+            format!("{}{}", self.prefix, path)
+        };
+
+        let mut components: Vec<&str> = Vec::new();
+        for component in joined.split(['/', '\\']) {
+            match component {
+                "" | "." => {}
+                ".." => {
+                    components.pop();
+                }
+                component => components.push(component),
+            }
+        }
+        components.join("/")
+    }
+
     /// Get the canonical (absolute, resolved) path of the working directory
     /// On Windows, this uses the \\?\ UNC prefix format for reliable path comparisons
     pub fn canonical_workdir(&self) -> &Path {
@@ -1288,13 +1456,8 @@ impl Repository {
             rp_args.push("--verify".to_string());
             rp_args.push(target_ref.clone());
 
-            let old_tip: Option<String> = match Command::new(config::Config::get().git_cmd())
-                .args(&rp_args)
-                .output()
-            {
-                Ok(output) if output.status.success() => {
-                    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
-                }
+            let old_tip: Option<String> = match exec_git(&rp_args) {
+                Ok(output) => Some(String::from_utf8_lossy(&output.stdout).trim().to_string()),
                 _ => None,
             };
 
@@ -1487,17 +1650,89 @@ impl Repository {
 
     /// Get the content of a file at a specific commit
     /// Uses `git show <commit>:<path>` for efficient single-call retrieval
-    #[allow(dead_code)]
     pub fn get_file_content(
         &self,
         file_path: &str,
         commit_hash: &str,
     ) -> Result<Vec<u8>, GitAiError> {
+        let content = if repo_path::is_quoted_key(file_path) {
+            self.blob_content_at_path(commit_hash, file_path)?
+        } else {
+            let mut args = self.global_args_for_exec();
+            args.push("show".to_string());
+            args.push(format!("{}:{}", commit_hash, file_path));
+            exec_git(&args)?.stdout
+        };
+        Ok(crate::git::lfs::smudge_blob_content(
+            self,
+            file_path,
+            &format!("{}:{}", commit_hash, file_path),
+            content,
+        ))
+    }
+
+    /// Content of the blob at `<rev>:<path>` for a path that isn't UTF-8 and so can't be passed as
+    /// an argument: `cat-file --batch` reads the object name from stdin as bytes. An empty `rev`
+    /// reads the index.
+    fn blob_content_at_path(&self, rev: &str, file_path: &str) -> Result<Vec<u8>, GitAiError> {
         let mut args = self.global_args_for_exec();
-        args.push("show".to_string());
-        args.push(format!("{}:{}", commit_hash, file_path));
-        let output = exec_git(&args)?;
-        Ok(output.stdout)
+        args.push("cat-file".to_string());
+        args.push("--batch".to_string());
+        let mut object_name = format!("{}:", rev).into_bytes();
+        object_name.extend(repo_path::key_to_bytes(file_path));
+        object_name.push(b'\n');
+
+        let output = exec_git_stdin(&args, &object_name)?;
+        let stdout = output.stdout;
+        // "<oid> blob <size>\n<content>\n", or "<name> missing\n"
+        let header_end = stdout.iter().position(|byte| *byte == b'\n').unwrap_or(0);
+        let header = String::from_utf8_lossy(&stdout[..header_end]);
+        let mut fields = header.split(' ').skip(1);
+        let size = match (fields.next(), fields.next()) {
+            (Some("blob"), Some(size)) => size.parse::<usize>().ok(),
+            _ => None,
+        };
+        let content = size.and_then(|size| stdout.get(header_end + 1..header_end + 1 + size));
+        content
+            .map(|content| content.to_vec())
+            .ok_or_else(|| GitAiError::Generic(format!("Path not found at {}: {}", rev, file_path)))
+    }
+
+    /// Contents of the blobs `oids`, read with one `cat-file --batch`. Fails if any of them is
+    /// missing or isn't a blob.
+    pub fn read_blobs(&self, oids: &[String]) -> Result<HashMap<String, Vec<u8>>, GitAiError> {
+        let mut blobs = HashMap::new();
+        if oids.is_empty() {
+            return Ok(blobs);
+        }
+        let mut args = self.global_args_for_exec();
+        args.push("cat-file".to_string());
+        args.push("--batch".to_string());
+        let mut input = oids.join("\n").into_bytes();
+        input.push(b'\n');
+
+        let stdout = exec_git_stdin(&args, &input)?.stdout;
+        // One "<oid> blob <size>\n<content>\n" (or "<name> missing\n") per requested object
+        let mut rest = &stdout[..];
+        for oid in oids {
+            let header_end = rest
+                .iter()
+                .position(|byte| *byte == b'\n')
+                .ok_or_else(|| GitAiError::Generic(format!("Blob not read: {}", oid)))?;
+            let header = String::from_utf8_lossy(&rest[..header_end]).to_string();
+            let mut fields = header.split(' ').skip(1);
+            let size = match (fields.next(), fields.next()) {
+                (Some("blob"), Some(size)) => size.parse::<usize>().ok(),
+                _ => None,
+            }
+            .ok_or_else(|| GitAiError::Generic(format!("Not a blob: {}", header)))?;
+            let content = rest
+                .get(header_end + 1..header_end + 1 + size)
+                .ok_or_else(|| GitAiError::Generic(format!("Blob cut short: {}", oid)))?;
+            blobs.insert(oid.clone(), content.to_vec());
+            rest = rest.get(header_end + 2 + size..).unwrap_or(&[]);
+        }
+        Ok(blobs)
     }
 
     /// Get content of all staged files concurrently
@@ -1506,7 +1741,7 @@ impl Repository {
     pub fn get_all_staged_files_content(
         &self,
         file_paths: &[String],
-    ) -> Result<HashMap<String, String>, GitAiError> {
+    ) -> Result<HashMap<String, std::sync::Arc<str>>, GitAiError> {
         use futures::future::join_all;
         use std::sync::Arc;
 
@@ -1526,10 +1761,7 @@ impl Repository {
 
                 async move {
                     let _permit = semaphore.acquire().await;
-                    let result = exec_git(&args).and_then(|output| {
-                        String::from_utf8(output.stdout)
-                            .map_err(|e| GitAiError::Utf8Error(e.utf8_error()))
-                    });
+                    let result = exec_git(&args).map(|output| file_text::decode(&output.stdout));
                     (file_path, result)
                 }
             })
@@ -1539,8 +1771,14 @@ impl Repository {
 
         let mut staged_files = HashMap::new();
         for (file_path, result) in results {
+            let result = if repo_path::is_quoted_key(&file_path) {
+                self.blob_content_at_path("", &file_path)
+                    .map(|content| file_text::decode(&content))
+            } else {
+                result
+            };
             if let Ok(content) = result {
-                staged_files.insert(file_path, content);
+                staged_files.insert(file_path, Arc::from(content));
             }
         }
 
@@ -1571,22 +1809,15 @@ impl Repository {
 
         args.push(commit_sha.to_string());
 
-        // Add pathspecs if provided
-        if let Some(paths) = pathspecs {
-            args.push("--".to_string());
-            for path in paths {
-                args.push(path.clone());
-            }
-        }
+        let narrowed = push_pathspecs(&mut args, pathspecs);
 
         let output = exec_git(&args)?;
-        let stdout = String::from_utf8(output.stdout)?;
-
-        let files: HashSet<String> = stdout
-            .lines()
-            .filter(|line| !line.is_empty())
-            .map(|line| line.to_string())
+        let mut files: HashSet<String> = repo_path::parse_name_only(&output.stdout)
+            .into_iter()
             .collect();
+        if !narrowed && let Some(paths) = pathspecs {
+            files.retain(|file| paths.contains(file));
+        }
 
         Ok(files)
     }
@@ -1610,18 +1841,34 @@ impl Repository {
         args.push(from_ref.to_string());
         args.push(to_ref.to_string());
 
-        // Add pathspecs if provided
-        if let Some(paths) = pathspecs {
-            args.push("--".to_string());
-            for path in paths {
-                args.push(path.clone());
-            }
-        }
+        let narrowed = push_pathspecs(&mut args, pathspecs);
 
         let output = exec_git(&args)?;
-        let diff_output = String::from_utf8(output.stdout)?;
+        let mut added_lines = parse_diff_added_lines(&output.stdout)?;
+        if !narrowed && let Some(paths) = pathspecs {
+            added_lines.retain(|file, _| paths.contains(file));
+        }
+        Ok(added_lines)
+    }
+
+    /// Get added line numbers from the staged changes, against `from_ref` (the empty tree when
+    /// None, for a first commit)
+    pub fn diff_staged_added_lines(
+        &self,
+        from_ref: Option<&str>,
+    ) -> Result<HashMap<String, Vec<u32>>, GitAiError> {
+        let mut args = self.global_args_for_exec();
+        args.push("diff".to_string());
+        args.push("--cached".to_string());
+        args.push("-U0".to_string());
+        args.push("--no-color".to_string());
+        args.push("--no-ext-diff".to_string());
+        if let Some(from_ref) = from_ref {
+            args.push(from_ref.to_string());
+        }
 
-        parse_diff_added_lines(&diff_output)
+        let output = exec_git(&args)?;
+        parse_diff_added_lines(&output.stdout)
     }
 
     /// Get list of changed files between two refs using `git diff --name-only`
@@ -1638,15 +1885,7 @@ impl Repository {
         args.push(to_ref.to_string());
 
         let output = exec_git(&args)?;
-        let stdout = String::from_utf8(output.stdout)?;
-
-        let files: Vec<String> = stdout
-            .lines()
-            .filter(|line| !line.is_empty())
-            .map(|line| line.to_string())
-            .collect();
-
-        Ok(files)
+        Ok(repo_path::parse_name_only(&output.stdout))
     }
 
     /// Get added line ranges from git diff between a commit and the working directory
@@ -1665,48 +1904,14 @@ impl Repository {
         args.push("--no-color".to_string());
         args.push(from_ref.to_string());
 
-        // Add pathspecs if provided
-        if let Some(paths) = pathspecs {
-            args.push("--".to_string());
-            for path in paths {
-                args.push(path.clone());
-            }
-        }
+        let narrowed = push_pathspecs(&mut args, pathspecs);
 
         let output = exec_git(&args)?;
-        let diff_output = String::from_utf8(output.stdout)?;
-
-        parse_diff_added_lines(&diff_output)
-    }
-
-    /// Get added line ranges from git diff between a commit and the working directory,
-    /// along with information about which lines are pure insertions (old_count=0).
-    ///
-    /// Returns (all_added_lines, pure_insertion_lines)
-    /// Pure insertions are lines that were added without modifying existing lines at that position.
-    pub fn diff_workdir_added_lines_with_insertions(
-        &self,
-        from_ref: &str,
-        pathspecs: Option<&HashSet<String>>,
-    ) -> Result<(HashMap<String, Vec<u32>>, HashMap<String, Vec<u32>>), GitAiError> {
-        let mut args = self.global_args_for_exec();
-        args.push("diff".to_string());
-        args.push("-U0".to_string()); // Zero context lines
-        args.push("--no-color".to_string());
-        args.push(from_ref.to_string());
-
-        // Add pathspecs if provided
-        if let Some(paths) = pathspecs {
-            args.push("--".to_string());
-            for path in paths {
-                args.push(path.clone());
-            }
+        let mut added_lines = parse_diff_added_lines(&output.stdout)?;
+        if !narrowed && let Some(paths) = pathspecs {
+            added_lines.retain(|file, _| paths.contains(file));
         }
-
-        let output = exec_git(&args)?;
-        let diff_output = String::from_utf8(output.stdout)?;
-
-        parse_diff_added_lines_with_insertions(&diff_output)
+        Ok(added_lines)
     }
 
     pub fn fetch_branch(&self, branch_name: &str, remote_name: &str) -> Result<(), GitAiError> {
@@ -1724,13 +1929,33 @@ pub fn find_repository(global_args: &Vec<String>) -> Result<Repository, GitAiErr
     args.push("rev-parse".to_string());
     args.push("--absolute-git-dir".to_string());
     args.push("--show-toplevel".to_string());
+    args.push("--show-prefix".to_string());
+
+    let output = match exec_git(&args) {
+        // --show-toplevel fails in a bare repository; open it without a work tree instead
+        Err(GitAiError::GitCliError { ref stderr, .. })
+            if stderr.contains("must be run in a work tree") =>
+        {
+            return find_bare_repository(global_args);
+        }
+        result => result,
+    };
+    let output = output.map_err(|e| match e {
+        GitAiError::GitCliError { ref stderr, .. }
+            if stderr.contains("not a git repository") || stderr.contains("cannot change to") =>
+        {
+            let path = invocation_dir(global_args).display().to_string();
+            GitAiError::RepositoryNotFound { path }
+        }
+        e => e,
+    })?;
+    let stdout = String::from_utf8(output.stdout)?;
 
-    let output = exec_git(&args)?;
-    let both_dirs = String::from_utf8(output.stdout)?;
-
-    let both_dirs = both_dirs.trim();
-    let git_dir_str = both_dirs.split("\n").next().unwrap();
-    let workdir_str = both_dirs.split("\n").nth(1).unwrap();
+    // The prefix line is empty at the top of the work tree, so don't trim it away
+    let mut lines = stdout.lines();
+    let git_dir_str = lines.next().unwrap_or_default().trim();
+    let workdir_str = lines.next().unwrap_or_default().trim();
+    let prefix = lines.next().unwrap_or_default().trim().to_string();
     let git_dir = PathBuf::from(git_dir_str);
     let workdir = PathBuf::from(workdir_str);
     if !git_dir.is_dir() {
@@ -1765,26 +1990,69 @@ pub fn find_repository(global_args: &Vec<String>) -> Result<Repository, GitAiErr
         pre_command_refname: None,
         workdir,
         canonical_workdir,
+        bare: false,
+        prefix,
     })
 }
 
+fn find_bare_repository(global_args: &Vec<String>) -> Result<Repository, GitAiError> {
+    let mut args = global_args.clone();
+    args.push("rev-parse".to_string());
+    args.push("--absolute-git-dir".to_string());
+    let output = exec_git(&args)?;
+    let git_dir = PathBuf::from(String::from_utf8(output.stdout)?.trim());
+    let mut repository = from_bare_repository(&git_dir)?;
+    // Keep the invocation's other options, like --no-replace-objects, so history reads the same
+    // objects it would through git
+    repository
+        .global_args
+        .extend(without_location_args(global_args));
+    Ok(repository)
+}
+
+/// `global_args` without the options that locate the repository (-C, --git-dir, --work-tree)
+fn without_location_args(global_args: &[String]) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut iter = global_args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "-C" | "--git-dir" | "--work-tree" => {
+                iter.next();
+            }
+            _ if arg.starts_with("-C")
+                || arg.starts_with("--git-dir=")
+                || arg.starts_with("--work-tree=") => {}
+            _ => args.push(arg.clone()),
+        }
+    }
+    args
+}
+
+/// Opens a repository with no work tree. Nothing is written to it until a command needs to: the
+/// read-only commands (stats, show, query, fsck) work from trees and notes alone.
 pub fn from_bare_repository(git_dir: &Path) -> Result<Repository, GitAiError> {
-    let workdir = git_dir
-        .parent()
-        .ok_or_else(|| GitAiError::Generic("Git directory has no parent".to_string()))?
-        .to_path_buf();
+    if !git_dir.is_dir() {
+        return Err(GitAiError::Generic(format!(
+            "Git directory does not exist: {}",
+            git_dir.display()
+        )));
+    }
     let global_args = vec!["-C".to_string(), git_dir.to_string_lossy().to_string()];
 
+    // Stand-ins so path helpers have something to compare against; workdir() still errors
+    let workdir = git_dir.to_path_buf();
     let canonical_workdir = workdir.canonicalize().unwrap_or_else(|_| workdir.clone());
 
     Ok(Repository {
         global_args,
-        storage: RepoStorage::for_repo_path(git_dir, &workdir),
+        storage: RepoStorage::for_bare_repo_path(git_dir),
         git_dir: git_dir.to_path_buf(),
         pre_command_base_commit: None,
         pre_command_refname: None,
         workdir,
         canonical_workdir,
+        bare: true,
+        prefix: String::new(),
     })
 }
 
@@ -1793,101 +2061,314 @@ pub fn find_repository_in_path(path: &str) -> Result<Repository, GitAiError> {
     return find_repository(&global_args);
 }
 
-/// Helper to execute a git command
-pub fn exec_git(args: &[String]) -> Result<Output, GitAiError> {
-    // TODO Make sure to handle process signals, etc.
-    let output = Command::new(config::Config::get().git_cmd())
-        .args(args)
-        .output()
-        .map_err(GitAiError::IoError)?;
+/// Age after which a rewrite journal is recovered even if its pid is alive (it's been reused)
+const REWRITE_JOURNAL_MAX_AGE_SECS: u64 = 60 * 60;
 
-    if !output.status.success() {
-        let code = output.status.code();
-        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-        return Err(GitAiError::GitCliError {
-            code,
-            stderr,
-            args: args.to_vec(),
-        });
+#[cfg(unix)]
+fn process_is_running(pid: u32) -> bool {
+    // Signal 0 only checks for existence; EPERM means it exists but belongs to someone else
+    let result = unsafe { libc::kill(pid as libc::pid_t, 0) };
+    result == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+/// Without a cheap liveness check, only the age limit keeps a live rewrite from being recovered
+#[cfg(not(unix))]
+fn process_is_running(_pid: u32) -> bool {
+    true
+}
+
+/// A git binary that can't be found gets its own error so callers can tell it apart
+fn spawn_error(err: std::io::Error) -> GitAiError {
+    if err.kind() == std::io::ErrorKind::NotFound {
+        GitAiError::GitNotFound {
+            git_cmd: config::Config::get().git_cmd().to_string(),
+        }
+    } else {
+        GitAiError::IoError(err)
     }
+}
 
-    Ok(output)
+/// Helper to execute a git command
+pub fn exec_git(args: &[String]) -> Result<Output, GitAiError> {
+    run_git(args, &[], None)
 }
 
 /// Helper to execute a git command with data provided on stdin
 pub fn exec_git_stdin(args: &[String], stdin_data: &[u8]) -> Result<Output, GitAiError> {
-    // TODO Make sure to handle process signals, etc.
-    let mut child = Command::new(config::Config::get().git_cmd())
-        .args(args)
-        .stdin(std::process::Stdio::piped())
-        .stdout(std::process::Stdio::piped())
-        .stderr(std::process::Stdio::piped())
-        .spawn()
-        .map_err(GitAiError::IoError)?;
+    run_git(args, &[], Some(stdin_data))
+}
 
-    if let Some(mut stdin) = child.stdin.take() {
-        use std::io::Write;
-        if let Err(e) = stdin.write_all(stdin_data) {
-            return Err(GitAiError::IoError(e));
-        }
-    }
+/// Helper to execute a git command with data provided on stdin and additional environment variables
+#[allow(dead_code)]
+pub fn exec_git_stdin_with_env(
+    args: &[String],
+    env: &Vec<(String, String)>,
+    stdin_data: &[u8],
+) -> Result<Output, GitAiError> {
+    run_git(args, env, Some(stdin_data))
+}
 
-    let output = child.wait_with_output().map_err(GitAiError::IoError)?;
+/// First delay before retrying a git command that hit another process's lock; doubles each retry
+const GIT_LOCK_RETRY_DELAY: Duration = Duration::from_millis(50);
+
+/// Runs git, killing it if it outlives the configured timeout (say, waiting on a credential
+/// prompt) and retrying with backoff while another git process holds a lock it needs
+fn run_git(
+    args: &[String],
+    env: &[(String, String)],
+    stdin_data: Option<&[u8]>,
+) -> Result<Output, GitAiError> {
+    let config = config::Config::get();
+    let mut retries = 0;
+    loop {
+        let output = run_git_once(args, env, stdin_data, config.git_timeout())?;
+        if output.status.success() {
+            return Ok(output);
+        }
 
-    if !output.status.success() {
-        let code = output.status.code();
         let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        if retries < config.git_lock_retries() && is_lock_contention(&stderr) {
+            let delay = GIT_LOCK_RETRY_DELAY * 2u32.pow(retries);
+            debug_log(&format!(
+                "git {} hit a lock, retrying in {:?}: {}",
+                args.join(" "),
+                delay,
+                stderr.trim()
+            ));
+            std::thread::sleep(delay);
+            retries += 1;
+            continue;
+        }
         return Err(GitAiError::GitCliError {
-            code,
+            code: output.status.code(),
             stderr,
             args: args.to_vec(),
         });
     }
+}
 
-    Ok(output)
+/// Whether git failed because a `.lock` file (index.lock, a ref lock, ...) already existed,
+/// which clears up once the git process holding it finishes
+fn is_lock_contention(stderr: &str) -> bool {
+    stderr.contains(".lock': File exists")
 }
 
-/// Helper to execute a git command with data provided on stdin and additional environment variables
-#[allow(dead_code)]
-pub fn exec_git_stdin_with_env(
+fn run_git_once(
     args: &[String],
-    env: &Vec<(String, String)>,
-    stdin_data: &[u8],
+    env: &[(String, String)],
+    stdin_data: Option<&[u8]>,
+    timeout: Option<Duration>,
 ) -> Result<Output, GitAiError> {
-    // TODO Make sure to handle process signals, etc.
     let mut cmd = Command::new(config::Config::get().git_cmd());
     cmd.args(args)
-        .stdin(std::process::Stdio::piped())
-        .stdout(std::process::Stdio::piped())
-        .stderr(std::process::Stdio::piped());
+        .envs(env.iter().map(|(k, v)| (k, v)))
+        .stdin(if stdin_data.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    let mut child = cmd.spawn().map_err(spawn_error)?;
+
+    // Feed stdin and drain stdout/stderr on their own threads, so git can't block on a full
+    // pipe while we wait for it to exit
+    let stdin_writer = child.stdin.take().zip(stdin_data).map(|(mut stdin, data)| {
+        let data = data.to_vec();
+        std::thread::spawn(move || stdin.write_all(&data))
+    });
+    let stdout_reader = child.stdout.take().map(read_pipe);
+    let stderr_reader = child.stderr.take().map(read_pipe);
+
+    let Some(status) = wait_with_timeout(&mut child, timeout)? else {
+        // The readers are left behind: anything git spawned (a credential helper) may still
+        // hold the pipes open
+        let _ = child.kill();
+        let _ = child.wait();
+        return Err(GitAiError::GitTimeout {
+            args: args.to_vec(),
+            timeout_secs: timeout.map_or(0, |t| t.as_secs()),
+        });
+    };
+
+    if let Some(writer) = stdin_writer {
+        join_pipe(writer)?;
+    }
+    Ok(Output {
+        status,
+        stdout: stdout_reader
+            .map(join_pipe)
+            .transpose()?
+            .unwrap_or_default(),
+        stderr: stderr_reader
+            .map(join_pipe)
+            .transpose()?
+            .unwrap_or_default(),
+    })
+}
+
+fn read_pipe(mut pipe: impl Read + Send + 'static) -> JoinHandle<std::io::Result<Vec<u8>>> {
+    std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        pipe.read_to_end(&mut buf).map(|_| buf)
+    })
+}
+
+fn join_pipe<T>(handle: JoinHandle<std::io::Result<T>>) -> Result<T, GitAiError> {
+    handle
+        .join()
+        .map_err(|_| GitAiError::Generic("git pipe thread panicked".to_string()))?
+        .map_err(GitAiError::IoError)
+}
+
+/// Waits for `child` to exit; None if it's still running after `timeout`
+fn wait_with_timeout(
+    child: &mut Child,
+    timeout: Option<Duration>,
+) -> Result<Option<ExitStatus>, GitAiError> {
+    let Some(timeout) = timeout else {
+        return Ok(Some(child.wait()?));
+    };
+    let started = Instant::now();
+    // Most git calls finish in a few milliseconds, so start polling fast
+    let mut poll = Duration::from_millis(1);
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        if started.elapsed() >= timeout {
+            return Ok(None);
+        }
+        std::thread::sleep(poll);
+        poll = (poll * 2).min(Duration::from_millis(50));
+    }
+}
 
-    // Apply env overrides
-    for (k, v) in env.iter() {
-        cmd.env(k, v);
+/// A git command whose stdout is read as it's produced. Its stderr is drained on a thread, and
+/// it's killed if it outlives the configured timeout or is dropped before `finish`.
+pub struct GitStream {
+    child: Arc<Mutex<Child>>,
+    stdout: Option<ChildStdout>,
+    stderr: Option<JoinHandle<std::io::Result<Vec<u8>>>>,
+    /// Dropped on finish, which stops the timeout watchdog
+    finished: Option<mpsc::Sender<()>>,
+    timed_out: Arc<AtomicBool>,
+    timeout: Option<Duration>,
+    args: Vec<String>,
+}
+
+impl GitStream {
+    /// The command's stdout, once
+    pub fn take_stdout(&mut self) -> Option<ChildStdout> {
+        self.stdout.take()
     }
 
-    let mut child = cmd.spawn().map_err(GitAiError::IoError)?;
+    /// Waits for git to exit, failing if it timed out or exited unsuccessfully
+    pub fn finish(&mut self) -> Result<(), GitAiError> {
+        self.stdout = None;
+        // Poll rather than block in wait(), so the watchdog can still take the lock to kill git
+        let status = loop {
+            if let Some(status) = self.child.lock().unwrap().try_wait()? {
+                break status;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        };
+        self.finished = None;
+        // The stderr reader is left behind on timeout: anything git spawned may still hold the
+        // pipe open
+        if self.timed_out.load(Ordering::SeqCst) {
+            return Err(GitAiError::GitTimeout {
+                args: self.args.clone(),
+                timeout_secs: self.timeout.map_or(0, |t| t.as_secs()),
+            });
+        }
+        let stderr = self
+            .stderr
+            .take()
+            .map(join_pipe)
+            .transpose()?
+            .unwrap_or_default();
+        if !status.success() {
+            return Err(GitAiError::GitCliError {
+                code: status.code(),
+                stderr: String::from_utf8_lossy(&stderr).to_string(),
+                args: self.args.clone(),
+            });
+        }
+        Ok(())
+    }
+}
 
-    if let Some(mut stdin) = child.stdin.take() {
-        use std::io::Write;
-        if let Err(e) = stdin.write_all(stdin_data) {
-            return Err(GitAiError::IoError(e));
+impl Drop for GitStream {
+    fn drop(&mut self) {
+        if self.finished.is_some() {
+            let mut child = self.child.lock().unwrap();
+            let _ = child.kill();
+            let _ = child.wait();
         }
     }
+}
 
-    let output = child.wait_with_output().map_err(GitAiError::IoError)?;
+/// Starts git for its output to be read as it comes, see `GitStream`. Unlike `exec_git` it isn't
+/// retried on lock contention, as its output may already have been used.
+pub fn spawn_git_stream(args: &[String]) -> Result<GitStream, GitAiError> {
+    spawn_git_stream_with_timeout(args, config::Config::get().git_timeout())
+}
 
-    if !output.status.success() {
-        let code = output.status.code();
-        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-        return Err(GitAiError::GitCliError {
-            code,
-            stderr,
-            args: args.to_vec(),
+fn spawn_git_stream_with_timeout(
+    args: &[String],
+    timeout: Option<Duration>,
+) -> Result<GitStream, GitAiError> {
+    let mut child = Command::new(config::Config::get().git_cmd())
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(spawn_error)?;
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take().map(read_pipe);
+    let child = Arc::new(Mutex::new(child));
+    let timed_out = Arc::new(AtomicBool::new(false));
+
+    let (finished, watchdog) = mpsc::channel::<()>();
+    if let Some(timeout) = timeout {
+        let child = Arc::clone(&child);
+        let timed_out = Arc::clone(&timed_out);
+        std::thread::spawn(move || {
+            if watchdog.recv_timeout(timeout) == Err(mpsc::RecvTimeoutError::Timeout) {
+                let mut child = child.lock().unwrap();
+                if matches!(child.try_wait(), Ok(None)) {
+                    timed_out.store(true, Ordering::SeqCst);
+                    let _ = child.kill();
+                }
+            }
         });
     }
 
-    Ok(output)
+    Ok(GitStream {
+        child,
+        stdout,
+        stderr,
+        finished: Some(finished),
+        timed_out,
+        timeout,
+        args: args.to_vec(),
+    })
+}
+
+/// Appends `-- <paths>` to narrow a diff to `paths` and returns true, unless a path isn't
+/// UTF-8 and can't be passed as an argument; then the caller narrows git's output itself
+pub fn push_pathspecs(args: &mut Vec<String>, pathspecs: Option<&HashSet<String>>) -> bool {
+    let Some(paths) = pathspecs else {
+        return true;
+    };
+    if paths.iter().any(|path| repo_path::is_quoted_key(path)) {
+        return false;
+    }
+    args.push("--".to_string());
+    args.extend(paths.iter().cloned());
+    true
 }
 
 /// Parse git diff output to extract added line numbers per file
@@ -1897,25 +2378,32 @@ pub fn exec_git_stdin_with_env(
 ///
 /// This means: old file line 10 (2 lines), new file line 15 (5 lines)
 /// We extract the "new file" line numbers to know which lines were added.
-fn parse_diff_added_lines(diff_output: &str) -> Result<HashMap<String, Vec<u32>>, GitAiError> {
+/// Works on bytes, since paths and file content needn't be UTF-8.
+fn parse_diff_added_lines(diff_output: &[u8]) -> Result<HashMap<String, Vec<u32>>, GitAiError> {
     let mut result: HashMap<String, Vec<u32>> = HashMap::new();
     let mut current_file: Option<String> = None;
+    // Between `diff --git` and the first hunk, where an added `++ x` line can't be mistaken
+    // for the `+++` header
+    let mut in_file_header = false;
 
-    for line in diff_output.lines() {
-        // Track current file being diffed
-        if line.starts_with("+++ b/") {
-            current_file = Some(line[6..].to_string());
-        } else if line.starts_with("+++ /dev/null") {
-            // File was deleted
+    for line in diff_output.split(|byte| *byte == b'\n') {
+        if line.starts_with(b"diff ") {
+            in_file_header = true;
             current_file = None;
-        } else if line.starts_with("@@ ") {
+        } else if in_file_header && let Some(header) = line.strip_prefix(b"+++ ") {
+            // Track current file being diffed; None for a deleted file (+++ /dev/null)
+            current_file = repo_path::key_from_diff_header(header);
+            in_file_header = false;
+        } else if line.starts_with(b"@@ ") {
+            in_file_header = false;
             // Parse hunk header: @@ -old_start,old_count +new_start,new_count @@
             if let Some(ref file) = current_file {
-                if let Some((added_lines, _is_pure_insertion)) = parse_hunk_header(line) {
+                let line = String::from_utf8_lossy(line);
+                if let Some((_, _, new_start, new_count)) = parse_hunk_header(&line) {
                     result
                         .entry(file.clone())
                         .or_insert_with(Vec::new)
-                        .extend(added_lines);
+                        .extend(new_start..new_start + new_count);
                 }
             }
         }
@@ -1930,116 +2418,260 @@ fn parse_diff_added_lines(diff_output: &str) -> Result<HashMap<String, Vec<u32>>
     Ok(result)
 }
 
-/// Parses the unified diff output to extract line numbers of added lines,
-/// along with information about which are pure insertions (old_count=0).
-///
-/// Returns (all_added_lines, pure_insertion_lines)
-fn parse_diff_added_lines_with_insertions(
-    diff_output: &str,
-) -> Result<(HashMap<String, Vec<u32>>, HashMap<String, Vec<u32>>), GitAiError> {
-    let mut all_lines: HashMap<String, Vec<u32>> = HashMap::new();
-    let mut insertion_lines: HashMap<String, Vec<u32>> = HashMap::new();
-    let mut current_file: Option<String> = None;
-
-    for line in diff_output.lines() {
-        // Track current file being diffed
-        if line.starts_with("+++ b/") {
-            current_file = Some(line[6..].to_string());
-        } else if line.starts_with("+++ /dev/null") {
-            // File was deleted
-            current_file = None;
-        } else if line.starts_with("@@ ") {
-            // Parse hunk header: @@ -old_start,old_count +new_start,new_count @@
-            if let Some(ref file) = current_file {
-                if let Some((added_lines, is_pure_insertion)) = parse_hunk_header(line) {
-                    all_lines
-                        .entry(file.clone())
-                        .or_insert_with(Vec::new)
-                        .extend(added_lines.clone());
-
-                    if is_pure_insertion {
-                        insertion_lines
-                            .entry(file.clone())
-                            .or_insert_with(Vec::new)
-                            .extend(added_lines);
-                    }
-                }
-            }
+/// `(old_start, old_count, new_start, new_count)` of a hunk header `@@ -l,s +l,s @@`, where
+/// a missing count means 1. Combined diffs' `@@@` headers aren't parsed.
+pub(crate) fn parse_hunk_header(line: &str) -> Option<(u32, u32, u32, u32)> {
+    let ranges = line.strip_prefix("@@ -")?;
+    let (old, rest) = ranges.split_once(" +")?;
+    let (new, _) = rest.split_once(" @@")?;
+    let range = |range: &str| -> Option<(u32, u32)> {
+        match range.split_once(',') {
+            Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+            None => Some((range.parse().ok()?, 1)),
         }
-    }
-
-    // Sort and deduplicate line numbers for each file
-    for lines in all_lines.values_mut() {
-        lines.sort_unstable();
-        lines.dedup();
-    }
-    for lines in insertion_lines.values_mut() {
-        lines.sort_unstable();
-        lines.dedup();
-    }
-
-    Ok((all_lines, insertion_lines))
-}
-
-/// Parse a hunk header line to extract added line numbers and whether it's a pure insertion
-///
-/// Format: @@ -old_start,old_count +new_start,new_count @@
-/// Returns (line numbers that were added, is_pure_insertion)
-/// is_pure_insertion is true when old_count=0, meaning these are new lines, not modifications
-fn parse_hunk_header(line: &str) -> Option<(Vec<u32>, bool)> {
-    // Find the part between @@ and @@
-    let parts: Vec<&str> = line.split("@@").collect();
-    if parts.len() < 2 {
-        return None;
-    }
-
-    let hunk_info = parts[1].trim();
-
-    // Split by space to get old and new ranges
-    let ranges: Vec<&str> = hunk_info.split_whitespace().collect();
-    if ranges.len() < 2 {
-        return None;
-    }
-
-    // Parse the old file range (starts with '-')
-    let old_range = ranges
-        .iter()
-        .find(|r| r.starts_with('-'))?
-        .trim_start_matches('-');
-
-    // Parse "start,count" or just "start" for old range
-    let old_parts: Vec<&str> = old_range.split(',').collect();
-    let old_count: u32 = if old_parts.len() > 1 {
-        old_parts[1].parse().ok()?
-    } else {
-        1 // If no count specified, it's 1 line
     };
+    let (old_start, old_count) = range(old)?;
+    let (new_start, new_count) = range(new)?;
+    Some((old_start, old_count, new_start, new_count))
+}
 
-    // Parse the new file range (starts with '+')
-    let new_range = ranges
-        .iter()
-        .find(|r| r.starts_with('+'))?
-        .trim_start_matches('+');
-
-    // Parse "start,count" or just "start"
-    let new_parts: Vec<&str> = new_range.split(',').collect();
-    let start: u32 = new_parts[0].parse().ok()?;
-    let count: u32 = if new_parts.len() > 1 {
-        new_parts[1].parse().ok()?
-    } else {
-        1 // If no count specified, it's 1 line
-    };
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::test_utils::TmpRepo;
 
-    // If count is 0, no lines were added (only deleted)
-    if count == 0 {
-        return Some((Vec::new(), false));
+    fn args(repo: &TmpRepo, rest: &[&str]) -> Vec<String> {
+        let mut args = repo.gitai_repo().global_args_for_exec();
+        args.extend(rest.iter().map(|arg| arg.to_string()));
+        args
     }
 
-    // Generate all line numbers in the range
-    let lines: Vec<u32> = (start..start + count).collect();
+    #[test]
+    fn test_run_git_once_kills_git_after_timeout() {
+        let tmp_repo = TmpRepo::new().unwrap();
+        let args = args(&tmp_repo, &["-c", "alias.stall=!sleep 5", "stall"]);
 
-    // Pure insertion if old_count is 0 (no lines from old file were modified)
-    let is_pure_insertion = old_count == 0;
+        let started = Instant::now();
+        match run_git_once(&args, &[], None, Some(Duration::from_millis(200))) {
+            Err(GitAiError::GitTimeout {
+                args: timed_out, ..
+            }) => assert_eq!(timed_out, args),
+            other => panic!("expected GitTimeout, got {:?}", other),
+        }
+        assert!(started.elapsed() < Duration::from_secs(4));
+    }
+
+    #[test]
+    fn test_to_repo_path_resolves_like_git() {
+        let tmp_repo = TmpRepo::new().unwrap();
+        let root = tmp_repo.repo().path().parent().unwrap().to_path_buf();
+        std::fs::create_dir(root.join("src")).unwrap();
+        let repo = find_repository_in_path(&root.join("src").to_string_lossy()).unwrap();
+
+        assert_eq!(repo.to_repo_path("lib.rs"), "src/lib.rs");
+        assert_eq!(repo.to_repo_path("./nested/../lib.rs"), "src/lib.rs");
+        assert_eq!(repo.to_repo_path("../README.md"), "README.md");
+        assert_eq!(repo.to_repo_path("."), "src");
+        assert_eq!(repo.to_repo_path(":/README.md"), "README.md");
+        assert_eq!(repo.to_repo_path(":(top)docs"), "docs");
+        assert_eq!(
+            repo.to_repo_path(&root.join("docs/a.md").to_string_lossy()),
+            "docs/a.md"
+        );
+
+        let top = find_repository_in_path(&root.to_string_lossy()).unwrap();
+        assert_eq!(top.to_repo_path("src/lib.rs"), "src/lib.rs");
+    }
+
+    #[test]
+    fn test_exec_git_retries_while_index_is_locked() {
+        let tmp_repo = TmpRepo::new().unwrap();
+        tmp_repo.write_file("a.txt", "a\n", false).unwrap();
+        let lock = tmp_repo.repo().path().join("index.lock");
+        std::fs::write(&lock, "").unwrap();
+        let unlock = std::thread::spawn({
+            let lock = lock.clone();
+            move || {
+                std::thread::sleep(Duration::from_millis(150));
+                std::fs::remove_file(lock).unwrap();
+            }
+        });
 
-    Some((lines, is_pure_insertion))
+        exec_git(&args(&tmp_repo, &["add", "a.txt"])).expect("add should succeed once unlocked");
+        unlock.join().unwrap();
+    }
+
+    #[test]
+    fn test_git_stream_drains_stderr_and_times_out() {
+        let tmp_repo = TmpRepo::new().unwrap();
+
+        // More stderr than a pipe holds, which would block git if nobody read it
+        let noisy = args(
+            &tmp_repo,
+            &[
+                "-c",
+                "alias.noisy=!head -c 1000000 /dev/zero >&2; echo done",
+                "noisy",
+            ],
+        );
+        let mut git = spawn_git_stream_with_timeout(&noisy, Some(Duration::from_secs(30))).unwrap();
+        let mut stdout = String::new();
+        git.take_stdout()
+            .unwrap()
+            .read_to_string(&mut stdout)
+            .unwrap();
+        assert_eq!(stdout, "done\n");
+        git.finish().unwrap();
+
+        let stall = args(&tmp_repo, &["-c", "alias.stall=!sleep 5", "stall"]);
+        let started = Instant::now();
+        let mut git =
+            spawn_git_stream_with_timeout(&stall, Some(Duration::from_millis(200))).unwrap();
+        assert!(matches!(git.finish(), Err(GitAiError::GitTimeout { .. })));
+        assert!(started.elapsed() < Duration::from_secs(4));
+    }
+
+    #[test]
+    fn test_read_blobs_reads_all_requested_blobs() {
+        let tmp_repo = TmpRepo::new().unwrap();
+        let repo = tmp_repo.gitai_repo();
+        let oid = |content: &str| {
+            let path = tmp_repo.repo().path().join("blob.tmp");
+            std::fs::write(&path, content).unwrap();
+            repo.git(&["hash-object", "-w", &path.to_string_lossy()])
+                .unwrap()
+                .trim()
+                .to_string()
+        };
+        let one = oid("one\n");
+        let empty = oid("");
+        let two = oid("two\nlines\n");
+
+        let blobs = repo
+            .read_blobs(&[one.clone(), empty.clone(), two.clone()])
+            .unwrap();
+        assert_eq!(blobs[&one], b"one\n");
+        assert_eq!(blobs[&empty], b"");
+        assert_eq!(blobs[&two], b"two\nlines\n");
+        assert!(repo.read_blobs(&["0".repeat(40)]).is_err());
+    }
+
+    #[test]
+    fn test_is_lock_contention() {
+        assert!(is_lock_contention(
+            "fatal: Unable to create '/repo/.git/index.lock': File exists.\n"
+        ));
+        assert!(is_lock_contention(
+            "error: cannot lock ref 'refs/notes/ai': Unable to create '/repo/.git/refs/notes/ai.lock': File exists.\n"
+        ));
+        assert!(!is_lock_contention(
+            "error: cannot lock ref 'refs/notes/ai': is at 1234 but expected 5678\n"
+        ));
+    }
+
+    #[test]
+    fn test_bare_repository_serves_read_only_commands() {
+        use crate::authorship::fsck::fsck;
+        use crate::authorship::query::{QueriedAuthor, query_attribution};
+        use crate::authorship::stats::stats_for_commit_stats;
+        use crate::git::test_utils::TmpRemote;
+
+        let remote = TmpRemote::new().unwrap();
+        let tmp_repo = TmpRepo::new().unwrap();
+        tmp_repo.write_file("a.txt", "from claude\n", true).unwrap();
+        tmp_repo
+            .trigger_checkpoint_with_ai("Claude", Some("claude-3-sonnet"), Some("cursor"))
+            .unwrap();
+        tmp_repo.commit_with_message("First").unwrap();
+        let sha = tmp_repo.head_commit_sha().unwrap();
+        tmp_repo.git_command(&["branch", "-M", "main"]).unwrap();
+        tmp_repo.add_remote("origin", &remote).unwrap();
+        tmp_repo.push("origin", "main").unwrap();
+
+        let bare = find_repository_in_path(&remote.url()).unwrap();
+        assert!(bare.is_bare());
+        assert!(bare.workdir().is_err());
+
+        let stats = stats_for_commit_stats(&bare, &sha, "refs/heads/main", false).unwrap();
+        assert_eq!(stats.ai_additions, 1);
+
+        let lines = query_attribution(&bare, "a.txt", Some("HEAD"), None).unwrap();
+        assert!(matches!(lines[0].author, QueriedAuthor::Ai { .. }));
+
+        let report = fsck(&bare, false).unwrap();
+        assert_eq!(report.commits_checked, 1);
+        assert!(report.findings.is_empty());
+
+        // Opening a mirror must not write git-ai's working state into it
+        assert!(!remote.path().join("ai").exists());
+    }
+
+    #[test]
+    fn test_history_walks_follow_replace_refs_unless_disabled() {
+        let tmp_repo = TmpRepo::new().unwrap();
+        tmp_repo.write_file("a.txt", "one\n", true).unwrap();
+        tmp_repo.commit_with_message("First").unwrap();
+        let first = tmp_repo.head_commit_sha().unwrap();
+        tmp_repo.write_file("a.txt", "two\n", true).unwrap();
+        tmp_repo.commit_with_message("Second").unwrap();
+        let second = tmp_repo.head_commit_sha().unwrap();
+        tmp_repo.write_file("a.txt", "three\n", true).unwrap();
+        tmp_repo.commit_with_message("Third").unwrap();
+        let third = tmp_repo.head_commit_sha().unwrap();
+
+        // Graft the second commit out of history
+        tmp_repo
+            .git_command(&["replace", "--graft", &third, &first])
+            .unwrap();
+
+        let repo = tmp_repo.gitai_repo();
+        let parents: Vec<String> = repo
+            .find_commit(third.clone())
+            .unwrap()
+            .parents()
+            .map(|parent| parent.id())
+            .collect();
+        assert_eq!(parents, vec![first.clone()]);
+
+        let path = tmp_repo.path().to_string_lossy().to_string();
+        let repo = find_repository(&vec![
+            "--no-replace-objects".to_string(),
+            "-C".to_string(),
+            path,
+        ])
+        .unwrap();
+        let parents: Vec<String> = repo
+            .find_commit(third.clone())
+            .unwrap()
+            .parents()
+            .map(|parent| parent.id())
+            .collect();
+        assert_eq!(parents, vec![second]);
+    }
+
+    #[test]
+    fn test_bare_repository_keeps_no_replace_objects() {
+        let remote = crate::git::test_utils::TmpRemote::new().unwrap();
+        let repo = find_repository(&vec![
+            "--no-replace-objects".to_string(),
+            "-C".to_string(),
+            remote.url(),
+        ])
+        .unwrap();
+        assert!(repo.is_bare());
+
+        let args = repo.global_args_for_exec();
+        assert!(args.iter().any(|arg| arg == "--no-replace-objects"));
+        assert_eq!(args.iter().filter(|arg| *arg == "-C").count(), 1);
+    }
+
+    #[test]
+    fn test_parse_hunk_header() {
+        assert_eq!(
+            parse_hunk_header("@@ -3 +4,2 @@ fn main() {"),
+            Some((3, 1, 4, 2))
+        );
+        assert_eq!(parse_hunk_header("@@ -5,0 +6 @@"), Some((5, 0, 6, 1)));
+        assert!(parse_hunk_header("@@@ -1,2 -1,2 +1,3 @@@").is_none());
+    }
 }
@@ -1,4 +1,5 @@
 use crate::error::GitAiError;
+use crate::utils::write_atomic;
 use serde::{Deserialize, Serialize};
 
 /// Simple case classes for rewrite events
@@ -205,13 +206,27 @@ impl MergeSquashEvent {
 pub struct RebaseStartEvent {
     pub original_head: String,
     pub is_interactive: bool,
+    /// The `<upstream>` commit, when given. Commits reachable from it aren't replayed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upstream: Option<String>,
+    /// The commit replayed onto (`--onto`, `--keep-base`'s merge base, or `<upstream>`), when
+    /// known from the command line
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub onto: Option<String>,
 }
 
 impl RebaseStartEvent {
-    pub fn new(original_head: String, is_interactive: bool) -> Self {
+    pub fn new(
+        original_head: String,
+        is_interactive: bool,
+        upstream: Option<String>,
+        onto: Option<String>,
+    ) -> Self {
         Self {
             original_head,
             is_interactive,
+            upstream,
+            onto,
         }
     }
 }
@@ -223,6 +238,12 @@ pub struct RebaseCompleteEvent {
     pub is_interactive: bool,
     pub original_commits: Vec<String>,
     pub new_commits: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upstream: Option<String>,
+    /// When set, `new_commits` are exactly the commits replayed onto it. Events from before it
+    /// was recorded can list commits of the new base too
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub onto: Option<String>,
 }
 
 impl RebaseCompleteEvent {
@@ -232,6 +253,8 @@ impl RebaseCompleteEvent {
         is_interactive: bool,
         original_commits: Vec<String>,
         new_commits: Vec<String>,
+        upstream: Option<String>,
+        onto: Option<String>,
     ) -> Self {
         Self {
             original_head,
@@ -239,6 +262,8 @@ impl RebaseCompleteEvent {
             is_interactive,
             original_commits,
             new_commits,
+            upstream,
+            onto,
         }
     }
 }
@@ -452,7 +477,6 @@ pub enum StashOperation {
 }
 
 /// Serialize events to JSONL format (newest events first)
-#[allow(dead_code)]
 pub fn serialize_events_to_jsonl(events: &[RewriteLogEvent]) -> Result<String, serde_json::Error> {
     let mut lines = Vec::new();
 
@@ -501,7 +525,7 @@ pub fn append_event_to_file(
 
     if !file_path.exists() {
         // File doesn't exist - create it with just the new event
-        std::fs::write(file_path, format!("{}\n", new_event_json))?;
+        write_atomic(file_path, format!("{}\n", new_event_json).as_bytes())?;
         return Ok(());
     }
 
@@ -510,7 +534,7 @@ pub fn append_event_to_file(
 
     if existing_content.trim().is_empty() {
         // Empty file - just write the new event
-        std::fs::write(file_path, format!("{}\n", new_event_json))?;
+        write_atomic(file_path, format!("{}\n", new_event_json).as_bytes())?;
         return Ok(());
     }
 
@@ -529,7 +553,7 @@ pub fn append_event_to_file(
     }
 
     // Write back to file
-    std::fs::write(file_path, lines.join("\n"))?;
+    write_atomic(file_path, lines.join("\n").as_bytes())?;
 
     Ok(())
 }
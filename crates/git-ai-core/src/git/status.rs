@@ -1,7 +1,7 @@
 use crate::error::GitAiError;
-use crate::git::repository::{Repository, exec_git};
+use crate::git::repo_path::{self, parse_name_only};
+use crate::git::repository::{Repository, exec_git, push_pathspecs};
 use std::collections::HashSet;
-use std::str;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum StatusCode {
@@ -70,12 +70,7 @@ impl Repository {
             )));
         }
 
-        let stdout = str::from_utf8(&output.stdout)?;
-        let filenames: HashSet<String> = stdout
-            .lines()
-            .filter(|line| !line.is_empty())
-            .map(|line| line.to_string())
-            .collect();
+        let filenames: HashSet<String> = parse_name_only(&output.stdout).into_iter().collect();
 
         Ok(filenames)
     }
@@ -134,12 +129,7 @@ impl Repository {
         }
 
         // Add combined pathspecs (staged files + provided paths)
-        if !combined_pathspecs.is_empty() {
-            args.push("--".to_string());
-            for path in &combined_pathspecs {
-                args.push(path.clone());
-            }
-        }
+        let narrowed = push_pathspecs(&mut args, Some(&combined_pathspecs));
 
         let output = exec_git(&args)?;
 
@@ -150,10 +140,23 @@ impl Repository {
             )));
         }
 
-        parse_porcelain_v2(&output.stdout)
+        let mut entries = parse_porcelain_v2(&output.stdout)?;
+        if !narrowed {
+            entries.retain(|entry| combined_pathspecs.contains(&entry.path));
+        }
+        Ok(entries)
     }
 }
 
+/// The path that ends a porcelain v2 record, after its first `fields` space-separated fields
+fn path_after_fields(record: &[u8], fields: usize) -> Result<String, GitAiError> {
+    record
+        .splitn(fields + 1, |byte| *byte == b' ')
+        .nth(fields)
+        .map(repo_path::key_from_bytes)
+        .ok_or_else(|| GitAiError::Generic("Missing path field".into()))
+}
+
 fn parse_porcelain_v2(data: &[u8]) -> Result<Vec<StatusEntry>, GitAiError> {
     let mut entries = Vec::new();
     let mut parts = data
@@ -162,7 +165,9 @@ fn parse_porcelain_v2(data: &[u8]) -> Result<Vec<StatusEntry>, GitAiError> {
         .peekable();
 
     while let Some(raw) = parts.next() {
-        let record = str::from_utf8(raw)?;
+        // Paths are raw bytes and needn't be UTF-8; the fields before them are ASCII
+        let record = String::from_utf8_lossy(raw);
+        let record = record.as_ref();
         let mut chars = record.chars();
         let tag = chars
             .next()
@@ -170,7 +175,9 @@ fn parse_porcelain_v2(data: &[u8]) -> Result<Vec<StatusEntry>, GitAiError> {
 
         match tag {
             '1' | 'u' => {
-                let mut fields = record.splitn(9, ' ');
+                // Unmerged records have a mode and object name for each of the three stages
+                let metadata_fields = if tag == 'u' { 8 } else { 6 };
+                let mut fields = record.splitn(metadata_fields + 3, ' ');
                 let _ = fields.next(); // tag
                 let xy = fields
                     .next()
@@ -184,15 +191,7 @@ fn parse_porcelain_v2(data: &[u8]) -> Result<Vec<StatusEntry>, GitAiError> {
                 let staged = StatusCode::from(xy.chars().next().unwrap());
                 let unstaged = StatusCode::from(xy.chars().nth(1).unwrap());
 
-                // skip submodule/metadata fields to capture path
-                for _ in 0..6 {
-                    fields.next();
-                }
-
-                let path = fields
-                    .next()
-                    .ok_or_else(|| GitAiError::Generic("Missing path field".into()))?
-                    .to_string();
+                let path = path_after_fields(raw, metadata_fields + 2)?;
 
                 entries.push(StatusEntry {
                     path,
@@ -223,20 +222,12 @@ fn parse_porcelain_v2(data: &[u8]) -> Result<Vec<StatusEntry>, GitAiError> {
                 let staged = StatusCode::from(xy.chars().next().unwrap());
                 let unstaged = StatusCode::from(xy.chars().nth(1).unwrap());
 
-                // skip submodule/metadata fields
-                for _ in 0..7 {
-                    fields.next();
-                }
-
-                let path = fields
-                    .next()
-                    .ok_or_else(|| GitAiError::Generic("Missing path field".into()))?
-                    .to_string();
+                let path = path_after_fields(raw, 9)?;
 
                 let orig_path_bytes = parts.next().ok_or_else(|| {
                     GitAiError::Generic("Missing original path for rename/copy".into())
                 })?;
-                let orig_path = str::from_utf8(orig_path_bytes)?.to_string();
+                let orig_path = repo_path::key_from_bytes(orig_path_bytes);
 
                 let kind = match staged {
                     StatusCode::Renamed => EntryKind::Rename,
@@ -253,7 +244,7 @@ fn parse_porcelain_v2(data: &[u8]) -> Result<Vec<StatusEntry>, GitAiError> {
                 });
             }
             '?' => {
-                let path = record.strip_prefix("? ").unwrap_or(record).to_string();
+                let path = path_after_fields(raw, 1)?;
 
                 entries.push(StatusEntry {
                     path,
@@ -264,7 +255,7 @@ fn parse_porcelain_v2(data: &[u8]) -> Result<Vec<StatusEntry>, GitAiError> {
                 });
             }
             '!' => {
-                let path = record.strip_prefix("! ").unwrap_or(record).to_string();
+                let path = path_after_fields(raw, 1)?;
 
                 entries.push(StatusEntry {
                     path,
@@ -307,7 +298,7 @@ mod tests {
         raw.extend_from_slice(b"? assets/logo (1).svg\0");
         raw.extend_from_slice(b"? dir with spaces/file name [draft].md\0");
         raw.extend_from_slice(b"! target/.keep\0");
-        raw.extend_from_slice(b"u UU N... 100644 100644 100644 eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee ffffffffffffffffffffffffffffffffffffffff 1 2 3 some unmerged/path.txt\0");
+        raw.extend_from_slice(b"u UU N... 100644 100644 100644 100644 eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee ffffffffffffffffffffffffffffffffffffffff 1111111111111111111111111111111111111111 some unmerged/path.txt\0");
 
         let entries: Vec<StatusEntry> = parse_porcelain_v2(&raw).expect("parse succeeds");
 
@@ -324,7 +315,11 @@ mod tests {
             entries.iter().any(|e| e.kind == EntryKind::Copy
                 && e.orig_path.as_deref() == Some("scripts/setup-old.sh"))
         );
-        assert!(entries.iter().any(|e| e.kind == EntryKind::Unmerged));
+        assert!(
+            entries
+                .iter()
+                .any(|e| e.kind == EntryKind::Unmerged && e.path == "some unmerged/path.txt")
+        );
         assert!(
             entries
                 .iter()
@@ -0,0 +1,624 @@
+use crate::git::refs::{
+    AI_AUTHORSHIP_PUSH_REFSPEC, conflicting_notes, copy_ref, merge_notes_from_ref, ref_exists,
+    tracking_ref_for_remote,
+};
+use crate::{
+    error::GitAiError,
+    git::{cli_parser::ParsedGitInvocation, repository::exec_git},
+    utils::debug_log,
+};
+
+use super::repository::Repository;
+
+/// Which way refs/notes/ai travels between this repository and a remote, set per remote with
+/// `git config remote.<name>.aiNotes <policy>`. With a fork and an upstream, for instance, the
+/// upstream is usually `fetch-from` and the fork keeps the default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NotesSyncPolicy {
+    /// Fetch notes from the remote and push them to it
+    #[default]
+    Sync,
+    /// Push notes to the remote. Its notes are still merged before a push, since the push
+    /// isn't forced, but fetching from it doesn't bring them in.
+    PushTo,
+    /// Fetch notes from the remote but never push to it
+    FetchFrom,
+    /// Leave the remote's notes alone
+    Ignore,
+}
+
+impl NotesSyncPolicy {
+    pub fn parse(value: &str) -> Result<NotesSyncPolicy, GitAiError> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "sync" => Ok(NotesSyncPolicy::Sync),
+            "push-to" => Ok(NotesSyncPolicy::PushTo),
+            "fetch-from" => Ok(NotesSyncPolicy::FetchFrom),
+            "ignore" => Ok(NotesSyncPolicy::Ignore),
+            other => Err(GitAiError::Generic(format!(
+                "invalid notes sync policy '{}', expected sync, push-to, fetch-from or ignore",
+                other
+            ))),
+        }
+    }
+
+    pub fn for_remote(
+        repository: &Repository,
+        remote_name: &str,
+    ) -> Result<NotesSyncPolicy, GitAiError> {
+        match repository.config_get_str(&format!("remote.{}.aiNotes", remote_name))? {
+            Some(value) => NotesSyncPolicy::parse(&value),
+            None => Ok(NotesSyncPolicy::default()),
+        }
+    }
+
+    /// The policy the fetch and push hooks follow, which fall back to the default rather than
+    /// failing the user's git command over a typo
+    pub fn for_hook(repository: &Repository, remote_name: &str) -> NotesSyncPolicy {
+        NotesSyncPolicy::for_remote(repository, remote_name).unwrap_or_else(|e| {
+            debug_log(&format!("remote.{}.aiNotes: {}", remote_name, e));
+            NotesSyncPolicy::default()
+        })
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotesSyncPolicy::Sync => "sync",
+            NotesSyncPolicy::PushTo => "push-to",
+            NotesSyncPolicy::FetchFrom => "fetch-from",
+            NotesSyncPolicy::Ignore => "ignore",
+        }
+    }
+
+    pub fn fetches(&self) -> bool {
+        matches!(self, NotesSyncPolicy::Sync | NotesSyncPolicy::FetchFrom)
+    }
+
+    pub fn pushes(&self) -> bool {
+        matches!(self, NotesSyncPolicy::Sync | NotesSyncPolicy::PushTo)
+    }
+}
+
+/// What `sync_notes` did for one remote
+#[derive(Debug, Clone)]
+pub struct NotesSyncReport {
+    pub remote: String,
+    pub policy: NotesSyncPolicy,
+    /// Whether the remote had notes that were merged into refs/notes/ai
+    pub fetched: bool,
+    /// Commits whose notes differed locally and on the remote. The notes merge keeps the local
+    /// note for these.
+    pub conflicts: Vec<String>,
+    pub pushed: bool,
+    pub error: Option<String>,
+}
+
+pub fn fetch_remote_from_args(
+    repository: &Repository,
+    parsed_args: &ParsedGitInvocation,
+) -> Result<String, GitAiError> {
+    let remotes = repository.remotes().ok();
+    let remote_names: Vec<String> = remotes
+        .as_ref()
+        .map(|r| {
+            (0..r.len())
+                .filter_map(|i| r.get(i).map(|s| s.to_string()))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    // 2) Fetch authorship refs from the appropriate remote
+    // Try to detect remote (named remote, URL, or local path) from args first
+    let positional_remote = extract_remote_from_fetch_args(&parsed_args.command_args);
+    let specified_remote = positional_remote.or_else(|| {
+        parsed_args
+            .command_args
+            .iter()
+            .find(|a| remote_names.iter().any(|r| r == *a))
+            .cloned()
+    });
+
+    let remote = specified_remote
+        .or_else(|| repository.upstream_remote().ok().flatten())
+        .or_else(|| repository.get_default_remote().ok().flatten());
+
+    Ok(remote.unwrap().to_string())
+}
+
+// for use with post-fetch and post-pull and post-clone hooks
+pub fn fetch_authorship_notes(
+    repository: &Repository,
+    remote_name: &str,
+) -> Result<(), GitAiError> {
+    // Generate tracking ref for this remote
+    let tracking_ref = tracking_ref_for_remote(&remote_name);
+
+    debug_log(&format!(
+        "fetching authorship notes for remote '{}' to tracking ref '{}'",
+        remote_name, tracking_ref
+    ));
+
+    // First, check if the remote has refs/notes/ai using ls-remote
+    // This is important for bare repos where the refmap might not be configured
+    let mut ls_remote_args = repository.global_args_for_exec();
+    ls_remote_args.push("ls-remote".to_string());
+    ls_remote_args.push(remote_name.to_string());
+    ls_remote_args.push("refs/notes/ai".to_string());
+
+    debug_log(&format!("ls-remote command: {:?}", ls_remote_args));
+
+    match exec_git(&ls_remote_args) {
+        Ok(output) => {
+            let result = String::from_utf8_lossy(&output.stdout).to_string();
+            debug_log(&format!("ls-remote stdout: '{}'", result));
+            debug_log(&format!(
+                "ls-remote stderr: '{}'",
+                String::from_utf8_lossy(&output.stderr)
+            ));
+
+            if result.trim().is_empty() {
+                debug_log(&format!(
+                    "no authorship notes found on remote '{}', nothing to sync",
+                    remote_name
+                ));
+                return Ok(());
+            }
+            debug_log(&format!(
+                "found authorship notes on remote '{}'",
+                remote_name
+            ));
+        }
+        Err(e) => {
+            debug_log(&format!(
+                "failed to check for authorship notes on remote '{}': {}",
+                remote_name, e
+            ));
+            return Ok(()); // Best-effort: if we can't check, silently continue
+        }
+    }
+
+    // Now fetch the notes to the tracking ref with explicit refspec
+    let fetch_authorship = notes_fetch_args(repository, remote_name, &tracking_ref);
+
+    debug_log(&format!("fetch command: {:?}", fetch_authorship));
+
+    match exec_git(&fetch_authorship) {
+        Ok(output) => {
+            debug_log(&format!(
+                "fetch stdout: '{}'",
+                String::from_utf8_lossy(&output.stdout)
+            ));
+            debug_log(&format!(
+                "fetch stderr: '{}'",
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+        Err(e) => {
+            debug_log(&format!("authorship fetch failed: {}", e));
+            return Ok(());
+        }
+    }
+
+    // After successful fetch, merge the tracking ref into refs/notes/ai
+    let local_notes_ref = "refs/notes/ai";
+
+    if crate::git::refs::ref_exists(&repository, &tracking_ref) {
+        if crate::git::refs::ref_exists(&repository, local_notes_ref) {
+            // Both exist - merge them
+            debug_log(&format!(
+                "merging authorship notes from {} into {}",
+                tracking_ref, local_notes_ref
+            ));
+            if let Err(e) = merge_notes_from_ref(&repository, &tracking_ref) {
+                debug_log(&format!("notes merge failed: {}", e));
+                // Don't fail on merge errors, just log and continue
+            }
+        } else {
+            // Only tracking ref exists - copy it to local
+            debug_log(&format!(
+                "initializing {} from tracking ref {}",
+                local_notes_ref, tracking_ref
+            ));
+            if let Err(e) = copy_ref(&repository, &tracking_ref, local_notes_ref) {
+                debug_log(&format!("notes copy failed: {}", e));
+                // Don't fail on copy errors, just log and continue
+            }
+        }
+    } else {
+        debug_log(&format!(
+            "tracking ref {} was not created after fetch",
+            tracking_ref
+        ));
+    }
+
+    Ok(())
+}
+// for use with post-push hook
+pub fn push_authorship_notes(repository: &Repository, remote_name: &str) -> Result<(), GitAiError> {
+    // STEP 1: Fetch remote notes into tracking ref and merge before pushing
+    // This ensures we don't lose notes from other branches/clones
+    let tracking_ref = tracking_ref_for_remote(&remote_name);
+    let fetch_before_push = notes_fetch_args(repository, remote_name, &tracking_ref);
+
+    debug_log(&format!(
+        "pre-push authorship fetch: {:?}",
+        &fetch_before_push
+    ));
+
+    // Fetch is best-effort; if it fails (e.g., no remote notes yet), continue
+    if exec_git(&fetch_before_push).is_ok() {
+        // Merge fetched notes into local refs/notes/ai
+        let local_notes_ref = "refs/notes/ai";
+
+        if ref_exists(repository, &tracking_ref) {
+            if ref_exists(repository, local_notes_ref) {
+                // Both exist - merge them
+                debug_log(&format!(
+                    "pre-push: merging {} into {}",
+                    tracking_ref, local_notes_ref
+                ));
+                if let Err(e) = merge_notes_from_ref(repository, &tracking_ref) {
+                    debug_log(&format!("pre-push notes merge failed: {}", e));
+                }
+            } else {
+                // Only tracking ref exists - copy it to local
+                debug_log(&format!(
+                    "pre-push: initializing {} from {}",
+                    local_notes_ref, tracking_ref
+                ));
+                if let Err(e) = copy_ref(repository, &tracking_ref, local_notes_ref) {
+                    debug_log(&format!("pre-push notes copy failed: {}", e));
+                }
+            }
+        }
+    }
+
+    // STEP 2: Push notes without force (requires fast-forward)
+    push_notes_ref(repository, remote_name)
+}
+
+/// Pushes refs/notes/ai to the remote without force, so it must already contain the remote's notes
+fn push_notes_ref(repository: &Repository, remote_name: &str) -> Result<(), GitAiError> {
+    let mut push_authorship: Vec<String> = repository.global_args_for_exec();
+    push_authorship.push("-c".to_string());
+    push_authorship.push("core.hooksPath=/dev/null".to_string());
+    push_authorship.push("push".to_string());
+    push_authorship.push("--quiet".to_string());
+    push_authorship.push("--no-recurse-submodules".to_string());
+    push_authorship.push("--no-verify".to_string());
+    push_authorship.push(remote_name.to_string());
+    push_authorship.push(AI_AUTHORSHIP_PUSH_REFSPEC.to_string());
+
+    debug_log(&format!(
+        "pushing authorship refs (no force): {:?}",
+        &push_authorship
+    ));
+    if let Err(e) = exec_git(&push_authorship) {
+        // Best-effort; don't fail user operation due to authorship sync issues
+        debug_log(&format!("authorship push skipped due to error: {}", e));
+        return Err(e);
+    }
+
+    Ok(())
+}
+
+/// Reconciles refs/notes/ai with each of `remotes` according to its policy: first the notes of
+/// every remote that can be fetched from are merged in, so that notes reach every remote that
+/// can be pushed to whichever remote they came from, then refs/notes/ai is pushed.
+pub fn sync_notes(repository: &Repository, remotes: &[String]) -> Vec<NotesSyncReport> {
+    let mut reports: Vec<NotesSyncReport> = remotes
+        .iter()
+        .map(|remote| {
+            let (policy, error) = match NotesSyncPolicy::for_remote(repository, remote) {
+                Ok(policy) => (policy, None),
+                Err(e) => (NotesSyncPolicy::Ignore, Some(e.to_string())),
+            };
+            NotesSyncReport {
+                remote: remote.clone(),
+                policy,
+                fetched: false,
+                conflicts: Vec::new(),
+                pushed: false,
+                error,
+            }
+        })
+        .collect();
+
+    for report in reports.iter_mut().filter(|r| r.policy.fetches()) {
+        match fetch_and_merge_notes(repository, &report.remote) {
+            Ok(Some(conflicts)) => {
+                report.fetched = true;
+                report.conflicts = conflicts;
+            }
+            Ok(None) => {}
+            Err(e) => report.error = Some(e.to_string()),
+        }
+    }
+
+    for report in reports
+        .iter_mut()
+        .filter(|r| r.policy.pushes() && r.error.is_none())
+    {
+        let merged = if report.policy.fetches() {
+            Ok(())
+        } else {
+            // Only what a push needs: the remote's notes, so the push is a fast-forward
+            fetch_and_merge_notes(repository, &report.remote).map(|conflicts| {
+                report.conflicts = conflicts.unwrap_or_default();
+            })
+        };
+        let pushed = merged.and_then(|()| {
+            if ref_exists(repository, "refs/notes/ai") {
+                push_notes_ref(repository, &report.remote).map(|()| true)
+            } else {
+                Ok(false)
+            }
+        });
+        match pushed {
+            Ok(pushed) => report.pushed = pushed,
+            Err(e) => report.error = Some(e.to_string()),
+        }
+    }
+
+    reports
+}
+
+/// Fetches the remote's notes into its tracking ref and merges them into refs/notes/ai.
+/// Returns the commits whose notes conflicted, or None if the remote has no notes.
+fn fetch_and_merge_notes(
+    repository: &Repository,
+    remote_name: &str,
+) -> Result<Option<Vec<String>>, GitAiError> {
+    let mut ls_remote_args = repository.global_args_for_exec();
+    ls_remote_args.push("ls-remote".to_string());
+    ls_remote_args.push(remote_name.to_string());
+    ls_remote_args.push("refs/notes/ai".to_string());
+    let output = exec_git(&ls_remote_args)?;
+    if String::from_utf8_lossy(&output.stdout).trim().is_empty() {
+        return Ok(None);
+    }
+
+    let tracking_ref = tracking_ref_for_remote(remote_name);
+    exec_git(&notes_fetch_args(repository, remote_name, &tracking_ref))?;
+
+    if !ref_exists(repository, "refs/notes/ai") {
+        copy_ref(repository, &tracking_ref, "refs/notes/ai")?;
+        return Ok(Some(Vec::new()));
+    }
+    let conflicts = conflicting_notes(repository, &tracking_ref)?;
+    merge_notes_from_ref(repository, &tracking_ref)?;
+    Ok(Some(conflicts))
+}
+
+/// Fetches the remote's refs/notes/ai into `tracking_ref`, with hooks and everything else a
+/// plain fetch would do turned off
+/// The refspec that makes a plain `git fetch` bring a remote's notes into their tracking ref,
+/// where the fetch hook and `notes sync` merge them from
+pub fn notes_fetch_refspec(remote_name: &str) -> String {
+    format!("+refs/notes/ai:{}", tracking_ref_for_remote(remote_name))
+}
+
+/// Adds `notes_fetch_refspec` to each remote whose policy fetches notes, so they come down even
+/// when git runs without git-ai (IDEs, CI). Git fails a whole fetch over a configured refspec
+/// the remote doesn't have, so remotes without notes yet are left to the fetch hook. Nothing
+/// is done for pushing: any `remote.<name>.push` refspec replaces what a plain `git push`
+/// pushes, and the push hook sends the notes anyway. Returns the remotes it configured.
+pub fn configure_notes_fetch_refspecs(repository: &Repository) -> Result<Vec<String>, GitAiError> {
+    let mut configured = Vec::new();
+    for remote in repository.remotes()? {
+        if remote.is_empty() || !NotesSyncPolicy::for_hook(repository, &remote).fetches() {
+            continue;
+        }
+        let refspec = notes_fetch_refspec(&remote);
+        let key = format!("remote.{}.fetch", remote);
+        if repository
+            .git(&["config", "--get-all", &key])
+            .is_ok_and(|refspecs| refspecs.lines().any(|existing| existing.trim() == refspec))
+        {
+            continue;
+        }
+        let has_notes = repository
+            .git(&["ls-remote", &remote, "refs/notes/ai"])
+            .is_ok_and(|output| !output.trim().is_empty());
+        if !has_notes {
+            continue;
+        }
+        repository.git(&["config", "--add", &key, &refspec])?;
+        configured.push(remote);
+    }
+    Ok(configured)
+}
+
+fn notes_fetch_args(repository: &Repository, remote_name: &str, tracking_ref: &str) -> Vec<String> {
+    // IMPORTANT: use repository.global_args_for_exec() to ensure -C flag is present for bare repos
+    let mut args: Vec<String> = repository.global_args_for_exec();
+    args.push("-c".to_string());
+    args.push("core.hooksPath=/dev/null".to_string());
+    args.push("fetch".to_string());
+    args.push("--no-tags".to_string());
+    args.push("--recurse-submodules=no".to_string());
+    args.push("--no-write-fetch-head".to_string());
+    args.push("--no-write-commit-graph".to_string());
+    args.push("--no-auto-maintenance".to_string());
+    args.push(remote_name.to_string());
+    args.push(format!("+refs/notes/ai:{}", tracking_ref));
+    args
+}
+
+fn extract_remote_from_fetch_args(args: &[String]) -> Option<String> {
+    let mut after_double_dash = false;
+
+    for arg in args {
+        if !after_double_dash {
+            if arg == "--" {
+                after_double_dash = true;
+                continue;
+            }
+            if arg.starts_with('-') {
+                // Option; skip
+                continue;
+            }
+        }
+
+        // Candidate positional arg; determine if it's a repository URL/path
+        let s = arg.as_str();
+
+        // 1) URL forms (https://, ssh://, file://, git://, etc.)
+        if s.contains("://") || s.starts_with("file://") {
+            return Some(arg.clone());
+        }
+
+        // 2) SCP-like syntax: user@host:path
+        if s.contains('@') && s.contains(':') && !s.contains("://") {
+            return Some(arg.clone());
+        }
+
+        // 3) Local path forms
+        if s.starts_with('/') || s.starts_with("./") || s.starts_with("../") || s.starts_with("~/")
+        {
+            return Some(arg.clone());
+        }
+
+        // Heuristic: bare repo directories often end with .git
+        if s.ends_with(".git") {
+            return Some(arg.clone());
+        }
+
+        // 4) As a last resort, if the path exists on disk, treat as local path
+        if std::path::Path::new(s).exists() {
+            return Some(arg.clone());
+        }
+
+        // Otherwise, do not treat this positional token as a repository; likely a refspec
+        break;
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{NotesSyncPolicy, sync_notes};
+    use crate::git::refs::{conflicting_notes, show_authorship_note};
+    use crate::git::test_utils::{TmpRemote, TmpRepo};
+
+    fn ai_commit(repo: &TmpRepo, filename: &str, contents: &str, message: &str) -> String {
+        repo.write_file(filename, contents, true).unwrap();
+        repo.trigger_checkpoint_with_ai("Claude", Some("claude-3-sonnet"), Some("cursor"))
+            .unwrap();
+        repo.commit_with_message(message).unwrap();
+        repo.head_commit_sha().unwrap()
+    }
+
+    #[test]
+    fn test_notes_travel_through_push_clone_and_pull() {
+        let remote = TmpRemote::new().unwrap();
+        let alice = TmpRepo::new().unwrap();
+        let first = ai_commit(&alice, "a.txt", "from claude\n", "First");
+        alice.git_command(&["branch", "-M", "main"]).unwrap();
+        alice.add_remote("origin", &remote).unwrap();
+        alice.push("origin", "main").unwrap();
+
+        let on_remote = remote.authorship_log(&first).unwrap().unwrap();
+        assert_eq!(on_remote.attestations[0].file_path, "a.txt");
+
+        let bob = remote.clone_into().unwrap();
+        assert!(show_authorship_note(bob.gitai_repo(), &first).is_some());
+
+        let second = ai_commit(&alice, "b.txt", "more from claude\n", "Second");
+        alice.push("origin", "main").unwrap();
+        bob.pull("origin", "main").unwrap();
+        assert_eq!(bob.head_commit_sha().unwrap(), second);
+        assert!(show_authorship_note(bob.gitai_repo(), &second).is_some());
+    }
+
+    #[test]
+    fn test_push_merges_notes_pushed_from_another_clone() {
+        let remote = TmpRemote::new().unwrap();
+        let alice = TmpRepo::new().unwrap();
+        let first = ai_commit(&alice, "a.txt", "from claude\n", "First");
+        alice.git_command(&["branch", "-M", "main"]).unwrap();
+        alice.add_remote("origin", &remote).unwrap();
+        alice.push("origin", "main").unwrap();
+
+        // Bob's notes reach the remote on a branch Alice never fetches
+        let bob = remote.clone_into().unwrap();
+        bob.create_branch("bob-feature").unwrap();
+        let bobs = ai_commit(&bob, "bob.txt", "bob's claude\n", "Bob's work");
+        bob.push("origin", "bob-feature").unwrap();
+
+        let alices = ai_commit(&alice, "c.txt", "alice's claude\n", "Alice's work");
+        alice.push("origin", "main").unwrap();
+
+        for sha in [&first, &bobs, &alices] {
+            assert!(remote.authorship_log(sha).unwrap().is_some());
+        }
+    }
+
+    #[test]
+    fn test_sync_notes_follows_remote_policies() {
+        let upstream = TmpRemote::new().unwrap();
+        let fork = TmpRemote::new().unwrap();
+        let alice = TmpRepo::new().unwrap();
+        let first = ai_commit(&alice, "a.txt", "from claude\n", "First");
+        alice.git_command(&["branch", "-M", "main"]).unwrap();
+        alice.add_remote("upstream", &upstream).unwrap();
+        alice.add_remote("origin", &fork).unwrap();
+        alice.push("upstream", "main").unwrap();
+        alice
+            .git_command(&["config", "remote.upstream.aiNotes", "fetch-from"])
+            .unwrap();
+
+        // Someone else lands AI work upstream
+        let bob = upstream.clone_into().unwrap();
+        let bobs = ai_commit(&bob, "bob.txt", "bob's claude\n", "Bob's work");
+        bob.push("origin", "main").unwrap();
+
+        alice.git_command(&["fetch", "upstream"]).unwrap();
+        alice
+            .git_command(&["merge", "--ff-only", "upstream/main"])
+            .unwrap();
+        let alices = ai_commit(&alice, "c.txt", "alice's claude\n", "Alice's work");
+        alice.git_command(&["push", "origin", "main"]).unwrap();
+        alice.git_command(&["push", "upstream", "main"]).unwrap();
+
+        let reports = sync_notes(
+            alice.gitai_repo(),
+            &["upstream".to_string(), "origin".to_string()],
+        );
+        assert!(reports.iter().all(|r| r.error.is_none()), "{:?}", reports);
+        assert_eq!(reports[0].policy, NotesSyncPolicy::FetchFrom);
+        assert!(reports[0].fetched && !reports[0].pushed);
+        assert!(reports[1].pushed);
+
+        // Upstream's notes reach the fork, but Alice's notes don't go upstream
+        for sha in [&first, &bobs, &alices] {
+            assert!(fork.authorship_log(sha).unwrap().is_some());
+        }
+        assert!(upstream.authorship_log(&alices).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_conflicting_notes_lists_commits_annotated_differently() {
+        let repo = TmpRepo::new().unwrap();
+        let sha = ai_commit(&repo, "a.txt", "from claude\n", "First");
+        repo.git_command(&[
+            "notes",
+            "--ref=ai-remote/other",
+            "add",
+            "-m",
+            "elsewhere",
+            &sha,
+        ])
+        .unwrap();
+        assert_eq!(
+            conflicting_notes(repo.gitai_repo(), "refs/notes/ai-remote/other").unwrap(),
+            vec![sha.clone()]
+        );
+
+        assert!(NotesSyncPolicy::parse("sideways").is_err());
+        assert_eq!(
+            NotesSyncPolicy::parse("Push-To").unwrap(),
+            NotesSyncPolicy::PushTo
+        );
+    }
+}
@@ -0,0 +1,429 @@
+//! Random multi-author edit histories for property tests.
+//!
+//! An `EditHistory` is a list of line edits to one file, each by a human or an AI session,
+//! with commits in between. Positions are taken modulo the current line count when the history
+//! is applied, so any subsequence of a history is also a valid history. That is what lets
+//! `check_histories` shrink a failing case down to the few edits that matter.
+
+use crate::authorship::authorship_log_serialization::AuthorshipLog;
+use crate::error::GitAiError;
+use crate::git::test_utils::{TestRng, TmpRepo};
+use std::collections::BTreeSet;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryAuthor {
+    Human(u8),
+    Ai(u8),
+}
+
+impl fmt::Display for HistoryAuthor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HistoryAuthor::Human(n) => write!(f, "human_{}", n),
+            HistoryAuthor::Ai(n) => write!(f, "ai_session_{}", n),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EditOp {
+    /// Insert `lines` before line `at`
+    Insert {
+        author: HistoryAuthor,
+        at: usize,
+        lines: Vec<String>,
+    },
+    /// Delete `count` lines starting at `at`
+    Delete {
+        author: HistoryAuthor,
+        at: usize,
+        count: usize,
+    },
+    /// Cut `count` lines starting at `from` and paste them before line `to` of what remains
+    Move {
+        author: HistoryAuthor,
+        from: usize,
+        count: usize,
+        to: usize,
+    },
+    Commit,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EditHistory {
+    pub ops: Vec<EditOp>,
+}
+
+/// What applying a history left behind
+#[derive(Debug, Clone, Default)]
+pub struct HistoryOutcome {
+    /// Commits made by the history, oldest first
+    pub commits: Vec<String>,
+    /// Final lines of the file
+    pub lines: Vec<String>,
+}
+
+impl EditHistory {
+    /// A random history of up to `max_ops` edits by two humans and two AI sessions
+    pub fn generate(rng: &mut TestRng, max_ops: usize) -> Self {
+        let mut ops = Vec::new();
+        let mut next_line = 0;
+        for _ in 0..rng.gen_range(1..max_ops.max(1) as u64 + 1) {
+            let author = match rng.gen_range(0..4) {
+                0 => HistoryAuthor::Human(0),
+                1 => HistoryAuthor::Human(1),
+                2 => HistoryAuthor::Ai(0),
+                _ => HistoryAuthor::Ai(1),
+            };
+            let at = rng.gen_range(0..64) as usize;
+            let op = match rng.gen_range(0..10) {
+                0..=4 => EditOp::Insert {
+                    author,
+                    at,
+                    lines: (0..rng.gen_range(1..5))
+                        .map(|_| {
+                            next_line += 1;
+                            format!("{} line {}", author, next_line)
+                        })
+                        .collect(),
+                },
+                5 | 6 => EditOp::Delete {
+                    author,
+                    at,
+                    count: rng.gen_range(1..4) as usize,
+                },
+                7 => EditOp::Move {
+                    author,
+                    from: at,
+                    count: rng.gen_range(1..4) as usize,
+                    to: rng.gen_range(0..64) as usize,
+                },
+                _ => EditOp::Commit,
+            };
+            ops.push(op);
+        }
+        EditHistory { ops }
+    }
+
+    /// Applies the history to `file` in `repo`, checkpointing after every edit and
+    /// committing at each `Commit` (and at the end) if there is something to commit
+    pub fn apply(&self, repo: &TmpRepo, file: &str) -> Result<HistoryOutcome, GitAiError> {
+        let mut outcome = HistoryOutcome::default();
+        let mut dirty = false;
+        for op in self.ops.iter().chain([&EditOp::Commit]) {
+            let before = outcome.lines.clone();
+            let lines = &mut outcome.lines;
+            let author = match op {
+                EditOp::Insert {
+                    author,
+                    at,
+                    lines: new_lines,
+                } => {
+                    let at = at % (lines.len() + 1);
+                    lines.splice(at..at, new_lines.iter().cloned());
+                    *author
+                }
+                EditOp::Delete { author, at, count } => {
+                    if !lines.is_empty() {
+                        let at = at % lines.len();
+                        let end = (at + count).min(lines.len());
+                        lines.drain(at..end);
+                    }
+                    *author
+                }
+                EditOp::Move {
+                    author,
+                    from,
+                    count,
+                    to,
+                } => {
+                    if !lines.is_empty() {
+                        let from = from % lines.len();
+                        let end = (from + count).min(lines.len());
+                        let moved: Vec<String> = lines.drain(from..end).collect();
+                        let to = to % (lines.len() + 1);
+                        lines.splice(to..to, moved);
+                    }
+                    *author
+                }
+                EditOp::Commit => {
+                    if dirty {
+                        repo.commit_with_message(&format!("commit {}", outcome.commits.len()))?;
+                        outcome.commits.push(repo.head_commit_sha()?);
+                        dirty = false;
+                    }
+                    continue;
+                }
+            };
+            if outcome.lines == before {
+                continue;
+            }
+            repo.write_file(file, &file_contents(&outcome.lines), true)?;
+            match author {
+                HistoryAuthor::Human(_) => {
+                    repo.trigger_checkpoint_with_author(&author.to_string())?;
+                }
+                HistoryAuthor::Ai(_) => {
+                    repo.trigger_checkpoint_with_ai(
+                        &author.to_string(),
+                        Some("test_model"),
+                        Some("test_tool"),
+                    )?;
+                }
+            }
+            dirty = true;
+        }
+        Ok(outcome)
+    }
+
+    /// This history with its `Move` ops dropped
+    pub fn without_moves(mut self) -> Self {
+        self.ops.retain(|op| !matches!(op, EditOp::Move { .. }));
+        self
+    }
+
+    /// Copies of this history with one op removed, the candidates `check_histories` tries
+    /// when shrinking
+    fn without_each_op(&self) -> impl Iterator<Item = EditHistory> + '_ {
+        (0..self.ops.len()).map(move |skip| EditHistory {
+            ops: self
+                .ops
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| *index != skip)
+                .map(|(_, op)| op.clone())
+                .collect(),
+        })
+    }
+}
+
+/// File contents for `lines`, newline-terminated
+pub fn file_contents(lines: &[String]) -> String {
+    lines.iter().map(|line| format!("{}\n", line)).collect()
+}
+
+/// Distinct lines of `file` that `log` attributes to AI
+pub fn ai_lines(log: &AuthorshipLog, file: &str) -> BTreeSet<u32> {
+    log.attestations
+        .iter()
+        .filter(|attestation| attestation.file_path == file)
+        .flat_map(|attestation| &attestation.entries)
+        .flat_map(|entry| &entry.line_ranges)
+        .flat_map(|range| range.expand())
+        .collect()
+}
+
+/// Runs `property` against `cases` generated inputs. On failure the input is shrunk by
+/// repeatedly dropping ops while the property still fails, and the test panics with the seed
+/// (replay with `GIT_AI_TEST_SEED`) and the smallest failing input.
+pub fn check_histories<T, G, P>(cases: usize, mut generate: G, property: P)
+where
+    T: Shrink + fmt::Debug,
+    G: FnMut(&mut TestRng) -> T,
+    P: Fn(&T) -> Result<(), String>,
+{
+    let mut rng = TestRng::from_env();
+    for case in 0..cases {
+        let input = generate(&mut rng);
+        let Err(error) = property(&input) else {
+            continue;
+        };
+
+        let (mut smallest, mut smallest_error) = (input, error);
+        'shrink: loop {
+            for candidate in smallest.shrink() {
+                if let Err(error) = property(&candidate) {
+                    smallest = candidate;
+                    smallest_error = error;
+                    continue 'shrink;
+                }
+            }
+            break;
+        }
+        panic!(
+            "property failed on case {} (seed {}): {}\nsmallest failing input: {:#?}",
+            case,
+            rng.seed(),
+            smallest_error,
+            smallest
+        );
+    }
+}
+
+/// Inputs `check_histories` knows how to shrink
+pub trait Shrink: Sized {
+    /// Strictly smaller variants of `self`
+    fn shrink(&self) -> Vec<Self>;
+}
+
+impl Shrink for EditHistory {
+    fn shrink(&self) -> Vec<Self> {
+        self.without_each_op().collect()
+    }
+}
+
+impl<A: Shrink + Clone, B: Shrink + Clone> Shrink for (A, B) {
+    fn shrink(&self) -> Vec<Self> {
+        let firsts = self.0.shrink().into_iter().map(|a| (a, self.1.clone()));
+        let seconds = self.1.shrink().into_iter().map(|b| (self.0.clone(), b));
+        firsts.chain(seconds).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authorship::rebase_authorship::rewrite_authorship_after_rebase_v2;
+    use crate::commands::blame::GitAiBlameOptions;
+    use crate::git::refs::get_authorship;
+    use crate::git::test_utils::run_git;
+    use std::collections::BTreeMap;
+
+    const FILE: &str = "history.txt";
+
+    fn ai_lines_at(repo: &TmpRepo, sha: &str, file: &str) -> BTreeSet<u32> {
+        get_authorship(repo.gitai_repo(), sha)
+            .map(|log| ai_lines(&log, file))
+            .unwrap_or_default()
+    }
+
+    /// git-ai blame of `FILE` at HEAD, line -> author
+    fn blame(repo: &TmpRepo) -> Result<BTreeMap<u32, String>, GitAiError> {
+        if std::fs::read(repo.path().join(FILE))
+            .unwrap_or_default()
+            .is_empty()
+        {
+            return Ok(BTreeMap::new());
+        }
+        let (lines, _) = repo
+            .gitai_repo()
+            .blame(FILE, &GitAiBlameOptions::default())?;
+        Ok(lines.into_iter().collect())
+    }
+
+    #[test]
+    fn test_ai_line_count_never_exceeds_file_length() {
+        check_histories(
+            6,
+            |rng| EditHistory::generate(rng, 10),
+            |history| {
+                let repo = TmpRepo::new().map_err(|e| e.to_string())?;
+                let outcome = history.apply(&repo, FILE).map_err(|e| e.to_string())?;
+                for sha in &outcome.commits {
+                    let line_count = run_git(repo.path(), &["show", &format!("{}:{}", sha, FILE)])
+                        .map(|contents| contents.lines().count() as u32)
+                        .unwrap_or(0);
+                    let ai = ai_lines_at(&repo, sha, FILE);
+                    if ai.len() as u32 > line_count
+                        || ai.iter().any(|line| *line == 0 || *line > line_count)
+                    {
+                        return Err(format!(
+                            "commit {} has {} lines but AI lines {:?}",
+                            sha, line_count, ai
+                        ));
+                    }
+                }
+                Ok(())
+            },
+        );
+    }
+
+    #[test]
+    fn test_rebase_preserves_ai_attribution_when_patches_are_unchanged() {
+        check_histories(
+            4,
+            // A moved line diffs ambiguously (its delete and insert can pair up differently),
+            // and the rebase rewrite re-derives attributions from its own diff, so moves are
+            // left out here
+            |rng| {
+                (
+                    EditHistory::generate(rng, 8).without_moves(),
+                    EditHistory::generate(rng, 4),
+                )
+            },
+            |(feature, upstream): &(EditHistory, EditHistory)| {
+                let run = || -> Result<Result<(), String>, GitAiError> {
+                    let repo = TmpRepo::new()?;
+                    repo.write_file("README.md", "base\n", true)?;
+                    repo.trigger_checkpoint_with_author("human_0")?;
+                    repo.commit_with_message("base")?;
+                    let main = repo.current_branch()?;
+
+                    repo.create_branch("feature")?;
+                    let original = feature.apply(&repo, FILE)?.commits;
+                    let Some(original_head) = original.last().cloned() else {
+                        return Ok(Ok(()));
+                    };
+                    let before = blame(&repo)?;
+                    repo.switch_branch(&main)?;
+                    upstream.apply(&repo, "upstream.txt")?;
+                    repo.switch_branch("feature")?;
+
+                    // The two branches touch different files, so every patch applies unchanged
+                    run_git(repo.path(), &["rebase", &main])?;
+                    let range = format!("{}..feature", main);
+                    let rebased: Vec<String> =
+                        run_git(repo.path(), &["rev-list", "--reverse", &range])?
+                            .lines()
+                            .map(str::to_string)
+                            .collect();
+                    if rebased != original {
+                        rewrite_authorship_after_rebase_v2(
+                            repo.gitai_repo(),
+                            &original_head,
+                            &original,
+                            &rebased,
+                            None,
+                            "Test User",
+                        )?;
+                    }
+
+                    let after = blame(&repo)?;
+                    if before != after {
+                        return Ok(Err(format!(
+                            "blame was {:?} before the rebase and {:?} after",
+                            before, after
+                        )));
+                    }
+                    Ok(Ok(()))
+                };
+                run().map_err(|e| e.to_string())?
+            },
+        );
+    }
+
+    #[test]
+    fn test_merge_keeps_branch_attribution() {
+        check_histories(
+            4,
+            |rng| (EditHistory::generate(rng, 8), EditHistory::generate(rng, 4)),
+            |(feature, upstream): &(EditHistory, EditHistory)| {
+                let run = || -> Result<Result<(), String>, GitAiError> {
+                    let repo = TmpRepo::new()?;
+                    repo.write_file("README.md", "base\n", true)?;
+                    repo.trigger_checkpoint_with_author("human_0")?;
+                    repo.commit_with_message("base")?;
+                    let main = repo.current_branch()?;
+
+                    repo.create_branch("feature")?;
+                    feature.apply(&repo, FILE)?;
+                    let before = blame(&repo)?;
+                    repo.switch_branch(&main)?;
+                    upstream.apply(&repo, "upstream.txt")?;
+                    repo.merge_branch("feature", "Merge feature")?;
+
+                    let after = blame(&repo)?;
+                    if before != after {
+                        return Ok(Err(format!(
+                            "blame was {:?} on the branch and {:?} after merging it",
+                            before, after
+                        )));
+                    }
+                    Ok(Ok(()))
+                };
+                run().map_err(|e| e.to_string())?
+            },
+        );
+    }
+}
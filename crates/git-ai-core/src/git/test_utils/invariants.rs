@@ -0,0 +1,44 @@
+//! Helpers for checking the invariants every output of `AttributionTracker::update_attributions`
+//! must satisfy. Shared by the randomized unit tests and the cargo-fuzz targets under `fuzz/`.
+
+use crate::authorship::attribution_tracker::Attribution;
+pub use crate::authorship::attribution_tracker::check_attribution_invariants;
+
+/// Attributions covering all of `content`, split at `cuts` (byte offsets, snapped back to char
+/// boundaries) and assigned round-robin to `authors`. Lets fuzzers build valid prior state
+/// from arbitrary numbers.
+pub fn attributions_from_cuts(
+    content: &str,
+    cuts: &[usize],
+    authors: &[&str],
+    ts: u128,
+) -> Vec<Attribution> {
+    if content.is_empty() || authors.is_empty() {
+        return Vec::new();
+    }
+    let mut boundaries: Vec<usize> = cuts
+        .iter()
+        .map(|cut| {
+            let mut cut = cut % content.len();
+            while !content.is_char_boundary(cut) {
+                cut -= 1;
+            }
+            cut
+        })
+        .chain([0, content.len()])
+        .collect();
+    boundaries.sort_unstable();
+    boundaries.dedup();
+    boundaries
+        .windows(2)
+        .enumerate()
+        .map(|(index, window)| {
+            Attribution::new(
+                window[0],
+                window[1],
+                authors[index % authors.len()].to_string(),
+                ts,
+            )
+        })
+        .collect()
+}
@@ -6,6 +6,7 @@ use crate::commands::checkpoint_agent::agent_presets::AgentRunResult;
 use crate::commands::{blame, checkpoint::run as checkpoint};
 use crate::error::GitAiError;
 use crate::git::repository::Repository as GitAiRepository;
+use crate::git::sync_authorship::{fetch_authorship_notes, push_authorship_notes};
 use git2::{Repository, Signature};
 use std::collections::BTreeMap;
 use std::fs;
@@ -13,6 +14,16 @@ use std::path::PathBuf;
 use std::process::Command;
 use std::sync::atomic::{AtomicU64, Ordering};
 
+mod history;
+mod invariants;
+mod snapshot;
+pub use history::{
+    EditHistory, EditOp, HistoryAuthor, HistoryOutcome, Shrink, ai_lines, check_histories,
+    file_contents,
+};
+pub use invariants::{attributions_from_cuts, check_attribution_invariants};
+pub use snapshot::{authorship_log_snapshot, working_log_snapshot};
+
 // Create a guaranteed-unique temporary directory under the OS temp dir.
 // Combines high-resolution time, process id, and an atomic counter, retrying on collisions.
 fn create_unique_tmp_dir(prefix: &str) -> Result<PathBuf, GitAiError> {
@@ -276,20 +287,26 @@ impl TmpRepo {
         println!("tmp_dir: {:?}", tmp_dir);
 
         // Initialize git repository
-        let repo_git2 = Repository::init(&tmp_dir)?;
+        Repository::init(&tmp_dir)?;
+
+        // (No initial empty commit)
+        Self::open(tmp_dir)
+    }
+
+    /// Wraps an existing working directory, configuring the test user for commits
+    fn open(path: PathBuf) -> Result<Self, GitAiError> {
+        let repo_git2 = Repository::open(&path)?;
 
         // Initialize gitai repository
-        let repo_gitai =
-            crate::git::repository::find_repository_in_path(tmp_dir.to_str().unwrap())?;
+        let repo_gitai = crate::git::repository::find_repository_in_path(path.to_str().unwrap())?;
 
         // Configure git user for commits
         let mut config = repo_git2.config()?;
         config.set_str("user.name", "Test User")?;
         config.set_str("user.email", "test@example.com")?;
 
-        // (No initial empty commit)
         Ok(TmpRepo {
-            path: tmp_dir,
+            path,
             repo_git2: repo_git2,
             repo_gitai: repo_gitai,
         })
@@ -354,6 +371,7 @@ impl TmpRepo {
             true,
             None, // agent_run_result
             false,
+            false, // include_generated
         )
     }
 
@@ -399,6 +417,11 @@ impl TmpRepo {
             edited_filepaths: None,
             will_edit_filepaths: None,
             dirty_files: None,
+            commit_message: None,
+            patches: None,
+            edit_phase: None,
+            author_class: None,
+            human_author: None,
         };
 
         checkpoint(
@@ -410,6 +433,7 @@ impl TmpRepo {
             true,
             Some(agent_run_result),
             false,
+            false, // include_generated
         )
     }
 
@@ -432,6 +456,7 @@ impl TmpRepo {
             true,  // quiet
             agent_run_result,
             false,
+            false, // include_generated
         )
     }
 
@@ -446,10 +471,7 @@ impl TmpRepo {
         let tree_id = index.write_tree()?;
         let tree = self.repo_git2.find_tree(tree_id)?;
 
-        // Use a fixed timestamp for stable test results
-        // Unix timestamp for 2023-01-01 12:00:00 UTC
-        let fixed_time = git2::Time::new(1672574400, 0);
-        let signature = Signature::new("Test User", "test@example.com", &fixed_time)?;
+        let signature = Signature::new("Test User", "test@example.com", &commit_time())?;
 
         // Check if there's a parent commit before we use it
         let _has_parent = if let Ok(head) = self.repo_git2.head() {
@@ -564,6 +586,10 @@ impl TmpRepo {
         let head = self.repo_git2.head()?;
         let merge_commit_sha = head.target().unwrap().to_string();
         let merge_commit = self.repo_git2.find_commit(head.target().unwrap())?;
+        // A fast-forward doesn't create a commit (and git doesn't run post-commit for it)
+        if merge_commit.parent_count() < 2 {
+            return Ok(());
+        }
         let parent_sha = merge_commit.parent(0).ok().map(|p| p.id().to_string());
 
         post_commit(
@@ -917,9 +943,7 @@ impl TmpRepo {
         index.read_tree(&tree)?;
         index.write()?;
 
-        // Use a fixed timestamp for stable test results
-        let fixed_time = git2::Time::new(1672574400, 0);
-        let signature = Signature::new("Test User", "test@example.com", &fixed_time)?;
+        let signature = Signature::new("Test User", "test@example.com", &commit_time())?;
 
         // Get the current HEAD for the parent commit
         let parent_commit = if let Ok(head) = self.repo_git2.head() {
@@ -1045,6 +1069,19 @@ impl TmpRepo {
         self.head_commit_sha()
     }
 
+    /// Canonical text form of the working log for HEAD, see `working_log_snapshot`
+    pub fn working_log_snapshot(&self) -> Result<String, GitAiError> {
+        let base_commit = self
+            .head_commit_sha()
+            .unwrap_or_else(|_| "initial".to_string());
+        let checkpoints = self
+            .repo_gitai
+            .storage
+            .working_log_for_base_commit(&base_commit)
+            .read_all_checkpoints()?;
+        Ok(working_log_snapshot(&checkpoints))
+    }
+
     /// Gets a reference to the gitai Repository
     pub fn gitai_repo(&self) -> &crate::git::repository::Repository {
         &self.repo_gitai
@@ -1207,6 +1244,29 @@ impl TmpRepo {
         Ok(())
     }
 
+    /// Adds `remote` under `name`
+    pub fn add_remote(&self, name: &str, remote: &TmpRemote) -> Result<(), GitAiError> {
+        self.git_command(&["remote", "add", name, &remote.url()])
+    }
+
+    /// Pushes `branch` and then the authorship notes, like the post-push hook does
+    pub fn push(&self, remote_name: &str, branch: &str) -> Result<(), GitAiError> {
+        self.git_command(&["push", remote_name, branch])?;
+        push_authorship_notes(&self.repo_gitai, remote_name)
+    }
+
+    /// Fetches `remote_name` and then its authorship notes, like the post-fetch hook does
+    pub fn fetch(&self, remote_name: &str) -> Result<(), GitAiError> {
+        self.git_command(&["fetch", remote_name])?;
+        fetch_authorship_notes(&self.repo_gitai, remote_name)
+    }
+
+    /// Fetches and fast-forwards the current branch to `remote_name/branch`
+    pub fn pull(&self, remote_name: &str, branch: &str) -> Result<(), GitAiError> {
+        self.fetch(remote_name)?;
+        self.git_command(&["merge", "--ff-only", &format!("{}/{}", remote_name, branch)])
+    }
+
     /// Execute git reset with git-ai hooks
     pub fn reset(
         &self,
@@ -1262,6 +1322,390 @@ impl TmpRepo {
 
         Ok(())
     }
+
+    /// Stashes all changes, untracked files included
+    pub fn stash_push(&self, message: Option<&str>) -> Result<(), GitAiError> {
+        let mut args = vec!["stash", "push", "--include-untracked"];
+        if let Some(message) = message {
+            args.extend(["-m", message]);
+        }
+        run_git(&self.path, &args).map(|_| ())
+    }
+
+    /// Re-applies and drops the latest stash
+    pub fn stash_pop(&self) -> Result<(), GitAiError> {
+        run_git(&self.path, &["stash", "pop"]).map(|_| ())
+    }
+
+    /// Adds a linked worktree on a new `branch` (started from HEAD) and opens it as a `TmpRepo`
+    pub fn add_worktree(&self, branch: &str) -> Result<TmpRepo, GitAiError> {
+        let path = create_unique_tmp_dir("git-ai-tmp-worktree")?;
+        fs::remove_dir(&path)?;
+        run_git(
+            &self.path,
+            &["worktree", "add", "-b", branch, path.to_str().unwrap()],
+        )?;
+        TmpRepo::open(path)
+    }
+
+    /// Stages everything and amends HEAD with git-ai's commit hooks, like `git commit --amend`
+    /// through the wrapper. Returns the amended commit's authorship log.
+    pub fn commit_amend(&self, message: &str) -> Result<AuthorshipLog, GitAiError> {
+        run_git(&self.path, &["add", "-A"])?;
+        let args: Vec<String> = ["commit", "--amend", "-m", message]
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect();
+        let parsed_args = crate::git::cli_parser::parse_git_cli_args(&args);
+        let mut repo_mut =
+            crate::git::repository::find_repository_in_path(self.path.to_str().unwrap())?;
+        let mut context = command_hooks_context();
+        let pre_commit_hook_result = crate::commands::hooks::commit_hooks::commit_pre_command_hook(
+            &parsed_args,
+            &mut repo_mut,
+            &mut context,
+        );
+
+        let output = Command::new(crate::config::Config::get().git_cmd())
+            .current_dir(&self.path)
+            .args(&args)
+            .output()
+            .map_err(|e| GitAiError::Generic(format!("Failed to run git commit --amend: {}", e)))?;
+        context.pre_commit_hook_result = Some(pre_commit_hook_result);
+        crate::commands::hooks::commit_hooks::commit_post_command_hook(
+            &parsed_args,
+            output.status,
+            &mut repo_mut,
+            &mut context,
+        );
+        if !output.status.success() {
+            return Err(GitAiError::Generic(format!(
+                "git commit --amend failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            )));
+        }
+        self.get_authorship_log()
+    }
+
+    /// Runs `git rebase -i upstream` with git-ai's rebase hooks, replacing the todo list git
+    /// generates with `todo` (via `GIT_SEQUENCE_EDITOR`). Squash messages are accepted as is.
+    pub fn rebase_interactive(
+        &self,
+        upstream: &str,
+        todo: &[RebaseTodo],
+    ) -> Result<(), GitAiError> {
+        let todo_path = self.repo_gitai.path().join("git-ai-test-rebase-todo");
+        let todo_contents: String = todo.iter().map(|step| format!("{}\n", step)).collect();
+        fs::write(&todo_path, todo_contents)?;
+
+        let args: Vec<String> = ["rebase", "-i", upstream]
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect();
+        let parsed_args = crate::git::cli_parser::parse_git_cli_args(&args);
+        let mut repo_mut =
+            crate::git::repository::find_repository_in_path(self.path.to_str().unwrap())?;
+        let mut context = command_hooks_context();
+        crate::commands::hooks::rebase_hooks::pre_rebase_hook(
+            &parsed_args,
+            &mut repo_mut,
+            &mut context,
+        );
+
+        let output = Command::new(crate::config::Config::get().git_cmd())
+            .current_dir(&self.path)
+            .args(&args)
+            .env(
+                "GIT_SEQUENCE_EDITOR",
+                format!("cp '{}'", todo_path.display()),
+            )
+            .env("GIT_EDITOR", "true")
+            .output()
+            .map_err(|e| GitAiError::Generic(format!("Failed to run git rebase -i: {}", e)))?;
+        let _ = fs::remove_file(&todo_path);
+
+        crate::commands::hooks::rebase_hooks::handle_rebase_post_command(
+            &context,
+            &parsed_args,
+            output.status,
+            &mut repo_mut,
+        );
+        if !output.status.success() {
+            return Err(GitAiError::Generic(format!(
+                "git rebase -i failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn command_hooks_context() -> crate::commands::hooks::CommandHooksContext {
+    crate::commands::hooks::CommandHooksContext {
+        pre_commit_hook_result: None,
+        rebase_original_head: None,
+        _rebase_onto: None,
+        push_authorship_handle: None,
+        fetch_authorship_handle: None,
+        staged_attributions: None,
+        commit_trailers: Vec::new(),
+        ai_ack: false,
+    }
+}
+
+/// One line of a scripted interactive rebase todo list
+#[allow(dead_code)]
+#[derive(Debug, Clone)]
+pub enum RebaseTodo {
+    Pick(String),
+    Squash(String),
+    Fixup(String),
+    Drop(String),
+}
+
+impl std::fmt::Display for RebaseTodo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RebaseTodo::Pick(sha) => write!(f, "pick {}", sha),
+            RebaseTodo::Squash(sha) => write!(f, "squash {}", sha),
+            RebaseTodo::Fixup(sha) => write!(f, "fixup {}", sha),
+            RebaseTodo::Drop(sha) => write!(f, "drop {}", sha),
+        }
+    }
+}
+
+/// A bare repository standing in for a hosted remote (GitHub, GitLab, ...)
+#[allow(dead_code)]
+pub struct TmpRemote {
+    path: PathBuf,
+}
+
+#[allow(dead_code)]
+impl TmpRemote {
+    /// Creates an empty bare repository whose default branch is `main`
+    pub fn new() -> Result<Self, GitAiError> {
+        let path = create_unique_tmp_dir("git-ai-tmp-remote")?;
+        run_git(&path, &["init", "--bare", "--initial-branch=main"])?;
+        Ok(TmpRemote { path })
+    }
+
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    /// URL to add as a remote or clone from
+    pub fn url(&self) -> String {
+        self.path.to_string_lossy().to_string()
+    }
+
+    /// Clones the remote into a new `TmpRepo` (remote `origin`) and fetches its authorship
+    /// notes, like the post-clone hook does
+    pub fn clone_into(&self) -> Result<TmpRepo, GitAiError> {
+        let path = create_unique_tmp_dir("git-ai-tmp")?;
+        run_git(&path, &["clone", &self.url(), "."])?;
+        let repo = TmpRepo::open(path)?;
+        fetch_authorship_notes(&repo.repo_gitai, "origin")?;
+        Ok(repo)
+    }
+
+    /// SHA `branch` points at
+    pub fn branch_sha(&self, branch: &str) -> Result<String, GitAiError> {
+        run_git(
+            &self.path,
+            &["rev-parse", &format!("refs/heads/{}", branch)],
+        )
+    }
+
+    /// The authorship log attached to `sha` on the remote, if any
+    pub fn authorship_log(&self, sha: &str) -> Result<Option<AuthorshipLog>, GitAiError> {
+        let Ok(note) = run_git(&self.path, &["notes", "--ref=ai", "show", sha]) else {
+            return Ok(None);
+        };
+        AuthorshipLog::deserialize_from_string(&note)
+            .map(Some)
+            .map_err(|e| GitAiError::Generic(format!("Failed to parse authorship log: {}", e)))
+    }
+
+    /// Squash-merges `branch` into `into` the way a hosting service does: a new single-parent
+    /// commit made outside git-ai, so it has no authorship note. Returns the new commit's SHA.
+    pub fn squash_merge(
+        &self,
+        branch: &str,
+        into: &str,
+        message: &str,
+    ) -> Result<String, GitAiError> {
+        let scratch = create_unique_tmp_dir("git-ai-tmp-hosted")?;
+        let result = (|| {
+            run_git(&scratch, &["clone", "--branch", into, &self.url(), "."])?;
+            run_git(
+                &scratch,
+                &["merge", "--squash", &format!("origin/{}", branch)],
+            )?;
+            run_git(
+                &scratch,
+                &[
+                    "-c",
+                    "user.name=Hosted Merge",
+                    "-c",
+                    "user.email=merge@example.com",
+                    "commit",
+                    "-m",
+                    message,
+                ],
+            )?;
+            run_git(&scratch, &["push", "origin", into])?;
+            run_git(&scratch, &["rev-parse", "HEAD"])
+        })();
+        let _ = fs::remove_dir_all(&scratch);
+        result
+    }
+}
+
+/// Unix timestamp for 2023-01-01 12:00:00 UTC, used for commits when no `MockClock` is installed
+pub const FIXED_COMMIT_TIME: i64 = 1672574400;
+
+/// Time for `TmpRepo` commits: the mock clock's current reading if one is installed (without
+/// advancing it), otherwise a fixed timestamp for stable test results
+fn commit_time() -> git2::Time {
+    let secs = crate::utils::mock_clock()
+        .map(|(now, _)| (now / 1000) as i64)
+        .unwrap_or(FIXED_COMMIT_TIME);
+    git2::Time::new(secs, 0)
+}
+
+/// Controls the clock git-ai reads timestamps from (checkpoint and attribution `ts`, commit
+/// times in `TmpRepo`) on the current thread. The real clock is restored when dropped.
+///
+/// ```ignore
+/// let clock = MockClock::ticking(1_700_000_000_000, 1);
+/// tmp_repo.trigger_checkpoint_with_author("test_user")?; // ts 1_700_000_000_000
+/// clock.advance(60_000);
+/// ```
+#[allow(dead_code)]
+pub struct MockClock {
+    // The clock is thread-local, so the guard must not move to another thread
+    _not_send: std::marker::PhantomData<*const ()>,
+}
+
+#[allow(dead_code)]
+impl MockClock {
+    /// A clock frozen at `millis`; every reading returns the same value
+    pub fn at(millis: u128) -> Self {
+        Self::ticking(millis, 0)
+    }
+
+    /// A clock starting at `start_millis` that moves forward `step_millis` after each
+    /// reading, so successive timestamps are distinct and strictly ordered
+    pub fn ticking(start_millis: u128, step_millis: u128) -> Self {
+        crate::utils::set_mock_clock(Some((start_millis, step_millis)));
+        MockClock {
+            _not_send: std::marker::PhantomData,
+        }
+    }
+
+    /// The value the next reading will return
+    pub fn now_millis(&self) -> u128 {
+        crate::utils::mock_clock().map_or(0, |(now, _)| now)
+    }
+
+    pub fn set(&self, millis: u128) {
+        let step = crate::utils::mock_clock().map_or(0, |(_, step)| step);
+        crate::utils::set_mock_clock(Some((millis, step)));
+    }
+
+    pub fn advance(&self, millis: u128) {
+        self.set(self.now_millis() + millis);
+    }
+}
+
+impl Drop for MockClock {
+    fn drop(&mut self) {
+        crate::utils::set_mock_clock(None);
+    }
+}
+
+/// Small deterministic RNG (splitmix64) for randomized tests. A failing run can be replayed
+/// by setting `GIT_AI_TEST_SEED` to the seed it printed.
+#[allow(dead_code)]
+#[derive(Debug, Clone)]
+pub struct TestRng {
+    seed: u64,
+    state: u64,
+}
+
+#[allow(dead_code)]
+impl TestRng {
+    pub fn from_seed(seed: u64) -> Self {
+        TestRng { seed, state: seed }
+    }
+
+    /// Seeds from `GIT_AI_TEST_SEED` if set, otherwise from the wall clock. The seed is
+    /// printed so it shows up in the output of a failing test.
+    pub fn from_env() -> Self {
+        let seed = std::env::var("GIT_AI_TEST_SEED")
+            .ok()
+            .and_then(|value| value.trim().parse().ok())
+            .unwrap_or_else(|| {
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_nanos() as u64
+            });
+        eprintln!(
+            "TestRng seed: {} (rerun with GIT_AI_TEST_SEED={})",
+            seed, seed
+        );
+        Self::from_seed(seed)
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform value in `range`; panics if the range is empty
+    pub fn gen_range(&mut self, range: std::ops::Range<u64>) -> u64 {
+        assert!(range.start < range.end, "empty range");
+        range.start + self.next_u64() % (range.end - range.start)
+    }
+
+    pub fn gen_bool(&mut self) -> bool {
+        self.next_u64() & 1 == 1
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        if items.is_empty() {
+            return None;
+        }
+        let index = self.gen_range(0..items.len() as u64) as usize;
+        items.get(index)
+    }
+}
+
+/// Runs plain git (no git-ai hooks) in `dir`, returning trimmed stdout
+fn run_git(dir: &std::path::Path, args: &[&str]) -> Result<String, GitAiError> {
+    let output = Command::new(crate::config::Config::get().git_cmd())
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .map_err(|e| GitAiError::Generic(format!("Failed to run git command: {}", e)))?;
+
+    if !output.status.success() {
+        return Err(GitAiError::Generic(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr)
+        )));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
 }
 
 // @todo move this acunniffe
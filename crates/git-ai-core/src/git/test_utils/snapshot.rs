@@ -0,0 +1,226 @@
+//! Canonical text forms of authorship logs and working logs for snapshot tests.
+//!
+//! Everything that changes from run to run (commit SHAs, prompt hashes, conversation ids,
+//! timestamps, the git-ai version) is redacted, prompts get stable placeholders in the order
+//! they first appear, and files and entries are sorted. Compare the output with
+//! `insta::assert_snapshot!` so a change in the attribution pipeline shows up as a line diff.
+
+use crate::authorship::authorship_log::{ColumnRange, PromptRecord};
+use crate::authorship::authorship_log_serialization::{
+    AuthorshipLog, format_line_ranges, generate_short_hash,
+};
+use crate::authorship::working_log::Checkpoint;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+/// Maps prompt hashes to `<prompt:N>` placeholders, numbered in order of first use
+#[derive(Default)]
+struct Redactor {
+    prompts: HashMap<String, String>,
+}
+
+impl Redactor {
+    fn prompt(&mut self, hash: &str) -> String {
+        let next = self.prompts.len() + 1;
+        self.prompts
+            .entry(hash.to_string())
+            .or_insert_with(|| format!("<prompt:{}>", next))
+            .clone()
+    }
+
+    /// Author ids in working logs are either a human name or the hash of a prompt in `prompts`
+    fn author(&mut self, author_id: &str, prompts: &HashSet<String>) -> String {
+        if prompts.contains(author_id) {
+            self.prompt(author_id)
+        } else {
+            author_id.to_string()
+        }
+    }
+}
+
+/// Canonical, redacted text form of `log`
+pub fn authorship_log_snapshot(log: &AuthorshipLog) -> String {
+    let mut redactor = Redactor::default();
+    let mut out = String::new();
+    writeln!(out, "schema: {}", log.metadata.schema_version).unwrap();
+
+    let mut files: Vec<_> = log.attestations.iter().collect();
+    files.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+    writeln!(out, "files:").unwrap();
+    for file in files {
+        writeln!(out, "  {}", file.file_path).unwrap();
+        let mut entries: Vec<_> = file.entries.iter().collect();
+        entries.sort_by_key(|entry| format_line_ranges(&entry.line_ranges));
+        for entry in entries {
+            let mut line = format!(
+                "    {} {}",
+                redactor.prompt(&entry.hash),
+                format_line_ranges(&entry.line_ranges)
+            );
+            if let Some(confidence) = entry.confidence {
+                write!(line, " ({}%)", confidence).unwrap();
+            }
+            writeln!(out, "{}", line).unwrap();
+            if !entry.column_ranges.is_empty() {
+                writeln!(
+                    out,
+                    "      columns {}",
+                    format_columns(&entry.column_ranges)
+                )
+                .unwrap();
+            }
+            if !entry.overridden_lines.is_empty() {
+                writeln!(
+                    out,
+                    "      overridden {}",
+                    format_line_ranges(&entry.overridden_lines)
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    // Prompts in placeholder order, then any that no attestation references
+    let mut prompts: Vec<(String, &PromptRecord)> = log
+        .metadata
+        .prompts
+        .iter()
+        .map(|(hash, record)| (redactor.prompt(hash), record))
+        .collect();
+    prompts.sort_by_key(|(placeholder, _)| placeholder_index(placeholder));
+    writeln!(out, "prompts:").unwrap();
+    for (placeholder, record) in prompts {
+        writeln!(
+            out,
+            "  {} {}/{} human={} messages={} +{} -{} accepted={} overridden={}",
+            placeholder,
+            record.agent_id.tool,
+            record.agent_id.model,
+            record.human_author.as_deref().unwrap_or("-"),
+            record.messages.len(),
+            record.total_additions,
+            record.total_deletions,
+            record.accepted_lines,
+            record.overriden_lines
+        )
+        .unwrap();
+    }
+    out
+}
+
+/// Canonical, redacted text form of a working log. Checkpoints keep their recorded order
+/// (it is part of the behavior under test); each file lists its line attributions.
+pub fn working_log_snapshot(checkpoints: &[Checkpoint]) -> String {
+    let mut redactor = Redactor::default();
+    let prompts: HashSet<String> = checkpoints
+        .iter()
+        .filter_map(|checkpoint| checkpoint.agent_id.as_ref())
+        .map(|agent_id| generate_short_hash(&agent_id.id, &agent_id.tool))
+        .collect();
+    let mut out = String::new();
+    for (index, checkpoint) in checkpoints.iter().enumerate() {
+        let mut header = format!("#{} {} {}", index + 1, checkpoint.kind, checkpoint.author);
+        if let Some(agent_id) = &checkpoint.agent_id {
+            write!(header, " ({}/{})", agent_id.tool, agent_id.model).unwrap();
+        }
+        writeln!(out, "{}", header).unwrap();
+
+        let mut entries: Vec<_> = checkpoint.entries.iter().collect();
+        entries.sort_by(|a, b| a.file.cmp(&b.file));
+        for entry in entries {
+            writeln!(out, "  {}", entry.file).unwrap();
+            let mut lines = entry.line_attributions.clone();
+            lines.sort_by_key(|line| (line.start_line, line.end_line));
+            for line in lines {
+                let author = redactor.author(&line.author_id, &prompts);
+                let mut text = if line.start_line == line.end_line {
+                    format!("    {} {}", line.start_line, author)
+                } else {
+                    format!("    {}-{} {}", line.start_line, line.end_line, author)
+                };
+                if let Some(overrode) = &line.overrode {
+                    write!(text, " overrode={}", redactor.author(overrode, &prompts)).unwrap();
+                }
+                if let Some(confidence) = line.confidence {
+                    write!(text, " ({}%)", confidence).unwrap();
+                }
+                writeln!(out, "{}", text).unwrap();
+            }
+        }
+    }
+    out
+}
+
+fn format_columns(ranges: &[ColumnRange]) -> String {
+    ranges
+        .iter()
+        .map(|range| format!("{}:{}-{}", range.line, range.start, range.end))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn placeholder_index(placeholder: &str) -> usize {
+    placeholder
+        .trim_start_matches("<prompt:")
+        .trim_end_matches('>')
+        .parse()
+        .unwrap_or(usize::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::test_utils::TmpRepo;
+    use insta::assert_snapshot;
+
+    #[test]
+    fn test_snapshots_of_mixed_human_and_ai_commit() {
+        let tmp_repo = TmpRepo::new().unwrap();
+        tmp_repo
+            .write_file("src/main.rs", "fn main() {\n}\n", true)
+            .unwrap();
+        tmp_repo
+            .trigger_checkpoint_with_author("test_user")
+            .unwrap();
+        tmp_repo
+            .write_file(
+                "src/main.rs",
+                "fn main() {\n    println!(\"hello\");\n}\n",
+                true,
+            )
+            .unwrap();
+        tmp_repo
+            .trigger_checkpoint_with_ai("Claude", Some("claude-3-sonnet"), Some("cursor"))
+            .unwrap();
+        tmp_repo.write_file("README.md", "# Demo\n", true).unwrap();
+        tmp_repo
+            .trigger_checkpoint_with_ai("Claude", Some("claude-3-sonnet"), Some("cursor"))
+            .unwrap();
+
+        assert_snapshot!("working_log", tmp_repo.working_log_snapshot().unwrap());
+        let log = tmp_repo.commit_with_message("Add greeting").unwrap();
+        assert_snapshot!("authorship_log", authorship_log_snapshot(&log));
+    }
+
+    #[test]
+    fn test_authorship_log_snapshot_is_independent_of_ordering_and_ids() {
+        // The conversation id (and so the prompt hash) differs between the two runs
+        let build = |conversation: &str, reversed: bool| {
+            let tmp_repo = TmpRepo::new().unwrap();
+            let files = if reversed {
+                ["b.txt", "a.txt"]
+            } else {
+                ["a.txt", "b.txt"]
+            };
+            for name in files {
+                tmp_repo.write_file(name, "one\ntwo\n", true).unwrap();
+            }
+            tmp_repo
+                .trigger_checkpoint_with_ai(conversation, Some("claude-3-sonnet"), Some("cursor"))
+                .unwrap();
+            authorship_log_snapshot(&tmp_repo.commit_with_message("Add files").unwrap())
+        };
+
+        assert_eq!(build("first", false), build("second", true));
+    }
+}
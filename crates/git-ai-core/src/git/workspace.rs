@@ -0,0 +1,200 @@
+//! Several repositories worked on together, for `git-ai workspace`. A workspace is listed in
+//! `.gitai-workspace`, a git-config format file in a directory above the repositories:
+//!
+//! ```ini
+//! [workspace]
+//!     repo = frontend
+//!     repo = services/api
+//!     submodules = true
+//! ```
+//!
+//! Paths are relative to the file. `submodules` adds the initialized submodules of each listed
+//! repository. Without the file, the superproject of the current repository and its submodules
+//! make the workspace.
+
+use std::path::{Path, PathBuf};
+
+use crate::error::GitAiError;
+use crate::git::repository::exec_git;
+
+pub const WORKSPACE_FILE: &str = ".gitai-workspace";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkspaceRepo {
+    /// The repository's path as listed, or relative to the superproject
+    pub name: String,
+    pub path: PathBuf,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Workspace {
+    /// Where `.gitai-workspace` is, or the superproject's working tree
+    pub root: PathBuf,
+    pub repos: Vec<WorkspaceRepo>,
+}
+
+impl Workspace {
+    /// The workspace `dir` is in: the nearest `.gitai-workspace` above it, or else the
+    /// superproject of the repository it's in
+    pub fn find(dir: &Path) -> Result<Workspace, GitAiError> {
+        if let Some(root) = dir
+            .ancestors()
+            .find(|ancestor| ancestor.join(WORKSPACE_FILE).is_file())
+        {
+            return Workspace::load(root);
+        }
+        let root = superproject(dir).ok_or_else(|| {
+            GitAiError::Generic(format!(
+                "no {} found and not in a git repository",
+                WORKSPACE_FILE
+            ))
+        })?;
+        let mut repos = vec![WorkspaceRepo {
+            name: ".".to_string(),
+            path: root.clone(),
+        }];
+        repos.extend(submodules(&root, None));
+        Ok(Workspace { root, repos })
+    }
+
+    /// Reads `.gitai-workspace` in `root`
+    fn load(root: &Path) -> Result<Workspace, GitAiError> {
+        let path = root.join(WORKSPACE_FILE);
+        let args = vec![
+            "config".to_string(),
+            "--file".to_string(),
+            path.to_string_lossy().to_string(),
+            "--get-regexp".to_string(),
+            r"^workspace\.".to_string(),
+        ];
+        // Exits non-zero when nothing matches
+        let output = exec_git(&args)
+            .map(|output| String::from_utf8_lossy(&output.stdout).to_string())
+            .unwrap_or_default();
+        let (names, with_submodules) = parse(&output);
+        if names.is_empty() {
+            return Err(GitAiError::Generic(format!(
+                "{} lists no repositories (add `repo = <path>` under [workspace])",
+                path.display()
+            )));
+        }
+
+        let mut repos: Vec<WorkspaceRepo> = Vec::new();
+        for name in names {
+            let repo = WorkspaceRepo {
+                path: root.join(&name),
+                name,
+            };
+            let nested = if with_submodules {
+                submodules(&repo.path, Some(&repo.name))
+            } else {
+                Vec::new()
+            };
+            for repo in std::iter::once(repo).chain(nested) {
+                if !repos.iter().any(|listed| listed.path == repo.path) {
+                    repos.push(repo);
+                }
+            }
+        }
+        Ok(Workspace {
+            root: root.to_path_buf(),
+            repos,
+        })
+    }
+}
+
+/// Parses `git config --get-regexp` output of `.gitai-workspace` into the listed repositories
+/// and whether to add their submodules
+fn parse(output: &str) -> (Vec<String>, bool) {
+    let mut names = Vec::new();
+    let mut with_submodules = false;
+    for line in output.lines() {
+        let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+        match key {
+            "workspace.repo" if !value.trim().is_empty() => {
+                let name = value.trim().to_string();
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+            "workspace.submodules" => {
+                with_submodules = matches!(
+                    value.trim().to_lowercase().as_str(),
+                    "" | "1" | "true" | "yes" | "on"
+                )
+            }
+            _ => {}
+        }
+    }
+    (names, with_submodules)
+}
+
+/// The working tree of the outermost repository `dir` is in, following submodules up
+fn superproject(dir: &Path) -> Option<PathBuf> {
+    let rev_parse = |dir: &Path, flag: &str| -> Option<PathBuf> {
+        let args = vec![
+            "-C".to_string(),
+            dir.to_string_lossy().to_string(),
+            "rev-parse".to_string(),
+            flag.to_string(),
+        ];
+        let output = exec_git(&args).ok()?;
+        let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        (!path.is_empty()).then(|| PathBuf::from(path))
+    };
+    let mut root = rev_parse(dir, "--show-toplevel")?;
+    while let Some(parent) = rev_parse(&root, "--show-superproject-working-tree") {
+        root = parent;
+    }
+    Some(root)
+}
+
+/// The initialized submodules of the repository at `repo`, named under `prefix`
+fn submodules(repo: &Path, prefix: Option<&str>) -> Vec<WorkspaceRepo> {
+    let gitmodules = repo.join(".gitmodules");
+    if !gitmodules.is_file() {
+        return Vec::new();
+    }
+    let args = vec![
+        "config".to_string(),
+        "--file".to_string(),
+        gitmodules.to_string_lossy().to_string(),
+        "--get-regexp".to_string(),
+        r"^submodule\..*\.path$".to_string(),
+    ];
+    let Ok(output) = exec_git(&args) else {
+        return Vec::new();
+    };
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.split_once(' ').map(|(_, path)| path.trim()))
+        // An uninitialized submodule is an empty directory
+        .filter(|path| repo.join(path).join(".git").exists())
+        .map(|path| WorkspaceRepo {
+            name: match prefix {
+                Some(prefix) => format!("{}/{}", prefix.trim_end_matches('/'), path),
+                None => path.to_string(),
+            },
+            path: repo.join(path),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_lists_repos_once_and_reads_submodules_flag() {
+        let output = "workspace.repo frontend\nworkspace.repo services/api\n\
+                      workspace.repo frontend\nworkspace.submodules true\nworkspace.other x\n";
+        assert_eq!(
+            parse(output),
+            (
+                vec!["frontend".to_string(), "services/api".to_string()],
+                true
+            )
+        );
+        assert_eq!(parse("workspace.repo .\n"), (vec![".".to_string()], false));
+    }
+}
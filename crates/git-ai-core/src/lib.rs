@@ -0,0 +1,245 @@
+//! Core of git-ai: authorship attribution, the authorship log and working log formats, and
+//! the repository abstraction they're read from and written to.
+//!
+//! The `git-ai` binary is a thin command-line frontend over this crate, and other Rust tools
+//! (bots, servers, analyzers) can depend on it to read and compute AI authorship without
+//! shelling out to the CLI. The functions and types at the crate root are the stable API;
+//! the modules underneath are what the CLI is built from and change with it.
+//!
+//! The main types are:
+//! - [`AttributionTracker`], which carries character-level attributions across edits
+//! - [`VirtualAttributions`], the in-memory attribution state of a set of files
+//! - [`AuthorshipLog`], the authorship note attached to a commit (`refs/notes/ai`)
+//!
+//! ```no_run
+//! let repo = git_ai_core::open_repository(".")?;
+//! if let Some(log) = git_ai_core::authorship_log_for_commit(&repo, "HEAD")? {
+//!     for file in &log.attestations {
+//!         println!("{}: {} AI entries", file.file_path, file.entries.len());
+//!     }
+//! }
+//! # Ok::<(), git_ai_core::GitAiError>(())
+//! ```
+
+pub mod authorship;
+pub mod commands;
+pub mod config;
+pub mod error;
+pub mod git;
+pub mod observability;
+pub mod utils;
+
+use std::collections::BTreeMap;
+
+use crate::commands::blame::GitAiBlameOptions;
+use crate::commands::checkpoint_agent::run_checkpoint_preset;
+use crate::git::refs::show_authorship_note;
+use crate::git::repo_storage::RepoStorage;
+
+pub use crate::authorship::attribution_tracker::{
+    Attribution, AttributionConfig, AttributionTracker, LineAttribution, MoveTimestampPolicy,
+    OverridePolicy, attributions_to_line_attributions,
+    attributions_to_line_attributions_with_policy, line_attributions_to_attributions,
+};
+pub use crate::authorship::authorship_log::{ColumnRange, LineRange, PromptRecord};
+pub use crate::authorship::authorship_log_serialization::{
+    AUTHORSHIP_LOG_VERSION, AttestationEntry, AuthorshipLog, AuthorshipMetadata, FileAttestation,
+};
+pub use crate::authorship::query::{
+    AttributedHunk, AttributionSource, AttributionStream, QueriedAuthor, QueriedLine,
+    query_attribution, stream_attribution,
+};
+pub use crate::authorship::stats::CommitStats;
+pub use crate::authorship::virtual_attribution::VirtualAttributions;
+pub use crate::authorship::working_log::{AgentId, Checkpoint, CheckpointKind};
+pub use crate::error::GitAiError;
+pub use crate::git::repository::Repository;
+
+/// Who wrote a line of a file, as reported by [`blame_file`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LineAuthor {
+    /// Written by a person; holds their git author name
+    Human(String),
+    /// Written by an AI agent session
+    Ai {
+        /// Short hash of the session, the key of its [`PromptRecord`]
+        prompt_hash: String,
+        /// Agent that wrote the line (e.g. `cursor`)
+        tool: String,
+        /// Model used by the agent
+        model: String,
+    },
+}
+
+/// One AI-authored line of a commit, as recorded in its authorship log. Flat so it maps
+/// directly onto a table row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AiLine {
+    pub file_path: String,
+    /// 1-indexed line number in the file as of the commit
+    pub line: u32,
+    /// Short hash of the session, the key of its [`PromptRecord`]
+    pub prompt_hash: String,
+    pub tool: String,
+    pub model: String,
+    /// Person who was driving the agent, if known
+    pub human_author: Option<String>,
+}
+
+/// Open the repository containing `path`.
+pub fn open_repository(path: &str) -> Result<Repository, GitAiError> {
+    crate::git::find_repository_in_path(path)
+}
+
+/// Authorship log attached to `revision`, or `None` if the commit has no authorship note.
+pub fn authorship_log_for_commit(
+    repo: &Repository,
+    revision: &str,
+) -> Result<Option<AuthorshipLog>, GitAiError> {
+    let commit_sha = repo.revparse_single(revision)?.id();
+    match show_authorship_note(repo, &commit_sha) {
+        Some(content) => AuthorshipLog::deserialize_from_string(&content)
+            .map(Some)
+            .map_err(|e| GitAiError::Generic(format!("Failed to parse authorship log: {}", e))),
+        None => Ok(None),
+    }
+}
+
+/// Commits reachable from `revision`, newest first, limited to `max_count` if given.
+pub fn commits(
+    repo: &Repository,
+    revision: &str,
+    max_count: Option<usize>,
+) -> Result<Vec<String>, GitAiError> {
+    let max_count = max_count.map(|n| format!("--max-count={}", n));
+    let mut args = vec!["rev-list"];
+    if let Some(max_count) = &max_count {
+        args.push(max_count);
+    }
+    args.extend(["--end-of-options", revision]);
+    Ok(repo.git(&args)?.lines().map(str::to_string).collect())
+}
+
+/// Every AI-authored line recorded in the authorship log of `revision`, ordered by file and
+/// line. Empty if the commit has no authorship note.
+pub fn ai_lines_for_commit(repo: &Repository, revision: &str) -> Result<Vec<AiLine>, GitAiError> {
+    let Some(log) = authorship_log_for_commit(repo, revision)? else {
+        return Ok(Vec::new());
+    };
+
+    let mut lines = Vec::new();
+    for file in &log.attestations {
+        for entry in &file.entries {
+            let prompt = log.metadata.prompts.get(&entry.hash);
+            for range in &entry.line_ranges {
+                for line in range.expand() {
+                    lines.push(AiLine {
+                        file_path: file.file_path.clone(),
+                        line,
+                        prompt_hash: entry.hash.clone(),
+                        tool: prompt.map(|p| p.agent_id.tool.clone()).unwrap_or_default(),
+                        model: prompt.map(|p| p.agent_id.model.clone()).unwrap_or_default(),
+                        human_author: prompt.and_then(|p| p.human_author.clone()),
+                    });
+                }
+            }
+        }
+    }
+    lines.sort_by(|a, b| (&a.file_path, a.line).cmp(&(&b.file_path, b.line)));
+    Ok(lines)
+}
+
+/// Record a checkpoint of the working directory, as `git-ai checkpoint [preset]` does, and
+/// return the number of files it captured. `preset` is anything the CLI accepts there: one of
+/// the agent presets (`claude`, `cursor`, `github-copilot`, `ai_tab`, `agent-v1`, `mock_ai`) or
+/// the name of an agent plugin, and takes its JSON payload in `hook_input`; `None` records a
+/// human checkpoint.
+pub fn checkpoint(
+    repo: &Repository,
+    preset: Option<&str>,
+    hook_input: Option<String>,
+) -> Result<usize, GitAiError> {
+    let agent_run_result = match preset {
+        Some(preset) => {
+            let workdir = repo.workdir()?.to_string_lossy().to_string();
+            match run_checkpoint_preset(preset, &[preset.to_string()], hook_input, None, &workdir)?
+            {
+                Some(agent_run) => Some(agent_run),
+                None => {
+                    return Err(GitAiError::PresetError(format!(
+                        "Unknown checkpoint preset: {}",
+                        preset
+                    )));
+                }
+            }
+        }
+        None => None,
+    };
+    let kind = agent_run_result
+        .as_ref()
+        .map(|r| r.checkpoint_kind)
+        .unwrap_or(CheckpointKind::Human);
+    let author = match repo.config_get_str("user.name") {
+        Ok(Some(name)) if !name.trim().is_empty() => name,
+        _ => "unknown".to_string(),
+    };
+
+    let (_, files_edited, _) = crate::commands::checkpoint::run(
+        repo,
+        &author,
+        kind,
+        false,
+        false,
+        true,
+        agent_run_result,
+        false,
+        false,
+    )?;
+    Ok(files_edited)
+}
+
+/// Checkpoints recorded since `HEAD`, oldest first. Empty once they have been committed.
+pub fn working_log(repo: &Repository) -> Result<Vec<Checkpoint>, GitAiError> {
+    let base_commit = match repo.head().and_then(|head| head.target()) {
+        Ok(sha) => sha,
+        Err(_) => "initial".to_string(),
+    };
+    RepoStorage::for_repo_path(repo.path(), &repo.workdir()?)
+        .working_log_for_base_commit(&base_commit)
+        .read_all_checkpoints()
+}
+
+/// Human vs AI line counts for `revision`, the same numbers `git-ai stats --json` prints.
+pub fn commit_stats(repo: &Repository, revision: &str) -> Result<CommitStats, GitAiError> {
+    let commit_sha = repo.revparse_single(revision)?.id();
+    crate::authorship::stats::stats_for_commit_stats(repo, &commit_sha, revision, false)
+}
+
+/// Author of every line of `file_path` (relative to the repository root) as it is in the
+/// working directory, keyed by 1-indexed line number.
+pub fn blame_file(
+    repo: &Repository,
+    file_path: &str,
+) -> Result<BTreeMap<u32, LineAuthor>, GitAiError> {
+    let options = GitAiBlameOptions {
+        use_prompt_hashes_as_names: true,
+        no_output: true,
+        ..Default::default()
+    };
+    let (line_authors, prompts) = repo.blame(file_path, &options)?;
+
+    Ok(line_authors
+        .into_iter()
+        .map(|(line, author)| {
+            let line_author = match prompts.get(&author) {
+                Some(prompt) => LineAuthor::Ai {
+                    tool: prompt.agent_id.tool.clone(),
+                    model: prompt.agent_id.model.clone(),
+                    prompt_hash: author,
+                },
+                None => LineAuthor::Human(author),
+            };
+            (line, line_author)
+        })
+        .collect())
+}
@@ -19,6 +19,11 @@ pub fn handle_flush_logs(args: &[String]) {
     }
 
     let config = Config::get();
+    if config.is_offline() {
+        // Logs stay on disk for a flush once back online
+        eprintln!("git-ai is in offline mode; not flushing logs");
+        std::process::exit(1);
+    }
 
     // Check for Enterprise DSN: config takes precedence over env var, which takes precedence over build-time value
     let enterprise_dsn = config
@@ -6,6 +6,8 @@ use std::sync::{Mutex, OnceLock};
 use std::time::Duration;
 
 pub mod flush;
+pub mod trace;
+pub mod usage;
 pub mod wrapper_performance_targets;
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -180,6 +182,9 @@ pub fn spawn_background_flush() {
     // and cleanup when telemetry_oss is "off"
     use std::process::Command;
 
+    if crate::config::Config::get().is_offline() {
+        return;
+    }
     if let Ok(exe) = crate::utils::current_git_ai_exe() {
         let _ = Command::new(exe)
             .arg("flush-logs")
@@ -0,0 +1,297 @@
+//! Structured tracing. Each phase of a command worth timing runs in a span named after it
+//! (`diff`, `move_detect`, `blame`, `notes_write`, ...). `GIT_AI_LOG=<level>` writes the spans
+//! and events at that level and above as JSON lines to `GIT_AI_LOG_FILE`, by default
+//! `~/.git-ai/logs/trace.jsonl`, and `--timing` (or `GIT_AI_TIMING=1`) prints how long each
+//! phase took when the command exits. `git-ai trace` records every span and debug event of
+//! the command it runs to put in its bundle.
+
+use std::fmt;
+use std::fs::OpenOptions;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use serde_json::{Map, Value, json};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::{Layer, Registry};
+
+static TIMING_REQUESTED: AtomicBool = AtomicBool::new(false);
+static RECORDING_REQUESTED: AtomicBool = AtomicBool::new(false);
+static STARTED: OnceLock<Instant> = OnceLock::new();
+/// Time spent in each phase as (span name, times it ran, total), in the order phases first ended
+static PHASES: Mutex<Vec<(&'static str, u32, Duration)>> = Mutex::new(Vec::new());
+/// Spans and events recorded since the last `take_recording`
+static RECORDED: Mutex<Vec<Value>> = Mutex::new(Vec::new());
+
+/// Asks for the phase breakdown at exit, as `--timing` does
+pub fn request_timing() {
+    TIMING_REQUESTED.store(true, Ordering::Relaxed);
+}
+
+/// Asks for every span and debug event to be recorded, for `git-ai trace`
+pub fn request_recording() {
+    RECORDING_REQUESTED.store(true, Ordering::Relaxed);
+}
+
+/// The spans (as they closed) and events recorded so far, oldest first, leaving none behind
+pub fn take_recording() -> Vec<Value> {
+    std::mem::take(&mut *RECORDED.lock().unwrap_or_else(|e| e.into_inner()))
+}
+
+/// Installs the subscriber the environment and `request_timing` ask for, if any. Only the first
+/// call does anything; without a subscriber, spans cost next to nothing.
+pub fn init() {
+    if STARTED.set(Instant::now()).is_err() {
+        return;
+    }
+    let timing = TIMING_REQUESTED.load(Ordering::Relaxed)
+        || std::env::var("GIT_AI_TIMING").unwrap_or_default() == "1";
+
+    let log_layer = log_level().and_then(|level| {
+        let path = log_file()?;
+        std::fs::create_dir_all(path.parent()?).ok()?;
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .ok()?;
+        Some(
+            tracing_subscriber::fmt::layer()
+                .json()
+                .with_span_events(FmtSpan::CLOSE)
+                .with_writer(Mutex::new(file))
+                .with_filter(level),
+        )
+    });
+    let timing_layer = timing.then_some(TimingLayer);
+    let recording_layer = RECORDING_REQUESTED
+        .load(Ordering::Relaxed)
+        .then(|| RecordingLayer.with_filter(LevelFilter::DEBUG));
+    if log_layer.is_none() && timing_layer.is_none() && recording_layer.is_none() {
+        return;
+    }
+
+    let subscriber = Registry::default()
+        .with(log_layer)
+        .with(timing_layer)
+        .with(recording_layer);
+    if tracing::subscriber::set_global_default(subscriber).is_ok() && timing {
+        // Commands end with `process::exit` as often as they return, so the breakdown is
+        // printed from an exit handler
+        unsafe {
+            libc::atexit(print_timing);
+        }
+    }
+}
+
+fn log_level() -> Option<LevelFilter> {
+    let level = LevelFilter::from_str(&std::env::var("GIT_AI_LOG").ok()?).ok()?;
+    (level != LevelFilter::OFF).then_some(level)
+}
+
+fn log_file() -> Option<PathBuf> {
+    match std::env::var("GIT_AI_LOG_FILE") {
+        Ok(path) if !path.is_empty() => Some(PathBuf::from(path)),
+        _ => dirs::home_dir().map(|home| home.join(".git-ai").join("logs").join("trace.jsonl")),
+    }
+}
+
+/// Adds up how long each span was open, by name
+struct TimingLayer;
+
+struct SpanStart(Instant);
+
+impl<S> Layer<S> for TimingLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, _attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(SpanStart(Instant::now()));
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let Some(elapsed) = span
+            .extensions()
+            .get::<SpanStart>()
+            .map(|start| start.0.elapsed())
+        else {
+            return;
+        };
+        let mut phases = PHASES.lock().unwrap_or_else(|e| e.into_inner());
+        match phases.iter_mut().find(|(name, _, _)| *name == span.name()) {
+            Some((_, count, total)) => {
+                *count += 1;
+                *total += elapsed;
+            }
+            None => phases.push((span.name(), 1, elapsed)),
+        }
+    }
+}
+
+/// Keeps spans and events as JSON objects in `RECORDED`
+struct RecordingLayer;
+
+struct RecordedSpan {
+    start: Instant,
+    fields: Map<String, Value>,
+}
+
+impl<S> Layer<S> for RecordingLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut fields = Map::new();
+        attrs.record(&mut JsonFields(&mut fields));
+        span.extensions_mut().insert(RecordedSpan {
+            start: Instant::now(),
+            fields,
+        });
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let mut fields = Map::new();
+        event.record(&mut JsonFields(&mut fields));
+        let message = fields.remove("message").unwrap_or_default();
+        record(json!({
+            "at_ms": since_start(),
+            "target": event.metadata().target(),
+            "level": event.metadata().level().as_str(),
+            "span": ctx.event_span(event).map(|span| span.name()),
+            "message": message,
+            "fields": fields,
+        }));
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let extensions = span.extensions();
+        let Some(recorded) = extensions.get::<RecordedSpan>() else {
+            return;
+        };
+        record(json!({
+            "at_ms": since_start(),
+            "target": span.metadata().target(),
+            "span": span.name(),
+            "duration_ms": recorded.start.elapsed().as_secs_f64() * 1000.0,
+            "fields": recorded.fields,
+        }));
+    }
+}
+
+fn record(value: Value) {
+    RECORDED
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .push(value);
+}
+
+fn since_start() -> f64 {
+    STARTED
+        .get()
+        .map(|started| started.elapsed().as_secs_f64() * 1000.0)
+        .unwrap_or_default()
+}
+
+struct JsonFields<'a>(&'a mut Map<String, Value>);
+
+impl Visit for JsonFields<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), format!("{:?}", value).into());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+}
+
+extern "C" fn print_timing() {
+    let Some(started) = STARTED.get() else {
+        return;
+    };
+    let phases = PHASES.lock().unwrap_or_else(|e| e.into_inner());
+    eprint!("{}", timing_report(started.elapsed(), &phases));
+}
+
+/// The phase breakdown, slowest first. Phases nest (a `diff` runs inside a `checkpoint`), and
+/// phases run on several threads at once add up their time, so the phases can add up to more
+/// than the total.
+fn timing_report(total: Duration, phases: &[(&str, u32, Duration)]) -> String {
+    let mut phases = phases.to_vec();
+    phases.sort_by_key(|(_, _, time)| std::cmp::Reverse(*time));
+    let width = phases
+        .iter()
+        .map(|(name, _, _)| name.len())
+        .max()
+        .unwrap_or(0);
+
+    let mut report = format!("git-ai timing: {} total\n", millis(total));
+    for (name, count, time) in phases {
+        report.push_str(&format!(
+            "  {:<width$}  {:>10}  {} {}\n",
+            name,
+            millis(time),
+            count,
+            if count == 1 { "call" } else { "calls" },
+            width = width
+        ));
+    }
+    report
+}
+
+fn millis(duration: Duration) -> String {
+    format!("{:.1}ms", duration.as_secs_f64() * 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timing_report_lists_slowest_phase_first() {
+        let report = timing_report(
+            Duration::from_millis(412),
+            &[
+                ("diff", 14, Duration::from_micros(120_500)),
+                ("post_commit", 1, Duration::from_millis(310)),
+            ],
+        );
+        assert_eq!(
+            report,
+            "git-ai timing: 412.0ms total\n  \
+             post_commit     310.0ms  1 call\n  \
+             diff            120.5ms  14 calls\n"
+        );
+    }
+}
@@ -0,0 +1,207 @@
+//! Opt-in anonymous usage counters.
+//!
+//! Off unless `usage_telemetry` is set in config.json. When on, coarse counters (which git-ai
+//! commands run, error codes, how many lines per commit were AI-attributed, bucketed) are added
+//! to a local spool, `~/.git-ai/usage.json`. Nothing leaves the machine until the user runs
+//! `git-ai telemetry send`. Prompts, code, file names, paths and repository names are never
+//! recorded.
+
+use crate::config::Config;
+use crate::error::GitAiError;
+use crate::utils::{debug_log, write_atomic};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const USAGE_ENDPOINT: &str = "https://usegitai.com/api/usage";
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsageSpool {
+    /// When counting started (RFC 3339), so a report covers a known period
+    pub since: Option<String>,
+    /// The git-ai version and OS that last wrote the spool
+    pub version: String,
+    pub os: String,
+    /// Runs of each git-ai command, and of the git commands git-ai hooks ("git:commit", ...)
+    pub commands: BTreeMap<String, u64>,
+    /// Failures by error code (see "Errors and Exit Codes" in docs/reference.mdx)
+    pub errors: BTreeMap<String, u64>,
+    /// Commits by how many of their lines are AI-attributed ("0", "1-9", "10-99", ...)
+    pub ai_lines_per_commit: BTreeMap<String, u64>,
+}
+
+impl UsageSpool {
+    pub fn load(path: &Path) -> UsageSpool {
+        fs::read(path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&mut self, path: &Path) -> Result<(), GitAiError> {
+        if self.since.is_none() {
+            self.since = Some(chrono::Utc::now().to_rfc3339());
+        }
+        self.version = env!("CARGO_PKG_VERSION").to_string();
+        self.os = std::env::consts::OS.to_string();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        write_atomic(path, &serde_json::to_vec_pretty(self)?)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty() && self.errors.is_empty() && self.ai_lines_per_commit.is_empty()
+    }
+
+    fn count_command(&mut self, name: &str) {
+        *self.commands.entry(name.to_string()).or_default() += 1;
+    }
+
+    fn count_error(&mut self, code: &str) {
+        *self.errors.entry(code.to_string()).or_default() += 1;
+    }
+
+    fn count_commit(&mut self, ai_lines: u32) {
+        *self
+            .ai_lines_per_commit
+            .entry(lines_bucket(ai_lines).to_string())
+            .or_default() += 1;
+    }
+}
+
+/// Order-of-magnitude bucket, so no exact sizes are kept
+fn lines_bucket(lines: u32) -> &'static str {
+    match lines {
+        0 => "0",
+        1..=9 => "1-9",
+        10..=99 => "10-99",
+        100..=999 => "100-999",
+        _ => "1000+",
+    }
+}
+
+pub fn spool_path() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".git-ai").join("usage.json"))
+}
+
+/// Applies `update` to the spool if the user opted in; a no-op (no file I/O) otherwise.
+/// Concurrent processes can lose each other's increments, which is fine for coarse counters.
+fn record(update: impl FnOnce(&mut UsageSpool)) {
+    if !Config::get().usage_telemetry_enabled() {
+        return;
+    }
+    let Some(path) = spool_path() else {
+        return;
+    };
+    let mut spool = UsageSpool::load(&path);
+    update(&mut spool);
+    if let Err(e) = spool.save(&path) {
+        debug_log(&format!("Failed to write usage spool: {}", e));
+    }
+}
+
+/// Counts a run of a git-ai command or hooked git command. Callers pass only names from a
+/// fixed list, never free-form user input.
+pub fn record_command(name: &str) {
+    record(|spool| spool.count_command(name));
+}
+
+pub fn record_error(err: &GitAiError) {
+    let code = err.code();
+    record(|spool| spool.count_error(code));
+}
+
+pub fn record_commit_ai_lines(ai_lines: u32) {
+    record(|spool| spool.count_commit(ai_lines));
+}
+
+/// Posts the spool to the usage endpoint and clears it once accepted
+pub fn send(path: &Path) -> Result<UsageSpool, GitAiError> {
+    let config = Config::get();
+    if !config.usage_telemetry_enabled() {
+        return Err(GitAiError::Generic(
+            "usage telemetry is off; set \"usage_telemetry\": true in ~/.git-ai/config.json to opt in"
+                .to_string(),
+        ));
+    }
+    if config.is_offline() {
+        return Err(GitAiError::Generic(
+            "git-ai is in offline mode; not sending usage counters".to_string(),
+        ));
+    }
+
+    let spool = UsageSpool::load(path);
+    if spool.is_empty() {
+        return Ok(spool);
+    }
+    let endpoint = config.usage_telemetry_endpoint().unwrap_or(USAGE_ENDPOINT);
+    let response = minreq::post(endpoint)
+        .with_header("Content-Type", "application/json")
+        .with_header(
+            "User-Agent",
+            format!("git-ai/{}", env!("CARGO_PKG_VERSION")),
+        )
+        .with_body(serde_json::to_vec(&spool)?)
+        .with_timeout(10)
+        .send()
+        .map_err(|e| GitAiError::Generic(format!("Failed to send usage counters: {}", e)))?;
+    if !(200..300).contains(&response.status_code) {
+        return Err(GitAiError::Generic(format!(
+            "Usage endpoint {} answered {}",
+            endpoint, response.status_code
+        )));
+    }
+    clear(path)?;
+    Ok(spool)
+}
+
+pub fn clear(path: &Path) -> Result<(), GitAiError> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spool_counts_and_round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("usage.json");
+
+        let mut spool = UsageSpool::load(&path);
+        assert!(spool.is_empty());
+        spool.count_command("stats");
+        spool.count_command("stats");
+        spool.count_error("git_failed");
+        spool.count_commit(0);
+        spool.count_commit(42);
+        spool.count_commit(57);
+        spool.save(&path).unwrap();
+
+        let loaded = UsageSpool::load(&path);
+        assert_eq!(loaded.commands["stats"], 2);
+        assert_eq!(loaded.errors["git_failed"], 1);
+        assert_eq!(loaded.ai_lines_per_commit["0"], 1);
+        assert_eq!(loaded.ai_lines_per_commit["10-99"], 2);
+        assert!(loaded.since.is_some());
+        assert_eq!(loaded.version, env!("CARGO_PKG_VERSION"));
+
+        clear(&path).unwrap();
+        assert!(!path.exists());
+        clear(&path).unwrap();
+    }
+
+    #[test]
+    fn test_lines_bucket() {
+        assert_eq!(lines_bucket(0), "0");
+        assert_eq!(lines_bucket(9), "1-9");
+        assert_eq!(lines_bucket(10), "10-99");
+        assert_eq!(lines_bucket(999), "100-999");
+        assert_eq!(lines_bucket(1000), "1000+");
+    }
+}
@@ -0,0 +1,349 @@
+use crate::error::GitAiError;
+use crate::git::diff_tree_to_tree::Diff;
+use std::path::{Path, PathBuf};
+
+/// Check if debug logging is enabled via environment variable
+///
+/// This is checked once at module initialization to avoid repeated environment variable lookups.
+static DEBUG_ENABLED: std::sync::OnceLock<bool> = std::sync::OnceLock::new();
+static DEBUG_PERFORMANCE_ENABLED: std::sync::OnceLock<bool> = std::sync::OnceLock::new();
+
+fn is_debug_enabled() -> bool {
+    *DEBUG_ENABLED.get_or_init(|| {
+        cfg!(debug_assertions)
+            || std::env::var("GIT_AI_DEBUG").unwrap_or_default() == "1"
+            || std::env::var("GIT_AI_DEBUG_PERFORMANCE").unwrap_or_default() == "1"
+    })
+}
+
+fn is_debug_performance_enabled() -> bool {
+    is_debug_enabled()
+        && *DEBUG_PERFORMANCE_ENABLED
+            .get_or_init(|| std::env::var("GIT_AI_DEBUG_PERFORMANCE").unwrap_or_default() == "1")
+}
+
+pub fn debug_performance_log(msg: &str) {
+    tracing::debug!(target: "git_ai::perf", "{}", msg);
+    if is_debug_performance_enabled() {
+        eprintln!("\x1b[1;33m[git-ai (perf)]\x1b[0m {}", msg);
+    }
+}
+
+/// Debug logging utility function
+///
+/// Prints debug messages with a colored prefix when debug assertions are enabled or when
+/// the `GIT_AI_DEBUG` environment variable is set to "1", and records them as debug events for
+/// `GIT_AI_LOG`.
+///
+/// # Arguments
+///
+/// * `msg` - The debug message to print
+pub fn debug_log(msg: &str) {
+    tracing::debug!("{}", msg);
+    if is_debug_enabled() {
+        eprintln!("\x1b[1;33m[git-ai]\x1b[0m {}", msg);
+    }
+}
+
+/// Print a git diff in a readable format
+///
+/// Prints the diff between two commits/trees showing which files changed and their status.
+/// This is useful for debugging and understanding what changes occurred.
+///
+/// # Arguments
+///
+/// * `diff` - The git diff object to print
+/// * `old_label` - Label for the "old" side (e.g., commit SHA or description)
+/// * `new_label` - Label for the "new" side (e.g., commit SHA or description)
+pub fn _print_diff(diff: &Diff, old_label: &str, new_label: &str) {
+    println!("Diff between {} and {}:", old_label, new_label);
+
+    let mut file_count = 0;
+    for delta in diff.deltas() {
+        file_count += 1;
+        let old_file = delta.old_file().path().unwrap_or(std::path::Path::new(""));
+        let new_file = delta.new_file().path().unwrap_or(std::path::Path::new(""));
+        let status = delta.status();
+
+        println!(
+            "  File {}: {} -> {} (status: {:?})",
+            file_count,
+            old_file.display(),
+            new_file.display(),
+            status
+        );
+    }
+
+    if file_count == 0 {
+        println!("  No changes between {} and {}", old_label, new_label);
+    }
+}
+
+
+#[inline]
+pub fn normalize_to_posix(path: &str) -> String {
+    // The backslashes of a quoted path (one that isn't UTF-8) are escapes, not separators
+    if crate::git::repo_path::is_quoted_key(path) {
+        return path.to_string();
+    }
+    path.replace('\\', "/")
+}
+
+/// Drops the `\\?\` prefix `canonicalize` adds on Windows (`\\?\C:\repo` becomes `C:\repo`,
+/// `\\?\UNC\server\share` becomes `\\server\share`), so canonical paths compare equal to the
+/// paths git reports and can be passed to cmd.exe. Other paths are returned unchanged.
+pub fn strip_verbatim_prefix(path: &Path) -> PathBuf {
+    let path_str = path.to_string_lossy();
+    if let Some(unc) = path_str.strip_prefix(r"\\?\UNC\") {
+        return PathBuf::from(format!(r"\\{}", unc));
+    }
+    match path_str.strip_prefix(r"\\?\") {
+        Some(rest) if rest.as_bytes().get(1) == Some(&b':') => PathBuf::from(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Whether the binary was invoked as git-ai rather than through the `git` shim. Windows file
+/// names are case-insensitive, so `GIT-AI.EXE` counts too.
+pub fn is_git_ai_binary_name(binary_name: &str) -> bool {
+    let name = binary_name.to_ascii_lowercase();
+    name == "git-ai" || name == "git-ai.exe"
+}
+
+/// How to write `program` at the start of a hook command an editor runs through a shell:
+/// without the verbatim prefix, and quoted if it has spaces (`C:\Users\Jane Doe\...`)
+pub fn shell_program(program: &Path) -> String {
+    let program = strip_verbatim_prefix(program)
+        .to_string_lossy()
+        .into_owned();
+    if program.contains(char::is_whitespace) {
+        format!("\"{}\"", program)
+    } else {
+        program
+    }
+}
+
+pub fn current_git_ai_exe() -> Result<PathBuf, GitAiError> {
+    let path = std::env::current_exe()?;
+    
+    // Get platform-specific executable names
+    let git_name = if cfg!(windows) { "git.exe" } else { "git" };
+    let git_ai_name = if cfg!(windows) { "git-ai.exe" } else { "git-ai" };
+    
+    // Check if the filename matches the git executable name for this platform
+    if let Some(file_name) = path.file_name().and_then(|n| n.to_str()) {
+        if file_name == git_name {
+            // Try replacing with git-ai executable name for this platform
+            let git_ai_path = path.with_file_name(git_ai_name);
+            
+            // Check if the git-ai file exists
+            if git_ai_path.exists() {
+                return Ok(git_ai_path);
+            }
+            
+            // If it doesn't exist, return the git-ai executable name as a PathBuf
+            return Ok(PathBuf::from(git_ai_name));
+        }
+    }
+    
+    Ok(path)
+}
+thread_local! {
+    /// Clock installed by `git::test_utils::MockClock` on this thread: (next reading in
+    /// milliseconds, step added after every reading)
+    static MOCK_CLOCK: std::cell::Cell<Option<(u128, u128)>> = const { std::cell::Cell::new(None) };
+}
+
+/// Current time in milliseconds since the Unix epoch. Timestamps git-ai records (checkpoints,
+/// attributions) come from here so tests can control them.
+pub fn now_millis() -> u128 {
+    if let Some((now, step)) = MOCK_CLOCK.get() {
+        MOCK_CLOCK.set(Some((now + step, step)));
+        return now;
+    }
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis()
+}
+
+/// Current time in seconds since the Unix epoch, see `now_millis`
+pub fn now_secs() -> u64 {
+    (now_millis() / 1000) as u64
+}
+
+#[cfg(feature = "test-support")]
+pub(crate) fn set_mock_clock(clock: Option<(u128, u128)>) {
+    MOCK_CLOCK.set(clock);
+}
+
+#[cfg(feature = "test-support")]
+pub(crate) fn mock_clock() -> Option<(u128, u128)> {
+    MOCK_CLOCK.get()
+}
+
+/// Replaces `path` with `data` so that a crash leaves either the old or the new content, never
+/// a partial file: writes a temp file next to it, fsyncs, then renames over it
+pub fn write_atomic(path: &std::path::Path, data: &[u8]) -> Result<(), GitAiError> {
+    use std::io::Write;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    // Tells apart the temp files of threads writing the same path
+    static NEXT_TMP: AtomicUsize = AtomicUsize::new(0);
+
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    // Unique per call so concurrent writers don't clobber each other's temp file
+    let tmp_path = path.with_file_name(format!(
+        ".{}.{}.{}.tmp",
+        file_name,
+        std::process::id(),
+        NEXT_TMP.fetch_add(1, Ordering::Relaxed)
+    ));
+    {
+        let mut file = std::fs::File::create(&tmp_path)?;
+        file.write_all(data)?;
+        file.sync_all()?;
+    }
+    if let Err(e) = std::fs::rename(&tmp_path, path) {
+        let _ = std::fs::remove_file(&tmp_path);
+        return Err(e.into());
+    }
+    // Persist the rename itself
+    #[cfg(unix)]
+    if let Some(parent) = path.parent()
+        && let Ok(dir) = std::fs::File::open(parent)
+    {
+        let _ = dir.sync_all();
+    }
+    Ok(())
+}
+
+/// The version number of a release tag: `v1.2.3-next-abc` is `1.2.3`
+pub fn semver_from_tag(tag: &str) -> String {
+    let trimmed = tag.trim().trim_start_matches('v');
+    trimmed
+        .split(|c| c == '-' || c == '+')
+        .next()
+        .unwrap_or("")
+        .to_string()
+}
+
+/// True if dotted version `latest` is newer than `current`
+pub fn is_newer_version(latest: &str, current: &str) -> bool {
+    let parse_version =
+        |v: &str| -> Vec<u32> { v.split('.').filter_map(|s| s.parse::<u32>().ok()).collect() };
+
+    let latest_parts = parse_version(latest);
+    let current_parts = parse_version(current);
+
+    for i in 0..latest_parts.len().max(current_parts.len()) {
+        let latest_part = latest_parts.get(i).copied().unwrap_or(0);
+        let current_part = current_parts.get(i).copied().unwrap_or(0);
+
+        if latest_part > current_part {
+            return true;
+        } else if latest_part < current_part {
+            return false;
+        }
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_newer_version() {
+        assert!(!is_newer_version("1.0.0", "1.0.0"));
+        assert!(!is_newer_version("1.0.10", "1.0.10"));
+
+        assert!(is_newer_version("1.0.1", "1.0.0"));
+        assert!(is_newer_version("1.0.11", "1.0.10"));
+        assert!(!is_newer_version("1.0.0", "1.0.1"));
+        assert!(!is_newer_version("1.0.10", "1.0.11"));
+
+        assert!(is_newer_version("1.1.0", "1.0.0"));
+        assert!(!is_newer_version("1.0.0", "1.1.0"));
+
+        assert!(is_newer_version("2.0.0", "1.0.0"));
+        assert!(is_newer_version("2.0.0", "1.9.9"));
+        assert!(!is_newer_version("1.9.9", "2.0.0"));
+
+        assert!(is_newer_version("1.0.0.1", "1.0.0"));
+        assert!(!is_newer_version("1.0.0", "1.0.0.1"));
+
+        assert!(is_newer_version("1.10.0", "1.9.0"));
+        assert!(is_newer_version("1.0.100", "1.0.99"));
+        assert!(is_newer_version("100.200.300", "100.200.299"));
+    }
+
+    #[test]
+    fn test_semver_from_tag_strips_prefix_and_suffix() {
+        assert_eq!(semver_from_tag("v1.2.3"), "1.2.3");
+        assert_eq!(semver_from_tag("1.2.3"), "1.2.3");
+        assert_eq!(semver_from_tag("v1.2.3-next-abc"), "1.2.3");
+    }
+
+    #[test]
+    fn test_strip_verbatim_prefix() {
+        assert_eq!(
+            strip_verbatim_prefix(Path::new(r"\\?\C:\Users\dev\repo")),
+            PathBuf::from(r"C:\Users\dev\repo")
+        );
+        assert_eq!(
+            strip_verbatim_prefix(Path::new(r"\\?\UNC\server\share\repo")),
+            PathBuf::from(r"\\server\share\repo")
+        );
+        // Verbatim paths with no plain equivalent (volume GUIDs) are kept
+        assert_eq!(
+            strip_verbatim_prefix(Path::new(r"\\?\Volume{1234}\repo")),
+            PathBuf::from(r"\\?\Volume{1234}\repo")
+        );
+        assert_eq!(
+            strip_verbatim_prefix(Path::new("/home/dev/repo")),
+            PathBuf::from("/home/dev/repo")
+        );
+    }
+
+    #[test]
+    fn test_is_git_ai_binary_name() {
+        assert!(is_git_ai_binary_name("git-ai"));
+        assert!(is_git_ai_binary_name("git-ai.exe"));
+        assert!(is_git_ai_binary_name("GIT-AI.EXE"));
+        assert!(!is_git_ai_binary_name("git"));
+        assert!(!is_git_ai_binary_name("git.exe"));
+    }
+
+    #[test]
+    fn test_shell_program_quotes_paths_with_spaces() {
+        assert_eq!(
+            shell_program(Path::new("/usr/local/bin/git-ai")),
+            "/usr/local/bin/git-ai"
+        );
+        assert_eq!(
+            shell_program(Path::new(r"\\?\C:\Users\Jane Doe\.git-ai\bin\git-ai.exe")),
+            r#""C:\Users\Jane Doe\.git-ai\bin\git-ai.exe""#
+        );
+    }
+
+    #[test]
+    fn test_write_atomic_from_concurrent_threads() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.json");
+        std::thread::scope(|scope| {
+            for i in 0..8 {
+                let path = &path;
+                scope.spawn(move || write_atomic(path, i.to_string().as_bytes()).unwrap());
+            }
+        });
+        let written: u32 = std::fs::read_to_string(&path).unwrap().parse().unwrap();
+        assert!(written < 8);
+        // No temp file is left behind
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
@@ -0,0 +1,113 @@
+use git_ai_core::git::test_utils::TmpRepo;
+use git_ai_core::{CheckpointKind, LineAuthor};
+
+#[test]
+fn test_reads_authorship_through_public_api() {
+    let tmp_repo = TmpRepo::new().unwrap();
+
+    let mut file = tmp_repo.write_file("test.txt", "Line1\n", true).unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    tmp_repo.commit_with_message("Initial commit").unwrap();
+
+    file.append("Line 2\nLine 3\n").unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", Some("claude-3-sonnet"), Some("cursor"))
+        .unwrap();
+    tmp_repo.commit_with_message("AI adds lines").unwrap();
+
+    let repo = git_ai_core::open_repository(tmp_repo.path().to_str().unwrap()).unwrap();
+
+    let log = git_ai_core::authorship_log_for_commit(&repo, "HEAD")
+        .unwrap()
+        .expect("AI commit should have an authorship note");
+    assert_eq!(log.attestations.len(), 1);
+    assert_eq!(log.attestations[0].file_path, "test.txt");
+
+    let stats = git_ai_core::commit_stats(&repo, "HEAD").unwrap();
+    assert_eq!(stats.ai_additions, 2);
+    assert_eq!(stats.human_additions, 0);
+
+    let ai_lines = git_ai_core::ai_lines_for_commit(&repo, "HEAD").unwrap();
+    assert_eq!(
+        ai_lines.iter().map(|l| l.line).collect::<Vec<_>>(),
+        vec![2, 3]
+    );
+    assert!(
+        ai_lines
+            .iter()
+            .all(|l| l.file_path == "test.txt" && l.tool == "cursor")
+    );
+
+    let commits = git_ai_core::commits(&repo, "HEAD", None).unwrap();
+    assert_eq!(commits.len(), 2);
+    assert_eq!(commits[0], repo.revparse_single("HEAD").unwrap().id());
+    assert_eq!(
+        git_ai_core::commits(&repo, "HEAD", Some(1)).unwrap(),
+        commits[..1]
+    );
+
+    let authors = git_ai_core::blame_file(&repo, "test.txt").unwrap();
+    assert!(matches!(authors[&1], LineAuthor::Human(_)));
+    for line in [2, 3] {
+        match &authors[&line] {
+            LineAuthor::Ai { tool, model, .. } => {
+                assert_eq!(tool, "cursor");
+                assert_eq!(model, "claude-3-sonnet");
+            }
+            other => panic!("Line {} should be AI-authored, got {:?}", line, other),
+        }
+    }
+}
+
+#[test]
+fn test_commit_without_note_has_no_authorship_log() {
+    let tmp_repo = TmpRepo::new().unwrap();
+    tmp_repo.write_file("test.txt", "Line1\n", true).unwrap();
+    tmp_repo
+        .git_command(&["commit", "-m", "Plain commit"])
+        .unwrap();
+
+    let repo = git_ai_core::open_repository(tmp_repo.path().to_str().unwrap()).unwrap();
+    assert!(
+        git_ai_core::authorship_log_for_commit(&repo, "HEAD")
+            .unwrap()
+            .is_none()
+    );
+}
+
+#[test]
+fn test_checkpoints_and_working_log_through_public_api() {
+    let tmp_repo = TmpRepo::new().unwrap();
+    let mut file = tmp_repo.write_file("test.txt", "Line1\n", true).unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    tmp_repo.commit_with_message("Initial commit").unwrap();
+
+    let repo = git_ai_core::open_repository(tmp_repo.path().to_str().unwrap()).unwrap();
+    assert!(git_ai_core::working_log(&repo).unwrap().is_empty());
+
+    file.append("Line 2\n").unwrap();
+    let hook_input = serde_json::json!({
+        "hook_event_name": "after_edit",
+        "tool": "tab-tool",
+        "model": "tab-model",
+        "edited_filepaths": ["test.txt"],
+    })
+    .to_string();
+    let files = git_ai_core::checkpoint(&repo, Some("ai_tab"), Some(hook_input)).unwrap();
+    assert_eq!(files, 1);
+
+    file.append("Line 3\n").unwrap();
+    git_ai_core::checkpoint(&repo, None, None).unwrap();
+
+    let checkpoints = git_ai_core::working_log(&repo).unwrap();
+    let kinds: Vec<_> = checkpoints.iter().map(|c| c.kind).collect();
+    assert_eq!(kinds, vec![CheckpointKind::AiTab, CheckpointKind::Human]);
+    assert_eq!(checkpoints[0].agent_id.as_ref().unwrap().tool, "tab-tool");
+
+    let err = git_ai_core::checkpoint(&repo, Some("no-such-preset"), None).unwrap_err();
+    assert!(err.to_string().contains("no-such-preset"));
+}
@@ -0,0 +1,44 @@
+/*
+ * C interface to git-ai authorship queries (crates/git-ai-ffi).
+ *
+ * Strings are NUL-terminated UTF-8. Strings returned through `out` are owned by the
+ * caller and must be released with git_ai_string_free().
+ */
+#ifndef GIT_AI_H
+#define GIT_AI_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define GIT_AI_OK 0
+#define GIT_AI_ERR_INVALID_ARGUMENT 1
+#define GIT_AI_ERR_NOT_FOUND 2
+#define GIT_AI_ERR_GIT 3
+#define GIT_AI_ERR_INTERNAL 4
+
+typedef struct GitAiRepo GitAiRepo;
+
+int git_ai_repo_open(const char *path, GitAiRepo **out);
+void git_ai_repo_free(GitAiRepo *repo);
+
+/* JSON array of per-line authors of a working-directory file */
+int git_ai_blame_file(const GitAiRepo *repo, const char *file_path, char **out);
+/* Authorship note text; GIT_AI_ERR_NOT_FOUND if the commit has none */
+int git_ai_authorship_log(const GitAiRepo *repo, const char *revision, char **out);
+/* Commit stats as JSON, as printed by `git-ai stats --json` */
+int git_ai_commit_stats(const GitAiRepo *repo, const char *revision, char **out);
+/* JSON array of commit SHAs reachable from revision; max_count <= 0 means no limit */
+int git_ai_commits(const GitAiRepo *repo, const char *revision, long long max_count, char **out);
+/* JSON array of the AI-authored lines recorded for a commit */
+int git_ai_commit_ai_lines(const GitAiRepo *repo, const char *revision, char **out);
+
+void git_ai_string_free(char *s);
+/* Last error on the calling thread; valid until the next call, do not free */
+const char *git_ai_last_error(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* GIT_AI_H */
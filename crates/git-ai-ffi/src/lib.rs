@@ -0,0 +1,295 @@
+//! C ABI for git-ai authorship queries.
+//!
+//! Built as a `cdylib` so editors and tools written in other languages can link against it
+//! instead of spawning `git-ai` per query. The declarations live in `include/git_ai.h`.
+//!
+//! Conventions:
+//! - A repository is an opaque `GitAiRepo*` from [`git_ai_repo_open`], released with
+//!   [`git_ai_repo_free`].
+//! - Every string crossing the boundary is NUL-terminated UTF-8. Strings returned through
+//!   an `out` pointer are owned by the caller and must be released with [`git_ai_string_free`].
+//! - Every query returns a `GIT_AI_*` status code. On failure, [`git_ai_last_error`] returns
+//!   a message describing the last error on the calling thread.
+
+use std::cell::RefCell;
+use std::ffi::{CStr, CString, c_char};
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::ptr;
+
+use git_ai_core::{GitAiError, LineAuthor, Repository};
+use serde_json::json;
+
+/// The call succeeded
+pub const GIT_AI_OK: i32 = 0;
+/// A pointer argument was null or a string was not valid UTF-8
+pub const GIT_AI_ERR_INVALID_ARGUMENT: i32 = 1;
+/// The requested data does not exist (e.g. the commit has no authorship note)
+pub const GIT_AI_ERR_NOT_FOUND: i32 = 2;
+/// The underlying git or attribution operation failed
+pub const GIT_AI_ERR_GIT: i32 = 3;
+/// A panic was caught at the boundary
+pub const GIT_AI_ERR_INTERNAL: i32 = 4;
+
+/// Opaque repository handle.
+pub struct GitAiRepo {
+    repo: Repository,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: impl Into<String>) {
+    let message = CString::new(message.into().replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+/// Runs `f` and converts its result, errors and panics into a status code.
+fn guard(f: impl FnOnce() -> Result<(), (i32, String)>) -> i32 {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => GIT_AI_OK,
+        Ok(Err((code, message))) => {
+            set_last_error(message);
+            code
+        }
+        Err(_) => {
+            set_last_error("panic in git-ai");
+            GIT_AI_ERR_INTERNAL
+        }
+    }
+}
+
+fn git_error(e: GitAiError) -> (i32, String) {
+    (GIT_AI_ERR_GIT, e.to_string())
+}
+
+/// # Safety
+/// `s` must be null or point to a NUL-terminated string.
+unsafe fn str_arg<'a>(s: *const c_char, name: &str) -> Result<&'a str, (i32, String)> {
+    if s.is_null() {
+        return Err((GIT_AI_ERR_INVALID_ARGUMENT, format!("{} is null", name)));
+    }
+    unsafe { CStr::from_ptr(s) }.to_str().map_err(|_| {
+        (
+            GIT_AI_ERR_INVALID_ARGUMENT,
+            format!("{} is not UTF-8", name),
+        )
+    })
+}
+
+/// # Safety
+/// `repo` must be null or a live handle from [`git_ai_repo_open`].
+unsafe fn repo_arg<'a>(repo: *const GitAiRepo) -> Result<&'a Repository, (i32, String)> {
+    unsafe { repo.as_ref() }
+        .map(|handle| &handle.repo)
+        .ok_or((GIT_AI_ERR_INVALID_ARGUMENT, "repo is null".to_string()))
+}
+
+/// # Safety
+/// `out` must be null or valid for writes.
+unsafe fn write_string(out: *mut *mut c_char, value: String) -> Result<(), (i32, String)> {
+    if out.is_null() {
+        return Err((GIT_AI_ERR_INVALID_ARGUMENT, "out is null".to_string()));
+    }
+    let value = CString::new(value).map_err(|_| {
+        (
+            GIT_AI_ERR_INTERNAL,
+            "result contains a NUL byte".to_string(),
+        )
+    })?;
+    unsafe { *out = value.into_raw() };
+    Ok(())
+}
+
+/// Open the repository containing `path` and store its handle in `*out`.
+///
+/// # Safety
+/// `path` must be a NUL-terminated string and `out` must be valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn git_ai_repo_open(path: *const c_char, out: *mut *mut GitAiRepo) -> i32 {
+    guard(|| {
+        let path = unsafe { str_arg(path, "path") }?;
+        if out.is_null() {
+            return Err((GIT_AI_ERR_INVALID_ARGUMENT, "out is null".to_string()));
+        }
+        let repo = git_ai_core::open_repository(path).map_err(git_error)?;
+        unsafe { *out = Box::into_raw(Box::new(GitAiRepo { repo })) };
+        Ok(())
+    })
+}
+
+/// Release a handle from [`git_ai_repo_open`]. Null is ignored.
+///
+/// # Safety
+/// `repo` must be null or a handle that has not been freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn git_ai_repo_free(repo: *mut GitAiRepo) {
+    if !repo.is_null() {
+        drop(unsafe { Box::from_raw(repo) });
+    }
+}
+
+/// Author of every line of `file_path` in the working directory, as a JSON array of
+/// `{"line": n, "kind": "human", "author": ...}` or
+/// `{"line": n, "kind": "ai", "prompt_hash": ..., "tool": ..., "model": ...}` objects.
+///
+/// # Safety
+/// `repo` must be a live handle, `file_path` a NUL-terminated string and `out` valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn git_ai_blame_file(
+    repo: *const GitAiRepo,
+    file_path: *const c_char,
+    out: *mut *mut c_char,
+) -> i32 {
+    guard(|| {
+        let repo = unsafe { repo_arg(repo) }?;
+        let file_path = unsafe { str_arg(file_path, "file_path") }?;
+        let authors = git_ai_core::blame_file(repo, file_path).map_err(git_error)?;
+
+        let lines: Vec<_> = authors
+            .into_iter()
+            .map(|(line, author)| match author {
+                LineAuthor::Human(author) => {
+                    json!({ "line": line, "kind": "human", "author": author })
+                }
+                LineAuthor::Ai {
+                    prompt_hash,
+                    tool,
+                    model,
+                } => json!({
+                    "line": line,
+                    "kind": "ai",
+                    "prompt_hash": prompt_hash,
+                    "tool": tool,
+                    "model": model,
+                }),
+            })
+            .collect();
+        unsafe { write_string(out, serde_json::Value::Array(lines).to_string()) }
+    })
+}
+
+/// Authorship note of `revision` in its on-disk text format. Returns
+/// [`GIT_AI_ERR_NOT_FOUND`] if the commit has no note.
+///
+/// # Safety
+/// `repo` must be a live handle, `revision` a NUL-terminated string and `out` valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn git_ai_authorship_log(
+    repo: *const GitAiRepo,
+    revision: *const c_char,
+    out: *mut *mut c_char,
+) -> i32 {
+    guard(|| {
+        let repo = unsafe { repo_arg(repo) }?;
+        let revision = unsafe { str_arg(revision, "revision") }?;
+        let log = git_ai_core::authorship_log_for_commit(repo, revision)
+            .map_err(git_error)?
+            .ok_or_else(|| {
+                (
+                    GIT_AI_ERR_NOT_FOUND,
+                    format!("{} has no authorship log", revision),
+                )
+            })?;
+        let text = log
+            .serialize_to_string()
+            .map_err(|e| (GIT_AI_ERR_INTERNAL, e.to_string()))?;
+        unsafe { write_string(out, text) }
+    })
+}
+
+/// Stats of `revision` as JSON, the same object `git-ai stats --json` prints.
+///
+/// # Safety
+/// `repo` must be a live handle, `revision` a NUL-terminated string and `out` valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn git_ai_commit_stats(
+    repo: *const GitAiRepo,
+    revision: *const c_char,
+    out: *mut *mut c_char,
+) -> i32 {
+    guard(|| {
+        let repo = unsafe { repo_arg(repo) }?;
+        let revision = unsafe { str_arg(revision, "revision") }?;
+        let stats = git_ai_core::commit_stats(repo, revision).map_err(git_error)?;
+        let json =
+            serde_json::to_string(&stats).map_err(|e| (GIT_AI_ERR_INTERNAL, e.to_string()))?;
+        unsafe { write_string(out, json) }
+    })
+}
+
+/// Commits reachable from `revision` as a JSON array of SHAs, newest first. `max_count` of
+/// zero or less means no limit.
+///
+/// # Safety
+/// `repo` must be a live handle, `revision` a NUL-terminated string and `out` valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn git_ai_commits(
+    repo: *const GitAiRepo,
+    revision: *const c_char,
+    max_count: i64,
+    out: *mut *mut c_char,
+) -> i32 {
+    guard(|| {
+        let repo = unsafe { repo_arg(repo) }?;
+        let revision = unsafe { str_arg(revision, "revision") }?;
+        let max_count = usize::try_from(max_count).ok().filter(|n| *n > 0);
+        let commits = git_ai_core::commits(repo, revision, max_count).map_err(git_error)?;
+        unsafe { write_string(out, json!(commits).to_string()) }
+    })
+}
+
+/// AI-authored lines recorded for `revision` as a JSON array of
+/// `{"file_path", "line", "prompt_hash", "tool", "model", "human_author"}` objects. Empty if
+/// the commit has no authorship log.
+///
+/// # Safety
+/// `repo` must be a live handle, `revision` a NUL-terminated string and `out` valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn git_ai_commit_ai_lines(
+    repo: *const GitAiRepo,
+    revision: *const c_char,
+    out: *mut *mut c_char,
+) -> i32 {
+    guard(|| {
+        let repo = unsafe { repo_arg(repo) }?;
+        let revision = unsafe { str_arg(revision, "revision") }?;
+        let lines: Vec<_> = git_ai_core::ai_lines_for_commit(repo, revision)
+            .map_err(git_error)?
+            .into_iter()
+            .map(|line| {
+                json!({
+                    "file_path": line.file_path,
+                    "line": line.line,
+                    "prompt_hash": line.prompt_hash,
+                    "tool": line.tool,
+                    "model": line.model,
+                    "human_author": line.human_author,
+                })
+            })
+            .collect();
+        unsafe { write_string(out, serde_json::Value::Array(lines).to_string()) }
+    })
+}
+
+/// Release a string returned by this library. Null is ignored.
+///
+/// # Safety
+/// `s` must be null or a string from this library that has not been freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn git_ai_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(unsafe { CString::from_raw(s) });
+    }
+}
+
+/// Message of the last error on the calling thread, or null if there was none. The pointer
+/// stays valid until the next call into this library on the same thread and must not be freed.
+#[unsafe(no_mangle)]
+pub extern "C" fn git_ai_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(ptr::null(), |message| message.as_ptr())
+    })
+}
@@ -0,0 +1,136 @@
+use std::ffi::{CStr, CString, c_char};
+use std::ptr;
+
+use git_ai_core::git::test_utils::TmpRepo;
+use git_ai_ffi::*;
+
+fn take_string(s: *mut c_char) -> String {
+    let value = unsafe { CStr::from_ptr(s) }.to_str().unwrap().to_string();
+    unsafe { git_ai_string_free(s) };
+    value
+}
+
+fn last_error() -> String {
+    unsafe { CStr::from_ptr(git_ai_last_error()) }
+        .to_str()
+        .unwrap()
+        .to_string()
+}
+
+#[test]
+fn test_queries_over_c_abi() {
+    let tmp_repo = TmpRepo::new().unwrap();
+    let mut file = tmp_repo.write_file("test.txt", "Line1\n", true).unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    tmp_repo.commit_with_message("Initial commit").unwrap();
+    file.append("Line 2\n").unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", Some("claude-3-sonnet"), Some("cursor"))
+        .unwrap();
+    tmp_repo.commit_with_message("AI adds a line").unwrap();
+
+    let path = CString::new(tmp_repo.path().to_str().unwrap()).unwrap();
+    let head = CString::new("HEAD").unwrap();
+    let file_path = CString::new("test.txt").unwrap();
+
+    let mut repo = ptr::null_mut();
+    assert_eq!(
+        unsafe { git_ai_repo_open(path.as_ptr(), &mut repo) },
+        GIT_AI_OK
+    );
+
+    let mut out = ptr::null_mut();
+    assert_eq!(
+        unsafe { git_ai_blame_file(repo, file_path.as_ptr(), &mut out) },
+        GIT_AI_OK
+    );
+    let blame: serde_json::Value = serde_json::from_str(&take_string(out)).unwrap();
+    assert_eq!(blame[0]["kind"], "human");
+    assert_eq!(blame[1]["kind"], "ai");
+    assert_eq!(blame[1]["tool"], "cursor");
+
+    assert_eq!(
+        unsafe { git_ai_commit_stats(repo, head.as_ptr(), &mut out) },
+        GIT_AI_OK
+    );
+    let stats: serde_json::Value = serde_json::from_str(&take_string(out)).unwrap();
+    assert_eq!(stats["ai_additions"], 1);
+
+    assert_eq!(
+        unsafe { git_ai_authorship_log(repo, head.as_ptr(), &mut out) },
+        GIT_AI_OK
+    );
+    assert!(take_string(out).contains("test.txt"));
+
+    assert_eq!(
+        unsafe { git_ai_commits(repo, head.as_ptr(), 0, &mut out) },
+        GIT_AI_OK
+    );
+    let commits: Vec<String> = serde_json::from_str(&take_string(out)).unwrap();
+    assert_eq!(commits.len(), 2);
+
+    assert_eq!(
+        unsafe { git_ai_commit_ai_lines(repo, head.as_ptr(), &mut out) },
+        GIT_AI_OK
+    );
+    let lines: serde_json::Value = serde_json::from_str(&take_string(out)).unwrap();
+    assert_eq!(lines.as_array().unwrap().len(), 1);
+    assert_eq!(lines[0]["line"], 2);
+    assert_eq!(lines[0]["model"], "claude-3-sonnet");
+
+    unsafe { git_ai_repo_free(repo) };
+}
+
+#[test]
+fn test_commit_without_note_is_not_found() {
+    let tmp_repo = TmpRepo::new().unwrap();
+    tmp_repo.write_file("test.txt", "Line1\n", true).unwrap();
+    tmp_repo
+        .git_command(&["commit", "-m", "Plain commit"])
+        .unwrap();
+
+    let path = CString::new(tmp_repo.path().to_str().unwrap()).unwrap();
+    let head = CString::new("HEAD").unwrap();
+    let mut repo = ptr::null_mut();
+    assert_eq!(
+        unsafe { git_ai_repo_open(path.as_ptr(), &mut repo) },
+        GIT_AI_OK
+    );
+
+    let mut out = ptr::null_mut();
+    assert_eq!(
+        unsafe { git_ai_authorship_log(repo, head.as_ptr(), &mut out) },
+        GIT_AI_ERR_NOT_FOUND
+    );
+    assert!(out.is_null());
+    assert!(last_error().contains("no authorship log"));
+
+    unsafe { git_ai_repo_free(repo) };
+}
+
+#[test]
+fn test_invalid_arguments_return_error_codes() {
+    let mut repo = ptr::null_mut();
+    assert_eq!(
+        unsafe { git_ai_repo_open(ptr::null(), &mut repo) },
+        GIT_AI_ERR_INVALID_ARGUMENT
+    );
+    assert!(last_error().contains("path is null"));
+
+    let head = CString::new("HEAD").unwrap();
+    let mut out = ptr::null_mut();
+    assert_eq!(
+        unsafe { git_ai_commit_stats(ptr::null(), head.as_ptr(), &mut out) },
+        GIT_AI_ERR_INVALID_ARGUMENT
+    );
+    assert!(out.is_null());
+
+    let missing = CString::new("/nonexistent/git-ai-ffi-test").unwrap();
+    assert_eq!(
+        unsafe { git_ai_repo_open(missing.as_ptr(), &mut repo) },
+        GIT_AI_ERR_GIT
+    );
+    assert!(repo.is_null());
+}
@@ -0,0 +1,6 @@
+fn main() {
+    // N-API functions are provided by the node binary that loads the addon; napi-build sets up
+    // the link arguments for that on each platform (dynamic lookup on macOS, node.lib on
+    // Windows).
+    napi_build::setup();
+}
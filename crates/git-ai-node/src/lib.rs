@@ -0,0 +1,108 @@
+//! Node.js native addon for git-ai, built with napi-rs.
+//!
+//! Lets editor extensions record checkpoints and query blame and the working log in-process
+//! instead of spawning `git-ai` on every buffer change. The JavaScript wrapper in
+//! `bindings/node` loads this library and is the supported entry point.
+//!
+//! Every export takes string arguments and returns a Promise of a JSON string. The work runs
+//! on the libuv thread pool so it never blocks the extension host's event loop.
+
+use std::panic::{AssertUnwindSafe, catch_unwind};
+
+use git_ai_core::{LineAuthor, Repository};
+use napi::bindgen_prelude::AsyncTask;
+use napi::{Env, Error, Result, Status, Task};
+use napi_derive::napi;
+use serde_json::json;
+
+/// A query run on the thread pool, resolved into its Promise once it finishes.
+pub struct Query(Option<Box<dyn FnOnce() -> std::result::Result<String, String> + Send>>);
+
+impl Query {
+    fn spawn(
+        query: impl FnOnce() -> std::result::Result<String, String> + Send + 'static,
+    ) -> AsyncTask<Query> {
+        AsyncTask::new(Query(Some(Box::new(query))))
+    }
+}
+
+impl Task for Query {
+    type Output = String;
+    type JsValue = String;
+
+    fn compute(&mut self) -> Result<String> {
+        let query = self
+            .0
+            .take()
+            .ok_or_else(|| Error::new(Status::GenericFailure, "query already ran"))?;
+        match catch_unwind(AssertUnwindSafe(query)) {
+            Ok(result) => result.map_err(|e| Error::new(Status::GenericFailure, e)),
+            Err(_) => Err(Error::new(
+                Status::GenericFailure,
+                "git-ai panicked while handling the request",
+            )),
+        }
+    }
+
+    fn resolve(&mut self, _env: Env, output: String) -> Result<String> {
+        Ok(output)
+    }
+}
+
+fn open(repo_path: &str) -> std::result::Result<Repository, String> {
+    git_ai_core::open_repository(repo_path).map_err(|e| e.to_string())
+}
+
+/// `checkpoint(repoPath, preset?, hookInput?)`: number of files captured.
+#[napi(ts_return_type = "Promise<string>")]
+pub fn checkpoint(
+    repo_path: String,
+    preset: Option<String>,
+    hook_input: Option<String>,
+) -> AsyncTask<Query> {
+    Query::spawn(move || {
+        let repo = open(&repo_path)?;
+        let files = git_ai_core::checkpoint(&repo, preset.as_deref(), hook_input)
+            .map_err(|e| e.to_string())?;
+        Ok(files.to_string())
+    })
+}
+
+/// `blame(repoPath, filePath)`: array of per-line authors.
+#[napi(ts_return_type = "Promise<string>")]
+pub fn blame(repo_path: String, file_path: String) -> AsyncTask<Query> {
+    Query::spawn(move || {
+        let repo = open(&repo_path)?;
+        let authors = git_ai_core::blame_file(&repo, &file_path).map_err(|e| e.to_string())?;
+        let lines: Vec<_> = authors
+            .into_iter()
+            .map(|(line, author)| match author {
+                LineAuthor::Human(author) => {
+                    json!({ "line": line, "kind": "human", "author": author })
+                }
+                LineAuthor::Ai {
+                    prompt_hash,
+                    tool,
+                    model,
+                } => json!({
+                    "line": line,
+                    "kind": "ai",
+                    "promptHash": prompt_hash,
+                    "tool": tool,
+                    "model": model,
+                }),
+            })
+            .collect();
+        Ok(serde_json::Value::Array(lines).to_string())
+    })
+}
+
+/// `workingLog(repoPath)`: checkpoints recorded since `HEAD`, oldest first.
+#[napi(ts_return_type = "Promise<string>")]
+pub fn working_log(repo_path: String) -> AsyncTask<Query> {
+    Query::spawn(move || {
+        let repo = open(&repo_path)?;
+        let checkpoints = git_ai_core::working_log(&repo).map_err(|e| e.to_string())?;
+        serde_json::to_string(&checkpoints).map_err(|e| e.to_string())
+    })
+}
@@ -0,0 +1,158 @@
+//! Python extension module for git-ai, built with PyO3.
+//!
+//! Imported as `gitai._native` by the `gitai` package in `bindings/python`, which is the
+//! supported entry point. Queries run in-process with the GIL released and return plain
+//! dicts and lists, so analytics code can load them straight into pandas instead of parsing
+//! `git-ai` CLI output.
+
+use git_ai_core::{GitAiError as CoreError, LineAuthor};
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+create_exception!(_native, GitAiError, PyException, "A git-ai query failed.");
+
+fn git_error(e: CoreError) -> PyErr {
+    GitAiError::new_err(e.to_string())
+}
+
+/// A git repository with git-ai authorship data.
+#[pyclass(subclass, module = "gitai")]
+pub struct Repository {
+    repo: Option<git_ai_core::Repository>,
+}
+
+impl Repository {
+    fn repo(&self) -> PyResult<&git_ai_core::Repository> {
+        self.repo
+            .as_ref()
+            .ok_or_else(|| GitAiError::new_err("repository is closed"))
+    }
+}
+
+#[pymethods]
+impl Repository {
+    #[new]
+    #[pyo3(signature = (path = "."))]
+    fn new(py: Python<'_>, path: &str) -> PyResult<Self> {
+        let repo = py
+            .allow_threads(|| git_ai_core::open_repository(path))
+            .map_err(git_error)?;
+        Ok(Repository { repo: Some(repo) })
+    }
+
+    fn close(&mut self) {
+        self.repo = None;
+    }
+
+    fn __enter__(slf: Py<Self>) -> Py<Self> {
+        slf
+    }
+
+    #[pyo3(signature = (*_exc))]
+    fn __exit__(&mut self, _exc: &Bound<'_, PyAny>) {
+        self.close();
+    }
+
+    /// SHAs of the commits reachable from `revision`, newest first.
+    #[pyo3(signature = (revision = "HEAD", max_count = None))]
+    fn commits(
+        &self,
+        py: Python<'_>,
+        revision: &str,
+        max_count: Option<usize>,
+    ) -> PyResult<Vec<String>> {
+        let repo = self.repo()?;
+        py.allow_threads(|| git_ai_core::commits(repo, revision, max_count.filter(|n| *n > 0)))
+            .map_err(git_error)
+    }
+
+    /// Authorship note of `revision` in its text format, or None if it has none.
+    #[pyo3(signature = (revision = "HEAD"))]
+    fn authorship_log(&self, py: Python<'_>, revision: &str) -> PyResult<Option<String>> {
+        let repo = self.repo()?;
+        let log = py
+            .allow_threads(|| git_ai_core::authorship_log_for_commit(repo, revision))
+            .map_err(git_error)?;
+        log.map(|log| {
+            log.serialize_to_string()
+                .map_err(|e| GitAiError::new_err(e.to_string()))
+        })
+        .transpose()
+    }
+
+    /// Stats of `revision`, the same dict `git-ai stats --json` prints.
+    #[pyo3(signature = (revision = "HEAD"))]
+    fn stats<'py>(&self, py: Python<'py>, revision: &str) -> PyResult<Bound<'py, PyAny>> {
+        let repo = self.repo()?;
+        let json = py
+            .allow_threads(|| {
+                let stats = git_ai_core::commit_stats(repo, revision)?;
+                serde_json::to_string(&stats).map_err(CoreError::from)
+            })
+            .map_err(git_error)?;
+        py.import("json")?.call_method1("loads", (json,))
+    }
+
+    /// One record per line of `file_path` in the working directory.
+    fn blame<'py>(&self, py: Python<'py>, file_path: &str) -> PyResult<Vec<Bound<'py, PyDict>>> {
+        let repo = self.repo()?;
+        let authors = py
+            .allow_threads(|| git_ai_core::blame_file(repo, file_path))
+            .map_err(git_error)?;
+        authors
+            .into_iter()
+            .map(|(line, author)| {
+                let record = PyDict::new(py);
+                record.set_item("line", line)?;
+                match author {
+                    LineAuthor::Human(author) => {
+                        record.set_item("kind", "human")?;
+                        record.set_item("author", author)?;
+                    }
+                    LineAuthor::Ai {
+                        prompt_hash,
+                        tool,
+                        model,
+                    } => {
+                        record.set_item("kind", "ai")?;
+                        record.set_item("prompt_hash", prompt_hash)?;
+                        record.set_item("tool", tool)?;
+                        record.set_item("model", model)?;
+                    }
+                }
+                Ok(record)
+            })
+            .collect()
+    }
+
+    /// One record per AI-authored line recorded for `revision`.
+    #[pyo3(signature = (revision = "HEAD"))]
+    fn ai_lines<'py>(&self, py: Python<'py>, revision: &str) -> PyResult<Vec<Bound<'py, PyDict>>> {
+        let repo = self.repo()?;
+        let lines = py
+            .allow_threads(|| git_ai_core::ai_lines_for_commit(repo, revision))
+            .map_err(git_error)?;
+        lines
+            .into_iter()
+            .map(|line| {
+                let record = PyDict::new(py);
+                record.set_item("file_path", line.file_path)?;
+                record.set_item("line", line.line)?;
+                record.set_item("prompt_hash", line.prompt_hash)?;
+                record.set_item("tool", line.tool)?;
+                record.set_item("model", line.model)?;
+                record.set_item("human_author", line.human_author)?;
+                Ok(record)
+            })
+            .collect()
+    }
+}
+
+#[pymodule]
+fn _native(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<Repository>()?;
+    m.add("GitAiError", m.py().get_type::<GitAiError>())?;
+    Ok(())
+}
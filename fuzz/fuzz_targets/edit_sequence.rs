@@ -0,0 +1,62 @@
+//! A sequence of splice edits by either author, checking the attributions after every step.
+//! Splice offsets are arbitrary and get snapped back to char boundaries.
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use git_ai::authorship::attribution_tracker::AttributionTracker;
+use git_ai::git::test_utils::check_attribution_invariants;
+use libfuzzer_sys::fuzz_target;
+
+#[derive(Debug, Arbitrary)]
+struct Edit {
+    at: usize,
+    remove: u8,
+    insert: String,
+    ai: bool,
+}
+
+#[derive(Debug, Arbitrary)]
+struct Input {
+    initial: String,
+    edits: Vec<Edit>,
+}
+
+fn snap(content: &str, mut offset: usize) -> usize {
+    offset = offset.min(content.len());
+    while !content.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    offset
+}
+
+fuzz_target!(|input: Input| {
+    let tracker = AttributionTracker::new();
+    let mut content = String::new();
+    let mut attributions = Vec::new();
+
+    let initial = std::iter::once(Edit {
+        at: 0,
+        remove: 0,
+        insert: input.initial,
+        ai: false,
+    });
+    for (step, edit) in initial.chain(input.edits).take(64).enumerate() {
+        let start = snap(&content, edit.at);
+        let end = snap(&content, start + edit.remove as usize);
+        let mut new_content = content.clone();
+        new_content.replace_range(start..end, &edit.insert);
+
+        let author = if edit.ai { "ai" } else { "human" };
+        attributions = tracker
+            .update_attributions(&content, &new_content, &attributions, author, step as u128)
+            .unwrap();
+        if let Err(violation) = check_attribution_invariants(&new_content, &attributions, true) {
+            panic!(
+                "step {}: {}\nold: {:?}\nnew: {:?}\nattributions: {:?}",
+                step, violation, content, new_content, attributions
+            );
+        }
+        content = new_content;
+    }
+});
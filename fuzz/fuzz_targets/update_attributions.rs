@@ -0,0 +1,38 @@
+//! One edit from arbitrary old content to arbitrary new content, starting from attributions
+//! that cover the old content.
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use git_ai::authorship::attribution_tracker::AttributionTracker;
+use git_ai::git::test_utils::{attributions_from_cuts, check_attribution_invariants};
+use libfuzzer_sys::fuzz_target;
+
+#[derive(Debug, Arbitrary)]
+struct Input {
+    old_content: String,
+    new_content: String,
+    cuts: Vec<usize>,
+    ai_edit: bool,
+}
+
+fuzz_target!(|input: Input| {
+    let tracker = AttributionTracker::new();
+    let old_attributions =
+        attributions_from_cuts(&input.old_content, &input.cuts, &["human", "ai"], 1);
+    check_attribution_invariants(&input.old_content, &old_attributions, true).unwrap();
+
+    let author = if input.ai_edit { "ai" } else { "human" };
+    let attributions = tracker
+        .update_attributions(
+            &input.old_content,
+            &input.new_content,
+            &old_attributions,
+            author,
+            2,
+        )
+        .unwrap();
+    if let Err(violation) = check_attribution_invariants(&input.new_content, &attributions, true) {
+        panic!("{}: {:?}", violation, attributions);
+    }
+});
@@ -129,6 +129,7 @@ case $ARCH in
 esac
 
 # Map OS to binary name
+EXE_SUFFIX=""
 case $OS in
     "darwin")
         OS="macos"
@@ -136,13 +137,18 @@ case $OS in
     "linux")
         OS="linux"
         ;;
+    mingw*|msys*|cygwin*)
+        # Git Bash / MSYS2 on Windows
+        OS="windows"
+        EXE_SUFFIX=".exe"
+        ;;
     *)
         error "Unsupported operating system: $OS"
         ;;
 esac
 
 # Determine binary name
-BINARY_NAME="git-ai-${OS}-${ARCH}"
+BINARY_NAME="git-ai-${OS}-${ARCH}${EXE_SUFFIX}"
 
 # Determine release tag (defaults to latest but can be overridden)
 RELEASE_TAG="${GIT_AI_RELEASE_TAG:-latest}"
@@ -173,15 +179,28 @@ if [ ! -s "$TMP_FILE" ]; then
     error "Downloaded file is empty"
 fi
 
-mv -f "$TMP_FILE" "${INSTALL_DIR}/git-ai"
+mv -f "$TMP_FILE" "${INSTALL_DIR}/git-ai${EXE_SUFFIX}"
 
 # Make executable
-chmod +x "${INSTALL_DIR}/git-ai"
-# Symlink git to git-ai
-ln -sf "${INSTALL_DIR}/git-ai" "${INSTALL_DIR}/git"
+chmod +x "${INSTALL_DIR}/git-ai${EXE_SUFFIX}"
+
+if [ "$OS" = "windows" ]; then
+    # Symlinks need developer mode or admin rights on Windows (and MSYS's ln silently copies
+    # instead), so like install.ps1 the git shim is a copy of the binary and git-og a wrapper:
+    # a script for Git Bash and a batch file for cmd.exe and PowerShell
+    cp -f "${INSTALL_DIR}/git-ai.exe" "${INSTALL_DIR}/git.exe"
+    printf '#!/bin/sh\nexec "%s" "$@"\n' "$STD_GIT_PATH" > "${INSTALL_DIR}/git-og"
+    chmod +x "${INSTALL_DIR}/git-og"
+    if command -v cygpath >/dev/null 2>&1; then
+        printf '@echo off\r\n"%s" %%*\r\n' "$(cygpath -w "$STD_GIT_PATH")" > "${INSTALL_DIR}/git-og.cmd"
+    fi
+else
+    # Symlink git to git-ai
+    ln -sf "${INSTALL_DIR}/git-ai" "${INSTALL_DIR}/git"
 
-# Symlink git-og to the detected standard git path
-ln -sf "$STD_GIT_PATH" "${INSTALL_DIR}/git-og"
+    # Symlink git-og to the detected standard git path
+    ln -sf "$STD_GIT_PATH" "${INSTALL_DIR}/git-og"
+fi
 
 # Remove quarantine attribute on macOS
 if [ "$OS" = "macos" ]; then
@@ -195,7 +214,7 @@ success "You can now run 'git-ai' from your terminal"
 
 # Install hooks
 echo "Setting up IDE/agent hooks..."
-if ! ${INSTALL_DIR}/git-ai install-hooks; then
+if ! "${INSTALL_DIR}/git-ai${EXE_SUFFIX}" install-hooks; then
     warn "Warning: Failed to set up IDE/agent hooks. Please try running 'git-ai install-hooks' manually."
 else
     success "Successfully set up IDE/agent hooks"
@@ -206,11 +225,17 @@ CONFIG_DIR="$HOME/.git-ai"
 CONFIG_JSON_PATH="$CONFIG_DIR/config.json"
 mkdir -p "$CONFIG_DIR"
 
+# git-ai.exe can't resolve MSYS paths like /mingw64/bin/git, so store C:/... instead
+CONFIG_GIT_PATH="$STD_GIT_PATH"
+if [ "$OS" = "windows" ] && command -v cygpath >/dev/null 2>&1; then
+    CONFIG_GIT_PATH=$(cygpath -m "$STD_GIT_PATH")
+fi
+
 if [ ! -f "$CONFIG_JSON_PATH" ]; then
     TMP_CFG="$CONFIG_JSON_PATH.tmp.$$"
     cat >"$TMP_CFG" <<EOF
 {
-  "git_path": "${STD_GIT_PATH}"
+  "git_path": "${CONFIG_GIT_PATH}"
 }
 EOF
     mv -f "$TMP_CFG" "$CONFIG_JSON_PATH"
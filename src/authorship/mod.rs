@@ -1,12 +0,0 @@
-pub mod attribution_tracker;
-pub mod authorship_log;
-pub mod authorship_log_serialization;
-pub mod move_detection;
-pub mod post_commit;
-pub mod pre_commit;
-pub mod range_authorship;
-pub mod rebase_authorship;
-pub mod stats;
-pub mod transcript;
-pub mod virtual_attribution;
-pub mod working_log;
@@ -87,3 +87,62 @@ impl CiContext {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::test_utils::{TmpRemote, TmpRepo};
+
+    #[test]
+    fn test_rewrites_authorship_for_hosted_squash_merge() {
+        let remote = TmpRemote::new().unwrap();
+        let dev = TmpRepo::new().unwrap();
+        dev.write_file("app.txt", "base\n", true).unwrap();
+        dev.trigger_checkpoint_with_author("Test User").unwrap();
+        dev.commit_with_message("Base").unwrap();
+        dev.git_command(&["branch", "-M", "main"]).unwrap();
+        dev.add_remote("origin", &remote).unwrap();
+        dev.push("origin", "main").unwrap();
+
+        dev.create_branch("feature").unwrap();
+        dev.write_file("app.txt", "base\nfrom claude\n", true)
+            .unwrap();
+        dev.trigger_checkpoint_with_ai("Claude", Some("claude-3-sonnet"), Some("cursor"))
+            .unwrap();
+        dev.commit_with_message("AI feature").unwrap();
+        dev.push("origin", "feature").unwrap();
+        let head_sha = dev.head_commit_sha().unwrap();
+
+        let merge_sha = remote
+            .squash_merge("feature", "main", "Feature (#1)")
+            .unwrap();
+        assert_eq!(remote.branch_sha("main").unwrap(), merge_sha);
+        assert!(remote.authorship_log(&merge_sha).unwrap().is_none());
+
+        let ci = remote.clone_into().unwrap();
+        let context = CiContext {
+            repo: ci.gitai_repo().clone(),
+            event: CiEvent::Merge {
+                merge_commit_sha: merge_sha.clone(),
+                head_ref: "feature".to_string(),
+                head_sha,
+                base_ref: "main".to_string(),
+                base_sha: String::new(),
+            },
+            temp_dir: ci.path().clone(),
+        };
+        context.run().unwrap();
+        context.teardown().unwrap();
+
+        let log = remote.authorship_log(&merge_sha).unwrap().unwrap();
+        let file = &log.attestations[0];
+        assert_eq!(file.file_path, "app.txt");
+        assert_eq!(file.entries[0].line_ranges.len(), 1);
+        let prompt = &log.metadata.prompts[&file.entries[0].hash];
+        assert_eq!(prompt.agent_id.tool, "cursor");
+
+        // Once pushed, a fresh clone sees the rewritten authorship
+        let later = remote.clone_into().unwrap();
+        assert!(crate::git::refs::show_authorship_note(later.gitai_repo(), &merge_sha).is_some());
+    }
+}
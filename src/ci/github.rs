@@ -7,6 +7,7 @@ use std::fs;
 use std::path::PathBuf;
 
 const GITHUB_CI_TEMPLATE_YAML: &str = include_str!("workflow_templates/github.yaml");
+const GITHUB_NOTIFY_TEMPLATE_YAML: &str = include_str!("workflow_templates/github_notify.yaml");
 
 #[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
 struct GithubCiEventPayload {
@@ -131,3 +132,23 @@ pub fn install_github_ci_workflow() -> Result<PathBuf, GitAiError> {
 
     Ok(dest_path)
 }
+
+/// Install or update a GitHub Actions workflow that posts `git-ai notify <channel>`'s report
+/// every week, to .github/workflows/git-ai-report.yaml at the repo root
+pub fn install_github_notify_workflow(channel: &str) -> Result<PathBuf, GitAiError> {
+    let repo = find_repository_in_path(".")?;
+    let workdir = repo.workdir()?;
+
+    let workflows_dir = workdir.join(".github").join("workflows");
+    fs::create_dir_all(&workflows_dir)
+        .map_err(|e| GitAiError::Generic(format!("Failed to create workflows dir: {}", e)))?;
+
+    let dest_path = workflows_dir.join("git-ai-report.yaml");
+    fs::write(
+        &dest_path,
+        GITHUB_NOTIFY_TEMPLATE_YAML.replace("{channel}", channel),
+    )
+    .map_err(|e| GitAiError::Generic(format!("Failed to write workflow file: {}", e)))?;
+
+    Ok(dest_path)
+}
@@ -0,0 +1,169 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::authorship::stats::stats_for_commit_stats;
+use crate::error::{GitAiError, exit_with_error};
+use crate::git::find_repository;
+use crate::git::refs::get_authorship;
+use crate::git::repository::Repository;
+
+const STATEMENT_TYPE: &str = "https://in-toto.io/Statement/v1";
+const PREDICATE_TYPE: &str = "https://usegitai.com/attestation/ai-attribution/v1";
+
+/// An in-toto statement about a commit, with its AI attribution as the predicate
+#[derive(Debug, Serialize)]
+pub struct Statement {
+    #[serde(rename = "_type")]
+    pub statement_type: String,
+    pub subject: Vec<Subject>,
+    #[serde(rename = "predicateType")]
+    pub predicate_type: String,
+    pub predicate: AttributionPredicate,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Subject {
+    pub name: String,
+    /// in-toto digest set, keyed by `gitCommit` / `gitTree`
+    pub digest: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AttributionPredicate {
+    pub commit: String,
+    pub tree: String,
+    pub generator: Generator,
+    /// Schema version of the authorship note the attribution was read from
+    pub authorship_schema_version: String,
+    pub attribution: AttributionSummary,
+    pub agents: Vec<AgentSession>,
+    pub models: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Generator {
+    pub name: String,
+    pub version: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AttributionSummary {
+    pub added_lines: u32,
+    pub deleted_lines: u32,
+    pub ai_lines: u32,
+    pub human_lines: u32,
+    /// AI lines a human edited before committing
+    pub mixed_lines: u32,
+    /// AI lines whose attribution isn't certain
+    pub ai_low_confidence_lines: u32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AgentSession {
+    /// Prompt id, as in the authorship note
+    pub prompt: String,
+    pub tool: String,
+    pub model: String,
+    pub accepted_lines: u32,
+    pub overridden_lines: u32,
+    /// SHA-256 of the session's transcript as stored in the note, so it can be checked against
+    /// the note without being published. None when the note has no transcript.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transcript_sha256: Option<String>,
+}
+
+pub fn handle_attest(args: &[String]) {
+    let mut commit = None;
+    for arg in args {
+        match arg.as_str() {
+            arg if commit.is_none() && !arg.starts_with('-') => commit = Some(arg.to_string()),
+            arg => {
+                eprintln!("Error: unexpected argument '{}'", arg);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let repo = match find_repository(&Vec::<String>::new()) {
+        Ok(repo) => repo,
+        Err(e) => {
+            exit_with_error("Failed to find repository", &e);
+        }
+    };
+
+    let statement = match attest_commit(&repo, commit.as_deref().unwrap_or("HEAD")) {
+        Ok(statement) => statement,
+        Err(e) => {
+            exit_with_error("Failed to attest commit", &e);
+        }
+    };
+    match serde_json::to_string_pretty(&statement) {
+        Ok(json) => println!("{}", json),
+        Err(e) => exit_with_error("Failed to serialize attestation", &GitAiError::from(e)),
+    }
+}
+
+/// Builds the attestation of `rev`, which must have an authorship note: a commit without one
+/// isn't known to be human-written, so it isn't attested as such
+pub fn attest_commit(repo: &Repository, rev: &str) -> Result<Statement, GitAiError> {
+    let commit_sha = repo.revparse_single(rev)?.peel_to_commit()?.id();
+    let tree_sha = repo.find_commit(commit_sha.clone())?.tree()?.id();
+    let authorship_log = get_authorship(repo, &commit_sha).ok_or_else(|| {
+        GitAiError::Generic(format!("No authorship data found for {}", commit_sha))
+    })?;
+    let stats = stats_for_commit_stats(repo, &commit_sha, &commit_sha, false)?;
+
+    let agents: Vec<AgentSession> = authorship_log
+        .metadata
+        .prompts
+        .iter()
+        .map(|(prompt, record)| AgentSession {
+            prompt: prompt.clone(),
+            tool: record.agent_id.tool.clone(),
+            model: record.agent_id.model.clone(),
+            accepted_lines: record.accepted_lines,
+            overridden_lines: record.overriden_lines,
+            transcript_sha256: (!record.messages.is_empty())
+                .then(|| serde_json::to_vec(&record.messages).ok())
+                .flatten()
+                .map(|transcript| format!("{:x}", Sha256::digest(&transcript))),
+        })
+        .collect();
+    let models: BTreeSet<String> = agents.iter().map(|agent| agent.model.clone()).collect();
+
+    Ok(Statement {
+        statement_type: STATEMENT_TYPE.to_string(),
+        subject: vec![
+            Subject {
+                name: "commit".to_string(),
+                digest: [("gitCommit".to_string(), commit_sha.clone())].into(),
+            },
+            Subject {
+                name: "tree".to_string(),
+                digest: [("gitTree".to_string(), tree_sha.clone())].into(),
+            },
+        ],
+        predicate_type: PREDICATE_TYPE.to_string(),
+        predicate: AttributionPredicate {
+            commit: commit_sha,
+            tree: tree_sha,
+            generator: Generator {
+                name: "git-ai".to_string(),
+                version: env!("CARGO_PKG_VERSION").to_string(),
+            },
+            authorship_schema_version: authorship_log.metadata.schema_version.clone(),
+            attribution: AttributionSummary {
+                added_lines: stats.git_diff_added_lines,
+                deleted_lines: stats.git_diff_deleted_lines,
+                ai_lines: stats.ai_additions,
+                human_lines: stats.human_additions,
+                mixed_lines: stats.mixed_additions,
+                ai_low_confidence_lines: stats.ai_low_confidence,
+            },
+            agents,
+            models: models.into_iter().collect(),
+        },
+    })
+}
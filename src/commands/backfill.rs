@@ -0,0 +1,65 @@
+use crate::authorship::backfill::backfill;
+use crate::error::exit_with_error;
+use crate::git::find_repository;
+
+pub fn handle_backfill(args: &[String]) {
+    let mut since = None;
+    let mut dry_run = false;
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--since" if i + 1 < args.len() => {
+                since = Some(args[i + 1].clone());
+                i += 1;
+            }
+            "--dry-run" => dry_run = true,
+            arg => {
+                eprintln!("Error: unexpected argument '{}'", arg);
+                std::process::exit(1);
+            }
+        }
+        i += 1;
+    }
+
+    let repo = match find_repository(&Vec::<String>::new()) {
+        Ok(repo) => repo,
+        Err(e) => {
+            exit_with_error("Failed to find repository", &e);
+        }
+    };
+
+    let report = match backfill(&repo, since.as_deref(), dry_run) {
+        Ok(report) => report,
+        Err(e) => {
+            exit_with_error("Failed to backfill", &e);
+        }
+    };
+
+    for commit in &report.backfilled {
+        println!(
+            "{} {}: {} AI {}, {}% confidence ({})",
+            &commit.sha[..7],
+            commit.inference.tool,
+            commit.ai_lines,
+            if commit.ai_lines == 1 {
+                "line"
+            } else {
+                "lines"
+            },
+            commit.inference.confidence,
+            commit.inference.reason
+        );
+    }
+    println!(
+        "{} {} {} ({} already had notes, {} not recognized)",
+        if dry_run { "Would infer" } else { "Inferred" },
+        report.backfilled.len(),
+        if report.backfilled.len() == 1 {
+            "commit"
+        } else {
+            "commits"
+        },
+        report.already_attributed,
+        report.unmatched
+    );
+}
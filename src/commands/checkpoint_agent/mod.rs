@@ -1,2 +0,0 @@
-pub mod agent_presets;
-pub mod agent_v1_preset;
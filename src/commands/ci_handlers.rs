@@ -1,5 +1,8 @@
+use crate::authorship::commit_policy::forbidden_attestations;
+use crate::authorship::review::review_range;
 use crate::ci::ci_context::{CiContext, CiEvent};
 use crate::ci::github::{get_github_ci_context, install_github_ci_workflow};
+use crate::error::{GitAiError, exit_with_error};
 use crate::git::repository::find_repository_in_path;
 use crate::utils::debug_log;
 
@@ -15,6 +18,9 @@ pub fn handle_ci(args: &[String]) {
         "local" => {
             handle_ci_local(&args[1..]);
         }
+        "check" => {
+            handle_ci_check(&args[1..]);
+        }
         _ => {
             eprintln!("Unknown ci subcommand: {}", args[0]);
             print_ci_help_and_exit();
@@ -34,19 +40,16 @@ fn handle_ci_github(args: &[String]) {
                 Ok(Some(ci_context)) => {
                     debug_log(&format!("GitHub CI context: {:?}", ci_context));
                     if let Err(e) = ci_context.run() {
-                        eprintln!("Error running GitHub CI context: {}", e);
-                        std::process::exit(1);
+                        exit_with_error("Error running GitHub CI context", &e);
                     }
                     if let Err(e) = ci_context.teardown() {
-                        eprintln!("Error tearing down GitHub CI context: {}", e);
-                        std::process::exit(1);
+                        exit_with_error("Error tearing down GitHub CI context", &e);
                     }
                     debug_log("GitHub CI context teared down");
                     std::process::exit(0);
                 }
                 Err(e) => {
-                    eprintln!("Failed to get GitHub CI context: {}", e);
-                    std::process::exit(1);
+                    exit_with_error("Failed to get GitHub CI context", &e);
                 }
                 Ok(None) => {
                     eprintln!("No GitHub CI context found");
@@ -60,8 +63,7 @@ fn handle_ci_github(args: &[String]) {
                 std::process::exit(0);
             }
             Err(e) => {
-                eprintln!("Failed to install GitHub CI workflow: {}", e);
-                std::process::exit(1);
+                exit_with_error("Failed to install GitHub CI workflow", &e);
             }
         },
         other => {
@@ -71,6 +73,84 @@ fn handle_ci_github(args: &[String]) {
     }
 }
 
+/// `git-ai ci check [<branch>|<base>..<head>]`: fails when the commits of a branch or PR have
+/// AI attestations in paths `.gitai` forbids AI authorship in
+fn handle_ci_check(args: &[String]) {
+    let mut json = false;
+    let mut base = None;
+    let mut spec = None;
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--json" => json = true,
+            "--base" if i + 1 >= args.len() => {
+                eprintln!("Missing value for flag --base");
+                std::process::exit(1);
+            }
+            "--base" => {
+                base = Some(args[i + 1].clone());
+                i += 1;
+            }
+            arg if spec.is_none() && !arg.starts_with('-') => spec = Some(arg.to_string()),
+            arg => {
+                eprintln!("Unexpected argument: {}", arg);
+                print_ci_help_and_exit();
+            }
+        }
+        i += 1;
+    }
+
+    let repo = match find_repository_in_path(".") {
+        Ok(r) => r,
+        Err(e) => {
+            exit_with_error("Failed to open repository in current directory", &e);
+        }
+    };
+    let (base, head) = match review_range(&repo, spec.as_deref(), base.as_deref()) {
+        Ok(range) => range,
+        Err(e) => {
+            exit_with_error("Failed to resolve the range to check", &e);
+        }
+    };
+    let found = match forbidden_attestations(&repo, &base, &head) {
+        Ok(found) => found,
+        Err(e) => {
+            exit_with_error("Failed to check forbidden paths", &e);
+        }
+    };
+
+    if json {
+        match serde_json::to_string(&found) {
+            Ok(json) => println!("{}", json),
+            Err(e) => exit_with_error("Failed to serialize results", &GitAiError::from(e)),
+        }
+    } else if found.is_empty() {
+        println!(
+            "No AI authorship in forbidden paths in {}..{}",
+            &base[..7],
+            &head[..7]
+        );
+    } else {
+        eprintln!("AI authorship in paths forbidden by ai.forbiddenPath in .gitai:");
+        for attestation in &found {
+            eprintln!(
+                "  {}  {} ({} AI {})",
+                &attestation.commit[..7],
+                attestation.file,
+                attestation.lines,
+                if attestation.lines == 1 {
+                    "line"
+                } else {
+                    "lines"
+                }
+            );
+        }
+    }
+    if !found.is_empty() {
+        std::process::exit(1);
+    }
+}
+
 fn handle_ci_local(args: &[String]) {
     if args.is_empty() {
         print_ci_local_help_and_exit();
@@ -100,8 +180,7 @@ fn handle_ci_local(args: &[String]) {
     let repo = match find_repository_in_path(".") {
         Ok(r) => r,
         Err(e) => {
-            eprintln!("Failed to open repository in current directory: {}", e);
-            std::process::exit(1);
+            exit_with_error("Failed to open repository in current directory", &e);
         }
     };
 
@@ -164,8 +243,7 @@ fn handle_ci_local(args: &[String]) {
 
             debug_log(&format!("Local CI context: {:?}", ctx));
             if let Err(e) = ctx.run() {
-                eprintln!("Error running local CI: {}", e);
-                std::process::exit(1);
+                exit_with_error("Error running local CI", &e);
             }
 
             println!("Local CI (merge) completed successfully");
@@ -187,6 +265,10 @@ fn print_ci_help_and_exit() -> ! {
     eprintln!("  github           GitHub CI");
     eprintln!("    run            Run GitHub CI in current repo");
     eprintln!("    install        Install/update workflow in current repo");
+    eprintln!("  check            Fail if a branch has AI authorship in forbidden paths");
+    eprintln!(
+        "                   Usage: git-ai ci check [<branch>|<base>..<head>] [--base <rev>] [--json]"
+    );
     eprintln!("  local            Run CI locally by event name and flags");
     eprintln!("                   Usage: git-ai ci local <event> [flags]");
     eprintln!("                   Events:");
@@ -0,0 +1,151 @@
+use crate::authorship::compare::{BranchComparison, FileComposition, compare_branches};
+use crate::error::exit_with_error;
+use crate::git::find_repository;
+
+/// Percentage points by which a file's AI share must differ to be listed
+const DEFAULT_THRESHOLD: f64 = 20.0;
+
+pub fn handle_compare(args: &[String]) {
+    let mut sides = Vec::new();
+    let mut threshold = DEFAULT_THRESHOLD;
+    let mut json = false;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--json" => json = true,
+            "--threshold" if i + 1 >= args.len() => {
+                eprintln!("Error: --threshold requires a value");
+                std::process::exit(1);
+            }
+            "--threshold" => {
+                threshold = match args[i + 1].parse::<f64>() {
+                    Ok(points) if (0.0..=100.0).contains(&points) => points,
+                    _ => {
+                        eprintln!(
+                            "Error: invalid threshold '{}', expected percentage points from 0 to 100",
+                            args[i + 1]
+                        );
+                        std::process::exit(1);
+                    }
+                };
+                i += 1;
+            }
+            arg if sides.len() < 2 && !arg.starts_with('-') => sides.push(arg.to_string()),
+            arg => {
+                eprintln!("Error: unexpected argument '{}'", arg);
+                std::process::exit(1);
+            }
+        }
+        i += 1;
+    }
+
+    let [a, b] = sides.as_slice() else {
+        eprintln!(
+            "Error: compare requires two branches or ranges, e.g. git-ai compare agent by-hand"
+        );
+        std::process::exit(1);
+    };
+
+    let repo = match find_repository(&Vec::<String>::new()) {
+        Ok(repo) => repo,
+        Err(e) => {
+            exit_with_error("Failed to find repository", &e);
+        }
+    };
+
+    let comparison = match compare_branches(&repo, a, b, threshold) {
+        Ok(comparison) => comparison,
+        Err(e) => {
+            exit_with_error("Compare failed", &e);
+        }
+    };
+
+    if json {
+        match serde_json::to_string(&comparison) {
+            Ok(json) => println!("{}", json),
+            Err(e) => {
+                eprintln!("Failed to serialize comparison: {}", e);
+                std::process::exit(1);
+            }
+        }
+    } else {
+        print_comparison(&comparison, threshold);
+    }
+}
+
+fn print_comparison(comparison: &BranchComparison, threshold: f64) {
+    let (a, b) = (&comparison.a, &comparison.b);
+    if a.base == b.base {
+        println!(
+            "Comparing {} and {} since {}",
+            a.spec,
+            b.spec,
+            &a.base[..a.base.len().min(8)]
+        );
+    } else {
+        println!("Comparing {} and {}", a.spec, b.spec);
+    }
+    println!();
+
+    let width = a.spec.len().max(b.spec.len()).max(16);
+    println!("{:<12} {:>width$} {:>width$}", "", a.spec, b.spec);
+    println!(
+        "{:<12} {:>width$} {:>width$}",
+        "commits", a.commits, b.commits
+    );
+    println!(
+        "{:<12} {:>width$} {:>width$}",
+        "added lines", a.added_lines, b.added_lines
+    );
+    println!(
+        "{:<12} {:>width$} {:>width$}",
+        "AI lines",
+        format!("{} ({:.1}%)", a.ai_lines, a.ai_percent()),
+        format!("{} ({:.1}%)", b.ai_lines, b.ai_percent())
+    );
+    println!();
+
+    if comparison.differences.is_empty() {
+        println!(
+            "No file's AI share differs by {:.0} points or more",
+            threshold
+        );
+        return;
+    }
+    println!(
+        "Files whose AI share differs by {:.0} points or more:",
+        threshold
+    );
+    let path_width = comparison
+        .differences
+        .iter()
+        .map(|d| d.path.len())
+        .max()
+        .unwrap_or(0)
+        .clamp(4, 60);
+    println!(
+        "  {:<path_width$} {:>width$} {:>width$}",
+        "file", a.spec, b.spec
+    );
+    for difference in &comparison.differences {
+        println!(
+            "  {:<path_width$} {:>width$} {:>width$}",
+            difference.path,
+            side(difference.a),
+            side(difference.b)
+        );
+    }
+}
+
+fn side(file: Option<FileComposition>) -> String {
+    match file {
+        Some(file) => format!(
+            "{}/{} {:>5.1}%",
+            file.ai_lines,
+            file.added_lines,
+            file.ai_percent()
+        ),
+        None => "unchanged".to_string(),
+    }
+}
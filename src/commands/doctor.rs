@@ -0,0 +1,238 @@
+use std::path::{Path, PathBuf};
+
+use crate::config::{Config, config_file_path, config_file_problem};
+use crate::git::find_repository;
+use crate::git::repository::Repository;
+use crate::git::sync_authorship::notes_fetch_refspec;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    Ok,
+    Warn,
+    Fail,
+}
+
+/// The outcome of one setup check
+#[derive(Debug, Clone)]
+pub struct Check {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+impl Check {
+    fn new(name: &'static str, status: CheckStatus, detail: impl Into<String>) -> Check {
+        Check {
+            name,
+            status,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// `git-ai doctor [--ci]`: checks that git-ai is set up to attribute commits, exiting non-zero
+/// when something is broken
+pub fn handle_doctor(args: &[String]) {
+    let mut ci = false;
+    for arg in args {
+        match arg.as_str() {
+            "--ci" => ci = true,
+            arg => {
+                eprintln!("Error: unexpected argument '{}'", arg);
+                std::process::exit(1);
+            }
+        }
+    }
+    if !print_checks(&run_checks(ci)) {
+        std::process::exit(1);
+    }
+}
+
+/// Prints `checks`, returning false if any failed
+pub fn print_checks(checks: &[Check]) -> bool {
+    let width = checks
+        .iter()
+        .map(|check| check.name.len())
+        .max()
+        .unwrap_or(0);
+    for check in checks {
+        let status = match check.status {
+            CheckStatus::Ok => "ok  ",
+            CheckStatus::Warn => "warn",
+            CheckStatus::Fail => "FAIL",
+        };
+        println!(
+            "{}  {:<width$}  {}",
+            status,
+            check.name,
+            check.detail,
+            width = width
+        );
+    }
+    let failed = checks
+        .iter()
+        .filter(|check| check.status == CheckStatus::Fail)
+        .count();
+    if failed > 0 {
+        println!(
+            "{} check{} failed",
+            failed,
+            if failed == 1 { "" } else { "s" }
+        );
+    }
+    failed == 0
+}
+
+/// Checks the config file, the real git, and the repository the command runs in, if any. On
+/// CI, commits aren't made through the `git` proxy, so it isn't checked for.
+pub fn run_checks(ci: bool) -> Vec<Check> {
+    let mut checks = vec![check_config(), check_git()];
+    if !ci {
+        checks.push(check_git_proxy());
+    }
+    if let Ok(repo) = find_repository(&Vec::<String>::new()) {
+        checks.push(check_repository(&repo));
+        checks.push(check_notes_fetch(&repo));
+    }
+    checks
+}
+
+fn check_config() -> Check {
+    let path = config_file_path()
+        .map(|path| path.display().to_string())
+        .unwrap_or_else(|| "~/.git-ai/config.json".to_string());
+    match config_file_problem() {
+        Some(problem) => Check::new(
+            "config",
+            CheckStatus::Fail,
+            format!("{} (ignored, so the defaults apply)", problem),
+        ),
+        None if Path::new(&path).exists() => Check::new("config", CheckStatus::Ok, path),
+        None => Check::new(
+            "config",
+            CheckStatus::Ok,
+            format!("no {}, using the defaults", path),
+        ),
+    }
+}
+
+fn check_git() -> Check {
+    let git = Config::get().git_cmd();
+    if same_file(Path::new(git), &current_exe()) {
+        return Check::new(
+            "git",
+            CheckStatus::Fail,
+            format!(
+                "git_path {} is git-ai itself; point it at the real git",
+                git
+            ),
+        );
+    }
+    match std::process::Command::new(git).arg("--version").output() {
+        Ok(output) if output.status.success() => Check::new(
+            "git",
+            CheckStatus::Ok,
+            format!(
+                "{} ({})",
+                git,
+                String::from_utf8_lossy(&output.stdout).trim()
+            ),
+        ),
+        _ => Check::new(
+            "git",
+            CheckStatus::Fail,
+            format!("{} doesn't run; set git_path in the config file", git),
+        ),
+    }
+}
+
+fn check_git_proxy() -> Check {
+    match git_on_path() {
+        Some(git) if same_file(&git, &current_exe()) => Check::new(
+            "git proxy",
+            CheckStatus::Ok,
+            format!("`git` is git-ai ({})", git.display()),
+        ),
+        Some(git) => Check::new(
+            "git proxy",
+            CheckStatus::Warn,
+            format!(
+                "`git` on PATH is {}, so commits made with it aren't attributed; put the directory \
+                 of git-ai's git shim first on PATH",
+                git.display()
+            ),
+        ),
+        None => Check::new("git proxy", CheckStatus::Warn, "no `git` on PATH"),
+    }
+}
+
+fn check_repository(repo: &Repository) -> Check {
+    let workdir = repo
+        .workdir()
+        .map(|workdir| workdir.display().to_string())
+        .unwrap_or_else(|_| "bare repository".to_string());
+    if Config::get().is_allowed_repository(&Some(repo.clone())) {
+        Check::new("repository", CheckStatus::Ok, workdir)
+    } else {
+        Check::new(
+            "repository",
+            CheckStatus::Warn,
+            format!(
+                "{} is left out by allow_repositories or exclude_repositories, so git-ai does nothing here",
+                workdir
+            ),
+        )
+    }
+}
+
+fn check_notes_fetch(repo: &Repository) -> Check {
+    let remotes: Vec<String> = repo
+        .remotes()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|remote| !remote.is_empty())
+        .collect();
+    if remotes.is_empty() {
+        return Check::new("notes fetch", CheckStatus::Ok, "no remotes");
+    }
+    let (configured, hook_only): (Vec<String>, Vec<String>) =
+        remotes.into_iter().partition(|remote| {
+            repo.git(&["config", "--get-all", &format!("remote.{}.fetch", remote)])
+                .is_ok_and(|refspecs| {
+                    refspecs
+                        .lines()
+                        .any(|refspec| refspec.trim() == notes_fetch_refspec(remote))
+                })
+        });
+    let mut detail = Vec::new();
+    if !configured.is_empty() {
+        detail.push(format!("refspec on {}", configured.join(", ")));
+    }
+    if !hook_only.is_empty() {
+        detail.push(format!(
+            "fetched by the git-ai fetch hook only from {}",
+            hook_only.join(", ")
+        ));
+    }
+    Check::new("notes fetch", CheckStatus::Ok, detail.join("; "))
+}
+
+fn current_exe() -> PathBuf {
+    std::env::current_exe().unwrap_or_default()
+}
+
+/// Whether `a` and `b` are the same file once symlinks are followed
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// The `git` a shell would run
+fn git_on_path() -> Option<PathBuf> {
+    let name = if cfg!(windows) { "git.exe" } else { "git" };
+    std::env::split_paths(&std::env::var_os("PATH")?)
+        .map(|dir| dir.join(name))
+        .find(|candidate| candidate.is_file())
+}
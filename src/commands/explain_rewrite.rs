@@ -0,0 +1,144 @@
+use crate::authorship::authorship_log::LineRange;
+use crate::authorship::rewrite_audit::{RewriteAudit, RewriteOutcome};
+use crate::commands::show::resolve_commits;
+use crate::error::exit_with_error;
+use crate::git::find_repository;
+
+pub fn handle_explain_rewrite(args: &[String]) {
+    let mut spec = None;
+    let mut json = false;
+
+    for arg in args {
+        match arg.as_str() {
+            "--json" => json = true,
+            arg if spec.is_none() && !arg.starts_with('-') => spec = Some(arg.to_string()),
+            arg => {
+                eprintln!("Error: unexpected argument '{}'", arg);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let repo = match find_repository(&Vec::<String>::new()) {
+        Ok(repo) => repo,
+        Err(e) => {
+            exit_with_error("Failed to find repository", &e);
+        }
+    };
+
+    let commits = match resolve_commits(&repo, spec.as_deref().unwrap_or("HEAD")) {
+        Ok(commits) => commits,
+        Err(e) => {
+            exit_with_error("Failed to resolve commits", &e);
+        }
+    };
+
+    if json {
+        let audits: Vec<RewriteAudit> = commits
+            .iter()
+            .filter_map(|commit| repo.storage.read_rewrite_audit(commit))
+            .collect();
+        match serde_json::to_string(&audits) {
+            Ok(json) => println!("{}", json),
+            Err(e) => {
+                eprintln!("Failed to serialize rewrite audits: {}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    for (index, commit) in commits.iter().enumerate() {
+        if index > 0 {
+            println!();
+        }
+        match repo.storage.read_rewrite_audit(commit) {
+            Some(audit) => print_audit(&audit),
+            None => println!("{}  no rewrite recorded", short_sha(commit)),
+        }
+    }
+}
+
+fn print_audit(audit: &RewriteAudit) {
+    let from: Vec<&str> = audit
+        .rewritten_from
+        .iter()
+        .map(|commit| short_sha(commit))
+        .collect();
+    if from.is_empty() {
+        println!("{}  {}", short_sha(&audit.commit), audit.operation);
+    } else {
+        println!(
+            "{}  {} of {}",
+            short_sha(&audit.commit),
+            audit.operation,
+            from.join(", ")
+        );
+    }
+
+    let mut current_file = None;
+    for entry in &audit.entries {
+        if current_file != Some(&entry.file_path) {
+            println!("  {}", entry.file_path);
+            current_file = Some(&entry.file_path);
+        }
+        let count = plural_lines(entry.line_count);
+        if entry.lines.is_empty() {
+            println!(
+                "    {:<14} {}  {}",
+                entry.outcome.as_str(),
+                entry.hash,
+                count
+            );
+        } else {
+            println!(
+                "    {:<14} {}  {}: {}",
+                entry.outcome.as_str(),
+                entry.hash,
+                count,
+                format_ranges(&entry.lines)
+            );
+        }
+    }
+
+    let totals = audit.totals();
+    let summary: Vec<String> = [
+        RewriteOutcome::Copied,
+        RewriteOutcome::Reconstructed,
+        RewriteOutcome::Restored,
+        RewriteOutcome::Dropped,
+    ]
+    .iter()
+    .map(|outcome| {
+        format!(
+            "{} {}",
+            totals.get(outcome).copied().unwrap_or(0),
+            outcome.as_str()
+        )
+    })
+    .collect();
+    println!("  {}", summary.join(", "));
+}
+
+fn format_ranges(ranges: &[LineRange]) -> String {
+    ranges
+        .iter()
+        .map(|range| match range {
+            LineRange::Single(line) => line.to_string(),
+            LineRange::Range(start, end) => format!("{}-{}", start, end),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn plural_lines(count: u32) -> String {
+    if count == 1 {
+        "1 line".to_string()
+    } else {
+        format!("{} lines", count)
+    }
+}
+
+fn short_sha(sha: &str) -> &str {
+    &sha[..sha.len().min(8)]
+}
@@ -0,0 +1,633 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::authorship::generated_files::GeneratedFileFilter;
+use crate::authorship::query::{QueriedAuthor, query_attribution};
+use crate::authorship::warehouse::{WarehouseBatch, Watermark, export_batch, table_schema};
+use crate::error::{GitAiError, exit_with_error};
+use crate::git::find_repository;
+use crate::git::path_scope::PathScope;
+use crate::git::repo_path::split_nul_fields;
+use crate::git::repository::Repository;
+
+/// Prefix of the file annotation that carries the attribution as JSON
+const ANNOTATION_PREFIX: &str = "git-ai-attribution: ";
+
+/// The `bq` binary `export --warehouse bigquery:...` runs, if not the one on the PATH
+const BQ_ENV_VAR: &str = "GIT_AI_BQ";
+/// File of a warehouse directory, and table of a BigQuery dataset, keeping each repository's
+/// watermark
+const WATERMARKS: &str = "git_ai_watermarks";
+
+/// An SPDX 2.3 document listing the files of a commit, each annotated with who wrote it
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpdxDocument {
+    pub spdx_version: String,
+    pub data_license: String,
+    #[serde(rename = "SPDXID")]
+    pub spdx_id: String,
+    pub name: String,
+    pub document_namespace: String,
+    pub creation_info: SpdxCreationInfo,
+    pub files: Vec<SpdxFile>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpdxCreationInfo {
+    pub created: String,
+    pub creators: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpdxFile {
+    pub file_name: String,
+    #[serde(rename = "SPDXID")]
+    pub spdx_id: String,
+    pub checksums: Vec<SpdxChecksum>,
+    /// The AI tools and models that wrote lines of the file, as `Tool: <tool>/<model>`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub file_contributors: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub annotations: Vec<SpdxAnnotation>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpdxChecksum {
+    pub algorithm: String,
+    pub checksum_value: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpdxAnnotation {
+    pub annotation_type: String,
+    pub annotator: String,
+    pub annotation_date: String,
+    pub comment: String,
+}
+
+/// Who wrote a file, carried as JSON in its annotation
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FileAttribution {
+    pub lines: u32,
+    pub ai_fraction: f64,
+    pub authors: Vec<AiAuthorship>,
+}
+
+/// The lines of a file one AI tool and model wrote
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AiAuthorship {
+    pub tool: String,
+    pub model: String,
+    pub lines: u32,
+    pub fraction: f64,
+    /// 1-indexed, inclusive line ranges such as `1-10` or `14`
+    pub ranges: Vec<String>,
+}
+
+pub fn handle_export(args: &[String]) {
+    let mut spdx = false;
+    let mut warehouse = None;
+    let mut repo_id = None;
+    let mut full = false;
+    let mut include_generated = false;
+    let mut commit = None;
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--spdx" => spdx = true,
+            "--warehouse" | "--repo-id" if i + 1 >= args.len() => {
+                eprintln!("Error: {} requires a value", args[i]);
+                std::process::exit(1);
+            }
+            "--warehouse" => {
+                i += 1;
+                warehouse = Some(args[i].clone());
+            }
+            "--repo-id" => {
+                i += 1;
+                repo_id = Some(args[i].clone());
+            }
+            "--full" => full = true,
+            "--include-generated" => include_generated = true,
+            arg if commit.is_none() && !arg.starts_with('-') => commit = Some(arg.to_string()),
+            arg => {
+                eprintln!("Error: unexpected argument '{}'", arg);
+                std::process::exit(1);
+            }
+        }
+        i += 1;
+    }
+    if spdx == warehouse.is_some() {
+        eprintln!(
+            "Error: export requires one format: --spdx or --warehouse <dir|bigquery:<project>.<dataset>>"
+        );
+        std::process::exit(1);
+    }
+
+    let repo = match find_repository(&Vec::<String>::new()) {
+        Ok(repo) => repo,
+        Err(e) => {
+            exit_with_error("Failed to find repository", &e);
+        }
+    };
+
+    if let Some(spec) = warehouse {
+        let target = match WarehouseTarget::parse(&spec) {
+            Ok(target) => target,
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        };
+        let repo_id = match repo_id {
+            Some(id) => id,
+            None => match repo.workdir() {
+                Ok(path) => path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_else(|| "repo".to_string()),
+                Err(e) => exit_with_error("Failed to find repository", &e),
+            },
+        };
+        let rev = commit.as_deref().unwrap_or("HEAD");
+        let batch = match export_warehouse(&repo, &target, &repo_id, rev, full) {
+            Ok(batch) => batch,
+            Err(e) => exit_with_error("Failed to export to the warehouse", &e),
+        };
+        println!(
+            "Exported {} commit{} ({} attestation rows, {} prompt rows, {} new sessions) of {} to {}",
+            batch.commits.len(),
+            if batch.commits.len() == 1 { "" } else { "s" },
+            batch.attestations.len(),
+            batch.prompts.len(),
+            batch.sessions.len(),
+            repo_id,
+            spec
+        );
+        return;
+    }
+
+    let document = match spdx_document(
+        &repo,
+        commit.as_deref().unwrap_or("HEAD"),
+        include_generated,
+    ) {
+        Ok(document) => document,
+        Err(e) => {
+            exit_with_error("Failed to export attribution", &e);
+        }
+    };
+    match serde_json::to_string_pretty(&document) {
+        Ok(json) => println!("{}", json),
+        Err(e) => exit_with_error("Failed to serialize export", &GitAiError::from(e)),
+    }
+}
+
+/// The SPDX document of the files at `rev`. It's dated with the commit rather than the export, so
+/// exporting the same commit again gives the same document.
+pub fn spdx_document(
+    repo: &Repository,
+    rev: &str,
+    include_generated: bool,
+) -> Result<SpdxDocument, GitAiError> {
+    let commit = repo.revparse_single(rev)?.peel_to_commit()?;
+    let commit_sha = commit.id();
+    let created = chrono::DateTime::from_timestamp(commit.committer()?.when().seconds(), 0)
+        .unwrap_or_default()
+        .format("%Y-%m-%dT%H:%M:%SZ")
+        .to_string();
+    let creator = format!("Tool: git-ai-{}", env!("CARGO_PKG_VERSION"));
+    let tree = commit.tree()?;
+
+    let mut paths: Vec<String> =
+        split_nul_fields(&repo.git_bytes(&["ls-tree", "-r", "-z", "--name-only", &commit_sha])?);
+    paths.retain(|path| !path.is_empty());
+    if !include_generated {
+        let filter = GeneratedFileFilter::for_paths(repo, paths.iter()).unwrap_or_default();
+        paths.retain(|path| !filter.is_generated(path));
+    }
+    let scope = PathScope::for_repo(repo);
+    paths.retain(|path| scope.contains(path));
+
+    let mut files = Vec::new();
+    for (index, path) in paths.iter().enumerate() {
+        let content = repo
+            .find_blob(tree.get_path(std::path::Path::new(path))?.id())?
+            .content()?;
+        let mut file = SpdxFile {
+            file_name: format!("./{}", path),
+            spdx_id: format!("SPDXRef-File-{}", index + 1),
+            checksums: vec![SpdxChecksum {
+                algorithm: "SHA256".to_string(),
+                checksum_value: format!("{:x}", Sha256::digest(&content)),
+            }],
+            file_contributors: Vec::new(),
+            comment: None,
+            annotations: Vec::new(),
+        };
+
+        // Binary files have no lines to attribute
+        if !content.contains(&0) && !content.is_empty() {
+            let attribution = file_attribution(repo, path, &commit_sha)?;
+            file.file_contributors = attribution
+                .authors
+                .iter()
+                .map(|author| format!("Tool: {}/{}", author.tool, author.model))
+                .collect();
+            file.comment = Some(describe(&attribution));
+            file.annotations.push(SpdxAnnotation {
+                annotation_type: "OTHER".to_string(),
+                annotator: creator.clone(),
+                annotation_date: created.clone(),
+                comment: format!(
+                    "{}{}",
+                    ANNOTATION_PREFIX,
+                    serde_json::to_string(&attribution)?
+                ),
+            });
+        }
+        files.push(file);
+    }
+
+    Ok(SpdxDocument {
+        spdx_version: "SPDX-2.3".to_string(),
+        data_license: "CC0-1.0".to_string(),
+        spdx_id: "SPDXRef-DOCUMENT".to_string(),
+        name: format!("git-ai attribution of {}", commit_sha),
+        document_namespace: format!("https://spdx.org/spdxdocs/git-ai-{}", commit_sha),
+        creation_info: SpdxCreationInfo {
+            created,
+            creators: vec![creator],
+        },
+        files,
+    })
+}
+
+/// Where `export --warehouse` writes its tables
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum WarehouseTarget {
+    /// `<table>.jsonl` files, appended to, with each table's BigQuery schema and the watermarks
+    /// beside them
+    Directory(PathBuf),
+    /// Tables of a BigQuery dataset, loaded with the `bq` CLI, which must be authenticated
+    BigQuery { project: String, dataset: String },
+}
+
+impl WarehouseTarget {
+    /// `bigquery:<project>.<dataset>`, or else a directory
+    fn parse(spec: &str) -> Result<Self, String> {
+        let Some(dataset) = spec.strip_prefix("bigquery:") else {
+            return Ok(WarehouseTarget::Directory(PathBuf::from(spec)));
+        };
+        match dataset.split_once('.') {
+            Some((project, dataset))
+                if !project.is_empty() && !dataset.is_empty() && !dataset.contains('.') =>
+            {
+                Ok(WarehouseTarget::BigQuery {
+                    project: project.to_string(),
+                    dataset: dataset.to_string(),
+                })
+            }
+            _ => Err(format!(
+                "Invalid BigQuery dataset '{}', expected bigquery:<project>.<dataset>",
+                spec
+            )),
+        }
+    }
+}
+
+/// Exports the commits `rev` reaches that the target's watermark for `repo_id` doesn't, or
+/// all of them with `full`, replacing what was exported of the repository before. The tables
+/// are written before the watermark, so a run that fails part-way exports the same commits
+/// again next time.
+fn export_warehouse(
+    repo: &Repository,
+    target: &WarehouseTarget,
+    repo_id: &str,
+    rev: &str,
+    full: bool,
+) -> Result<WarehouseBatch, GitAiError> {
+    let previous = if full {
+        Watermark::default()
+    } else {
+        match target {
+            WarehouseTarget::Directory(dir) => directory_watermarks(dir)?
+                .remove(repo_id)
+                .unwrap_or_default(),
+            WarehouseTarget::BigQuery { project, dataset } => {
+                bigquery_watermark(project, dataset, repo_id)?
+            }
+        }
+    };
+    let batch = export_batch(repo, repo_id, rev, &previous)?;
+    match target {
+        WarehouseTarget::Directory(dir) => write_directory(dir, repo_id, &batch, full)?,
+        WarehouseTarget::BigQuery { project, dataset } => {
+            load_bigquery(project, dataset, repo_id, &batch, full)?
+        }
+    }
+    Ok(batch)
+}
+
+fn directory_watermarks(dir: &Path) -> Result<BTreeMap<String, Watermark>, GitAiError> {
+    match std::fs::read_to_string(dir.join(format!("{}.json", WATERMARKS))) {
+        Ok(content) => Ok(serde_json::from_str(&content)?),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn write_directory(
+    dir: &Path,
+    repo_id: &str,
+    batch: &WarehouseBatch,
+    full: bool,
+) -> Result<(), GitAiError> {
+    std::fs::create_dir_all(dir)?;
+    for (table, rows) in batch.tables()? {
+        let path = dir.join(format!("{}.jsonl", table));
+        let mut content = std::fs::read_to_string(&path).unwrap_or_default();
+        if full {
+            // Other repositories exported to the same directory keep their rows
+            content = content
+                .lines()
+                .filter(|line| {
+                    serde_json::from_str::<serde_json::Value>(line)
+                        .map_or(true, |row| row["repo"] != repo_id)
+                })
+                .map(|line| format!("{}\n", line))
+                .collect();
+        }
+        content.push_str(&rows);
+        std::fs::write(&path, content)?;
+        std::fs::write(
+            dir.join(format!("{}.schema.json", table)),
+            serde_json::to_string_pretty(&table_schema(table))?,
+        )?;
+    }
+
+    let mut watermarks = directory_watermarks(dir)?;
+    watermarks.insert(repo_id.to_string(), batch.watermark.clone());
+    let path = dir.join(format!("{}.json", WATERMARKS));
+    let tmp_path = dir.join(format!("{}.json.tmp", WATERMARKS));
+    std::fs::write(&tmp_path, serde_json::to_string_pretty(&watermarks)?)?;
+    std::fs::rename(&tmp_path, &path)?;
+    Ok(())
+}
+
+/// Runs `bq` with `args`, returning its standard output
+fn bq(args: &[&str]) -> Result<String, GitAiError> {
+    let program = std::env::var(BQ_ENV_VAR).unwrap_or_else(|_| "bq".to_string());
+    let output = Command::new(&program).args(args).output().map_err(|e| {
+        GitAiError::Generic(format!(
+            "Failed to run '{}' (install the Google Cloud CLI or set {}): {}",
+            program, BQ_ENV_VAR, e
+        ))
+    })?;
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    if !output.status.success() {
+        return Err(GitAiError::Generic(format!(
+            "bq {} failed: {}{}",
+            args.first().unwrap_or(&""),
+            String::from_utf8_lossy(&output.stderr).trim(),
+            stdout.trim()
+        )));
+    }
+    Ok(stdout)
+}
+
+/// Whether `bq` failed because a table doesn't exist yet, as on the first export
+fn is_not_found(error: &GitAiError) -> bool {
+    error.to_string().contains("Not found")
+}
+
+fn bigquery_watermark(
+    project: &str,
+    dataset: &str,
+    repo_id: &str,
+) -> Result<Watermark, GitAiError> {
+    let sql = format!(
+        "SELECT state FROM `{}.{}.{}` WHERE repo = @repo ORDER BY exported_at DESC LIMIT 1",
+        project, dataset, WATERMARKS
+    );
+    let output = match bq(&[
+        "--format=json",
+        &format!("--project_id={}", project),
+        "query",
+        "--nouse_legacy_sql",
+        &format!("--parameter=repo::{}", repo_id),
+        &sql,
+    ]) {
+        Ok(output) => output,
+        Err(e) if is_not_found(&e) => return Ok(Watermark::default()),
+        Err(e) => return Err(e),
+    };
+    let rows: Vec<serde_json::Value> = serde_json::from_str(output.trim()).unwrap_or_default();
+    match rows.first().and_then(|row| row["state"].as_str()) {
+        Some(state) => Ok(serde_json::from_str(state)?),
+        None => Ok(Watermark::default()),
+    }
+}
+
+fn load_bigquery(
+    project: &str,
+    dataset: &str,
+    repo_id: &str,
+    batch: &WarehouseBatch,
+    full: bool,
+) -> Result<(), GitAiError> {
+    let project_arg = format!("--project_id={}", project);
+    let dir = std::env::temp_dir().join(format!("git-ai-warehouse-{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+    let result = (|| -> Result<(), GitAiError> {
+        let mut tables = batch.tables()?;
+        tables.push((
+            WATERMARKS,
+            format!(
+                "{}\n",
+                serde_json::json!({
+                    "repo": repo_id,
+                    "state": serde_json::to_string(&batch.watermark)?,
+                    "exported_at": batch.watermark.exported_at,
+                })
+            ),
+        ));
+        for (table, rows) in tables {
+            if full && table != WATERMARKS {
+                let sql = format!(
+                    "DELETE FROM `{}.{}.{}` WHERE repo = @repo",
+                    project, dataset, table
+                );
+                match bq(&[
+                    &project_arg,
+                    "query",
+                    "--nouse_legacy_sql",
+                    &format!("--parameter=repo::{}", repo_id),
+                    &sql,
+                ]) {
+                    Err(e) if !is_not_found(&e) => return Err(e),
+                    _ => {}
+                }
+            }
+            if rows.is_empty() {
+                continue;
+            }
+            let data = dir.join(format!("{}.jsonl", table));
+            let schema = dir.join(format!("{}.schema.json", table));
+            std::fs::write(&data, rows)?;
+            std::fs::write(&schema, serde_json::to_string(&bigquery_schema(table))?)?;
+            bq(&[
+                &project_arg,
+                "load",
+                "--source_format=NEWLINE_DELIMITED_JSON",
+                &format!("{}.{}", dataset, table),
+                &data.to_string_lossy(),
+                &schema.to_string_lossy(),
+            ])?;
+        }
+        Ok(())
+    })();
+    let _ = std::fs::remove_dir_all(&dir);
+    result
+}
+
+fn bigquery_schema(table: &str) -> serde_json::Value {
+    if table == WATERMARKS {
+        return serde_json::json!([
+            { "name": "repo", "type": "STRING", "mode": "REQUIRED" },
+            { "name": "state", "type": "STRING", "mode": "REQUIRED" },
+            { "name": "exported_at", "type": "TIMESTAMP", "mode": "REQUIRED" },
+        ]);
+    }
+    table_schema(table)
+}
+
+/// Who wrote each line of `path` at `commit`, grouped by AI tool and model
+fn file_attribution(
+    repo: &Repository,
+    path: &str,
+    commit: &str,
+) -> Result<FileAttribution, GitAiError> {
+    let lines = query_attribution(repo, path, Some(commit), None)?;
+    let total = lines.len() as u32;
+
+    let mut ai_lines: BTreeMap<(String, String), Vec<u32>> = BTreeMap::new();
+    for line in &lines {
+        if let QueriedAuthor::Ai { tool, model, .. } = &line.author {
+            ai_lines
+                .entry((tool.clone(), model.clone()))
+                .or_default()
+                .push(line.line);
+        }
+    }
+
+    let fraction = |count: usize| {
+        if total == 0 {
+            0.0
+        } else {
+            // Rounded to 4 places, so the document doesn't carry float noise
+            (count as f64 / total as f64 * 10000.0).round() / 10000.0
+        }
+    };
+    let ai_total: usize = ai_lines.values().map(Vec::len).sum();
+    Ok(FileAttribution {
+        lines: total,
+        ai_fraction: fraction(ai_total),
+        authors: ai_lines
+            .into_iter()
+            .map(|((tool, model), lines)| AiAuthorship {
+                tool,
+                model,
+                lines: lines.len() as u32,
+                fraction: fraction(lines.len()),
+                ranges: line_ranges(&lines),
+            })
+            .collect(),
+    })
+}
+
+/// Sorted line numbers as compact ranges: [1, 2, 3, 7] is ["1-3", "7"]
+fn line_ranges(lines: &[u32]) -> Vec<String> {
+    let mut ranges: Vec<(u32, u32)> = Vec::new();
+    for &line in lines {
+        match ranges.last_mut() {
+            Some((_, end)) if *end + 1 == line => *end = line,
+            _ => ranges.push((line, line)),
+        }
+    }
+    ranges
+        .into_iter()
+        .map(|(start, end)| {
+            if start == end {
+                start.to_string()
+            } else {
+                format!("{}-{}", start, end)
+            }
+        })
+        .collect()
+}
+
+/// One line description for the file's SPDX comment
+fn describe(attribution: &FileAttribution) -> String {
+    if attribution.authors.is_empty() {
+        return "No AI-authored lines".to_string();
+    }
+    let authors: Vec<String> = attribution
+        .authors
+        .iter()
+        .map(|author| {
+            format!(
+                "{} {} lines {}",
+                author.tool,
+                author.model,
+                author.ranges.join(",")
+            )
+        })
+        .collect();
+    format!(
+        "AI-authored: {:.1}% ({})",
+        attribution.ai_fraction * 100.0,
+        authors.join("; ")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_warehouse_target() {
+        assert_eq!(
+            WarehouseTarget::parse("bigquery:acme-data.git_ai"),
+            Ok(WarehouseTarget::BigQuery {
+                project: "acme-data".to_string(),
+                dataset: "git_ai".to_string(),
+            })
+        );
+        assert_eq!(
+            WarehouseTarget::parse("exports/warehouse"),
+            Ok(WarehouseTarget::Directory(PathBuf::from(
+                "exports/warehouse"
+            )))
+        );
+        assert!(WarehouseTarget::parse("bigquery:acme-data").is_err());
+        assert!(WarehouseTarget::parse("bigquery:a.b.c").is_err());
+    }
+
+    #[test]
+    fn test_line_ranges() {
+        assert_eq!(line_ranges(&[1, 2, 3, 7, 9, 10]), vec!["1-3", "7", "9-10"]);
+        assert!(line_ranges(&[]).is_empty());
+    }
+}
@@ -0,0 +1,82 @@
+use crate::authorship::fsck::{FsckReport, fsck};
+use crate::error::exit_with_error;
+use crate::git::find_repository;
+
+pub fn handle_fsck(args: &[String]) {
+    let mut repair = false;
+    let mut json = false;
+
+    for arg in args {
+        match arg.as_str() {
+            "--repair" => repair = true,
+            "--json" => json = true,
+            arg => {
+                eprintln!("Error: unexpected argument '{}'", arg);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let repo = match find_repository(&Vec::<String>::new()) {
+        Ok(repo) => repo,
+        Err(e) => {
+            exit_with_error("Failed to find repository", &e);
+        }
+    };
+
+    let report = match fsck(&repo, repair) {
+        Ok(report) => report,
+        Err(e) => {
+            exit_with_error("fsck failed", &e);
+        }
+    };
+
+    if json {
+        match serde_json::to_string(&report) {
+            Ok(json) => println!("{}", json),
+            Err(e) => {
+                eprintln!("Failed to serialize fsck report: {}", e);
+                std::process::exit(1);
+            }
+        }
+    } else {
+        print_report(&report, repair);
+    }
+
+    if report.unresolved() > 0 {
+        std::process::exit(1);
+    }
+}
+
+fn print_report(report: &FsckReport, repair: bool) {
+    for finding in &report.findings {
+        let status = if finding.repaired {
+            "repaired"
+        } else {
+            "error"
+        };
+        println!(
+            "{} {}: {}",
+            status,
+            &finding.commit[..finding.commit.len().min(8)],
+            finding.issue
+        );
+    }
+
+    let unresolved = report.unresolved();
+    println!(
+        "Checked {} authorship notes: {} issues, {} repaired",
+        report.commits_checked,
+        report.findings.len(),
+        report.findings.len() - unresolved
+    );
+    if let Some(backup_ref) = &report.backup_ref {
+        println!(
+            "Original notes saved to {} (restore with `git-ai undo-notes`)",
+            backup_ref
+        );
+    }
+    if unresolved > 0 && !repair {
+        println!("Run `git-ai fsck --repair` to fix what can be fixed safely");
+    }
+}
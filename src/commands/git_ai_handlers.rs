@@ -1,28 +1,98 @@
+use crate::authorship::acceptance;
+use crate::authorship::author_class;
+use crate::authorship::file_text;
+use crate::authorship::prompters;
 use crate::authorship::range_authorship;
 use crate::authorship::stats::stats_command;
+use crate::authorship::time_to_commit;
 use crate::authorship::working_log::{AgentId, CheckpointKind};
 use crate::commands;
-use crate::commands::checkpoint_agent::agent_presets::{
-    AgentCheckpointFlags, AgentCheckpointPreset, AgentRunResult, AiTabPreset, ClaudePreset,
-    CursorPreset, GithubCopilotPreset,
+use crate::commands::checkpoint_agent::agent_presets::{AgentEditPhase, AgentRunResult};
+use crate::commands::checkpoint_agent::{
+    get_all_files_for_mock_ai, plugins, run_checkpoint_preset,
 };
-use crate::commands::checkpoint_agent::agent_v1_preset::AgentV1Preset;
 use crate::config;
+use crate::error::{ErrorFormat, exit_with_error, set_error_format};
 use crate::git::find_repository;
 use crate::git::find_repository_in_path;
-use crate::git::repository::CommitRange;
+use crate::git::jj;
+use crate::git::path_scope;
+use crate::git::repo_config::enforce_required_version;
+use crate::git::repository::{CommitRange, Repository};
 use crate::observability;
+use crate::observability::trace;
+use crate::observability::usage;
 use crate::observability::wrapper_performance_targets::log_performance_for_checkpoint;
+use std::collections::HashMap;
 use std::env;
 use std::io::IsTerminal;
 use std::io::Read;
+use std::path::Path;
 use std::time::{SystemTime, UNIX_EPOCH};
 
+/// Commands whose names go into usage counters; anything else is counted as "unknown"
+const COUNTED_COMMANDS: &[&str] = &[
+    "help",
+    "version",
+    "stats",
+    "show",
+    "attest",
+    "export",
+    "scan",
+    "import",
+    "backfill",
+    "own",
+    "review",
+    "fsck",
+    "undo-notes",
+    "notes",
+    "query",
+    "timeline",
+    "compare",
+    "replay",
+    "serve",
+    "checkpoint",
+    "blame",
+    "git-path",
+    "install-hooks",
+    "prepare-commit-msg",
+    "squash-authorship",
+    "restore-authorship",
+    "ci",
+    "upgrade",
+    "telemetry",
+    "tui",
+    "trace",
+    "pair",
+    "status",
+    "workspace",
+    "init",
+    "doctor",
+    "clone",
+    "notify",
+];
+
 pub fn handle_git_ai(args: &[String]) {
+    let args = take_global_options(args);
+    // `trace` records what the command it runs does through the same subscriber
+    if args.first().is_some_and(|arg| arg == "trace") {
+        trace::request_recording();
+    }
+    trace::init();
     if args.is_empty() {
         print_help();
         return;
     }
+    // flush-logs runs in the background on its own, so it isn't counted
+    if args[0] != "flush-logs" {
+        usage::record_command(if COUNTED_COMMANDS.contains(&args[0].as_str()) {
+            &args[0]
+        } else {
+            "unknown"
+        });
+    }
+
+    let _command = tracing::info_span!("git-ai", command = %args[0]).entered();
 
     let current_dir = env::current_dir().unwrap().to_string_lossy().to_string();
     let repository_option = find_repository_in_path(&current_dir).ok();
@@ -32,6 +102,18 @@ pub fn handle_git_ai(args: &[String]) {
         observability::set_repo_context(repo);
     }
 
+    // Commands that manage git-ai itself work whatever version the repository requires
+    let manages_install = matches!(
+        args[0].as_str(),
+        "help" | "--help" | "-h" | "version" | "--version" | "-v" | "upgrade" | "git-path"
+    );
+    if !manages_install
+        && let Some(repo) = repository_option.as_ref()
+        && let Some(err) = enforce_required_version(repo)
+    {
+        exit_with_error("git-ai is too old for this repository", &err);
+    }
+
     let config = config::Config::get();
 
     let allowed_repository = config.is_allowed_repository(&repository_option);
@@ -54,6 +136,54 @@ pub fn handle_git_ai(args: &[String]) {
         "show" => {
             commands::show::handle_show(&args[1..]);
         }
+        "attest" => {
+            commands::attest::handle_attest(&args[1..]);
+        }
+        "export" => {
+            commands::export::handle_export(&args[1..]);
+        }
+        "scan" => {
+            commands::scan::handle_scan(&args[1..]);
+        }
+        "import" => {
+            commands::import::handle_import(&args[1..]);
+        }
+        "backfill" => {
+            commands::backfill::handle_backfill(&args[1..]);
+        }
+        "own" => {
+            commands::own::handle_own(&args[1..]);
+        }
+        "review" => {
+            commands::review::handle_review(&args[1..]);
+        }
+        "fsck" => {
+            commands::fsck::handle_fsck(&args[1..]);
+        }
+        "undo-notes" => {
+            commands::undo_notes::handle_undo_notes(&args[1..]);
+        }
+        "explain-rewrite" => {
+            commands::explain_rewrite::handle_explain_rewrite(&args[1..]);
+        }
+        "notes" => {
+            commands::notes::handle_notes(&args[1..]);
+        }
+        "query" => {
+            commands::query::handle_query(&args[1..]);
+        }
+        "timeline" => {
+            commands::timeline::handle_timeline(&args[1..]);
+        }
+        "compare" => {
+            commands::compare::handle_compare(&args[1..]);
+        }
+        "replay" => {
+            commands::replay::handle_replay(&args[1..]);
+        }
+        "serve" => {
+            commands::serve::handle_serve(&args[1..]);
+        }
         "checkpoint" => {
             if !allowed_repository {
                 eprintln!(
@@ -73,13 +203,18 @@ pub fn handle_git_ai(args: &[String]) {
         }
         "install-hooks" => {
             if let Err(e) = commands::install_hooks::run(&args[1..]) {
-                eprintln!("Install hooks failed: {}", e);
-                std::process::exit(1);
+                exit_with_error("Install hooks failed", &e);
             }
         }
+        "prepare-commit-msg" => {
+            commands::prepare_commit_msg::handle_prepare_commit_msg(&args[1..]);
+        }
         "squash-authorship" => {
             commands::squash_authorship::handle_squash_authorship(&args[1..]);
         }
+        "restore-authorship" => {
+            commands::restore_authorship::handle_restore_authorship(&args[1..]);
+        }
         "ci" => {
             commands::ci_handlers::handle_ci(&args[1..]);
         }
@@ -89,6 +224,38 @@ pub fn handle_git_ai(args: &[String]) {
         "flush-logs" => {
             commands::flush_logs::handle_flush_logs(&args[1..]);
         }
+        "telemetry" => {
+            commands::telemetry::handle_telemetry(&args[1..]);
+        }
+        "tui" => {
+            commands::tui::handle_tui(&args[1..]);
+        }
+        "notify" => {
+            commands::notify::handle_notify(&args[1..]);
+        }
+        "trace" => {
+            commands::trace::handle_trace(&args[1..]);
+        }
+        "pair" => {
+            commands::pair::handle_pair(&args[1..]);
+        }
+        "status" => {
+            commands::status::handle_status(&args[1..]);
+        }
+        "workspace" => {
+            commands::workspace::handle_workspace(&args[1..]);
+        }
+        "init" => {
+            commands::init::handle_init(&args[1..]);
+        }
+        "doctor" => {
+            commands::doctor::handle_doctor(&args[1..]);
+        }
+        "clone" => {
+            let mut clone_args = vec!["clone".to_string()];
+            clone_args.extend_from_slice(&args[1..]);
+            commands::git_handlers::handle_git(&clone_args);
+        }
         _ => {
             println!("Unknown git-ai command: {}", args[0]);
             std::process::exit(1);
@@ -96,36 +263,337 @@ pub fn handle_git_ai(args: &[String]) {
     }
 }
 
+/// Applies the global options that come before the command, in any order, and returns the rest
+fn take_global_options(mut args: &[String]) -> &[String] {
+    loop {
+        let rest = take_timing(take_path_scope(take_error_format(args)));
+        if rest.len() == args.len() {
+            return rest;
+        }
+        args = rest;
+    }
+}
+
+/// Applies a leading `--path <spec>` (or `--path=<spec>`), which limits the command to part of
+/// the repository, and returns the remaining arguments
+fn take_path_scope(args: &[String]) -> &[String] {
+    let (spec, rest) = match args.first().map(String::as_str) {
+        Some("--path") => match args.get(1) {
+            Some(spec) => (spec.as_str(), &args[2..]),
+            None => {
+                eprintln!("Error: --path requires a value");
+                std::process::exit(1);
+            }
+        },
+        Some(arg) if arg.starts_with("--path=") => (&arg["--path=".len()..], &args[1..]),
+        _ => return args,
+    };
+    if let Err(e) = path_scope::add_cli_pathspec(spec) {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+    rest
+}
+
+/// Applies a leading `--timing`, which prints how long each phase took when the command exits,
+/// and returns the remaining arguments
+fn take_timing(args: &[String]) -> &[String] {
+    match args.first().map(String::as_str) {
+        Some("--timing") => {
+            trace::request_timing();
+            &args[1..]
+        }
+        _ => args,
+    }
+}
+
+/// Applies a leading `--error-format <text|json>` (or `--error-format=<...>`) and returns the
+/// remaining arguments
+fn take_error_format(args: &[String]) -> &[String] {
+    let (value, rest) = match args.first().map(String::as_str) {
+        Some("--error-format") => match args.get(1) {
+            Some(value) => (value.as_str(), &args[2..]),
+            None => {
+                eprintln!("Error: --error-format requires a value");
+                std::process::exit(1);
+            }
+        },
+        Some(arg) if arg.starts_with("--error-format=") => {
+            (&arg["--error-format=".len()..], &args[1..])
+        }
+        _ => return args,
+    };
+    match value.parse::<ErrorFormat>() {
+        Ok(format) => set_error_format(format),
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
+    rest
+}
+
 fn print_help() {
     eprintln!("git-ai - git proxy with AI authorship tracking");
     eprintln!("");
-    eprintln!("Usage: git-ai <command> [args...]");
+    eprintln!(
+        "Usage: git-ai [--error-format text|json] [--path <spec>]... [--timing] <command> [args...]"
+    );
+    eprintln!(
+        "  --error-format <fmt>   Print errors as text (default) or as JSON with a stable code"
+    );
+    eprintln!(
+        "  --path <spec>          Only work on paths matching <spec> (repeatable, overrides ai.defaultPathspec)"
+    );
+    eprintln!(
+        "  --timing               Print how long each phase of the command took when it exits"
+    );
     eprintln!("");
     eprintln!("Commands:");
     eprintln!("  checkpoint         Checkpoint working changes and attribute author");
     eprintln!("    Presets: claude, cursor, github-copilot, ai_tab, mock_ai");
+    eprintln!(
+        "    begin-agent <preset>        Before an agent's edit: credit changes so far to the human"
+    );
+    eprintln!(
+        "    end-agent <preset>          After it: credit the agent with what changed since begin-agent"
+    );
     eprintln!(
         "    --hook-input <json|stdin>   JSON payload required by presets, or 'stdin' to read from stdin"
     );
     eprintln!("    --show-working-log          Display current working log");
     eprintln!("    --reset                     Reset working log");
+    eprintln!(
+        "    --patch <file|->            Attribute exactly the lines this unified diff of the agent's edit adds"
+    );
+    eprintln!(
+        "    --content <path>=<file>     Checkpoint <path> with the contents of <file> instead of what's on disk"
+    );
+    eprintln!(
+        "    --contents-stdin            Same, for a JSON object of paths to contents read from stdin"
+    );
+    eprintln!(
+        "    --include-generated         Also checkpoint generated/vendored files (lockfiles, linguist-generated, ...)"
+    );
+    eprintln!(
+        "    --class <class> [paths...]  Credit the changes to an author class (codegen, import, unknown or author_classes)"
+    );
     eprintln!("    mock_ai [pathspecs...]      Test preset accepting optional file pathspecs");
-    eprintln!("  blame <file>       Git blame with AI authorship overlay");
+    eprintln!(
+        "      --commit-message <msg>    Record that the mock agent wrote this commit message"
+    );
+    eprintln!(
+        "      --session <id>            Session id of the mock agent (default: a new one per checkpoint)"
+    );
+    eprintln!(
+        "      --human-author <who>      Who prompted the mock agent (default: the git user)"
+    );
+    eprintln!(
+        "    <plugin>                    Agent plugin from ~/.git-ai/plugins (see plugins_dir)"
+    );
+    eprintln!("  blame <file>...    Git blame with AI authorship overlay");
+    eprintln!("    --stdin                Also blame the paths listed on stdin, one per line");
+    eprintln!(
+        "    --include-generated    With several files, also blame generated and vendored ones"
+    );
+    eprintln!(
+        "    --interleave           With several files, name the file on each line instead of grouping"
+    );
+    eprintln!("    --min-confidence <pct> Only attribute lines to AI at or above this confidence");
+    eprintln!(
+        "    --show-prompter        Name the human who prompted the agent next to each AI line"
+    );
     eprintln!("  stats [commit]     Show AI authorship statistics for a commit");
     eprintln!("    --json                 Output in JSON format");
+    eprintln!("    --include-generated    Count generated/vendored files in the stats");
+    eprintln!("    --exclude-inferred     Count commits with notes from `backfill` as human");
+    eprintln!(
+        "    --time-to-commit       Time from AI sessions' checkpoints to their commits, per agent and model"
+    );
+    eprintln!(
+        "    --acceptance           Share of AI sessions' lines committed unchanged, per session, agent and model"
+    );
+    eprintln!("    --by-prompter          AI lines per human who prompted the agents");
     eprintln!(
         "  stats-delta        Generate authorship logs for children of commits with working logs"
     );
     eprintln!("    --json                 Output created notes as JSON");
+    eprintln!("  status             Show who wrote the uncommitted changes");
+    eprintln!("    --json                 Output in JSON format");
     eprintln!("  show <rev|range>   Display authorship logs for a revision or range");
+    eprintln!(
+        "    --prompters            List each AI session with the human who prompted it instead"
+    );
+    eprintln!("  attest [commit]    Print an in-toto statement of a commit's AI attribution");
+    eprintln!("  export --spdx [commit]  Print an SPDX manifest of each file's AI-authored lines");
+    eprintln!("    --include-generated    List generated/vendored files too");
+    eprintln!(
+        "  export --warehouse <dir|bigquery:<project>.<dataset>> [rev]  Export new commits' authorship as warehouse tables"
+    );
+    eprintln!(
+        "    --repo-id <id>         Value of the tables' repo column (default: directory name)"
+    );
+    eprintln!(
+        "    --full                 Re-export the whole history instead of resuming from the watermark"
+    );
+    eprintln!(
+        "  scan --licenses [commit]  Look for license headers and copied code in AI-authored lines"
+    );
+    eprintln!("    --json                 Output in JSON format");
+    eprintln!("    --include-generated    Scan generated/vendored files too");
+    eprintln!(
+        "  import <agent> --since <date>  Backfill notes from cursor, claude-code or aider history"
+    );
+    eprintln!(
+        "    --from <path>          Read the history from here instead of the agent's default"
+    );
+    eprintln!("    --dry-run              Show what would be imported without writing notes");
+    eprintln!(
+        "  backfill           Write inferred notes for old commits made by bots or marked as AI-generated"
+    );
+    eprintln!("    --since <date>         Only look at commits made since this date");
+    eprintln!("    --dry-run              Show what would be inferred without writing notes");
+    eprintln!("  own [commit]       Report AI-authored lines per CODEOWNERS owner");
+    eprintln!("    --json                 Output in JSON format");
+    eprintln!("    --include-generated    Count generated/vendored files too");
+    eprintln!(
+        "  review list [<branch>|<base>..<head>]  List AI hunks no human has modified, riskiest first"
+    );
+    eprintln!(
+        "    --base <rev>           Compare the branch with this instead of the default branch"
+    );
+    eprintln!("    --json                 Output in JSON format");
+    eprintln!("    --include-generated    List hunks in generated/vendored files too");
+    eprintln!(
+        "  review mark <commit> <file>[:<start>[-<end>]]  Record that lines a commit added were reviewed"
+    );
+    eprintln!(
+        "    --staged               Mark uncommitted lines, for the next commit (instead of <commit>)"
+    );
+    eprintln!("    --by <user>            Reviewer to record (defaults to user.name)");
+    eprintln!("  fsck               Check authorship notes for inconsistencies");
+    eprintln!(
+        "    --repair               Fix what can be fixed safely, backing up the notes first"
+    );
+    eprintln!("    --json                 Output in JSON format");
+    eprintln!("  undo-notes [backup]  Restore authorship notes from before a rewrite or repair");
+    eprintln!("    --list                 List the available backups");
+    eprintln!(
+        "  explain-rewrite [rev|range]  Show how a rebase, cherry-pick or amend moved attribution"
+    );
+    eprintln!("    --json                 Output in JSON format");
+    eprintln!(
+        "  notes sync [remote...]  Reconcile authorship notes with remotes per remote.<name>.aiNotes"
+    );
+    eprintln!(
+        "  query <file>       Show who wrote each line, combining notes, working log and blame"
+    );
+    eprintln!(
+        "    --commit <rev>         Query the file as of a commit instead of the working directory"
+    );
+    eprintln!("    -L <start>,<end>       Only query these lines");
+    eprintln!("    --json                 Output in JSON format");
+    eprintln!("    --stream               Print one JSON object per blame hunk as it resolves");
+    eprintln!(
+        "  timeline <file>    Show how a file's AI and human lines evolved, commit by commit"
+    );
+    eprintln!("    --rev <rev>            Walk history from this revision instead of HEAD");
+    eprintln!("    --json                 Output in JSON format");
+    eprintln!("  compare <a> <b>    Contrast the AI share of two branches (or base..head ranges)");
+    eprintln!(
+        "    --threshold <points>   List files whose AI share differs by this much (default 20)"
+    );
+    eprintln!("    --json                 Output in JSON format");
+    eprintln!("  replay [repo]      Re-simulate attribution over recent history and report timing");
+    eprintln!("    --commits <n>          Number of first-parent commits to replay (default 100)");
+    eprintln!("    --json                 Output in JSON format");
+    eprintln!("  serve              Serve read-only attribution queries over HTTP");
+    eprintln!("    --http <addr>          Address to listen on (e.g. 127.0.0.1:8080)");
+    eprintln!("    --repo [<id>=]<path>   Repository to serve; repeat for several");
+    eprintln!("    --token <token>        Bearer token clients must send (or GIT_AI_SERVE_TOKEN)");
+    eprintln!(
+        "    --web                  Also serve a web UI (defaults to this repo on 127.0.0.1:8080)"
+    );
+    eprintln!(
+        "  notify <slack|teams>  Post the last week's AI authorship report to a chat channel"
+    );
+    eprintln!("    --webhook <url>        Incoming webhook to post to (or GIT_AI_NOTIFY_WEBHOOK)");
+    eprintln!("    --days <n>             Days the report covers (default 7)");
+    eprintln!("    --rev <rev>            Branch whose commits to report on (default HEAD)");
+    eprintln!("    --dry-run              Print the message instead of posting it");
+    eprintln!(
+        "    --install-workflow     Install a GitHub Actions workflow that posts it every Monday"
+    );
+    eprintln!("  tui                Browse AI attribution interactively in the terminal");
+    eprintln!(
+        "    --commits <n>          Recent commits to read sessions and trends from (default 200)"
+    );
+    eprintln!(
+        "  trace <git command...>  Run a git command, recording each attribution decision to a bundle for bug reports"
+    );
+    eprintln!(
+        "    -o, --output <file>    Where to write the bundle (default git-ai-trace-<time>.json)"
+    );
+    eprintln!(
+        "    replay <bundle>        Re-run a traced command from its recorded state and compare the notes"
+    );
+    eprintln!("  pair               Record who is pairing on agent sessions in this repository");
+    eprintln!("    show                   Show who is pairing (the default)");
+    eprintln!(
+        "    start [--driver <author>] --navigator <author>...  Start pairing; the driver defaults to you"
+    );
+    eprintln!("    stop                   Stop pairing");
+    eprintln!(
+        "  workspace          Run commands across the repositories of a workspace (.gitai-workspace, or a superproject and its submodules)"
+    );
+    eprintln!("    list                   List the workspace's repositories (the default)");
+    eprintln!("    stats                  Stats of each repository's HEAD and their total");
+    eprintln!("    verify                 Check the authorship notes of every repository");
+    eprintln!(
+        "    sync-notes             Sync authorship notes of every repository with its remotes"
+    );
+    eprintln!("    --json                 Output in JSON format (list, stats, verify)");
+    eprintln!(
+        "  init               Set up git-ai: config, agent and editor hooks, notes refspecs, then doctor"
+    );
+    eprintln!(
+        "    --minimal              Only the config and hooks, leaving the repository's git config alone"
+    );
+    eprintln!(
+        "    --ci                   For CI runners: config without update checks and notes refspecs, no hooks"
+    );
+    eprintln!("  doctor             Check that git-ai is set up to attribute commits");
+    eprintln!("    --ci                   Skip the checks that don't apply on CI runners");
+    eprintln!(
+        "  clone <git clone args>  Clone, then fetch authorship notes and configure fetching them"
+    );
     eprintln!("  install-hooks      Install git hooks for AI authorship tracking");
+    eprintln!(
+        "  prepare-commit-msg <file> [source]  Summarize the staged AI and human lines in the commit message (for a prepare-commit-msg hook)"
+    );
     eprintln!("  ci                 Continuous integration utilities");
     eprintln!("    github                 GitHub CI helpers");
     eprintln!("  squash-authorship  Generate authorship log for squashed commits");
     eprintln!(
         "    <base_branch> <new_sha> <old_sha>  Required: base branch, new commit SHA, old commit SHA"
     );
+    eprintln!(
+        "    --pr <number>         Fetch the old head from the remote's pull/merge request ref instead of <old_sha>"
+    );
     eprintln!("    --dry-run             Show what would be done without making changes");
+    eprintln!(
+        "  restore-authorship <base_branch> <squash_sha>  Write (and push) the authorship note for a PR squash-merged by the host"
+    );
+    eprintln!(
+        "    --head <sha>          Head of the merged branch (searched for in the reflog and PR and remote-tracking refs if omitted)"
+    );
+    eprintln!("    --pr <number>         Fetch the head from the remote's pull/merge request ref");
+    eprintln!("    --remote <name>       Remote to fetch from and push to (default: origin)");
+    eprintln!(
+        "    --provider <name>     github, gitlab or bitbucket (default: guessed from the remote URL)"
+    );
+    eprintln!("    --no-push             Write the note locally without pushing it");
+    eprintln!("  telemetry [show|send|clear]  Manage opt-in anonymous usage counters");
     eprintln!("  git-path           Print the path to the underlying git executable");
     eprintln!("  upgrade            Check for updates and install if available");
     eprintln!("    --force               Reinstall latest version even if already up to date");
@@ -136,7 +604,14 @@ fn print_help() {
 }
 
 fn handle_checkpoint(args: &[String]) {
-    let mut repository_working_dir = std::env::current_dir()
+    // `checkpoint begin-agent <preset>` and `end-agent <preset>` bracket a single agent edit
+    let (edit_phase, args) = match args.first().map(String::as_str) {
+        Some("begin-agent") => (Some(AgentEditPhase::Begin), &args[1..]),
+        Some("end-agent") => (Some(AgentEditPhase::End), &args[1..]),
+        _ => (None, args),
+    };
+
+    let repository_working_dir = std::env::current_dir()
         .unwrap()
         .to_string_lossy()
         .to_string();
@@ -144,7 +619,28 @@ fn handle_checkpoint(args: &[String]) {
     // Parse checkpoint-specific arguments
     let mut show_working_log = false;
     let mut reset = false;
+    let mut include_generated = false;
     let mut hook_input = None;
+    let mut commit_message = None;
+    let mut patches = None;
+    let mut author_class = None;
+    let mut file_contents: HashMap<String, String> = HashMap::new();
+
+    // Only one flag can have stdin
+    let stdin_readers = args
+        .windows(2)
+        .filter(|pair| {
+            (pair[0] == "--patch" && pair[1] == "-")
+                || (pair[0] == "--hook-input" && pair[1] == "stdin")
+        })
+        .count()
+        + args.iter().filter(|arg| *arg == "--contents-stdin").count();
+    if stdin_readers > 1 {
+        eprintln!(
+            "Error: only one of --hook-input stdin, --patch - and --contents-stdin can read stdin"
+        );
+        std::process::exit(1);
+    }
 
     let mut i = 0;
     while i < args.len() {
@@ -157,6 +653,87 @@ fn handle_checkpoint(args: &[String]) {
                 reset = true;
                 i += 1;
             }
+            "--include-generated" => {
+                include_generated = true;
+                i += 1;
+            }
+            "--commit-message" => {
+                if i + 1 < args.len() {
+                    commit_message = Some(args[i + 1].clone());
+                    i += 2;
+                } else {
+                    eprintln!("Error: --commit-message requires a value");
+                    std::process::exit(1);
+                }
+            }
+            "--class" => {
+                let Some(class) = args.get(i + 1) else {
+                    eprintln!("Error: --class requires an author class");
+                    std::process::exit(1);
+                };
+                match author_class::parse_declared_class(class) {
+                    Ok(class) => author_class = Some(class.to_string()),
+                    Err(reason) => {
+                        eprintln!("Error: {}", reason);
+                        std::process::exit(1);
+                    }
+                }
+                i += 2;
+            }
+            "--patch" => {
+                if i + 1 < args.len() {
+                    let source = &args[i + 1];
+                    let text = if source == "-" {
+                        let mut buffer = String::new();
+                        std::io::stdin().read_to_string(&mut buffer).map(|_| buffer)
+                    } else {
+                        std::fs::read_to_string(source)
+                    };
+                    let text = match text {
+                        Ok(text) => text,
+                        Err(e) => exit_with_error("Failed to read patch", &e.into()),
+                    };
+                    match crate::authorship::patch::parse(&text) {
+                        Ok(parsed) => patches = Some(parsed),
+                        Err(e) => exit_with_error("Failed to parse patch", &e),
+                    }
+                    i += 2;
+                } else {
+                    eprintln!("Error: --patch requires a file, or '-' to read from stdin");
+                    std::process::exit(1);
+                }
+            }
+            "--content" => {
+                let Some((path, source)) = args.get(i + 1).and_then(|arg| arg.split_once('='))
+                else {
+                    eprintln!("Error: --content requires <path>=<file with its contents>");
+                    std::process::exit(1);
+                };
+                match std::fs::read(source) {
+                    Ok(bytes) => {
+                        file_contents.insert(path.to_string(), file_text::decode(&bytes));
+                    }
+                    Err(e) => exit_with_error(
+                        &format!("Failed to read the contents of {}", path),
+                        &e.into(),
+                    ),
+                }
+                i += 2;
+            }
+            "--contents-stdin" => {
+                let mut buffer = String::new();
+                if let Err(e) = std::io::stdin().read_to_string(&mut buffer) {
+                    exit_with_error("Failed to read file contents from stdin", &e.into());
+                }
+                match serde_json::from_str::<HashMap<String, String>>(&buffer) {
+                    Ok(contents) => file_contents.extend(contents),
+                    Err(e) => exit_with_error(
+                        "Expected a JSON object of file paths to contents on stdin",
+                        &e.into(),
+                    ),
+                }
+                i += 1;
+            }
             "--hook-input" => {
                 if i + 1 < args.len() {
                     hook_input = Some(args[i + 1].clone());
@@ -164,8 +741,7 @@ fn handle_checkpoint(args: &[String]) {
                         let mut stdin = std::io::stdin();
                         let mut buffer = String::new();
                         if let Err(e) = stdin.read_to_string(&mut buffer) {
-                            eprintln!("Failed to read stdin for hook input: {}", e);
-                            std::process::exit(1);
+                            exit_with_error("Failed to read stdin for hook input", &e.into());
                         }
                         if !buffer.trim().is_empty() {
                             hook_input = Some(buffer);
@@ -190,128 +766,63 @@ fn handle_checkpoint(args: &[String]) {
         }
     }
 
-    let mut agent_run_result = None;
     // Handle preset arguments after parsing all flags
-    if !args.is_empty() {
-        match args[0].as_str() {
-            "claude" => {
-                match ClaudePreset.run(AgentCheckpointFlags {
-                    hook_input: hook_input.clone(),
-                }) {
-                    Ok(agent_run) => {
-                        if agent_run.repo_working_dir.is_some() {
-                            repository_working_dir = agent_run.repo_working_dir.clone().unwrap();
-                        }
-                        agent_run_result = Some(agent_run);
-                    }
-                    Err(e) => {
-                        eprintln!("Claude preset error: {}", e);
-                        std::process::exit(1);
-                    }
-                }
-            }
-            "cursor" => {
-                match CursorPreset.run(AgentCheckpointFlags {
-                    hook_input: hook_input.clone(),
-                }) {
-                    Ok(agent_run) => {
-                        if agent_run.repo_working_dir.is_some() {
-                            repository_working_dir = agent_run.repo_working_dir.clone().unwrap();
-                        }
-                        agent_run_result = Some(agent_run);
-                    }
-                    Err(e) => {
-                        eprintln!("Error running Cursor preset: {}", e);
-                        std::process::exit(1);
-                    }
-                }
-            }
-            "github-copilot" => {
-                match GithubCopilotPreset.run(AgentCheckpointFlags {
-                    hook_input: hook_input.clone(),
-                }) {
-                    Ok(agent_run) => {
-                        agent_run_result = Some(agent_run);
-                    }
-                    Err(e) => {
-                        eprintln!("Github Copilot preset error: {}", e);
-                        std::process::exit(1);
-                    }
-                }
-            }
-            "ai_tab" => {
-                match AiTabPreset.run(AgentCheckpointFlags {
-                    hook_input: hook_input.clone(),
-                }) {
-                    Ok(agent_run) => {
-                        if agent_run.repo_working_dir.is_some() {
-                            repository_working_dir = agent_run.repo_working_dir.clone().unwrap();
-                        }
-                        agent_run_result = Some(agent_run);
-                    }
-                    Err(e) => {
-                        eprintln!("ai_tab preset error: {}", e);
-                        std::process::exit(1);
-                    }
-                }
-            }
-            "agent-v1" => {
-                match AgentV1Preset.run(AgentCheckpointFlags {
-                    hook_input: hook_input.clone(),
-                }) {
-                    Ok(agent_run) => {
-                        agent_run_result = Some(agent_run);
-                    }
-                    Err(e) => {
-                        eprintln!("Agent V1 preset error: {}", e);
-                        std::process::exit(1);
-                    }
-                }
-            }
-            "mock_ai" => {
-                let mock_agent_id = format!(
-                    "ai-thread-{}",
-                    SystemTime::now()
-                        .duration_since(UNIX_EPOCH)
-                        .map(|d| d.as_nanos())
-                        .unwrap_or_else(|_| 0)
-                );
-
-                // Collect all remaining args (after mock_ai and flags) as pathspecs
-                let edited_filepaths = if args.len() > 1 {
-                    let mut paths = Vec::new();
-                    for arg in &args[1..] {
-                        // Skip flags
-                        if !arg.starts_with("--") {
-                            paths.push(arg.clone());
-                        }
-                    }
-                    if paths.is_empty() { None } else { Some(paths) }
-                } else {
-                    let working_dir = agent_run_result
-                        .as_ref()
-                        .and_then(|r| r.repo_working_dir.clone())
-                        .unwrap_or(repository_working_dir.clone());
-                    // Find the git repository
-                    Some(get_all_files_for_mock_ai(&working_dir))
-                };
+    let mut agent_run_result = match args.first() {
+        Some(name) => match run_checkpoint_preset(
+            name,
+            args,
+            hook_input.clone(),
+            commit_message.clone(),
+            &repository_working_dir,
+        ) {
+            Ok(agent_run) => agent_run,
+            Err(e) => exit_with_error(&format!("{} preset error", name), &e),
+        },
+        None => None,
+    };
 
-                agent_run_result = Some(AgentRunResult {
-                    agent_id: AgentId {
-                        tool: "mock_ai".to_string(),
-                        id: mock_agent_id,
-                        model: "unknown".to_string(),
-                    },
-                    checkpoint_kind: CheckpointKind::AiAgent,
-                    transcript: None,
-                    repo_working_dir: None,
-                    edited_filepaths,
-                    will_edit_filepaths: None,
-                    dirty_files: None,
-                });
+    // Lines of an author class are neither a person's nor an agent's
+    if let Some(class) = &author_class {
+        if agent_run_result.is_some() {
+            eprintln!("Error: --class is for changes no agent made and can't take a preset");
+            std::process::exit(1);
+        }
+        let mut paths = Vec::new();
+        let mut rest = args.iter();
+        while let Some(arg) = rest.next() {
+            // Skip flags, and the values of flags that take one
+            if ["--class", "--patch", "--content", "--hook-input"].contains(&arg.as_str()) {
+                rest.next();
+            } else if !arg.starts_with("--") {
+                paths.push(arg.clone());
             }
-            _ => {}
         }
+        if paths.is_empty() {
+            paths = get_all_files_for_mock_ai(&repository_working_dir);
+        }
+        agent_run_result = Some(AgentRunResult {
+            agent_id: AgentId {
+                tool: class.clone(),
+                id: class.clone(),
+                model: "unknown".to_string(),
+            },
+            checkpoint_kind: CheckpointKind::AuthorClass,
+            transcript: None,
+            repo_working_dir: None,
+            edited_filepaths: Some(paths),
+            will_edit_filepaths: None,
+            dirty_files: None,
+            commit_message: None,
+            patches: None,
+            edit_phase: None,
+            human_author: None,
+            author_class: Some(class.clone()),
+        });
+    }
+
+    // Without a preset, let agent plugins recognize their agent from the environment
+    if agent_run_result.is_none() {
+        agent_run_result = plugins::detect_agent(Path::new(&repository_working_dir));
     }
 
     let final_working_dir = agent_run_result
@@ -319,14 +830,37 @@ fn handle_checkpoint(args: &[String]) {
         .and_then(|r| r.repo_working_dir.clone())
         .unwrap_or_else(|| repository_working_dir);
     // Find the git repository
-    let repo = match find_repository_in_path(&final_working_dir) {
+    let mut repo = match find_repository_in_path(&final_working_dir) {
         Ok(repo) => repo,
         Err(e) => {
-            eprintln!("Failed to find repository: {}", e);
-            std::process::exit(1);
+            exit_with_error("Failed to find repository", &e);
         }
     };
 
+    if let Some(phase) = edit_phase {
+        let Some(result) = agent_run_result.as_mut() else {
+            eprintln!("Error: begin-agent and end-agent need an agent preset");
+            std::process::exit(1);
+        };
+        match phase {
+            // Whatever changed before the agent starts is someone else's
+            AgentEditPhase::Begin => {
+                if result.checkpoint_kind != CheckpointKind::Human {
+                    result.checkpoint_kind = CheckpointKind::Human;
+                    result.will_edit_filepaths = result.edited_filepaths.take();
+                    result.transcript = None;
+                }
+            }
+            AgentEditPhase::End => {
+                if result.checkpoint_kind == CheckpointKind::Human {
+                    eprintln!("Error: end-agent needs the agent's post-edit hook input");
+                    std::process::exit(1);
+                }
+            }
+        }
+        result.edit_phase = Some(phase);
+    }
+
     let checkpoint_kind = agent_run_result
         .as_ref()
         .map(|r| r.checkpoint_kind)
@@ -355,9 +889,42 @@ fn handle_checkpoint(args: &[String]) {
             edited_filepaths: None,
             repo_working_dir: Some(final_working_dir),
             dirty_files: None,
+            commit_message: None,
+            patches: None,
+            edit_phase: None,
+            author_class: None,
+            human_author: None,
         });
     }
 
+    // The patch says which files the agent edited, and exactly how
+    if let Some(patches) = patches {
+        if checkpoint_kind == CheckpointKind::Human {
+            eprintln!("Error: --patch describes an agent's edit and needs an agent preset");
+            std::process::exit(1);
+        }
+        if let Some(result) = agent_run_result.as_mut() {
+            let edited = result.edited_filepaths.get_or_insert_with(Vec::new);
+            for patch in &patches {
+                if !edited.contains(&patch.path) {
+                    edited.push(patch.path.clone());
+                }
+            }
+            result.patches = Some(patches);
+        }
+    }
+
+    // The agent's own copy of what it wrote wins over the file on disk, which an editor may be
+    // halfway through writing
+    if !file_contents.is_empty()
+        && let Some(result) = agent_run_result.as_mut()
+    {
+        result
+            .dirty_files
+            .get_or_insert_with(HashMap::new)
+            .extend(file_contents);
+    }
+
     // Get the current user name from git config
     let default_user_name = match repo.config_get_str("user.name") {
         Ok(Some(name)) if !name.trim().is_empty() => name,
@@ -367,6 +934,12 @@ fn handle_checkpoint(args: &[String]) {
         }
     };
 
+    // jj commits and rewrites without git's hooks, so catch up before checkpointing on its HEAD.
+    // Shadow mode doesn't replay rewrites.
+    if !repo.storage.shadowed {
+        jj::sync_operations(&mut repo);
+    }
+
     let checkpoint_start = std::time::Instant::now();
     let agent_tool = agent_run_result.as_ref().map(|r| r.agent_id.tool.clone());
     let checkpoint_result = commands::checkpoint::run(
@@ -378,6 +951,8 @@ fn handle_checkpoint(args: &[String]) {
         false,
         agent_run_result,
         false,
+        // Generated and vendored files are what classes like codegen and import are for
+        include_generated || author_class.is_some(),
     );
     match checkpoint_result {
         Ok((_, files_edited, _)) => {
@@ -395,7 +970,7 @@ fn handle_checkpoint(args: &[String]) {
                 "checkpoint_kind": format!("{:?}", checkpoint_kind)
             });
             observability::log_error(&e, Some(context));
-            std::process::exit(1);
+            std::process::exit(e.exit_code());
         }
     }
 }
@@ -411,19 +986,26 @@ fn handle_ai_blame(args: &[String]) {
     let repo = match find_repository(&Vec::<String>::new()) {
         Ok(repo) => repo,
         Err(e) => {
-            eprintln!("Failed to find repository: {}", e);
-            std::process::exit(1);
+            exit_with_error("Failed to find repository", &e);
         }
     };
 
     // Parse blame arguments
-    let (file_path, options) = match commands::blame::parse_blame_args(args) {
+    let (mut file_paths, options) = match commands::blame::parse_blame_args(args) {
         Ok(result) => result,
         Err(e) => {
-            eprintln!("Failed to parse blame arguments: {}", e);
-            std::process::exit(1);
+            exit_with_error("Failed to parse blame arguments", &e);
         }
     };
+    if options.paths_from_stdin {
+        file_paths.extend(
+            std::io::stdin()
+                .lines()
+                .map_while(Result::ok)
+                .map(|line| line.trim().to_string())
+                .filter(|line| !line.is_empty()),
+        );
+    }
 
     // Check if this is an interactive terminal
     let is_interactive = std::io::stdout().is_terminal();
@@ -435,9 +1017,8 @@ fn handle_ai_blame(args: &[String]) {
         std::process::exit(1);
     }
 
-    if let Err(e) = repo.blame(&file_path, &options) {
-        eprintln!("Blame failed: {}", e);
-        std::process::exit(1);
+    if let Err(e) = repo.blame_files(&file_paths, &options) {
+        exit_with_error("Blame failed", &e);
     }
 }
 
@@ -446,12 +1027,26 @@ fn handle_stats(args: &[String]) {
     let repo = match find_repository(&Vec::<String>::new()) {
         Ok(repo) => repo,
         Err(e) => {
-            eprintln!("Failed to find repository: {}", e);
-            std::process::exit(1);
+            exit_with_error("Failed to find repository", &e);
         }
     };
+    if args.iter().any(|arg| arg == "--time-to-commit") {
+        handle_time_to_commit(&repo, args);
+        return;
+    }
+    if args.iter().any(|arg| arg == "--acceptance") {
+        handle_acceptance(&repo, args);
+        return;
+    }
+    if args.iter().any(|arg| arg == "--by-prompter") {
+        handle_by_prompter(&repo, args);
+        return;
+    }
+
     // Parse stats-specific arguments
     let mut json_output = false;
+    let mut include_generated = false;
+    let mut include_inferred = true;
     let mut commit_sha = None;
     let mut commit_range: Option<CommitRange> = None;
 
@@ -462,6 +1057,14 @@ fn handle_stats(args: &[String]) {
                 json_output = true;
                 i += 1;
             }
+            "--include-generated" => {
+                include_generated = true;
+                i += 1;
+            }
+            "--exclude-inferred" => {
+                include_inferred = false;
+                i += 1;
+            }
             _ => {
                 // First non-flag argument is treated as commit SHA or range
                 if commit_sha.is_none() {
@@ -481,8 +1084,7 @@ fn handle_stats(args: &[String]) {
                                     commit_range = Some(range);
                                 }
                                 Err(e) => {
-                                    eprintln!("Failed to create commit range: {}", e);
-                                    std::process::exit(1);
+                                    exit_with_error("Failed to create commit range", &e);
                                 }
                             }
                         } else {
@@ -503,7 +1105,7 @@ fn handle_stats(args: &[String]) {
 
     // Handle commit range if detected
     if let Some(range) = commit_range {
-        match range_authorship::range_authorship(range, true) {
+        match range_authorship::range_authorship(range, true, include_generated) {
             Ok(stats) => {
                 if json_output {
                     let json_str = serde_json::to_string(&stats).unwrap();
@@ -513,14 +1115,19 @@ fn handle_stats(args: &[String]) {
                 }
             }
             Err(e) => {
-                eprintln!("Range authorship failed: {}", e);
-                std::process::exit(1);
+                exit_with_error("Range authorship failed", &e);
             }
         }
         return;
     }
 
-    if let Err(e) = stats_command(&repo, commit_sha.as_deref(), json_output) {
+    if let Err(e) = stats_command(
+        &repo,
+        commit_sha.as_deref(),
+        json_output,
+        include_generated,
+        include_inferred,
+    ) {
         match e {
             crate::error::GitAiError::Generic(msg) if msg.starts_with("No commit found:") => {
                 eprintln!("{}", msg);
@@ -533,20 +1140,71 @@ fn handle_stats(args: &[String]) {
     }
 }
 
-fn get_all_files_for_mock_ai(working_dir: &str) -> Vec<String> {
-    // Find the git repository
-    let repo = match find_repository_in_path(&working_dir) {
-        Ok(repo) => repo,
+/// `--json` and the `[<rev>]` (default `HEAD`) of the `stats` views over a range of commits,
+/// selected by `view_flag`
+fn parse_stats_view_args(args: &[String], view_flag: &str) -> (bool, String) {
+    let mut json_output = false;
+    let mut rev = None;
+    for arg in args {
+        match arg.as_str() {
+            "--json" => json_output = true,
+            arg if arg == view_flag => {}
+            arg if rev.is_none() && !arg.starts_with('-') => rev = Some(arg.to_string()),
+            arg => {
+                eprintln!("Unknown stats argument: {}", arg);
+                std::process::exit(1);
+            }
+        }
+    }
+    (json_output, rev.unwrap_or_else(|| "HEAD".to_string()))
+}
+
+/// `stats --time-to-commit [<rev>]`, over every commit `rev` (default `HEAD`) selects
+fn handle_time_to_commit(repo: &Repository, args: &[String]) {
+    let (json_output, rev) = parse_stats_view_args(args, "--time-to-commit");
+    let sessions = match time_to_commit::session_times(repo, &rev) {
+        Ok(sessions) => sessions,
         Err(e) => {
-            eprintln!("Failed to find repository: {}", e);
-            return Vec::new();
+            exit_with_error("Time to commit failed", &e);
         }
     };
-    match repo.get_staged_and_unstaged_filenames() {
-        Ok(filenames) => {
-            println!("filenames for mock_ai: {:?}", filenames);
-            filenames.into_iter().collect()
+    let stats = time_to_commit::by_tool_model(&sessions);
+    if json_output {
+        println!("{}", serde_json::to_string(&stats).unwrap());
+    } else {
+        time_to_commit::print_time_to_commit(&stats);
+    }
+}
+
+/// `stats --acceptance [<rev>]`, over every commit `rev` (default `HEAD`) selects
+fn handle_acceptance(repo: &Repository, args: &[String]) {
+    let (json_output, rev) = parse_stats_view_args(args, "--acceptance");
+    let sessions = match acceptance::session_acceptance(repo, &rev) {
+        Ok(sessions) => sessions,
+        Err(e) => {
+            exit_with_error("Acceptance stats failed", &e);
+        }
+    };
+    let report = acceptance::report(sessions);
+    if json_output {
+        println!("{}", serde_json::to_string(&report).unwrap());
+    } else {
+        acceptance::print_acceptance(&report);
+    }
+}
+
+/// `stats --by-prompter [<rev>]`, over every commit `rev` (default `HEAD`) selects
+fn handle_by_prompter(repo: &Repository, args: &[String]) {
+    let (json_output, rev) = parse_stats_view_args(args, "--by-prompter");
+    let stats = match prompters::by_prompter(repo, &rev) {
+        Ok(stats) => stats,
+        Err(e) => {
+            exit_with_error("Prompter stats failed", &e);
         }
-        Err(_) => Vec::new(),
+    };
+    if json_output {
+        println!("{}", serde_json::to_string(&stats).unwrap());
+    } else {
+        prompters::print_prompters(&stats);
     }
 }
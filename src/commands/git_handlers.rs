@@ -1,23 +1,36 @@
+use crate::authorship::diff_annotation::DiffAnnotator;
+use crate::commands::hooks::CommandHooksContext;
+use crate::commands::hooks::abort_hooks;
+use crate::commands::hooks::add_hooks;
 use crate::commands::hooks::cherry_pick_hooks;
+use crate::commands::hooks::clone_hooks;
 use crate::commands::hooks::commit_hooks;
 use crate::commands::hooks::fetch_hooks;
 use crate::commands::hooks::merge_hooks;
 use crate::commands::hooks::push_hooks;
 use crate::commands::hooks::rebase_hooks;
+use crate::commands::hooks::rerere_hooks;
 use crate::commands::hooks::reset_hooks;
+use crate::commands::hooks::shadow_hooks;
+use crate::commands::upgrade;
 use crate::config;
 use crate::git::cli_parser::{ParsedGitInvocation, parse_git_cli_args};
 use crate::git::find_repository;
+use crate::git::jj;
+use crate::git::repo_config::enforce_required_version;
 use crate::git::repository::Repository;
 use crate::observability;
+use crate::observability::trace;
+use crate::observability::usage;
 
 use crate::observability::wrapper_performance_targets::log_performance_target_if_violated;
 use crate::utils::debug_log;
+use std::io::{BufRead, BufReader, BufWriter, IsTerminal, Write};
 #[cfg(unix)]
 use std::os::unix::process::CommandExt;
 #[cfg(unix)]
 use std::os::unix::process::ExitStatusExt;
-use std::process::Command;
+use std::process::{Command, Stdio};
 #[cfg(unix)]
 use std::sync::atomic::{AtomicI32, Ordering};
 use std::time::Instant;
@@ -69,14 +82,6 @@ fn uninstall_forwarding_handlers() {
     }
 }
 
-pub struct CommandHooksContext {
-    pub pre_commit_hook_result: Option<bool>,
-    pub rebase_original_head: Option<String>,
-    pub _rebase_onto: Option<String>,
-    pub push_authorship_handle: Option<std::thread::JoinHandle<()>>,
-    pub fetch_authorship_handle: Option<std::thread::JoinHandle<()>>,
-}
-
 pub fn handle_git(args: &[String]) {
     // If we're being invoked from a shell completion context, bypass git-ai logic
     // and delegate directly to the real git so existing completion scripts work.
@@ -86,9 +91,21 @@ pub fn handle_git(args: &[String]) {
         return;
     }
 
-    let parsed_args = parse_git_cli_args(args);
+    trace::init();
+    exit_with_status(run_git(args));
+}
+
+/// Runs a git command with git-ai's hooks around it, as `handle_git` does, and returns git's
+/// exit status instead of exiting with it
+pub fn run_git(args: &[String]) -> std::process::ExitStatus {
+    let mut parsed_args = parse_git_cli_args(args);
+    // git doesn't know --ai-ack, so it's taken out whether or not the hooks run
+    let ai_ack = commit_hooks::take_ai_ack(&mut parsed_args);
 
-    let mut repository_option = find_repository(&parsed_args.global_args).ok();
+    // Bare repositories have no work tree for the hooks to track, so git runs as-is there
+    let mut repository_option = find_repository(&parsed_args.global_args)
+        .ok()
+        .filter(|repo| !repo.is_bare());
 
     let has_repo = repository_option.is_some();
 
@@ -98,40 +115,93 @@ pub fn handle_git(args: &[String]) {
 
     let config = config::Config::get();
 
-    let skip_hooks = !config.is_allowed_repository(&repository_option);
+    let mut skip_hooks = !config.is_allowed_repository(&repository_option);
     if skip_hooks {
         debug_log(
             "Skipping git-ai hooks because repository is excluded or not in allow_repositories list",
         );
     }
 
+    // A repository can refuse git-ai versions older than its .gitai requires. The git command
+    // itself still runs, just without attribution.
+    if !skip_hooks
+        && !parsed_args.is_help
+        && let Some(repo) = repository_option.as_ref()
+        && let Some(err) = enforce_required_version(repo)
+    {
+        eprintln!("git-ai: {} (running git without git-ai hooks)", err);
+        skip_hooks = true;
+    }
+
+    // In shadow mode git runs untouched and git-ai only logs what its hooks would have done
+    if config.is_shadow_mode() && !skip_hooks && !parsed_args.is_help {
+        let shadow_context = repository_option
+            .as_mut()
+            .map(|repo| shadow_hooks::shadow_pre_command_hook(&parsed_args, repo));
+        let exit_status = proxy_to_git(&parsed_args.to_invocation_vec(), false);
+        if let (Some(context), Some(repo)) = (shadow_context, repository_option.as_mut()) {
+            shadow_hooks::shadow_post_command_hook(context, &parsed_args, exit_status, repo);
+        }
+        return exit_status;
+    }
+
+    // A clone has no repository until git has made it, so its hook runs on its own
+    if parsed_args.command.as_deref() == Some("clone") && !parsed_args.is_help {
+        usage::record_command("git:clone");
+        let exit_status = proxy_to_git(&parsed_args.to_invocation_vec(), false);
+        clone_hooks::post_clone_hook(&parsed_args, exit_status);
+        return exit_status;
+    }
+
     // run with hooks
-    let exit_status = if !parsed_args.is_help && has_repo && !skip_hooks {
+    if !parsed_args.is_help && has_repo && !skip_hooks {
         let mut command_hooks_context = CommandHooksContext {
             pre_commit_hook_result: None,
             rebase_original_head: None,
             _rebase_onto: None,
             push_authorship_handle: None,
             fetch_authorship_handle: None,
+            staged_attributions: None,
+            commit_trailers: Vec::new(),
+            ai_ack,
         };
 
         let repository = repository_option.as_mut().unwrap();
 
+        // What jj did since the last command never went through these hooks
+        jj::sync_operations(repository);
+
         let pre_command_start = Instant::now();
-        run_pre_command_hooks(&mut command_hooks_context, &parsed_args, repository);
+        tracing::info_span!("pre_command").in_scope(|| {
+            run_pre_command_hooks(&mut command_hooks_context, &parsed_args, repository)
+        });
         let pre_command_duration = pre_command_start.elapsed();
 
         let git_start = Instant::now();
-        let exit_status = proxy_to_git(&parsed_args.to_invocation_vec(), false);
+        let exit_status = tracing::info_span!("git").in_scope(|| {
+            if should_annotate_diff(&parsed_args) {
+                proxy_to_git_annotated(&parsed_args, repository)
+            } else {
+                proxy_to_git(
+                    &commit_hooks::invocation_with_trailers(
+                        &parsed_args,
+                        &command_hooks_context.commit_trailers,
+                    ),
+                    false,
+                )
+            }
+        });
         let git_duration = git_start.elapsed();
 
         let post_command_start = Instant::now();
-        run_post_command_hooks(
-            &mut command_hooks_context,
-            &parsed_args,
-            exit_status,
-            repository,
-        );
+        tracing::info_span!("post_command").in_scope(|| {
+            run_post_command_hooks(
+                &mut command_hooks_context,
+                &parsed_args,
+                exit_status,
+                repository,
+            )
+        });
         let post_command_duration = post_command_start.elapsed();
 
         log_performance_target_if_violated(
@@ -145,8 +215,7 @@ pub fn handle_git(args: &[String]) {
     } else {
         // run without hooks
         proxy_to_git(&parsed_args.to_invocation_vec(), false)
-    };
-    exit_with_status(exit_status);
+    }
 }
 
 fn run_pre_command_hooks(
@@ -154,13 +223,32 @@ fn run_pre_command_hooks(
     parsed_args: &ParsedGitInvocation,
     repository: &mut Repository,
 ) {
+    if let Some(
+        command @ ("commit" | "rebase" | "reset" | "cherry-pick" | "merge" | "push" | "fetch"
+        | "pull" | "stash"),
+    ) = parsed_args.command.as_deref()
+    {
+        usage::record_command(&format!("git:{}", command));
+    }
+
     let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        // Before git records the resolutions of the conflicts this command concludes
+        rerere_hooks::pre_rerere_hook(parsed_args, repository);
+
         // Pre-command hooks
         match parsed_args.command.as_deref() {
+            // Rolled back in the post-command hook once git has aborted
+            Some("merge" | "rebase" | "cherry-pick") if abort_hooks::is_abort(parsed_args) => {}
+            Some("merge") => {
+                merge_hooks::pre_merge_hook(parsed_args, repository);
+            }
             Some("commit") => {
-                command_hooks_context.pre_commit_hook_result = Some(
-                    commit_hooks::commit_pre_command_hook(parsed_args, repository),
-                );
+                command_hooks_context.pre_commit_hook_result =
+                    Some(commit_hooks::commit_pre_command_hook(
+                        parsed_args,
+                        repository,
+                        command_hooks_context,
+                    ));
             }
             Some("rebase") => {
                 rebase_hooks::pre_rebase_hook(parsed_args, repository, command_hooks_context);
@@ -168,6 +256,9 @@ fn run_pre_command_hooks(
             Some("reset") => {
                 reset_hooks::pre_reset_hook(parsed_args, repository);
             }
+            Some("add") => {
+                add_hooks::pre_add_hook(parsed_args, repository);
+            }
             Some("cherry-pick") => {
                 cherry_pick_hooks::pre_cherry_pick_hook(
                     parsed_args,
@@ -176,10 +267,12 @@ fn run_pre_command_hooks(
                 );
             }
             Some("push") => {
+                upgrade::maybe_schedule_background_update_check();
                 command_hooks_context.push_authorship_handle =
                     push_hooks::push_pre_command_hook(parsed_args, repository);
             }
             Some("fetch") | Some("pull") => {
+                upgrade::maybe_schedule_background_update_check();
                 command_hooks_context.fetch_authorship_handle =
                     fetch_hooks::fetch_pull_pre_command_hook(parsed_args, repository);
             }
@@ -217,6 +310,9 @@ fn run_post_command_hooks(
     let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
         // Post-command hooks
         match parsed_args.command.as_deref() {
+            Some("merge" | "rebase" | "cherry-pick") if abort_hooks::is_abort(parsed_args) => {
+                abort_hooks::post_abort_hook(parsed_args, repository, exit_status)
+            }
             Some("commit") => commit_hooks::commit_post_command_hook(
                 parsed_args,
                 exit_status,
@@ -236,6 +332,7 @@ fn run_post_command_hooks(
                 command_hooks_context,
             ),
             Some("reset") => reset_hooks::post_reset_hook(parsed_args, repository, exit_status),
+            Some("add") => add_hooks::post_add_hook(parsed_args, repository, exit_status),
             Some("merge") => merge_hooks::post_merge_hook(parsed_args, exit_status, repository),
             Some("rebase") => rebase_hooks::handle_rebase_post_command(
                 command_hooks_context,
@@ -251,6 +348,9 @@ fn run_post_command_hooks(
             ),
             _ => {}
         }
+
+        // Conflicts the command stopped on that rerere resolved from a recorded resolution
+        rerere_hooks::post_rerere_hook(parsed_args, repository);
     }));
 
     if let Err(panic_payload) = result {
@@ -385,6 +485,167 @@ fn proxy_to_git(args: &[String], exit_on_completion: bool) -> std::process::Exit
     }
 }
 
+/// `git show` and `git log -p` printing to a terminal, when `annotate_diffs` is on. Output that
+/// isn't a plain unified diff (word diffs, external diff tools) is left alone.
+fn should_annotate_diff(parsed_args: &ParsedGitInvocation) -> bool {
+    let shows_patch = match parsed_args.command.as_deref() {
+        Some("show") => {
+            !parsed_args.has_command_flag("--no-patch") && !parsed_args.has_command_flag("-s")
+        }
+        Some("log") => ["-p", "-u", "--patch"]
+            .iter()
+            .any(|flag| parsed_args.has_command_flag(flag)),
+        _ => false,
+    };
+    shows_patch
+        && config::Config::get().annotate_diffs()
+        && std::io::stdout().is_terminal()
+        && !parsed_args.command_args.iter().any(|arg| {
+            arg.starts_with("--word-diff")
+                || arg.starts_with("--color-words")
+                || arg == "--ext-diff"
+        })
+}
+
+/// Runs git with its output going through `DiffAnnotator` and then to git's pager, which git
+/// can't start itself as its output is a pipe
+fn proxy_to_git_annotated(
+    parsed_args: &ParsedGitInvocation,
+    repository: &Repository,
+) -> std::process::ExitStatus {
+    let color = !parsed_args
+        .command_args
+        .iter()
+        .any(|arg| arg == "--no-color" || arg == "--color=never");
+    let mut invocation = parsed_args.clone();
+    if color
+        && !invocation
+            .command_args
+            .iter()
+            .any(|arg| arg.starts_with("--color"))
+    {
+        invocation
+            .command_args
+            .insert(0, "--color=always".to_string());
+    }
+    let mut args = vec!["--no-pager".to_string()];
+    args.extend(invocation.to_invocation_vec());
+
+    let mut child = match Command::new(config::Config::get().git_cmd())
+        .args(&args)
+        .stdout(Stdio::piped())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => {
+            eprintln!("Failed to execute git command: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let use_pager = !parsed_args
+        .global_args
+        .iter()
+        .any(|arg| arg == "--no-pager" || arg == "-P");
+    let mut pager = if use_pager {
+        spawn_pager(repository)
+    } else {
+        None
+    };
+    let mut out: Box<dyn Write> = match pager.as_mut().and_then(|pager| pager.stdin.take()) {
+        Some(stdin) => Box::new(BufWriter::new(stdin)),
+        None => Box::new(BufWriter::new(std::io::stdout().lock())),
+    };
+
+    // Like git, leave Ctrl-C to the pager (and to git, which shares our process group)
+    #[cfg(unix)]
+    if pager.is_some() {
+        unsafe {
+            libc::signal(libc::SIGINT, libc::SIG_IGN);
+        }
+    }
+
+    let mut annotator = DiffAnnotator::new(repository, color);
+    let stdout = child.stdout.take().expect("git stdout is piped");
+    let mut write_failed = false;
+    for line in BufReader::new(stdout).split(b'\n') {
+        let Ok(line) = line else {
+            break;
+        };
+        let annotated = annotator.line(&String::from_utf8_lossy(&line));
+        if annotated
+            .iter()
+            .any(|line| writeln!(out, "{}", line).is_err())
+        {
+            write_failed = true;
+            break;
+        }
+    }
+    if !write_failed {
+        for line in annotator.finish() {
+            let _ = writeln!(out, "{}", line);
+        }
+    }
+    let _ = out.flush();
+    drop(out);
+
+    if write_failed {
+        // The pager was closed before the end of the output
+        let _ = child.kill();
+    }
+    let status = child.wait();
+    if let Some(mut pager) = pager {
+        let _ = pager.wait();
+    }
+    #[cfg(unix)]
+    unsafe {
+        libc::signal(libc::SIGINT, libc::SIG_DFL);
+    }
+
+    match status {
+        Ok(status) if write_failed => {
+            // Quitting the pager early isn't an error
+            #[cfg(unix)]
+            if status.signal().is_some() {
+                return std::process::ExitStatus::from_raw(0);
+            }
+            status
+        }
+        Ok(status) => status,
+        Err(e) => {
+            eprintln!("Failed to wait for git process: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Starts the pager git would use, with git's defaults for `less` and `lv`
+fn spawn_pager(repository: &Repository) -> Option<std::process::Child> {
+    if cfg!(not(unix)) {
+        return None;
+    }
+    // `git var GIT_PAGER` says "cat" when its own output isn't a terminal, so look it up the
+    // same way here
+    let pager = std::env::var("GIT_PAGER")
+        .ok()
+        .or_else(|| repository.config_get_str("core.pager").ok().flatten())
+        .or_else(|| std::env::var("PAGER").ok())
+        .unwrap_or_else(|| "less".to_string());
+    let pager = pager.trim();
+    if pager.is_empty() || pager == "cat" {
+        return None;
+    }
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c").arg(pager).stdin(Stdio::piped());
+    if std::env::var_os("LESS").is_none() {
+        cmd.env("LESS", "FRX");
+    }
+    if std::env::var_os("LV").is_none() {
+        cmd.env("LV", "-c");
+    }
+    cmd.spawn().ok()
+}
+
 // Exit mirroring the child's termination: same signal if signaled, else exit code
 fn exit_with_status(status: std::process::ExitStatus) -> ! {
     #[cfg(unix)]
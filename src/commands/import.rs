@@ -0,0 +1,107 @@
+use std::path::PathBuf;
+
+use crate::authorship::import::{HistorySource, import_sessions};
+use crate::error::exit_with_error;
+use crate::git::find_repository;
+
+pub fn handle_import(args: &[String]) {
+    let mut source = None;
+    let mut since = None;
+    let mut from = None;
+    let mut dry_run = false;
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--since" | "--from" if i + 1 >= args.len() => {
+                eprintln!("Error: {} requires a value", args[i]);
+                std::process::exit(1);
+            }
+            "--since" => {
+                since = Some(args[i + 1].clone());
+                i += 1;
+            }
+            "--from" => {
+                from = Some(PathBuf::from(&args[i + 1]));
+                i += 1;
+            }
+            "--dry-run" => dry_run = true,
+            arg if source.is_none() && !arg.starts_with('-') => match HistorySource::from_name(arg)
+            {
+                Some(found) => source = Some(found),
+                None => {
+                    eprintln!(
+                        "Error: unknown agent '{}', expected cursor, claude-code or aider",
+                        arg
+                    );
+                    std::process::exit(1);
+                }
+            },
+            arg => {
+                eprintln!("Error: unexpected argument '{}'", arg);
+                std::process::exit(1);
+            }
+        }
+        i += 1;
+    }
+    let (Some(source), Some(since)) = (source, since) else {
+        eprintln!("Usage: git-ai import <cursor|claude-code|aider> --since <date> [--from <path>]");
+        std::process::exit(1);
+    };
+
+    let repo = match find_repository(&Vec::<String>::new()) {
+        Ok(repo) => repo,
+        Err(e) => {
+            exit_with_error("Failed to find repository", &e);
+        }
+    };
+    let workdir = match repo.workdir() {
+        Ok(workdir) => workdir,
+        Err(e) => {
+            exit_with_error("Failed to find repository", &e);
+        }
+    };
+
+    let location = match from
+        .map(Ok)
+        .unwrap_or_else(|| source.default_location(&workdir))
+    {
+        Ok(location) => location,
+        Err(e) => {
+            exit_with_error("Failed to find the agent's history", &e);
+        }
+    };
+    let sessions = match source.read_sessions(&location, &workdir) {
+        Ok(sessions) => sessions,
+        Err(e) => {
+            exit_with_error("Failed to read the agent's history", &e);
+        }
+    };
+    let report = match import_sessions(&repo, &sessions, &since, dry_run) {
+        Ok(report) => report,
+        Err(e) => {
+            exit_with_error("Failed to import history", &e);
+        }
+    };
+
+    for commit in &report.imported {
+        println!("{} {}", &commit.sha[..7], commit.summary.describe());
+    }
+    println!(
+        "{} {} {} from {} {} ({} already had notes, {} had no matching edits)",
+        if dry_run { "Would import" } else { "Imported" },
+        report.imported.len(),
+        if report.imported.len() == 1 {
+            "commit"
+        } else {
+            "commits"
+        },
+        sessions.len(),
+        if sessions.len() == 1 {
+            "session"
+        } else {
+            "sessions"
+        },
+        report.already_attributed,
+        report.unmatched
+    );
+}
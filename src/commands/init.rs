@@ -0,0 +1,67 @@
+use crate::commands::doctor::{print_checks, run_checks};
+use crate::commands::install_hooks;
+use crate::config::write_default_config_file;
+use crate::git::find_repository;
+use crate::git::sync_authorship::configure_notes_fetch_refspecs;
+
+/// What `git-ai init` sets up
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Profile {
+    /// A developer machine: config, agent and editor hooks, the repository's notes refspecs
+    Full,
+    /// Config and hooks, leaving the repository's git config alone
+    Minimal,
+    /// A CI runner: config without update checks and the repository's notes refspecs. No agents
+    /// or editors run there.
+    Ci,
+}
+
+/// `git-ai init [--ci | --minimal]`: sets git-ai up in one go, then runs `doctor`
+pub fn handle_init(args: &[String]) {
+    let mut profile = Profile::Full;
+    for arg in args {
+        let flag_profile = match arg.as_str() {
+            "--ci" => Profile::Ci,
+            "--minimal" => Profile::Minimal,
+            arg => {
+                eprintln!("Error: unexpected argument '{}'", arg);
+                std::process::exit(1);
+            }
+        };
+        if profile != Profile::Full && profile != flag_profile {
+            eprintln!("Error: --ci and --minimal can't be used together");
+            std::process::exit(1);
+        }
+        profile = flag_profile;
+    }
+
+    match write_default_config_file(profile == Profile::Ci) {
+        Ok(Some(path)) => println!("Wrote {}", path.display()),
+        Ok(None) => println!("Config file already exists, leaving it as it is"),
+        Err(e) => eprintln!("Failed to write the config file: {}", e),
+    }
+
+    if profile != Profile::Ci
+        && let Err(e) = install_hooks::run(&[])
+    {
+        eprintln!("Failed to install hooks: {}", e);
+    }
+
+    if profile != Profile::Minimal {
+        match find_repository(&Vec::<String>::new()) {
+            Ok(repo) => match configure_notes_fetch_refspecs(&repo) {
+                Ok(remotes) if remotes.is_empty() => {
+                    println!("No remotes need a notes fetch refspec")
+                }
+                Ok(remotes) => println!("Configured notes fetching from {}", remotes.join(", ")),
+                Err(e) => eprintln!("Failed to configure notes refspecs: {}", e),
+            },
+            Err(_) => println!("Not in a repository, skipping notes refspecs"),
+        }
+    }
+
+    println!();
+    if !print_checks(&run_checks(profile == Profile::Ci)) {
+        std::process::exit(1);
+    }
+}
@@ -1,12 +1,11 @@
 use crate::error::GitAiError;
-use crate::utils::debug_log;
+use crate::utils::{debug_log, shell_program, strip_verbatim_prefix, write_atomic};
 use indicatif::{ProgressBar, ProgressStyle};
 use jsonc_parser::ParseOptions;
 use jsonc_parser::cst::CstRootNode;
 use serde_json::{Value, json};
 use similar::{ChangeTag, TextDiff};
 use std::fs;
-use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
@@ -125,8 +124,7 @@ async fn async_run(binary_path: PathBuf, dry_run: bool) -> Result<(), GitAiError
                     }
                     Ok(false) => {
                         if dry_run {
-                            extension_spinner
-                                .pending("Cursor: Pending extension install");
+                            extension_spinner.pending("Cursor: Pending extension install");
                         } else {
                             match install_vsc_editor_extension("cursor", "git-ai.git-ai-vscode") {
                                 Ok(()) => {
@@ -209,8 +207,7 @@ async fn async_run(binary_path: PathBuf, dry_run: bool) -> Result<(), GitAiError
                     }
                     Ok(false) => {
                         if dry_run {
-                            spinner
-                                .pending("VS Code: Pending extension install");
+                            spinner.pending("VS Code: Pending extension install");
                         } else {
                             match install_vsc_editor_extension("code", "git-ai.git-ai-vscode") {
                                 Ok(()) => {
@@ -727,8 +724,12 @@ fn install_cursor_hooks(binary_path: &Path, dry_run: bool) -> Result<Option<Stri
     };
 
     // Build commands with absolute path
-    let before_submit_cmd = format!("{} {}", binary_path.display(), CURSOR_BEFORE_SUBMIT_CMD);
-    let after_edit_cmd = format!("{} {}", binary_path.display(), CURSOR_AFTER_EDIT_CMD);
+    let before_submit_cmd = format!(
+        "{} {}",
+        shell_program(binary_path),
+        CURSOR_BEFORE_SUBMIT_CMD
+    );
+    let after_edit_cmd = format!("{} {}", shell_program(binary_path), CURSOR_AFTER_EDIT_CMD);
 
     // Desired hooks payload for Cursor with new hook names
     let desired: Value = json!({
@@ -870,17 +871,6 @@ fn cursor_hooks_path() -> PathBuf {
     home_dir().join(".cursor").join("hooks.json")
 }
 
-fn write_atomic(path: &Path, data: &[u8]) -> Result<(), GitAiError> {
-    let tmp_path = path.with_extension("tmp");
-    {
-        let mut file = fs::File::create(&tmp_path)?;
-        file.write_all(data)?;
-        file.sync_all()?;
-    }
-    fs::rename(&tmp_path, path)?;
-    Ok(())
-}
-
 fn home_dir() -> PathBuf {
     if let Ok(home) = std::env::var("HOME") {
         return PathBuf::from(home);
@@ -894,9 +884,11 @@ fn home_dir() -> PathBuf {
     PathBuf::from(".")
 }
 
+/// The `git` shim install.ps1 copies next to git-ai.exe (a copy, not a symlink, since creating
+/// symlinks on Windows needs developer mode or admin rights)
 #[cfg(windows)]
 fn git_shim_path() -> PathBuf {
-    home_dir().join(".git-ai").join("bin").join("git")
+    home_dir().join(".git-ai").join("bin").join("git.exe")
 }
 
 #[cfg(windows)]
@@ -1130,10 +1122,11 @@ fn update_git_path_setting(
 fn get_current_binary_path() -> Result<PathBuf, GitAiError> {
     let path = std::env::current_exe()?;
 
-    // Canonicalize to resolve any symlinks
+    // Canonicalize to resolve any symlinks, minus the \\?\ prefix Windows adds, which cmd.exe
+    // can't run
     let canonical = path.canonicalize()?;
 
-    Ok(canonical)
+    Ok(strip_verbatim_prefix(&canonical))
 }
 
 fn is_vsc_editor_extension_installed(program: &str, id_or_vsix: &str) -> Result<bool, GitAiError> {
@@ -1,13 +1,40 @@
-pub mod blame;
-pub mod checkpoint;
-pub mod checkpoint_agent;
+pub mod attest;
+pub mod backfill;
 pub mod ci_handlers;
+pub mod compare;
+pub mod doctor;
+pub mod explain_rewrite;
+pub mod export;
 pub mod flush_logs;
+pub mod fsck;
 pub mod git_ai_handlers;
 pub mod git_handlers;
-pub mod hooks;
+pub mod import;
+pub mod init;
 pub mod install_hooks;
+pub mod notes;
+pub mod notify;
+pub mod own;
+pub mod pair;
+pub mod prepare_commit_msg;
+pub mod query;
+pub mod replay;
+pub mod restore_authorship;
+pub mod review;
+pub mod scan;
+pub mod serve;
+#[cfg(feature = "graphql")]
+pub mod serve_graphql;
 pub mod show;
 pub mod squash_authorship;
 pub mod stats_delta;
+pub mod status;
+pub mod telemetry;
+pub mod timeline;
+pub mod trace;
+pub mod tui;
+pub mod undo_notes;
 pub mod upgrade;
+pub mod workspace;
+
+pub use git_ai_core::commands::{blame, checkpoint, checkpoint_agent, hooks};
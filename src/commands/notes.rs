@@ -0,0 +1,94 @@
+use crate::error::{GitAiError, exit_with_error};
+use crate::git::find_repository;
+use crate::git::sync_authorship::sync_notes;
+
+pub fn handle_notes(args: &[String]) {
+    match args.first().map(String::as_str) {
+        Some("sync") => handle_sync(&args[1..]),
+        Some(other) => {
+            eprintln!("Error: unknown notes command '{}', expected sync", other);
+            std::process::exit(1);
+        }
+        None => {
+            eprintln!("Error: expected a notes command: sync");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// `git-ai notes sync [<remote>...]`: reconciles refs/notes/ai with the given remotes, or all of
+/// them, following each remote's `remote.<name>.aiNotes` policy
+fn handle_sync(args: &[String]) {
+    if let Some(flag) = args.iter().find(|arg| arg.starts_with('-')) {
+        eprintln!("Error: unexpected argument '{}'", flag);
+        std::process::exit(1);
+    }
+
+    let repo = match find_repository(&Vec::<String>::new()) {
+        Ok(repo) => repo,
+        Err(e) => {
+            exit_with_error("Failed to find repository", &e);
+        }
+    };
+
+    let configured: Vec<String> = match repo.remotes() {
+        Ok(remotes) => remotes.into_iter().filter(|r| !r.is_empty()).collect(),
+        Err(e) => {
+            exit_with_error("Failed to list remotes", &e);
+        }
+    };
+    let remotes: Vec<String> = if args.is_empty() {
+        configured
+    } else {
+        if let Some(unknown) = args.iter().find(|arg| !configured.contains(arg)) {
+            exit_with_error(
+                "Failed to sync notes",
+                &GitAiError::Generic(format!("no such remote '{}'", unknown)),
+            );
+        }
+        args.to_vec()
+    };
+    if remotes.is_empty() {
+        println!("No remotes to sync with");
+        return;
+    }
+
+    let reports = sync_notes(&repo, &remotes);
+    let mut failed = false;
+    for report in &reports {
+        let mut done = Vec::new();
+        if report.fetched {
+            done.push("merged".to_string());
+        }
+        if !report.conflicts.is_empty() {
+            done.push(format!(
+                "{} conflicting note{} kept local",
+                report.conflicts.len(),
+                if report.conflicts.len() == 1 { "" } else { "s" }
+            ));
+        }
+        if report.pushed {
+            done.push("pushed".to_string());
+        }
+        if let Some(error) = &report.error {
+            failed = true;
+            done.push(format!("failed: {}", error));
+        }
+        if done.is_empty() {
+            done.push("nothing to do".to_string());
+        }
+        println!(
+            "{} ({}): {}",
+            report.remote,
+            report.policy.as_str(),
+            done.join(", ")
+        );
+        for commit in &report.conflicts {
+            println!("  {}", commit);
+        }
+    }
+
+    if failed {
+        std::process::exit(1);
+    }
+}
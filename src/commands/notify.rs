@@ -0,0 +1,378 @@
+//! `git-ai notify slack|teams`: posts the report of the last few days' commits to a chat
+//! channel's incoming webhook, as Slack Block Kit blocks or a Teams Adaptive Card.
+
+use crate::authorship::report::{PeriodReport, period_report};
+use crate::ci::github::install_github_notify_workflow;
+use crate::commands::tui::sparkline;
+use crate::error::{GitAiError, exit_with_error};
+use crate::git::find_repository;
+use serde_json::{Value, json};
+
+/// Where the webhook comes from when `--webhook` isn't given, so it can stay a CI secret
+const WEBHOOK_ENV_VAR: &str = "GIT_AI_NOTIFY_WEBHOOK";
+const DEFAULT_DAYS: u32 = 7;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Channel {
+    Slack,
+    Teams,
+}
+
+impl Channel {
+    fn name(self) -> &'static str {
+        match self {
+            Channel::Slack => "slack",
+            Channel::Teams => "teams",
+        }
+    }
+}
+
+pub fn handle_notify(args: &[String]) {
+    let channel = match args.first().map(String::as_str) {
+        Some("slack") => Channel::Slack,
+        Some("teams") => Channel::Teams,
+        _ => {
+            eprintln!(
+                "Usage: git-ai notify <slack|teams> [--webhook <url>] [--days <n>] [--rev <rev>] [--dry-run] [--install-workflow]"
+            );
+            std::process::exit(1);
+        }
+    };
+    let mut webhook = std::env::var(WEBHOOK_ENV_VAR)
+        .ok()
+        .filter(|url| !url.is_empty());
+    let mut days = DEFAULT_DAYS;
+    let mut rev = "HEAD".to_string();
+    let mut dry_run = false;
+    let mut install_workflow = false;
+    let mut i = 1;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--webhook" | "--days" | "--rev" if i + 1 >= args.len() => {
+                eprintln!("Error: {} requires a value", args[i]);
+                std::process::exit(1);
+            }
+            "--webhook" => {
+                i += 1;
+                webhook = Some(args[i].clone());
+            }
+            "--days" => {
+                i += 1;
+                days = match args[i].parse() {
+                    Ok(days) if days > 0 => days,
+                    _ => {
+                        eprintln!("Error: invalid --days '{}'", args[i]);
+                        std::process::exit(1);
+                    }
+                };
+            }
+            "--rev" => {
+                i += 1;
+                rev = args[i].clone();
+            }
+            "--dry-run" => dry_run = true,
+            "--install-workflow" => install_workflow = true,
+            arg => {
+                eprintln!("Error: unexpected argument '{}'", arg);
+                std::process::exit(1);
+            }
+        }
+        i += 1;
+    }
+
+    if install_workflow {
+        match install_github_notify_workflow(channel.name()) {
+            Ok(path) => {
+                println!("Installed GitHub Actions workflow to {}", path.display());
+                println!(
+                    "Add the channel's incoming webhook as the repository secret {}",
+                    WEBHOOK_ENV_VAR
+                );
+                return;
+            }
+            Err(e) => exit_with_error("Failed to install the notify workflow", &e),
+        }
+    }
+    if webhook.is_none() && !dry_run {
+        eprintln!(
+            "Error: notify requires --webhook <url> or {} (or --dry-run to print the message)",
+            WEBHOOK_ENV_VAR
+        );
+        std::process::exit(1);
+    }
+
+    let repo = match find_repository(&Vec::<String>::new()) {
+        Ok(repo) => repo,
+        Err(e) => exit_with_error("Failed to find repository", &e),
+    };
+    let repo_name = repo
+        .workdir()
+        .ok()
+        .and_then(|path| {
+            path.file_name()
+                .map(|name| name.to_string_lossy().to_string())
+        })
+        .unwrap_or_else(|| "repository".to_string());
+    let now = chrono::Utc::now().timestamp();
+    let report = match period_report(&repo, &rev, days, now) {
+        Ok(report) => report,
+        Err(e) => exit_with_error("Failed to build the report", &e),
+    };
+    let message = match channel {
+        Channel::Slack => slack_message(&repo_name, &report),
+        Channel::Teams => teams_message(&repo_name, &report),
+    };
+
+    match webhook {
+        Some(url) if !dry_run => {
+            if let Err(e) = post(&url, &message) {
+                exit_with_error("Failed to post the report", &e);
+            }
+            println!("Posted the report for {} to {}", repo_name, channel.name());
+        }
+        _ => println!(
+            "{}",
+            serde_json::to_string_pretty(&message).unwrap_or_default()
+        ),
+    }
+}
+
+fn post(url: &str, message: &Value) -> Result<(), GitAiError> {
+    let response = minreq::post(url)
+        .with_header("Content-Type", "application/json")
+        .with_body(message.to_string())
+        .with_timeout(30)
+        .send()
+        .map_err(|e| GitAiError::Generic(format!("Failed to reach the webhook: {}", e)))?;
+    if !(200..300).contains(&response.status_code) {
+        return Err(GitAiError::Generic(format!(
+            "The webhook returned {}: {}",
+            response.status_code,
+            response.as_str().unwrap_or("").trim()
+        )));
+    }
+    Ok(())
+}
+
+/// The report's figures as (label, value) pairs, shared by both channels
+fn facts(report: &PeriodReport) -> Vec<(&'static str, String)> {
+    let share = match report.ai_percent {
+        Some(percent) => format!("{}%{}", percent, change(report)),
+        None => "no lines added".to_string(),
+    };
+    let acceptance = if report.acceptance.total_additions == 0 {
+        "no AI lines".to_string()
+    } else {
+        format!(
+            "{:.0}% of {} lines AI wrote were committed unchanged",
+            report.acceptance.acceptance_rate() * 100.0,
+            report.acceptance.total_additions
+        )
+    };
+    vec![
+        ("AI share", share),
+        ("Commits", report.commits.to_string()),
+        (
+            "Lines added",
+            format!(
+                "{} ({} by AI)",
+                report.stats.git_diff_added_lines, report.stats.ai_additions
+            ),
+        ),
+        ("AI sessions", report.sessions.to_string()),
+        ("Acceptance", acceptance),
+    ]
+}
+
+/// How the AI share moved from the period before, e.g. ` (up 5 pts)`
+fn change(report: &PeriodReport) -> String {
+    match (report.ai_percent, report.previous_ai_percent) {
+        (Some(now), Some(before)) if now > before => format!(" (up {} pts)", now - before),
+        (Some(now), Some(before)) if now < before => format!(" (down {} pts)", before - now),
+        (Some(_), Some(_)) => " (unchanged)".to_string(),
+        _ => String::new(),
+    }
+}
+
+/// Lines each agent and model wrote, most first
+fn agents(report: &PeriodReport) -> Vec<String> {
+    let mut agents: Vec<(&String, u32)> = report
+        .stats
+        .tool_model_breakdown
+        .iter()
+        .map(|(tool_model, stats)| (tool_model, stats.ai_additions))
+        .filter(|(_, lines)| *lines > 0)
+        .collect();
+    agents.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+    agents
+        .into_iter()
+        .map(|(tool_model, lines)| format!("{}: {} lines", tool_model, lines))
+        .collect()
+}
+
+fn title(repo_name: &str, report: &PeriodReport) -> String {
+    format!(
+        "AI authorship in {}, last {} day{}",
+        repo_name,
+        report.days,
+        if report.days == 1 { "" } else { "s" }
+    )
+}
+
+fn footer(report: &PeriodReport) -> String {
+    let date = |time: i64| {
+        chrono::DateTime::from_timestamp(time, 0)
+            .unwrap_or_default()
+            .format("%Y-%m-%d")
+            .to_string()
+    };
+    format!(
+        "{} from {} to {} · git-ai {}",
+        report.rev,
+        date(report.since),
+        date(report.until),
+        env!("CARGO_PKG_VERSION")
+    )
+}
+
+/// A Block Kit message, with `text` as the notification's fallback
+fn slack_message(repo_name: &str, report: &PeriodReport) -> Value {
+    let facts = facts(report);
+    let fields: Vec<Value> = facts
+        .iter()
+        .map(
+            |(label, value)| json!({ "type": "mrkdwn", "text": format!("*{}*\n{}", label, value) }),
+        )
+        .collect();
+    let mut blocks = vec![
+        json!({ "type": "header", "text": { "type": "plain_text", "text": title(repo_name, report) } }),
+        json!({ "type": "section", "fields": fields }),
+        json!({
+            "type": "section",
+            "text": {
+                "type": "mrkdwn",
+                "text": format!("*AI share by day*\n`{}`", sparkline(&report.daily_ai_percent)),
+            },
+        }),
+    ];
+    let agents = agents(report);
+    if !agents.is_empty() {
+        blocks.push(json!({
+            "type": "section",
+            "text": {
+                "type": "mrkdwn",
+                "text": format!("*By agent*\n{}", agents.iter().map(|a| format!("• {}", a)).collect::<Vec<_>>().join("\n")),
+            },
+        }));
+    }
+    blocks.push(json!({
+        "type": "context",
+        "elements": [{ "type": "mrkdwn", "text": footer(report) }],
+    }));
+    json!({
+        "text": format!("{}: {} of added lines written by AI", title(repo_name, report), facts[0].1),
+        "blocks": blocks,
+    })
+}
+
+/// An Adaptive Card in the message envelope Teams incoming webhooks and workflows take
+fn teams_message(repo_name: &str, report: &PeriodReport) -> Value {
+    let facts: Vec<Value> = facts(report)
+        .into_iter()
+        .map(|(label, value)| json!({ "title": label, "value": value }))
+        .collect();
+    let mut body = vec![
+        json!({ "type": "TextBlock", "text": title(repo_name, report), "size": "Large", "weight": "Bolder", "wrap": true }),
+        json!({ "type": "FactSet", "facts": facts }),
+        json!({ "type": "TextBlock", "text": "AI share by day", "weight": "Bolder", "spacing": "Medium" }),
+        json!({ "type": "TextBlock", "text": sparkline(&report.daily_ai_percent), "fontType": "Monospace" }),
+    ];
+    let agents = agents(report);
+    if !agents.is_empty() {
+        body.push(json!({ "type": "TextBlock", "text": "By agent", "weight": "Bolder", "spacing": "Medium" }));
+        body.push(json!({
+            "type": "TextBlock",
+            "text": agents.iter().map(|a| format!("- {}", a)).collect::<Vec<_>>().join("\n"),
+            "wrap": true,
+        }));
+    }
+    body.push(json!({ "type": "TextBlock", "text": footer(report), "isSubtle": true, "size": "Small", "wrap": true }));
+    json!({
+        "type": "message",
+        "attachments": [{
+            "contentType": "application/vnd.microsoft.card.adaptive",
+            "content": {
+                "$schema": "http://adaptivecards.io/schemas/adaptive-card.json",
+                "type": "AdaptiveCard",
+                "version": "1.4",
+                "body": body,
+            },
+        }],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authorship::stats::{CommitStats, ToolModelHeadlineStats};
+    use std::collections::BTreeMap;
+
+    fn report() -> PeriodReport {
+        PeriodReport {
+            rev: "main".to_string(),
+            days: 7,
+            since: 1_700_000_000,
+            until: 1_700_604_800,
+            commits: 3,
+            stats: CommitStats {
+                git_diff_added_lines: 100,
+                ai_additions: 40,
+                tool_model_breakdown: BTreeMap::from([(
+                    "cursor::gpt-4".to_string(),
+                    ToolModelHeadlineStats {
+                        ai_additions: 40,
+                        ..Default::default()
+                    },
+                )]),
+                ..Default::default()
+            },
+            ai_percent: Some(40),
+            previous_ai_percent: Some(35),
+            daily_ai_percent: vec![None, Some(0), Some(100)],
+            sessions: 2,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_slack_message() {
+        let message = slack_message("web", &report());
+        assert_eq!(
+            message["text"],
+            "AI authorship in web, last 7 days: 40% (up 5 pts) of added lines written by AI"
+        );
+        assert_eq!(message["blocks"][0]["type"], "header");
+        assert_eq!(
+            message["blocks"][1]["fields"][0]["text"],
+            "*AI share*\n40% (up 5 pts)"
+        );
+        assert_eq!(
+            message["blocks"][2]["text"]["text"],
+            "*AI share by day*\n` ▁█`"
+        );
+        assert_eq!(
+            message["blocks"][3]["text"]["text"],
+            "*By agent*\n• cursor::gpt-4: 40 lines"
+        );
+        assert_eq!(message["blocks"][4]["type"], "context");
+    }
+
+    #[test]
+    fn test_teams_message() {
+        let message = teams_message("web", &report());
+        let card = &message["attachments"][0]["content"];
+        assert_eq!(card["type"], "AdaptiveCard");
+        assert_eq!(card["body"][1]["facts"][2]["value"], "100 (40 by AI)");
+        assert_eq!(card["body"][1]["facts"][4]["value"], "no AI lines");
+    }
+}
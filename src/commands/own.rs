@@ -0,0 +1,298 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::Serialize;
+
+use crate::authorship::authorship_log_serialization::AuthorshipLog;
+use crate::authorship::generated_files::GeneratedFileFilter;
+use crate::authorship::query::{QueriedAuthor, query_attribution};
+use crate::commands::tui::line_counts;
+use crate::error::{GitAiError, exit_with_error};
+use crate::git::codeowners::CodeOwners;
+use crate::git::find_repository;
+use crate::git::path_scope::PathScope;
+use crate::git::refs::get_authorship;
+use crate::git::repo_config::RepoConfig;
+use crate::git::repository::Repository;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct OwnershipTotals {
+    pub files: u32,
+    pub lines: u32,
+    pub ai_lines: u32,
+    /// AI lines whose session was driven by someone who isn't one of the owners
+    pub outside_ai_lines: u32,
+}
+
+impl OwnershipTotals {
+    fn add(&mut self, file: &OwnedFile, outside_ai_lines: u32) {
+        self.files += 1;
+        self.lines += file.lines;
+        self.ai_lines += file.ai_lines.len() as u32;
+        self.outside_ai_lines += outside_ai_lines;
+    }
+
+    fn percent(&self, lines: u32) -> f64 {
+        if self.lines == 0 {
+            0.0
+        } else {
+            lines as f64 * 100.0 / self.lines as f64
+        }
+    }
+}
+
+/// The part of an owner's area one CODEOWNERS rule gives them
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OwnedArea {
+    pub pattern: String,
+    #[serde(flatten)]
+    pub totals: OwnershipTotals,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OwnerReport {
+    pub owner: String,
+    #[serde(flatten)]
+    pub totals: OwnershipTotals,
+    /// By rule, in CODEOWNERS order
+    pub areas: Vec<OwnedArea>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OwnershipReport {
+    pub commit: String,
+    /// Sorted by owner
+    pub owners: Vec<OwnerReport>,
+    /// Files no rule gives an owner
+    pub unowned: OwnershipTotals,
+}
+
+/// A file at the reported commit, with the human behind each of its AI lines
+struct OwnedFile {
+    lines: u32,
+    /// The `human_author` of the session that wrote each AI line, if known
+    ai_lines: Vec<Option<String>>,
+}
+
+pub fn handle_own(args: &[String]) {
+    let mut json = false;
+    let mut include_generated = false;
+    let mut commit = None;
+    for arg in args {
+        match arg.as_str() {
+            "--json" => json = true,
+            "--include-generated" => include_generated = true,
+            arg if commit.is_none() && !arg.starts_with('-') => commit = Some(arg.to_string()),
+            arg => {
+                eprintln!("Error: unexpected argument '{}'", arg);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let repo = match find_repository(&Vec::<String>::new()) {
+        Ok(repo) => repo,
+        Err(e) => {
+            exit_with_error("Failed to find repository", &e);
+        }
+    };
+
+    let report = match ownership_report(
+        &repo,
+        commit.as_deref().unwrap_or("HEAD"),
+        include_generated,
+    ) {
+        Ok(report) => report,
+        Err(e) => {
+            exit_with_error("Failed to build the ownership report", &e);
+        }
+    };
+
+    if json {
+        match serde_json::to_string(&report) {
+            Ok(json) => println!("{}", json),
+            Err(e) => exit_with_error("Failed to serialize report", &GitAiError::from(e)),
+        }
+        return;
+    }
+    for owner in &report.owners {
+        println!("{}  {}", owner.owner, describe(&owner.totals));
+        for area in &owner.areas {
+            println!("    {}  {}", area.pattern, describe(&area.totals));
+        }
+    }
+    if report.unowned.files > 0 {
+        println!("(unowned)  {}", describe(&report.unowned));
+    }
+}
+
+fn describe(totals: &OwnershipTotals) -> String {
+    format!(
+        "{} {}, {} lines, {} AI ({:.1}%), {} AI by others ({:.1}%)",
+        totals.files,
+        if totals.files == 1 { "file" } else { "files" },
+        totals.lines,
+        totals.ai_lines,
+        totals.percent(totals.ai_lines),
+        totals.outside_ai_lines,
+        totals.percent(totals.outside_ai_lines)
+    )
+}
+
+/// AI authorship of the files at `rev`, rolled up per CODEOWNERS owner and, within an owner,
+/// per rule. A file with several owners counts towards each of them. Every text file is blamed,
+/// so this costs about as much as `git-ai blame` on the whole tree.
+pub fn ownership_report(
+    repo: &Repository,
+    rev: &str,
+    include_generated: bool,
+) -> Result<OwnershipReport, GitAiError> {
+    let commit = repo.revparse_single(rev)?.peel_to_commit()?.id();
+    let codeowners = CodeOwners::load(repo, &commit)?.ok_or_else(|| {
+        GitAiError::Generic("No CODEOWNERS file in .github/, docs/ or the root".to_string())
+    })?;
+    let team_members = RepoConfig::load(repo).team_members;
+
+    let mut counts = line_counts(repo, &commit)?;
+    if !include_generated {
+        let filter = GeneratedFileFilter::for_paths(repo, counts.iter().map(|(path, _)| path))
+            .unwrap_or_default();
+        counts.retain(|(path, _)| !filter.is_generated(path));
+    }
+    let scope = PathScope::for_repo(repo);
+    counts.retain(|(path, _)| scope.contains(path));
+
+    let mut notes: HashMap<String, Option<AuthorshipLog>> = HashMap::new();
+    let mut owners: BTreeMap<String, (OwnershipTotals, Vec<OwnedArea>)> = BTreeMap::new();
+    let mut unowned = OwnershipTotals::default();
+    for (path, lines) in counts {
+        let mut file = OwnedFile {
+            lines,
+            ai_lines: Vec::new(),
+        };
+        for line in query_attribution(repo, &path, Some(&commit), None)? {
+            let QueriedAuthor::Ai { prompt_hash, .. } = &line.author else {
+                continue;
+            };
+            let human_author = line.commit.as_ref().and_then(|sha| {
+                notes
+                    .entry(sha.clone())
+                    .or_insert_with(|| get_authorship(repo, sha))
+                    .as_ref()?
+                    .metadata
+                    .prompts
+                    .get(prompt_hash)?
+                    .human_author
+                    .clone()
+            });
+            file.ai_lines.push(human_author);
+        }
+
+        let Some(rule) = codeowners
+            .rule_for(&path)
+            .filter(|rule| !rule.owners.is_empty())
+        else {
+            unowned.add(&file, 0);
+            continue;
+        };
+        for owner in &rule.owners {
+            let outside = file
+                .ai_lines
+                .iter()
+                .flatten()
+                .filter(|author| !is_member(owner, author, &team_members))
+                .count() as u32;
+            let (totals, areas) = owners.entry(owner.clone()).or_default();
+            totals.add(&file, outside);
+            let area = match areas.iter().position(|area| area.pattern == rule.pattern) {
+                Some(index) => &mut areas[index],
+                None => {
+                    areas.push(OwnedArea {
+                        pattern: rule.pattern.clone(),
+                        totals: OwnershipTotals::default(),
+                    });
+                    areas.last_mut().unwrap()
+                }
+            };
+            area.totals.add(&file, outside);
+        }
+    }
+
+    let rule_order = |pattern: &str| {
+        codeowners
+            .rules
+            .iter()
+            .position(|rule| rule.pattern == pattern)
+    };
+    Ok(OwnershipReport {
+        commit,
+        owners: owners
+            .into_iter()
+            .map(|(owner, (totals, mut areas))| {
+                areas.sort_by_key(|area| rule_order(&area.pattern));
+                OwnerReport {
+                    owner,
+                    totals,
+                    areas,
+                }
+            })
+            .collect(),
+        unowned,
+    })
+}
+
+/// Whether `author` ("Name <email>") is `owner`, or belongs to it. An email owner matches that
+/// email, `@user` matches the user's GitHub noreply address, and any owner matches the emails
+/// `.gitai` lists for it with `teamMember`.
+fn is_member(owner: &str, author: &str, team_members: &[(String, String)]) -> bool {
+    let email = author
+        .rsplit_once('<')
+        .map(|(_, email)| email.trim_end_matches('>'))
+        .unwrap_or(author)
+        .trim()
+        .to_lowercase();
+    if team_members
+        .iter()
+        .any(|(team, member)| team.eq_ignore_ascii_case(owner) && member.to_lowercase() == email)
+    {
+        return true;
+    }
+    match owner.strip_prefix('@') {
+        Some(user) if !user.contains('/') => email
+            .strip_suffix("@users.noreply.github.com")
+            .is_some_and(|login| login.rsplit('+').next() == Some(user.to_lowercase().as_str())),
+        Some(_) => false,
+        None => owner.to_lowercase() == email,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_member() {
+        let members = vec![("@acme/payments".to_string(), "jane@example.com".to_string())];
+        assert!(is_member(
+            "@acme/payments",
+            "Jane <Jane@example.com>",
+            &members
+        ));
+        assert!(!is_member(
+            "@acme/payments",
+            "Joe <joe@example.com>",
+            &members
+        ));
+        assert!(!is_member(
+            "@acme/core",
+            "Jane <jane@example.com>",
+            &members
+        ));
+        assert!(is_member("joe@example.com", "Joe <joe@example.com>", &[]));
+        assert!(is_member(
+            "@octocat",
+            "Octo <583231+octocat@users.noreply.github.com>",
+            &[]
+        ));
+        assert!(!is_member("@octocat", "Octo <octocat@example.com>", &[]));
+    }
+}
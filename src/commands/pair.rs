@@ -0,0 +1,112 @@
+use crate::authorship::authorship_log::{Participant, ParticipantRole};
+use crate::commands::hooks::commit_hooks::get_commit_default_author;
+use crate::error::{GitAiError, exit_with_error};
+use crate::git::find_repository;
+use crate::git::repository::Repository;
+
+pub fn handle_pair(args: &[String]) {
+    let repo = match find_repository(&Vec::<String>::new()) {
+        Ok(repo) => repo,
+        Err(e) => {
+            exit_with_error("Failed to find repository", &e);
+        }
+    };
+
+    match args.first().map(String::as_str) {
+        Some("show") | None => {
+            let participants = repo.storage.read_pairing();
+            if participants.is_empty() {
+                println!("Not pairing");
+            }
+            for participant in participants {
+                println!("{}  {}", participant.role.as_str(), participant.author);
+            }
+        }
+        Some("start") => {
+            let participants = match parse_participants(&repo, &args[1..]) {
+                Ok(participants) => participants,
+                Err(e) => exit_with_error("Failed to start pairing", &e),
+            };
+            if let Err(e) = repo.storage.write_pairing(&participants) {
+                exit_with_error("Failed to start pairing", &e);
+            }
+            println!(
+                "Pairing: {}. Agent sessions checkpointed from now on record everyone, until \
+                 `git-ai pair stop`",
+                participants
+                    .iter()
+                    .map(|participant| format!(
+                        "{} ({})",
+                        participant.author,
+                        participant.role.as_str()
+                    ))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+        Some("stop") => match repo.storage.clear_pairing() {
+            Ok(()) => println!("Stopped pairing"),
+            Err(e) => exit_with_error("Failed to stop pairing", &e),
+        },
+        Some(other) => {
+            eprintln!(
+                "Error: unknown pair command '{}', expected show, start or stop",
+                other
+            );
+            std::process::exit(1);
+        }
+    }
+}
+
+/// The participants `pair start` names. The driver defaults to the git user, and there has to
+/// be someone to pair with.
+fn parse_participants(repo: &Repository, args: &[String]) -> Result<Vec<Participant>, GitAiError> {
+    let mut driver = None;
+    let mut navigators = Vec::new();
+    let mut i = 0;
+    while i < args.len() {
+        let role = match args[i].as_str() {
+            "--driver" => ParticipantRole::Driver,
+            "--navigator" => ParticipantRole::Navigator,
+            arg => {
+                return Err(GitAiError::Generic(format!(
+                    "unexpected argument '{}'",
+                    arg
+                )));
+            }
+        };
+        let author = args
+            .get(i + 1)
+            .map(|author| author.trim())
+            .filter(|author| !author.is_empty())
+            .ok_or_else(|| GitAiError::Generic(format!("{} requires a value", args[i])))?;
+        match role {
+            ParticipantRole::Driver => driver = Some(author.to_string()),
+            ParticipantRole::Navigator => navigators.push(author.to_string()),
+        }
+        i += 2;
+    }
+    if navigators.is_empty() {
+        return Err(GitAiError::Generic(
+            "name at least one --navigator to pair with".to_string(),
+        ));
+    }
+
+    let driver = driver.unwrap_or_else(|| get_commit_default_author(repo, &[]));
+    let mut participants = vec![Participant {
+        author: driver,
+        role: ParticipantRole::Driver,
+    }];
+    for author in navigators {
+        if participants
+            .iter()
+            .all(|participant| participant.author != author)
+        {
+            participants.push(Participant {
+                author,
+                role: ParticipantRole::Navigator,
+            });
+        }
+    }
+    Ok(participants)
+}
@@ -0,0 +1,94 @@
+use crate::authorship::commit_summary::summarize_staged;
+use crate::git::find_repository;
+use crate::git::repository::Repository;
+use crate::utils::debug_log;
+
+/// `git-ai prepare-commit-msg <file> [<source> [<sha>]]`, run from a `prepare-commit-msg` hook.
+/// Adds a commented summary of who wrote the staged lines to the message the editor opens with.
+/// It never fails the commit: anything that goes wrong leaves the message alone.
+pub fn handle_prepare_commit_msg(args: &[String]) {
+    let Some(message_file) = args.first() else {
+        eprintln!("Error: prepare-commit-msg requires the commit message file");
+        std::process::exit(1);
+    };
+
+    // Git sets GIT_EDITOR=: when the message won't be edited, and its comments would then be
+    // committed as they are
+    if std::env::var("GIT_EDITOR").is_ok_and(|editor| editor == ":") {
+        return;
+    }
+    // Shadow mode leaves what git does alone, commit messages included
+    if crate::config::Config::get().is_shadow_mode() {
+        return;
+    }
+
+    let repo = match find_repository(&Vec::<String>::new()) {
+        Ok(repo) => repo,
+        Err(e) => {
+            debug_log(&format!("prepare-commit-msg: no repository: {}", e));
+            return;
+        }
+    };
+    let summary = match summarize_staged(&repo) {
+        Ok(summary) => summary,
+        Err(e) => {
+            debug_log(&format!("prepare-commit-msg: failed to summarize: {}", e));
+            return;
+        }
+    };
+    if summary.ai_lines == 0 && summary.human_lines == 0 {
+        return;
+    }
+
+    let Ok(message) = std::fs::read_to_string(message_file) else {
+        return;
+    };
+    let comment_line = format!("{} {}", comment_char(&repo), summary.describe());
+    if let Err(e) = std::fs::write(message_file, with_comment(&message, &comment_line)) {
+        debug_log(&format!(
+            "prepare-commit-msg: failed to write message: {}",
+            e
+        ));
+    }
+}
+
+/// The character git strips comment lines by, per `core.commentChar`
+fn comment_char(repo: &Repository) -> String {
+    repo.config_get_str("core.commentChar")
+        .ok()
+        .flatten()
+        .filter(|comment_char| !comment_char.is_empty() && comment_char != "auto")
+        .unwrap_or_else(|| "#".to_string())
+}
+
+/// `message` with `comment_line` ahead of git's own comments, or at the end if it has none
+fn with_comment(message: &str, comment_line: &str) -> String {
+    let comment_prefix: String = comment_line.chars().take(1).collect();
+    let mut lines: Vec<&str> = message.lines().collect();
+    let position = lines
+        .iter()
+        .position(|line| line.starts_with(&comment_prefix))
+        .unwrap_or(lines.len());
+    lines.insert(position, comment_line);
+    let mut result = lines.join("\n");
+    result.push('\n');
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_with_comment_goes_ahead_of_git_comments() {
+        let message = "\n# Please enter the commit message for your changes.\n#\n";
+        assert_eq!(
+            with_comment(message, "# 2 AI lines, 1 human line"),
+            "\n# 2 AI lines, 1 human line\n# Please enter the commit message for your changes.\n#\n"
+        );
+        assert_eq!(
+            with_comment("Fix bug\n", "; 2 AI lines, 1 human line"),
+            "Fix bug\n; 2 AI lines, 1 human line\n"
+        );
+    }
+}
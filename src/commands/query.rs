@@ -0,0 +1,160 @@
+use crate::authorship::query::{QueriedAuthor, QueriedLine, query_attribution, stream_attribution};
+use crate::error::exit_with_error;
+use crate::git::find_repository;
+use crate::git::repository::Repository;
+use std::io::Write;
+
+pub fn handle_query(args: &[String]) {
+    let mut file_path = None;
+    let mut commit = None;
+    let mut line_range = None;
+    let mut json = false;
+    let mut stream = false;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--json" => json = true,
+            "--stream" => stream = true,
+            "--commit" | "-L" if i + 1 >= args.len() => {
+                eprintln!("Error: {} requires a value", args[i]);
+                std::process::exit(1);
+            }
+            "--commit" => {
+                commit = Some(args[i + 1].clone());
+                i += 1;
+            }
+            "-L" => {
+                line_range = match parse_line_range(&args[i + 1]) {
+                    Some(range) => Some(range),
+                    None => {
+                        eprintln!(
+                            "Error: invalid line range '{}', expected <start>,<end>",
+                            args[i + 1]
+                        );
+                        std::process::exit(1);
+                    }
+                };
+                i += 1;
+            }
+            arg if file_path.is_none() && !arg.starts_with('-') => {
+                file_path = Some(arg.to_string());
+            }
+            arg => {
+                eprintln!("Error: unexpected argument '{}'", arg);
+                std::process::exit(1);
+            }
+        }
+        i += 1;
+    }
+
+    let Some(file_path) = file_path else {
+        eprintln!("Error: query requires a file argument");
+        std::process::exit(1);
+    };
+
+    let repo = match find_repository(&Vec::<String>::new()) {
+        Ok(repo) => repo,
+        Err(e) => {
+            exit_with_error("Failed to find repository", &e);
+        }
+    };
+
+    // A bare repository has no working copy to query, so read the file as of HEAD
+    if commit.is_none() && repo.is_bare() {
+        commit = Some("HEAD".to_string());
+    }
+
+    if stream {
+        stream_lines(&repo, &file_path, commit.as_deref(), line_range);
+        return;
+    }
+
+    let lines = match query_attribution(&repo, &file_path, commit.as_deref(), line_range) {
+        Ok(lines) => lines,
+        Err(e) => {
+            exit_with_error("Query failed", &e);
+        }
+    };
+
+    if json {
+        match serde_json::to_string(&lines) {
+            Ok(json) => println!("{}", json),
+            Err(e) => {
+                eprintln!("Failed to serialize query result: {}", e);
+                std::process::exit(1);
+            }
+        }
+    } else {
+        print_lines(&lines);
+    }
+}
+
+/// One JSON object per blame hunk, printed as soon as it resolves
+fn stream_lines(
+    repo: &Repository,
+    file_path: &str,
+    commit: Option<&str>,
+    line_range: Option<(u32, u32)>,
+) {
+    let hunks = match stream_attribution(repo, file_path, commit, line_range) {
+        Ok(hunks) => hunks,
+        Err(e) => {
+            exit_with_error("Query failed", &e);
+        }
+    };
+    let mut stdout = std::io::stdout().lock();
+    for hunk in hunks {
+        let json = match hunk.and_then(|hunk| Ok(serde_json::to_string(&hunk)?)) {
+            Ok(json) => json,
+            Err(e) => {
+                exit_with_error("Query failed", &e);
+            }
+        };
+        // Stop quietly once the reader goes away
+        if writeln!(stdout, "{}", json)
+            .and_then(|_| stdout.flush())
+            .is_err()
+        {
+            return;
+        }
+    }
+}
+
+/// `<start>,<end>`, or a single line number
+pub(crate) fn parse_line_range(value: &str) -> Option<(u32, u32)> {
+    let (start, end) = value.split_once(',').unwrap_or((value, value));
+    let (start, end) = (start.trim().parse().ok()?, end.trim().parse().ok()?);
+    (start >= 1 && start <= end).then_some((start, end))
+}
+
+fn print_lines(lines: &[QueriedLine]) {
+    let authors: Vec<String> = lines
+        .iter()
+        .map(|line| match &line.author {
+            QueriedAuthor::Human { name } => name.clone(),
+            QueriedAuthor::Ai { tool, model, .. } => format!("{} ({})", tool, model),
+        })
+        .collect();
+    let author_width = authors.iter().map(|a| a.len()).max().unwrap_or(0);
+    let line_width = lines.last().map_or(1, |l| l.line.to_string().len());
+
+    for (line, author) in lines.iter().zip(&authors) {
+        let commit = line
+            .commit
+            .as_deref()
+            .map_or("-", |sha| &sha[..sha.len().min(7)]);
+        let confidence = line
+            .confidence
+            .map(|c| format!(" {}%", c))
+            .unwrap_or_default();
+        println!(
+            "{:>line_width$}  {:<author_width$}  {:<11}  {}{}",
+            line.line,
+            author,
+            line.source.as_str(),
+            commit,
+            confidence
+        );
+    }
+}
@@ -0,0 +1,121 @@
+use crate::authorship::replay::{ReplayReport, replay_history};
+use crate::error::exit_with_error;
+use crate::git::find_repository_in_path;
+
+const DEFAULT_COMMITS: usize = 100;
+
+pub fn handle_replay(args: &[String]) {
+    let mut repo_path = None;
+    let mut commits = DEFAULT_COMMITS;
+    let mut json = false;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--json" => json = true,
+            "--commits" if i + 1 >= args.len() => {
+                eprintln!("Error: --commits requires a value");
+                std::process::exit(1);
+            }
+            "--commits" => {
+                commits = match args[i + 1].parse() {
+                    Ok(count) if count > 0 => count,
+                    _ => {
+                        eprintln!(
+                            "Error: invalid commit count '{}', expected a positive number",
+                            args[i + 1]
+                        );
+                        std::process::exit(1);
+                    }
+                };
+                i += 1;
+            }
+            arg if repo_path.is_none() && !arg.starts_with('-') => {
+                repo_path = Some(arg.to_string());
+            }
+            arg => {
+                eprintln!("Error: unexpected argument '{}'", arg);
+                std::process::exit(1);
+            }
+        }
+        i += 1;
+    }
+
+    let repo_path = repo_path.unwrap_or_else(|| ".".to_string());
+    let repo = match find_repository_in_path(&repo_path) {
+        Ok(repo) => repo,
+        Err(e) => {
+            exit_with_error(&format!("Failed to find repository at {}", repo_path), &e);
+        }
+    };
+
+    let report = match replay_history(&repo, commits) {
+        Ok(report) => report,
+        Err(e) => {
+            exit_with_error("Replay failed", &e);
+        }
+    };
+
+    if json {
+        match serde_json::to_string(&report) {
+            Ok(json) => println!("{}", json),
+            Err(e) => {
+                eprintln!("Failed to serialize replay report: {}", e);
+                std::process::exit(1);
+            }
+        }
+    } else {
+        print_report(&report);
+    }
+
+    if !report.violations.is_empty() {
+        std::process::exit(1);
+    }
+}
+
+fn print_report(report: &ReplayReport) {
+    println!(
+        "Replayed {} commits: {} updates, {} non-text files skipped",
+        report.commits.len(),
+        report.updates,
+        report.skipped_files
+    );
+    let average = if report.updates == 0 {
+        0.0
+    } else {
+        report.update_ms / report.updates as f64
+    };
+    println!(
+        "Attribution: {:.1}ms total, {:.3}ms per update; replay: {:.1}ms total",
+        report.update_ms, average, report.total_ms
+    );
+
+    if !report.slowest.is_empty() {
+        println!();
+        println!("Slowest updates:");
+        for update in &report.slowest {
+            println!(
+                "  {:>9.3}ms  {:>9} bytes  {} {}",
+                update.ms,
+                update.bytes,
+                &update.commit[..update.commit.len().min(8)],
+                update.file
+            );
+        }
+    }
+
+    println!();
+    if report.violations.is_empty() {
+        println!("No invariant violations");
+    } else {
+        println!("{} invariant violations:", report.violations.len());
+        for violation in &report.violations {
+            println!(
+                "  {} {}: {}",
+                &violation.commit[..violation.commit.len().min(8)],
+                violation.file,
+                violation.message
+            );
+        }
+    }
+}
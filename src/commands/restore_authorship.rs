@@ -0,0 +1,457 @@
+use crate::authorship::rebase_authorship::rewrite_authorship_after_squash_or_rebase;
+use crate::error::{GitAiError, exit_with_error};
+use crate::git::find_repository;
+use crate::git::hosting::{HostingProvider, fetch_review_head};
+use crate::git::repository::{Repository, exec_git_stdin};
+use crate::git::sync_authorship::fetch_authorship_notes;
+use std::collections::HashSet;
+
+const USAGE: &str = "Usage: git-ai restore-authorship <base_branch> <squash_sha> [--head <sha> | --pr <number>] [--remote <name>] [--provider <github|gitlab|bitbucket>] [--no-push]";
+
+/// Where the head of the squash-merged branch comes from
+#[derive(Debug, Clone, PartialEq)]
+pub enum SourceHead {
+    /// A commit already in the repository, such as the local branch's tip
+    Sha(String),
+    /// A pull or merge request's head, fetched from the provider's review ref (which the host
+    /// keeps after the branch is deleted)
+    PullRequest(u32),
+    /// Whatever pre-squash head can still be found: a review ref, a remote-tracking ref
+    /// or a reflog entry whose changes are the ones the squash commit made
+    Search,
+}
+
+pub fn handle_restore_authorship(args: &[String]) {
+    let mut base_branch = None;
+    let mut squash_sha = None;
+    let mut source = SourceHead::Search;
+    let mut remote = "origin".to_string();
+    let mut provider = None;
+    let mut push = true;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--head" | "--pr" | "--remote" | "--provider" => {
+                let Some(value) = args.get(i + 1) else {
+                    eprintln!("Error: {} requires a value", args[i]);
+                    eprintln!("{}", USAGE);
+                    std::process::exit(1);
+                };
+                match args[i].as_str() {
+                    "--head" => source = SourceHead::Sha(value.clone()),
+                    "--pr" => match value.trim_start_matches(['#', '!']).parse() {
+                        Ok(number) => source = SourceHead::PullRequest(number),
+                        Err(_) => {
+                            eprintln!(
+                                "Error: --pr expects a pull or merge request number, got '{}'",
+                                value
+                            );
+                            std::process::exit(1);
+                        }
+                    },
+                    "--provider" => match HostingProvider::parse(value) {
+                        Ok(parsed) => provider = Some(parsed),
+                        Err(e) => {
+                            eprintln!("Error: {}", e);
+                            std::process::exit(1);
+                        }
+                    },
+                    _ => remote = value.clone(),
+                }
+                i += 2;
+            }
+            "--no-push" => {
+                push = false;
+                i += 1;
+            }
+            arg if !arg.starts_with('-') && base_branch.is_none() => {
+                base_branch = Some(arg.to_string());
+                i += 1;
+            }
+            arg if !arg.starts_with('-') && squash_sha.is_none() => {
+                squash_sha = Some(arg.to_string());
+                i += 1;
+            }
+            arg => {
+                eprintln!("Unknown restore-authorship argument: {}", arg);
+                eprintln!("{}", USAGE);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let (Some(base_branch), Some(squash_sha)) = (base_branch, squash_sha) else {
+        eprintln!("Error: base_branch and squash_sha are required");
+        eprintln!("{}", USAGE);
+        std::process::exit(1);
+    };
+
+    let repo = match find_repository(&Vec::<String>::new()) {
+        Ok(repo) => repo,
+        Err(e) => {
+            exit_with_error("Failed to find repository", &e);
+        }
+    };
+
+    let provider = provider.unwrap_or_else(|| HostingProvider::detect(&repo, &remote));
+    match restore_authorship(
+        &repo,
+        &base_branch,
+        &squash_sha,
+        &source,
+        &remote,
+        provider,
+        push,
+    ) {
+        Ok(head_sha) => {
+            println!(
+                "Restored authorship for {} from {}",
+                &squash_sha[..squash_sha.len().min(8)],
+                &head_sha[..head_sha.len().min(8)]
+            );
+            if push {
+                println!("Pushed authorship notes to {}", remote);
+            }
+        }
+        Err(e) => {
+            exit_with_error("Restore authorship failed", &e);
+        }
+    }
+}
+
+/// Writes the authorship note for `squash_sha`, a commit the remote made when it squash-merged
+/// a branch into `base_branch`, from the authorship of the branch's commits, and pushes it to
+/// `remote` if `push`. `provider` says where the remote keeps review heads. Returns the branch
+/// head the note was built from.
+pub fn restore_authorship(
+    repo: &Repository,
+    base_branch: &str,
+    squash_sha: &str,
+    source: &SourceHead,
+    remote: &str,
+    provider: HostingProvider,
+    push: bool,
+) -> Result<String, GitAiError> {
+    // The squash commit only exists on the remote until the base branch is fetched
+    repo.fetch_branch(base_branch, remote)?;
+    let head_sha = match source {
+        SourceHead::Sha(sha) => sha.clone(),
+        SourceHead::PullRequest(number) => fetch_review_head(repo, provider, remote, *number)?,
+        SourceHead::Search => find_source_head(repo, squash_sha, remote, provider)?,
+    };
+    fetch_authorship_notes(repo, remote)?;
+
+    let squash_commit = repo.find_commit(squash_sha.to_string())?;
+    if squash_commit.parents().count() > 1 {
+        return Err(GitAiError::Generic(format!(
+            "{} is a merge commit, not a squash merge",
+            squash_sha
+        )));
+    }
+
+    rewrite_authorship_after_squash_or_rebase(
+        repo,
+        "",
+        &format!("refs/remotes/{}/{}", remote, base_branch),
+        &head_sha,
+        squash_sha,
+        false,
+    )?;
+    if push {
+        repo.push_authorship(remote)?;
+    }
+    Ok(head_sha)
+}
+
+/// Finds the head of the branch that was squash-merged as `squash_sha` when it's no longer a
+/// local branch: the review named in the commit message, then any provider's review ref or a
+/// remote-tracking ref, then the reflog. A candidate matches if it has the squash
+/// commit's tree, or if the branch's changes since it left the base are the squash commit's.
+fn find_source_head(
+    repo: &Repository,
+    squash_sha: &str,
+    remote: &str,
+    provider: HostingProvider,
+) -> Result<String, GitAiError> {
+    let squash_commit = repo.find_commit(squash_sha.to_string())?;
+    let parent_sha = squash_commit.parent(0)?.id();
+    let squash_tree = repo.git(&["rev-parse", &format!("{}^{{tree}}", squash_sha)])?;
+    let squash_tree = squash_tree.trim();
+    let squash_patch_id = patch_id(repo, &parent_sha, squash_sha)?;
+
+    let review_number = provider.number_from_message(&squash_commit.message()?);
+    let mut candidates = Vec::new();
+    if let Some(number) = review_number {
+        candidates.extend(fetch_review_head(repo, provider, remote, number).ok());
+    }
+    let mut ref_args = vec!["for-each-ref", "--format=%(objectname)", "refs/remotes"];
+    ref_args.extend(HostingProvider::ALL.iter().flat_map(|p| p.ref_prefixes()));
+    candidates.extend(repo.git(&ref_args)?.lines().map(str::to_string));
+    candidates.extend(
+        repo.git(&["reflog", "show", "--all", "--format=%H"])
+            .unwrap_or_default()
+            .lines()
+            .map(str::to_string),
+    );
+
+    let mut seen = HashSet::new();
+    for candidate in candidates {
+        let candidate = candidate.trim().to_string();
+        if candidate.is_empty() || candidate == squash_sha || !seen.insert(candidate.clone()) {
+            continue;
+        }
+        // Commits already on the base branch aren't the branch's head
+        if repo
+            .git(&["merge-base", "--is-ancestor", &candidate, &parent_sha])
+            .is_ok()
+        {
+            continue;
+        }
+        let Ok(tree) = repo.git(&["rev-parse", &format!("{}^{{tree}}", candidate)]) else {
+            continue;
+        };
+        if tree.trim() == squash_tree {
+            return Ok(candidate);
+        }
+        let Ok(fork_point) = repo.merge_base(candidate.clone(), parent_sha.clone()) else {
+            continue;
+        };
+        if squash_patch_id.is_some()
+            && patch_id(repo, &fork_point, &candidate).ok().flatten() == squash_patch_id
+        {
+            return Ok(candidate);
+        }
+    }
+
+    let fetch_hint = match review_number {
+        Some(number) => format!(
+            "it looks like {0} #{1}; if {2} doesn't serve {3}, fetch the branch's head from \
+             wherever it was pushed",
+            provider.review_name(),
+            number,
+            remote,
+            provider.head_ref(number)
+        ),
+        None => format!(
+            "fetch the {}'s head (git fetch {} {}) or the branch it was merged from",
+            provider.review_name(),
+            remote,
+            provider.head_ref(0).replace("/0/", "/<number>/")
+        ),
+    };
+    Err(GitAiError::Generic(format!(
+        "Couldn't find the branch that was squash-merged as {} in the reflog, review refs or \
+         remote-tracking refs; {}, then pass it with --head <sha> (or use --pr <number>)",
+        squash_sha, fetch_hint
+    )))
+}
+
+/// The stable patch ID of the diff from `from` to `to`, or `None` if they don't differ
+fn patch_id(repo: &Repository, from: &str, to: &str) -> Result<Option<String>, GitAiError> {
+    let diff = repo.git_bytes(&["diff", "--full-index", from, to])?;
+    let mut args = repo.global_args_for_exec();
+    args.push("patch-id".to_string());
+    args.push("--stable".to_string());
+    let output = exec_git_stdin(&args, &diff)?;
+    Ok(String::from_utf8(output.stdout)?
+        .split_whitespace()
+        .next()
+        .map(str::to_string))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::test_utils::{TmpRemote, TmpRepo};
+
+    /// A dev clone with an AI commit on `feature`, pushed and squash-merged into `main` by
+    /// the remote. Returns the squash commit and the branch head.
+    fn squash_merged_feature(remote: &TmpRemote) -> (TmpRepo, String, String) {
+        squash_merged_feature_with_message(remote, "Feature (#1)")
+    }
+
+    fn squash_merged_feature_with_message(
+        remote: &TmpRemote,
+        message: &str,
+    ) -> (TmpRepo, String, String) {
+        let dev = TmpRepo::new().unwrap();
+        dev.write_file("app.txt", "base\n", true).unwrap();
+        dev.trigger_checkpoint_with_author("Test User").unwrap();
+        dev.commit_with_message("Base").unwrap();
+        dev.git_command(&["branch", "-M", "main"]).unwrap();
+        dev.add_remote("origin", remote).unwrap();
+        dev.push("origin", "main").unwrap();
+
+        dev.create_branch("feature").unwrap();
+        dev.write_file("app.txt", "base\nfrom claude\n", true)
+            .unwrap();
+        dev.trigger_checkpoint_with_ai("Claude", Some("claude-3-sonnet"), Some("cursor"))
+            .unwrap();
+        dev.commit_with_message("AI feature").unwrap();
+        dev.push("origin", "feature").unwrap();
+        let head_sha = dev.head_commit_sha().unwrap();
+
+        let squash_sha = remote.squash_merge("feature", "main", message).unwrap();
+        (dev, squash_sha, head_sha)
+    }
+
+    #[test]
+    fn test_restores_from_local_branch_head() {
+        let remote = TmpRemote::new().unwrap();
+        let (dev, squash_sha, head_sha) = squash_merged_feature(&remote);
+
+        let source = SourceHead::Sha(head_sha.clone());
+        let used = restore_authorship(
+            dev.gitai_repo(),
+            "main",
+            &squash_sha,
+            &source,
+            "origin",
+            HostingProvider::GitHub,
+            false,
+        )
+        .unwrap();
+        assert_eq!(used, head_sha);
+
+        let note = crate::git::refs::show_authorship_note(dev.gitai_repo(), &squash_sha).unwrap();
+        assert!(note.contains("app.txt"), "{}", note);
+        // Not pushed
+        assert!(remote.authorship_log(&squash_sha).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_restores_from_pull_request_head_and_pushes() {
+        let remote = TmpRemote::new().unwrap();
+        let (dev, squash_sha, head_sha) = squash_merged_feature(&remote);
+        // The host keeps the PR head after the branch is deleted
+        dev.git_command(&["push", "origin", "feature:refs/pull/1/head"])
+            .unwrap();
+        dev.git_command(&["push", "origin", "--delete", "feature"])
+            .unwrap();
+
+        // A clone that never had the branch
+        let other = remote.clone_into().unwrap();
+        let used = restore_authorship(
+            other.gitai_repo(),
+            "main",
+            &squash_sha,
+            &SourceHead::PullRequest(1),
+            "origin",
+            HostingProvider::GitHub,
+            true,
+        )
+        .unwrap();
+        assert_eq!(used, head_sha);
+
+        let log = remote.authorship_log(&squash_sha).unwrap().unwrap();
+        let file = &log.attestations[0];
+        assert_eq!(file.file_path, "app.txt");
+        let prompt = &log.metadata.prompts[&file.entries[0].hash];
+        assert_eq!(prompt.agent_id.tool, "cursor");
+    }
+
+    #[test]
+    fn test_finds_deleted_branch_in_reflog() {
+        let remote = TmpRemote::new().unwrap();
+        let (dev, squash_sha, head_sha) = squash_merged_feature(&remote);
+        // Branch deleted locally and on the remote, so only the reflog remembers it
+        dev.git_command(&["checkout", "main"]).unwrap();
+        dev.git_command(&["branch", "-D", "feature"]).unwrap();
+        dev.git_command(&["push", "origin", "--delete", "feature"])
+            .unwrap();
+
+        let used = restore_authorship(
+            dev.gitai_repo(),
+            "main",
+            &squash_sha,
+            &SourceHead::Search,
+            "origin",
+            HostingProvider::GitHub,
+            true,
+        )
+        .unwrap();
+        assert_eq!(used, head_sha);
+        assert!(remote.authorship_log(&squash_sha).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_finds_pull_request_named_in_subject() {
+        let remote = TmpRemote::new().unwrap();
+        let (dev, squash_sha, head_sha) = squash_merged_feature(&remote);
+        dev.git_command(&["push", "origin", "feature:refs/pull/1/head"])
+            .unwrap();
+        dev.git_command(&["push", "origin", "--delete", "feature"])
+            .unwrap();
+
+        let other = remote.clone_into().unwrap();
+        let used = restore_authorship(
+            other.gitai_repo(),
+            "main",
+            &squash_sha,
+            &SourceHead::Search,
+            "origin",
+            HostingProvider::GitHub,
+            false,
+        )
+        .unwrap();
+        assert_eq!(used, head_sha);
+    }
+
+    #[test]
+    fn test_finds_gitlab_merge_request_named_in_message() {
+        let remote = TmpRemote::new().unwrap();
+        let (dev, squash_sha, head_sha) = squash_merged_feature_with_message(
+            &remote,
+            "Feature\n\nSee merge request group/project!2",
+        );
+        dev.git_command(&["push", "origin", "feature:refs/merge-requests/2/head"])
+            .unwrap();
+        dev.git_command(&["push", "origin", "--delete", "feature"])
+            .unwrap();
+
+        let other = remote.clone_into().unwrap();
+        let used = restore_authorship(
+            other.gitai_repo(),
+            "main",
+            &squash_sha,
+            &SourceHead::Search,
+            "origin",
+            HostingProvider::GitLab,
+            false,
+        )
+        .unwrap();
+        assert_eq!(used, head_sha);
+        assert!(
+            other
+                .gitai_repo()
+                .git(&["rev-parse", "--verify", "refs/gitlab/mr/2"])
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_says_what_to_fetch_when_head_is_gone() {
+        let remote = TmpRemote::new().unwrap();
+        let (dev, squash_sha, _) = squash_merged_feature(&remote);
+        dev.git_command(&["push", "origin", "--delete", "feature"])
+            .unwrap();
+
+        // A fresh clone never saw the branch, and the remote has no pull request refs
+        let other = remote.clone_into().unwrap();
+        let err = restore_authorship(
+            other.gitai_repo(),
+            "main",
+            &squash_sha,
+            &SourceHead::Search,
+            "origin",
+            HostingProvider::GitHub,
+            false,
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("pull request #1"), "{}", err);
+        assert!(err.contains("refs/pull/1/head"), "{}", err);
+        assert!(err.contains("--head"), "{}", err);
+    }
+}
@@ -0,0 +1,267 @@
+use crate::authorship::review::{ReviewMark, add_review_mark, review_range, unreviewed_ai_hunks};
+use crate::error::{GitAiError, exit_with_error};
+use crate::git::find_repository;
+use crate::git::repository::Repository;
+
+pub fn handle_review(args: &[String]) {
+    match args.first().map(String::as_str) {
+        Some("list") => handle_list(&args[1..]),
+        Some("mark") => handle_mark(&args[1..]),
+        Some(other) => {
+            eprintln!(
+                "Error: unknown review command '{}', expected list or mark",
+                other
+            );
+            std::process::exit(1);
+        }
+        None => {
+            eprintln!("Error: expected a review command: list or mark");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// `git-ai review list [<branch>|<base>..<head>]`: the AI hunks a branch adds that still need a
+/// human to look at them, riskiest first
+fn handle_list(args: &[String]) {
+    let mut json = false;
+    let mut include_generated = false;
+    let mut base = None;
+    let mut spec = None;
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--json" => json = true,
+            "--include-generated" => include_generated = true,
+            "--base" if i + 1 >= args.len() => {
+                eprintln!("Error: --base requires a value");
+                std::process::exit(1);
+            }
+            "--base" => {
+                base = Some(args[i + 1].clone());
+                i += 1;
+            }
+            arg if spec.is_none() && !arg.starts_with('-') => spec = Some(arg.to_string()),
+            arg => {
+                eprintln!("Error: unexpected argument '{}'", arg);
+                std::process::exit(1);
+            }
+        }
+        i += 1;
+    }
+
+    let repo = match find_repository(&Vec::<String>::new()) {
+        Ok(repo) => repo,
+        Err(e) => {
+            exit_with_error("Failed to find repository", &e);
+        }
+    };
+
+    let (base, head) = match review_range(&repo, spec.as_deref(), base.as_deref()) {
+        Ok(range) => range,
+        Err(e) => {
+            exit_with_error("Failed to resolve the range to review", &e);
+        }
+    };
+    let hunks = match unreviewed_ai_hunks(&repo, &base, &head, include_generated) {
+        Ok(hunks) => hunks,
+        Err(e) => {
+            exit_with_error("Failed to list AI hunks", &e);
+        }
+    };
+
+    if json {
+        match serde_json::to_string(&hunks) {
+            Ok(json) => println!("{}", json),
+            Err(e) => exit_with_error("Failed to serialize hunks", &GitAiError::from(e)),
+        }
+        return;
+    }
+    for hunk in &hunks {
+        let location = if hunk.start == hunk.end {
+            format!("{}:{}", hunk.file, hunk.start)
+        } else {
+            format!("{}:{}-{}", hunk.file, hunk.start, hunk.end)
+        };
+        let sensitive = hunk
+            .sensitive
+            .as_ref()
+            .map(|reason| format!(" [{}]", reason))
+            .unwrap_or_default();
+        println!(
+            "{:>4}  {}  {} {}, {} {} ({}){}",
+            hunk.risk,
+            location,
+            hunk.lines,
+            if hunk.lines == 1 { "line" } else { "lines" },
+            hunk.tool,
+            hunk.model,
+            &hunk.commit[..hunk.commit.len().min(7)],
+            sensitive
+        );
+    }
+    let lines: u32 = hunks.iter().map(|hunk| hunk.lines).sum();
+    println!(
+        "{} unreviewed AI {} ({} lines) in {}..{}",
+        hunks.len(),
+        if hunks.len() == 1 { "hunk" } else { "hunks" },
+        lines,
+        &base[..7],
+        &head[..7]
+    );
+}
+
+/// `git-ai review mark <commit> <file>[:<start>[-<end>]] [--by <user>]`: records that lines the
+/// commit added have been reviewed. With `--staged` instead of a commit, the lines are
+/// uncommitted ones in the working tree, and the mark goes into the next commit.
+fn handle_mark(args: &[String]) {
+    let mut by = None;
+    let mut staged = false;
+    let mut positional = Vec::new();
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--staged" => staged = true,
+            "--by" if i + 1 >= args.len() => {
+                eprintln!("Error: --by requires a value");
+                std::process::exit(1);
+            }
+            "--by" => {
+                by = Some(args[i + 1].clone());
+                i += 1;
+            }
+            arg if positional.len() < 2 && !arg.starts_with('-') => positional.push(arg),
+            arg => {
+                eprintln!("Error: unexpected argument '{}'", arg);
+                std::process::exit(1);
+            }
+        }
+        i += 1;
+    }
+    let (commit, target) = match positional[..] {
+        [target] if staged => (None, target),
+        [commit, target] if !staged => (Some(commit), target),
+        _ => {
+            eprintln!(
+                "Usage: git-ai review mark (<commit> | --staged) <file>[:<start>[-<end>]] [--by <user>]"
+            );
+            std::process::exit(1);
+        }
+    };
+
+    let repo = match find_repository(&Vec::<String>::new()) {
+        Ok(repo) => repo,
+        Err(e) => {
+            exit_with_error("Failed to find repository", &e);
+        }
+    };
+    let by = match by.or_else(|| repo.config_get_str("user.name").ok().flatten()) {
+        Some(by) if !by.trim().is_empty() => by.trim().to_string(),
+        _ => {
+            eprintln!("Error: pass --by <user>, or set user.name");
+            std::process::exit(1);
+        }
+    };
+
+    let (commit, mark) = match parse_mark(&repo, commit, target, by) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            exit_with_error("Invalid review mark", &e);
+        }
+    };
+    let recorded = match &commit {
+        Some(commit) => add_review_mark(&repo, commit, &mark),
+        None => {
+            let base_commit = repo
+                .head()
+                .and_then(|head| head.target())
+                .unwrap_or_else(|_| "initial".to_string());
+            repo.storage
+                .working_log_for_base_commit(&base_commit)
+                .append_review_mark(&mark)
+        }
+    };
+    if let Err(e) = recorded {
+        exit_with_error("Failed to record review mark", &e);
+    }
+    let lines = match mark.lines {
+        Some((start, end)) if start == end => format!(":{}", start),
+        Some((start, end)) => format!(":{}-{}", start, end),
+        None => String::new(),
+    };
+    let location = match &commit {
+        Some(commit) => format!("in {}", &commit[..7]),
+        None => "for the next commit".to_string(),
+    };
+    println!(
+        "Marked {}{} {} as reviewed by {}",
+        mark.file, lines, location, mark.by
+    );
+}
+
+/// The full commit sha (None for the working tree) and the mark, checking the file and lines
+/// exist there
+fn parse_mark(
+    repo: &Repository,
+    commit: Option<&str>,
+    target: &str,
+    by: String,
+) -> Result<(Option<String>, ReviewMark), GitAiError> {
+    let (file, lines) = match target.rsplit_once(':') {
+        Some((file, range))
+            if !range.is_empty() && range.chars().all(|c| c.is_ascii_digit() || c == '-') =>
+        {
+            let (start, end) = range.split_once('-').unwrap_or((range, range));
+            let (Ok(start), Ok(end)) = (start.parse::<u32>(), end.parse::<u32>()) else {
+                return Err(GitAiError::Generic(format!(
+                    "invalid line range '{}'",
+                    range
+                )));
+            };
+            if start == 0 || start > end {
+                return Err(GitAiError::Generic(format!(
+                    "invalid line range '{}'",
+                    range
+                )));
+            }
+            (file, Some((start, end)))
+        }
+        _ => (target, None),
+    };
+
+    let (commit, content, location) = match commit {
+        Some(commit) => {
+            let commit = repo.revparse_single(commit)?.peel_to_commit()?.id();
+            let blob = repo
+                .revparse_single(&format!("{}:{}", commit, file))
+                .map_err(|_| {
+                    GitAiError::Generic(format!("{} isn't in commit {}", file, &commit[..7]))
+                })?;
+            let content = repo.find_blob(blob.id())?.content()?;
+            let location = format!("commit {}", &commit[..7]);
+            (Some(commit), content, location)
+        }
+        None => {
+            let content = std::fs::read(repo.workdir()?.join(file))
+                .map_err(|_| GitAiError::Generic(format!("{} isn't in the working tree", file)))?;
+            (None, content, "the working tree".to_string())
+        }
+    };
+    let line_count = String::from_utf8_lossy(&content).lines().count() as u32;
+    if lines.is_some_and(|(_, end)| end > line_count) {
+        return Err(GitAiError::Generic(format!(
+            "{} has {} lines in {}",
+            file, line_count, location
+        )));
+    }
+
+    Ok((
+        commit,
+        ReviewMark {
+            file: file.to_string(),
+            lines,
+            by,
+            at: chrono::Utc::now().timestamp(),
+        },
+    ))
+}
@@ -0,0 +1,213 @@
+use serde::Serialize;
+
+use crate::authorship::generated_files::GeneratedFileFilter;
+use crate::authorship::query::{QueriedAuthor, query_attribution};
+use crate::error::{GitAiError, exit_with_error};
+use crate::git::find_repository;
+use crate::git::path_scope::PathScope;
+use crate::git::repo_config::RepoConfig;
+use crate::git::repo_path::split_nul_fields;
+use crate::git::repository::Repository;
+
+/// Checks `scan --licenses` always runs, as (check name, text to look for). Matching ignores case.
+const DEFAULT_LICENSE_PATTERNS: &[(&str, &str)] = &[
+    ("license-header", "SPDX-License-Identifier"),
+    ("license-header", "Licensed under the"),
+    ("license-header", "GNU General Public License"),
+    ("license-header", "GNU Lesser General Public License"),
+    ("license-header", "Mozilla Public License"),
+    (
+        "license-header",
+        "Permission is hereby granted, free of charge",
+    ),
+    (
+        "license-header",
+        "Redistribution and use in source and binary forms",
+    ),
+    ("known-source", "stackoverflow.com/"),
+    ("known-source", "Copied from http"),
+    ("known-source", "Adapted from http"),
+    ("do-not-copy", "do not copy"),
+    ("do-not-copy", "do not distribute"),
+    ("do-not-copy", "proprietary and confidential"),
+    ("do-not-copy", "confidential and proprietary"),
+    ("do-not-copy", "all rights reserved"),
+];
+
+/// A pattern that matched an AI-written line
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Finding {
+    pub file: String,
+    pub line: u32,
+    pub check: String,
+    pub pattern: String,
+    pub text: String,
+    /// Prompt id of the session that wrote the line
+    pub prompt: String,
+    pub tool: String,
+    pub model: String,
+}
+
+pub fn handle_scan(args: &[String]) {
+    let mut licenses = false;
+    let mut json = false;
+    let mut include_generated = false;
+    let mut commit = None;
+    for arg in args {
+        match arg.as_str() {
+            "--licenses" => licenses = true,
+            "--json" => json = true,
+            "--include-generated" => include_generated = true,
+            arg if commit.is_none() && !arg.starts_with('-') => commit = Some(arg.to_string()),
+            arg => {
+                eprintln!("Error: unexpected argument '{}'", arg);
+                std::process::exit(1);
+            }
+        }
+    }
+    if !licenses {
+        eprintln!("Error: scan requires a check: --licenses");
+        std::process::exit(1);
+    }
+
+    let repo = match find_repository(&Vec::<String>::new()) {
+        Ok(repo) => repo,
+        Err(e) => {
+            exit_with_error("Failed to find repository", &e);
+        }
+    };
+
+    let mut patterns: Vec<(String, String)> = DEFAULT_LICENSE_PATTERNS
+        .iter()
+        .map(|(check, text)| (check.to_string(), text.to_string()))
+        .collect();
+    patterns.extend(RepoConfig::load(&repo).license_patterns);
+
+    let findings = match scan_ai_lines(
+        &repo,
+        commit.as_deref().unwrap_or("HEAD"),
+        &patterns,
+        include_generated,
+    ) {
+        Ok(findings) => findings,
+        Err(e) => {
+            exit_with_error("Failed to scan", &e);
+        }
+    };
+
+    if json {
+        match serde_json::to_string(&findings) {
+            Ok(json) => println!("{}", json),
+            Err(e) => exit_with_error("Failed to serialize findings", &GitAiError::from(e)),
+        }
+    } else {
+        for finding in &findings {
+            println!(
+                "{}:{}: {} \"{}\" ({} {}, session {})",
+                finding.file,
+                finding.line,
+                finding.check,
+                finding.pattern,
+                finding.tool,
+                finding.model,
+                finding.prompt
+            );
+            println!("    {}", finding.text);
+        }
+        if findings.is_empty() {
+            println!("No findings in AI-authored lines");
+        }
+    }
+
+    // Findings fail the scan, so it can gate CI
+    if !findings.is_empty() {
+        std::process::exit(1);
+    }
+}
+
+/// Lines written by AI in the files at `rev` that contain one of `patterns`. Only files with a
+/// match anywhere are blamed, so the scan costs little more than reading the tree.
+pub fn scan_ai_lines(
+    repo: &Repository,
+    rev: &str,
+    patterns: &[(String, String)],
+    include_generated: bool,
+) -> Result<Vec<Finding>, GitAiError> {
+    let commit = repo.revparse_single(rev)?.peel_to_commit()?;
+    let commit_sha = commit.id();
+    let tree = commit.tree()?;
+    let lowercase_patterns: Vec<String> = patterns
+        .iter()
+        .map(|(_, text)| text.to_lowercase())
+        .collect();
+
+    let mut paths: Vec<String> =
+        split_nul_fields(&repo.git_bytes(&["ls-tree", "-r", "-z", "--name-only", &commit_sha])?);
+    paths.retain(|path| !path.is_empty());
+    if !include_generated {
+        let filter = GeneratedFileFilter::for_paths(repo, paths.iter()).unwrap_or_default();
+        paths.retain(|path| !filter.is_generated(path));
+    }
+    let scope = PathScope::for_repo(repo);
+    paths.retain(|path| scope.contains(path));
+
+    let mut findings = Vec::new();
+    for path in &paths {
+        let content = repo
+            .find_blob(tree.get_path(std::path::Path::new(path))?.id())?
+            .content()?;
+        if content.contains(&0) {
+            continue;
+        }
+        let content = String::from_utf8_lossy(&content);
+
+        // (line number, pattern index) of every match, before asking who wrote the lines
+        let matches: Vec<(u32, usize)> = content
+            .lines()
+            .enumerate()
+            .flat_map(|(index, line)| {
+                let line = line.to_lowercase();
+                lowercase_patterns
+                    .iter()
+                    .enumerate()
+                    .filter(move |(_, pattern)| line.contains(pattern.as_str()))
+                    .map(move |(pattern_index, _)| (index as u32 + 1, pattern_index))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        let (Some(first), Some(last)) = (
+            matches.iter().map(|(line, _)| *line).min(),
+            matches.iter().map(|(line, _)| *line).max(),
+        ) else {
+            continue;
+        };
+
+        let attributed = query_attribution(repo, path, Some(&commit_sha), Some((first, last)))?;
+        let lines: Vec<&str> = content.lines().collect();
+        for (line, pattern_index) in matches {
+            let Some(QueriedAuthor::Ai {
+                prompt_hash,
+                tool,
+                model,
+            }) = attributed
+                .iter()
+                .find(|queried| queried.line == line)
+                .map(|queried| &queried.author)
+            else {
+                continue;
+            };
+            let (check, pattern) = &patterns[pattern_index];
+            findings.push(Finding {
+                file: path.clone(),
+                line,
+                check: check.clone(),
+                pattern: pattern.clone(),
+                text: lines[line as usize - 1].trim().to_string(),
+                prompt: prompt_hash.clone(),
+                tool: tool.clone(),
+                model: model.clone(),
+            });
+        }
+    }
+    Ok(findings)
+}
@@ -0,0 +1,1178 @@
+use crate::authorship::authorship_log::LineRange;
+use crate::authorship::authorship_log_serialization::AuthorshipLog;
+use crate::authorship::query::{AttributionStream, query_attribution, stream_attribution};
+use crate::authorship::stats::stats_for_commit_stats;
+use crate::commands::query::parse_line_range;
+#[cfg(feature = "graphql")]
+use crate::commands::serve_graphql::{GraphqlSchema, schema};
+use crate::commands::tui::{RecentCommit, ai_share_trend, file_lines, recent_commits, sessions_in};
+use crate::error::{GitAiError, exit_with_error};
+use crate::git::refs::show_authorship_note;
+use crate::git::repository::Repository;
+use crate::git::{find_repository, find_repository_in_path};
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+const TOKEN_ENV_VAR: &str = "GIT_AI_SERVE_TOKEN";
+const MAX_HEADER_BYTES: usize = 16 * 1024;
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+
+/// Where `serve --web` listens unless told otherwise
+const DEFAULT_WEB_ADDR: &str = "127.0.0.1:8080";
+
+/// Commits whose notes the file and session views read, unless `commits=` says otherwise
+const DEFAULT_COMMITS: usize = 200;
+
+/// The web UI, served from `/` with `--web`
+const WEB_ASSETS: [(&str, &str, &str); 3] = [
+    (
+        "/",
+        "text/html; charset=utf-8",
+        include_str!("serve_web/index.html"),
+    ),
+    (
+        "/app.js",
+        "text/javascript; charset=utf-8",
+        include_str!("serve_web/app.js"),
+    ),
+    (
+        "/style.css",
+        "text/css; charset=utf-8",
+        include_str!("serve_web/style.css"),
+    ),
+];
+
+/// Repositories served by `git-ai serve` (id -> working directory), the token clients
+/// must present, the GraphQL schema over them (with the `graphql` feature), and whether the
+/// web UI is served too.
+pub struct ServeState {
+    repos: BTreeMap<String, String>,
+    token: String,
+    #[cfg(feature = "graphql")]
+    graphql: GraphqlSchema,
+    web: bool,
+}
+
+impl ServeState {
+    pub fn new(repos: BTreeMap<String, String>, token: String) -> Self {
+        Self {
+            #[cfg(feature = "graphql")]
+            graphql: schema(repos.clone()),
+            repos,
+            token,
+            web: false,
+        }
+    }
+
+    /// Also serve the web UI's pages, which need no token; the UI sends it with every API call
+    pub fn with_web(mut self) -> Self {
+        self.web = true;
+        self
+    }
+}
+
+pub fn handle_serve(args: &[String]) {
+    let mut addr = None;
+    let mut repos = BTreeMap::new();
+    let mut token = std::env::var(TOKEN_ENV_VAR).ok();
+    let mut web = false;
+
+    let mut i = 0;
+    while i < args.len() {
+        let value = args.get(i + 1);
+        match (args[i].as_str(), value) {
+            ("--web", _) => {
+                web = true;
+                i += 1;
+                continue;
+            }
+            ("--http", Some(value)) => addr = Some(value.clone()),
+            ("--token", Some(value)) => token = Some(value.clone()),
+            ("--repo", Some(value)) => {
+                let (id, path) = parse_repo_arg(value);
+                if let Err(e) = find_repository_in_path(&path) {
+                    eprintln!("Failed to open repository '{}': {}", path, e);
+                    std::process::exit(1);
+                }
+                if repos.insert(id.clone(), path).is_some() {
+                    eprintln!("Error: repository id '{}' is used more than once", id);
+                    std::process::exit(1);
+                }
+            }
+            (flag, _) => {
+                eprintln!("Error: unknown or incomplete serve option '{}'", flag);
+                std::process::exit(1);
+            }
+        }
+        i += 2;
+    }
+
+    // The web UI is for clicking a link on your own machine, so it fills in what it can
+    if web {
+        addr.get_or_insert_with(|| DEFAULT_WEB_ADDR.to_string());
+        token = token
+            .filter(|token| !token.is_empty())
+            .or_else(|| Some(random_token()));
+        if repos.is_empty() {
+            let repo = match find_repository(&Vec::<String>::new()) {
+                Ok(repo) => repo,
+                Err(e) => {
+                    exit_with_error("Failed to find repository", &e);
+                }
+            };
+            let path = match repo.workdir() {
+                Ok(path) => path.to_string_lossy().to_string(),
+                Err(e) => {
+                    exit_with_error("Failed to find repository", &e);
+                }
+            };
+            let (id, path) = parse_repo_arg(&path);
+            repos.insert(id, path);
+        }
+    }
+
+    let Some(addr) = addr else {
+        eprintln!("Error: serve requires --http <addr>");
+        std::process::exit(1);
+    };
+    let token = match token {
+        Some(token) if !token.is_empty() => token,
+        _ => {
+            eprintln!("Error: serve requires --token <token> or {}", TOKEN_ENV_VAR);
+            std::process::exit(1);
+        }
+    };
+    if repos.is_empty() {
+        eprintln!("Error: serve requires at least one --repo [<id>=]<path>");
+        std::process::exit(1);
+    }
+
+    let listener = match TcpListener::bind(&addr) {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("Failed to listen on {}: {}", addr, e);
+            std::process::exit(1);
+        }
+    };
+    let local_addr = listener.local_addr().map(|a| a.to_string()).unwrap_or(addr);
+    eprintln!(
+        "Serving {} repositor{} on http://{}",
+        repos.len(),
+        if repos.len() == 1 { "y" } else { "ies" },
+        local_addr
+    );
+    let mut state = ServeState::new(repos, token);
+    if web {
+        // The token rides in the fragment, which browsers never send to the server
+        eprintln!("Open http://{}/#token={}", local_addr, state.token);
+        state = state.with_web();
+    }
+    serve(listener, Arc::new(state));
+}
+
+/// 128 bits from the OS-seeded hasher keys, enough for a token nobody else on the machine
+/// can guess
+fn random_token() -> String {
+    (0..2)
+        .map(|_| format!("{:016x}", RandomState::new().build_hasher().finish()))
+        .collect()
+}
+
+/// `<id>=<path>`, or just `<path>` with the directory name as the id
+fn parse_repo_arg(value: &str) -> (String, String) {
+    if let Some((id, path)) = value.split_once('=') {
+        return (id.to_string(), path.to_string());
+    }
+    let id = Path::new(value)
+        .canonicalize()
+        .ok()
+        .and_then(|p| p.file_name().map(|n| n.to_string_lossy().to_string()))
+        .unwrap_or_else(|| value.to_string());
+    (id, value.to_string())
+}
+
+/// Accept connections forever, handling each on its own thread.
+pub fn serve(listener: TcpListener, state: Arc<ServeState>) {
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else { continue };
+        let state = Arc::clone(&state);
+        std::thread::spawn(move || {
+            if let Err(e) = handle_connection(stream, &state) {
+                eprintln!("serve: {}", e);
+            }
+        });
+    }
+}
+
+fn handle_connection(stream: TcpStream, state: &ServeState) -> Result<(), GitAiError> {
+    stream.set_read_timeout(Some(Duration::from_secs(30)))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut authorization = None;
+    let mut content_length = 0;
+    let mut header_bytes = request_line.len();
+    loop {
+        let mut header = String::new();
+        let read = reader.read_line(&mut header)?;
+        header_bytes += read;
+        if read == 0 || header == "\r\n" || header == "\n" || header_bytes > MAX_HEADER_BYTES {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("authorization") {
+                authorization = Some(value.trim().to_string());
+            } else if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+        }
+    }
+    if content_length > MAX_BODY_BYTES {
+        let (status, body) = error(400, "Request body too large");
+        return write_response(stream, status, &body);
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+    let body = String::from_utf8_lossy(&body);
+
+    let mut parts = request_line.split_whitespace();
+    match (parts.next(), parts.next()) {
+        (Some(method), Some(target)) => {
+            match route(state, method, target, authorization.as_deref(), &body) {
+                Response::Json(status, body) => write_response(stream, status, &body),
+                Response::Stream(hunks) => write_stream(stream, hunks),
+                Response::Asset(content_type, body) => write_asset(stream, content_type, body),
+            }
+        }
+        _ => {
+            let (status, body) = error(400, "Malformed request");
+            write_response(stream, status, &body)
+        }
+    }
+}
+
+fn write_response(mut stream: TcpStream, status: u16, body: &Value) -> Result<(), GitAiError> {
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Internal Server Error",
+    };
+    let body = body.to_string();
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        body.len(),
+        body
+    )?;
+    stream.flush()?;
+    Ok(())
+}
+
+fn write_asset(mut stream: TcpStream, content_type: &str, body: &str) -> Result<(), GitAiError> {
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n{}",
+        content_type,
+        body.len(),
+        body
+    )?;
+    stream.flush()?;
+    Ok(())
+}
+
+/// Newline-delimited JSON with chunked encoding, one line per hunk, each flushed as soon as
+/// it resolves. A failure part-way through ends the stream with an `{"error": ...}` line.
+fn write_stream(mut stream: TcpStream, hunks: Box<AttributionStream>) -> Result<(), GitAiError> {
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: application/x-ndjson\r\nTransfer-Encoding: chunked\r\nConnection: close\r\n\r\n"
+    )?;
+    for hunk in hunks {
+        let (line, failed) = match hunk {
+            Ok(hunk) => (serde_json::to_string(&hunk)?, false),
+            Err(e) => (json!({ "error": e.to_string() }).to_string(), true),
+        };
+        write!(stream, "{:x}\r\n{}\n\r\n", line.len() + 1, line)?;
+        stream.flush()?;
+        if failed {
+            break;
+        }
+    }
+    write!(stream, "0\r\n\r\n")?;
+    stream.flush()?;
+    Ok(())
+}
+
+fn error(status: u16, message: &str) -> (u16, Value) {
+    (status, json!({ "error": message }))
+}
+
+pub enum Response {
+    Json(u16, Value),
+    /// Attributed blame hunks, sent as they resolve
+    Stream(Box<AttributionStream>),
+    /// A page of the web UI: content type and body
+    Asset(&'static str, &'static str),
+}
+
+/// Compare without short-circuiting so response timing doesn't leak how much of the token
+/// matched.
+fn token_matches(presented: &str, expected: &str) -> bool {
+    presented.len() == expected.len()
+        && presented
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Answer one request. Every endpoint is read-only and needs `Authorization: Bearer <token>`,
+/// except the web UI's pages. Only `/graphql`, built with the `graphql` feature, reads the
+/// request body.
+#[cfg_attr(not(feature = "graphql"), allow(unused_variables))]
+pub fn route(
+    state: &ServeState,
+    method: &str,
+    target: &str,
+    authorization: Option<&str>,
+    body: &str,
+) -> Response {
+    if state.web && method == "GET" {
+        let path = target.split_once('?').map_or(target, |(path, _)| path);
+        if let Some((_, content_type, body)) = WEB_ASSETS.iter().find(|(p, _, _)| *p == path) {
+            return Response::Asset(content_type, body);
+        }
+    }
+    let presented = authorization.and_then(|value| value.strip_prefix("Bearer "));
+    if !presented.is_some_and(|token| token_matches(token.trim(), &state.token)) {
+        return json_error(401, "Missing or invalid bearer token");
+    }
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let query = parse_query(query);
+    #[cfg(feature = "graphql")]
+    if path.trim_end_matches('/') == "/graphql" {
+        return graphql(state, method, &query, body);
+    }
+    if method != "GET" {
+        return json_error(405, "Only GET is supported");
+    }
+
+    let segments: Vec<String> = path
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .map(percent_decode)
+        .collect();
+    let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+
+    let result = match segments.as_slice() {
+        ["repos"] => Ok(Some(
+            json!({ "repos": state.repos.keys().collect::<Vec<_>>() }),
+        )),
+        ["repos", id, rest @ ..] => {
+            let Some(repo_path) = state.repos.get(*id) else {
+                return json_error(404, &format!("Unknown repository '{}'", id));
+            };
+            let repo = match find_repository_in_path(repo_path) {
+                Ok(repo) => repo,
+                Err(e) => return json_error(500, &e.to_string()),
+            };
+            match rest {
+                ["commits", sha, "attribution"] => commit_attribution(&repo, sha),
+                ["commits", sha, "prompts", hash] => prompt(&repo, sha, hash),
+                ["contents"] => contents(&repo, &query),
+                ["files"] => files(&repo, &query),
+                ["sessions"] => sessions(&repo, &query),
+                ["trend"] => trend(&repo, &query),
+                ["blame"] => blame(&repo, &query),
+                ["blame", "stream"] => match blame_stream(&repo, &query) {
+                    Ok(hunks) => return Response::Stream(Box::new(hunks)),
+                    Err(e) => Err(e),
+                },
+                ["stats"] => stats(&repo, query.get("rev").map_or("HEAD", |r| r)),
+                _ => return json_error(404, "Not found"),
+            }
+        }
+        _ => return json_error(404, "Not found"),
+    };
+
+    let (status, body) = match result {
+        Ok(Some(body)) => (200, body),
+        Ok(None) => error(404, "No authorship data found for this revision"),
+        Err(RequestError::BadRequest(message)) => error(400, &message),
+        Err(RequestError::Git(e)) => error(500, &e.to_string()),
+    };
+    Response::Json(status, body)
+}
+
+/// A GraphQL request: a JSON body on `POST`, or `query`, `variables` and `operationName`
+/// parameters on `GET`. Errors in the query come back as a 200 with `errors`, as GraphQL
+/// clients expect.
+#[cfg(feature = "graphql")]
+fn graphql(
+    state: &ServeState,
+    method: &str,
+    query: &BTreeMap<String, String>,
+    body: &str,
+) -> Response {
+    let request = match method {
+        "POST" => match serde_json::from_str::<async_graphql::Request>(body) {
+            Ok(request) => request,
+            Err(e) => return json_error(400, &format!("Invalid GraphQL request: {}", e)),
+        },
+        "GET" => {
+            let Some(document) = query.get("query") else {
+                return json_error(400, "graphql requires a 'query' parameter");
+            };
+            let mut request = async_graphql::Request::new(document);
+            if let Some(name) = query.get("operationName") {
+                request = request.operation_name(name);
+            }
+            if let Some(variables) = query.get("variables") {
+                match serde_json::from_str(variables) {
+                    Ok(variables) => {
+                        request = request.variables(async_graphql::Variables::from_json(variables))
+                    }
+                    Err(e) => return json_error(400, &format!("Invalid variables: {}", e)),
+                }
+            }
+            request
+        }
+        _ => return json_error(405, "graphql supports GET and POST"),
+    };
+    let response = smol::block_on(state.graphql.execute(request));
+    match serde_json::to_value(&response) {
+        Ok(body) => Response::Json(200, body),
+        Err(e) => json_error(500, &e.to_string()),
+    }
+}
+
+fn json_error(status: u16, message: &str) -> Response {
+    let (status, body) = error(status, message);
+    Response::Json(status, body)
+}
+
+enum RequestError {
+    BadRequest(String),
+    Git(GitAiError),
+}
+
+impl From<GitAiError> for RequestError {
+    fn from(err: GitAiError) -> Self {
+        RequestError::Git(err)
+    }
+}
+
+/// Resolve a client-supplied revision, refusing anything git could read as an option.
+fn resolve_rev(repo: &Repository, rev: &str) -> Result<String, RequestError> {
+    if rev.is_empty() || rev.starts_with('-') {
+        return Err(RequestError::BadRequest(format!(
+            "Invalid revision '{}'",
+            rev
+        )));
+    }
+    repo.revparse_single(rev)
+        .map(|object| object.id())
+        .map_err(|_| RequestError::BadRequest(format!("Unknown revision '{}'", rev)))
+}
+
+fn commit_attribution(repo: &Repository, rev: &str) -> Result<Option<Value>, RequestError> {
+    let sha = resolve_rev(repo, rev)?;
+    let Some(content) = show_authorship_note(repo, &sha) else {
+        return Ok(None);
+    };
+    let log = AuthorshipLog::deserialize_from_string(&content).map_err(|e| {
+        GitAiError::InvalidAuthorshipLog {
+            commit: sha.clone(),
+            reason: e.to_string(),
+        }
+    })?;
+    Ok(Some(authorship_log_json(&sha, &log)))
+}
+
+/// JSON view of an authorship log. Prompt transcripts are left out; dashboards only need
+/// who wrote what.
+fn authorship_log_json(sha: &str, log: &AuthorshipLog) -> Value {
+    let files: Vec<Value> = log
+        .attestations
+        .iter()
+        .map(|file| {
+            let entries: Vec<Value> = file
+                .entries
+                .iter()
+                .map(|entry| {
+                    let lines: Vec<Vec<u32>> = entry
+                        .line_ranges
+                        .iter()
+                        .map(|range| match range {
+                            LineRange::Single(line) => vec![*line, *line],
+                            LineRange::Range(start, end) => vec![*start, *end],
+                        })
+                        .collect();
+                    json!({
+                        "prompt_hash": entry.hash,
+                        "lines": lines,
+                        "confidence": entry.confidence,
+                    })
+                })
+                .collect();
+            json!({ "path": file.file_path, "entries": entries })
+        })
+        .collect();
+
+    let prompts: BTreeMap<&String, Value> = log
+        .metadata
+        .prompts
+        .iter()
+        .map(|(hash, prompt)| {
+            (
+                hash,
+                json!({
+                    "tool": prompt.agent_id.tool,
+                    "model": prompt.agent_id.model,
+                    "human_author": prompt.human_author,
+                    "total_additions": prompt.total_additions,
+                    "total_deletions": prompt.total_deletions,
+                    "accepted_lines": prompt.accepted_lines,
+                }),
+            )
+        })
+        .collect();
+
+    json!({ "commit": sha, "files": files, "prompts": prompts })
+}
+
+/// Path, resolved revision and optional `lines=<start>,<end>` of a blame request
+struct BlameRequest {
+    file_path: String,
+    sha: String,
+    line_range: Option<(u32, u32)>,
+}
+
+fn blame_request(
+    repo: &Repository,
+    query: &BTreeMap<String, String>,
+) -> Result<BlameRequest, RequestError> {
+    let Some(file_path) = query.get("path") else {
+        return Err(RequestError::BadRequest(
+            "blame requires a 'path' query parameter".to_string(),
+        ));
+    };
+    if file_path.starts_with('-') || Path::new(file_path).is_absolute() {
+        return Err(RequestError::BadRequest(format!(
+            "Invalid path '{}'",
+            file_path
+        )));
+    }
+    let line_range =
+        match query.get("lines") {
+            Some(lines) => Some(parse_line_range(lines).ok_or_else(|| {
+                RequestError::BadRequest(format!("Invalid line range '{}'", lines))
+            })?),
+            None => None,
+        };
+    let sha = resolve_rev(repo, query.get("rev").map_or("HEAD", |r| r))?;
+    Ok(BlameRequest {
+        file_path: file_path.clone(),
+        sha,
+        line_range,
+    })
+}
+
+fn blame(
+    repo: &Repository,
+    query: &BTreeMap<String, String>,
+) -> Result<Option<Value>, RequestError> {
+    let BlameRequest {
+        file_path,
+        sha,
+        line_range,
+    } = blame_request(repo, query)?;
+    let lines = query_attribution(repo, &file_path, Some(&sha), line_range)?;
+    Ok(Some(
+        json!({ "commit": sha, "path": file_path, "lines": lines }),
+    ))
+}
+
+fn blame_stream(
+    repo: &Repository,
+    query: &BTreeMap<String, String>,
+) -> Result<AttributionStream, RequestError> {
+    let BlameRequest {
+        file_path,
+        sha,
+        line_range,
+    } = blame_request(repo, query)?;
+    Ok(stream_attribution(
+        repo,
+        &file_path,
+        Some(&sha),
+        line_range,
+    )?)
+}
+
+fn stats(repo: &Repository, rev: &str) -> Result<Option<Value>, RequestError> {
+    let sha = resolve_rev(repo, rev)?;
+    let stats = stats_for_commit_stats(repo, &sha, rev, false)?;
+    Ok(Some(
+        serde_json::to_value(&stats).map_err(GitAiError::from)?,
+    ))
+}
+
+/// One prompt of a commit's authorship log, transcript included
+fn prompt(repo: &Repository, rev: &str, hash: &str) -> Result<Option<Value>, RequestError> {
+    let sha = resolve_rev(repo, rev)?;
+    let Some(content) = show_authorship_note(repo, &sha) else {
+        return Ok(None);
+    };
+    let log = AuthorshipLog::deserialize_from_string(&content).map_err(|e| {
+        GitAiError::InvalidAuthorshipLog {
+            commit: sha.clone(),
+            reason: e.to_string(),
+        }
+    })?;
+    let Some(record) = log.metadata.prompts.get(hash) else {
+        return Ok(None);
+    };
+    let mut body = serde_json::to_value(record).map_err(GitAiError::from)?;
+    body["commit"] = json!(sha);
+    body["prompt_hash"] = json!(hash);
+    body["prompter"] = json!(record.prompter());
+    Ok(Some(body))
+}
+
+fn contents(
+    repo: &Repository,
+    query: &BTreeMap<String, String>,
+) -> Result<Option<Value>, RequestError> {
+    let BlameRequest { file_path, sha, .. } = blame_request(repo, query)?;
+    let contents = repo
+        .git(&["show", &format!("{}:{}", sha, file_path)])
+        .map_err(|_| RequestError::BadRequest(format!("No file '{}' at {}", file_path, sha)))?;
+    Ok(Some(
+        json!({ "commit": sha, "path": file_path, "contents": contents }),
+    ))
+}
+
+/// The revision and `commits=<n>` of the views over recent history
+fn history_request(
+    repo: &Repository,
+    query: &BTreeMap<String, String>,
+) -> Result<(String, Vec<RecentCommit>), RequestError> {
+    let sha = resolve_rev(repo, query.get("rev").map_or("HEAD", |r| r))?;
+    let commits = match query.get("commits") {
+        Some(commits) => match commits.parse() {
+            Ok(count) if count > 0 => count,
+            _ => {
+                return Err(RequestError::BadRequest(format!(
+                    "Invalid commit count '{}'",
+                    commits
+                )));
+            }
+        },
+        None => DEFAULT_COMMITS,
+    };
+    let history = recent_commits(repo, &sha, commits)?;
+    Ok((sha, history))
+}
+
+/// Total and AI lines of every file at `rev`, counting AI lines only in files the notes of
+/// the last `commits` commits attest to
+fn files(
+    repo: &Repository,
+    query: &BTreeMap<String, String>,
+) -> Result<Option<Value>, RequestError> {
+    let (sha, history) = history_request(repo, query)?;
+    let (_, ai_files) = sessions_in(repo, &history);
+    let files: Vec<Value> = file_lines(repo, &sha, &ai_files)?
+        .into_iter()
+        .map(|(path, total_lines, ai_lines)| {
+            json!({ "path": path, "total_lines": total_lines, "ai_lines": ai_lines })
+        })
+        .collect();
+    Ok(Some(json!({ "commit": sha, "files": files })))
+}
+
+/// The AI sessions in the notes of the last `commits` commits, newest first
+fn sessions(
+    repo: &Repository,
+    query: &BTreeMap<String, String>,
+) -> Result<Option<Value>, RequestError> {
+    let (sha, history) = history_request(repo, query)?;
+    let (sessions, _) = sessions_in(repo, &history);
+    Ok(Some(json!({ "commit": sha, "sessions": sessions })))
+}
+
+/// The AI share of the lines each of the last `commits` commits added, oldest first
+fn trend(
+    repo: &Repository,
+    query: &BTreeMap<String, String>,
+) -> Result<Option<Value>, RequestError> {
+    let (sha, history) = history_request(repo, query)?;
+    let shares = ai_share_trend(repo, &history);
+    let commits: Vec<Value> = history
+        .iter()
+        .rev()
+        .zip(shares)
+        .map(|(commit, ai_percent)| {
+            json!({
+                "commit": commit.sha,
+                "time": commit.time,
+                "subject": commit.subject,
+                "ai_percent": ai_percent,
+            })
+        })
+        .collect();
+    Ok(Some(json!({ "commit": sha, "commits": commits })))
+}
+
+fn parse_query(query: &str) -> BTreeMap<String, String> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (
+                percent_decode(&key.replace('+', " ")),
+                percent_decode(&value.replace('+', " ")),
+            )
+        })
+        .collect()
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| bytes.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                out.push(byte);
+                i += 3;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authorship::review::{ReviewMark, add_review_mark};
+    use crate::git::test_utils::TmpRepo;
+
+    const TOKEN: &str = "secret-token";
+    const AUTH: Option<&str> = Some("Bearer secret-token");
+
+    fn json_route(
+        state: &ServeState,
+        method: &str,
+        target: &str,
+        authorization: Option<&str>,
+    ) -> (u16, Value) {
+        match route(state, method, target, authorization, "") {
+            Response::Json(status, body) => (status, body),
+            _ => panic!("expected a JSON response for {}", target),
+        }
+    }
+
+    fn serve_tmp_repo() -> (TmpRepo, ServeState) {
+        let tmp_repo = TmpRepo::new().unwrap();
+        let mut file = tmp_repo.write_file("test.txt", "Line1\n", true).unwrap();
+        tmp_repo
+            .trigger_checkpoint_with_author("test_user")
+            .unwrap();
+        tmp_repo.commit_with_message("Initial commit").unwrap();
+        file.append("Line 2\n").unwrap();
+        tmp_repo
+            .trigger_checkpoint_with_ai("Claude", Some("claude-3-sonnet"), Some("cursor"))
+            .unwrap();
+        tmp_repo.commit_with_message("AI adds a line").unwrap();
+
+        let repos = BTreeMap::from([(
+            "demo".to_string(),
+            tmp_repo.path().to_string_lossy().to_string(),
+        )]);
+        (tmp_repo, ServeState::new(repos, TOKEN.to_string()))
+    }
+
+    #[test]
+    fn test_routes_attribution_blame_and_stats() {
+        let (_tmp_repo, state) = serve_tmp_repo();
+
+        let (status, body) = json_route(&state, "GET", "/repos", AUTH);
+        assert_eq!(status, 200);
+        assert_eq!(body["repos"], json!(["demo"]));
+
+        let (status, body) =
+            json_route(&state, "GET", "/repos/demo/commits/HEAD/attribution", AUTH);
+        assert_eq!(status, 200);
+        assert_eq!(body["files"][0]["path"], "test.txt");
+        assert_eq!(body["files"][0]["entries"][0]["lines"], json!([[2, 2]]));
+        let hash = body["files"][0]["entries"][0]["prompt_hash"]
+            .as_str()
+            .unwrap();
+        assert_eq!(body["prompts"][hash]["tool"], "cursor");
+        assert!(body["prompts"][hash].get("messages").is_none());
+
+        let (status, body) = json_route(
+            &state,
+            "GET",
+            "/repos/demo/blame?path=test.txt&rev=HEAD",
+            AUTH,
+        );
+        assert_eq!(status, 200);
+        assert_eq!(body["lines"][0]["author"]["kind"], "human");
+        assert_eq!(body["lines"][1]["author"]["kind"], "ai");
+        assert_eq!(body["lines"][1]["author"]["model"], "claude-3-sonnet");
+        assert_eq!(body["lines"][1]["source"], "note");
+
+        let (status, body) = json_route(
+            &state,
+            "GET",
+            "/repos/demo/blame?path=test.txt&lines=2,2",
+            AUTH,
+        );
+        assert_eq!(status, 200);
+        assert_eq!(body["lines"].as_array().unwrap().len(), 1);
+        assert_eq!(body["lines"][0]["line"], 2);
+        assert_eq!(body["lines"][0]["author"]["kind"], "ai");
+
+        let (status, body) = json_route(&state, "GET", "/repos/demo/stats?rev=HEAD", AUTH);
+        assert_eq!(status, 200);
+        assert_eq!(body["ai_additions"], 1);
+    }
+
+    #[test]
+    fn test_rejects_unauthorized_and_invalid_requests() {
+        let (tmp_repo, state) = serve_tmp_repo();
+        tmp_repo.write_file("plain.txt", "plain\n", true).unwrap();
+        tmp_repo
+            .git_command(&["commit", "-m", "Commit without git-ai"])
+            .unwrap();
+        assert_eq!(
+            json_route(&state, "GET", "/repos/demo/commits/HEAD/attribution", AUTH).0,
+            404
+        );
+
+        assert_eq!(json_route(&state, "GET", "/repos", None).0, 401);
+        assert_eq!(
+            json_route(&state, "GET", "/repos", Some("Bearer wrong-token")).0,
+            401
+        );
+        assert_eq!(json_route(&state, "GET", "/repos", Some(TOKEN)).0, 401);
+        assert_eq!(json_route(&state, "POST", "/repos", AUTH).0, 405);
+        assert_eq!(json_route(&state, "GET", "/repos/other/stats", AUTH).0, 404);
+        assert_eq!(json_route(&state, "GET", "/repos/demo/blame", AUTH).0, 400);
+        assert_eq!(
+            json_route(
+                &state,
+                "GET",
+                "/repos/demo/blame?path=test.txt&lines=2,1",
+                AUTH
+            )
+            .0,
+            400
+        );
+        assert_eq!(
+            json_route(&state, "GET", "/repos/demo/blame/stream", AUTH).0,
+            400
+        );
+        assert_eq!(
+            json_route(
+                &state,
+                "GET",
+                "/repos/demo/stats?rev=--output%3D%2Ftmp%2Fx",
+                AUTH
+            )
+            .0,
+            400
+        );
+        assert_eq!(
+            json_route(&state, "GET", "/repos/demo/commits/nope/attribution", AUTH).0,
+            400
+        );
+    }
+
+    #[test]
+    fn test_serves_over_http() {
+        let (_tmp_repo, state) = serve_tmp_repo();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || serve(listener, Arc::new(state)));
+
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(
+            stream,
+            "GET /repos/demo/stats HTTP/1.1\r\nHost: localhost\r\nAuthorization: Bearer {}\r\n\r\n",
+            TOKEN
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        let body = response.split("\r\n\r\n").nth(1).unwrap();
+        let stats: Value = serde_json::from_str(body).unwrap();
+        assert_eq!(stats["ai_additions"], 1);
+    }
+
+    #[test]
+    fn test_streams_blame_hunks_as_ndjson() {
+        let (_tmp_repo, state) = serve_tmp_repo();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || serve(listener, Arc::new(state)));
+
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(
+            stream,
+            "GET /repos/demo/blame/stream?path=test.txt HTTP/1.1\r\nHost: localhost\r\nAuthorization: Bearer {}\r\n\r\n",
+            TOKEN
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains("Transfer-Encoding: chunked\r\n"));
+        assert!(response.ends_with("0\r\n\r\n"));
+        let mut lines: Vec<Value> = response
+            .lines()
+            .filter(|line| line.starts_with('{'))
+            .flat_map(|line| {
+                let hunk: Value = serde_json::from_str(line).unwrap();
+                hunk["lines"].as_array().unwrap().clone()
+            })
+            .collect();
+        lines.sort_by_key(|line| line["line"].as_u64());
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["author"]["kind"], "human");
+        assert_eq!(lines[1]["author"]["kind"], "ai");
+    }
+
+    #[test]
+    fn test_routes_web_ui_views() {
+        let (_tmp_repo, state) = serve_tmp_repo();
+
+        let (status, body) = json_route(&state, "GET", "/repos/demo/files", AUTH);
+        assert_eq!(status, 200);
+        assert_eq!(
+            body["files"],
+            json!([{ "path": "test.txt", "total_lines": 2, "ai_lines": 1 }])
+        );
+
+        let (status, body) = json_route(&state, "GET", "/repos/demo/sessions", AUTH);
+        assert_eq!(status, 200);
+        let session = &body["sessions"][0];
+        assert_eq!(session["tool"], "cursor");
+        assert_eq!(session["subject"], "AI adds a line");
+
+        let target = format!(
+            "/repos/demo/commits/{}/prompts/{}",
+            session["commit"].as_str().unwrap(),
+            session["prompt_hash"].as_str().unwrap()
+        );
+        let (status, body) = json_route(&state, "GET", &target, AUTH);
+        assert_eq!(status, 200);
+        assert_eq!(body["agent_id"]["model"], "claude-3-sonnet");
+        assert!(body["messages"].is_array());
+
+        let (status, body) = json_route(&state, "GET", "/repos/demo/trend", AUTH);
+        assert_eq!(status, 200);
+        assert_eq!(body["commits"][0]["ai_percent"], 0);
+        assert_eq!(body["commits"][1]["ai_percent"], 100);
+
+        let (status, body) = json_route(
+            &state,
+            "GET",
+            "/repos/demo/contents?path=test.txt&rev=HEAD~1",
+            AUTH,
+        );
+        assert_eq!(status, 200);
+        assert_eq!(body["contents"], "Line1\n");
+        assert_eq!(
+            json_route(&state, "GET", "/repos/demo/trend?commits=0", AUTH).0,
+            400
+        );
+    }
+
+    #[test]
+    fn test_serves_web_ui_pages_only_with_web() {
+        let (_tmp_repo, state) = serve_tmp_repo();
+        // Without --web, `/` is just another API path
+        assert_eq!(json_route(&state, "GET", "/", None).0, 401);
+
+        let state = state.with_web();
+        match route(&state, "GET", "/", None, "") {
+            Response::Asset(content_type, body) => {
+                assert!(content_type.starts_with("text/html"));
+                assert!(body.contains("/app.js"));
+            }
+            _ => panic!("expected the web UI's page"),
+        }
+        assert!(matches!(
+            route(&state, "GET", "/app.js", None, ""),
+            Response::Asset(..)
+        ));
+        // The data behind it still needs the token
+        assert_eq!(json_route(&state, "GET", "/repos", None).0, 401);
+    }
+
+    #[cfg(feature = "graphql")]
+    fn graphql_route(state: &ServeState, body: Value) -> Value {
+        match route(state, "POST", "/graphql", AUTH, &body.to_string()) {
+            Response::Json(200, body) => body,
+            _ => panic!("expected a GraphQL response"),
+        }
+    }
+
+    #[cfg(feature = "graphql")]
+    #[test]
+    fn test_graphql_joins_commits_attestations_prompts_and_sessions() {
+        let (tmp_repo, state) = serve_tmp_repo();
+        let head = tmp_repo.head_commit_sha().unwrap();
+        add_review_mark(
+            &find_repository_in_path(tmp_repo.path().to_str().unwrap()).unwrap(),
+            &head,
+            &ReviewMark {
+                file: "test.txt".to_string(),
+                lines: Some((2, 2)),
+                by: "Reviewer <reviewer@example.com>".to_string(),
+                at: 1_700_000_000,
+            },
+        )
+        .unwrap();
+
+        let body = graphql_route(
+            &state,
+            json!({ "query": r#"{
+                repository(id: "demo") {
+                    commits(first: 1) {
+                        pageInfo { hasNextPage endCursor }
+                        edges { node {
+                            subject
+                            reviewedAiLines
+                            attestations(path: "test.txt") { path entries {
+                                lines { start end }
+                                prompt { tool model prompter messages { kind } }
+                                reviewMarks { by startLine }
+                            } }
+                        } }
+                    }
+                    sessions { edges { node { tool acceptedLines commits { subject } } } }
+                }
+            }"# }),
+        );
+        assert_eq!(body.get("errors"), None, "{}", body);
+        let commits = &body["data"]["repository"]["commits"];
+        assert_eq!(commits["pageInfo"]["hasNextPage"], true);
+        let commit = &commits["edges"][0]["node"];
+        assert_eq!(commit["subject"], "AI adds a line");
+        assert_eq!(commit["reviewedAiLines"], 1);
+        let entry = &commit["attestations"][0]["entries"][0];
+        assert_eq!(entry["lines"], json!([{ "start": 2, "end": 2 }]));
+        assert_eq!(entry["prompt"]["tool"], "cursor");
+        assert_eq!(entry["prompt"]["model"], "claude-3-sonnet");
+        assert_eq!(
+            entry["reviewMarks"],
+            json!([{ "by": "Reviewer <reviewer@example.com>", "startLine": 2 }])
+        );
+        let session = &body["data"]["repository"]["sessions"]["edges"][0]["node"];
+        assert_eq!(session["tool"], "cursor");
+        assert_eq!(session["commits"], json!([{ "subject": "AI adds a line" }]));
+
+        // The next page picks up after the cursor
+        let cursor = commits["pageInfo"]["endCursor"].as_str().unwrap();
+        let body = graphql_route(
+            &state,
+            json!({
+                "query": "query($after: String) { repository(id: \"demo\") { commits(first: 5, after: $after) { pageInfo { hasNextPage hasPreviousPage } edges { node { subject hasAuthorship } } } } }",
+                "variables": { "after": cursor },
+            }),
+        );
+        let commits = &body["data"]["repository"]["commits"];
+        assert_eq!(commits["pageInfo"]["hasNextPage"], false);
+        assert_eq!(commits["pageInfo"]["hasPreviousPage"], true);
+        assert_eq!(commits["edges"][0]["node"]["subject"], "Initial commit");
+    }
+
+    #[cfg(feature = "graphql")]
+    #[test]
+    fn test_graphql_requests() {
+        let (_tmp_repo, state) = serve_tmp_repo();
+        let target = format!(
+            "/graphql?query={}",
+            "%7B%20repos%20repository(id%3A%20%22other%22)%20%7B%20id%20%7D%20%7D"
+        );
+        let (status, body) = json_route(&state, "GET", &target, AUTH);
+        assert_eq!(status, 200);
+        assert_eq!(
+            body["data"],
+            json!({ "repos": ["demo"], "repository": null })
+        );
+
+        let body = graphql_route(
+            &state,
+            json!({ "query": "{ repository(id: \"demo\") { commits(rev: \"--all\") { edges { cursor } } } }" }),
+        );
+        assert!(
+            body["errors"][0]["message"]
+                .as_str()
+                .unwrap()
+                .contains("Invalid revision")
+        );
+
+        assert_eq!(json_route(&state, "POST", "/graphql", None).0, 401);
+        assert_eq!(json_route(&state, "POST", "/graphql", AUTH).0, 400);
+        assert_eq!(json_route(&state, "DELETE", "/graphql", AUTH).0, 405);
+    }
+
+    #[cfg(not(feature = "graphql"))]
+    #[test]
+    fn test_graphql_is_not_served_without_the_feature() {
+        let (_tmp_repo, state) = serve_tmp_repo();
+        assert_eq!(json_route(&state, "POST", "/graphql", AUTH).0, 405);
+        assert_eq!(json_route(&state, "GET", "/graphql", AUTH).0, 404);
+    }
+
+    #[test]
+    fn test_random_tokens_differ() {
+        let token = random_token();
+        assert_eq!(token.len(), 32);
+        assert_ne!(token, random_token());
+    }
+
+    #[test]
+    fn test_percent_decode() {
+        assert_eq!(percent_decode("src%2Fmain.rs"), "src/main.rs");
+        assert_eq!(percent_decode("HEAD%5E"), "HEAD^");
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%zz"), "%zz");
+        assert_eq!(
+            parse_query("path=a+b.txt&rev=HEAD")["path"],
+            "a b.txt".to_string()
+        );
+    }
+}
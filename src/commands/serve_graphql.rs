@@ -0,0 +1,635 @@
+//! The GraphQL schema `git-ai serve` answers at `/graphql`, for tools that join commits,
+//! attestations, prompts, sessions and review marks in one query instead of walking the JSON
+//! endpoints.
+//!
+//! Lists that grow with history (commits, sessions) are Relay-style connections paged with
+//! `first`/`after`; everything hanging off a single commit is a plain list.
+
+use crate::authorship::authorship_log::{LineRange, PromptRecord};
+use crate::authorship::authorship_log_serialization::{AttestationEntry, AuthorshipLog};
+use crate::authorship::review::{ReviewMark, review_marks, reviewed_ai_lines};
+use crate::authorship::transcript::Message;
+use crate::git::find_repository_in_path;
+use crate::git::refs::get_authorship;
+use crate::git::repository::Repository;
+use async_graphql::connection::{Connection, Edge, query};
+use async_graphql::{
+    Context, EmptyMutation, EmptySubscription, Error, Object, Result, Schema, SimpleObject,
+};
+use std::collections::BTreeMap;
+use std::sync::{Arc, OnceLock};
+
+/// Page size of connections when `first` isn't given
+const DEFAULT_PAGE_SIZE: usize = 20;
+/// Largest page a connection returns, whatever `first` asks for
+const MAX_PAGE_SIZE: usize = 100;
+/// Commits whose notes `sessions` reads, unless `commits` says otherwise
+const DEFAULT_SESSION_COMMITS: usize = 200;
+/// Deepest selection a query may make, so one request can't fan out without bound
+const MAX_QUERY_DEPTH: usize = 16;
+
+/// `git log` format of the commit fields: sha, commit time, `Name <email>` and subject
+const COMMIT_FORMAT: &str = "--format=%H%x00%ct%x00%an <%ae>%x00%s";
+
+pub type GraphqlSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+/// The schema over `repos` (id -> working directory)
+pub fn schema(repos: BTreeMap<String, String>) -> GraphqlSchema {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .data(ServedRepos(repos))
+        .limit_depth(MAX_QUERY_DEPTH)
+        .finish()
+}
+
+struct ServedRepos(BTreeMap<String, String>);
+
+/// Refuse anything git could read as an option
+fn checked_rev(rev: &str) -> Result<&str> {
+    if rev.is_empty() || rev.starts_with('-') {
+        return Err(Error::new(format!("Invalid revision '{}'", rev)));
+    }
+    Ok(rev)
+}
+
+fn page_size(first: Option<usize>) -> usize {
+    first.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE)
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// Ids of the served repositories
+    async fn repos(&self, ctx: &Context<'_>) -> Vec<String> {
+        ctx.data_unchecked::<ServedRepos>()
+            .0
+            .keys()
+            .cloned()
+            .collect()
+    }
+
+    /// A served repository by id
+    async fn repository(&self, ctx: &Context<'_>, id: String) -> Result<Option<RepositoryNode>> {
+        let Some(path) = ctx.data_unchecked::<ServedRepos>().0.get(&id) else {
+            return Ok(None);
+        };
+        let repo = find_repository_in_path(path).map_err(|e| Error::new(e.to_string()))?;
+        Ok(Some(RepositoryNode {
+            id,
+            repo: Arc::new(repo),
+        }))
+    }
+}
+
+pub struct RepositoryNode {
+    id: String,
+    repo: Arc<Repository>,
+}
+
+#[Object(name = "Repository")]
+impl RepositoryNode {
+    async fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Commits `rev` selects (default `HEAD`; ranges such as `main..feature` work), newest
+    /// first, optionally only those touching `path`
+    async fn commits(
+        &self,
+        rev: Option<String>,
+        path: Option<String>,
+        after: Option<String>,
+        first: Option<i32>,
+    ) -> Result<Connection<usize, CommitNode>> {
+        let rev = checked_rev(rev.as_deref().unwrap_or("HEAD"))?.to_string();
+        query(
+            after,
+            None,
+            first,
+            None,
+            |after: Option<usize>, _: Option<usize>, first, _| async move {
+                let skip = after.map_or(0, |after| after + 1);
+                let first = page_size(first);
+                let skip_arg = format!("--skip={}", skip);
+                let count_arg = format!("--max-count={}", first + 1);
+                let mut args = vec!["log", COMMIT_FORMAT, &skip_arg, &count_arg, &rev];
+                if let Some(path) = &path {
+                    args.extend(["--", path]);
+                }
+                let mut commits = commit_nodes(&self.repo, &args)?;
+                let has_next = commits.len() > first;
+                commits.truncate(first);
+
+                let mut connection = Connection::new(skip > 0, has_next);
+                connection.edges.extend(
+                    commits
+                        .into_iter()
+                        .enumerate()
+                        .map(|(i, commit)| Edge::new(skip + i, commit)),
+                );
+                Ok::<_, Error>(connection)
+            },
+        )
+        .await
+    }
+
+    /// One commit, or null if `rev` doesn't name one
+    async fn commit(&self, rev: String) -> Result<Option<CommitNode>> {
+        let rev = checked_rev(&rev)?;
+        if self.repo.revparse_single(rev).is_err() {
+            return Ok(None);
+        }
+        Ok(
+            commit_nodes(&self.repo, &["log", "-1", COMMIT_FORMAT, rev])?
+                .into_iter()
+                .next(),
+        )
+    }
+
+    /// AI sessions in the notes of the last `commits` commits (default 200) `rev` selects,
+    /// most recently committed first. A session spans every commit its lines landed in.
+    async fn sessions(
+        &self,
+        rev: Option<String>,
+        commits: Option<i32>,
+        after: Option<String>,
+        first: Option<i32>,
+    ) -> Result<Connection<usize, SessionNode>> {
+        let rev = checked_rev(rev.as_deref().unwrap_or("HEAD"))?;
+        let commits = match commits {
+            Some(commits) if commits <= 0 => {
+                return Err(Error::new(format!("Invalid commit count {}", commits)));
+            }
+            Some(commits) => commits as usize,
+            None => DEFAULT_SESSION_COMMITS,
+        };
+        let sessions = sessions_in(&self.repo, rev, commits)?;
+        query(
+            after,
+            None,
+            first,
+            None,
+            |after: Option<usize>, _: Option<usize>, first, _| async move {
+                let skip = after.map_or(0, |after| after + 1);
+                let first = page_size(first);
+                let mut connection =
+                    Connection::new(skip > 0, sessions.len() > skip.saturating_add(first));
+                connection.edges.extend(
+                    sessions
+                        .into_iter()
+                        .enumerate()
+                        .skip(skip)
+                        .take(first)
+                        .map(|(i, session)| Edge::new(i, session)),
+                );
+                Ok::<_, Error>(connection)
+            },
+        )
+        .await
+    }
+}
+
+/// Commits `git log <args>` lists, `args` including [`COMMIT_FORMAT`]
+fn commit_nodes(repo: &Arc<Repository>, args: &[&str]) -> Result<Vec<CommitNode>> {
+    let output = repo.git(args).map_err(|e| Error::new(e.to_string()))?;
+    Ok(output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(4, '\0');
+            let sha = fields.next()?.trim();
+            if sha.is_empty() {
+                return None;
+            }
+            Some(CommitNode(Arc::new(CommitData {
+                repo: Arc::clone(repo),
+                sha: sha.to_string(),
+                time: fields.next()?.parse().unwrap_or(0),
+                author: fields.next()?.to_string(),
+                subject: fields.next().unwrap_or("").to_string(),
+                log: OnceLock::new(),
+                marks: OnceLock::new(),
+            })))
+        })
+        .collect())
+}
+
+/// A commit, its authorship note and review marks read once however many fields use them
+struct CommitData {
+    repo: Arc<Repository>,
+    sha: String,
+    time: i64,
+    author: String,
+    subject: String,
+    log: OnceLock<Option<AuthorshipLog>>,
+    marks: OnceLock<Vec<ReviewMark>>,
+}
+
+impl CommitData {
+    fn log(&self) -> Option<&AuthorshipLog> {
+        self.log
+            .get_or_init(|| get_authorship(&self.repo, &self.sha))
+            .as_ref()
+    }
+
+    fn marks(&self) -> &[ReviewMark] {
+        self.marks
+            .get_or_init(|| review_marks(&self.repo, &self.sha))
+    }
+
+    fn prompt(self: &Arc<Self>, hash: &str) -> Option<PromptNode> {
+        let record = self.log()?.metadata.prompts.get(hash)?;
+        Some(PromptNode {
+            commit: self.sha.clone(),
+            hash: hash.to_string(),
+            record: record.clone(),
+        })
+    }
+}
+
+pub struct CommitNode(Arc<CommitData>);
+
+#[Object(name = "Commit")]
+impl CommitNode {
+    async fn sha(&self) -> &str {
+        &self.0.sha
+    }
+
+    /// Unix time of the commit
+    async fn time(&self) -> i64 {
+        self.0.time
+    }
+
+    /// `Name <email>`
+    async fn author(&self) -> &str {
+        &self.0.author
+    }
+
+    async fn subject(&self) -> &str {
+        &self.0.subject
+    }
+
+    /// Whether the commit has an authorship note; without one every other list is empty
+    async fn has_authorship(&self) -> bool {
+        self.0.log().is_some()
+    }
+
+    /// AI-authored lines per file, optionally only `path`'s
+    async fn attestations(&self, path: Option<String>) -> Vec<FileAttestationNode> {
+        let Some(log) = self.0.log() else {
+            return Vec::new();
+        };
+        log.attestations
+            .iter()
+            .filter(|file| path.as_ref().is_none_or(|path| file.file_path == *path))
+            .map(|file| FileAttestationNode {
+                commit: Arc::clone(&self.0),
+                path: file.file_path.clone(),
+                entries: file.entries.clone(),
+            })
+            .collect()
+    }
+
+    /// The AI sessions that wrote the commit's lines, as of this commit
+    async fn prompts(&self) -> Vec<PromptNode> {
+        let Some(log) = self.0.log() else {
+            return Vec::new();
+        };
+        log.metadata
+            .prompts
+            .keys()
+            .filter_map(|hash| self.0.prompt(hash))
+            .collect()
+    }
+
+    async fn prompt(&self, hash: String) -> Option<PromptNode> {
+        self.0.prompt(&hash)
+    }
+
+    /// Reviewers' sign-offs on lines the commit added, oldest first
+    async fn review_marks(&self) -> Vec<ReviewMarkNode> {
+        self.0.marks().iter().map(ReviewMarkNode::from).collect()
+    }
+
+    /// How many of the commit's AI lines a review mark covers
+    async fn reviewed_ai_lines(&self) -> u32 {
+        self.0
+            .log()
+            .map_or(0, |log| reviewed_ai_lines(log, self.0.marks()))
+    }
+}
+
+pub struct FileAttestationNode {
+    commit: Arc<CommitData>,
+    path: String,
+    entries: Vec<AttestationEntry>,
+}
+
+#[Object(name = "FileAttestation")]
+impl FileAttestationNode {
+    async fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The file's path before this commit renamed it, to follow it into older commits
+    async fn renamed_from(&self) -> Option<String> {
+        self.commit
+            .log()
+            .and_then(|log| log.metadata.renamed_from(&self.path))
+            .map(str::to_string)
+    }
+
+    async fn entries(&self) -> Vec<AttestationEntryNode> {
+        self.entries
+            .iter()
+            .map(|entry| AttestationEntryNode {
+                commit: Arc::clone(&self.commit),
+                path: self.path.clone(),
+                entry: entry.clone(),
+            })
+            .collect()
+    }
+}
+
+pub struct AttestationEntryNode {
+    commit: Arc<CommitData>,
+    path: String,
+    entry: AttestationEntry,
+}
+
+#[Object(name = "AttestationEntry")]
+impl AttestationEntryNode {
+    async fn prompt_hash(&self) -> &str {
+        &self.entry.hash
+    }
+
+    /// Lines of the file, as of the commit, the prompt wrote
+    async fn lines(&self) -> Vec<LineSpan> {
+        self.entry.line_ranges.iter().map(LineSpan::from).collect()
+    }
+
+    /// Of `lines`, those a human edited without taking over the attribution
+    async fn overridden_lines(&self) -> Vec<LineSpan> {
+        self.entry
+            .overridden_lines
+            .iter()
+            .map(LineSpan::from)
+            .collect()
+    }
+
+    /// How certain the attribution is, as a percentage; null when it was recorded directly
+    async fn confidence(&self) -> Option<u8> {
+        self.entry.confidence
+    }
+
+    async fn prompt(&self) -> Option<PromptNode> {
+        self.commit.prompt(&self.entry.hash)
+    }
+
+    /// The commit's review marks covering any of `lines`
+    async fn review_marks(&self) -> Vec<ReviewMarkNode> {
+        self.commit
+            .marks()
+            .iter()
+            .filter(|mark| {
+                self.entry
+                    .line_ranges
+                    .iter()
+                    .flat_map(LineRange::expand)
+                    .any(|line| mark.covers(&self.path, line))
+            })
+            .map(ReviewMarkNode::from)
+            .collect()
+    }
+}
+
+/// 1-indexed, inclusive
+#[derive(SimpleObject)]
+pub struct LineSpan {
+    start: u32,
+    end: u32,
+}
+
+impl From<&LineRange> for LineSpan {
+    fn from(range: &LineRange) -> Self {
+        match range {
+            LineRange::Single(line) => LineSpan {
+                start: *line,
+                end: *line,
+            },
+            LineRange::Range(start, end) => LineSpan {
+                start: *start,
+                end: *end,
+            },
+        }
+    }
+}
+
+/// One AI session's record in one commit's note
+pub struct PromptNode {
+    commit: String,
+    hash: String,
+    record: PromptRecord,
+}
+
+#[Object(name = "Prompt")]
+impl PromptNode {
+    /// Also the session's id, the same in every commit it wrote lines in
+    async fn hash(&self) -> &str {
+        &self.hash
+    }
+
+    async fn commit(&self) -> &str {
+        &self.commit
+    }
+
+    async fn tool(&self) -> &str {
+        &self.record.agent_id.tool
+    }
+
+    async fn model(&self) -> &str {
+        &self.record.agent_id.model
+    }
+
+    /// `Name <email>` of the human driving the session, if the note says
+    async fn prompter(&self) -> Option<&str> {
+        self.record.prompter()
+    }
+
+    async fn total_additions(&self) -> u32 {
+        self.record.total_additions
+    }
+
+    async fn total_deletions(&self) -> u32 {
+        self.record.total_deletions
+    }
+
+    /// Lines committed as the session wrote them
+    async fn accepted_lines(&self) -> u32 {
+        self.record.accepted_lines
+    }
+
+    /// Lines the session wrote that a human edited before committing
+    async fn overridden_lines(&self) -> u32 {
+        self.record.overriden_lines
+    }
+
+    /// The transcript, if the note kept it
+    async fn messages(&self) -> Vec<MessageNode> {
+        self.record.messages.iter().map(MessageNode::from).collect()
+    }
+}
+
+#[derive(SimpleObject)]
+#[graphql(name = "Message")]
+pub struct MessageNode {
+    /// `user`, `assistant` or `tool_use`
+    kind: &'static str,
+    /// What a user or assistant said
+    text: Option<String>,
+    /// The tool a `tool_use` message called
+    tool: Option<String>,
+    timestamp: Option<String>,
+}
+
+impl From<&Message> for MessageNode {
+    fn from(message: &Message) -> Self {
+        match message {
+            Message::User { text, timestamp } => MessageNode {
+                kind: "user",
+                text: Some(text.clone()),
+                tool: None,
+                timestamp: timestamp.clone(),
+            },
+            Message::Assistant { text, timestamp } => MessageNode {
+                kind: "assistant",
+                text: Some(text.clone()),
+                tool: None,
+                timestamp: timestamp.clone(),
+            },
+            Message::ToolUse {
+                name, timestamp, ..
+            } => MessageNode {
+                kind: "tool_use",
+                text: None,
+                tool: Some(name.clone()),
+                timestamp: timestamp.clone(),
+            },
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+#[graphql(name = "ReviewMark")]
+pub struct ReviewMarkNode {
+    file: String,
+    /// First line marked, in the file as of the marked commit; null for the whole file
+    start_line: Option<u32>,
+    end_line: Option<u32>,
+    /// Who marked the lines reviewed
+    by: String,
+    /// Unix time of the mark
+    at: i64,
+}
+
+impl From<&ReviewMark> for ReviewMarkNode {
+    fn from(mark: &ReviewMark) -> Self {
+        ReviewMarkNode {
+            file: mark.file.clone(),
+            start_line: mark.lines.map(|(start, _)| start),
+            end_line: mark.lines.map(|(_, end)| end),
+            by: mark.by.clone(),
+            at: mark.at,
+        }
+    }
+}
+
+/// One AI session, summed over the commits it wrote lines in
+pub struct SessionNode {
+    repo: Arc<Repository>,
+    id: String,
+    tool: String,
+    model: String,
+    prompter: Option<String>,
+    /// Newest first
+    commits: Vec<String>,
+    total_additions: u32,
+    accepted_lines: u32,
+    overriden_lines: u32,
+}
+
+#[Object(name = "Session")]
+impl SessionNode {
+    /// The hash its prompts have in every commit's note
+    async fn id(&self) -> &str {
+        &self.id
+    }
+
+    async fn tool(&self) -> &str {
+        &self.tool
+    }
+
+    async fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// `Name <email>` of the human driving the session, if the notes say
+    async fn prompter(&self) -> Option<&str> {
+        self.prompter.as_deref()
+    }
+
+    async fn total_additions(&self) -> u32 {
+        self.total_additions
+    }
+
+    async fn accepted_lines(&self) -> u32 {
+        self.accepted_lines
+    }
+
+    async fn overridden_lines(&self) -> u32 {
+        self.overriden_lines
+    }
+
+    /// Commits the session wrote lines in, newest first
+    async fn commits(&self) -> Result<Vec<CommitNode>> {
+        let mut args = vec!["log", "--no-walk=unsorted", COMMIT_FORMAT];
+        args.extend(self.commits.iter().map(String::as_str));
+        commit_nodes(&self.repo, &args)
+    }
+}
+
+/// Sessions of the last `commits` commits `rev` selects, most recently committed first
+fn sessions_in(repo: &Arc<Repository>, rev: &str, commits: usize) -> Result<Vec<SessionNode>> {
+    let count_arg = format!("--max-count={}", commits);
+    let output = repo
+        .git(&["log", "--format=%H", &count_arg, rev])
+        .map_err(|e| Error::new(e.to_string()))?;
+    let mut sessions: Vec<SessionNode> = Vec::new();
+    let mut index: BTreeMap<String, usize> = BTreeMap::new();
+    for commit in output.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        let Some(log) = get_authorship(repo, commit) else {
+            continue;
+        };
+        for (hash, record) in &log.metadata.prompts {
+            let i = *index.entry(hash.clone()).or_insert_with(|| {
+                sessions.push(SessionNode {
+                    repo: Arc::clone(repo),
+                    id: hash.clone(),
+                    tool: record.agent_id.tool.clone(),
+                    model: record.agent_id.model.clone(),
+                    prompter: record.prompter().map(str::to_string),
+                    commits: Vec::new(),
+                    total_additions: 0,
+                    accepted_lines: 0,
+                    overriden_lines: 0,
+                });
+                sessions.len() - 1
+            });
+            let session = &mut sessions[i];
+            session.commits.push(commit.to_string());
+            session.total_additions += record.total_additions;
+            session.accepted_lines += record.accepted_lines;
+            session.overriden_lines += record.overriden_lines;
+        }
+    }
+    Ok(sessions)
+}
@@ -0,0 +1,291 @@
+// The web UI of `git-ai serve --web`. Everything it shows comes from the server's JSON API,
+// called with the token the server printed in the link's fragment.
+"use strict";
+
+const token = (() => {
+  const match = location.hash.match(/token=([^&]+)/);
+  if (match) {
+    sessionStorage.setItem("git-ai-token", decodeURIComponent(match[1]));
+    history.replaceState(null, "", location.pathname);
+  }
+  return sessionStorage.getItem("git-ai-token");
+})();
+
+const main = document.getElementById("main");
+const popover = document.getElementById("popover");
+const status = document.getElementById("status");
+const repoSelect = document.getElementById("repo");
+let view = "files";
+
+async function api(path) {
+  const response = await fetch(path, { headers: { Authorization: `Bearer ${token}` } });
+  const body = await response.json();
+  if (!response.ok) {
+    throw new Error(body.error || response.statusText);
+  }
+  return body;
+}
+
+function repoPath(path) {
+  return `/repos/${encodeURIComponent(repoSelect.value)}${path}`;
+}
+
+function el(tag, attrs = {}, ...children) {
+  const node = document.createElement(tag);
+  for (const [key, value] of Object.entries(attrs)) {
+    if (key === "class") node.className = value;
+    else if (key.startsWith("on")) node.addEventListener(key.slice(2), value);
+    else node.setAttribute(key, value);
+  }
+  for (const child of children) {
+    if (child !== null && child !== undefined) {
+      node.append(child instanceof Node ? child : String(child));
+    }
+  }
+  return node;
+}
+
+// Gray for no AI, then green, yellow, red and magenta as the AI share grows, as in `git-ai tui`
+function heat(percent) {
+  if (percent === 0) return "heat-0";
+  if (percent < 25) return "heat-1";
+  if (percent < 50) return "heat-2";
+  if (percent < 75) return "heat-3";
+  return "heat-4";
+}
+
+function percentOf(ai, total) {
+  return total ? Math.floor((ai * 100) / total) : 0;
+}
+
+async function load(label, what, render) {
+  status.textContent = `Loading ${label}...`;
+  main.replaceChildren();
+  try {
+    render(await what());
+    status.textContent = "";
+  } catch (e) {
+    status.textContent = `Failed: ${e.message}`;
+  }
+}
+
+// Files: the tree at HEAD, each entry colored by the share of its lines AI wrote
+
+function buildTree(files) {
+  const root = { name: "", children: new Map(), ai: 0, total: 0 };
+  for (const file of files) {
+    let node = root;
+    const parts = file.path.split("/");
+    parts.forEach((part, i) => {
+      node.ai += file.ai_lines;
+      node.total += file.total_lines;
+      if (!node.children.has(part)) {
+        node.children.set(part, {
+          name: part,
+          path: parts.slice(0, i + 1).join("/"),
+          children: new Map(),
+          ai: 0,
+          total: 0,
+          isDir: i + 1 < parts.length,
+        });
+      }
+      node = node.children.get(part);
+    });
+    node.ai += file.ai_lines;
+    node.total += file.total_lines;
+  }
+  return root;
+}
+
+function renderTree(node) {
+  const list = el("ul", { class: "tree" });
+  const children = [...node.children.values()].sort(
+    (a, b) => b.isDir - a.isDir || a.name.localeCompare(b.name),
+  );
+  for (const child of children) {
+    const percent = percentOf(child.ai, child.total);
+    const label = el(
+      "span",
+      { class: `entry ${heat(percent)}` },
+      child.isDir ? `${child.name}/` : child.name,
+      el("span", { class: "percent" }, `${percent}% AI`),
+    );
+    const item = el("li", {}, label);
+    if (child.isDir) {
+      const sub = renderTree(child);
+      sub.hidden = true;
+      label.addEventListener("click", () => (sub.hidden = !sub.hidden));
+      item.append(sub);
+    } else {
+      label.addEventListener("click", () => showBlame(child.path));
+    }
+    list.append(item);
+  }
+  return list;
+}
+
+function showFiles() {
+  load(
+    "files",
+    () => api(repoPath("/files")),
+    (body) => main.append(renderTree(buildTree(body.files))),
+  );
+}
+
+// Blame: one file's lines, AI lines highlighted, with the prompt behind each on click
+
+function showBlame(path) {
+  const query = `?path=${encodeURIComponent(path)}`;
+  load(
+    path,
+    () => Promise.all([api(repoPath(`/contents${query}`)), api(repoPath(`/blame${query}`))]),
+    ([contents, blame]) => {
+      const authors = new Map(blame.lines.map((line) => [line.line, line]));
+      const table = el("table", { class: "blame" });
+      contents.contents.replace(/\n$/, "").split("\n").forEach((text, i) => {
+        const line = authors.get(i + 1);
+        const author = line ? line.author : null;
+        const isAi = author && author.kind === "ai";
+        const label = !author ? "" : isAi ? `${author.tool} ${author.model}` : author.name;
+        const row = el(
+          "tr",
+          { class: isAi ? "ai" : "" },
+          el("td", { class: "num" }, i + 1),
+          el("td", { class: "author" }, label),
+          el("td", { class: "code" }, el("pre", {}, text)),
+        );
+        if (isAi && line.commit) {
+          row.addEventListener("click", (event) =>
+            showPrompt(line.commit, author.prompt_hash, event),
+          );
+        }
+        table.append(row);
+      });
+      main.append(el("h2", {}, path), table);
+    },
+  );
+}
+
+async function showPrompt(commit, hash, event) {
+  popover.replaceChildren("Loading...");
+  popover.style.left = `${event.pageX + 8}px`;
+  popover.style.top = `${event.pageY + 8}px`;
+  popover.hidden = false;
+  try {
+    const prompt = await api(repoPath(`/commits/${commit}/prompts/${hash}`));
+    const messages = prompt.messages
+      .filter((message) => message.type === "user" || message.type === "assistant")
+      .slice(0, 6)
+      .map((message) => el("div", { class: `message ${message.type}` }, message.text));
+    popover.replaceChildren(
+      el("button", { class: "close", onclick: () => (popover.hidden = true) }, "×"),
+      el("strong", {}, `${prompt.agent_id.tool} ${prompt.agent_id.model}`),
+      el("div", {}, `Prompted by ${prompt.prompter || "unknown"}`),
+      el(
+        "div",
+        {},
+        `${prompt.accepted_lines} accepted of ${prompt.total_additions} added, ${prompt.overriden_lines} edited by a human`,
+      ),
+      el("div", { class: "commit" }, `Commit ${commit.slice(0, 10)}`),
+      ...messages,
+    );
+  } catch (e) {
+    popover.replaceChildren(`Failed: ${e.message}`);
+  }
+}
+
+// Sessions: the AI prompts in recent commits
+
+function showSessions() {
+  load(
+    "sessions",
+    () => api(repoPath("/sessions")),
+    (body) => {
+      const table = el(
+        "table",
+        { class: "sessions" },
+        el(
+          "tr",
+          {},
+          ...["When", "Commit", "Agent", "Prompted by", "Accepted", "Added", "Messages"].map(
+            (heading) => el("th", {}, heading),
+          ),
+        ),
+      );
+      for (const session of body.sessions) {
+        table.append(
+          el(
+            "tr",
+            { onclick: (event) => showPrompt(session.commit, session.prompt_hash, event) },
+            el("td", {}, new Date(session.time * 1000).toLocaleString()),
+            el("td", { title: session.subject }, `${session.commit.slice(0, 8)} ${session.subject}`),
+            el("td", {}, `${session.tool} ${session.model}`),
+            el("td", {}, session.author || ""),
+            el("td", { class: "num" }, session.accepted_lines),
+            el("td", { class: "num" }, session.total_additions),
+            el("td", { class: "num" }, session.messages),
+          ),
+        );
+      }
+      main.append(table);
+    },
+  );
+}
+
+// Trends: the AI share of the lines each recent commit added
+
+function showTrends() {
+  load(
+    "trends",
+    () => api(repoPath("/trend?commits=60")),
+    (body) => {
+      const chart = el("div", { class: "trend" });
+      for (const commit of body.commits) {
+        const percent = commit.ai_percent;
+        chart.append(
+          el("div", {
+            class: `bar ${percent === null ? "empty" : heat(percent)}`,
+            style: `height: ${percent === null ? 0 : Math.max(percent, 1)}%`,
+            title: `${commit.commit.slice(0, 8)} ${commit.subject}: ${
+              percent === null ? "no added lines" : `${percent}% AI`
+            }`,
+          }),
+        );
+      }
+      main.append(el("h2", {}, "AI share of added lines, last 60 commits"), chart);
+    },
+  );
+}
+
+const views = { files: showFiles, sessions: showSessions, trends: showTrends };
+
+function show(name) {
+  view = name;
+  popover.hidden = true;
+  for (const button of document.querySelectorAll("nav button")) {
+    button.classList.toggle("active", button.dataset.view === name);
+  }
+  views[name]();
+}
+
+document.querySelectorAll("nav button").forEach((button) =>
+  button.addEventListener("click", () => show(button.dataset.view)),
+);
+repoSelect.addEventListener("change", () => show(view));
+document.addEventListener("keydown", (event) => {
+  if (event.key === "Escape") popover.hidden = true;
+});
+
+(async () => {
+  if (!token) {
+    status.textContent = "Open the link `git-ai serve --web` printed, which carries the token";
+    return;
+  }
+  try {
+    const { repos } = await api("/repos");
+    repoSelect.replaceChildren(...repos.map((id) => el("option", { value: id }, id)));
+    show("files");
+  } catch (e) {
+    status.textContent = `Failed: ${e.message}`;
+  }
+})();
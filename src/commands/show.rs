@@ -1,4 +1,4 @@
-use crate::error::GitAiError;
+use crate::error::{GitAiError, exit_with_error};
 use crate::git::find_repository;
 use crate::git::refs::{CommitAuthorship, get_commits_with_notes_from_list};
 use crate::git::repository::{CommitRange, Repository};
@@ -6,6 +6,8 @@ use crate::git::repository::{CommitRange, Repository};
 const NO_AUTHORSHIP_DATA_MESSAGE: &str = "No authorship data found for this revision";
 
 pub fn handle_show(args: &[String]) {
+    let prompters = args.iter().any(|arg| arg == "--prompters");
+    let args: Vec<&String> = args.iter().filter(|arg| *arg != "--prompters").collect();
     if args.is_empty() {
         eprintln!("Error: show requires a revision or range");
         std::process::exit(1);
@@ -19,18 +21,17 @@ pub fn handle_show(args: &[String]) {
     let repo = match find_repository(&Vec::<String>::new()) {
         Ok(repo) => repo,
         Err(e) => {
-            eprintln!("Failed to find repository: {}", e);
-            std::process::exit(1);
+            exit_with_error("Failed to find repository", &e);
         }
     };
 
-    if let Err(e) = show_authorship(&repo, &args[0]) {
-        eprintln!("Failed to show authorship: {}", e);
-        std::process::exit(1);
+    if let Err(e) = show_authorship(&repo, args[0], prompters) {
+        exit_with_error("Failed to show authorship", &e);
     }
 }
 
-fn show_authorship(repo: &Repository, spec: &str) -> Result<(), GitAiError> {
+/// Prints each commit's authorship log, or with `prompters` just who prompted each AI session
+fn show_authorship(repo: &Repository, spec: &str, prompters: bool) -> Result<(), GitAiError> {
     let commits = resolve_commits(repo, spec)?;
     if commits.is_empty() {
         println!("{}", NO_AUTHORSHIP_DATA_MESSAGE);
@@ -54,6 +55,18 @@ fn show_authorship(repo: &Repository, spec: &str) -> Result<(), GitAiError> {
                 if multiple_commits {
                     println!("{}", sha);
                 }
+                if prompters {
+                    for (session_id, record) in &authorship_log.metadata.prompts {
+                        println!(
+                            "{}  {}::{}  {}",
+                            session_id,
+                            record.agent_id.tool,
+                            record.agent_id.model,
+                            record.prompter().unwrap_or("unknown")
+                        );
+                    }
+                    continue;
+                }
                 let serialized = authorship_log.serialize_to_string().map_err(|_| {
                     GitAiError::Generic("Failed to serialize authorship log".to_string())
                 })?;
@@ -71,7 +84,7 @@ fn show_authorship(repo: &Repository, spec: &str) -> Result<(), GitAiError> {
     Ok(())
 }
 
-fn resolve_commits(repo: &Repository, spec: &str) -> Result<Vec<String>, GitAiError> {
+pub(crate) fn resolve_commits(repo: &Repository, spec: &str) -> Result<Vec<String>, GitAiError> {
     if let Some((start, end)) = spec.split_once("..") {
         if start.is_empty() || end.is_empty() {
             return Err(GitAiError::Generic(
@@ -1,11 +1,16 @@
 use crate::authorship::rebase_authorship::rewrite_authorship_after_squash_or_rebase;
+use crate::error::exit_with_error;
 use crate::git::find_repository_in_path;
+use crate::git::hosting::{HostingProvider, fetch_review_head};
 
 pub fn handle_squash_authorship(args: &[String]) {
     // Parse squash-authorship-specific arguments
     let mut base_branch = None;
     let mut new_sha = None;
     let mut old_sha = None;
+    let mut pr_number = None;
+    let mut remote = "origin".to_string();
+    let mut provider = None;
 
     let mut i = 0;
     while i < args.len() {
@@ -14,6 +19,27 @@ pub fn handle_squash_authorship(args: &[String]) {
                 // Dry-run flag is parsed but not used in current implementation
                 i += 1;
             }
+            "--pr" | "--remote" | "--provider" if i + 1 < args.len() => {
+                let value = &args[i + 1];
+                match args[i].as_str() {
+                    "--pr" => match value.trim_start_matches(['#', '!']).parse::<u32>() {
+                        Ok(number) => pr_number = Some(number),
+                        Err(_) => {
+                            eprintln!("Error: --pr expects a review number, got '{}'", value);
+                            std::process::exit(1);
+                        }
+                    },
+                    "--remote" => remote = value.clone(),
+                    _ => match HostingProvider::parse(value) {
+                        Ok(parsed) => provider = Some(parsed),
+                        Err(e) => {
+                            eprintln!("Error: {}", e);
+                            std::process::exit(1);
+                        }
+                    },
+                }
+                i += 2;
+            }
             _ => {
                 // Positional arguments: base_branch, new_sha, old_sha
                 if base_branch.is_none() {
@@ -37,7 +63,7 @@ pub fn handle_squash_authorship(args: &[String]) {
         None => {
             eprintln!("Error: base_branch argument is required");
             eprintln!(
-                "Usage: git-ai squash-authorship <base_branch> <new_sha> <old_sha> [--dry-run]"
+                "Usage: git-ai squash-authorship <base_branch> <new_sha> (<old_sha> | --pr <number>) [--remote <name>] [--provider <github|gitlab|bitbucket>] [--dry-run]"
             );
             std::process::exit(1);
         }
@@ -48,34 +74,48 @@ pub fn handle_squash_authorship(args: &[String]) {
         None => {
             eprintln!("Error: new_sha argument is required");
             eprintln!(
-                "Usage: git-ai squash-authorship <base_branch> <new_sha> <old_sha> [--dry-run]"
+                "Usage: git-ai squash-authorship <base_branch> <new_sha> (<old_sha> | --pr <number>) [--remote <name>] [--provider <github|gitlab|bitbucket>] [--dry-run]"
             );
             std::process::exit(1);
         }
     };
 
-    let old_sha = match old_sha {
-        Some(s) => s,
-        None => {
-            eprintln!("Error: old_sha argument is required");
-            eprintln!(
-                "Usage: git-ai squash-authorship <base_branch> <new_sha> <old_sha> [--dry-run]"
-            );
-            std::process::exit(1);
-        }
-    };
-
-    // TODO Think about whether or not path should be an optional argument
-
     // Find the git repository
     let repo = match find_repository_in_path(".") {
         Ok(repo) => repo,
         Err(e) => {
-            eprintln!("Failed to find repository: {}", e);
+            exit_with_error("Failed to find repository", &e);
+        }
+    };
+
+    // Without the old head, fetch it from the review the remote squash-merged
+    let old_sha = match (old_sha, pr_number) {
+        (Some(s), _) => s,
+        (None, Some(number)) => {
+            let provider = provider.unwrap_or_else(|| HostingProvider::detect(&repo, &remote));
+            match fetch_review_head(&repo, provider, &remote, number) {
+                Ok(sha) => sha,
+                Err(e) => exit_with_error(
+                    &format!(
+                        "Failed to fetch {} from {}",
+                        provider.head_ref(number),
+                        remote
+                    ),
+                    &e,
+                ),
+            }
+        }
+        (None, None) => {
+            eprintln!("Error: old_sha argument or --pr is required");
+            eprintln!(
+                "Usage: git-ai squash-authorship <base_branch> <new_sha> (<old_sha> | --pr <number>) [--remote <name>] [--provider <github|gitlab|bitbucket>] [--dry-run]"
+            );
             std::process::exit(1);
         }
     };
 
+    // TODO Think about whether or not path should be an optional argument
+
     // Use the same function as CI handlers to create authorship log for the new commit
     if let Err(e) = rewrite_authorship_after_squash_or_rebase(
         &repo,
@@ -85,7 +125,6 @@ pub fn handle_squash_authorship(args: &[String]) {
         &new_sha,     // merge_commit_sha - the new commit
         false,        // suppress_output
     ) {
-        eprintln!("Squash authorship failed: {}", e);
-        std::process::exit(1);
+        exit_with_error("Squash authorship failed", &e);
     }
 }
@@ -0,0 +1,88 @@
+use crate::authorship::commit_summary::{CommitSummary, summarize_uncommitted};
+use crate::authorship::working_log::CheckpointKind;
+use crate::commands::checkpoint;
+use crate::commands::hooks::commit_hooks::get_commit_default_author;
+use crate::error::exit_with_error;
+use crate::git::find_repository;
+
+/// `git-ai status [--json]`: who wrote the uncommitted changes to tracked files. Checkpoints
+/// your edits first, as a commit would, so the answer is current.
+pub fn handle_status(args: &[String]) {
+    let mut json = false;
+    for arg in args {
+        match arg.as_str() {
+            "--json" => json = true,
+            _ => {
+                eprintln!("Error: unknown status argument '{}'", arg);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let repo = match find_repository(&Vec::<String>::new()) {
+        Ok(repo) => repo,
+        Err(e) => exit_with_error("Failed to find repository", &e),
+    };
+    let author = get_commit_default_author(&repo, &[]);
+    if let Err(e) = checkpoint::run(
+        &repo,
+        &author,
+        CheckpointKind::Human,
+        false,
+        false,
+        true,
+        None,
+        false,
+        false,
+    ) {
+        exit_with_error("Failed to checkpoint", &e);
+    }
+    let (summary, files) = match summarize_uncommitted(&repo) {
+        Ok(summaries) => summaries,
+        Err(e) => exit_with_error("Failed to summarize uncommitted changes", &e),
+    };
+
+    if json {
+        let files: serde_json::Map<String, serde_json::Value> = files
+            .iter()
+            .map(|(file, summary)| (file.clone(), summary_json(summary)))
+            .collect();
+        let mut output = summary_json(&summary);
+        output["models"] = serde_json::json!(summary.models);
+        output["sessions"] = serde_json::json!(summary.sessions);
+        output["files"] = serde_json::Value::Object(files);
+        println!("{}", output);
+        return;
+    }
+
+    if files.is_empty() {
+        println!("No uncommitted changes");
+        return;
+    }
+    println!("Uncommitted: {}", summary.describe());
+    let width = files.keys().map(String::len).max().unwrap_or(0);
+    for (file, file_summary) in &files {
+        let mut line = format!(
+            "  {:<width$}  {} AI, {} human",
+            file,
+            file_summary.ai_lines,
+            file_summary.human_lines,
+            width = width
+        );
+        if file_summary.possible_ai_lines > 0 {
+            line.push_str(&format!(
+                " ({} possibly pasted from AI)",
+                file_summary.possible_ai_lines
+            ));
+        }
+        println!("{}", line);
+    }
+}
+
+fn summary_json(summary: &CommitSummary) -> serde_json::Value {
+    serde_json::json!({
+        "ai_lines": summary.ai_lines,
+        "human_lines": summary.human_lines,
+        "possible_ai_lines": summary.possible_ai_lines,
+    })
+}
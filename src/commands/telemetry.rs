@@ -0,0 +1,47 @@
+use crate::config::Config;
+use crate::error::{GitAiError, exit_with_error};
+use crate::observability::usage::{self, UsageSpool};
+
+pub fn handle_telemetry(args: &[String]) {
+    let Some(path) = usage::spool_path() else {
+        exit_with_error(
+            "Failed to locate usage spool",
+            &GitAiError::Generic("no home directory".to_string()),
+        );
+    };
+
+    match args.first().map(String::as_str) {
+        Some("show") | None => {
+            let enabled = Config::get().usage_telemetry_enabled();
+            println!(
+                "Usage telemetry is {} (usage_telemetry in ~/.git-ai/config.json)",
+                if enabled { "on" } else { "off" }
+            );
+            let spool = UsageSpool::load(&path);
+            if spool.is_empty() {
+                println!("Nothing recorded");
+            } else {
+                match serde_json::to_string_pretty(&spool) {
+                    Ok(json) => println!("{}", json),
+                    Err(e) => exit_with_error("Failed to show usage counters", &e.into()),
+                }
+            }
+        }
+        Some("send") => match usage::send(&path) {
+            Ok(spool) if spool.is_empty() => println!("Nothing to send"),
+            Ok(_) => println!("Sent usage counters and cleared the local spool"),
+            Err(e) => exit_with_error("Failed to send usage counters", &e),
+        },
+        Some("clear") => match usage::clear(&path) {
+            Ok(()) => println!("Cleared usage counters"),
+            Err(e) => exit_with_error("Failed to clear usage counters", &e),
+        },
+        Some(other) => {
+            eprintln!(
+                "Error: unknown telemetry command '{}', expected show, send or clear",
+                other
+            );
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use crate::authorship::timeline::{TimelineEntry, file_timeline};
+use crate::error::exit_with_error;
+use crate::git::find_repository;
+
+pub fn handle_timeline(args: &[String]) {
+    let mut file_path = None;
+    let mut rev = "HEAD".to_string();
+    let mut json = false;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--json" => json = true,
+            "--rev" if i + 1 >= args.len() => {
+                eprintln!("Error: --rev requires a value");
+                std::process::exit(1);
+            }
+            "--rev" => {
+                rev = args[i + 1].clone();
+                i += 1;
+            }
+            arg if file_path.is_none() && !arg.starts_with('-') => {
+                file_path = Some(arg.to_string());
+            }
+            arg => {
+                eprintln!("Error: unexpected argument '{}'", arg);
+                std::process::exit(1);
+            }
+        }
+        i += 1;
+    }
+
+    let Some(file_path) = file_path else {
+        eprintln!("Error: timeline requires a file argument");
+        std::process::exit(1);
+    };
+
+    let repo = match find_repository(&Vec::<String>::new()) {
+        Ok(repo) => repo,
+        Err(e) => {
+            exit_with_error("Failed to find repository", &e);
+        }
+    };
+
+    let timeline = match file_timeline(&repo, &file_path, &rev) {
+        Ok(timeline) => timeline,
+        Err(e) => {
+            exit_with_error("Timeline failed", &e);
+        }
+    };
+
+    if json {
+        match serde_json::to_string(&timeline) {
+            Ok(json) => println!("{}", json),
+            Err(e) => {
+                eprintln!("Failed to serialize timeline: {}", e);
+                std::process::exit(1);
+            }
+        }
+    } else {
+        print_timeline(&timeline);
+    }
+}
+
+fn print_timeline(timeline: &[TimelineEntry]) {
+    println!(
+        "{:<8}  {:<10}  {:>8}  {:>8}  {:>8}  {:>6}  summary",
+        "commit", "date", "+ai", "+human", "-lines", "ai"
+    );
+    let mut path: Option<&str> = None;
+    for entry in timeline {
+        if path.is_some_and(|p| p != entry.path) {
+            println!("          renamed to {}", entry.path);
+        }
+        path = Some(&entry.path);
+        let date = chrono::DateTime::from_timestamp(entry.timestamp, 0)
+            .map(|d| d.format("%Y-%m-%d").to_string())
+            .unwrap_or_default();
+        println!(
+            "{:<8}  {:<10}  {:>8}  {:>8}  {:>8}  {:>5.1}%  {} ({})",
+            &entry.commit[..entry.commit.len().min(8)],
+            date,
+            entry.ai_added,
+            entry.human_added,
+            entry.deleted,
+            entry.ai_percent(),
+            entry.summary,
+            entry.author
+        );
+    }
+}
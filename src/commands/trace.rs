@@ -0,0 +1,450 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::commands::git_handlers::run_git;
+use crate::error::{GitAiError, exit_with_error};
+use crate::git::find_repository;
+use crate::git::refs::AI_AUTHORSHIP_REFNAME;
+use crate::git::repository::{Repository, exec_git_stdin};
+use crate::observability::trace;
+use crate::utils::now_secs;
+
+const BUNDLE_VERSION: u32 = 1;
+
+/// What under .git/ai isn't part of the state a command starts from: logs, caches and locks
+const UNRECORDED_AI_PATHS: &[&str] = &["logs", "attribution_snapshots", "working_logs.lock"];
+
+/// Everything `git-ai trace` saw of a git command: the state it started from, each decision
+/// git-ai made while it ran and the notes it wrote. Enough to run the command again on a copy
+/// of the repository and compare.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraceBundle {
+    pub version: u32,
+    pub git_ai_version: String,
+    /// Unix time the command started
+    pub recorded_at: u64,
+    /// The git command, without `git`
+    pub command: Vec<String>,
+    pub exit_code: Option<i32>,
+    pub head_before: Option<String>,
+    /// None when HEAD was detached
+    pub branch_before: Option<String>,
+    pub notes_before: Option<String>,
+    pub head_after: Option<String>,
+    /// The files under .git/ai before the command, by path relative to it
+    pub ai_state: BTreeMap<String, String>,
+    /// Staged changes before the command, as `git diff --cached --binary`
+    pub index_patch: String,
+    /// Unstaged changes before the command, as `git diff --binary`
+    pub worktree_patch: String,
+    /// Every span and debug event recorded while the command ran, oldest first
+    pub events: Vec<Value>,
+    /// The notes the command wrote, in order
+    pub notes: Vec<WrittenNote>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WrittenNote {
+    pub notes_ref: String,
+    pub commit: String,
+    pub note: String,
+}
+
+/// An authorship note the replay wrote differently from the recorded run, or didn't write
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoteDifference {
+    /// 1-based, in the order the notes were written
+    pub index: usize,
+    pub recorded: Option<WrittenNote>,
+    pub replayed: Option<WrittenNote>,
+}
+
+pub fn handle_trace(args: &[String]) {
+    match args.first().map(String::as_str) {
+        Some("replay") => handle_replay(&args[1..]),
+        _ => handle_record(args),
+    }
+}
+
+/// `git-ai trace [-o <bundle>] <git command>...`: runs the command through git-ai, recording
+/// what it did to a bundle
+fn handle_record(args: &[String]) {
+    let mut output = None;
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "-o" | "--output" => {
+                let Some(path) = args.get(i + 1) else {
+                    eprintln!("Error: {} requires a value", args[i]);
+                    std::process::exit(1);
+                };
+                output = Some(path.clone());
+                i += 2;
+            }
+            _ => break,
+        }
+    }
+    let mut command = &args[i..];
+    if command.first().is_some_and(|arg| arg == "git") {
+        command = &command[1..];
+    }
+    if command.is_empty() {
+        eprintln!("Error: expected a git command to trace, e.g. git-ai trace commit -m \"...\"");
+        std::process::exit(1);
+    }
+
+    let repo = match find_repository(&Vec::<String>::new()) {
+        Ok(repo) => repo,
+        Err(e) => {
+            exit_with_error("Failed to find repository", &e);
+        }
+    };
+    let mut bundle = match capture_state(&repo, command) {
+        Ok(bundle) => bundle,
+        Err(e) => {
+            exit_with_error("Failed to record the repository state", &e);
+        }
+    };
+
+    // Only what the command itself does goes into the bundle
+    trace::take_recording();
+    let status = run_git(command);
+    bundle.events = trace::take_recording();
+    bundle.notes = written_notes(&bundle.events);
+    bundle.exit_code = status.code();
+    bundle.head_after = rev_parse(&repo, "HEAD");
+
+    let output = output.unwrap_or_else(|| format!("git-ai-trace-{}.json", bundle.recorded_at));
+    let written = serde_json::to_string_pretty(&bundle)
+        .map_err(GitAiError::from)
+        .and_then(|json| fs::write(&output, json).map_err(GitAiError::from));
+    if let Err(e) = written {
+        exit_with_error("Failed to write the trace bundle", &e);
+    }
+    eprintln!(
+        "git-ai: recorded {} events and {} notes to {}",
+        bundle.events.len(),
+        bundle.notes.len(),
+        output
+    );
+    std::process::exit(status.code().unwrap_or(1));
+}
+
+/// `git-ai trace replay <bundle>`: puts the repository back in the state a bundle was recorded
+/// in, runs its command again and compares the notes written
+fn handle_replay(args: &[String]) {
+    let [path] = args else {
+        eprintln!("Error: expected the path of one trace bundle");
+        std::process::exit(1);
+    };
+    let bundle: TraceBundle = match fs::read_to_string(path)
+        .map_err(GitAiError::from)
+        .and_then(|json| serde_json::from_str(&json).map_err(GitAiError::from))
+    {
+        Ok(bundle) => bundle,
+        Err(e) => {
+            exit_with_error(&format!("Failed to read trace bundle {}", path), &e);
+        }
+    };
+    if bundle.version > BUNDLE_VERSION {
+        exit_with_error(
+            "Failed to read trace bundle",
+            &GitAiError::Generic(format!(
+                "{} was recorded by git-ai {}, upgrade to replay it",
+                path, bundle.git_ai_version
+            )),
+        );
+    }
+
+    let repo = match find_repository(&Vec::<String>::new()) {
+        Ok(repo) => repo,
+        Err(e) => {
+            exit_with_error("Failed to find repository", &e);
+        }
+    };
+    if let Err(e) = restore_state(&repo, &bundle) {
+        exit_with_error("Failed to restore the recorded state", &e);
+    }
+
+    trace::take_recording();
+    let status = run_git(&bundle.command);
+    let replayed = written_notes(&trace::take_recording());
+
+    eprintln!(
+        "Replayed `git {}` (recorded with git-ai {}): git exited with {} (recorded {})",
+        bundle.command.join(" "),
+        bundle.git_ai_version,
+        exit_code(status.code()),
+        exit_code(bundle.exit_code)
+    );
+    let differences = compare_notes(&bundle.notes, &replayed);
+    for difference in &differences {
+        eprintln!("note {} differs", difference.index);
+        for (label, note) in [
+            ("recorded", &difference.recorded),
+            ("replayed", &difference.replayed),
+        ] {
+            match note {
+                Some(note) => {
+                    eprintln!("  {} on {}:", label, note.commit);
+                    for line in attestations(&note.note) {
+                        eprintln!("    {}", line);
+                    }
+                }
+                None => eprintln!("  {}: not written", label),
+            }
+        }
+    }
+    let compared = bundle.notes.len().max(replayed.len());
+    if differences.is_empty() {
+        eprintln!("All {} authorship notes match", compared);
+    } else {
+        eprintln!(
+            "{} of {} authorship notes differ",
+            differences.len(),
+            compared
+        );
+        std::process::exit(1);
+    }
+}
+
+fn exit_code(code: Option<i32>) -> String {
+    code.map(|code| code.to_string())
+        .unwrap_or_else(|| "a signal".to_string())
+}
+
+fn capture_state(repo: &Repository, command: &[String]) -> Result<TraceBundle, GitAiError> {
+    let mut ai_state = BTreeMap::new();
+    read_ai_state(&repo.storage.repo_path.join("ai"), "", &mut ai_state)?;
+    Ok(TraceBundle {
+        version: BUNDLE_VERSION,
+        git_ai_version: env!("CARGO_PKG_VERSION").to_string(),
+        recorded_at: now_secs(),
+        command: command.to_vec(),
+        exit_code: None,
+        head_before: rev_parse(repo, "HEAD"),
+        branch_before: repo
+            .git(&["symbolic-ref", "--short", "-q", "HEAD"])
+            .ok()
+            .map(|branch| branch.trim().to_string())
+            .filter(|branch| !branch.is_empty()),
+        notes_before: repo.notes_ref_target(),
+        head_after: None,
+        ai_state,
+        index_patch: repo.git(&["diff", "--cached", "--binary"])?,
+        worktree_patch: repo.git(&["diff", "--binary"])?,
+        events: Vec::new(),
+        notes: Vec::new(),
+    })
+}
+
+fn read_ai_state(
+    dir: &Path,
+    prefix: &str,
+    state: &mut BTreeMap<String, String>,
+) -> Result<(), GitAiError> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        if prefix.is_empty() && UNRECORDED_AI_PATHS.contains(&name.as_str()) {
+            continue;
+        }
+        let relative = format!("{}{}", prefix, name);
+        if entry.file_type()?.is_dir() {
+            read_ai_state(&entry.path(), &format!("{}/", relative), state)?;
+        } else if let Ok(content) = fs::read_to_string(entry.path()) {
+            state.insert(relative, content);
+        }
+    }
+    Ok(())
+}
+
+/// Checks out the commit the bundle started from, with its notes, .git/ai state and uncommitted
+/// changes. Refuses to run over uncommitted changes, which it would throw away.
+fn restore_state(repo: &Repository, bundle: &TraceBundle) -> Result<(), GitAiError> {
+    if !repo.git(&["status", "--porcelain"])?.trim().is_empty() {
+        return Err(GitAiError::Generic(
+            "the working tree has changes, and replaying resets it; replay in a clean copy of \
+             the repository"
+                .to_string(),
+        ));
+    }
+    let head = bundle.head_before.as_deref().ok_or_else(|| {
+        GitAiError::Generic("the command was traced before the first commit".to_string())
+    })?;
+    if rev_parse(repo, &format!("{}^{{commit}}", head)).is_none() {
+        return Err(GitAiError::Generic(format!(
+            "commit {} isn't in this repository; fetch it, or replay in a copy of the traced one",
+            head
+        )));
+    }
+    match &bundle.branch_before {
+        Some(branch) => repo.git(&["checkout", "-q", "-B", branch, head])?,
+        None => repo.git(&["checkout", "-q", "--detach", head])?,
+    };
+    repo.git(&["reset", "-q", "--hard", head])?;
+    let notes_ref = format!("refs/notes/{}", AI_AUTHORSHIP_REFNAME);
+    match &bundle.notes_before {
+        Some(oid) => {
+            repo.git(&["update-ref", &notes_ref, oid])?;
+        }
+        None => {
+            // Fails when there's no ref to delete, which is what's wanted
+            let _ = repo.git(&["update-ref", "-d", &notes_ref]);
+        }
+    }
+
+    let ai_dir = repo.storage.repo_path.join("ai");
+    for entry in fs::read_dir(&ai_dir)? {
+        let entry = entry?;
+        if UNRECORDED_AI_PATHS.contains(&entry.file_name().to_string_lossy().as_ref()) {
+            continue;
+        }
+        if entry.file_type()?.is_dir() {
+            fs::remove_dir_all(entry.path())?;
+        } else {
+            fs::remove_file(entry.path())?;
+        }
+    }
+    for (path, content) in &bundle.ai_state {
+        let path = ai_dir.join(path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, content)?;
+    }
+
+    for (patch, index) in [(&bundle.index_patch, true), (&bundle.worktree_patch, false)] {
+        if patch.is_empty() {
+            continue;
+        }
+        let mut args = repo.global_args_for_exec();
+        args.push("apply".to_string());
+        if index {
+            args.push("--index".to_string());
+        }
+        exec_git_stdin(&args, patch.as_bytes())?;
+    }
+    Ok(())
+}
+
+fn rev_parse(repo: &Repository, rev: &str) -> Option<String> {
+    repo.git(&["rev-parse", "--verify", "--quiet", rev])
+        .ok()
+        .map(|oid| oid.trim().to_string())
+        .filter(|oid| !oid.is_empty())
+}
+
+/// The notes written according to the "note written" events of a recording
+fn written_notes(events: &[Value]) -> Vec<WrittenNote> {
+    events
+        .iter()
+        .filter(|event| event["message"] == "note written")
+        .filter_map(|event| {
+            let field = |name: &str| event["fields"][name].as_str().map(str::to_string);
+            Some(WrittenNote {
+                notes_ref: field("notes_ref")?,
+                commit: field("commit")?,
+                note: field("note")?,
+            })
+        })
+        .collect()
+}
+
+/// The authorship notes of the two runs that differ, matched up in the order they were
+/// written. Commits made again get new hashes, so only the attestations are compared, not the
+/// metadata that names them.
+fn compare_notes(recorded: &[WrittenNote], replayed: &[WrittenNote]) -> Vec<NoteDifference> {
+    let authorship = |notes: &[WrittenNote]| -> Vec<WrittenNote> {
+        notes
+            .iter()
+            .filter(|note| note.notes_ref == AI_AUTHORSHIP_REFNAME)
+            .cloned()
+            .collect()
+    };
+    let recorded = authorship(recorded);
+    let replayed = authorship(replayed);
+
+    (0..recorded.len().max(replayed.len()))
+        .filter_map(|i| {
+            let (before, after) = (recorded.get(i), replayed.get(i));
+            let same = match (before, after) {
+                (Some(before), Some(after)) => {
+                    attestations(&before.note).eq(attestations(&after.note))
+                }
+                _ => false,
+            };
+            (!same).then(|| NoteDifference {
+                index: i + 1,
+                recorded: before.cloned(),
+                replayed: after.cloned(),
+            })
+        })
+        .collect()
+}
+
+/// The attestation lines of a serialized authorship note, before its metadata
+fn attestations(note: &str) -> impl Iterator<Item = &str> {
+    note.lines().take_while(|line| *line != "---")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn note(commit: &str, attestations: &str) -> WrittenNote {
+        WrittenNote {
+            notes_ref: AI_AUTHORSHIP_REFNAME.to_string(),
+            commit: commit.to_string(),
+            note: format!(
+                "{}\n---\n{{\"base_commit_sha\": \"{}\"}}\n",
+                attestations, commit
+            ),
+        }
+    }
+
+    #[test]
+    fn test_written_notes_from_events() {
+        let events = vec![
+            json!({"message": "files considered", "fields": {"files": "[\"a.rs\"]"}}),
+            json!({"message": "note written", "fields": {
+                "notes_ref": "ai", "commit": "abc", "note": "a.rs\n  a1b2 1-2\n---\n{}"
+            }}),
+        ];
+        let notes = written_notes(&events);
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].commit, "abc");
+    }
+
+    #[test]
+    fn test_compare_notes_ignores_commit_metadata() {
+        let recorded = vec![
+            note("abc", "a.rs\n  a1b2 1-2"),
+            note("def", "b.rs\n  a1b2 3"),
+        ];
+        let same = vec![
+            note("123", "a.rs\n  a1b2 1-2"),
+            note("456", "b.rs\n  a1b2 3"),
+        ];
+        assert!(compare_notes(&recorded, &same).is_empty());
+
+        let different = vec![note("123", "a.rs\n  a1b2 1")];
+        let differences = compare_notes(&recorded, &different);
+        assert_eq!(
+            differences
+                .iter()
+                .map(|difference| (
+                    difference.index,
+                    difference.recorded.is_some(),
+                    difference.replayed.is_some()
+                ))
+                .collect::<Vec<_>>(),
+            vec![(1, true, true), (2, true, false)]
+        );
+    }
+}
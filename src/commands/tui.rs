@@ -0,0 +1,890 @@
+//! `git-ai tui`: an interactive dashboard in the terminal.
+//!
+//! Three views, switched with Tab: a file tree colored by the share of each file's lines at HEAD
+//! that AI wrote, which drills down into per-line attribution of a file, and a list of the AI
+//! sessions (prompts) in recent commits. A sparkline of the AI share of added lines in recent
+//! commits sits above all of them.
+
+use crate::authorship::query::{QueriedAuthor, query_attribution};
+use crate::authorship::stats::stats_for_commit_stats;
+use crate::error::{GitAiError, exit_with_error};
+use crate::git::find_repository;
+use crate::git::path_scope::PathScope;
+use crate::git::refs::get_authorship;
+use crate::git::repository::Repository;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::symbols;
+use ratatui::text::Line;
+use ratatui::widgets::{Cell, Paragraph, Row as TableRow, Sparkline, Table, TableState, Tabs};
+use ratatui::{DefaultTerminal, Frame};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::io::IsTerminal;
+
+const DEFAULT_COMMITS: usize = 200;
+
+/// Commits the trend sparkline covers, newest last
+const TREND_COMMITS: usize = 60;
+
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Ratatui's bars, except that a commit with no AI lines still shows a bar, telling it apart
+/// from one that added nothing
+const TREND_BARS: symbols::bar::Set = symbols::bar::Set {
+    empty: "▁",
+    ..symbols::bar::NINE_LEVELS
+};
+
+pub fn handle_tui(args: &[String]) {
+    let mut commits = DEFAULT_COMMITS;
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--commits" if i + 1 >= args.len() => {
+                eprintln!("Error: --commits requires a value");
+                std::process::exit(1);
+            }
+            "--commits" => {
+                commits = match args[i + 1].parse() {
+                    Ok(count) if count > 0 => count,
+                    _ => {
+                        eprintln!(
+                            "Error: invalid commit count '{}', expected a positive number",
+                            args[i + 1]
+                        );
+                        std::process::exit(1);
+                    }
+                };
+                i += 1;
+            }
+            other => {
+                eprintln!("Error: unexpected argument '{}'", other);
+                std::process::exit(1);
+            }
+        }
+        i += 1;
+    }
+
+    if !std::io::stdout().is_terminal() {
+        exit_with_error(
+            "Failed to start the dashboard",
+            &GitAiError::Generic("git-ai tui needs an interactive terminal".to_string()),
+        );
+    }
+
+    let repo = match find_repository(&Vec::<String>::new()) {
+        Ok(repo) => repo,
+        Err(e) => {
+            exit_with_error("Failed to find repository", &e);
+        }
+    };
+
+    eprintln!("Loading attribution for the last {} commits...", commits);
+    let dashboard = match Dashboard::load(&repo, commits) {
+        Ok(dashboard) => dashboard,
+        Err(e) => {
+            exit_with_error("Failed to load the dashboard", &e);
+        }
+    };
+
+    let mut app = App::new(dashboard);
+    if let Err(e) = run(&repo, &mut app) {
+        exit_with_error("Dashboard failed", &e.into());
+    }
+}
+
+/// A file, or a directory with the totals of everything under it
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct FileNode {
+    name: String,
+    /// Repository-relative path; empty for the root
+    path: String,
+    ai_lines: u32,
+    total_lines: u32,
+    /// Sorted by name, directories first; empty for files
+    children: Vec<FileNode>,
+    is_dir: bool,
+}
+
+impl FileNode {
+    fn ai_percent(&self) -> u32 {
+        (self.ai_lines * 100)
+            .checked_div(self.total_lines)
+            .unwrap_or(0)
+    }
+
+    /// Builds the tree from `(path, total lines, AI lines)` of every file
+    fn build(files: &[(String, u32, u32)]) -> FileNode {
+        let mut root = FileNode {
+            is_dir: true,
+            ..Default::default()
+        };
+        for (path, total_lines, ai_lines) in files {
+            let mut node = &mut root;
+            let mut prefix = String::new();
+            let parts: Vec<&str> = path.split('/').collect();
+            for (i, part) in parts.iter().enumerate() {
+                node.ai_lines += ai_lines;
+                node.total_lines += total_lines;
+                if !prefix.is_empty() {
+                    prefix.push('/');
+                }
+                prefix.push_str(part);
+                let is_dir = i + 1 < parts.len();
+                let index = match node.children.iter().position(|c| c.name == *part) {
+                    Some(index) => index,
+                    None => {
+                        node.children.push(FileNode {
+                            name: part.to_string(),
+                            path: prefix.clone(),
+                            is_dir,
+                            ..Default::default()
+                        });
+                        node.children.len() - 1
+                    }
+                };
+                node = &mut node.children[index];
+            }
+            node.ai_lines += ai_lines;
+            node.total_lines += total_lines;
+        }
+        root.sort();
+        root
+    }
+
+    fn sort(&mut self) {
+        self.children
+            .sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.cmp(&b.name)));
+        for child in &mut self.children {
+            child.sort();
+        }
+    }
+
+    /// The rows on screen: children of expanded directories, depth first
+    fn visible<'a>(&'a self, expanded: &HashSet<String>, depth: usize, rows: &mut Vec<Row<'a>>) {
+        for child in &self.children {
+            rows.push(Row { depth, node: child });
+            if child.is_dir && expanded.contains(&child.path) {
+                child.visible(expanded, depth + 1, rows);
+            }
+        }
+    }
+}
+
+struct Row<'a> {
+    depth: usize,
+    node: &'a FileNode,
+}
+
+/// One prompt's contribution to one commit
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub(crate) struct Session {
+    pub(crate) commit: String,
+    pub(crate) time: i64,
+    pub(crate) subject: String,
+    pub(crate) prompt_hash: String,
+    pub(crate) tool: String,
+    pub(crate) model: String,
+    /// Who prompted the agent
+    pub(crate) author: Option<String>,
+    pub(crate) accepted_lines: u32,
+    pub(crate) total_additions: u32,
+    pub(crate) messages: usize,
+}
+
+/// A commit of the history the dashboard reads
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RecentCommit {
+    pub(crate) sha: String,
+    pub(crate) time: i64,
+    pub(crate) subject: String,
+}
+
+/// Everything the dashboard shows, read once at startup
+#[derive(Debug, Clone)]
+struct Dashboard {
+    head: String,
+    tree: FileNode,
+    sessions: Vec<Session>,
+    /// AI share of added lines per commit, oldest first (None when a commit adds nothing)
+    trend: Vec<Option<u32>>,
+}
+
+impl Dashboard {
+    /// Reads the file tree at HEAD and the notes of the last `commits` commits. Only files that
+    /// one of those notes attests to are blamed; every other file counts as human.
+    fn load(repo: &Repository, commits: usize) -> Result<Dashboard, GitAiError> {
+        let head = repo.revparse_single("HEAD")?.id();
+        let history = recent_commits(repo, &head, commits)?;
+        let (sessions, ai_files) = sessions_in(repo, &history);
+        let files = file_lines(repo, &head, &ai_files)?;
+        let trend = ai_share_trend(repo, &history[..history.len().min(TREND_COMMITS)]);
+
+        Ok(Dashboard {
+            head,
+            tree: FileNode::build(&files),
+            sessions,
+            trend,
+        })
+    }
+}
+
+/// The last `commits` commits up to `head`, newest first
+pub(crate) fn recent_commits(
+    repo: &Repository,
+    head: &str,
+    commits: usize,
+) -> Result<Vec<RecentCommit>, GitAiError> {
+    let log = repo.git(&[
+        "log",
+        "-n",
+        &commits.to_string(),
+        "--format=%H%x00%ct%x00%s",
+        head,
+    ])?;
+    Ok(log
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, '\0');
+            Some(RecentCommit {
+                sha: fields.next()?.to_string(),
+                time: fields.next()?.parse().ok()?,
+                subject: fields.next().unwrap_or_default().to_string(),
+            })
+        })
+        .collect())
+}
+
+/// The AI sessions in the notes of `history`, and every file those notes attest to
+pub(crate) fn sessions_in(
+    repo: &Repository,
+    history: &[RecentCommit],
+) -> (Vec<Session>, HashSet<String>) {
+    let mut sessions = Vec::new();
+    let mut ai_files = HashSet::new();
+    for commit in history {
+        let Some(authorship_log) = get_authorship(repo, &commit.sha) else {
+            continue;
+        };
+        ai_files.extend(
+            authorship_log
+                .attestations
+                .iter()
+                .map(|a| a.file_path.clone()),
+        );
+        for (prompt_hash, prompt) in &authorship_log.metadata.prompts {
+            sessions.push(Session {
+                commit: commit.sha.clone(),
+                time: commit.time,
+                subject: commit.subject.clone(),
+                prompt_hash: prompt_hash.clone(),
+                tool: prompt.agent_id.tool.clone(),
+                model: prompt.agent_id.model.clone(),
+                author: prompt.prompter().map(str::to_string),
+                accepted_lines: prompt.accepted_lines,
+                total_additions: prompt.total_additions,
+                messages: prompt.messages.len(),
+            });
+        }
+    }
+    (sessions, ai_files)
+}
+
+/// `(path, total lines, AI lines)` of each text file at `head` in the path scope. Only the
+/// files in `ai_files` are blamed; the rest count as human.
+pub(crate) fn file_lines(
+    repo: &Repository,
+    head: &str,
+    ai_files: &HashSet<String>,
+) -> Result<Vec<(String, u32, u32)>, GitAiError> {
+    let scope = PathScope::for_repo(repo);
+    let mut files = Vec::new();
+    for (path, total_lines) in line_counts(repo, head)? {
+        if !scope.contains(&path) {
+            continue;
+        }
+        let ai_lines = if ai_files.contains(&path) {
+            query_attribution(repo, &path, Some(head), None)
+                .map(|lines| {
+                    lines
+                        .iter()
+                        .filter(|l| matches!(l.author, QueriedAuthor::Ai { .. }))
+                        .count() as u32
+                })
+                .unwrap_or(0)
+        } else {
+            0
+        };
+        files.push((path, total_lines, ai_lines));
+    }
+    Ok(files)
+}
+
+/// AI share of the lines each commit of `history` (newest first) added, oldest first. None
+/// when a commit adds nothing.
+pub(crate) fn ai_share_trend(repo: &Repository, history: &[RecentCommit]) -> Vec<Option<u32>> {
+    history
+        .iter()
+        .rev()
+        .map(|commit| {
+            stats_for_commit_stats(repo, &commit.sha, "HEAD", false)
+                .ok()
+                .filter(|stats| stats.git_diff_added_lines > 0)
+                .map(|stats| stats.ai_additions * 100 / stats.git_diff_added_lines)
+        })
+        .collect()
+}
+
+/// Lines in each text file at `commit`
+pub(crate) fn line_counts(
+    repo: &Repository,
+    commit: &str,
+) -> Result<Vec<(String, u32)>, GitAiError> {
+    let mut args = repo.global_args_for_exec();
+    args.extend(
+        ["grep", "-I", "-c", "-z", "", commit, "--"]
+            .iter()
+            .map(|s| s.to_string()),
+    );
+    let stdout = match crate::git::repository::exec_git(&args) {
+        Ok(output) => String::from_utf8(output.stdout)?,
+        // Exit code 1: no text files with any lines
+        Err(GitAiError::GitCliError { code: Some(1), .. }) => String::new(),
+        Err(e) => return Err(e),
+    };
+    // Each line is "<commit>:<path>\0<count>"
+    let prefix = format!("{}:", commit);
+    Ok(stdout
+        .lines()
+        .filter_map(|line| {
+            let (path, count) = line.split_once('\0')?;
+            Some((
+                path.strip_prefix(&prefix).unwrap_or(path).to_string(),
+                count.parse().ok()?,
+            ))
+        })
+        .collect())
+}
+
+pub(crate) fn sparkline(values: &[Option<u32>]) -> String {
+    values
+        .iter()
+        .map(|value| match value {
+            Some(percent) => SPARKS[(*percent as usize * (SPARKS.len() - 1)) / 100],
+            None => ' ',
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum View {
+    Files,
+    Blame {
+        path: String,
+        /// Author label and text of each line
+        lines: Vec<(String, bool, String)>,
+        scroll: usize,
+    },
+    Sessions,
+}
+
+struct App {
+    dashboard: Dashboard,
+    view: View,
+    expanded: HashSet<String>,
+    /// Selected row of the file tree
+    selected: usize,
+    /// Selected session
+    session: usize,
+    /// Rows available for the list under the header
+    height: usize,
+}
+
+/// What the app wants after a key press
+#[derive(Debug, PartialEq, Eq)]
+enum Action {
+    Continue,
+    /// Load the per-line attribution of this file
+    OpenBlame(String),
+    Quit,
+}
+
+impl App {
+    fn new(dashboard: Dashboard) -> App {
+        App {
+            dashboard,
+            view: View::Files,
+            expanded: HashSet::new(),
+            selected: 0,
+            session: 0,
+            height: 20,
+        }
+    }
+
+    fn rows(&self) -> Vec<Row<'_>> {
+        let mut rows = Vec::new();
+        self.dashboard.tree.visible(&self.expanded, 0, &mut rows);
+        rows
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> Action {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return Action::Quit;
+        }
+        let page = self.height.max(1);
+        match &mut self.view {
+            View::Files => {
+                let rows = self.rows();
+                let last = rows.len().saturating_sub(1);
+                let current = rows
+                    .get(self.selected)
+                    .map(|row| (row.node.path.clone(), row.node.is_dir, row.depth));
+                match key.code {
+                    KeyCode::Char('q') | KeyCode::Esc => return Action::Quit,
+                    KeyCode::Tab | KeyCode::BackTab => self.view = View::Sessions,
+                    KeyCode::Up | KeyCode::Char('k') => {
+                        self.selected = self.selected.saturating_sub(1)
+                    }
+                    KeyCode::Down | KeyCode::Char('j') => {
+                        self.selected = (self.selected + 1).min(last)
+                    }
+                    KeyCode::PageUp => self.selected = self.selected.saturating_sub(page),
+                    KeyCode::PageDown => self.selected = (self.selected + page).min(last),
+                    KeyCode::Home => self.selected = 0,
+                    KeyCode::End => self.selected = last,
+                    KeyCode::Right | KeyCode::Enter | KeyCode::Char('l') => match current {
+                        Some((path, true, _)) => {
+                            if key.code == KeyCode::Enter && self.expanded.contains(&path) {
+                                // Enter toggles, → only expands
+                                self.expanded.remove(&path);
+                            } else {
+                                self.expanded.insert(path);
+                            }
+                        }
+                        Some((path, false, _)) => return Action::OpenBlame(path),
+                        None => {}
+                    },
+                    KeyCode::Left | KeyCode::Backspace | KeyCode::Char('h') => match current {
+                        Some((path, true, _)) if self.expanded.contains(&path) => {
+                            self.expanded.remove(&path);
+                        }
+                        Some((path, _, depth)) if depth > 0 => {
+                            // Jump to the parent directory
+                            let parent = path.rsplit_once('/').map(|(p, _)| p.to_string());
+                            if let Some(index) = rows
+                                .iter()
+                                .position(|row| Some(&row.node.path) == parent.as_ref())
+                            {
+                                self.selected = index;
+                            }
+                        }
+                        _ => {}
+                    },
+                    _ => {}
+                }
+            }
+            View::Blame { lines, scroll, .. } => {
+                let last = lines.len().saturating_sub(page);
+                match key.code {
+                    KeyCode::Char('q')
+                    | KeyCode::Esc
+                    | KeyCode::Left
+                    | KeyCode::Backspace
+                    | KeyCode::Char('h') => self.view = View::Files,
+                    KeyCode::Up | KeyCode::Char('k') => *scroll = scroll.saturating_sub(1),
+                    KeyCode::Down | KeyCode::Char('j') => *scroll = (*scroll + 1).min(last),
+                    KeyCode::PageUp => *scroll = scroll.saturating_sub(page),
+                    KeyCode::PageDown | KeyCode::Char(' ') => *scroll = (*scroll + page).min(last),
+                    KeyCode::Home => *scroll = 0,
+                    KeyCode::End => *scroll = last,
+                    _ => {}
+                }
+            }
+            View::Sessions => {
+                let last = self.dashboard.sessions.len().saturating_sub(1);
+                match key.code {
+                    KeyCode::Char('q') | KeyCode::Esc => return Action::Quit,
+                    KeyCode::Tab | KeyCode::BackTab => self.view = View::Files,
+                    KeyCode::Up | KeyCode::Char('k') => {
+                        self.session = self.session.saturating_sub(1)
+                    }
+                    KeyCode::Down | KeyCode::Char('j') => {
+                        self.session = (self.session + 1).min(last)
+                    }
+                    KeyCode::PageUp => self.session = self.session.saturating_sub(page),
+                    KeyCode::PageDown => self.session = (self.session + page).min(last),
+                    KeyCode::Home => self.session = 0,
+                    KeyCode::End => self.session = last,
+                    _ => {}
+                }
+            }
+        }
+        Action::Continue
+    }
+
+    /// Draws the whole screen, with `status` in place of the key help when there is one
+    fn render(&self, frame: &mut Frame, status: Option<&str>) {
+        let [header, trend, title, body, footer] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        let [name, tabs, head] = Layout::horizontal([
+            Constraint::Length(8),
+            Constraint::Length(20),
+            Constraint::Min(0),
+        ])
+        .areas(header);
+        frame.render_widget(Paragraph::new("git-ai".bold()), name);
+        frame.render_widget(
+            Tabs::new(["Files", "Sessions"])
+                .select(usize::from(self.view == View::Sessions))
+                .highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+                .divider(" ")
+                .padding(" ", " "),
+            tabs,
+        );
+        frame.render_widget(
+            Paragraph::new(
+                format!(
+                    "@ {}",
+                    &self.dashboard.head[..self.dashboard.head.len().min(8)]
+                )
+                .dark_gray(),
+            ),
+            head,
+        );
+
+        let known: Vec<u32> = self.dashboard.trend.iter().flatten().copied().collect();
+        let average = if known.is_empty() {
+            0
+        } else {
+            known.iter().sum::<u32>() / known.len() as u32
+        };
+        let label = format!(
+            "AI share of added lines, last {} commits  ",
+            self.dashboard.trend.len()
+        );
+        let [label_area, sparkline_area, average_area] = Layout::horizontal([
+            Constraint::Length(label.chars().count() as u16),
+            Constraint::Length(self.dashboard.trend.len() as u16),
+            Constraint::Min(0),
+        ])
+        .areas(trend);
+        frame.render_widget(Paragraph::new(label), label_area);
+        frame.render_widget(
+            Sparkline::default()
+                .data(
+                    self.dashboard
+                        .trend
+                        .iter()
+                        .map(|value| value.map(u64::from)),
+                )
+                .max(100)
+                .bar_set(TREND_BARS)
+                .absent_value_symbol(" ")
+                .magenta(),
+            sparkline_area,
+        );
+        frame.render_widget(Paragraph::new(format!("  avg {}%", average)), average_area);
+
+        let selected = Style::new().add_modifier(Modifier::REVERSED);
+        match &self.view {
+            View::Files => {
+                let rows = self.rows();
+                if rows.is_empty() {
+                    frame.render_widget(Paragraph::new("No files at HEAD".dark_gray()), body);
+                }
+                let table = Table::new(
+                    rows.iter().map(|row| {
+                        let marker = match (row.node.is_dir, self.expanded.contains(&row.node.path))
+                        {
+                            (true, true) => "▾ ",
+                            (true, false) => "▸ ",
+                            (false, _) => "  ",
+                        };
+                        TableRow::new([
+                            Cell::from(format!(
+                                "{}{}{}",
+                                "  ".repeat(row.depth),
+                                marker,
+                                row.node.name
+                            )),
+                            Cell::from(
+                                Line::from(format!("{}%", row.node.ai_percent()))
+                                    .right_aligned()
+                                    .fg(percent_color(row.node.ai_percent())),
+                            ),
+                            Cell::from(
+                                Line::from(format!("{} lines", row.node.total_lines))
+                                    .right_aligned(),
+                            ),
+                        ])
+                    }),
+                    [
+                        Constraint::Min(10),
+                        Constraint::Length(5),
+                        Constraint::Length(14),
+                    ],
+                )
+                .row_highlight_style(selected);
+                let mut state = TableState::new().with_selected(Some(self.selected));
+                frame.render_stateful_widget(table, body, &mut state);
+            }
+            View::Blame {
+                path,
+                lines,
+                scroll,
+            } => {
+                frame.render_widget(Paragraph::new(path.as_str().bold()), title);
+                let table =
+                    Table::new(
+                        lines.iter().enumerate().skip(*scroll).map(
+                            |(index, (author, is_ai, text))| {
+                                TableRow::new([
+                                    Cell::from(Line::from((index + 1).to_string()).right_aligned()),
+                                    Cell::from(author.as_str()).fg(if *is_ai {
+                                        Color::Magenta
+                                    } else {
+                                        Color::DarkGray
+                                    }),
+                                    Cell::from(format!("│ {}", text)),
+                                ])
+                            },
+                        ),
+                        [
+                            Constraint::Length(5),
+                            Constraint::Length(20),
+                            Constraint::Min(10),
+                        ],
+                    );
+                frame.render_widget(table, body);
+            }
+            View::Sessions => {
+                if self.dashboard.sessions.is_empty() {
+                    frame.render_widget(
+                        Paragraph::new("No AI sessions in these commits".dark_gray()),
+                        body,
+                    );
+                }
+                let table = Table::new(
+                    self.dashboard.sessions.iter().map(|session| {
+                        let date = chrono::DateTime::from_timestamp(session.time, 0)
+                            .map(|d| d.format("%Y-%m-%d").to_string())
+                            .unwrap_or_default();
+                        TableRow::new([
+                            Cell::from(date),
+                            Cell::from(session.commit[..session.commit.len().min(8)].to_string()),
+                            Cell::from(format!("{} {}", session.tool, session.model)),
+                            Cell::from(
+                                Line::from(format!(
+                                    "{}/{} lines",
+                                    session.accepted_lines, session.total_additions
+                                ))
+                                .right_aligned(),
+                            ),
+                            Cell::from(
+                                Line::from(format!("{} msgs", session.messages)).right_aligned(),
+                            ),
+                            Cell::from(session.author.clone().unwrap_or("unknown".to_string())),
+                            Cell::from(session.subject.as_str()),
+                        ])
+                    }),
+                    [
+                        Constraint::Length(10),
+                        Constraint::Length(8),
+                        Constraint::Length(24),
+                        Constraint::Length(15),
+                        Constraint::Length(8),
+                        Constraint::Length(20),
+                        Constraint::Min(0),
+                    ],
+                )
+                .row_highlight_style(selected);
+                let mut state = TableState::new().with_selected(Some(self.session));
+                frame.render_stateful_widget(table, body, &mut state);
+            }
+        }
+
+        let footer_line = match (status, &self.view) {
+            (Some(message), _) => message.red(),
+            (None, View::Blame { .. }) => "↑↓ scroll · ← back · q back".dark_gray(),
+            (None, _) => "↑↓ move · →/Enter open · ← back · Tab switch view · q quit".dark_gray(),
+        };
+        frame.render_widget(Paragraph::new(footer_line), footer);
+    }
+}
+
+/// Green for mostly human, through yellow, to magenta for mostly AI
+fn percent_color(percent: u32) -> Color {
+    match percent {
+        0 => Color::DarkGray,
+        1..=24 => Color::Green,
+        25..=49 => Color::Yellow,
+        50..=74 => Color::Red,
+        _ => Color::Magenta,
+    }
+}
+
+fn blame_lines(
+    repo: &Repository,
+    head: &str,
+    path: &str,
+) -> Result<Vec<(String, bool, String)>, GitAiError> {
+    let contents = repo.git(&["show", &format!("{}:{}", head, path)])?;
+    let authors: HashMap<u32, QueriedAuthor> = query_attribution(repo, path, Some(head), None)?
+        .into_iter()
+        .map(|line| (line.line, line.author))
+        .collect();
+    Ok(contents
+        .lines()
+        .enumerate()
+        .map(|(index, text)| {
+            let (label, is_ai) = match authors.get(&(index as u32 + 1)) {
+                Some(QueriedAuthor::Ai { tool, model, .. }) => {
+                    (format!("{} {}", tool, model), true)
+                }
+                Some(QueriedAuthor::Human { name }) => (name.clone(), false),
+                None => (String::new(), false),
+            };
+            (label, is_ai, text.replace('\t', "    "))
+        })
+        .collect())
+}
+
+fn run(repo: &Repository, app: &mut App) -> std::io::Result<()> {
+    // Raw mode on the alternate screen, so the shell's scrollback is left as it was
+    let mut terminal = ratatui::try_init()?;
+    let result = event_loop(&mut terminal, repo, app);
+    ratatui::try_restore()?;
+    result
+}
+
+fn event_loop(
+    terminal: &mut DefaultTerminal,
+    repo: &Repository,
+    app: &mut App,
+) -> std::io::Result<()> {
+    let mut status: Option<String> = None;
+    loop {
+        app.height = (terminal.size()?.height as usize).saturating_sub(4);
+        terminal.draw(|frame| app.render(frame, status.as_deref()))?;
+
+        // Resizes only need the redraw above
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        status = None;
+        match app.handle_key(key) {
+            Action::Quit => return Ok(()),
+            Action::OpenBlame(path) => match blame_lines(repo, &app.dashboard.head, &path) {
+                Ok(lines) => {
+                    app.view = View::Blame {
+                        path,
+                        lines,
+                        scroll: 0,
+                    }
+                }
+                Err(e) => status = Some(format!("Failed to blame {}: {}", path, e)),
+            },
+            Action::Continue => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::test_utils::TmpRepo;
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::from(code)
+    }
+
+    #[test]
+    fn test_tree_totals_and_navigation() {
+        let tree = FileNode::build(&[
+            ("src/main.rs".to_string(), 10, 5),
+            ("src/lib/mod.rs".to_string(), 30, 0),
+            ("README.md".to_string(), 10, 0),
+        ]);
+        assert_eq!((tree.ai_lines, tree.total_lines), (5, 50));
+        assert_eq!(tree.children[0].name, "src");
+        assert_eq!(tree.children[0].ai_percent(), 12);
+
+        let mut app = App::new(Dashboard {
+            head: "0123456789".to_string(),
+            tree,
+            sessions: Vec::new(),
+            trend: vec![Some(0), None, Some(100)],
+        });
+        assert_eq!(app.rows().len(), 2);
+        app.handle_key(key(KeyCode::Enter));
+        let names: Vec<String> = app.rows().iter().map(|r| r.node.path.clone()).collect();
+        assert_eq!(names, vec!["src", "src/lib", "src/main.rs", "README.md"]);
+
+        app.handle_key(key(KeyCode::Down));
+        app.handle_key(key(KeyCode::Down));
+        assert_eq!(
+            app.handle_key(key(KeyCode::Enter)),
+            Action::OpenBlame("src/main.rs".to_string())
+        );
+        app.handle_key(key(KeyCode::Left));
+        assert_eq!(app.selected, 0);
+        app.handle_key(key(KeyCode::Left));
+        assert_eq!(app.rows().len(), 2);
+
+        app.handle_key(key(KeyCode::Tab));
+        assert_eq!(app.view, View::Sessions);
+        assert_eq!(app.handle_key(key(KeyCode::Char('q'))), Action::Quit);
+
+        assert_eq!(sparkline(&app.dashboard.trend), "▁ █");
+        let mut terminal = Terminal::new(TestBackend::new(80, 10)).unwrap();
+        terminal.draw(|frame| app.render(frame, None)).unwrap();
+        let buffer = terminal.backend().buffer();
+        let trend: String = (0..80).map(|x| buffer[(x, 1)].symbol()).collect();
+        assert!(trend.contains("▁ █  avg 50%"), "{}", trend);
+    }
+
+    #[test]
+    fn test_dashboard_loads_ai_lines_and_sessions() {
+        let repo = TmpRepo::new().unwrap();
+        repo.write_file("human.txt", "one\ntwo\n", true).unwrap();
+        repo.trigger_checkpoint_with_author("Alice").unwrap();
+        repo.commit_with_message("Human").unwrap();
+        repo.write_file("ai.txt", "from claude\nmore\n", true)
+            .unwrap();
+        repo.trigger_checkpoint_with_ai("Claude", Some("claude-3-sonnet"), Some("cursor"))
+            .unwrap();
+        repo.commit_with_message("AI").unwrap();
+
+        let dashboard = Dashboard::load(repo.gitai_repo(), 10).unwrap();
+        let ai = dashboard
+            .tree
+            .children
+            .iter()
+            .find(|c| c.name == "ai.txt")
+            .unwrap();
+        assert_eq!((ai.ai_lines, ai.total_lines), (2, 2));
+        assert_eq!(
+            (dashboard.tree.ai_lines, dashboard.tree.total_lines),
+            (2, 4)
+        );
+        assert_eq!(dashboard.sessions.len(), 1);
+        assert_eq!(dashboard.sessions[0].subject, "AI");
+        assert_eq!(dashboard.sessions[0].tool, "cursor");
+        assert_eq!(dashboard.sessions[0].accepted_lines, 2);
+        assert_eq!(dashboard.trend.last(), Some(&Some(100)));
+    }
+}
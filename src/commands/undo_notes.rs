@@ -0,0 +1,103 @@
+use crate::error::{GitAiError, exit_with_error};
+use crate::git::find_repository;
+use crate::git::refs::{
+    AI_AUTHORSHIP_REFNAME, AI_NOTES_BACKUP_PREFIX, list_ai_notes_backups, ref_exists,
+    restore_ai_notes,
+};
+use crate::git::repository::Repository;
+
+pub fn handle_undo_notes(args: &[String]) {
+    let mut list = false;
+    let mut target = None;
+
+    for arg in args {
+        match arg.as_str() {
+            "--list" => list = true,
+            arg if target.is_none() && !arg.starts_with('-') => target = Some(arg.to_string()),
+            arg => {
+                eprintln!("Error: unexpected argument '{}'", arg);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let repo = match find_repository(&Vec::<String>::new()) {
+        Ok(repo) => repo,
+        Err(e) => {
+            exit_with_error("Failed to find repository", &e);
+        }
+    };
+
+    if list {
+        print_backups(&repo);
+        return;
+    }
+
+    let (source, commit) = match resolve_target(&repo, target.as_deref()) {
+        Ok(resolved) => resolved,
+        Err(e) => {
+            exit_with_error("Failed to find notes to restore", &e);
+        }
+    };
+    match restore_ai_notes(&repo, &commit) {
+        Ok(backup) => {
+            println!(
+                "Restored refs/notes/{} to {} from {}",
+                AI_AUTHORSHIP_REFNAME,
+                &commit[..commit.len().min(8)],
+                source
+            );
+            if let Some(backup) = backup {
+                println!("The notes it replaced are saved to {}", backup);
+            }
+        }
+        Err(e) => {
+            exit_with_error("Failed to restore notes", &e);
+        }
+    }
+}
+
+/// The ref (or revision) to restore and the notes commit it points at. Without a target, the
+/// most recent backup that differs from the current notes.
+fn resolve_target(repo: &Repository, target: Option<&str>) -> Result<(String, String), GitAiError> {
+    let Some(target) = target else {
+        let current = repo
+            .git(&[
+                "rev-parse",
+                "--verify",
+                "--quiet",
+                &format!("refs/notes/{}", AI_AUTHORSHIP_REFNAME),
+            ])
+            .unwrap_or_default();
+        return list_ai_notes_backups(repo)?
+            .into_iter()
+            .find(|backup| backup.target != current.trim())
+            .map(|backup| (backup.refname, backup.target))
+            .ok_or_else(|| GitAiError::Generic("No notes backups to restore".to_string()));
+    };
+
+    // A backup's notes commit (or a prefix of it), or any revision such as refs/notes/ai@{1}
+    let backup_ref = format!("{}{}", AI_NOTES_BACKUP_PREFIX, target);
+    let source = if ref_exists(repo, &backup_ref) {
+        backup_ref
+    } else {
+        target.to_string()
+    };
+    let commit = repo.git(&["rev-parse", "--verify", &format!("{}^{{commit}}", source)])?;
+    Ok((source, commit.trim().to_string()))
+}
+
+fn print_backups(repo: &Repository) {
+    let backups = match list_ai_notes_backups(repo) {
+        Ok(backups) => backups,
+        Err(e) => {
+            exit_with_error("Failed to list notes backups", &e);
+        }
+    };
+    if backups.is_empty() {
+        println!("No notes backups");
+    }
+    for backup in backups {
+        println!("{}  {}", backup.date, backup.refname);
+    }
+}
@@ -1,4 +1,5 @@
 use crate::config::{self, UpdateChannel};
+use crate::utils::{is_newer_version, semver_from_tag};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
@@ -13,7 +14,6 @@ use std::os::windows::process::CommandExt;
 #[cfg(windows)]
 const CREATE_NO_WINDOW: u32 = 0x08000000;
 
-const UPDATE_CHECK_INTERVAL_HOURS: u64 = 24;
 const INSTALL_SCRIPT_URL: &str =
     "https://raw.githubusercontent.com/acunniffe/git-ai/main/install.sh";
 #[cfg(windows)]
@@ -108,7 +108,11 @@ fn current_timestamp() -> u64 {
         .as_secs()
 }
 
-fn should_check_for_updates(channel: UpdateChannel, cache: Option<&UpdateCache>) -> bool {
+fn should_check_for_updates(
+    channel: UpdateChannel,
+    cache: Option<&UpdateCache>,
+    interval: Duration,
+) -> bool {
     let now = current_timestamp();
     match cache {
         Some(cache) if cache.last_checked_at > 0 => {
@@ -117,21 +121,12 @@ fn should_check_for_updates(channel: UpdateChannel, cache: Option<&UpdateCache>)
                 return true;
             }
             let elapsed = now.saturating_sub(cache.last_checked_at);
-            elapsed > UPDATE_CHECK_INTERVAL_HOURS * 3600
+            elapsed > interval.as_secs()
         }
         _ => true,
     }
 }
 
-fn semver_from_tag(tag: &str) -> String {
-    let trimmed = tag.trim().trim_start_matches('v');
-    trimmed
-        .split(|c| c == '-' || c == '+')
-        .next()
-        .unwrap_or("")
-        .to_string()
-}
-
 fn determine_action(force: bool, release: &ChannelRelease, current_version: &str) -> UpgradeAction {
     if force {
         return UpgradeAction::ForceReinstall;
@@ -347,6 +342,15 @@ pub fn run_with_args(args: &[String]) {
 
 fn run_impl(force: bool, background: bool) {
     let config = config::Config::get();
+    if config.is_offline() {
+        if !background {
+            eprintln!(
+                "git-ai is in offline mode (offline in config.json or GIT_AI_OFFLINE); not checking for updates"
+            );
+            std::process::exit(1);
+        }
+        return;
+    }
     let channel = config.update_channel();
     let skip_install = background && config.auto_updates_disabled();
     let _ = run_impl_with_url(force, None, channel, skip_install);
@@ -483,7 +487,7 @@ pub fn maybe_schedule_background_update_check() {
         }
     }
 
-    if !should_check_for_updates(channel, cache.as_ref()) {
+    if !should_check_for_updates(channel, cache.as_ref(), config.update_check_interval()) {
         return;
     }
 
@@ -493,54 +497,58 @@ pub fn maybe_schedule_background_update_check() {
         return;
     }
 
+    // Count the attempt as a check up front: if the release server can't be reached (an
+    // air-gapped machine, say), later git commands shouldn't each spawn another attempt
+    record_check_attempt(channel, cache, now);
     if spawn_background_upgrade_process() {
         LAST_BACKGROUND_SPAWN.store(now, Ordering::SeqCst);
     }
 }
 
+/// Marks the cache as checked at `now`, keeping what it knew about available updates
+fn record_check_attempt(channel: UpdateChannel, cache: Option<UpdateCache>, now: u64) {
+    let mut cache = cache
+        .filter(|cache| cache.matches_channel(channel))
+        .unwrap_or_else(|| UpdateCache::new(channel));
+    cache.last_checked_at = now;
+    write_update_cache(&cache);
+}
+
 fn spawn_background_upgrade_process() -> bool {
     match crate::utils::current_git_ai_exe() {
         Ok(exe) => {
             let mut cmd = Command::new(exe);
+            // Fully detached from the git command that triggered it, which doesn't wait for it
             cmd.arg("upgrade")
                 .arg("--background")
+                .stdin(Stdio::null())
                 .stdout(Stdio::null())
                 .stderr(Stdio::null());
+            #[cfg(windows)]
+            cmd.creation_flags(CREATE_NO_WINDOW);
             cmd.spawn().is_ok()
         }
         Err(_) => false,
     }
 }
 
-fn is_newer_version(latest: &str, current: &str) -> bool {
-    let parse_version =
-        |v: &str| -> Vec<u32> { v.split('.').filter_map(|s| s.parse::<u32>().ok()).collect() };
-
-    let latest_parts = parse_version(latest);
-    let current_parts = parse_version(current);
-
-    for i in 0..latest_parts.len().max(current_parts.len()) {
-        let latest_part = latest_parts.get(i).copied().unwrap_or(0);
-        let current_part = current_parts.get(i).copied().unwrap_or(0);
-
-        if latest_part > current_part {
-            return true;
-        } else if latest_part < current_part {
-            return false;
-        }
-    }
-
-    false
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    fn set_test_cache_dir(dir: &tempfile::TempDir) {
+    const DAY: Duration = Duration::from_secs(24 * 3600);
+
+    /// Tests that point the cache at a temp dir hold the returned guard, so they don't swap the
+    /// env var out from under each other
+    fn set_test_cache_dir(dir: &tempfile::TempDir) -> std::sync::MutexGuard<'static, ()> {
+        static CACHE_DIR_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+        let guard = CACHE_DIR_LOCK
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
         unsafe {
             std::env::set_var("GIT_AI_TEST_CACHE_DIR", dir.path());
         }
+        guard
     }
 
     fn clear_test_cache_dir() {
@@ -549,42 +557,10 @@ mod tests {
         }
     }
 
-    #[test]
-    fn test_is_newer_version() {
-        assert!(!is_newer_version("1.0.0", "1.0.0"));
-        assert!(!is_newer_version("1.0.10", "1.0.10"));
-
-        assert!(is_newer_version("1.0.1", "1.0.0"));
-        assert!(is_newer_version("1.0.11", "1.0.10"));
-        assert!(!is_newer_version("1.0.0", "1.0.1"));
-        assert!(!is_newer_version("1.0.10", "1.0.11"));
-
-        assert!(is_newer_version("1.1.0", "1.0.0"));
-        assert!(!is_newer_version("1.0.0", "1.1.0"));
-
-        assert!(is_newer_version("2.0.0", "1.0.0"));
-        assert!(is_newer_version("2.0.0", "1.9.9"));
-        assert!(!is_newer_version("1.9.9", "2.0.0"));
-
-        assert!(is_newer_version("1.0.0.1", "1.0.0"));
-        assert!(!is_newer_version("1.0.0", "1.0.0.1"));
-
-        assert!(is_newer_version("1.10.0", "1.9.0"));
-        assert!(is_newer_version("1.0.100", "1.0.99"));
-        assert!(is_newer_version("100.200.300", "100.200.299"));
-    }
-
-    #[test]
-    fn test_semver_from_tag_strips_prefix_and_suffix() {
-        assert_eq!(semver_from_tag("v1.2.3"), "1.2.3");
-        assert_eq!(semver_from_tag("1.2.3"), "1.2.3");
-        assert_eq!(semver_from_tag("v1.2.3-next-abc"), "1.2.3");
-    }
-
     #[test]
     fn test_run_impl_with_url() {
         let temp_dir = tempfile::tempdir().unwrap();
-        set_test_cache_dir(&temp_dir);
+        let _guard = set_test_cache_dir(&temp_dir);
 
         let mock_url = |body: &str| format!("mock://{}", body);
         let current = env!("CARGO_PKG_VERSION");
@@ -652,13 +628,54 @@ mod tests {
         let now = current_timestamp();
         let mut cache = UpdateCache::new(UpdateChannel::Latest);
         cache.last_checked_at = now;
-        assert!(!should_check_for_updates(UpdateChannel::Latest, Some(&cache)));
+        assert!(!should_check_for_updates(
+            UpdateChannel::Latest,
+            Some(&cache),
+            DAY
+        ));
 
-        let stale_offset = (UPDATE_CHECK_INTERVAL_HOURS * 3600) + 10;
+        let stale_offset = DAY.as_secs() + 10;
         cache.last_checked_at = now.saturating_sub(stale_offset);
-        assert!(should_check_for_updates(UpdateChannel::Latest, Some(&cache)));
+        assert!(should_check_for_updates(
+            UpdateChannel::Latest,
+            Some(&cache),
+            DAY
+        ));
+        // A longer configured interval isn't up yet
+        assert!(!should_check_for_updates(
+            UpdateChannel::Latest,
+            Some(&cache),
+            DAY * 7
+        ));
 
-        assert!(should_check_for_updates(UpdateChannel::Latest, None));
+        assert!(should_check_for_updates(UpdateChannel::Latest, None, DAY));
+    }
+
+    #[test]
+    fn test_record_check_attempt_defers_next_check() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let _guard = set_test_cache_dir(&temp_dir);
+
+        let mut stale = UpdateCache::new(UpdateChannel::Latest);
+        stale.available_semver = Some("999.0.0".to_string());
+        stale.available_tag = Some("v999.0.0".to_string());
+        record_check_attempt(UpdateChannel::Latest, Some(stale), current_timestamp());
+
+        let cache = read_update_cache().unwrap();
+        assert!(!should_check_for_updates(
+            UpdateChannel::Latest,
+            Some(&cache),
+            DAY
+        ));
+        assert_eq!(cache.available_semver.as_deref(), Some("999.0.0"));
+
+        // A cache for another channel starts over
+        record_check_attempt(UpdateChannel::Next, Some(cache), current_timestamp());
+        let cache = read_update_cache().unwrap();
+        assert!(cache.matches_channel(UpdateChannel::Next));
+        assert!(!cache.update_available());
+
+        clear_test_cache_dir();
     }
 
     #[test]
@@ -668,9 +685,17 @@ mod tests {
         cache.last_checked_at = now;
         
         // Cache matches channel - should respect interval
-        assert!(!should_check_for_updates(UpdateChannel::Latest, Some(&cache)));
+        assert!(!should_check_for_updates(
+            UpdateChannel::Latest,
+            Some(&cache),
+            DAY
+        ));
         
         // Cache doesn't match channel - should check for updates
-        assert!(should_check_for_updates(UpdateChannel::Next, Some(&cache)));
+        assert!(should_check_for_updates(
+            UpdateChannel::Next,
+            Some(&cache),
+            DAY
+        ));
     }
 }
@@ -0,0 +1,218 @@
+use std::collections::BTreeMap;
+
+use crate::authorship::fsck::{FsckReport, fsck};
+use crate::authorship::stats::{CommitStats, stats_for_commit_with_log, write_stats_to_terminal};
+use crate::error::{GitAiError, exit_with_error};
+use crate::git::find_repository_in_path;
+use crate::git::refs::get_authorship;
+use crate::git::repository::Repository;
+use crate::git::sync_authorship::sync_notes;
+use crate::git::workspace::{Workspace, WorkspaceRepo};
+
+/// `git-ai workspace <list|stats|verify|sync-notes>`: runs a command in every repository of the
+/// workspace (see `git::workspace`) and puts the results together
+pub fn handle_workspace(args: &[String]) {
+    let (command, args) = match args.split_first() {
+        Some((command, args)) => (command.as_str(), args),
+        None => ("list", args),
+    };
+    let mut json = false;
+    let mut include_generated = false;
+    for arg in args {
+        match arg.as_str() {
+            "--json" => json = true,
+            "--include-generated" if command == "stats" => include_generated = true,
+            arg => {
+                eprintln!("Error: unexpected argument '{}'", arg);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let cwd = match std::env::current_dir() {
+        Ok(cwd) => cwd,
+        Err(e) => exit_with_error("Failed to read the current directory", &e.into()),
+    };
+    let workspace = match Workspace::find(&cwd) {
+        Ok(workspace) => workspace,
+        Err(e) => exit_with_error("Failed to find workspace", &e),
+    };
+
+    let ok = match command {
+        "list" => {
+            list(&workspace, json);
+            true
+        }
+        "stats" => stats(&workspace, json, include_generated),
+        "verify" => verify(&workspace, json),
+        "sync-notes" => sync(&workspace),
+        other => {
+            eprintln!(
+                "Error: unknown workspace command '{}', expected list, stats, verify or sync-notes",
+                other
+            );
+            std::process::exit(1);
+        }
+    };
+    if !ok {
+        std::process::exit(1);
+    }
+}
+
+/// Runs `f` on each repository of the workspace, keeping going past failures
+fn for_each_repo<T>(
+    workspace: &Workspace,
+    f: impl Fn(&Repository) -> Result<T, GitAiError>,
+) -> Vec<(&WorkspaceRepo, Result<T, GitAiError>)> {
+    workspace
+        .repos
+        .iter()
+        .map(|repo| {
+            let result = find_repository_in_path(&repo.path.to_string_lossy())
+                .and_then(|repository| f(&repository));
+            (repo, result)
+        })
+        .collect()
+}
+
+fn list(workspace: &Workspace, json: bool) {
+    if json {
+        let repos: Vec<serde_json::Value> = workspace
+            .repos
+            .iter()
+            .map(|repo| serde_json::json!({"name": repo.name, "path": repo.path}))
+            .collect();
+        println!(
+            "{}",
+            serde_json::json!({"root": workspace.root, "repos": repos})
+        );
+        return;
+    }
+    println!("Workspace {}", workspace.root.display());
+    for repo in &workspace.repos {
+        println!("  {}", repo.name);
+    }
+}
+
+/// The stats of each repository's HEAD commit, and of all of them together
+fn stats(workspace: &Workspace, json: bool, include_generated: bool) -> bool {
+    let results = for_each_repo(workspace, |repo| {
+        let head = repo.head()?.target()?;
+        stats_for_commit_with_log(repo, &head, get_authorship(repo, &head), include_generated)
+    });
+    let mut total = CommitStats::default();
+    let mut repos = BTreeMap::new();
+    let mut ok = true;
+    for (repo, result) in results {
+        match result {
+            Ok(stats) => {
+                total.add(&stats);
+                repos.insert(repo.name.clone(), stats);
+            }
+            Err(e) => {
+                ok = false;
+                eprintln!("{}: stats failed: {}", repo.name, e);
+            }
+        }
+    }
+
+    if json {
+        println!("{}", serde_json::json!({"repos": repos, "total": total}));
+        return ok;
+    }
+    for (name, stats) in &repos {
+        println!(
+            "{}: {} AI, {} human, {} mixed",
+            name, stats.ai_additions, stats.human_additions, stats.mixed_additions
+        );
+    }
+    println!();
+    write_stats_to_terminal(&total, true);
+    ok
+}
+
+/// Checks the authorship notes of every repository, as `git-ai fsck` does
+fn verify(workspace: &Workspace, json: bool) -> bool {
+    let results = for_each_repo(workspace, |repo| fsck(repo, false));
+    let mut reports: BTreeMap<String, FsckReport> = BTreeMap::new();
+    let mut ok = true;
+    for (repo, result) in results {
+        match result {
+            Ok(report) => {
+                ok &= report.unresolved() == 0;
+                reports.insert(repo.name.clone(), report);
+            }
+            Err(e) => {
+                ok = false;
+                eprintln!("{}: verify failed: {}", repo.name, e);
+            }
+        }
+    }
+
+    if json {
+        match serde_json::to_string(&reports) {
+            Ok(json) => println!("{}", json),
+            Err(e) => {
+                eprintln!("Failed to serialize fsck reports: {}", e);
+                std::process::exit(1);
+            }
+        }
+        return ok;
+    }
+    for (name, report) in &reports {
+        println!(
+            "{}: checked {} authorship notes, {} issues",
+            name,
+            report.commits_checked,
+            report.unresolved()
+        );
+        for finding in report.findings.iter().filter(|finding| !finding.repaired) {
+            println!(
+                "  error {}: {}",
+                &finding.commit[..finding.commit.len().min(8)],
+                finding.issue
+            );
+        }
+    }
+    if !ok {
+        println!("Run `git-ai fsck --repair` in a repository to fix what can be fixed safely");
+    }
+    ok
+}
+
+/// Reconciles refs/notes/ai of every repository with all of its remotes, as
+/// `git-ai notes sync` does
+fn sync(workspace: &Workspace) -> bool {
+    let results = for_each_repo(workspace, |repo| {
+        let remotes: Vec<String> = repo
+            .remotes()?
+            .into_iter()
+            .filter(|remote| !remote.is_empty())
+            .collect();
+        Ok(sync_notes(repo, &remotes))
+    });
+    let mut ok = true;
+    for (repo, result) in results {
+        match result {
+            Ok(reports) if reports.is_empty() => println!("{}: no remotes", repo.name),
+            Ok(reports) => {
+                for report in reports {
+                    let outcome = match &report.error {
+                        Some(error) => {
+                            ok = false;
+                            format!("failed: {}", error)
+                        }
+                        None if report.fetched || report.pushed => "synced".to_string(),
+                        None => "nothing to do".to_string(),
+                    };
+                    println!("{}: {}: {}", repo.name, report.remote, outcome);
+                }
+            }
+            Err(e) => {
+                ok = false;
+                eprintln!("{}: sync failed: {}", repo.name, e);
+            }
+        }
+    }
+    ok
+}
@@ -1,8 +1,4 @@
-pub mod authorship;
 pub mod ci;
 pub mod commands;
-pub mod config;
-pub mod error;
-pub mod git;
-pub mod observability;
-pub mod utils;
+
+pub use git_ai_core::{authorship, config, error, git, observability, utils};
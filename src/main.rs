@@ -1,13 +1,5 @@
-mod authorship;
-mod ci;
-mod commands;
-mod config;
-mod error;
-mod git;
-mod observability;
-mod utils;
-
 use clap::Parser;
+use git_ai::commands;
 
 #[derive(Parser)]
 #[command(name = "git-ai")]
@@ -42,7 +34,7 @@ fn main() {
         }
     }
 
-    if binary_name == "git-ai" || binary_name == "git-ai.exe" {
+    if git_ai::utils::is_git_ai_binary_name(&binary_name) {
         commands::git_ai_handlers::handle_git_ai(&cli.args);
         std::process::exit(0);
     }
@@ -0,0 +1,35 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+#[test]
+fn test_acceptance_per_session_and_agent() {
+    let repo = TestRepo::new();
+    let mut lib = repo.filename("lib.rs");
+    lib.set_contents(lines!["fn a() {}".human()]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    lib.set_contents(lines![
+        "fn a() {}".human(),
+        "fn agent() {}".ai(),
+        "fn more() {}".ai()
+    ]);
+    repo.stage_all_and_commit("Agent's edit").unwrap();
+
+    let output = repo.git_ai(&["stats", "--acceptance", "--json"]).unwrap();
+    let report: serde_json::Value = serde_json::from_str(output.trim()).unwrap();
+    let by_tool_model = report["by_tool_model"].as_array().unwrap();
+    assert_eq!(by_tool_model.len(), 1, "{}", output);
+    assert_eq!(by_tool_model[0]["tool"], "mock_ai");
+    assert_eq!(by_tool_model[0]["accepted_lines"], 2, "{}", output);
+    assert_eq!(by_tool_model[0]["acceptance_rate"], 1.0, "{}", output);
+
+    let sessions = report["sessions"].as_array().unwrap();
+    assert_eq!(sessions.len(), 1, "{}", output);
+    assert_eq!(sessions[0]["commits"], 1);
+
+    // The initial commit has no AI sessions
+    let output = repo.git_ai(&["stats", "--acceptance", "HEAD~1"]).unwrap();
+    assert!(output.contains("No AI sessions"), "{}", output);
+}
@@ -0,0 +1,77 @@
+#![cfg(unix)]
+
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::process::Command;
+
+/// Commits through a `fake-agent` process, the way an agent without hooks runs git. Without
+/// `agent_processes`, GIT_AI_AGENT_PROCESSES is left unset.
+fn commit_as_fake_agent(repo: &TestRepo, agent_processes: Option<&str>, message: &str) {
+    let dir = tempfile::tempdir().unwrap();
+    let agent = dir.path().join("fake-agent");
+    // Not `exec`, so the agent stays the parent of git
+    std::fs::write(&agent, "#!/bin/sh\n\"$@\"\nexit $?\n").unwrap();
+    std::fs::set_permissions(&agent, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    repo.git(&["add", "-A"]).unwrap();
+    let mut command = Command::new(&agent);
+    command
+        .arg(env!("CARGO_BIN_EXE_git-ai"))
+        .args(["-C", repo.path().to_str().unwrap(), "commit", "-m", message])
+        .env("GIT_AI", "git")
+        .env_remove("GIT_AI_AGENT_PROCESSES");
+    if let Some(agent_processes) = agent_processes {
+        command.env("GIT_AI_AGENT_PROCESSES", agent_processes);
+    }
+    let output = command.output().unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+fn note_for_head(repo: &TestRepo) -> String {
+    repo.git(&["notes", "--ref=ai", "show", "HEAD"]).unwrap()
+}
+
+#[test]
+fn test_commit_by_agent_without_hooks_is_attributed_to_it() {
+    let repo = TestRepo::new_with_file("README.md", &["# Project"]);
+    std::fs::write(Path::new(repo.path()).join("lib.rs"), "fn helper() {}\n").unwrap();
+
+    commit_as_fake_agent(&repo, Some("codex,fake-agent"), "Add helper");
+    let note = note_for_head(&repo);
+    assert!(note.contains("\"tool\": \"fake-agent\""), "{}", note);
+    assert_eq!(repo.head_additions(), (1, 0, 0));
+}
+
+#[test]
+fn test_unlisted_agents_and_checkpointing_agents_are_left_alone() {
+    let repo = TestRepo::new_with_file("README.md", &["# Project"]);
+    std::fs::write(Path::new(repo.path()).join("lib.rs"), "fn helper() {}\n").unwrap();
+    commit_as_fake_agent(&repo, Some("codex"), "Add helper");
+    assert_eq!(repo.head_additions(), (0, 1, 0));
+
+    // No agent processes are looked for unless some are configured
+    std::fs::write(Path::new(repo.path()).join("notes.md"), "by hand\n").unwrap();
+    commit_as_fake_agent(&repo, None, "Add notes");
+    assert_eq!(repo.head_additions(), (0, 1, 0));
+
+    // An agent that checkpointed its own work keeps it, and the human edit stays human
+    let mut lib = repo.filename("lib.rs");
+    lib.set_contents(lines!["fn helper() {}", "fn other() {}".ai()]);
+    std::fs::write(
+        Path::new(repo.path()).join("README.md"),
+        "# Project\nEdited by hand\n",
+    )
+    .unwrap();
+    commit_as_fake_agent(&repo, Some("fake-agent"), "Add other");
+    let note = note_for_head(&repo);
+    assert!(!note.contains("fake-agent"), "{}", note);
+    assert_eq!(repo.head_additions().0, 1);
+}
@@ -441,3 +441,128 @@ fn test_amend_with_unstaged_middle_section() {
         "// AI section 3 line 2".ai()
     ]);
 }
+
+/// Test amending with one hunk staged through `git add -p` carries the other hunk over
+#[test]
+fn test_amend_with_patch_staged_hunk() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("code.txt");
+
+    file.set_contents(lines!["// header", "// footer"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    file.insert_at(1, lines!["// AI committed".ai()]);
+    repo.stage_all_and_commit("Add AI line").unwrap();
+
+    file.insert_at(3, lines!["// AI unstaged".ai()]);
+    file.insert_at(2, lines!["// AI staged".ai()]);
+    file.insert_at(1, lines!["// AI also unstaged".ai()]);
+    repo.add_edit("code.txt", "sed -i -e '/^+.*unstaged/d'");
+
+    repo.git(&["commit", "--amend", "-m", "Add AI lines"])
+        .unwrap();
+    let stats = repo.stats().unwrap();
+    assert_eq!(stats.ai_additions, 2);
+    assert_eq!(stats.human_additions, 0);
+
+    repo.stage_all_and_commit("Add the rest").unwrap();
+    let stats = repo.stats().unwrap();
+    assert_eq!(stats.ai_additions, 2);
+
+    file.assert_lines_and_blame(lines![
+        "// header".human(),
+        "// AI also unstaged".ai(),
+        "// AI committed".ai(),
+        "// AI staged".ai(),
+        "// footer".human(),
+        "// AI unstaged".ai()
+    ]);
+}
+
+/// Test amending with a staged line rewritten in the working copy after staging
+#[test]
+fn test_amend_with_staged_line_rewritten_after_staging() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("code.txt");
+
+    file.set_contents(lines!["// header", "// footer"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    file.insert_at(1, lines!["// AI committed".ai()]);
+    repo.stage_all_and_commit("Add AI line").unwrap();
+
+    // A human line is staged, then AI rewrites it before the amend
+    file.insert_at(2, lines!["// human line".human()]);
+    repo.add_edit("code.txt", "true");
+    file.replace_at(2, "// AI rewrite".ai());
+
+    repo.git(&["commit", "--amend", "-m", "Add lines"]).unwrap();
+    let stats = repo.stats().unwrap();
+    assert_eq!(stats.ai_additions, 1);
+    assert_eq!(stats.human_additions, 1);
+
+    // The rewrite is carried over and committed next
+    repo.stage_all_and_commit("Rewrite line").unwrap();
+    file.assert_lines_and_blame(lines![
+        "// header".human(),
+        "// AI committed".ai(),
+        "// AI rewrite".ai(),
+        "// footer".human()
+    ]);
+}
+
+/// Test amending with just some paths keeps the staged attribution of the rest of the index
+#[test]
+fn test_amend_only_paths_keeps_staged_attribution_of_other_files() {
+    let repo = TestRepo::new();
+    let mut staged = repo.filename("staged.txt");
+    let mut other = repo.filename("other.txt");
+
+    staged.set_contents(lines!["start", "end"]);
+    other.set_contents(lines!["other", "end"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    other.insert_at(1, lines!["AI in other".ai()]);
+    repo.stage_all_and_commit("Add AI line").unwrap();
+
+    // A human line is staged, then AI rewrites it in the working copy
+    staged.insert_at(1, lines!["human line".human()]);
+    repo.add_edit("staged.txt", "true");
+    staged.replace_at(1, "AI rewrite".ai());
+
+    other.insert_at(2, lines!["AI amended into other".ai()]);
+    repo.git(&["commit", "--amend", "-m", "Add AI lines", "--", "other.txt"])
+        .unwrap();
+    let stats = repo.stats().unwrap();
+    assert_eq!(stats.ai_additions, 2);
+
+    // The staged version of staged.txt is committed as the human's
+    repo.commit("Commit staged").unwrap();
+    let stats = repo.stats().unwrap();
+    assert_eq!(stats.ai_additions, 0);
+    assert_eq!(stats.human_additions, 1);
+}
+
+/// Test that amending without changing the message keeps the agent's authorship of it
+#[test]
+fn test_amend_keeps_ai_commit_message() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("test.txt");
+
+    file.set_contents(lines!["line 1"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    file.insert_at(1, lines!["line 2".ai()]);
+    repo.git_ai(&["checkpoint", "mock_ai", "--commit-message", "Add line 2"])
+        .unwrap();
+    let commit = repo.stage_all_and_commit("Add line 2").unwrap();
+    let prompt_id = commit.authorship_log.metadata.commit_message_prompt.clone();
+    assert!(prompt_id.is_some());
+
+    file.insert_at(2, lines!["line 3".human()]);
+    repo.git(&["add", "-A"]).unwrap();
+    repo.git(&["commit", "--amend", "--no-edit"]).unwrap();
+
+    let stats = repo.stats().unwrap();
+    assert_eq!(stats.ai_commit_message.as_deref(), Some("mock_ai::unknown"));
+}
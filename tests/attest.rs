@@ -0,0 +1,50 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+#[test]
+fn test_attest_commit() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("code.txt");
+    file.set_contents(lines!["start", "end"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    file.insert_at(1, lines!["AI 1".ai(), "AI 2".ai(), "human".human()]);
+    let commit = repo.stage_all_and_commit("Add lines").unwrap();
+
+    let output = repo.git_ai(&["attest"]).unwrap();
+    let statement: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(statement["_type"], "https://in-toto.io/Statement/v1");
+    assert_eq!(
+        statement["subject"][0]["digest"]["gitCommit"],
+        commit.commit_sha.as_str()
+    );
+    let tree = repo.git(&["rev-parse", "HEAD^{tree}"]).unwrap();
+    assert_eq!(statement["subject"][1]["digest"]["gitTree"], tree.trim());
+
+    let predicate = &statement["predicate"];
+    assert_eq!(predicate["attribution"]["added_lines"], 3);
+    assert_eq!(predicate["attribution"]["ai_lines"], 2);
+    assert_eq!(predicate["attribution"]["human_lines"], 1);
+
+    // One session, named by the same prompt id as the authorship note
+    let agents = predicate["agents"].as_array().unwrap();
+    assert_eq!(agents.len(), 1);
+    let prompt = agents[0]["prompt"].as_str().unwrap();
+    assert!(commit.authorship_log.metadata.prompts.contains_key(prompt));
+    assert_eq!(agents[0]["tool"], "mock_ai");
+}
+
+#[test]
+fn test_attest_commit_without_authorship_fails() {
+    let repo = TestRepo::new();
+    std::fs::write(repo.path().join("file.txt"), "content\n").unwrap();
+    repo.git(&["add", "-A"]).unwrap();
+    repo.git(&["commit", "-m", "Initial commit"]).unwrap();
+    // Drop the commit's note, as if it was made without git-ai
+    repo.git(&["notes", "--ref=ai", "remove", "HEAD"]).unwrap();
+
+    let err = repo.git_ai(&["attest", "HEAD"]).unwrap_err();
+    assert!(err.contains("No authorship data found"));
+}
@@ -0,0 +1,110 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+const GAP_WARNING: &str = "were never checkpointed";
+
+fn commit(repo: &TestRepo, message: &str) -> String {
+    repo.git(&["add", "-A"]).unwrap();
+    repo.git(&["commit", "-m", message]).unwrap()
+}
+
+#[test]
+fn test_commit_mostly_missed_by_checkpoints_warns() {
+    let repo = TestRepo::new();
+    let mut lib = repo.filename("lib.rs");
+    lib.set_contents(lines!["fn helper() {}".ai()]);
+    repo.write_lines("unseen.rs", 30);
+
+    let output = commit(&repo, "Add helper and unseen code");
+    assert!(output.contains(GAP_WARNING), "{}", output);
+    assert!(output.contains("unseen.rs (30 lines)"), "{}", output);
+    assert!(!output.contains("lib.rs ("), "{}", output);
+}
+
+#[test]
+fn test_checkpointed_or_uninstrumented_commits_dont_warn() {
+    let repo = TestRepo::new();
+    let mut lib = repo.filename("lib.rs");
+    lib.set_contents(lines!["fn helper() {}".ai()]);
+    let output = commit(&repo, "Add helper");
+    assert!(!output.contains(GAP_WARNING), "{}", output);
+
+    // Nothing has checkpointed since, so there's nothing to compare against
+    repo.write_lines("by_hand.rs", 30);
+    let output = commit(&repo, "Add code by hand");
+    assert!(!output.contains(GAP_WARNING), "{}", output);
+}
+
+#[test]
+fn test_gap_warnings_can_be_turned_off() {
+    let repo = TestRepo::new();
+    let mut lib = repo.filename("lib.rs");
+    lib.set_contents(lines!["fn helper() {}".ai()]);
+    repo.write_lines("unseen.rs", 30);
+    repo.git(&["add", "-A"]).unwrap();
+    let output = repo
+        .git_with_env(
+            &["commit", "-m", "Add helper and unseen code"],
+            &[("GIT_AI_ATTRIBUTION_GAP_WARNINGS", "0")],
+        )
+        .unwrap();
+    assert!(!output.contains(GAP_WARNING), "{}", output);
+}
+
+#[test]
+fn test_warn_lists_even_small_gaps() {
+    let repo = TestRepo::new();
+    let mut lib = repo.filename("lib.rs");
+    lib.set_contents(lines!["fn helper() {}".ai()]);
+    repo.write_lines("unseen.rs", 2);
+    repo.git(&["add", "-A"]).unwrap();
+    let output = repo
+        .git_with_env(
+            &["commit", "-m", "Add helper and unseen code"],
+            &[("GIT_AI_UNATTRIBUTED_CHANGES", "warn")],
+        )
+        .unwrap();
+    assert!(output.contains("unseen.rs (2 lines)"), "{}", output);
+}
+
+#[test]
+fn test_block_refuses_commits_with_unattributed_changes() {
+    let repo = TestRepo::new();
+    let mut lib = repo.filename("lib.rs");
+    lib.set_contents(lines!["fn helper() {}".ai()]);
+    repo.write_lines("unseen.rs", 2);
+    repo.git(&["add", "-A"]).unwrap();
+    let block = [("GIT_AI_UNATTRIBUTED_CHANGES", "block")];
+    let error = repo
+        .git_with_env(&["commit", "-m", "Add helper and unseen code"], &block)
+        .unwrap_err();
+    assert!(error.contains("commit blocked"), "{}", error);
+    assert!(error.contains("unseen.rs (2 lines)"), "{}", error);
+    assert!(repo.git(&["log", "--oneline"]).is_err());
+
+    // Once checkpointed, the same changes commit
+    repo.git_ai(&["checkpoint"]).unwrap();
+    repo.git_with_env(&["commit", "-m", "Add helper and unseen code"], &block)
+        .unwrap();
+}
+
+#[test]
+fn test_unknown_attributes_unattributed_changes_to_no_one() {
+    let repo = TestRepo::new();
+    let mut lib = repo.filename("lib.rs");
+    lib.set_contents(lines!["fn helper() {}".ai()]);
+    repo.write_lines("unseen.rs", 3);
+    repo.git(&["add", "-A"]).unwrap();
+    repo.git_with_env(
+        &["commit", "-m", "Add helper and unseen code"],
+        &[("GIT_AI_UNATTRIBUTED_CHANGES", "unknown")],
+    )
+    .unwrap();
+
+    let stats = repo.stats().unwrap();
+    assert_eq!(stats.ai_additions, 1);
+    assert_eq!(stats.unknown_additions, 3);
+    assert_eq!(stats.human_additions, 0);
+}
@@ -0,0 +1,70 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+fn note(repo: &TestRepo) -> serde_json::Value {
+    let note = repo.git(&["notes", "--ref=ai", "show", "HEAD"]).unwrap();
+    let metadata = note.split("---\n").nth(1).unwrap();
+    serde_json::from_str(metadata.trim()).unwrap()
+}
+
+#[test]
+fn test_author_classes_are_counted_apart_from_humans() {
+    let repo = TestRepo::new();
+    let mut lib = repo.filename("lib.rs");
+    lib.set_contents(lines!["fn helper() {}".ai()]);
+
+    std::fs::write(
+        repo.path().join("schema.rs"),
+        "pub struct A;\npub struct B;\npub struct C;\n",
+    )
+    .unwrap();
+    repo.git_ai(&["checkpoint", "--class", "codegen", "schema.rs"])
+        .unwrap();
+    std::fs::write(repo.path().join("notes.md"), "written by hand\n").unwrap();
+    repo.stage_all_and_commit("Add generated schema").unwrap();
+
+    let stats = repo.stats().unwrap();
+    assert_eq!(stats.ai_additions, 1);
+    assert_eq!(stats.class_additions.get("codegen"), Some(&3));
+    assert_eq!(stats.human_additions, 1);
+    assert_eq!(
+        note(&repo)["author_classes"],
+        serde_json::json!(["codegen"])
+    );
+
+    let blame = repo.git_ai(&["blame", "schema.rs"]).unwrap();
+    let lines: Vec<&str> = blame
+        .lines()
+        .filter(|line| line.contains("struct"))
+        .collect();
+    assert_eq!(lines.len(), 3);
+    assert!(
+        lines.iter().all(|line| line.contains("codegen")),
+        "{}",
+        blame
+    );
+}
+
+#[test]
+fn test_configured_author_class() {
+    let repo = TestRepo::new();
+    std::fs::write(repo.path().join("vendor.rs"), "fn vendored() {}\n").unwrap();
+
+    let err = repo
+        .git_ai(&["checkpoint", "--class", "third_party", "vendor.rs"])
+        .unwrap_err();
+    assert!(err.contains("unknown author class"), "{}", err);
+
+    let env = [("GIT_AI_AUTHOR_CLASSES", "third_party")];
+    repo.git_ai_with_env(&["checkpoint", "--class", "third_party", "vendor.rs"], &env)
+        .unwrap();
+    repo.stage_all_and_commit("Vendor a dependency").unwrap();
+
+    // Readers that aren't configured with the class still tell it apart from AI and humans
+    let stats = repo.stats().unwrap();
+    assert_eq!(stats.class_additions.get("third_party"), Some(&1));
+    assert_eq!(stats.ai_additions, 0);
+    assert_eq!(stats.human_additions, 0);
+}
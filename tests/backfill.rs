@@ -0,0 +1,82 @@
+#[macro_use]
+mod repos;
+use repos::test_repo::TestRepo;
+
+/// Commits `contents` to `code.rs` as `author`
+fn commit_as(repo: &TestRepo, contents: &str, message: &str, author: (&str, &str)) {
+    std::fs::write(repo.path().join("code.rs"), contents).unwrap();
+    repo.git(&["add", "-A"]).unwrap();
+    repo.git_with_env(
+        &["commit", "-m", message],
+        &[
+            ("GIT_AUTHOR_NAME", author.0),
+            ("GIT_AUTHOR_EMAIL", author.1),
+        ],
+    )
+    .unwrap();
+}
+
+fn stats_json(repo: &TestRepo, args: &[&str]) -> serde_json::Value {
+    let mut stats_args = vec!["stats", "--json"];
+    stats_args.extend(args);
+    let output = repo.git_ai(&stats_args).unwrap();
+    let json = output.lines().find(|line| line.starts_with('{')).unwrap();
+    serde_json::from_str(json).unwrap()
+}
+
+#[test]
+fn test_backfill_infers_bot_and_marked_commits() {
+    let repo = TestRepo::new();
+    let human = ("Jane", "jane@example.com");
+    commit_as(&repo, "start\nend", "Initial commit", human);
+    commit_as(
+        &repo,
+        "start\nfn a() {}\nfn b() {}\nend",
+        "Add a and b",
+        (
+            "copilot-swe-agent[bot]",
+            "198982749+Copilot@users.noreply.github.com",
+        ),
+    );
+    commit_as(
+        &repo,
+        "start\nfn a() {}\nfn b() {}\nfn c() {}\nend",
+        "Add c\n\nGenerated with [Claude Code](https://claude.com/claude-code)",
+        human,
+    );
+    commit_as(
+        &repo,
+        "start\nfn a() {}\nfn b() {}\nfn c() {}\nfn d() {}\nend",
+        "Add d",
+        human,
+    );
+    // As if git-ai was installed after these commits were made
+    repo.git(&["update-ref", "-d", "refs/notes/ai"]).unwrap();
+
+    let output = repo.git_ai(&["backfill", "--dry-run"]).unwrap();
+    assert!(output.contains("Would infer 2 commits (0 already had notes, 2 not recognized)"));
+    assert!(repo.git(&["notes", "--ref=ai", "show", "HEAD~1"]).is_err());
+
+    let output = repo.git_ai(&["backfill"]).unwrap();
+    assert!(
+        output
+            .contains("github-copilot: 2 AI lines, 80% confidence (author copilot-swe-agent[bot])")
+    );
+    assert!(output.contains("claude: 1 AI line, 70% confidence"));
+    assert!(output.contains("Inferred 2 commits"));
+
+    let note = repo.git(&["notes", "--ref=ai", "show", "HEAD~2"]).unwrap();
+    assert!(note.contains("\"inferred\": \"author copilot-swe-agent[bot]\""));
+    assert!(repo.git(&["notes", "--ref=ai", "show", "HEAD"]).is_err());
+
+    let stats = stats_json(&repo, &["HEAD~2"]);
+    assert_eq!(stats["ai_additions"], 2);
+    assert_eq!(stats["ai_low_confidence"], 2);
+    let stats = stats_json(&repo, &["HEAD~2", "--exclude-inferred"]);
+    assert_eq!(stats["ai_additions"], 0);
+    assert_eq!(stats["human_additions"], 2);
+
+    // A second run leaves the inferred notes alone
+    let output = repo.git_ai(&["backfill"]).unwrap();
+    assert!(output.contains("Inferred 0 commits (2 already had notes, 2 not recognized)"));
+}
@@ -656,3 +656,192 @@ fn test_blame_with_ai_authorship() {
         git_ai_authors
     );
 }
+
+#[test]
+fn test_blame_min_confidence_hides_uncertain_ai_lines() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("lib.rs");
+
+    file.set_contents(lines![
+        "fn helper() {".ai(),
+        "    let a = 1;".ai(),
+        "    let b = 2;".ai(),
+        "    a + b".ai(),
+        "}".ai(),
+        "",
+        "fn main() {",
+        "    println!(\"start\");",
+        "    setup();",
+        "    run();",
+        "    check();",
+        "    teardown();",
+        "    println!(\"end\");",
+        "}",
+    ]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    // A human moves the AI helper into a module, reindenting it on the way
+    let moved = "fn main() {\n    println!(\"start\");\n    setup();\n    run();\n    check();\n    teardown();\n    println!(\"end\");\n}\n\nmod inner {\n    fn helper() {\n        let a = 1;\n        let b = 2;\n        a + b\n    }\n}";
+    std::fs::write(repo.path().join("lib.rs"), moved).unwrap();
+    repo.git_ai(&["checkpoint"]).unwrap();
+    let commit = repo.stage_all_and_commit("Move helper").unwrap();
+
+    let confidences: Vec<Option<u8>> = commit
+        .authorship_log
+        .attestations
+        .iter()
+        .flat_map(|f| f.entries.iter().map(|e| e.confidence))
+        .collect();
+    assert!(
+        confidences.iter().any(|c| matches!(c, Some(c) if *c < 100)),
+        "Reindented move should be recorded with reduced confidence: {:?}",
+        confidences
+    );
+
+    let all = extract_authors(&repo.git_ai(&["blame", "lib.rs"]).unwrap());
+    assert!(
+        all.iter().any(|a| a.contains("mock_ai")),
+        "Moved lines stay attributed to AI by default: {:?}",
+        all
+    );
+
+    let certain = extract_authors(
+        &repo
+            .git_ai(&["blame", "--min-confidence", "100", "lib.rs"])
+            .unwrap(),
+    );
+    assert!(
+        !certain.iter().any(|a| a.contains("mock_ai")),
+        "Uncertain AI lines should fall back to the commit author: {:?}",
+        certain
+    );
+}
+
+#[test]
+fn test_blame_shows_co_written_lines() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("calc.rs");
+
+    file.set_contents(lines![
+        "fn total(a: u32, b: u32, c: u32) -> u32 {",
+        "    let sum = a + b + c;",
+        "    sum",
+        "}",
+    ]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    // The AI rewrites only the expression in the middle of a human line
+    let edited = "fn total(a: u32, b: u32, c: u32) -> u32 {\n    let sum = a.saturating_add(b).saturating_add(c);\n    sum\n}";
+    std::fs::write(repo.path().join("calc.rs"), edited).unwrap();
+    repo.git_ai(&["checkpoint", "mock_ai"]).unwrap();
+    let commit = repo.stage_all_and_commit("Use saturating adds").unwrap();
+
+    let column_ranges: Vec<_> = commit
+        .authorship_log
+        .attestations
+        .iter()
+        .flat_map(|f| f.entries.iter().flat_map(|e| e.column_ranges.clone()))
+        .collect();
+    assert!(
+        column_ranges.iter().all(|range| range.line == 2) && !column_ranges.is_empty(),
+        "Only the co-written line should get column ranges: {:?}",
+        column_ranges
+    );
+
+    let output = repo.git_ai(&["blame", "calc.rs"]).unwrap();
+    let mixed_line = output
+        .lines()
+        .find(|line| line.contains("saturating_add"))
+        .unwrap();
+    let author = &mixed_line[mixed_line.find('(').unwrap() + 1..];
+    assert!(
+        author.contains("mock_ai") && author.contains('+'),
+        "Co-written line should show both authors: {}",
+        mixed_line
+    );
+
+    let untouched = output
+        .lines()
+        .find(|line| line.contains("    sum"))
+        .unwrap();
+    assert!(
+        !untouched.contains("mock_ai"),
+        "Untouched lines show a single author: {}",
+        untouched
+    );
+}
+
+#[test]
+fn test_blame_several_files() {
+    let repo = TestRepo::new();
+    let mut a = repo.filename("a.txt");
+    a.set_contents(lines!["Alpha", "Beta".ai()]);
+    let mut b = repo.filename("b.txt");
+    b.set_contents(lines!["Gamma".ai()]);
+    repo.stage_all_and_commit("Add files").unwrap();
+
+    let output = repo.git_ai(&["blame", "a.txt", "b.txt"]).unwrap();
+    let a_header = output.find("==> a.txt <==").expect(&output);
+    let b_header = output.find("==> b.txt <==").expect(&output);
+    assert!(a_header < b_header, "{}", output);
+    assert!(output[a_header..b_header].contains("Beta"), "{}", output);
+    assert!(output[b_header..].contains("Gamma"), "{}", output);
+
+    // Interleaved output names the file on every line instead
+    let output = repo
+        .git_ai(&["blame", "--interleave", "a.txt", "b.txt"])
+        .unwrap();
+    assert!(!output.contains("==>"), "{}", output);
+    for line in output.lines().filter(|line| !line.is_empty()) {
+        assert!(
+            line.contains("a.txt") || line.contains("b.txt"),
+            "{}",
+            output
+        );
+    }
+
+    let output = repo
+        .git_ai_with_stdin(&["blame", "--stdin"], "a.txt\nb.txt\n")
+        .unwrap();
+    assert!(
+        output.contains("==> a.txt <==") && output.contains("==> b.txt <=="),
+        "{}",
+        output
+    );
+}
+
+#[test]
+fn test_blame_several_files_reports_failures() {
+    let repo = TestRepo::new();
+    let mut a = repo.filename("a.txt");
+    a.set_contents(lines!["Alpha".ai()]);
+    repo.stage_all_and_commit("Add a").unwrap();
+
+    let err = repo.git_ai(&["blame", "a.txt", "missing.txt"]).unwrap_err();
+    assert!(err.contains("missing.txt"), "{}", err);
+    assert!(err.contains("1 of 2 files"), "{}", err);
+}
+
+#[test]
+fn test_blame_several_files_skips_generated_files() {
+    let repo = TestRepo::new();
+    let mut a = repo.filename("a.txt");
+    a.set_contents(lines!["Alpha".ai()]);
+    let mut lockfile = repo.filename("yarn.lock");
+    lockfile.set_contents(lines!["lock 1", "lock 2"]);
+    repo.stage_all_and_commit("Add files").unwrap();
+
+    let output = repo.git_ai(&["blame", "a.txt", "yarn.lock"]).unwrap();
+    assert!(output.contains("Alpha"), "{}", output);
+    assert!(!output.contains("lock 1"), "{}", output);
+    assert!(output.contains("Skipped 1 generated file"), "{}", output);
+
+    let output = repo
+        .git_ai(&["blame", "--include-generated", "a.txt", "yarn.lock"])
+        .unwrap();
+    assert!(output.contains("==> yarn.lock <=="), "{}", output);
+
+    // Named on its own, a generated file is still blamed
+    let output = repo.git_ai(&["blame", "yarn.lock"]).unwrap();
+    assert!(output.contains("lock 1"), "{}", output);
+}
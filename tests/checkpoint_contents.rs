@@ -0,0 +1,47 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+const GENERATED: &str = "fn a() {}\nfn agent() {}\n";
+
+/// The editor is halfway through writing the agent's edit when the checkpoint runs
+fn write_half(repo: &TestRepo) {
+    std::fs::write(repo.path().join("lib.rs"), "fn a() {}\nfn age").unwrap();
+}
+
+fn finish_and_commit(repo: &TestRepo) {
+    std::fs::write(repo.path().join("lib.rs"), GENERATED).unwrap();
+    repo.stage_all_and_commit("Agent's edit").unwrap();
+    let mut lib = repo.filename("lib.rs");
+    lib.assert_lines_and_blame(lines!["fn a() {}".human(), "fn agent() {}".ai()]);
+}
+
+#[test]
+fn test_content_from_file_wins_over_disk() {
+    let repo = TestRepo::new_with_file("lib.rs", &["fn a() {}"]);
+    write_half(&repo);
+
+    let generated = repo.path().join(".git").join("generated-lib.rs");
+    std::fs::write(&generated, GENERATED).unwrap();
+    let content = format!("lib.rs={}", generated.display());
+    repo.git_ai(&["checkpoint", "mock_ai", "lib.rs", "--content", &content])
+        .unwrap();
+
+    finish_and_commit(&repo);
+}
+
+#[test]
+fn test_contents_from_stdin_win_over_disk() {
+    let repo = TestRepo::new_with_file("lib.rs", &["fn a() {}"]);
+    write_half(&repo);
+
+    let contents = serde_json::json!({ "lib.rs": GENERATED }).to_string();
+    repo.git_ai_with_stdin(
+        &["checkpoint", "mock_ai", "lib.rs", "--contents-stdin"],
+        &contents,
+    )
+    .unwrap();
+
+    finish_and_commit(&repo);
+}
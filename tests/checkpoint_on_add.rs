@@ -0,0 +1,62 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+const CHECKPOINT_ON_ADD: &[(&str, &str)] = &[("GIT_AI_CHECKPOINT_ON_ADD", "1")];
+
+#[test]
+fn test_add_checkpoints_human_edits_before_the_agent() {
+    let repo = TestRepo::new();
+    std::fs::create_dir_all(repo.path().join("src")).unwrap();
+    let mut lib = repo.filename("src/lib.rs");
+    lib.set_contents(lines!["fn a() {}"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    // The human stages an edit, with no editor hooks to checkpoint it
+    std::fs::write(repo.path().join("src/lib.rs"), "fn a() {}\nfn human() {}\n").unwrap();
+    repo.git_with_env(&["add", "src/lib.rs"], CHECKPOINT_ON_ADD)
+        .unwrap();
+
+    // Then an agent edits the same file
+    std::fs::write(
+        repo.path().join("src/lib.rs"),
+        "fn a() {}\nfn human() {}\nfn agent() {}\n",
+    )
+    .unwrap();
+    repo.git_ai(&["checkpoint", "mock_ai", "src/lib.rs"])
+        .unwrap();
+
+    repo.stage_all_and_commit("Edits").unwrap();
+    lib.assert_lines_and_blame(lines![
+        "fn a() {}".human(),
+        "fn human() {}".human(),
+        "fn agent() {}".ai(),
+    ]);
+}
+
+#[test]
+fn test_add_of_a_directory_checkpoints_its_files() {
+    let repo = TestRepo::new();
+    std::fs::create_dir_all(repo.path().join("src")).unwrap();
+    let mut lib = repo.filename("src/lib.rs");
+    lib.set_contents(lines!["fn a() {}"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    std::fs::write(repo.path().join("src/lib.rs"), "fn a() {}\nfn human() {}\n").unwrap();
+    repo.git_with_env(&["add", "."], CHECKPOINT_ON_ADD).unwrap();
+    std::fs::write(
+        repo.path().join("src/lib.rs"),
+        "fn a() {}\nfn human() {}\nfn agent() {}\n",
+    )
+    .unwrap();
+    repo.git_ai(&["checkpoint", "mock_ai", "src/lib.rs"])
+        .unwrap();
+
+    repo.stage_all_and_commit("Edits").unwrap();
+    lib.assert_lines_and_blame(lines![
+        "fn a() {}".human(),
+        "fn human() {}".human(),
+        "fn agent() {}".ai(),
+    ]);
+}
@@ -0,0 +1,65 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+const AGENT_PATCH: &str = "\
+--- a/lib.rs
++++ b/lib.rs
+@@ -2,2 +2,3 @@
+ fn b() {}
+-fn c() {}
++fn c() { todo!() }
++fn d() {}
+";
+
+#[test]
+fn test_patch_attributes_only_the_agents_lines() {
+    let repo = TestRepo::new_with_file("lib.rs", &["fn a() {}", "fn b() {}", "fn c() {}"]);
+
+    // A human edit nobody checkpointed lands before the agent's patch
+    std::fs::write(
+        repo.path().join("lib.rs"),
+        "fn a() { human }\nfn b() {}\nfn c() { todo!() }\nfn d() {}\n",
+    )
+    .unwrap();
+    let patch = repo.path().join("agent.patch");
+    std::fs::write(&patch, AGENT_PATCH).unwrap();
+    repo.git_ai(&["checkpoint", "mock_ai", "--patch", patch.to_str().unwrap()])
+        .unwrap();
+    std::fs::remove_file(&patch).unwrap();
+
+    repo.stage_all_and_commit("Agent's edit").unwrap();
+    let mut lib = repo.filename("lib.rs");
+    lib.assert_lines_and_blame(lines![
+        "fn a() { human }".human(),
+        "fn b() {}".human(),
+        "fn c() { todo!() }".ai(),
+        "fn d() {}".ai(),
+    ]);
+}
+
+#[test]
+fn test_stale_patch_falls_back_to_diffing() {
+    let repo = TestRepo::new_with_file("lib.rs", &["fn a() {}", "fn b() {}", "fn c() {}"]);
+
+    // The file moved on since the agent made its patch
+    std::fs::write(
+        repo.path().join("lib.rs"),
+        "fn a() {}\nfn b() {}\nfn c() { unimplemented!() }\n",
+    )
+    .unwrap();
+    let patch = repo.path().join("agent.patch");
+    std::fs::write(&patch, AGENT_PATCH).unwrap();
+    repo.git_ai(&["checkpoint", "mock_ai", "--patch", patch.to_str().unwrap()])
+        .unwrap();
+    std::fs::remove_file(&patch).unwrap();
+
+    repo.stage_all_and_commit("Agent's edit").unwrap();
+    let mut lib = repo.filename("lib.rs");
+    lib.assert_lines_and_blame(lines![
+        "fn a() {}".human(),
+        "fn b() {}".human(),
+        "fn c() { unimplemented!() }".ai(),
+    ]);
+}
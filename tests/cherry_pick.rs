@@ -251,6 +251,48 @@ fn test_cherry_pick_abort() {
     ]);
 }
 
+/// Test that aborting a cherry-pick drops the AI's attempt at resolving it and its rewrite events
+#[test]
+fn test_cherry_pick_abort_rolls_back_working_log_and_rewrite_log() {
+    let repo = TestRepo::new();
+
+    let mut file = repo.filename("file.txt");
+    file.set_contents(lines!["Line 1", "Line 2"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+    let main_branch = repo.current_branch();
+
+    repo.git(&["checkout", "-b", "feature"]).unwrap();
+    file.replace_at(1, "Feature line 2".human());
+    repo.stage_all_and_commit("Feature change").unwrap();
+    let feature_commit = repo.git(&["rev-parse", "HEAD"]).unwrap().trim().to_string();
+
+    repo.git(&["checkout", &main_branch]).unwrap();
+    file.replace_at(1, "Main line 2".human());
+    repo.stage_all_and_commit("Main change").unwrap();
+    let rewrite_log_before = repo.rewrite_log();
+
+    assert!(repo.git(&["cherry-pick", &feature_commit]).is_err());
+
+    // AI resolves the conflict, then the cherry-pick is abandoned
+    file.set_contents(lines!["Line 1", "AI resolution".ai()]);
+    assert!(
+        !repo
+            .current_working_logs()
+            .read_all_checkpoints()
+            .unwrap()
+            .is_empty()
+    );
+    repo.git(&["cherry-pick", "--abort"]).unwrap();
+
+    assert!(
+        repo.current_working_logs()
+            .read_all_checkpoints()
+            .unwrap()
+            .is_empty()
+    );
+    assert_eq!(repo.rewrite_log(), rewrite_log_before);
+}
+
 /// Test cherry-picking from branch without AI authorship
 #[test]
 fn test_cherry_pick_no_ai_authorship() {
@@ -0,0 +1,76 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+use std::path::Path;
+use std::process::Command;
+
+fn upstream_with_ai_commit() -> TestRepo {
+    let upstream = TestRepo::new();
+    let mut lib = upstream.filename("lib.rs");
+    lib.set_contents(lines!["fn helper() {}".ai(), "fn other() {}".ai()]);
+    upstream.stage_all_and_commit("Add helpers").unwrap();
+    upstream
+}
+
+/// Runs real git in `dir`, bypassing git-ai
+fn plain_git(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+fn assert_clone_has_notes(clone: &Path) {
+    let notes = plain_git(clone, &["notes", "--ref=ai", "list"]);
+    assert!(!notes.trim().is_empty(), "clone has no authorship notes");
+    let refspecs = plain_git(clone, &["config", "--get-all", "remote.origin.fetch"]);
+    assert!(
+        refspecs
+            .lines()
+            .any(|refspec| refspec == "+refs/notes/ai:refs/notes/ai-remote/origin"),
+        "{}",
+        refspecs
+    );
+}
+
+#[test]
+fn test_git_clone_fetches_notes_and_configures_refspec() {
+    let upstream = upstream_with_ai_commit();
+    let dir = tempfile::tempdir().unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_git-ai"))
+        .args(["clone", upstream.path().to_str().unwrap(), "work"])
+        .current_dir(dir.path())
+        .env("GIT_AI", "git")
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{}", stderr);
+    assert!(
+        stderr.contains("fetched authorship notes from origin"),
+        "{}",
+        stderr
+    );
+    assert_clone_has_notes(&dir.path().join("work"));
+}
+
+#[test]
+fn test_git_ai_clone_uses_the_repository_name_as_directory() {
+    let upstream = upstream_with_ai_commit();
+    let dir = tempfile::tempdir().unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_git-ai"))
+        .args(["clone", "--quiet", upstream.path().to_str().unwrap()])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{}", stderr);
+    assert!(!stderr.contains("fetched authorship notes"), "{}", stderr);
+
+    let name = upstream.path().file_name().unwrap();
+    assert_clone_has_notes(&dir.path().join(name));
+}
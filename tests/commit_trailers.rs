@@ -0,0 +1,106 @@
+#[macro_use]
+mod repos;
+use git_ai::authorship::authorship_log_serialization::generate_short_hash;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+/// Records an agent-v1 checkpoint of `agent_name` editing `file`
+fn agent_checkpoint(repo: &TestRepo, agent_name: &str, conversation_id: &str, file: &str) {
+    let hook_input = serde_json::json!({
+        "type": "ai_agent",
+        "repo_working_dir": repo.canonical_path().to_string_lossy(),
+        "edited_filepaths": [file],
+        "transcript": { "messages": [] },
+        "agent_name": agent_name,
+        "model": "test-model",
+        "conversation_id": conversation_id,
+    });
+    repo.git_ai(&[
+        "checkpoint",
+        "agent-v1",
+        "--hook-input",
+        &hook_input.to_string(),
+    ])
+    .unwrap();
+}
+
+fn head_message(repo: &TestRepo) -> String {
+    repo.git(&["log", "-1", "--format=%B"]).unwrap()
+}
+
+#[test]
+fn test_commit_trailers_name_agent_sessions() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("code.txt");
+    file.set_contents(lines!["start"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    repo.git_ai(&["checkpoint"]).unwrap();
+    std::fs::write(repo.path().join("code.txt"), "start\nby claude\n").unwrap();
+    agent_checkpoint(&repo, "claude", "conversation-1", "code.txt");
+
+    repo.git(&["add", "-A"]).unwrap();
+    repo.git_with_env(
+        &["commit", "-m", "Add line"],
+        &[("GIT_AI_COMMIT_TRAILERS", "1")],
+    )
+    .unwrap();
+
+    let message = head_message(&repo);
+    assert!(message.starts_with("Add line\n"));
+    assert!(message.contains("Co-authored-by: Claude <noreply@anthropic.com>"));
+    assert!(message.contains(&format!(
+        "Git-AI-Session: {}",
+        generate_short_hash("conversation-1", "claude")
+    )));
+
+    // The trailer names the same session the authorship log does
+    let stats = repo.stats().unwrap();
+    assert_eq!(stats.ai_additions, 1);
+}
+
+#[test]
+fn test_commit_trailers_skip_sessions_outside_commit() {
+    let repo = TestRepo::new();
+    let mut first = repo.filename("first.txt");
+    let mut second = repo.filename("second.txt");
+    first.set_contents(lines!["first"]);
+    second.set_contents(lines!["second"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    // The agent edits a file that isn't committed, a human the one that is
+    second.insert_at(1, lines!["AI line".ai()]);
+    first.insert_at(1, lines!["human line".human()]);
+
+    repo.git_with_env(
+        &["commit", "-m", "Commit first", "--", "first.txt"],
+        &[("GIT_AI_COMMIT_TRAILERS", "1")],
+    )
+    .unwrap();
+    assert_eq!(head_message(&repo).trim(), "Commit first");
+
+    // Trailers are opt-in
+    repo.stage_all_and_commit("Commit second").unwrap();
+    assert_eq!(head_message(&repo).trim(), "Commit second");
+}
+
+#[test]
+fn test_commit_trailers_for_mock_agent_have_no_co_author() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("code.txt");
+    file.set_contents(lines!["start"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    file.insert_at(1, lines!["AI line".ai()]);
+    repo.git(&["add", "-A"]).unwrap();
+    repo.git_with_env(
+        &["commit", "-m", "Add line"],
+        &[("GIT_AI_COMMIT_TRAILERS", "1")],
+    )
+    .unwrap();
+
+    // git-ai doesn't know an identity for the agent, so only its session is named
+    let message = head_message(&repo);
+    assert!(!message.contains("Co-authored-by:"));
+    assert!(message.contains("Git-AI-Session: "));
+}
@@ -0,0 +1,50 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+use serde_json::Value;
+
+fn json_error(repo: &TestRepo, args: &[&str]) -> Value {
+    let mut full_args = vec!["--error-format", "json"];
+    full_args.extend_from_slice(args);
+    let stderr = repo.git_ai(&full_args).unwrap_err();
+    let error: Value = serde_json::from_str(stderr.trim()).unwrap();
+    error["error"].clone()
+}
+
+#[test]
+fn test_error_format_json_reports_stable_codes() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("app.py");
+    file.set_contents(lines!["def main():", "    return 0".ai()]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    let error = json_error(&repo, &["show", "not-a-revision"]);
+    assert_eq!(error["code"], "git_failed");
+    assert_eq!(error["exit_code"], 5);
+    assert_eq!(error["context"], "Failed to show authorship");
+
+    let outside = std::env::temp_dir();
+    for path in [outside.to_str().unwrap(), "/nonexistent/path/for/git-ai"] {
+        let error = json_error(&repo, &["replay", path]);
+        assert_eq!(error["code"], "repository_not_found");
+        assert_eq!(error["exit_code"], 4);
+    }
+}
+
+#[test]
+fn test_error_format_defaults_to_text() {
+    let repo = TestRepo::new();
+    let stderr = repo.git_ai(&["show", "not-a-revision"]).unwrap_err();
+    assert!(
+        stderr.starts_with("Failed to show authorship: "),
+        "{}",
+        stderr
+    );
+    assert!(serde_json::from_str::<Value>(stderr.trim()).is_err());
+
+    let stderr = repo
+        .git_ai(&["--error-format", "yaml", "show", "HEAD"])
+        .unwrap_err();
+    assert!(stderr.contains("invalid error format 'yaml'"), "{}", stderr);
+}
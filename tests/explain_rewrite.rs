@@ -0,0 +1,82 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+/// (file, outcome, line count) of each entry of the audit of `rev`
+fn outcomes(repo: &TestRepo, rev: &str) -> Vec<(String, String, u64)> {
+    let output = repo.git_ai(&["explain-rewrite", "--json", rev]).unwrap();
+    let json = output.lines().find(|line| line.starts_with('[')).unwrap();
+    let audits: serde_json::Value = serde_json::from_str(json).unwrap();
+    audits[0]["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| {
+            (
+                entry["file_path"].as_str().unwrap().to_string(),
+                entry["outcome"].as_str().unwrap().to_string(),
+                entry["line_count"].as_u64().unwrap(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn test_explain_rewrite_after_rebase() {
+    let repo = TestRepo::new();
+    let mut shared = repo.filename("shared.txt");
+    shared.set_contents(lines!["one", "two", "three", "four", "five"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+    let default_branch = repo.current_branch();
+
+    repo.git(&["checkout", "-b", "feature"]).unwrap();
+    let mut feature = repo.filename("feature.txt");
+    feature.set_contents(lines!["ai one".ai(), "ai two".ai()]);
+    shared.set_contents(lines!["one", "two", "three", "ai".ai(), "four", "five"]);
+    repo.stage_all_and_commit("Add feature").unwrap();
+
+    // Upstream inserts a line above the AI line in shared.txt, so its attestation has to move
+    repo.git(&["checkout", &default_branch]).unwrap();
+    shared.set_contents(lines!["header", "one", "two", "three", "four", "five"]);
+    repo.stage_all_and_commit("Add header").unwrap();
+
+    repo.git(&["checkout", "feature"]).unwrap();
+    repo.git(&["rebase", &default_branch]).unwrap();
+
+    assert_eq!(
+        outcomes(&repo, "HEAD"),
+        vec![
+            ("feature.txt".to_string(), "copied".to_string(), 2),
+            ("shared.txt".to_string(), "reconstructed".to_string(), 1),
+        ]
+    );
+
+    let output = repo.git_ai(&["explain-rewrite", "HEAD"]).unwrap();
+    assert!(output.contains("rebase of"), "{}", output);
+    assert!(output.contains("1 line: 5"), "{}", output);
+}
+
+#[test]
+fn test_explain_rewrite_after_amend_reports_dropped_lines() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("app.txt");
+    file.set_contents(lines!["human"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+    file.set_contents(lines!["human", "ai one".ai(), "ai two".ai()]);
+    repo.stage_all_and_commit("Add AI lines").unwrap();
+
+    std::fs::write(repo.path().join("app.txt"), "human\nai one\n").unwrap();
+    repo.git(&["commit", "-a", "--amend", "--no-edit"]).unwrap();
+
+    assert_eq!(
+        outcomes(&repo, "HEAD"),
+        vec![
+            ("app.txt".to_string(), "copied".to_string(), 1),
+            ("app.txt".to_string(), "dropped".to_string(), 1),
+        ]
+    );
+
+    let output = repo.git_ai(&["explain-rewrite", "HEAD~1"]).unwrap();
+    assert!(output.contains("no rewrite recorded"), "{}", output);
+}
@@ -0,0 +1,198 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+#[test]
+fn test_export_spdx() {
+    let repo = TestRepo::new();
+    let mut code = repo.filename("code.txt");
+    let mut notes = repo.filename("notes.txt");
+    code.set_contents(lines!["start", "end"]);
+    notes.set_contents(lines!["human only"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    code.insert_at(1, lines!["AI 1".ai(), "AI 2".ai()]);
+    repo.stage_all_and_commit("Add lines").unwrap();
+
+    let output = repo.git_ai(&["export", "--spdx"]).unwrap();
+    let document: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(document["spdxVersion"], "SPDX-2.3");
+    let files = document["files"].as_array().unwrap();
+    assert_eq!(files.len(), 2);
+
+    let code_file = files
+        .iter()
+        .find(|file| file["fileName"] == "./code.txt")
+        .unwrap();
+    assert_eq!(code_file["fileContributors"][0], "Tool: mock_ai/unknown");
+    let annotation = code_file["annotations"][0]["comment"].as_str().unwrap();
+    let attribution: serde_json::Value =
+        serde_json::from_str(annotation.strip_prefix("git-ai-attribution: ").unwrap()).unwrap();
+    assert_eq!(attribution["lines"], 4);
+    assert_eq!(attribution["ai_fraction"], 0.5);
+    assert_eq!(attribution["authors"][0]["ranges"][0], "2-3");
+
+    let notes_file = files
+        .iter()
+        .find(|file| file["fileName"] == "./notes.txt")
+        .unwrap();
+    assert!(notes_file.get("fileContributors").is_none());
+    assert_eq!(notes_file["comment"], "No AI-authored lines");
+
+    // Exporting the same commit again gives the same document
+    assert_eq!(repo.git_ai(&["export", "--spdx", "HEAD"]).unwrap(), output);
+}
+
+#[test]
+fn test_export_requires_format() {
+    let repo = TestRepo::new();
+    let err = repo.git_ai(&["export"]).unwrap_err();
+    assert!(err.contains("--spdx"));
+}
+
+fn read_rows(dir: &std::path::Path, table: &str) -> Vec<serde_json::Value> {
+    std::fs::read_to_string(dir.join(format!("{}.jsonl", table)))
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_export_warehouse_directory_is_incremental() {
+    let repo = TestRepo::new();
+    let warehouse = tempfile::tempdir().unwrap();
+    let dir = warehouse.path().to_str().unwrap();
+    let mut code = repo.filename("code.txt");
+    code.set_contents(lines!["start"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    let output = repo
+        .git_ai(&["export", "--warehouse", dir, "--repo-id", "demo"])
+        .unwrap();
+    assert!(output.contains("Exported 1 commit "), "{}", output);
+
+    code.insert_at(1, lines!["AI 1".ai(), "AI 2".ai()]);
+    repo.stage_all_and_commit("Add lines").unwrap();
+    let output = repo
+        .git_ai(&["export", "--warehouse", dir, "--repo-id", "demo"])
+        .unwrap();
+    assert!(output.contains("Exported 1 commit "), "{}", output);
+
+    let commits = read_rows(warehouse.path(), "commits");
+    assert_eq!(commits.len(), 2);
+    assert_eq!(commits[0]["subject"], "Initial commit");
+    assert_eq!(commits[1]["subject"], "Add lines");
+    assert_eq!(commits[1]["ai_lines"], 2);
+    assert_eq!(commits[1]["parents"][0], commits[0]["sha"]);
+
+    let attestations = read_rows(warehouse.path(), "attestations");
+    assert_eq!(attestations.len(), 1);
+    assert_eq!(attestations[0]["file"], "code.txt");
+    assert_eq!(
+        (
+            attestations[0]["start_line"].clone(),
+            attestations[0]["end_line"].clone()
+        ),
+        (serde_json::json!(2), serde_json::json!(3))
+    );
+    let prompts = read_rows(warehouse.path(), "prompts");
+    assert_eq!(prompts[0]["tool"], "mock_ai");
+    let sessions = read_rows(warehouse.path(), "sessions");
+    assert_eq!(sessions.len(), 1);
+    assert_eq!(sessions[0]["session_id"], prompts[0]["session_id"]);
+    assert_eq!(sessions[0]["first_commit"], commits[1]["sha"]);
+
+    let schema: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(warehouse.path().join("commits.schema.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(schema[0]["name"], "repo");
+
+    // Nothing new since the last run
+    let output = repo
+        .git_ai(&["export", "--warehouse", dir, "--repo-id", "demo"])
+        .unwrap();
+    assert!(output.contains("Exported 0 commits"), "{}", output);
+    assert_eq!(read_rows(warehouse.path(), "commits").len(), 2);
+
+    // --full replaces the repository's rows rather than adding to them
+    repo.git_ai(&["export", "--warehouse", dir, "--repo-id", "demo", "--full"])
+        .unwrap();
+    assert_eq!(read_rows(warehouse.path(), "commits").len(), 2);
+    assert_eq!(read_rows(warehouse.path(), "sessions").len(), 1);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_export_warehouse_bigquery_loads_tables_then_watermark() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let repo = TestRepo::new();
+    let mut code = repo.filename("code.txt");
+    code.set_contents(lines!["start"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+    code.insert_at(1, lines!["AI".ai()]);
+    repo.stage_all_and_commit("Add a line").unwrap();
+
+    // Stands in for `bq`: logs its arguments, and has no watermark table yet
+    let bin = tempfile::tempdir().unwrap();
+    let log = bin.path().join("calls.log");
+    let script = bin.path().join("bq");
+    std::fs::write(
+        &script,
+        format!(
+            "#!/bin/sh\necho \"$@\" >> '{}'\ncase \"$*\" in *query*) echo 'Not found: Table acme:git_ai.git_ai_watermarks' >&2; exit 2;; esac\n",
+            log.display()
+        ),
+    )
+    .unwrap();
+    std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    repo.git_ai_with_env(
+        &[
+            "export",
+            "--warehouse",
+            "bigquery:acme.git_ai",
+            "--repo-id",
+            "demo",
+        ],
+        &[("GIT_AI_BQ", script.to_str().unwrap())],
+    )
+    .unwrap();
+    let calls = std::fs::read_to_string(&log).unwrap();
+    let loads: Vec<&str> = calls
+        .lines()
+        .filter(|call| call.contains(" load "))
+        .map(|call| call.split_whitespace().nth(3).unwrap())
+        .collect();
+    assert_eq!(
+        loads,
+        vec![
+            "git_ai.commits",
+            "git_ai.attestations",
+            "git_ai.prompts",
+            "git_ai.sessions",
+            "git_ai.git_ai_watermarks"
+        ]
+    );
+    assert!(
+        calls
+            .lines()
+            .next()
+            .unwrap()
+            .contains("--parameter=repo::demo")
+    );
+}
+
+#[test]
+fn test_export_warehouse_rejects_bad_bigquery_spec() {
+    let repo = TestRepo::new();
+    repo.filename("code.txt").set_contents(lines!["start"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+    let err = repo
+        .git_ai(&["export", "--warehouse", "bigquery:acme"])
+        .unwrap_err();
+    assert!(err.contains("bigquery:<project>.<dataset>"), "{}", err);
+}
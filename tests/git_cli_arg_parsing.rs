@@ -1,4 +1,4 @@
-use git_ai::git::cli_parser::parse_git_cli_args;
+use git_ai::git::cli_parser::{invocation_dir, parse_git_cli_args};
 
 fn s(v: &[&str]) -> Vec<String> {
     v.iter().map(|x| x.to_string()).collect()
@@ -817,3 +817,18 @@ fn unknown_top_level_blocks_version_rewrite() {
     assert_eq!(got.command_args, s(&["--bogus", "--version"])); // no rewrite to `version`
     assert!(!got.is_help);
 }
+
+#[test]
+fn invocation_dir_applies_every_dash_c() {
+    let cwd = std::env::current_dir().unwrap();
+    assert_eq!(invocation_dir(&s(&[])), cwd);
+    assert_eq!(invocation_dir(&s(&["-C", "a", "-Cb"])), cwd.join("a/b"));
+    assert_eq!(
+        invocation_dir(&s(&["-C", "a", "-C", "/srv/repo", "-C", ""])),
+        std::path::PathBuf::from("/srv/repo")
+    );
+    assert_eq!(
+        invocation_dir(&s(&["-c", "core.editor=vi", "--git-dir=.git"])),
+        cwd
+    );
+}
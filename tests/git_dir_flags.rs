@@ -0,0 +1,104 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+use std::path::Path;
+use std::process::Command;
+
+/// Runs git-ai as git in `dir`, with `args` and `envs` as a script would pass them
+fn proxy_git(dir: &Path, args: &[&str], envs: &[(&str, &str)]) {
+    let output = Command::new(env!("CARGO_BIN_EXE_git-ai"))
+        .args(args)
+        .current_dir(dir)
+        .env("GIT_AI", "git")
+        .env("GIT_AI_AGENT_PROCESSES", "")
+        .envs(envs.iter().copied())
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+/// A repository with AI lines in `lib.rs` staged on top of a first commit
+fn repo_with_staged_ai_lines() -> TestRepo {
+    let repo = TestRepo::new();
+    let mut readme = repo.filename("README.md");
+    readme.set_contents(lines!["# Project"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+    let mut lib = repo.filename("lib.rs");
+    lib.set_contents(lines!["fn a() {}".ai(), "fn b() {}".ai()]);
+    repo.git(&["add", "lib.rs"]).unwrap();
+    repo
+}
+
+fn assert_head_attributes_lib_to_ai(repo: &TestRepo) {
+    let mut lib = repo.filename("lib.rs");
+    lib.assert_lines_and_blame(lines!["fn a() {}".ai(), "fn b() {}".ai()]);
+}
+
+#[test]
+fn test_commit_with_git_dir_and_work_tree_flags_from_outside() {
+    let repo = repo_with_staged_ai_lines();
+    let outside = tempfile::tempdir().unwrap();
+    let git_dir = format!("--git-dir={}", repo.path().join(".git").display());
+    let work_tree = format!("--work-tree={}", repo.path().display());
+
+    proxy_git(
+        outside.path(),
+        &[&git_dir, &work_tree, "commit", "-m", "Add lib"],
+        &[],
+    );
+    assert_head_attributes_lib_to_ai(&repo);
+}
+
+#[test]
+fn test_commit_with_git_dir_and_work_tree_env_from_outside() {
+    let repo = repo_with_staged_ai_lines();
+    let outside = tempfile::tempdir().unwrap();
+    let git_dir = repo.path().join(".git");
+
+    proxy_git(
+        outside.path(),
+        &["commit", "-m", "Add lib"],
+        &[
+            ("GIT_DIR", git_dir.to_str().unwrap()),
+            ("GIT_WORK_TREE", repo.path().to_str().unwrap()),
+        ],
+    );
+    assert_head_attributes_lib_to_ai(&repo);
+}
+
+#[test]
+fn test_pathspec_reset_is_relative_to_dash_c_directory() {
+    let repo = TestRepo::new();
+    std::fs::create_dir(repo.path().join("src")).unwrap();
+    let mut lib = repo.filename("src/lib.rs");
+    let mut main = repo.filename("src/main.rs");
+    lib.set_contents(lines!["// lib"]);
+    main.set_contents(lines!["// main"]);
+    let base = repo.stage_all_and_commit("Base").unwrap();
+
+    lib.insert_at(1, lines!["fn lib() {}".ai()]);
+    main.insert_at(1, lines!["fn main() {}".ai()]);
+    repo.stage_all_and_commit("AI changes").unwrap();
+    main.insert_at(2, lines!["fn more() {}".ai()]);
+
+    // `lib.rs` is relative to src/, as git reads it
+    proxy_git(
+        repo.path(),
+        &["-C", "src", "reset", &base.commit_sha, "--", "lib.rs"],
+        &[],
+    );
+    let staged = repo.git(&["diff", "--cached", "--name-only"]).unwrap();
+    assert_eq!(staged.trim(), "src/lib.rs");
+
+    repo.stage_all_and_commit("After pathspec reset").unwrap();
+    main.assert_lines_and_blame(lines![
+        "// main".human(),
+        "fn main() {}".ai(),
+        "fn more() {}".ai(),
+    ]);
+}
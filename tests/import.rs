@@ -0,0 +1,159 @@
+#[macro_use]
+mod repos;
+use repos::test_repo::TestRepo;
+
+/// A Claude Code transcript whose only edit writes `new_string` into `file_path`
+fn claude_code_transcript(file_path: &str, new_string: &str, timestamp: &str) -> String {
+    let entries = [
+        serde_json::json!({
+            "type": "user",
+            "timestamp": timestamp,
+            "message": { "role": "user", "content": "add a helper" },
+        }),
+        serde_json::json!({
+            "type": "assistant",
+            "timestamp": timestamp,
+            "message": {
+                "model": "claude-sonnet-4",
+                "role": "assistant",
+                "content": [{
+                    "type": "tool_use",
+                    "name": "Edit",
+                    "input": {
+                        "file_path": file_path,
+                        "old_string": "start\n",
+                        "new_string": new_string,
+                    },
+                }],
+            },
+        }),
+    ];
+    entries
+        .iter()
+        .map(|entry| entry.to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Commits `code.rs` with `contents`, dated `date`
+fn commit_at(repo: &TestRepo, contents: &str, message: &str, date: &str) {
+    std::fs::write(repo.path().join("code.rs"), contents).unwrap();
+    repo.git(&["add", "-A"]).unwrap();
+    repo.git_with_env(
+        &["commit", "-m", message],
+        &[("GIT_AUTHOR_DATE", date), ("GIT_COMMITTER_DATE", date)],
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_import_claude_code_history_backfills_notes() {
+    let repo = TestRepo::new();
+    commit_at(
+        &repo,
+        "start\nend",
+        "Initial commit",
+        "2025-02-01T12:00:00Z",
+    );
+
+    commit_at(
+        &repo,
+        "start\nfn helper() {\n    compute()\n}\nlet human = 1;\nend",
+        "Add helper",
+        "2025-03-01T12:00:00Z",
+    );
+    // As if git-ai was installed after these commits were made
+    repo.git(&["update-ref", "-d", "refs/notes/ai"]).unwrap();
+
+    let history = tempfile::tempdir().unwrap();
+    let project = history.path().join("-project");
+    std::fs::create_dir(&project).unwrap();
+    std::fs::write(
+        project.join("0b7ab7b1-5f7a-4a3b-9f6e-2f3c1d2e4a5b.jsonl"),
+        claude_code_transcript(
+            &repo.canonical_path().join("code.rs").to_string_lossy(),
+            "start\nfn helper() {\n    compute()\n}\n",
+            "2025-03-01T11:00:00Z",
+        ),
+    )
+    .unwrap();
+    let from = history.path().to_string_lossy().to_string();
+
+    let output = repo
+        .git_ai(&[
+            "import",
+            "claude-code",
+            "--since",
+            "2025-01-01",
+            "--from",
+            &from,
+        ])
+        .unwrap();
+    assert!(output.contains("3 AI lines (claude-sonnet-4, 1 session), 1 human line"));
+    assert!(output.contains("Imported 1 commit from 1 session"));
+
+    let note = repo.git(&["notes", "--ref=ai", "show", "HEAD"]).unwrap();
+    assert!(note.starts_with("code.rs\n"));
+    assert!(note.contains(" 2-4\n"));
+    assert!(note.contains("\"tool\": \"claude\""));
+    assert!(note.contains("add a helper"));
+
+    // Commits that already have a note are left alone
+    let output = repo
+        .git_ai(&[
+            "import",
+            "claude-code",
+            "--since",
+            "2025-01-01",
+            "--from",
+            &from,
+        ])
+        .unwrap();
+    assert!(output.contains("Imported 0 commits from 1 session (1 already had notes, 1 had no"));
+}
+
+#[test]
+fn test_import_ignores_edits_after_the_commit() {
+    let repo = TestRepo::new();
+    commit_at(
+        &repo,
+        "start\nend",
+        "Initial commit",
+        "2025-02-01T12:00:00Z",
+    );
+
+    commit_at(
+        &repo,
+        "start\nfn helper() {}\nend",
+        "Add helper",
+        "2025-03-01T12:00:00Z",
+    );
+    repo.git(&["update-ref", "-d", "refs/notes/ai"]).unwrap();
+
+    let history = tempfile::tempdir().unwrap();
+    let project = history.path().join("-project");
+    std::fs::create_dir(&project).unwrap();
+    std::fs::write(
+        project.join("session.jsonl"),
+        claude_code_transcript(
+            &repo.canonical_path().join("code.rs").to_string_lossy(),
+            "fn helper() {}\n",
+            "2025-03-02T09:00:00Z",
+        ),
+    )
+    .unwrap();
+
+    let output = repo
+        .git_ai(&[
+            "import",
+            "claude-code",
+            "--since",
+            "2025-01-01",
+            "--from",
+            &history.path().to_string_lossy(),
+            "--dry-run",
+        ])
+        .unwrap();
+    assert!(output.contains("Would import 0 commits from 1 session"));
+    assert!(repo.git(&["notes", "--ref=ai", "show", "HEAD"]).is_err());
+}
@@ -0,0 +1,101 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+use std::path::Path;
+use std::process::Command;
+
+/// Runs git-ai in `dir` with its own home directory, so the real config is left alone
+fn git_ai_with_home(home: &Path, dir: &Path, args: &[&str]) -> (bool, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_git-ai"))
+        .args(args)
+        .current_dir(dir)
+        .env("HOME", home)
+        .env("USERPROFILE", home)
+        .output()
+        .unwrap();
+    (
+        output.status.success(),
+        format!(
+            "{}{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        ),
+    )
+}
+
+#[test]
+fn test_init_ci_writes_config_and_notes_refspec_once() {
+    let upstream = TestRepo::new();
+    let mut lib = upstream.filename("lib.rs");
+    lib.set_contents(lines!["fn helper() {}".ai()]);
+    upstream.stage_all_and_commit("Add helper").unwrap();
+
+    let repo = TestRepo::new();
+    repo.git(&["remote", "add", "origin", upstream.path().to_str().unwrap()])
+        .unwrap();
+    let home = tempfile::tempdir().unwrap();
+
+    let (ok, output) = git_ai_with_home(home.path(), repo.path(), &["init", "--ci"]);
+    assert!(ok, "{}", output);
+    assert!(
+        output.contains("Configured notes fetching from origin"),
+        "{}",
+        output
+    );
+    let config: serde_json::Value = serde_json::from_slice(
+        &std::fs::read(home.path().join(".git-ai").join("config.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(config["disable_auto_updates"], true);
+    assert!(config["git_path"].is_string());
+
+    let (ok, output) = git_ai_with_home(home.path(), repo.path(), &["init", "--ci"]);
+    assert!(ok, "{}", output);
+    assert!(output.contains("already exists"), "{}", output);
+    let refspecs = repo
+        .git(&["config", "--get-all", "remote.origin.fetch"])
+        .unwrap();
+    assert_eq!(
+        refspecs
+            .lines()
+            .filter(|refspec| *refspec == "+refs/notes/ai:refs/notes/ai-remote/origin")
+            .count(),
+        1,
+        "{}",
+        refspecs
+    );
+
+    // A plain fetch now brings the notes down
+    repo.git(&["fetch", "origin"]).unwrap();
+    assert!(
+        repo.git(&["show-ref", "refs/notes/ai-remote/origin"])
+            .is_ok()
+    );
+}
+
+#[test]
+fn test_doctor_fails_on_a_broken_config_file() {
+    let repo = TestRepo::new();
+    let home = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(home.path().join(".git-ai")).unwrap();
+    std::fs::write(
+        home.path().join(".git-ai").join("config.json"),
+        "{ not json",
+    )
+    .unwrap();
+
+    let (ok, output) = git_ai_with_home(home.path(), repo.path(), &["doctor", "--ci"]);
+    assert!(!ok, "{}", output);
+    assert!(output.contains("FAIL  config"), "{}", output);
+    assert!(output.contains("ok    repository"), "{}", output);
+}
+
+#[test]
+fn test_init_profiles_are_exclusive() {
+    let repo = TestRepo::new();
+    let home = tempfile::tempdir().unwrap();
+    let (ok, output) = git_ai_with_home(home.path(), repo.path(), &["init", "--ci", "--minimal"]);
+    assert!(!ok);
+    assert!(output.contains("can't be used together"), "{}", output);
+}
@@ -45,6 +45,7 @@ fn test_initial_only_no_blame_data() {
         end_line: 3,
         author_id: "initial-ai-123".to_string(),
         overrode: None,
+        confidence: None,
     });
     initial_attributions.insert("newfile.txt".to_string(), line_attrs);
 
@@ -58,11 +59,14 @@ fn test_initial_only_no_blame_data() {
                 model: "test-model".to_string(),
             },
             human_author: None,
+            human_authors: Vec::new(),
             messages: vec![Message::assistant("Initial attribution".to_string(), None)],
             total_additions: 0,
             total_deletions: 0,
             accepted_lines: 0,
             overriden_lines: 0,
+            first_checkpoint_at: None,
+            last_checkpoint_at: None,
         },
     );
 
@@ -133,6 +137,7 @@ fn test_initial_wins_overlaps() {
         end_line: 2,
         author_id: "initial-override-456".to_string(),
         overrode: None,
+        confidence: None,
     });
     initial_attributions.insert("example.txt".to_string(), line_attrs);
 
@@ -146,11 +151,14 @@ fn test_initial_wins_overlaps() {
                 model: "override-model".to_string(),
             },
             human_author: None,
+            human_authors: Vec::new(),
             messages: vec![Message::assistant("Override attribution".to_string(), None)],
             total_additions: 0,
             total_deletions: 0,
             accepted_lines: 0,
             overriden_lines: 0,
+            first_checkpoint_at: None,
+            last_checkpoint_at: None,
         },
     );
 
@@ -202,12 +210,14 @@ fn test_initial_and_blame_merge() {
         end_line: 3,
         author_id: "initial-123".to_string(),
         overrode: None,
+        confidence: None,
     });
     line_attrs.push(LineAttribution {
         start_line: 5,
         end_line: 5,
         author_id: "initial-456".to_string(),
         overrode: None,
+        confidence: None,
     });
     initial_attributions.insert("example.txt".to_string(), line_attrs);
 
@@ -221,11 +231,14 @@ fn test_initial_and_blame_merge() {
                 model: "model1".to_string(),
             },
             human_author: None,
+            human_authors: Vec::new(),
             messages: vec![Message::assistant("Attribution 123".to_string(), None)],
             total_additions: 0,
             total_deletions: 0,
             accepted_lines: 0,
             overriden_lines: 0,
+            first_checkpoint_at: None,
+            last_checkpoint_at: None,
         },
     );
     prompts.insert(
@@ -237,11 +250,14 @@ fn test_initial_and_blame_merge() {
                 model: "model2".to_string(),
             },
             human_author: None,
+            human_authors: Vec::new(),
             messages: vec![Message::assistant("Attribution 456".to_string(), None)],
             total_additions: 0,
             total_deletions: 0,
             accepted_lines: 0,
             overriden_lines: 0,
+            first_checkpoint_at: None,
+            last_checkpoint_at: None,
         },
     );
 
@@ -293,6 +309,7 @@ fn test_partial_file_coverage() {
         end_line: 2,
         author_id: "initial-fileA".to_string(),
         overrode: None,
+        confidence: None,
     });
     initial_attributions.insert("fileA.txt".to_string(), line_attrs);
     // Note: fileB.txt is not in INITIAL
@@ -307,11 +324,14 @@ fn test_partial_file_coverage() {
                 model: "modelA".to_string(),
             },
             human_author: None,
+            human_authors: Vec::new(),
             messages: vec![Message::assistant("FileA attribution".to_string(), None)],
             total_additions: 0,
             total_deletions: 0,
             accepted_lines: 0,
             overriden_lines: 0,
+            first_checkpoint_at: None,
+            last_checkpoint_at: None,
         },
     );
 
@@ -379,6 +399,7 @@ fn test_initial_attributions_in_subsequent_checkpoint() {
         end_line: 2,
         author_id: "subsequent-initial-789".to_string(),
         overrode: None,
+        confidence: None,
     });
     initial_attributions.insert("fileB.txt".to_string(), line_attrs);
 
@@ -392,6 +413,7 @@ fn test_initial_attributions_in_subsequent_checkpoint() {
                 model: "subsequent-model".to_string(),
             },
             human_author: None,
+            human_authors: Vec::new(),
             messages: vec![Message::assistant(
                 "Subsequent checkpoint attribution".to_string(),
                 None,
@@ -400,6 +422,8 @@ fn test_initial_attributions_in_subsequent_checkpoint() {
             total_deletions: 0,
             accepted_lines: 0,
             overriden_lines: 0,
+            first_checkpoint_at: None,
+            last_checkpoint_at: None,
         },
     );
 
@@ -0,0 +1,126 @@
+#![cfg(unix)]
+
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+
+/// Stands in for `jj`: prints `ops` for `jj op log`, and the file named after the operation for
+/// `jj log --at-op <operation>`. Every call is appended to `calls`.
+fn fake_jj(dir: &Path) -> String {
+    let script = dir.join("jj");
+    std::fs::write(
+        &script,
+        "#!/bin/sh\ndir=$(dirname \"$0\")\necho \"$*\" >> \"$dir/calls\"\nop=\nprev=\nfor arg in \"$@\"; do\n  [ \"$prev\" = --at-op ] && op=\"$arg\"\n  prev=\"$arg\"\ndone\ncase \" $* \" in\n  *\" op log \"*) cat \"$dir/ops\" ;;\n  *) cat \"$dir/$op\" ;;\nesac\n",
+    )
+    .unwrap();
+    std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+    script.to_str().unwrap().to_string()
+}
+
+fn head(repo: &TestRepo) -> String {
+    // Debug builds log to stderr, which comes after the sha, that jj isn't installed
+    let output = repo.git(&["rev-parse", "HEAD"]).unwrap();
+    output.lines().next().unwrap().to_string()
+}
+
+#[test]
+fn test_attribution_follows_a_jj_rewrite() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("lib.rs");
+    file.set_contents(lines!["fn a() {}"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+    let base = head(&repo);
+    file.insert_at(1, lines!["fn agent() {}".ai()]);
+    repo.stage_all_and_commit("Agent edit").unwrap();
+    let original = head(&repo);
+
+    std::fs::create_dir(repo.path().join(".jj")).unwrap();
+    let jj_dir = tempfile::tempdir().unwrap();
+    let jj = fake_jj(jj_dir.path());
+    let env = [("GIT_AI_JJ", jj.as_str())];
+
+    // git-ai first sees the repository at op1
+    std::fs::write(jj_dir.path().join("ops"), "op1\n").unwrap();
+    std::fs::write(
+        jj_dir.path().join("op1"),
+        format!("pppp {} - {}\nwwww 1111 @ {}\n", original, base, original),
+    )
+    .unwrap();
+    repo.git_with_env(&["status"], &env).unwrap();
+
+    // jj rewrites the commit (here `jj describe`) without going through git's hooks
+    let output = std::process::Command::new("git")
+        .current_dir(repo.path())
+        .args(["commit", "--amend", "-m", "Described by jj"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let rewritten = head(&repo);
+    assert!(
+        repo.git(&["notes", "--ref=ai", "show", &rewritten])
+            .is_err()
+    );
+
+    std::fs::write(jj_dir.path().join("ops"), "op2\nop1\n").unwrap();
+    std::fs::write(
+        jj_dir.path().join("op2"),
+        format!("pppp {} - {}\nwwww 2222 @ {}\n", rewritten, base, rewritten),
+    )
+    .unwrap();
+    repo.git_with_env(&["status"], &env).unwrap();
+
+    assert!(repo.git(&["notes", "--ref=ai", "show", &rewritten]).is_ok());
+    file.assert_lines_and_blame(lines!["fn a() {}".human(), "fn agent() {}".ai()]);
+}
+
+/// Makes `operation` jj's only operation head, where jj keeps it in its store
+fn set_operation_head(repo: &TestRepo, operation: &str) {
+    let heads = repo.path().join(".jj/repo/op_heads/heads");
+    let _ = std::fs::remove_dir_all(&heads);
+    std::fs::create_dir_all(&heads).unwrap();
+    std::fs::write(heads.join(operation), "").unwrap();
+}
+
+fn jj_calls(dir: &Path) -> usize {
+    std::fs::read_to_string(dir.join("calls"))
+        .map(|calls| calls.lines().count())
+        .unwrap_or(0)
+}
+
+#[test]
+fn test_jj_only_runs_when_its_operation_changed() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("lib.rs");
+    file.set_contents(lines!["fn a() {}"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+    let base = head(&repo);
+
+    let jj_dir = tempfile::tempdir().unwrap();
+    let jj = fake_jj(jj_dir.path());
+    let env = [("GIT_AI_JJ", jj.as_str())];
+    set_operation_head(&repo, "op1");
+    std::fs::write(jj_dir.path().join("ops"), "op1\n").unwrap();
+    std::fs::write(jj_dir.path().join("op1"), format!("wwww 1111 @ {}\n", base)).unwrap();
+
+    repo.git_with_env(&["status"], &env).unwrap();
+    let calls = jj_calls(jj_dir.path());
+    assert!(calls > 0);
+
+    // Nothing happened in jj since, so its operation log isn't read again
+    repo.git_with_env(&["status"], &env).unwrap();
+    repo.git_with_env(&["log", "-1"], &env).unwrap();
+    assert_eq!(jj_calls(jj_dir.path()), calls);
+
+    set_operation_head(&repo, "op2");
+    std::fs::write(jj_dir.path().join("ops"), "op2\nop1\n").unwrap();
+    std::fs::write(jj_dir.path().join("op2"), format!("wwww 2222 @ {}\n", base)).unwrap();
+    repo.git_with_env(&["status"], &env).unwrap();
+    let calls_after_op2 = jj_calls(jj_dir.path());
+    assert!(calls_after_op2 > calls);
+
+    repo.git_with_env(&["status"], &env).unwrap();
+    assert_eq!(jj_calls(jj_dir.path()), calls_after_op2);
+}
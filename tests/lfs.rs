@@ -0,0 +1,108 @@
+#![cfg(unix)]
+
+#[macro_use]
+mod repos;
+use repos::test_repo::TestRepo;
+use std::os::unix::fs::PermissionsExt;
+use std::process::Command;
+
+/// Stands in for git-lfs: `clean` stores the file under .git/fake-lfs and leaves an LFS pointer
+/// in the index, `smudge` turns the pointer back into the file
+const CLEAN: &str = r#"#!/bin/sh
+tmp=$(mktemp)
+cat > "$tmp"
+oid=$(sha256sum "$tmp" | cut -d' ' -f1)
+dir=$(git rev-parse --git-common-dir)/fake-lfs
+mkdir -p "$dir"
+size=$(wc -c < "$tmp" | tr -d ' ')
+mv "$tmp" "$dir/$oid"
+printf 'version https://git-lfs.github.com/spec/v1\noid sha256:%s\nsize %s\n' "$oid" "$size"
+"#;
+
+const SMUDGE: &str = r#"#!/bin/sh
+oid=$(sed -n 's/^oid sha256://p')
+cat "$(git rev-parse --git-common-dir)/fake-lfs/$oid"
+"#;
+
+/// A repository whose `*.dat` files go through the fake LFS filter
+fn lfs_repo() -> TestRepo {
+    let repo = TestRepo::new();
+    let git_dir = repo.path().join(".git");
+    for (name, script) in [("fake-lfs-clean", CLEAN), ("fake-lfs-smudge", SMUDGE)] {
+        let path = git_dir.join(name);
+        std::fs::write(&path, script).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let key = format!("filter.lfs.{}", name.trim_start_matches("fake-lfs-"));
+        repo.git(&["config", &key, path.to_str().unwrap()]).unwrap();
+    }
+    std::fs::write(
+        repo.path().join(".gitattributes"),
+        "*.dat filter=lfs -text\n",
+    )
+    .unwrap();
+    std::fs::write(repo.path().join("README.md"), "# Project\n").unwrap();
+    repo.stage_all_and_commit("Initial commit").unwrap();
+    repo
+}
+
+fn git_ai(repo: &TestRepo, args: &[&str], mode: &str) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_git-ai"))
+        .args(args)
+        .current_dir(repo.path())
+        .env("GIT_AI_LFS_FILES", mode)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+fn commit(repo: &TestRepo, message: &str, mode: &str) {
+    repo.git_with_env(&["add", "-A"], &[("GIT_AI_LFS_FILES", mode)])
+        .unwrap();
+    repo.git_with_env(&["commit", "-m", message], &[("GIT_AI_LFS_FILES", mode)])
+        .unwrap();
+}
+
+#[test]
+fn test_lfs_files_are_skipped_and_listed_in_the_note() {
+    let repo = lfs_repo();
+    repo.write_lines("model.dat", 5);
+    repo.write_lines("lib.rs", 2);
+    git_ai(&repo, &["checkpoint", "mock_ai"], "skip");
+    commit(&repo, "Add model and lib", "skip");
+
+    assert_eq!(
+        repo.head_additions_with_env(&[("GIT_AI_LFS_FILES", "skip")]),
+        (2, 0, 0)
+    );
+    let note = repo.git(&["notes", "--ref=ai", "show", "HEAD"]).unwrap();
+    assert!(note.contains("\"skipped_files\""), "{}", note);
+    assert!(note.contains("\"model.dat\": \"lfs\""), "{}", note);
+    // Nothing is attested for it
+    let attestations = note.split("---").next().unwrap();
+    assert!(!attestations.contains("model.dat"), "{}", note);
+}
+
+#[test]
+fn test_smudged_lfs_files_are_attributed_on_their_content() {
+    let repo = lfs_repo();
+    repo.write_lines("model.dat", 5);
+    commit(&repo, "Add model", "smudge");
+
+    // Compared with the pointer in HEAD, every line would look new
+    repo.write_lines("model.dat", 7);
+    git_ai(&repo, &["checkpoint", "mock_ai"], "smudge");
+    commit(&repo, "Extend model", "smudge");
+
+    assert_eq!(
+        repo.head_additions_with_env(&[("GIT_AI_LFS_FILES", "smudge")]),
+        (2, 0, 0)
+    );
+    let note = repo.git(&["notes", "--ref=ai", "show", "HEAD"]).unwrap();
+    assert!(note.contains("model.dat"), "{}", note);
+    assert!(!note.contains("skipped_files"), "{}", note);
+}
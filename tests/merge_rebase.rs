@@ -258,3 +258,90 @@ fn test_blame_after_merge_conflict_resolution() {
         "Line 10".human(),
     ]);
 }
+
+/// Test that aborting a conflicted merge drops the AI's attempt at resolving it
+#[test]
+fn test_merge_abort_restores_working_log() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("test.txt");
+
+    file.set_contents(lines!["Line 1", "Line 2"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+    let default_branch = repo.current_branch();
+
+    repo.git(&["checkout", "-b", "feature"]).unwrap();
+    file.replace_at(1, "FEATURE VERSION".human());
+    repo.stage_all_and_commit("feature changes").unwrap();
+
+    repo.git(&["checkout", &default_branch]).unwrap();
+    file.replace_at(1, "MAIN VERSION".human());
+    repo.stage_all_and_commit("main changes").unwrap();
+    let rewrite_log_before = repo.rewrite_log();
+
+    assert!(
+        repo.git(&["merge", "feature", "-m", "merge feature"])
+            .is_err()
+    );
+
+    // AI resolves the conflict, then the merge is abandoned
+    file.set_contents(lines!["Line 1", "AI RESOLUTION".ai()]);
+    repo.git(&["merge", "--abort"]).unwrap();
+
+    assert!(
+        repo.current_working_logs()
+            .read_all_checkpoints()
+            .unwrap()
+            .is_empty()
+    );
+    assert_eq!(repo.rewrite_log(), rewrite_log_before);
+
+    file.assert_lines_and_blame(lines!["Line 1".human(), "MAIN VERSION".human()]);
+}
+
+/// Test that a conflict resolution rerere replays keeps the attribution it was recorded with
+#[test]
+fn test_merge_rerere_replayed_resolution_keeps_ai_attribution() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "rerere.enabled", "true"]).unwrap();
+    let mut file = repo.filename("test.txt");
+
+    file.set_contents(lines!["Line 1", "Line 2", "Line 3"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+    let default_branch = repo.current_branch();
+
+    repo.git(&["checkout", "-b", "feature"]).unwrap();
+    file.replace_at(1, "FEATURE VERSION".human());
+    repo.stage_all_and_commit("feature changes").unwrap();
+
+    repo.git(&["checkout", &default_branch]).unwrap();
+    file.replace_at(1, "MAIN VERSION".human());
+    repo.stage_all_and_commit("main changes").unwrap();
+    let main_head = repo.git(&["rev-parse", "HEAD"]).unwrap().trim().to_string();
+
+    // AI resolves the conflict, and rerere records the resolution
+    assert!(repo.git(&["merge", "feature", "-m", "merge"]).is_err());
+    file.set_contents(lines!["Line 1", "AI RESOLUTION".ai(), "Line 3"]);
+    repo.commit("merge feature").unwrap();
+    file.assert_lines_and_blame(lines![
+        "Line 1".human(),
+        "AI RESOLUTION".ai(),
+        "Line 3".human()
+    ]);
+
+    // Redo the merge. rerere resolves the conflict the same way, without the AI
+    repo.git(&["reset", "--hard", &main_head]).unwrap();
+    assert!(repo.git(&["merge", "feature", "-m", "merge"]).is_err());
+    assert_eq!(
+        repo.read_file("test.txt").unwrap(),
+        "Line 1\nAI RESOLUTION\nLine 3"
+    );
+    repo.git(&["add", "test.txt"]).unwrap();
+    repo.commit("merge feature again").unwrap();
+
+    let mut file = repo.filename("test.txt");
+    file.assert_lines_and_blame(lines![
+        "Line 1".human(),
+        "AI RESOLUTION".ai(),
+        "Line 3".human()
+    ]);
+}
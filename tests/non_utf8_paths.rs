@@ -0,0 +1,87 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+#[test]
+fn test_non_ascii_file_name_is_attributed() {
+    let repo = TestRepo::new();
+    let mut readme = repo.filename("README.md");
+    readme.set_contents(lines!["# Project"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    // git quotes this name as "caf\303\251.rs" in diffs unless core.quotepath is off
+    let mut file = repo.filename("café.rs");
+    file.set_contents(lines!["fn a() {}".ai(), "fn b() {}".ai()]);
+    let commit = repo.stage_all_and_commit("Add café").unwrap();
+
+    assert_eq!(commit.authorship_log.attestations.len(), 1);
+    assert_eq!(commit.authorship_log.attestations[0].file_path, "café.rs");
+    file.assert_lines_and_blame(lines!["fn a() {}".ai(), "fn b() {}".ai()]);
+
+    repo.git(&["config", "core.quotepath", "false"]).unwrap();
+    file.insert_at(2, lines!["fn c() {}".ai()]);
+    repo.stage_all_and_commit("Extend café").unwrap();
+    file.assert_lines_and_blame(lines!["fn a() {}".ai(), "fn b() {}".ai(), "fn c() {}".ai()]);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_latin1_file_name_is_attributed() {
+    use std::os::unix::ffi::OsStrExt;
+
+    let repo = TestRepo::new();
+    let mut readme = repo.filename("README.md");
+    readme.set_contents(lines!["# Project"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    // "latín.rs" in latin-1, which isn't valid UTF-8
+    let name = std::ffi::OsStr::from_bytes(b"lat\xedn.rs");
+    std::fs::write(repo.path().join(name), "fn a() {}\nfn b() {}\n").unwrap();
+    repo.git_ai(&["checkpoint", "mock_ai"]).unwrap();
+    let commit = repo.stage_all_and_commit("Add latin-1 file").unwrap();
+
+    assert_eq!(commit.authorship_log.attestations.len(), 1);
+    assert_eq!(
+        commit.authorship_log.attestations[0].file_path,
+        "\"lat\\355n.rs\""
+    );
+
+    // Reloaded from the note, the key is unchanged
+    let note = repo.git(&["notes", "--ref=ai", "show", "HEAD"]).unwrap();
+    assert!(note.starts_with("\"lat\\355n.rs\"\n"), "{}", note);
+    let stats = repo.git_ai(&["stats", "--json"]).unwrap();
+    let json = stats.lines().find(|line| line.starts_with('{')).unwrap();
+    let stats: serde_json::Value = serde_json::from_str(json).unwrap();
+    assert_eq!(stats["ai_additions"], 2, "{}", json);
+}
+
+#[test]
+fn test_latin1_file_content_is_attributed() {
+    let repo = TestRepo::new();
+    let path = repo.path().join("legacy.c");
+    std::fs::write(&path, b"/* caf\xe9 */\nint a;\n").unwrap();
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    // AI adds latin-1 lines next to the human ones
+    std::fs::write(&path, b"/* caf\xe9 */\nint a;\n/* na\xefve */\nint b;\n").unwrap();
+    repo.git_ai(&["checkpoint", "mock_ai"]).unwrap();
+    let commit = repo.stage_all_and_commit("AI edit").unwrap();
+    assert_eq!(commit.authorship_log.attestations.len(), 1);
+
+    // A later human edit elsewhere leaves those lines attributed to the AI
+    std::fs::write(
+        &path,
+        b"/* caf\xe9 */\nint a;\n/* na\xefve */\nint b;\nint c;\n",
+    )
+    .unwrap();
+    repo.git_ai(&["checkpoint"]).unwrap();
+    repo.stage_all_and_commit("Human edit").unwrap();
+
+    let blame = repo.git_ai(&["blame", "legacy.c"]).unwrap();
+    let lines: Vec<&str> = blame.lines().collect();
+    assert_eq!(lines.len(), 5, "{}", blame);
+    for (line, ai) in lines.iter().zip([false, false, true, true, false]) {
+        assert_eq!(line.contains("mock_ai"), ai, "{}", blame);
+    }
+}
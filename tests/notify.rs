@@ -0,0 +1,66 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+#[test]
+fn test_notify_dry_run_renders_report() {
+    let repo = TestRepo::new();
+    let mut code = repo.filename("code.txt");
+    code.set_contents(lines!["human 1", "human 2"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+    code.insert_at(2, lines!["AI 1".ai(), "AI 2".ai()]);
+    repo.stage_all_and_commit("Add lines").unwrap();
+
+    let output = repo.git_ai(&["notify", "slack", "--dry-run"]).unwrap();
+    let message: serde_json::Value = serde_json::from_str(&output).unwrap();
+    let fields = &message["blocks"][1]["fields"];
+    // Inserting after the last line rewrites it to add its newline, so 5 lines are added
+    assert_eq!(fields[0]["text"], "*AI share*\n40%");
+    assert_eq!(fields[1]["text"], "*Commits*\n2");
+    assert_eq!(fields[2]["text"], "*Lines added*\n5 (2 by AI)");
+    assert_eq!(
+        message["blocks"][3]["text"]["text"],
+        "*By agent*\n• mock_ai::unknown: 2 lines"
+    );
+
+    let output = repo
+        .git_ai(&["notify", "teams", "--dry-run", "--days", "1"])
+        .unwrap();
+    let message: serde_json::Value = serde_json::from_str(&output).unwrap();
+    let card = &message["attachments"][0]["content"];
+    assert_eq!(
+        card["body"][0]["text"],
+        format!(
+            "AI authorship in {}, last 1 day",
+            repo.canonical_path().file_name().unwrap().to_string_lossy()
+        )
+    );
+    assert_eq!(card["body"][1]["facts"][0]["value"], "40%");
+}
+
+#[test]
+fn test_notify_requires_webhook() {
+    let repo = TestRepo::new();
+    repo.filename("code.txt").set_contents(lines!["start"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+    let err = repo.git_ai(&["notify", "slack"]).unwrap_err();
+    assert!(err.contains("--webhook"), "{}", err);
+}
+
+#[test]
+fn test_notify_installs_scheduled_workflow() {
+    let repo = TestRepo::new();
+    repo.git_ai(&["notify", "teams", "--install-workflow"])
+        .unwrap();
+    let workflow = std::fs::read_to_string(
+        repo.path()
+            .join(".github")
+            .join("workflows")
+            .join("git-ai-report.yaml"),
+    )
+    .unwrap();
+    assert!(workflow.contains("cron:"));
+    assert!(workflow.contains("run: git-ai notify teams"));
+    assert!(workflow.contains("secrets.GIT_AI_NOTIFY_WEBHOOK"));
+}
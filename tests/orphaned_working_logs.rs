@@ -0,0 +1,83 @@
+#[macro_use]
+mod repos;
+use repos::test_repo::TestRepo;
+use std::fs;
+use std::process::Command;
+
+/// Two branches off a shared commit, each adding its own file
+fn repo_with_sibling_branches() -> TestRepo {
+    let repo = TestRepo::new();
+    fs::write(repo.path().join("README.md"), "readme\n").unwrap();
+    repo.stage_all_and_commit("Initial").unwrap();
+    let main = repo.current_branch();
+
+    repo.git(&["checkout", "-b", "branch-a"]).unwrap();
+    fs::write(repo.path().join("a.txt"), "a\n").unwrap();
+    repo.stage_all_and_commit("Add a").unwrap();
+
+    repo.git(&["checkout", &main]).unwrap();
+    repo.git(&["checkout", "-b", "branch-b"]).unwrap();
+    fs::write(repo.path().join("b.txt"), "b\n").unwrap();
+    repo.stage_all_and_commit("Add b").unwrap();
+
+    repo.git(&["checkout", "branch-a"]).unwrap();
+    repo
+}
+
+#[test]
+fn test_switching_back_and_forth_keeps_both_working_logs() {
+    let repo = repo_with_sibling_branches();
+    let a_head = repo.git(&["rev-parse", "HEAD"]).unwrap().trim().to_string();
+
+    fs::write(repo.path().join("README.md"), "readme\nai on a\n").unwrap();
+    repo.git_ai(&["checkpoint", "mock_ai", "README.md"])
+        .unwrap();
+
+    // The uncommitted change comes along to branch-b, where the agent keeps going
+    repo.git(&["checkout", "branch-b"]).unwrap();
+    let b_head = repo.git(&["rev-parse", "HEAD"]).unwrap().trim().to_string();
+    fs::write(repo.path().join("README.md"), "readme\nai on a\nai on b\n").unwrap();
+    repo.git_ai(&["checkpoint", "mock_ai", "README.md"])
+        .unwrap();
+
+    repo.git(&["checkout", "branch-a"]).unwrap();
+    fs::write(
+        repo.path().join("README.md"),
+        "readme\nai on a\nai on b\nai on a again\n",
+    )
+    .unwrap();
+    repo.git_ai(&["checkpoint", "mock_ai", "README.md"])
+        .unwrap();
+
+    let storage = git_ai::git::repository::find_repository_in_path(repo.path().to_str().unwrap())
+        .unwrap()
+        .storage;
+    assert!(storage.working_log_for_base_commit(&a_head).has_data());
+    assert!(storage.working_log_for_base_commit(&b_head).has_data());
+}
+
+#[test]
+fn test_commit_that_bypassed_hooks_carries_working_log() {
+    let repo = TestRepo::new();
+    fs::write(repo.path().join("a.txt"), "a\n").unwrap();
+    fs::write(repo.path().join("b.txt"), "b\n").unwrap();
+    repo.stage_all_and_commit("Initial").unwrap();
+
+    fs::write(repo.path().join("a.txt"), "a\nai line\n").unwrap();
+    repo.git_ai(&["checkpoint", "mock_ai", "a.txt"]).unwrap();
+
+    // An editor's own git commits another file behind git-ai's back
+    fs::write(repo.path().join("b.txt"), "b\nmore\n").unwrap();
+    let status = Command::new("git")
+        .current_dir(repo.path())
+        .args(["commit", "-q", "-m", "Outside git-ai", "b.txt"])
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    fs::write(repo.path().join("b.txt"), "b\nmore\nagain\n").unwrap();
+    repo.git_ai(&["checkpoint", "mock_ai", "b.txt"]).unwrap();
+
+    let initial = repo.current_working_logs().read_initial_attributions();
+    assert!(initial.files.contains_key("a.txt"), "{:?}", initial.files);
+}
@@ -0,0 +1,98 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+fn own_json(repo: &TestRepo) -> serde_json::Value {
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_git-ai"))
+        .args(["own", "--json"])
+        .current_dir(repo.path())
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+fn owner<'a>(report: &'a serde_json::Value, name: &str) -> &'a serde_json::Value {
+    report["owners"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|owner| owner["owner"] == name)
+        .unwrap()
+}
+
+#[test]
+fn test_own_reports_ai_lines_per_owner() {
+    let repo = TestRepo::new();
+    for dir in [".github", "payments", "web"] {
+        std::fs::create_dir_all(repo.path().join(dir)).unwrap();
+    }
+    repo.filename(".github/CODEOWNERS").set_contents(lines![
+        "/payments/ @acme/payments",
+        "/web/ test@example.com",
+    ]);
+    let mut payments = repo.filename("payments/charge.rs");
+    payments.set_contents(lines!["fn charge() {}"]);
+    let mut web = repo.filename("web/app.ts");
+    web.set_contents(lines!["export {}"]);
+    repo.filename("README.md").set_contents(lines!["# Demo"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    payments.insert_at(1, lines!["fn refund() {}".ai(), "fn retry() {}".ai()]);
+    web.insert_at(1, lines!["export const x = 1;".ai()]);
+    repo.stage_all_and_commit("Add AI code").unwrap();
+
+    let report = own_json(&repo);
+    let payments_team = owner(&report, "@acme/payments");
+    assert_eq!(payments_team["files"], 1);
+    assert_eq!(payments_team["lines"], 3);
+    assert_eq!(payments_team["ai_lines"], 2);
+    // The session was driven by test@example.com, who isn't on the payments team
+    assert_eq!(payments_team["outside_ai_lines"], 2);
+    assert_eq!(payments_team["areas"][0]["pattern"], "/payments/");
+
+    let web_owner = owner(&report, "test@example.com");
+    assert_eq!(web_owner["ai_lines"], 1);
+    assert_eq!(web_owner["outside_ai_lines"], 0);
+
+    // CODEOWNERS and the README have no owner
+    assert_eq!(report["unowned"]["files"], 2);
+}
+
+#[test]
+fn test_own_uses_team_members_from_repo_config() {
+    let repo = TestRepo::new();
+    repo.filename(".gitai").set_contents(lines![
+        "[ai]",
+        "    teamMember = @acme/payments: test@example.com",
+    ]);
+    repo.filename("CODEOWNERS")
+        .set_contents(lines!["* @acme/payments"]);
+    let mut payments = repo.filename("charge.rs");
+    payments.set_contents(lines!["fn charge() {}"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    payments.insert_at(1, lines!["fn refund() {}".ai()]);
+    repo.stage_all_and_commit("Add AI code").unwrap();
+
+    let report = own_json(&repo);
+    let payments_team = owner(&report, "@acme/payments");
+    assert_eq!(payments_team["ai_lines"], 1);
+    assert_eq!(payments_team["outside_ai_lines"], 0);
+}
+
+#[test]
+fn test_own_without_codeowners_fails() {
+    let repo = TestRepo::new();
+    repo.filename("code.rs")
+        .set_contents(lines!["fn main() {}"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    let err = repo.git_ai(&["own"]).unwrap_err();
+    assert!(err.contains("No CODEOWNERS file"), "{}", err);
+}
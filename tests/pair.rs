@@ -0,0 +1,76 @@
+#[macro_use]
+mod repos;
+use git_ai::authorship::authorship_log::{Participant, ParticipantRole};
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+#[test]
+fn test_pairing_participants_are_recorded_on_agent_sessions() {
+    let repo = TestRepo::new();
+    let mut readme = repo.filename("README.md");
+    readme.set_contents(lines!["# Project"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    assert!(repo.git_ai(&["pair"]).unwrap().contains("Not pairing"));
+    repo.git_ai(&[
+        "pair",
+        "start",
+        "--driver",
+        "Jane <jane@example.com>",
+        "--navigator",
+        "Joe <joe@example.com>",
+    ])
+    .unwrap();
+    let shown = repo.git_ai(&["pair", "show"]).unwrap();
+    assert!(
+        shown.contains("navigator  Joe <joe@example.com>"),
+        "{}",
+        shown
+    );
+
+    let mut lib = repo.filename("lib.rs");
+    lib.set_contents(lines!["fn helper() {}".ai()]);
+    let commit = repo.stage_all_and_commit("Add helper").unwrap();
+    let prompt = commit
+        .authorship_log
+        .metadata
+        .prompts
+        .values()
+        .next()
+        .unwrap();
+    assert_eq!(
+        prompt.human_authors,
+        vec![
+            Participant {
+                author: "Jane <jane@example.com>".to_string(),
+                role: ParticipantRole::Driver,
+            },
+            Participant {
+                author: "Joe <joe@example.com>".to_string(),
+                role: ParticipantRole::Navigator,
+            },
+        ]
+    );
+
+    // Sessions after pairing stops record the committer alone
+    repo.git_ai(&["pair", "stop"]).unwrap();
+    lib.set_contents(lines!["fn helper() {}".ai(), "fn other() {}".ai()]);
+    let commit = repo.stage_all_and_commit("Add other").unwrap();
+    assert!(
+        commit
+            .authorship_log
+            .metadata
+            .prompts
+            .values()
+            .all(|prompt| prompt.human_authors.is_empty())
+    );
+}
+
+#[test]
+fn test_pair_start_needs_a_navigator() {
+    let repo = TestRepo::new();
+    let err = repo
+        .git_ai(&["pair", "start", "--driver", "Jane <jane@example.com>"])
+        .unwrap_err();
+    assert!(err.contains("--navigator"), "{}", err);
+}
@@ -0,0 +1,77 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+fn write_lib(repo: &TestRepo, content: &str) {
+    std::fs::write(repo.path().join("lib.rs"), content).unwrap();
+}
+
+#[test]
+fn test_paired_checkpoints_attribute_exactly_the_agents_edit() {
+    let repo = TestRepo::new_with_file("lib.rs", &["fn a() {}"]);
+
+    // The human types, then the agent's tool starts
+    write_lib(&repo, "fn a() {}\nfn human() {}\n");
+    repo.git_ai(&[
+        "checkpoint",
+        "begin-agent",
+        "mock_ai",
+        "lib.rs",
+        "--session",
+        "s1",
+    ])
+    .unwrap();
+
+    // Another hook checkpoints while the agent is halfway through its edit
+    write_lib(&repo, "fn a() {}\nfn human() {}\nfn agent() {}\n");
+    repo.git_ai(&["checkpoint"]).unwrap();
+    write_lib(
+        &repo,
+        "fn a() {}\nfn human() {}\nfn agent() {}\nfn helper() {}\n",
+    );
+    repo.git_ai(&[
+        "checkpoint",
+        "end-agent",
+        "mock_ai",
+        "lib.rs",
+        "--session",
+        "s1",
+    ])
+    .unwrap();
+
+    repo.stage_all_and_commit("Agent's edit").unwrap();
+    let mut lib = repo.filename("lib.rs");
+    lib.assert_lines_and_blame(lines![
+        "fn a() {}".human(),
+        "fn human() {}".human(),
+        "fn agent() {}".ai(),
+        "fn helper() {}".ai(),
+    ]);
+}
+
+#[test]
+fn test_end_agent_without_begin_attributes_since_last_checkpoint() {
+    let repo = TestRepo::new_with_file("lib.rs", &["fn a() {}"]);
+
+    write_lib(&repo, "fn a() {}\nfn human() {}\n");
+    repo.git_ai(&["checkpoint"]).unwrap();
+    write_lib(&repo, "fn a() {}\nfn human() {}\nfn agent() {}\n");
+    repo.git_ai(&[
+        "checkpoint",
+        "end-agent",
+        "mock_ai",
+        "lib.rs",
+        "--session",
+        "s2",
+    ])
+    .unwrap();
+
+    repo.stage_all_and_commit("Agent's edit").unwrap();
+    let mut lib = repo.filename("lib.rs");
+    lib.assert_lines_and_blame(lines![
+        "fn a() {}".human(),
+        "fn human() {}".human(),
+        "fn agent() {}".ai(),
+    ]);
+}
@@ -0,0 +1,254 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+/// Test that only the staged hunk is committed when the unstaged hunk repeats its lines
+#[test]
+fn test_partial_staging_with_repeated_lines() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("code.rs");
+
+    file.set_contents(lines!["// header", "// footer"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    // AI adds two functions whose closing braces are the same line
+    file.insert_at(
+        1,
+        lines![
+            "fn first() {".ai(),
+            "}".ai(),
+            "fn second() {".ai(),
+            "}".ai()
+        ],
+    );
+
+    // Stage only the first function
+    repo.add_edit("code.rs", "sed -i -e '/^+fn second/,+1d'");
+    repo.commit("Add first").unwrap();
+
+    let stats = repo.stats().unwrap();
+    assert_eq!(stats.ai_additions, 2);
+    assert_eq!(stats.human_additions, 0);
+
+    // The second function is carried over and committed next
+    repo.stage_all_and_commit("Add second").unwrap();
+    let stats = repo.stats().unwrap();
+    assert_eq!(stats.ai_additions, 2);
+    assert_eq!(stats.human_additions, 0);
+
+    file.assert_lines_and_blame(lines![
+        "// header".human(),
+        "fn first() {".ai(),
+        "}".ai(),
+        "fn second() {".ai(),
+        "}".ai(),
+        "// footer".human()
+    ]);
+}
+
+/// Test that staging the middle of an AI block commits the staged lines and leaves the rest
+#[test]
+fn test_partial_staging_skips_unstaged_hunks_around_staged_one() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("notes.txt");
+
+    file.set_contents(lines!["a", "b", "c"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    file.insert_at(3, lines!["AI after c".ai()]);
+    file.insert_at(2, lines!["AI after b".ai()]);
+    file.insert_at(1, lines!["AI after a".ai()]);
+
+    // Stage only the line after b
+    repo.add_edit("notes.txt", "sed -i -e '/^+AI after [ac]/d'");
+    repo.commit("Add line after b").unwrap();
+
+    let stats = repo.stats().unwrap();
+    assert_eq!(stats.ai_additions, 1);
+    assert_eq!(stats.git_diff_added_lines, 1);
+
+    repo.stage_all_and_commit("Add the rest").unwrap();
+    file.assert_lines_and_blame(lines![
+        "a".human(),
+        "AI after a".ai(),
+        "b".human(),
+        "AI after b".ai(),
+        "c".human(),
+        "AI after c".ai()
+    ]);
+}
+
+/// Test that a line edited again after it was staged is attributed to who wrote the staged version
+#[test]
+fn test_partial_staging_then_editing_staged_line() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("code.txt");
+
+    file.set_contents(lines!["// Initial line", "// Last line"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    file.insert_at(
+        1,
+        lines![
+            "// AI line 1".ai(),
+            "// AI line 2".ai(),
+            "// AI line 3".ai()
+        ],
+    );
+
+    // Stage all of the AI lines through the patch editor
+    repo.add_edit("code.txt", "true");
+
+    // A human rewrites a staged line before committing
+    file.replace_at(2, "// Human rewrite".human());
+    repo.commit("Add AI lines").unwrap();
+
+    let stats = repo.stats().unwrap();
+    assert_eq!(stats.ai_additions, 3);
+    assert_eq!(stats.human_additions, 0);
+
+    // The rewrite is committed next, as the human's
+    repo.stage_all_and_commit("Rewrite line").unwrap();
+    file.assert_lines_and_blame(lines![
+        "// Initial line".human(),
+        "// AI line 1".ai(),
+        "// Human rewrite".human(),
+        "// AI line 3".ai(),
+        "// Last line".human()
+    ]);
+}
+
+/// Test that `git commit -- <path>` commits that path and leaves the other staged file for later
+#[test]
+fn test_commit_only_paths_leaves_other_staged_files() {
+    let repo = TestRepo::new();
+    let mut first = repo.filename("first.txt");
+    let mut second = repo.filename("second.txt");
+
+    first.set_contents(lines!["first", "end"]);
+    second.set_contents(lines!["second", "end"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    first.insert_at(1, lines!["AI in first".ai()]);
+    second.insert_at(1, lines!["AI in second 1".ai(), "AI in second 2".ai()]);
+    repo.git(&["add", "-A"]).unwrap();
+
+    repo.git(&["commit", "-m", "Commit first", "--", "first.txt"])
+        .unwrap();
+    let stats = repo.stats().unwrap();
+    assert_eq!(stats.ai_additions, 1);
+    assert_eq!(stats.git_diff_added_lines, 1);
+
+    // second.txt is still staged, and its attribution was carried over
+    repo.commit("Commit second").unwrap();
+    let stats = repo.stats().unwrap();
+    assert_eq!(stats.ai_additions, 2);
+
+    first.assert_lines_and_blame(lines!["first".human(), "AI in first".ai(), "end".human()]);
+    second.assert_lines_and_blame(lines![
+        "second".human(),
+        "AI in second 1".ai(),
+        "AI in second 2".ai(),
+        "end".human()
+    ]);
+}
+
+/// Test that `git commit --only <path>` commits the working copy of a partially staged file
+#[test]
+fn test_commit_only_path_commits_working_copy_of_partially_staged_file() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("notes.txt");
+
+    file.set_contents(lines!["a", "b", "c"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    file.insert_at(2, lines!["AI after b".ai()]);
+    file.insert_at(1, lines!["AI after a".ai()]);
+    repo.add_edit("notes.txt", "sed -i -e '/^+AI after b/d'");
+
+    repo.git(&["commit", "-m", "Commit file", "--only", "notes.txt"])
+        .unwrap();
+    let stats = repo.stats().unwrap();
+    assert_eq!(stats.ai_additions, 2);
+    assert_eq!(stats.human_additions, 0);
+
+    file.assert_lines_and_blame(lines![
+        "a".human(),
+        "AI after a".ai(),
+        "b".human(),
+        "AI after b".ai(),
+        "c".human()
+    ]);
+}
+
+/// Test that `git commit -p` commits the picked hunks and carries the rest over
+#[test]
+fn test_commit_patch_picks_hunks() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("code.txt");
+
+    file.set_contents(lines![
+        "line 1", "line 2", "line 3", "line 4", "line 5", "line 6", "line 7", "line 8", "line 9",
+        "line 10"
+    ]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    // Two hunks, far enough apart not to share context
+    file.insert_at(9, lines!["AI near the end".ai()]);
+    file.insert_at(1, lines!["AI near the start".ai()]);
+
+    // Commit the first hunk, skip the second
+    repo.git_with_stdin(&["commit", "-p", "-m", "Pick first hunk"], "y\nn\n")
+        .unwrap();
+    let stats = repo.stats().unwrap();
+    assert_eq!(stats.ai_additions, 1);
+    assert_eq!(stats.git_diff_added_lines, 1);
+
+    repo.stage_all_and_commit("Commit the rest").unwrap();
+    let stats = repo.stats().unwrap();
+    assert_eq!(stats.ai_additions, 1);
+
+    file.assert_lines_and_blame(lines![
+        "line 1".human(),
+        "AI near the start".ai(),
+        "line 2".human(),
+        "line 3".human(),
+        "line 4".human(),
+        "line 5".human(),
+        "line 6".human(),
+        "line 7".human(),
+        "line 8".human(),
+        "line 9".human(),
+        "AI near the end".ai(),
+        "line 10".human()
+    ]);
+}
+
+/// Test that a file staged with `git add -p` keeps its staged attribution through a commit of
+/// other paths
+#[test]
+fn test_staged_attribution_survives_commit_of_other_paths() {
+    let repo = TestRepo::new();
+    let mut staged = repo.filename("staged.txt");
+    let mut other = repo.filename("other.txt");
+
+    staged.set_contents(lines!["start", "end"]);
+    other.set_contents(lines!["other"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    // A human line is staged, then AI rewrites it in the working copy
+    staged.insert_at(1, lines!["human line".human()]);
+    repo.add_edit("staged.txt", "true");
+    staged.replace_at(1, "AI rewrite".ai());
+
+    other.insert_at(1, lines!["AI in other".ai()]);
+    repo.git(&["commit", "-m", "Commit other", "--", "other.txt"])
+        .unwrap();
+
+    // The staged version of staged.txt is committed as the human's
+    repo.commit("Commit staged").unwrap();
+    let stats = repo.stats().unwrap();
+    assert_eq!(stats.ai_additions, 0);
+    assert_eq!(stats.human_additions, 1);
+}
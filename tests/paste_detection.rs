@@ -0,0 +1,54 @@
+#[macro_use]
+mod repos;
+use repos::test_repo::TestRepo;
+use std::process::Command;
+
+const PASTE_DETECTION: &[(&str, &str)] = &[("GIT_AI_PASTE_DETECTION", "1")];
+
+fn status_with_paste_detection(repo: &TestRepo, args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_git-ai"))
+        .arg("status")
+        .args(args)
+        .current_dir(repo.path())
+        .env("GIT_AI_AGENT_PROCESSES", "")
+        .envs(PASTE_DETECTION.iter().copied())
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+#[test]
+fn test_large_paste_is_flagged_as_possible_ai() {
+    let repo = TestRepo::new_with_file("README.md", &["# Project"]);
+    repo.write_lines("pasted.rs", 40);
+    repo.git(&["add", "-A"]).unwrap();
+
+    let status = status_with_paste_detection(&repo, &[]);
+    assert!(status.contains("40 possibly pasted from AI"), "{}", status);
+    let json = status_with_paste_detection(&repo, &["--json"]);
+    let json: serde_json::Value = serde_json::from_str(json.trim()).unwrap();
+    assert_eq!(json["files"]["pasted.rs"]["possible_ai_lines"], 40);
+
+    repo.git_with_env(&["commit", "-m", "Add pasted code"], PASTE_DETECTION)
+        .unwrap();
+    assert_eq!(repo.head_additions(), (0, 40, 40));
+}
+
+#[test]
+fn test_small_edits_and_detection_off_stay_human() {
+    let repo = TestRepo::new_with_file("README.md", &["# Project"]);
+    repo.write_lines("small.rs", 5);
+    repo.git(&["add", "-A"]).unwrap();
+    repo.git_with_env(&["commit", "-m", "Add small file"], PASTE_DETECTION)
+        .unwrap();
+    assert_eq!(repo.head_additions(), (0, 5, 0));
+
+    repo.write_lines("large.rs", 40);
+    repo.stage_all_and_commit("Add large file").unwrap();
+    assert_eq!(repo.head_additions(), (0, 40, 0));
+}
@@ -0,0 +1,62 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+use std::fs;
+
+/// Commits an AI line in services/payments and another in services/billing
+fn commit_ai_lines_in_two_services(repo: &TestRepo) {
+    for service in ["payments", "billing"] {
+        fs::create_dir_all(repo.path().join("services").join(service)).unwrap();
+        let mut file = repo.filename(&format!("services/{}/app.txt", service));
+        file.set_contents(lines!["human line"]);
+    }
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    for service in ["payments", "billing"] {
+        let mut file = repo.filename(&format!("services/{}/app.txt", service));
+        file.set_contents(lines!["human line", "ai line".ai()]);
+    }
+    repo.stage_all_and_commit("AI edits").unwrap();
+}
+
+#[test]
+fn test_default_pathspec_limits_checkpoints_and_stats() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "ai.defaultPathspec", "services/payments"])
+        .unwrap();
+
+    commit_ai_lines_in_two_services(&repo);
+
+    let note = repo.git(&["notes", "--ref=ai", "show", "HEAD"]).unwrap();
+    assert!(note.contains("services/payments/app.txt"), "{}", note);
+    assert!(!note.contains("services/billing/app.txt"), "{}", note);
+
+    let stats = repo.stats().unwrap();
+    assert_eq!(stats.ai_additions, 1);
+    // The rewritten last line of payments/app.txt plus the new one
+    assert_eq!(stats.git_diff_added_lines, 2);
+}
+
+#[test]
+fn test_path_option_scopes_a_single_command() {
+    let repo = TestRepo::new();
+    commit_ai_lines_in_two_services(&repo);
+
+    let unscoped = repo.stats().unwrap();
+    assert_eq!(unscoped.ai_additions, 2);
+    assert_eq!(unscoped.git_diff_added_lines, 4);
+
+    let output = repo
+        .git_ai(&["--path", "services/billing", "stats", "--json"])
+        .unwrap();
+    let stats: serde_json::Value =
+        serde_json::from_str(output.lines().find(|l| l.starts_with('{')).unwrap()).unwrap();
+    assert_eq!(stats["ai_additions"], 1);
+    assert_eq!(stats["git_diff_added_lines"], 2);
+
+    let err = repo
+        .git_ai(&["--path", "services/[oops", "stats"])
+        .unwrap_err();
+    assert!(err.contains("invalid pathspec"), "{}", err);
+}
@@ -0,0 +1,69 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+/// Installs a prepare-commit-msg hook that runs `git-ai prepare-commit-msg`
+fn install_hook(repo: &TestRepo) {
+    let hook = repo.path().join(".git/hooks/prepare-commit-msg");
+    std::fs::write(
+        &hook,
+        format!(
+            "#!/bin/sh\nenv -u GIT_AI '{}' prepare-commit-msg \"$@\"\n",
+            env!("CARGO_BIN_EXE_git-ai")
+        ),
+    )
+    .unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&hook, std::fs::Permissions::from_mode(0o755)).unwrap();
+    }
+}
+
+/// An editor that saves the message it's opened with next to it, then writes `message`
+fn editor_saving_message(message: &str) -> String {
+    format!(
+        "f() {{ cp \"$1\" \"$1.saved\"; echo '{}' > \"$1\"; }}; f",
+        message
+    )
+}
+
+#[test]
+fn test_prepare_commit_msg_summarizes_staged_lines() {
+    let repo = TestRepo::new();
+    install_hook(&repo);
+    let mut file = repo.filename("code.txt");
+    file.set_contents(lines!["start", "end"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    file.insert_at(1, lines!["AI 1".ai(), "AI 2".ai(), "human".human()]);
+    repo.git(&["add", "-A"]).unwrap();
+    repo.git_with_env(
+        &["commit"],
+        &[("GIT_EDITOR", &editor_saving_message("Add lines"))],
+    )
+    .unwrap();
+
+    let editor_message = repo.read_file(".git/COMMIT_EDITMSG.saved").unwrap();
+    assert!(editor_message.contains("# 2 AI lines (1 session), 1 human line\n"));
+
+    // The summary is a comment, so it isn't committed
+    let message = repo.git(&["log", "-1", "--format=%B"]).unwrap();
+    assert_eq!(message.trim(), "Add lines");
+}
+
+#[test]
+fn test_prepare_commit_msg_leaves_unedited_message_alone() {
+    let repo = TestRepo::new();
+    install_hook(&repo);
+    let mut file = repo.filename("code.txt");
+    file.set_contents(lines!["start"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    file.insert_at(1, lines!["AI line".ai()]);
+    repo.stage_all_and_commit("Add line").unwrap();
+
+    let message = repo.git(&["log", "-1", "--format=%B"]).unwrap();
+    assert_eq!(message.trim(), "Add line");
+}
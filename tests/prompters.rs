@@ -0,0 +1,69 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+#[test]
+fn test_prompter_is_recorded_and_shown() {
+    let repo = TestRepo::new();
+    let mut lib = repo.filename("lib.rs");
+    lib.set_contents(lines!["fn a() {}".human()]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    // The agent's session names who prompted it
+    std::fs::write(repo.path().join("lib.rs"), "fn a() {}\nfn jane() {}\n").unwrap();
+    repo.git_ai(&[
+        "checkpoint",
+        "mock_ai",
+        "lib.rs",
+        "--session",
+        "s1",
+        "--human-author",
+        "Jane <jane@example.com>",
+    ])
+    .unwrap();
+    // Otherwise it's the git user
+    std::fs::write(
+        repo.path().join("lib.rs"),
+        "fn a() {}\nfn jane() {}\nfn user() {}\n",
+    )
+    .unwrap();
+    repo.git_ai(&["checkpoint", "mock_ai", "lib.rs", "--session", "s2"])
+        .unwrap();
+    repo.stage_all_and_commit("Agents' edits").unwrap();
+
+    let blame = repo
+        .git_ai(&["blame", "--show-prompter", "lib.rs"])
+        .unwrap();
+    let jane_line = blame.lines().find(|l| l.contains("fn jane")).unwrap();
+    assert!(
+        jane_line.contains("mock_ai (prompted by Jane)"),
+        "{}",
+        blame
+    );
+    let user_line = blame.lines().find(|l| l.contains("fn user")).unwrap();
+    assert!(
+        user_line.contains("mock_ai (prompted by Test User)"),
+        "{}",
+        blame
+    );
+
+    let show = repo.git_ai(&["show", "HEAD", "--prompters"]).unwrap();
+    assert!(
+        show.contains("mock_ai::unknown  Jane <jane@example.com>"),
+        "{}",
+        show
+    );
+    assert!(show.contains("Test User <test@example.com>"), "{}", show);
+
+    let output = repo.git_ai(&["stats", "--by-prompter", "--json"]).unwrap();
+    let stats: serde_json::Value = serde_json::from_str(output.trim()).unwrap();
+    let stats = stats.as_array().unwrap();
+    assert_eq!(stats.len(), 2, "{}", output);
+    let jane = stats
+        .iter()
+        .find(|s| s["prompter"] == "Jane <jane@example.com>")
+        .unwrap();
+    assert_eq!(jane["sessions"], 1);
+    assert_eq!(jane["accepted_lines"], 1, "{}", output);
+}
@@ -0,0 +1,142 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+fn protected_repo() -> TestRepo {
+    let repo = TestRepo::new();
+    std::fs::create_dir_all(repo.path().join("crypto")).unwrap();
+    std::fs::write(
+        repo.path().join(".gitai"),
+        "[ai]\n\tprotectedPath = crypto/**\n",
+    )
+    .unwrap();
+    let mut readme = repo.filename("README.md");
+    readme.set_contents(lines!["# Project"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+    repo
+}
+
+#[test]
+fn test_ai_changes_to_protected_paths_block_the_commit() {
+    let repo = protected_repo();
+    let mut aes = repo.filename("crypto/aes.rs");
+    aes.set_contents(lines!["fn encrypt() {}".ai(), "fn decrypt() {}".ai()]);
+
+    let err = repo.stage_all_and_commit("Add AES").unwrap_err();
+    assert!(
+        err.contains("crypto/aes.rs (2 unreviewed AI lines)"),
+        "{}",
+        err
+    );
+    assert!(err.contains("--ai-ack"), "{}", err);
+
+    // Nothing was committed, and acknowledging the AI code lets it through
+    assert_eq!(
+        repo.git(&["rev-list", "--count", "HEAD"]).unwrap().trim(),
+        "1"
+    );
+    repo.git(&["commit", "--ai-ack", "-m", "Add AES"]).unwrap();
+    assert_eq!(
+        repo.git(&["rev-list", "--count", "HEAD"]).unwrap().trim(),
+        "2"
+    );
+    aes.assert_lines_and_blame(lines!["fn encrypt() {}".ai(), "fn decrypt() {}".ai()]);
+}
+
+#[test]
+fn test_staged_review_mark_unblocks_the_commit() {
+    let repo = protected_repo();
+    let mut aes = repo.filename("crypto/aes.rs");
+    aes.set_contents(lines!["fn encrypt() {}".ai(), "fn decrypt() {}".ai()]);
+    repo.git(&["add", "-A"]).unwrap();
+
+    repo.git_ai(&[
+        "review",
+        "mark",
+        "--staged",
+        "crypto/aes.rs:1",
+        "--by",
+        "joe",
+    ])
+    .unwrap();
+    let err = repo.commit("Add AES").unwrap_err();
+    assert!(
+        err.contains("crypto/aes.rs (1 unreviewed AI line)"),
+        "{}",
+        err
+    );
+
+    repo.git_ai(&[
+        "review",
+        "mark",
+        "--staged",
+        "crypto/aes.rs:2",
+        "--by",
+        "joe",
+    ])
+    .unwrap();
+    let commit = repo.commit("Add AES").unwrap();
+
+    // The marks moved into the commit's review note
+    let blame = repo.git_ai(&["blame", "crypto/aes.rs"]).unwrap();
+    assert!(blame.contains("reviewed by joe"), "{}", blame);
+    let stats = repo
+        .git_ai(&["stats", &commit.commit_sha, "--json"])
+        .unwrap();
+    let json = stats.lines().find(|line| line.starts_with('{')).unwrap();
+    let stats: serde_json::Value = serde_json::from_str(json).unwrap();
+    assert_eq!(stats["ai_reviewed"], 2);
+}
+
+#[test]
+fn test_unprotected_and_human_changes_commit_normally() {
+    let repo = protected_repo();
+    let mut lib = repo.filename("lib.rs");
+    lib.set_contents(lines!["fn helper() {}".ai()]);
+    let mut rsa = repo.filename("crypto/rsa.rs");
+    rsa.set_contents(lines!["fn sign() {}".human()]);
+    repo.stage_all_and_commit("Add helpers").unwrap();
+}
+
+#[test]
+fn test_ai_edits_to_forbidden_paths_warn_and_fail_ci_check() {
+    let repo = TestRepo::new();
+    std::fs::create_dir_all(repo.path().join("secrets")).unwrap();
+    std::fs::write(
+        repo.path().join(".gitai"),
+        "[ai]\n\tforbiddenPath = secrets\n",
+    )
+    .unwrap();
+    let mut readme = repo.filename("README.md");
+    readme.set_contents(lines!["# Project"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+    let base = repo.current_branch();
+    repo.git(&["checkout", "-b", "feature"]).unwrap();
+
+    let mut lib = repo.filename("lib.rs");
+    lib.set_contents(lines!["fn helper() {}".ai()]);
+    repo.stage_all_and_commit("Add helper").unwrap();
+    let range = format!("{}..feature", base);
+    repo.git_ai(&["ci", "check", &range]).unwrap();
+
+    std::fs::write(
+        repo.path().join("secrets/keys.rs"),
+        "const KEY: &str = \"\";\n",
+    )
+    .unwrap();
+    repo.git(&["add", "-A"]).unwrap();
+    let output = repo.git_ai(&["checkpoint", "mock_ai"]).unwrap();
+    assert!(
+        output.contains("AI authorship isn't allowed") && output.contains("secrets/keys.rs"),
+        "{}",
+        output
+    );
+    repo.stage_all_and_commit("Add keys").unwrap();
+
+    let err = repo.git_ai(&["ci", "check", &range]).unwrap_err();
+    assert!(err.contains("secrets/keys.rs (1 AI line)"), "{}", err);
+    // The current branch against --base checks the same commits
+    let err = repo.git_ai(&["ci", "check", "--base", &base]).unwrap_err();
+    assert!(err.contains("secrets/keys.rs"), "{}", err);
+}
@@ -0,0 +1,123 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+use serde_json::Value;
+
+fn query(repo: &TestRepo, args: &[&str]) -> Vec<Value> {
+    let mut full_args = vec!["query"];
+    full_args.extend_from_slice(args);
+    full_args.push("--json");
+    let output = repo.git_ai(&full_args).unwrap();
+    serde_json::from_str(output.trim()).unwrap()
+}
+
+#[test]
+fn test_query_combines_notes_working_log_and_blame() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("app.py");
+    file.set_contents(lines![
+        "def main():",
+        "    print('hi')".ai(),
+        "    return 0".ai()
+    ]);
+    let commit = repo.stage_all_and_commit("Initial commit").unwrap();
+
+    // Committed lines: the note answers for AI lines, git blame for the rest
+    let lines = query(&repo, &["app.py", "--commit", "HEAD"]);
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0]["source"], "blame");
+    assert_eq!(lines[0]["author"]["kind"], "human");
+    assert_eq!(lines[0]["author"]["name"], "Test User");
+    for line in &lines[1..] {
+        assert_eq!(line["source"], "note");
+        assert_eq!(line["author"]["kind"], "ai");
+        assert_eq!(line["author"]["tool"], "mock_ai");
+        assert_eq!(line["commit"], commit.commit_sha.as_str());
+    }
+
+    // Uncommitted AI line: answered from the working log
+    std::fs::write(
+        repo.path().join("app.py"),
+        "def main():\n    print('hi')\n    return 0\n# TODO: handle errors",
+    )
+    .unwrap();
+    repo.git_ai(&["checkpoint", "mock_ai"]).unwrap();
+
+    let lines = query(&repo, &["app.py", "-L", "3,4"]);
+    assert_eq!(
+        lines
+            .iter()
+            .map(|l| l["line"].as_u64().unwrap())
+            .collect::<Vec<_>>(),
+        vec![3, 4]
+    );
+    assert_eq!(lines[0]["source"], "note");
+    assert_eq!(lines[1]["source"], "working_log");
+    assert_eq!(lines[1]["author"]["kind"], "ai");
+    assert!(lines[1]["commit"].is_null());
+
+    // Once the file moves on without a checkpoint, the working log no longer applies
+    std::fs::write(
+        repo.path().join("app.py"),
+        "def main():\n    print('hi')\n    return 0\n# TODO: handle errors\n# edited by hand",
+    )
+    .unwrap();
+    let lines = query(&repo, &["app.py", "-L", "5"]);
+    assert_eq!(lines[0]["source"], "blame");
+    assert_eq!(lines[0]["author"]["kind"], "human");
+}
+
+#[test]
+fn test_query_rejects_invalid_line_ranges() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("app.py");
+    file.set_contents(lines!["one", "two"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    assert!(repo.git_ai(&["query", "app.py", "-L", "2,1"]).is_err());
+    assert!(repo.git_ai(&["query", "app.py", "-L", "0,1"]).is_err());
+
+    let output = repo.git_ai(&["query", "app.py"]).unwrap();
+    assert_eq!(output.lines().count(), 2);
+    assert!(output.lines().all(|line| line.contains("Test User")));
+}
+
+#[test]
+fn test_query_stream_yields_the_same_lines_hunk_by_hunk() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("app.py");
+    file.set_contents(lines!["import os", "def main():", "    pass"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+    file.set_contents(lines![
+        "import os",
+        "import sys".ai(),
+        "def main():",
+        "    pass",
+        "    return 0".ai()
+    ]);
+    repo.stage_all_and_commit("AI edits").unwrap();
+
+    let output = repo.git_ai(&["query", "app.py", "--stream"]).unwrap();
+    let hunks: Vec<Value> = output
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert!(hunks.len() >= 3);
+
+    let mut streamed: Vec<Value> = Vec::new();
+    for hunk in &hunks {
+        let lines = hunk["lines"].as_array().unwrap();
+        assert_eq!(lines.first().unwrap()["line"], hunk["start_line"]);
+        assert_eq!(lines.last().unwrap()["line"], hunk["end_line"]);
+        streamed.extend(lines.iter().cloned());
+    }
+    streamed.sort_by_key(|line| line["line"].as_u64());
+    assert_eq!(streamed, query(&repo, &["app.py"]));
+
+    let output = repo
+        .git_ai(&["query", "app.py", "--stream", "-L", "5"])
+        .unwrap();
+    let hunk: Value = serde_json::from_str(output.trim()).unwrap();
+    assert_eq!(hunk["lines"][0]["author"]["kind"], "ai");
+}
@@ -403,6 +403,57 @@ fn test_rebase_rerere() {
     // Authorship tracking is handled by hooks regardless of rerere
 }
 
+/// Test that rebasing again with rerere keeps the AI's recorded conflict resolution as AI
+#[test]
+fn test_rebase_rerere_replayed_resolution_keeps_ai_attribution() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "rerere.enabled", "true"]).unwrap();
+
+    let mut conflict_file = repo.filename("conflict.txt");
+    conflict_file.set_contents(lines!["line 1", "line 2", "line 3"]);
+    repo.stage_all_and_commit("Initial").unwrap();
+    let default_branch = repo.current_branch();
+
+    repo.git(&["checkout", "-b", "feature"]).unwrap();
+    conflict_file.replace_at(1, "FEATURE CHANGE".human());
+    repo.stage_all_and_commit("Feature changes").unwrap();
+    let feature_head = repo.git(&["rev-parse", "HEAD"]).unwrap().trim().to_string();
+
+    repo.git(&["checkout", &default_branch]).unwrap();
+    conflict_file.replace_at(1, "MAIN CHANGE".human());
+    repo.stage_all_and_commit("Main changes").unwrap();
+
+    // AI resolves the conflict in the first rebase
+    repo.git(&["checkout", "feature"]).unwrap();
+    assert!(repo.git(&["rebase", &default_branch]).is_err());
+    conflict_file.set_contents(lines!["line 1", "AI RESOLUTION".ai(), "line 3"]);
+    repo.git_with_env(&["rebase", "--continue"], &[("GIT_EDITOR", "true")])
+        .unwrap();
+    conflict_file.assert_lines_and_blame(lines![
+        "line 1".human(),
+        "AI RESOLUTION".ai(),
+        "line 3".human()
+    ]);
+
+    // Rebase the original branch again. rerere replays the resolution
+    repo.git(&["reset", "--hard", &feature_head]).unwrap();
+    assert!(repo.git(&["rebase", &default_branch]).is_err());
+    assert_eq!(
+        repo.read_file("conflict.txt").unwrap(),
+        "line 1\nAI RESOLUTION\nline 3"
+    );
+    repo.git(&["add", "conflict.txt"]).unwrap();
+    repo.git_with_env(&["rebase", "--continue"], &[("GIT_EDITOR", "true")])
+        .unwrap();
+
+    let mut conflict_file = repo.filename("conflict.txt");
+    conflict_file.assert_lines_and_blame(lines![
+        "line 1".human(),
+        "AI RESOLUTION".ai(),
+        "line 3".human()
+    ]);
+}
+
 /// Test dependent branch stack (patch-stack workflow)
 #[test]
 fn test_rebase_patch_stack() {
@@ -494,6 +545,73 @@ fn test_rebase_already_up_to_date() {
     feature_file.assert_lines_and_blame(lines!["// AI".ai()]);
 }
 
+/// Test that `--onto` only rewrites the replayed commits, not the new base's commits
+#[test]
+fn test_rebase_onto_leaves_new_base_commits_alone() {
+    let repo = TestRepo::new();
+
+    let mut base_file = repo.filename("base.txt");
+    base_file.set_contents(lines!["base content"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+    let default_branch = repo.current_branch();
+
+    repo.git(&["checkout", "-b", "old_base"]).unwrap();
+    let mut old_file = repo.filename("old.txt");
+    old_file.set_contents(lines!["old base"]);
+    repo.stage_all_and_commit("Old base commit").unwrap();
+
+    repo.git(&["checkout", "-b", "feature"]).unwrap();
+    let mut feature_file = repo.filename("feature.txt");
+    feature_file.set_contents(lines!["// AI feature".ai()]);
+    repo.stage_all_and_commit("AI feature").unwrap();
+
+    // The new base has a commit without an authorship log, like one fetched without notes
+    repo.git(&["checkout", &default_branch]).unwrap();
+    repo.git(&["checkout", "-b", "new_base"]).unwrap();
+    let mut new_file = repo.filename("new.txt");
+    new_file.set_contents(lines!["new base"]);
+    repo.stage_all_and_commit("New base commit").unwrap();
+    let new_base_sha = repo.git(&["rev-parse", "HEAD"]).unwrap().trim().to_string();
+    repo.git(&["notes", "--ref=ai", "remove", "HEAD"]).unwrap();
+
+    repo.git(&["rebase", "--onto", "new_base", "old_base", "feature"])
+        .expect("Rebase --onto should succeed");
+
+    feature_file.assert_lines_and_blame(lines!["// AI feature".ai()]);
+    assert!(
+        repo.git(&["notes", "--ref=ai", "show", &new_base_sha])
+            .is_err(),
+        "The new base's commit isn't part of the rebase"
+    );
+    assert!(repo.read_file("old.txt").is_none());
+}
+
+/// Test that `git rebase <upstream> <branch>` rewrites the branch, not the checked out one
+#[test]
+fn test_rebase_with_branch_argument() {
+    let repo = TestRepo::new();
+
+    let mut base_file = repo.filename("base.txt");
+    base_file.set_contents(lines!["base content"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+    let default_branch = repo.current_branch();
+
+    repo.git(&["checkout", "-b", "feature"]).unwrap();
+    let mut feature_file = repo.filename("feature.txt");
+    feature_file.set_contents(lines!["// AI feature".ai(), "human line"]);
+    repo.stage_all_and_commit("AI feature").unwrap();
+
+    repo.git(&["checkout", &default_branch]).unwrap();
+    base_file.set_contents(lines!["base content", "more base content"]);
+    repo.stage_all_and_commit("Main commit").unwrap();
+
+    // Rebase feature while main is checked out
+    repo.git(&["rebase", &default_branch, "feature"]).unwrap();
+    assert_eq!(repo.current_branch(), "feature");
+
+    feature_file.assert_lines_and_blame(lines!["// AI feature".ai(), "human line".human()]);
+}
+
 /// Test rebase with conflicts - verifies reconstruction works after conflict resolution
 #[test]
 fn test_rebase_with_conflicts() {
@@ -581,6 +699,34 @@ fn test_rebase_abort() {
     conflict_file.assert_lines_and_blame(lines!["line 1".human(), "AI CHANGE".ai()]);
 }
 
+/// Test that aborting a rebase leaves no rebase events behind in the rewrite log
+#[test]
+fn test_rebase_abort_rolls_back_rewrite_log() {
+    let repo = TestRepo::new();
+
+    let mut conflict_file = repo.filename("conflict.txt");
+    conflict_file.set_contents(lines!["line 1", "line 2"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+    let default_branch = repo.current_branch();
+
+    repo.git(&["checkout", "-b", "feature"]).unwrap();
+    conflict_file.replace_at(1, "AI CHANGE".ai());
+    repo.stage_all_and_commit("AI changes").unwrap();
+
+    repo.git(&["checkout", &default_branch]).unwrap();
+    conflict_file.replace_at(1, "MAIN CHANGE".human());
+    repo.stage_all_and_commit("Main changes").unwrap();
+
+    repo.git(&["checkout", "feature"]).unwrap();
+    let rewrite_log_before = repo.rewrite_log();
+
+    assert!(repo.git(&["rebase", &default_branch]).is_err());
+    assert_ne!(repo.rewrite_log(), rewrite_log_before);
+    repo.git(&["rebase", "--abort"]).unwrap();
+
+    assert_eq!(repo.rewrite_log(), rewrite_log_before);
+}
+
 /// Test branch switch during rebase - ensures proper state handling
 #[test]
 fn test_rebase_branch_switch_during() {
@@ -0,0 +1,36 @@
+#[macro_use]
+mod repos;
+use git_ai::authorship::authorship_log_serialization::AuthorshipLog;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+fn authorship_log(repo: &TestRepo, rev: &str) -> AuthorshipLog {
+    let note = repo.git(&["notes", "--ref=ai", "show", rev]).unwrap();
+    AuthorshipLog::deserialize_from_string(&note).unwrap()
+}
+
+#[test]
+fn test_commit_records_renames_in_its_log() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("old.rs");
+    file.set_contents(lines!["fn a() {}", "fn b() {}", "fn c() {}", "fn d() {}"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+    assert!(authorship_log(&repo, "HEAD").metadata.renames.is_empty());
+
+    repo.git(&["mv", "old.rs", "new.rs"]).unwrap();
+    let mut file = repo.filename("new.rs");
+    file.insert_at(4, lines!["fn agent() {}".ai()]);
+    repo.stage_all_and_commit("Rename and extend").unwrap();
+
+    let log = authorship_log(&repo, "HEAD");
+    assert_eq!(
+        log.metadata.renames.get("old.rs").map(String::as_str),
+        Some("new.rs")
+    );
+    assert_eq!(log.metadata.renamed_from("new.rs"), Some("old.rs"));
+    assert!(
+        log.attestations
+            .iter()
+            .any(|file| file.file_path == "new.rs")
+    );
+}
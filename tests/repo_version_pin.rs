@@ -0,0 +1,71 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+fn write_repo_config(repo: &TestRepo, contents: &str) {
+    std::fs::write(repo.path().join(".gitai"), contents).unwrap();
+}
+
+#[test]
+fn test_required_version_refuse_blocks_git_ai_but_not_git() {
+    let repo = TestRepo::new();
+    write_repo_config(
+        &repo,
+        "[ai]\n\trequiredVersion = 999.0.0\n\tversionPolicy = refuse\n",
+    );
+
+    let stderr = repo
+        .git_ai(&["--error-format", "json", "stats"])
+        .unwrap_err();
+    let error: serde_json::Value = serde_json::from_str(stderr.trim()).unwrap();
+    assert_eq!(error["error"]["code"], "version_too_old");
+    assert_eq!(error["error"]["exit_code"], 9);
+
+    // git still works, without writing attribution
+    std::fs::write(repo.path().join("app.py"), "def main():\n    return 0\n").unwrap();
+    let output = repo.git(&["add", "-A"]).unwrap();
+    assert!(
+        output.contains("running git without git-ai hooks"),
+        "{}",
+        output
+    );
+    repo.git(&["commit", "-m", "Initial commit"]).unwrap();
+    assert!(repo.git(&["notes", "--ref=ai", "show", "HEAD"]).is_err());
+
+    // Commands that manage the install still run
+    repo.git_ai(&["version"]).unwrap();
+}
+
+#[test]
+fn test_required_version_warns_by_default() {
+    let repo = TestRepo::new();
+    write_repo_config(&repo, "[ai]\n\trequiredVersion = v999.0.0\n");
+
+    let mut file = repo.filename("app.py");
+    file.set_contents(lines!["def main():", "    return 0".ai()]);
+    let commit = repo.stage_all_and_commit("Initial commit").unwrap();
+    assert!(!commit.authorship_log.attestations.is_empty());
+
+    let output = repo.git_ai(&["stats"]).unwrap();
+    assert!(
+        output.contains("requires git-ai 999.0.0 or newer"),
+        "{}",
+        output
+    );
+}
+
+#[test]
+fn test_satisfied_required_version_is_silent() {
+    let repo = TestRepo::new();
+    write_repo_config(
+        &repo,
+        "[ai]\n\trequiredVersion = 0.0.1\n\tversionPolicy = refuse\n",
+    );
+
+    let mut file = repo.filename("app.py");
+    file.set_contents(lines!["def main():", "    return 0".ai()]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+    let output = repo.git_ai(&["stats"]).unwrap();
+    assert!(!output.contains("requires git-ai"), "{}", output);
+}
@@ -2,12 +2,14 @@ use git_ai::authorship::authorship_log_serialization::AuthorshipLog;
 use git_ai::authorship::stats::CommitStats;
 use git_ai::git::repo_storage::PersistedWorkingLog;
 use git_ai::git::repository as GitAiRepository;
+use git_ai::git::test_utils::authorship_log_snapshot;
 use git2::Repository;
-use insta::assert_debug_snapshot;
+use insta::{assert_debug_snapshot, assert_snapshot};
 use rand::Rng;
 use std::fs;
+use std::io::Write;
 use std::path::PathBuf;
-use std::process::Command;
+use std::process::{Command, Stdio};
 use std::sync::OnceLock;
 
 use super::test_file::TestFile;
@@ -35,6 +37,14 @@ impl TestRepo {
         Self { path }
     }
 
+    /// A repository whose initial commit holds `file` with `lines`
+    pub fn new_with_file(file: &str, lines: &[&str]) -> Self {
+        let repo = Self::new();
+        repo.filename(file).set_contents(lines.to_vec());
+        repo.stage_all_and_commit("Initial commit").unwrap();
+        repo
+    }
+
     pub fn path(&self) -> &PathBuf {
         &self.path
     }
@@ -46,12 +56,51 @@ impl TestRepo {
     }
 
     pub fn stats(&self) -> Result<CommitStats, String> {
-        let mut stats = self.git_ai(&["stats", "--json"]).unwrap();
-        stats = stats.split("}}}").next().unwrap().to_string() + "}}}";
-        let stats: CommitStats = serde_json::from_str(&stats).unwrap();
+        self.stats_with_args(&[])
+    }
+
+    /// `git-ai stats --json` with extra `args`
+    pub fn stats_with_args(&self, args: &[&str]) -> Result<CommitStats, String> {
+        let mut stats_args = vec!["stats", "--json"];
+        stats_args.extend_from_slice(args);
+        let output = self.git_ai(&stats_args)?;
+        // The JSON comes first; debug builds log to stderr after it
+        let stats: CommitStats = serde_json::Deserializer::from_str(&output)
+            .into_iter()
+            .next()
+            .unwrap()
+            .unwrap();
         Ok(stats)
     }
 
+    /// `(ai_additions, human_additions, possible_ai_additions)` of HEAD
+    pub fn head_additions(&self) -> (u32, u32, u32) {
+        self.head_additions_with_env(&[])
+    }
+
+    /// `head_additions` with `envs` set for `git-ai stats`
+    pub fn head_additions_with_env(&self, envs: &[(&str, &str)]) -> (u32, u32, u32) {
+        let output = self.git_ai_with_env(&["stats", "--json"], envs).unwrap();
+        let stats: CommitStats = serde_json::Deserializer::from_str(&output)
+            .into_iter()
+            .next()
+            .unwrap()
+            .unwrap();
+        (
+            stats.ai_additions,
+            stats.human_additions,
+            stats.possible_ai_additions,
+        )
+    }
+
+    /// Writes `count` distinct numbered lines to `file`
+    pub fn write_lines(&self, file: &str, count: usize) {
+        let content: String = (1..=count)
+            .map(|i| format!("let value_{} = {};\n", i, i))
+            .collect();
+        fs::write(self.path.join(file), content).unwrap();
+    }
+
     pub fn current_branch(&self) -> String {
         self.git(&["branch", "--show-current"])
             .unwrap()
@@ -86,12 +135,36 @@ impl TestRepo {
         }
     }
 
+    pub fn git_ai_with_env(&self, args: &[&str], envs: &[(&str, &str)]) -> Result<String, String> {
+        let binary_path = get_binary_path();
+
+        let mut command = Command::new(binary_path);
+        command.args(args).current_dir(&self.path);
+        for (key, value) in envs {
+            command.env(key, value);
+        }
+        let output = command.output().expect(&format!(
+            "Failed to execute git-ai command with env: {:?}",
+            args
+        ));
+
+        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+
+        if output.status.success() {
+            Ok(format!("{}{}", stdout, stderr))
+        } else {
+            Err(stderr)
+        }
+    }
+
     pub fn git(&self, args: &[&str]) -> Result<String, String> {
         let binary_path = get_binary_path();
 
         let mut full_args = vec!["-C", self.path.to_str().unwrap()];
         full_args.extend(args);
 
+        // Commits are the test's own, whatever agent may be running the test suite
         let output = Command::new(binary_path)
             .args(&full_args)
             .env("GIT_AI", "git")
@@ -153,6 +226,74 @@ impl TestRepo {
         }
     }
 
+    pub fn git_with_stdin(&self, args: &[&str], stdin: &str) -> Result<String, String> {
+        let binary_path = get_binary_path();
+
+        let mut full_args = vec!["-C", self.path.to_str().unwrap()];
+        full_args.extend(args);
+
+        let mut child = Command::new(binary_path)
+            .args(&full_args)
+            .env("GIT_AI", "git")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .expect(&format!("Failed to execute git command: {:?}", args));
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(stdin.as_bytes())
+            .unwrap();
+        let output = child.wait_with_output().unwrap();
+
+        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+
+        if output.status.success() {
+            Ok(format!("{}{}", stdout, stderr))
+        } else {
+            Err(stderr)
+        }
+    }
+
+    pub fn git_ai_with_stdin(&self, args: &[&str], stdin: &str) -> Result<String, String> {
+        let binary_path = get_binary_path();
+
+        let mut child = Command::new(binary_path)
+            .args(args)
+            .current_dir(&self.path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .expect(&format!("Failed to execute git-ai command: {:?}", args));
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(stdin.as_bytes())
+            .unwrap();
+        let output = child.wait_with_output().unwrap();
+
+        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+
+        if output.status.success() {
+            Ok(format!("{}{}", stdout, stderr))
+        } else {
+            Err(stderr)
+        }
+    }
+
+    /// Stages part of the working copy's changes with `git add -e`, using `editor` (a shell
+    /// command given the patch file) to edit the patch the way a user would in `git add -p`
+    pub fn add_edit(&self, path: &str, editor: &str) {
+        self.git_with_env(&["add", "-e", path], &[("GIT_EDITOR", editor)])
+            .unwrap();
+    }
+
     pub fn filename(&self, filename: &str) -> TestFile {
         let file_path = self.path.join(filename);
 
@@ -180,6 +321,11 @@ impl TestRepo {
         repo.storage.working_log_for_base_commit(&commit_sha)
     }
 
+    /// Raw contents of `.git/ai/rewrite_log`, empty if nothing has been logged yet
+    pub fn rewrite_log(&self) -> String {
+        fs::read_to_string(self.path.join(".git/ai/rewrite_log")).unwrap_or_default()
+    }
+
     pub fn commit(&self, message: &str) -> Result<NewCommit, String> {
         let output = self.git(&["commit", "-m", message]);
 
@@ -245,6 +391,10 @@ impl NewCommit {
     pub fn assert_authorship_snapshot(&self) {
         assert_debug_snapshot!(self.authorship_log);
     }
+    /// Snapshot of the canonical, redacted text form, which diffs line by line
+    pub fn assert_authorship_text_snapshot(&self) {
+        assert_snapshot!(authorship_log_snapshot(&self.authorship_log));
+    }
     pub fn print_authorship(&self) {
         // Debug method to print authorship log
         println!("{}", self.authorship_log.serialize_to_string().unwrap());
@@ -0,0 +1,117 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+fn review_list(repo: &TestRepo, args: &[&str]) -> Vec<serde_json::Value> {
+    let mut full_args = vec!["review", "list", "--json"];
+    full_args.extend(args);
+    let output = repo.git_ai(&full_args).unwrap();
+    serde_json::from_str::<serde_json::Value>(output.trim())
+        .unwrap()
+        .as_array()
+        .unwrap()
+        .clone()
+}
+
+#[test]
+fn test_review_list_orders_untouched_ai_hunks_by_risk() {
+    let repo = TestRepo::new();
+    std::fs::create_dir_all(repo.path().join("auth")).unwrap();
+    let mut util = repo.filename("util.rs");
+    util.set_contents(lines!["fn util() {}", "", "fn end() {}"]);
+    let mut login = repo.filename("auth/login.rs");
+    login.set_contents(lines!["fn login() {}"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+    let base = repo.current_branch();
+    repo.git(&["checkout", "-b", "feature"]).unwrap();
+
+    util.insert_at(
+        1,
+        lines!["fn a() {}".ai(), "fn b() {}".ai(), "fn c() {}".ai()],
+    );
+    util.insert_at(5, lines!["fn d() {}".ai(), "fn e() {}".human()]);
+    login.insert_at(0, lines!["fn check() {}".ai(), "fn verify() {}".ai()]);
+    repo.stage_all_and_commit("Add helpers").unwrap();
+
+    let hunks = review_list(&repo, &[]);
+    // The hunk a human wrote in is left out; the smaller hunk in auth/ comes first
+    assert_eq!(hunks.len(), 2, "{:?}", hunks);
+    assert_eq!(hunks[0]["file"], "auth/login.rs");
+    assert_eq!(hunks[0]["sensitive"], "auth");
+    assert_eq!(hunks[0]["lines"], 2);
+    assert_eq!(hunks[1]["file"], "util.rs");
+    assert_eq!(hunks[1]["start"], 2);
+    assert_eq!(hunks[1]["end"], 4);
+
+    // An explicit range gives the same queue
+    let range = format!("{}..feature", base);
+    assert_eq!(review_list(&repo, &[&range]), hunks);
+}
+
+#[test]
+fn test_review_list_skips_ai_lines_edited_by_a_human() {
+    let repo = TestRepo::new();
+    let mut code = repo.filename("code.rs");
+    code.set_contents(lines!["fn main() {}"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+    let base = repo.current_branch();
+    repo.git(&["checkout", "-b", "feature"]).unwrap();
+
+    code.insert_at(0, lines!["fn a() {}".ai(), "fn b() {}".ai()]);
+    repo.stage_all_and_commit("Add AI code").unwrap();
+    assert_eq!(review_list(&repo, &["--base", &base]).len(), 1);
+
+    code.replace_at(1, "fn b() -> u32 { 1 }".human());
+    repo.stage_all_and_commit("Fix b").unwrap();
+    let hunks = review_list(&repo, &["--base", &base]);
+    assert!(hunks.is_empty(), "{:?}", hunks);
+}
+
+#[test]
+fn test_review_mark_is_shown_in_list_blame_and_stats() {
+    let repo = TestRepo::new();
+    let mut code = repo.filename("code.rs");
+    code.set_contents(lines!["fn main() {}"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+    let base = repo.current_branch();
+    repo.git(&["checkout", "-b", "feature"]).unwrap();
+
+    code.insert_at(
+        0,
+        lines!["fn a() {}".ai(), "fn b() {}".ai(), "fn c() {}".ai()],
+    );
+    repo.stage_all_and_commit("Add AI code").unwrap();
+    assert_eq!(review_list(&repo, &["--base", &base]).len(), 1);
+
+    // Reviewing part of the hunk leaves it in the queue
+    let output = repo
+        .git_ai(&["review", "mark", "HEAD", "code.rs:1-2", "--by", "jane"])
+        .unwrap();
+    assert!(output.contains("reviewed by jane"), "{}", output);
+    assert_eq!(review_list(&repo, &["--base", &base]).len(), 1);
+
+    let blame = repo.git_ai(&["blame", "code.rs"]).unwrap();
+    let blame_lines: Vec<&str> = blame.lines().collect();
+    assert!(
+        blame_lines[0].contains("mock_ai (reviewed by jane)"),
+        "{}",
+        blame
+    );
+    assert!(!blame_lines[2].contains("reviewed"), "{}", blame);
+
+    let stats = repo.git_ai(&["stats", "--json"]).unwrap();
+    let stats: serde_json::Value =
+        serde_json::from_str(stats.lines().find(|line| line.starts_with('{')).unwrap()).unwrap();
+    assert_eq!(stats["ai_reviewed"], 2);
+
+    // Marking the whole file finishes it
+    repo.git_ai(&["review", "mark", "HEAD", "code.rs", "--by", "joe"])
+        .unwrap();
+    assert!(review_list(&repo, &["--base", &base]).is_empty());
+
+    assert!(
+        repo.git_ai(&["review", "mark", "HEAD", "code.rs:3-9", "--by", "joe"])
+            .is_err()
+    );
+}
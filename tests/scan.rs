@@ -0,0 +1,70 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+#[test]
+fn test_scan_licenses_only_reports_ai_lines() {
+    let repo = TestRepo::new();
+    let mut code = repo.filename("code.rs");
+    code.set_contents(lines![
+        "// Copyright Acme. All rights reserved.",
+        "fn main() {}"
+    ]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    code.insert_at(
+        2,
+        lines![
+            "// Licensed under the GNU General Public License".ai(),
+            "fn helper() {}".ai()
+        ],
+    );
+    repo.stage_all_and_commit("Add helper").unwrap();
+
+    // The human's "All rights reserved" isn't reported, the AI's license header is
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_git-ai"))
+        .args(["scan", "--licenses", "--json"])
+        .current_dir(repo.path())
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let findings: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let findings = findings.as_array().unwrap();
+    assert_eq!(findings.len(), 2);
+    assert!(findings.iter().all(|finding| finding["line"] == 3));
+    assert!(findings.iter().all(|finding| finding["tool"] == "mock_ai"));
+    assert!(
+        findings
+            .iter()
+            .any(|finding| finding["pattern"] == "GNU General Public License")
+    );
+}
+
+#[test]
+fn test_scan_licenses_with_repo_patterns() {
+    let repo = TestRepo::new();
+    std::fs::write(
+        repo.path().join(".gitai"),
+        "[ai]\n\tlicensePattern = internal: Acme internal\n",
+    )
+    .unwrap();
+    let mut code = repo.filename("code.rs");
+    code.set_contents(lines!["fn main() {}"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    code.insert_at(1, lines!["// Acme INTERNAL helper".ai()]);
+    repo.stage_all_and_commit("Add helper").unwrap();
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_git-ai"))
+        .args(["scan", "--licenses", "--json"])
+        .current_dir(repo.path())
+        .output()
+        .unwrap();
+    let findings: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(findings[0]["check"], "internal");
+    assert_eq!(findings[0]["line"], 2);
+
+    // Nothing AI wrote matches at the first commit
+    repo.git_ai(&["scan", "--licenses", "HEAD~1"]).unwrap();
+}
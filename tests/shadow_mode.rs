@@ -0,0 +1,99 @@
+#[macro_use]
+mod repos;
+use repos::test_repo::TestRepo;
+
+const SHADOW: &[(&str, &str)] = &[("GIT_AI_MODE", "shadow")];
+
+fn shadow_log(repo: &TestRepo) -> Vec<serde_json::Value> {
+    std::fs::read_to_string(repo.path().join(".git/ai/shadow_log"))
+        .unwrap_or_default()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+fn head(repo: &TestRepo) -> String {
+    repo.git(&["rev-parse", "HEAD"]).unwrap().trim().to_string()
+}
+
+#[test]
+fn test_shadow_mode_logs_instead_of_writing() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("lib.rs");
+    file.set_contents(lines!["fn a() {}"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    std::fs::write(repo.path().join("lib.rs"), "fn a() {}\nfn agent() {}\n").unwrap();
+    repo.git_ai_with_env(&["checkpoint", "mock_ai", "lib.rs"], SHADOW)
+        .unwrap();
+    repo.git_with_env(&["add", "lib.rs"], SHADOW).unwrap();
+    repo.git_with_env(&["commit", "-m", "Agent edit"], SHADOW)
+        .unwrap();
+    let committed = head(&repo);
+
+    // Neither the checkpoint nor a note for the new commit was written
+    let working_logs = std::fs::read_dir(repo.path().join(".git/ai/working_logs"))
+        .map(|entries| entries.count())
+        .unwrap_or(0);
+    assert_eq!(working_logs, 0);
+    let notes = repo.git(&["notes", "--ref=ai", "list"]).unwrap();
+    assert!(notes.lines().all(|line| !line.ends_with(&committed)));
+
+    let entries = shadow_log(&repo);
+    assert_eq!(entries.len(), 2, "{:?}", entries);
+    assert_eq!(entries[0]["command"], "checkpoint");
+    assert_eq!(entries[0]["action"], "checkpoint");
+    assert_eq!(entries[0]["agent"], "mock_ai");
+    assert_eq!(entries[0]["paths"], serde_json::json!(["lib.rs"]));
+    assert_eq!(entries[0]["checkpoint"]["entries"][0]["file"], "lib.rs");
+
+    // The note the real post-commit pipeline would have written, attributing the agent's line
+    assert_eq!(entries[1]["command"], "commit");
+    assert_eq!(entries[1]["action"], "note");
+    assert_eq!(entries[1]["commit"], committed);
+    assert_eq!(entries[1]["notes_ref"], "ai");
+    let note = entries[1]["note"].as_str().unwrap();
+    assert!(note.starts_with("lib.rs\n"), "{}", note);
+    assert!(note.contains(" 2\n"), "{}", note);
+    assert!(note.contains("\"tool\": \"mock_ai\""), "{}", note);
+
+    // Amending would have carried the note over to the new commit, built from the logged one
+    repo.git_with_env(&["commit", "--amend", "-m", "Reworded"], SHADOW)
+        .unwrap();
+    let entries = shadow_log(&repo);
+    assert_eq!(entries.len(), 3, "{:?}", entries);
+    assert_eq!(entries[2]["action"], "note");
+    assert_eq!(entries[2]["commit"], head(&repo));
+    let attestations = |note: &serde_json::Value| {
+        let note = note.as_str().unwrap();
+        note[..note.find("---").unwrap()].to_string()
+    };
+    assert_eq!(
+        attestations(&entries[2]["note"]),
+        attestations(&entries[1]["note"])
+    );
+    let notes = repo.git(&["notes", "--ref=ai", "list"]).unwrap();
+    assert!(notes.lines().all(|line| !line.ends_with(&head(&repo))));
+}
+
+#[test]
+fn test_shadow_mode_leaves_git_commands_alone() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("lib.rs");
+    file.set_contents(lines!["fn a() {}"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    std::fs::write(repo.path().join("lib.rs"), "fn a() {}\nfn agent() {}\n").unwrap();
+    repo.git_ai(&["checkpoint", "mock_ai", "lib.rs"]).unwrap();
+    repo.git(&["add", "lib.rs"]).unwrap();
+
+    // Trailers would have been added to the message outside shadow mode
+    repo.git_with_env(
+        &["commit", "-m", "Agent edit"],
+        &[("GIT_AI_MODE", "shadow"), ("GIT_AI_COMMIT_TRAILERS", "1")],
+    )
+    .unwrap();
+    let message = repo.git(&["log", "-1", "--format=%B"]).unwrap();
+    assert_eq!(message.trim(), "Agent edit");
+    assert!(repo.git(&["notes", "--ref=ai", "list", "HEAD"]).is_err());
+}
@@ -0,0 +1,52 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+const BOB: &[(&str, &str)] = &[("GIT_AI_USER", "bob@example.com")];
+
+#[test]
+fn test_other_users_ai_checkpoints_stay_out_of_commit() {
+    let repo = TestRepo::new();
+    let mut shared = repo.filename("shared.rs");
+    shared.set_contents(lines!["fn main() {}"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    // Bob's agent edits the file from his session on the shared checkout
+    std::fs::write(repo.path().join("shared.rs"), "fn main() {}\nfn bob() {}\n").unwrap();
+    repo.git_ai_with_env(&["checkpoint", "mock_ai"], BOB)
+        .unwrap();
+
+    // Alice commits it without ever checkpointing AI work of her own
+    let commit = repo.stage_all_and_commit("Alice's commit").unwrap();
+    assert!(
+        commit.authorship_log.attestations.is_empty(),
+        "{:?}",
+        commit.authorship_log.attestations
+    );
+}
+
+#[test]
+fn test_other_users_checkpoints_carry_over_to_their_commit() {
+    let repo = TestRepo::new();
+    let mut readme = repo.filename("README.md");
+    readme.set_contents(lines!["# Project"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    std::fs::write(repo.path().join("bob.rs"), "fn bob() {}\nfn helper() {}\n").unwrap();
+    repo.git_ai_with_env(&["checkpoint", "mock_ai"], BOB)
+        .unwrap();
+
+    // Alice commits her own file first, moving HEAD under Bob
+    std::fs::write(repo.path().join("alice.rs"), "fn alice() {}\n").unwrap();
+    repo.git(&["add", "alice.rs"]).unwrap();
+    let commit = repo.commit("Alice's commit").unwrap();
+    assert!(commit.authorship_log.attestations.is_empty());
+
+    // Bob's checkpoints are still his when he commits
+    repo.git_with_env(&["add", "bob.rs"], BOB).unwrap();
+    repo.git_with_env(&["commit", "-m", "Bob's commit"], BOB)
+        .unwrap();
+    let mut bob = repo.filename("bob.rs");
+    bob.assert_lines_and_blame(lines!["fn bob() {}".ai(), "fn helper() {}".ai()]);
+}
@@ -57,9 +57,7 @@ fn test_authorship_log_stats() {
 
     assert_eq!(first_commit.authorship_log.attestations.len(), 1);
 
-    let mut stats = repo.git_ai(&["stats", "--json"]).unwrap();
-    stats = stats.split("}}}").next().unwrap().to_string() + "}}}";
-    let stats: CommitStats = serde_json::from_str(&stats).unwrap();
+    let stats = repo.stats().unwrap();
     assert_eq!(stats.human_additions, 4);
     assert_eq!(stats.mixed_additions, 1);
     assert_eq!(stats.ai_additions, 6); // Includes the one mixed line (Neptune (override))
@@ -120,6 +118,124 @@ fn test_authorship_log_stats() {
     );
 }
 
+#[test]
+fn test_stats_exclude_generated_files() {
+    let repo = TestRepo::new();
+
+    let mut readme = repo.filename("README.md");
+    readme.set_contents(lines!["# Project"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    let mut lockfile = repo.filename("yarn.lock");
+    lockfile.set_contents(lines!["lock 1".ai(), "lock 2".ai(), "lock 3".ai()]);
+
+    let mut file = repo.filename("planets.txt");
+    file.set_contents(lines!["Mercury".human(), "Venus".ai()]);
+
+    let commit = repo
+        .stage_all_and_commit("Add planets and lockfile")
+        .unwrap();
+
+    // The lockfile is never checkpointed, so it has no attestation
+    assert!(
+        commit
+            .authorship_log
+            .attestations
+            .iter()
+            .all(|a| a.file_path != "yarn.lock")
+    );
+
+    let stats = repo.stats().unwrap();
+    assert_eq!(stats.git_diff_added_lines, 2);
+    assert_eq!(stats.ai_accepted, 1);
+    assert_eq!(stats.human_additions, 1);
+
+    let stats = repo.stats_with_args(&["--include-generated"]).unwrap();
+    assert_eq!(stats.git_diff_added_lines, 5);
+
+    // A range counts the same lines as the commit it's made of. Range stats fetch the branch
+    // first, so the repository needs a remote.
+    let path = repo.path().to_string_lossy().to_string();
+    repo.git(&["remote", "add", "origin", &path]).unwrap();
+    let range_added_lines = |args: &[&str]| {
+        let mut stats_args = vec!["stats", "--json", "HEAD~1..HEAD"];
+        stats_args.extend_from_slice(args);
+        let output = repo.git_ai(&stats_args).unwrap();
+        let json = output.lines().find(|line| line.starts_with('{')).unwrap();
+        let stats: serde_json::Value = serde_json::from_str(json).unwrap();
+        stats["range_stats"]["git_diff_added_lines"]
+            .as_u64()
+            .unwrap()
+    };
+    assert_eq!(range_added_lines(&[]), 2);
+    assert_eq!(range_added_lines(&["--include-generated"]), 5);
+}
+
+#[test]
+fn test_stats_commit_message_written_by_ai() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("notes.txt");
+    file.set_contents(lines!["first"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    // A human writes the change, the agent only writes the commit message
+    file.insert_at(1, lines!["second".human()]);
+    repo.git_ai(&[
+        "checkpoint",
+        "mock_ai",
+        "--commit-message",
+        "Add second line",
+    ])
+    .unwrap();
+    let commit = repo.stage_all_and_commit("Add second line").unwrap();
+
+    let prompt_id = commit
+        .authorship_log
+        .metadata
+        .commit_message_prompt
+        .clone()
+        .expect("commit message should be attributed to the agent");
+    assert!(
+        commit
+            .authorship_log
+            .metadata
+            .prompts
+            .contains_key(&prompt_id)
+    );
+
+    let stats = repo.stats().unwrap();
+    assert_eq!(stats.ai_commit_message.as_deref(), Some("mock_ai::unknown"));
+    assert_eq!(stats.ai_additions, 0);
+
+    let show = repo.git_ai(&["show", "HEAD"]).unwrap();
+    assert!(show.contains(&format!("\"commit_message_prompt\": \"{}\"", prompt_id)));
+}
+
+#[test]
+fn test_stats_commit_message_edited_by_human() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("notes.txt");
+    file.set_contents(lines!["first"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    file.insert_at(1, lines!["second".ai()]);
+    repo.git_ai(&[
+        "checkpoint",
+        "mock_ai",
+        "--commit-message",
+        "Add second line",
+    ])
+    .unwrap();
+    let commit = repo
+        .stage_all_and_commit("Add the second line, reworded")
+        .unwrap();
+
+    assert_eq!(commit.authorship_log.metadata.commit_message_prompt, None);
+    let stats = repo.stats().unwrap();
+    assert_eq!(stats.ai_commit_message, None);
+    assert_eq!(stats.ai_additions, 1);
+}
+
 #[test]
 fn test_markdown_stats_deletion_only() {
     use git_ai::authorship::stats::write_stats_to_markdown;
@@ -135,6 +251,13 @@ fn test_markdown_stats_deletion_only() {
         time_waiting_for_ai: 0,
         git_diff_deleted_lines: 5,
         git_diff_added_lines: 0,
+        ai_low_confidence: 0,
+        ai_overridden_kept: 0,
+        ai_reviewed: 0,
+        possible_ai_additions: 0,
+        unknown_additions: 0,
+        class_additions: BTreeMap::new(),
+        ai_commit_message: None,
         tool_model_breakdown: BTreeMap::new(),
     };
 
@@ -158,6 +281,13 @@ fn test_markdown_stats_all_human() {
         time_waiting_for_ai: 0,
         git_diff_deleted_lines: 0,
         git_diff_added_lines: 10,
+        ai_low_confidence: 0,
+        ai_overridden_kept: 0,
+        ai_reviewed: 0,
+        possible_ai_additions: 0,
+        unknown_additions: 0,
+        class_additions: BTreeMap::new(),
+        ai_commit_message: None,
         tool_model_breakdown: BTreeMap::new(),
     };
 
@@ -181,6 +311,13 @@ fn test_markdown_stats_all_ai() {
         time_waiting_for_ai: 30,
         git_diff_deleted_lines: 0,
         git_diff_added_lines: 15,
+        ai_low_confidence: 0,
+        ai_overridden_kept: 0,
+        ai_reviewed: 0,
+        possible_ai_additions: 0,
+        unknown_additions: 0,
+        class_additions: BTreeMap::new(),
+        ai_commit_message: None,
         tool_model_breakdown: BTreeMap::new(),
     };
 
@@ -204,6 +341,13 @@ fn test_markdown_stats_mixed() {
         time_waiting_for_ai: 45,
         git_diff_deleted_lines: 5,
         git_diff_added_lines: 30,
+        ai_low_confidence: 0,
+        ai_overridden_kept: 0,
+        ai_reviewed: 0,
+        possible_ai_additions: 0,
+        unknown_additions: 0,
+        class_additions: BTreeMap::new(),
+        ai_commit_message: None,
         tool_model_breakdown: BTreeMap::new(),
     };
 
@@ -227,6 +371,13 @@ fn test_markdown_stats_no_mixed() {
         time_waiting_for_ai: 15,
         git_diff_deleted_lines: 0,
         git_diff_added_lines: 20,
+        ai_low_confidence: 0,
+        ai_overridden_kept: 0,
+        ai_reviewed: 0,
+        possible_ai_additions: 0,
+        unknown_additions: 0,
+        class_additions: BTreeMap::new(),
+        ai_commit_message: None,
         tool_model_breakdown: BTreeMap::new(),
     };
 
@@ -251,6 +402,13 @@ fn test_markdown_stats_minimal_human() {
         time_waiting_for_ai: 10,
         git_diff_deleted_lines: 0,
         git_diff_added_lines: 100,
+        ai_low_confidence: 0,
+        ai_overridden_kept: 0,
+        ai_reviewed: 0,
+        possible_ai_additions: 0,
+        unknown_additions: 0,
+        class_additions: BTreeMap::new(),
+        ai_commit_message: None,
         tool_model_breakdown: BTreeMap::new(),
     };
 
@@ -287,6 +445,13 @@ fn test_markdown_stats_formatting() {
         time_waiting_for_ai: 25,
         git_diff_deleted_lines: 2,
         git_diff_added_lines: 13,
+        ai_low_confidence: 0,
+        ai_overridden_kept: 0,
+        ai_reviewed: 0,
+        possible_ai_additions: 0,
+        unknown_additions: 0,
+        class_additions: BTreeMap::new(),
+        ai_commit_message: None,
         tool_model_breakdown,
     };
 
@@ -0,0 +1,50 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+#[test]
+fn test_summary_note_written_alongside_authorship() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("code.txt");
+    file.set_contents(lines!["start", "end"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    file.insert_at(1, lines!["AI 1".ai(), "AI 2".ai(), "human".human()]);
+    repo.git(&["add", "-A"]).unwrap();
+    repo.git_with_env(
+        &["commit", "-m", "Add lines"],
+        &[("GIT_AI_SUMMARY_NOTES", "1")],
+    )
+    .unwrap();
+
+    let note = repo
+        .git(&["notes", "--ref=commits", "show", "HEAD"])
+        .unwrap();
+    assert!(note.starts_with("git-ai: 2 AI lines (1 session), 1 human line\n"));
+    assert!(note.contains("mock_ai"));
+    assert!(note.contains("  code.txt: 2-3\n"));
+
+    // The authorship note is still the structured log
+    let authorship = repo.git(&["notes", "--ref=ai", "show", "HEAD"]).unwrap();
+    assert!(authorship.contains("\n---\n"));
+
+    let log = repo.git(&["log", "-1", "--notes"]).unwrap();
+    assert!(log.contains("git-ai: 2 AI lines"));
+}
+
+#[test]
+fn test_summary_note_off_by_default() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("code.txt");
+    file.set_contents(lines!["start", "end"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    file.insert_at(1, lines!["AI 1".ai()]);
+    repo.stage_all_and_commit("Add line").unwrap();
+
+    assert!(
+        repo.git(&["notes", "--ref=commits", "show", "HEAD"])
+            .is_err()
+    );
+}
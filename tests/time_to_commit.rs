@@ -0,0 +1,40 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+#[test]
+fn test_time_to_commit_per_agent_and_model() {
+    let repo = TestRepo::new();
+    let mut lib = repo.filename("lib.rs");
+    lib.set_contents(lines!["fn a() {}".human()]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    lib.set_contents(lines!["fn a() {}".human(), "fn agent() {}".ai()]);
+    repo.stage_all_and_commit("Agent's edit").unwrap();
+    lib.set_contents(lines![
+        "fn a() {}".human(),
+        "fn agent() {}".ai(),
+        "fn more() {}".ai()
+    ]);
+    repo.stage_all_and_commit("More of the agent's edits")
+        .unwrap();
+
+    let output = repo
+        .git_ai(&["stats", "--time-to-commit", "--json"])
+        .unwrap();
+    let stats: serde_json::Value = serde_json::from_str(output.trim()).unwrap();
+    let stats = stats.as_array().unwrap();
+    assert_eq!(stats.len(), 1, "{}", output);
+    assert_eq!(stats[0]["tool"], "mock_ai");
+    assert_eq!(stats[0]["sessions"], 2);
+    // The commits follow their checkpoints within the test
+    assert!(stats[0]["max_secs"].as_u64().unwrap() < 60, "{}", output);
+
+    // A range only counts its own commits
+    let output = repo
+        .git_ai(&["stats", "--time-to-commit", "--json", "HEAD~1..HEAD"])
+        .unwrap();
+    let stats: serde_json::Value = serde_json::from_str(output.trim()).unwrap();
+    assert_eq!(stats[0]["sessions"], 1, "{}", output);
+}
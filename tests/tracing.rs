@@ -0,0 +1,111 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+use serde_json::Value;
+
+#[test]
+fn test_timing_prints_phase_breakdown() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("lib.rs");
+    file.set_contents(lines!["fn main() {}"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    std::fs::write(repo.path().join("lib.rs"), "fn main() {}\nfn helper() {}\n").unwrap();
+    let output = repo.git_ai(&["--timing", "checkpoint", "mock_ai"]).unwrap();
+    let report = &output[output.find("git-ai timing:").expect(&output)..];
+    for phase in ["checkpoint", "diff"] {
+        assert!(
+            report
+                .lines()
+                .any(|line| line.trim_start().starts_with(phase)),
+            "{}",
+            report
+        );
+    }
+}
+
+#[test]
+fn test_log_writes_structured_spans() {
+    let repo = TestRepo::new();
+    let log_dir = tempfile::tempdir().unwrap();
+    let log_file = log_dir.path().join("trace.jsonl");
+    let mut file = repo.filename("lib.rs");
+    file.set_contents(lines!["fn main() {}", "fn helper() {}".ai()]);
+    repo.git(&["add", "-A"]).unwrap();
+
+    repo.git_with_env(
+        &["commit", "-m", "Add helper"],
+        &[
+            ("GIT_AI_LOG", "debug"),
+            ("GIT_AI_LOG_FILE", log_file.to_str().unwrap()),
+        ],
+    )
+    .unwrap();
+
+    let spans: Vec<String> = std::fs::read_to_string(&log_file)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str::<Value>(line).unwrap())
+        .filter_map(|event| event["span"]["name"].as_str().map(str::to_string))
+        .collect();
+    for phase in ["post_commit", "notes_write"] {
+        assert!(spans.iter().any(|span| span == phase), "{:?}", spans);
+    }
+}
+
+#[test]
+fn test_trace_records_a_bundle_that_replays() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("lib.rs");
+    file.set_contents(lines!["fn main() {}"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    file.set_contents(lines!["fn main() {}", "fn helper() {}".ai()]);
+    repo.git(&["add", "-A"]).unwrap();
+    let bundle_dir = tempfile::tempdir().unwrap();
+    let bundle_path = bundle_dir.path().join("trace.json");
+    let output = repo
+        .git_ai(&[
+            "trace",
+            "-o",
+            bundle_path.to_str().unwrap(),
+            "commit",
+            "-m",
+            "Add helper",
+        ])
+        .unwrap();
+    assert!(output.contains("recorded"), "{}", output);
+
+    let bundle: Value =
+        serde_json::from_str(&std::fs::read_to_string(&bundle_path).unwrap()).unwrap();
+    assert_eq!(bundle["command"][0], "commit");
+    assert_eq!(bundle["exit_code"], 0);
+    assert_ne!(bundle["head_before"], bundle["head_after"]);
+    let messages: Vec<&str> = bundle["events"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|event| event["message"].as_str())
+        .collect();
+    for message in ["files considered", "note written"] {
+        assert!(messages.contains(&message), "{:?}", messages);
+    }
+    assert!(
+        bundle["notes"][0]["note"]
+            .as_str()
+            .unwrap()
+            .contains("lib.rs")
+    );
+
+    // Replaying goes back to the parent commit and commits the staged change again
+    let output = repo
+        .git_ai(&["trace", "replay", bundle_path.to_str().unwrap()])
+        .unwrap();
+    assert!(
+        output.contains("All 1 authorship notes match"),
+        "{}",
+        output
+    );
+    file.assert_lines_and_blame(lines!["fn main() {}", "fn helper() {}".ai()]);
+}
@@ -0,0 +1,63 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+fn notes_tip(repo: &TestRepo) -> String {
+    repo.git(&["rev-parse", "refs/notes/ai"])
+        .unwrap()
+        .trim()
+        .to_string()
+}
+
+#[test]
+fn test_amend_backs_up_notes_and_undo_notes_restores_them() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("app.py");
+    file.set_contents(lines!["def main():", "    return 0".ai()]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+    let before_amend = notes_tip(&repo);
+
+    file.set_contents(lines!["def main():", "    return 1".ai()]);
+    repo.git(&["add", "-A"]).unwrap();
+    repo.git(&["commit", "--amend", "-m", "Initial commit (amended)"])
+        .unwrap();
+    let after_amend = notes_tip(&repo);
+    assert_ne!(before_amend, after_amend);
+
+    let backup_ref = format!("refs/notes/ai-backup/{}", before_amend);
+    let list = repo.git_ai(&["undo-notes", "--list"]).unwrap();
+    assert!(list.contains(&backup_ref), "{}", list);
+
+    let output = repo.git_ai(&["undo-notes"]).unwrap();
+    assert!(output.contains(&backup_ref), "{}", output);
+    assert_eq!(notes_tip(&repo), before_amend);
+    // The amended commit's note didn't exist before the amend
+    assert!(repo.git(&["notes", "--ref=ai", "show", "HEAD"]).is_err());
+
+    // Undoing again puts back the notes the first undo replaced
+    repo.git_ai(&["undo-notes"]).unwrap();
+    assert_eq!(notes_tip(&repo), after_amend);
+}
+
+#[test]
+fn test_undo_notes_accepts_a_notes_revision() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("app.py");
+    file.set_contents(lines!["def main():", "    return 0".ai()]);
+    repo.stage_all_and_commit("First").unwrap();
+    let first = notes_tip(&repo);
+    file.set_contents(lines!["def main():", "    return 0".ai(), "main()"]);
+    repo.stage_all_and_commit("Second").unwrap();
+    assert_ne!(notes_tip(&repo), first);
+
+    repo.git_ai(&["undo-notes", "refs/notes/ai~1"]).unwrap();
+    assert_eq!(notes_tip(&repo), first);
+
+    let stderr = repo.git_ai(&["undo-notes", "no-such-backup"]).unwrap_err();
+    assert!(
+        stderr.contains("Failed to find notes to restore"),
+        "{}",
+        stderr
+    );
+}
@@ -0,0 +1,44 @@
+#[macro_use]
+mod repos;
+
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+use std::fs;
+use std::path::Path;
+
+/// Writes an AI line into src/app.txt and checkpoints it through `file_path`, the way an editor
+/// hands git-ai the absolute path of the file it just changed
+fn checkpoint_ai_edit_via(repo: &TestRepo, file_path: &Path) {
+    let mut file = repo.filename("src/app.txt");
+    file.set_contents(lines!["human line"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    fs::write(
+        repo.path().join("src").join("app.txt"),
+        "human line\nai line\n",
+    )
+    .unwrap();
+    repo.git_ai(&["checkpoint", "mock_ai", file_path.to_str().unwrap()])
+        .unwrap();
+    repo.stage_all_and_commit("AI edit").unwrap();
+
+    file.assert_lines_and_blame(lines!["human line".human(), "ai line".ai()]);
+}
+
+/// Absolute paths use the platform's own separators (`C:\repo\src\app.txt` on Windows)
+#[test]
+fn test_checkpoint_with_native_absolute_path() {
+    let repo = TestRepo::new();
+    fs::create_dir_all(repo.path().join("src")).unwrap();
+    let file_path = repo.path().join("src").join("app.txt");
+    checkpoint_ai_edit_via(&repo, &file_path);
+}
+
+/// Canonical paths, which on Windows carry the `\\?\` prefix while git reports `C:/...`
+#[test]
+fn test_checkpoint_with_canonical_path() {
+    let repo = TestRepo::new();
+    fs::create_dir_all(repo.path().join("src")).unwrap();
+    let file_path = repo.canonical_path().join("src").join("app.txt");
+    checkpoint_ai_edit_via(&repo, &file_path);
+}
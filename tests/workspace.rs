@@ -0,0 +1,91 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+use std::path::Path;
+use std::process::Command;
+
+fn repo_with_commit(lines: Vec<repos::test_file::ExpectedLine>) -> TestRepo {
+    let repo = TestRepo::new();
+    let mut lib = repo.filename("lib.rs");
+    lib.set_contents(lines);
+    repo.stage_all_and_commit("Add lib").unwrap();
+    repo
+}
+
+fn workspace(dir: &Path, args: &[&str]) -> Result<String, String> {
+    let output = Command::new(env!("CARGO_BIN_EXE_git-ai"))
+        .arg("workspace")
+        .args(args)
+        .current_dir(dir)
+        .env("GIT_AI_AGENT_PROCESSES", "")
+        .output()
+        .unwrap();
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).to_string())
+    }
+}
+
+#[test]
+fn test_workspace_file_stats_and_verify_cover_every_repo() {
+    let frontend = repo_with_commit(lines!["fn a() {}".ai(), "fn b() {}".ai()]);
+    let api = repo_with_commit(lines!["fn c() {}".ai(), "fn d() {}"]);
+    let root = tempfile::tempdir().unwrap();
+    std::fs::write(
+        root.path().join(".gitai-workspace"),
+        format!(
+            "[workspace]\n\trepo = {}\n\trepo = {}\n",
+            frontend.path().display(),
+            api.path().display()
+        ),
+    )
+    .unwrap();
+
+    let listed = workspace(root.path(), &["list"]).unwrap();
+    assert_eq!(listed.lines().count(), 3, "{}", listed);
+
+    let stats: serde_json::Value =
+        serde_json::from_str(workspace(root.path(), &["stats", "--json"]).unwrap().trim()).unwrap();
+    assert_eq!(stats["repos"].as_object().unwrap().len(), 2);
+    assert_eq!(stats["total"]["ai_additions"], 3);
+    assert_eq!(stats["total"]["human_additions"], 1);
+
+    let verified = workspace(root.path(), &["verify"]).unwrap();
+    assert_eq!(verified.matches("0 issues").count(), 2, "{}", verified);
+}
+
+#[test]
+fn test_superproject_and_submodules_make_a_workspace() {
+    let submodule = repo_with_commit(lines!["fn a() {}".ai()]);
+    let superproject = repo_with_commit(lines!["fn b() {}"]);
+    superproject
+        .git(&[
+            "-c",
+            "protocol.file.allow=always",
+            "submodule",
+            "add",
+            submodule.path().to_str().unwrap(),
+            "vendor/sub",
+        ])
+        .unwrap();
+
+    let sub_dir = Path::new(superproject.path()).join("vendor/sub");
+    let listed = workspace(&sub_dir, &["list", "--json"]).unwrap();
+    let listed: serde_json::Value = serde_json::from_str(listed.trim()).unwrap();
+    let names: Vec<&str> = listed["repos"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|repo| repo["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, vec![".", "vendor/sub"]);
+}
+
+#[test]
+fn test_workspace_outside_any_repo_fails() {
+    let dir = tempfile::tempdir().unwrap();
+    let err = workspace(dir.path(), &["stats"]).unwrap_err();
+    assert!(err.contains(".gitai-workspace"), "{}", err);
+}